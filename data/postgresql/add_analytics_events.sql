@@ -0,0 +1,2 @@
+CREATE TABLE analytics_events (id UUID PRIMARY KEY NOT NULL, session_id VARCHAR NOT NULL, event VARCHAR NOT NULL, project_id VARCHAR DEFAULT NULL, created_at TIMESTAMPTZ NOT NULL);
+CREATE INDEX analytics_events_created_at_idx ON analytics_events (created_at);
@@ -0,0 +1,2 @@
+CREATE TABLE authorized_senders (id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(), keplr_wallet_pubkey VARCHAR NOT NULL, project_id VARCHAR NOT NULL, sender VARCHAR NOT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
+CREATE UNIQUE INDEX authorized_sender_idx ON authorized_senders (keplr_wallet_pubkey, project_id, sender);
@@ -0,0 +1,3 @@
+CREATE TABLE batch_analytics (id UUID PRIMARY KEY NOT NULL, project_id VARCHAR NOT NULL, transaction_hash VARCHAR DEFAULT NULL, item_count INTEGER NOT NULL, fee NUMERIC(78, 0) DEFAULT NULL, outcome VARCHAR NOT NULL, rejection_reason TEXT DEFAULT NULL, submitted_at TIMESTAMPTZ NOT NULL, latency_ms BIGINT DEFAULT NULL);
+CREATE INDEX batch_analytics_submitted_at_idx ON batch_analytics (submitted_at);
+CREATE INDEX batch_analytics_project_idx ON batch_analytics (project_id, submitted_at);
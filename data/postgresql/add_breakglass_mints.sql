@@ -0,0 +1 @@
+CREATE TABLE breakglass_mints (id UUID PRIMARY KEY NOT NULL, starknet_project_addr VARCHAR NOT NULL, starknet_account_addr VARCHAR NOT NULL, token_ids TEXT[] NOT NULL, reason TEXT NOT NULL, requested_by VARCHAR NOT NULL, requested_at TIMESTAMPTZ NOT NULL, confirmed_by VARCHAR DEFAULT NULL, transaction_hash VARCHAR DEFAULT NULL, status VARCHAR NOT NULL, updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
@@ -0,0 +1,2 @@
+CREATE TABLE bridge_check_results (keplr_wallet_pubkey VARCHAR NOT NULL, project_id VARCHAR NOT NULL, token_id VARCHAR NOT NULL, height BIGINT NOT NULL, failure VARCHAR DEFAULT NULL, checked_at TIMESTAMPTZ NOT NULL, PRIMARY KEY (keplr_wallet_pubkey, project_id, token_id, height));
+CREATE INDEX bridge_check_results_checked_at_idx ON bridge_check_results (checked_at);
@@ -0,0 +1,2 @@
+CREATE TABLE bridge_requests (id UUID PRIMARY KEY NOT NULL, keplr_wallet_pubkey VARCHAR NOT NULL, starknet_account_addr VARCHAR NOT NULL, project_id VARCHAR NOT NULL, token_ids VARCHAR[] NOT NULL, checks TEXT NOT NULL, outcome VARCHAR NOT NULL, status INTEGER NOT NULL, created_at TIMESTAMPTZ NOT NULL);
+CREATE INDEX bridge_requests_wallet_idx ON bridge_requests (keplr_wallet_pubkey, created_at);
@@ -0,0 +1,2 @@
+ALTER TABLE customer_keys ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+CREATE TABLE daily_reports (day VARCHAR(10) PRIMARY KEY NOT NULL, report TEXT NOT NULL, signature VARCHAR NOT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
@@ -0,0 +1 @@
+CREATE TABLE issue_records (subject VARCHAR PRIMARY KEY NOT NULL, issue_url VARCHAR NOT NULL, created_at TIMESTAMPTZ NOT NULL);
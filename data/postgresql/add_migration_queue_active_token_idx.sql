@@ -0,0 +1,3 @@
+-- Tokens queued by several wallets before this index keep their oldest active item, the others fail
+UPDATE migration_queue SET migration_status = 'error', note = 'DuplicateQueueItem', note_params = NULL WHERE id IN (SELECT id FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY project_id, token_id ORDER BY created_at, id) AS position FROM migration_queue WHERE migration_status IN ('pending', 'processing')) AS active WHERE position > 1);
+CREATE UNIQUE INDEX migration_queue_active_token_idx ON migration_queue (project_id, token_id) WHERE migration_status IN ('pending', 'processing');
@@ -0,0 +1,3 @@
+ALTER TYPE migration_status_values ADD VALUE 'dead_letter';
+ALTER TABLE migration_queue ADD attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE migration_queue ADD next_attempt_at TIMESTAMPTZ DEFAULT NULL;
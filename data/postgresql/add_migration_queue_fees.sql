@@ -0,0 +1 @@
+ALTER TABLE migration_queue ADD estimated_fee NUMERIC(78, 0) DEFAULT NULL, ADD actual_fee NUMERIC(78, 0) DEFAULT NULL;
@@ -0,0 +1,25 @@
+ALTER TABLE migration_queue ADD created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE migration_queue ADD updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE TABLE migration_queue_history (id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(), queue_item_id UUID NOT NULL REFERENCES migration_queue (id) ON DELETE CASCADE, migration_status migration_status_values NOT NULL, transaction_hash VARCHAR DEFAULT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
+CREATE INDEX migration_queue_history_created_at_idx ON migration_queue_history (created_at);
+
+CREATE OR REPLACE FUNCTION touch_migration_queue() RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at := NOW();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION record_migration_queue_history() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.migration_status IS NOT DISTINCT FROM OLD.migration_status AND NEW.transaction_hash IS NOT DISTINCT FROM OLD.transaction_hash THEN
+        RETURN NULL;
+    END IF;
+    INSERT INTO migration_queue_history (queue_item_id, migration_status, transaction_hash) VALUES (NEW.id, NEW.migration_status, NEW.transaction_hash);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER migration_queue_touch_trigger BEFORE UPDATE ON migration_queue FOR EACH ROW EXECUTE FUNCTION touch_migration_queue();
+CREATE TRIGGER migration_queue_history_trigger AFTER INSERT OR UPDATE ON migration_queue FOR EACH ROW EXECUTE FUNCTION record_migration_queue_history();
@@ -0,0 +1,13 @@
+ALTER TABLE migration_queue ADD updated_by VARCHAR DEFAULT NULL;
+ALTER TABLE migration_queue_history ADD worker_id VARCHAR DEFAULT NULL;
+CREATE INDEX migration_queue_history_item_idx ON migration_queue_history (queue_item_id, created_at);
+
+CREATE OR REPLACE FUNCTION record_migration_queue_history() RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND NEW.migration_status IS NOT DISTINCT FROM OLD.migration_status AND NEW.transaction_hash IS NOT DISTINCT FROM OLD.transaction_hash THEN
+        RETURN NULL;
+    END IF;
+    INSERT INTO migration_queue_history (queue_item_id, migration_status, transaction_hash, worker_id) VALUES (NEW.id, NEW.migration_status, NEW.transaction_hash, NEW.updated_by);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
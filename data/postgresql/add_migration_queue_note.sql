@@ -0,0 +1 @@
+ALTER TABLE migration_queue ADD note VARCHAR DEFAULT NULL;
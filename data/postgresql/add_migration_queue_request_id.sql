@@ -0,0 +1 @@
+ALTER TABLE migration_queue ADD request_id VARCHAR DEFAULT NULL;
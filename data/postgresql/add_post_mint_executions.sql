@@ -0,0 +1,2 @@
+CREATE TABLE post_mint_executions (id UUID PRIMARY KEY NOT NULL, queue_item_id UUID NOT NULL REFERENCES migration_queue (id) ON DELETE CASCADE, hook VARCHAR NOT NULL, project_id VARCHAR NOT NULL, token_id VARCHAR NOT NULL, starknet_account_addr VARCHAR NOT NULL, transaction_hash VARCHAR NOT NULL, attempts INTEGER NOT NULL DEFAULT 0, last_error TEXT DEFAULT NULL, status VARCHAR NOT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
+CREATE INDEX post_mint_executions_status_idx ON post_mint_executions (status);
@@ -0,0 +1 @@
+CREATE TABLE project_batch_sizes (project_id VARCHAR PRIMARY KEY NOT NULL, batch_size INTEGER NOT NULL, consecutive_successes INTEGER NOT NULL DEFAULT 0, updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
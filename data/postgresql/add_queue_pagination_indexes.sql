@@ -0,0 +1,2 @@
+CREATE INDEX migration_queue_created_at_id_idx ON migration_queue (created_at, id);
+CREATE INDEX migration_queue_history_created_at_id_idx ON migration_queue_history (created_at, id);
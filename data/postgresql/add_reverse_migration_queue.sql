@@ -0,0 +1,3 @@
+CREATE TABLE reverse_migration_queue (id UUID PRIMARY KEY NOT NULL, starknet_account_addr VARCHAR NOT NULL, keplr_wallet_pubkey VARCHAR NOT NULL, starknet_project_id VARCHAR NOT NULL, project_id VARCHAR NOT NULL, token_id VARCHAR NOT NULL, starknet_transaction_hash VARCHAR NOT NULL, status VARCHAR NOT NULL, juno_transaction_hash VARCHAR DEFAULT NULL, note TEXT DEFAULT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), UNIQUE (project_id, token_id, starknet_transaction_hash));
+CREATE INDEX reverse_migration_queue_status_idx ON reverse_migration_queue (status, created_at);
+CREATE INDEX reverse_migration_queue_account_idx ON reverse_migration_queue (starknet_account_addr, starknet_project_id);
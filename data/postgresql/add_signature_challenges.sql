@@ -0,0 +1 @@
+CREATE TABLE signature_challenges (nonce VARCHAR PRIMARY KEY NOT NULL, keplr_wallet_pubkey VARCHAR NOT NULL, expires_at BIGINT NOT NULL, used_at TIMESTAMPTZ DEFAULT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
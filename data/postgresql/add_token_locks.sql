@@ -0,0 +1 @@
+CREATE TABLE token_locks (project_id VARCHAR NOT NULL, token_id VARCHAR NOT NULL, holder VARCHAR NOT NULL, locked_until TIMESTAMPTZ NOT NULL, PRIMARY KEY (project_id, token_id));
@@ -0,0 +1 @@
+ALTER TABLE transfer_proofs ADD block_time TIMESTAMPTZ DEFAULT NULL;
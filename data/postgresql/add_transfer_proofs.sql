@@ -0,0 +1 @@
+CREATE TABLE transfer_proofs (project_id VARCHAR NOT NULL, token_id VARCHAR NOT NULL, sender VARCHAR NOT NULL, recipient VARCHAR NOT NULL, transaction_hash VARCHAR, height BIGINT, created_at TIMESTAMPTZ NOT NULL, PRIMARY KEY (project_id, token_id));
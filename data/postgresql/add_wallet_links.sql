@@ -0,0 +1 @@
+CREATE TABLE wallet_links (keplr_wallet_pubkey VARCHAR PRIMARY KEY NOT NULL, starknet_account_addr VARCHAR NOT NULL, verified_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
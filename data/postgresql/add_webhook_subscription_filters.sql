@@ -0,0 +1,3 @@
+ALTER TABLE webhook_subscriptions ADD events VARCHAR NOT NULL DEFAULT 'item_succeeded,item_failed';
+ALTER TABLE webhook_subscriptions ADD secret VARCHAR DEFAULT NULL;
+CREATE INDEX webhook_subscriptions_callback_idx ON webhook_subscriptions (keplr_wallet_pubkey, callback_url);
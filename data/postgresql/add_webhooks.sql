@@ -0,0 +1,6 @@
+CREATE TABLE webhook_subscriptions (id UUID PRIMARY KEY NOT NULL, keplr_wallet_pubkey VARCHAR DEFAULT NULL, callback_url VARCHAR NOT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
+CREATE INDEX webhook_subscriptions_wallet_idx ON webhook_subscriptions (keplr_wallet_pubkey);
+CREATE TABLE webhook_deliveries (id UUID PRIMARY KEY NOT NULL, subscription_id UUID NOT NULL REFERENCES webhook_subscriptions (id) ON DELETE CASCADE, callback_url VARCHAR NOT NULL, payload TEXT NOT NULL, attempts INTEGER NOT NULL DEFAULT 0, next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), last_error TEXT DEFAULT NULL, status VARCHAR NOT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(), updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
+CREATE INDEX webhook_deliveries_due_idx ON webhook_deliveries (status, next_attempt_at);
+CREATE TABLE webhook_delivery_log (id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(), delivery_id UUID NOT NULL REFERENCES webhook_deliveries (id) ON DELETE CASCADE, attempt INTEGER NOT NULL, status_code INTEGER DEFAULT NULL, error TEXT DEFAULT NULL, created_at TIMESTAMPTZ NOT NULL DEFAULT NOW());
+CREATE INDEX webhook_delivery_log_delivery_idx ON webhook_delivery_log (delivery_id, created_at);
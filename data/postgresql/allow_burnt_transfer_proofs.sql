@@ -0,0 +1 @@
+ALTER TABLE transfer_proofs ALTER COLUMN recipient DROP NOT NULL;
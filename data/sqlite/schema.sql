@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS customer_keys (keplr_wallet_pubkey TEXT NOT NULL, project_id TEXT NOT NULL, token_ids TEXT NOT NULL, PRIMARY KEY (keplr_wallet_pubkey, project_id));
+CREATE TABLE IF NOT EXISTS authorized_senders (keplr_wallet_pubkey TEXT NOT NULL, project_id TEXT NOT NULL, sender TEXT NOT NULL, PRIMARY KEY (keplr_wallet_pubkey, project_id, sender));
+CREATE TABLE IF NOT EXISTS migration_queue (id TEXT PRIMARY KEY NOT NULL, keplr_wallet_pubkey TEXT NOT NULL, starknet_wallet_pubkey TEXT NOT NULL, project_id TEXT NOT NULL, token_id TEXT NOT NULL, transaction_hash TEXT DEFAULT NULL, migration_status TEXT NOT NULL DEFAULT 'pending', note TEXT DEFAULT NULL, note_params TEXT DEFAULT NULL, attempts INTEGER NOT NULL DEFAULT 0, estimated_fee TEXT DEFAULT NULL, actual_fee TEXT DEFAULT NULL, request_id TEXT DEFAULT NULL, next_attempt_at INTEGER DEFAULT NULL, updated_by TEXT DEFAULT NULL, created_at INTEGER NOT NULL, updated_at INTEGER NOT NULL);
+CREATE UNIQUE INDEX IF NOT EXISTS migration_item_idx ON migration_queue (keplr_wallet_pubkey, project_id, token_id);
+UPDATE migration_queue SET migration_status = 'error', note = 'DuplicateQueueItem', note_params = NULL WHERE id IN (SELECT id FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY project_id, token_id ORDER BY created_at, id) AS position FROM migration_queue WHERE migration_status IN ('pending', 'processing')) WHERE position > 1);
+CREATE UNIQUE INDEX IF NOT EXISTS migration_queue_active_token_idx ON migration_queue (project_id, token_id) WHERE migration_status IN ('pending', 'processing');
+CREATE INDEX IF NOT EXISTS migration_queue_created_at_id_idx ON migration_queue (created_at, id);
+CREATE TABLE IF NOT EXISTS migration_queue_history (id TEXT PRIMARY KEY NOT NULL, queue_item_id TEXT NOT NULL REFERENCES migration_queue (id) ON DELETE CASCADE, migration_status TEXT NOT NULL, transaction_hash TEXT DEFAULT NULL, worker_id TEXT DEFAULT NULL, created_at INTEGER NOT NULL);
+CREATE INDEX IF NOT EXISTS migration_queue_history_created_at_id_idx ON migration_queue_history (created_at, id);
+CREATE INDEX IF NOT EXISTS migration_queue_history_item_idx ON migration_queue_history (queue_item_id, created_at);
+CREATE TRIGGER IF NOT EXISTS migration_queue_history_insert_trigger AFTER INSERT ON migration_queue BEGIN INSERT INTO migration_queue_history (id, queue_item_id, migration_status, transaction_hash, worker_id, created_at) VALUES (lower(hex(randomblob(16))), NEW.id, NEW.migration_status, NEW.transaction_hash, NEW.updated_by, NEW.updated_at); END;
+CREATE TRIGGER IF NOT EXISTS migration_queue_history_update_trigger AFTER UPDATE ON migration_queue WHEN NEW.migration_status IS NOT OLD.migration_status OR NEW.transaction_hash IS NOT OLD.transaction_hash BEGIN INSERT INTO migration_queue_history (id, queue_item_id, migration_status, transaction_hash, worker_id, created_at) VALUES (lower(hex(randomblob(16))), NEW.id, NEW.migration_status, NEW.transaction_hash, NEW.updated_by, NEW.updated_at); END;
+CREATE TABLE IF NOT EXISTS token_locks (project_id TEXT NOT NULL, token_id TEXT NOT NULL, holder TEXT NOT NULL, locked_until INTEGER NOT NULL, PRIMARY KEY (project_id, token_id));
@@ -0,0 +1,54 @@
+Feature: Worker records fee, size and latency of every batch it sends
+    Rule:
+        - Every batch sent to starknet is recorded with its item count and fee
+        - Minted items keep the estimated and actual fee of their transaction
+        - Latency is measured from submission to the transaction being accepted or rejected
+        - Rejected batches keep the sequencer rejection reason
+        - Batches that never reached the chain are recorded without transaction
+        - Records older than the retention period are pruned
+
+    Scenario: Accepted batch is recorded with its fee
+        Given tokens "600,601,602" are queued
+        When the worker consumes the queue
+        Then 1 batch should be recorded
+        And the last batch should be "accepted" with 3 items
+        And the last batch should have paid "1200000000000000"
+        And the last batch latency should be recorded
+
+    Scenario: Minted items keep the estimated and actual fee
+        Given tokens "605,606" are queued
+        When the worker consumes the queue
+        Then token "605" should have been estimated to "1000000000000000" and paid "1200000000000000"
+        And token "606" should have been estimated to "1000000000000000" and paid "1200000000000000"
+
+    Scenario: Rejected batch keeps its rejection reason
+        Given tokens "610" are queued
+        Given the sequencer rejects transactions with "INVALID_TRANSACTION_NONCE"
+        When the worker consumes the queue
+        Then the last batch should be "rejected" with 1 items
+        And the last batch should have been rejected because of "INVALID_TRANSACTION_NONCE"
+
+    Scenario: Batch failing before reaching the chain is recorded
+        Given tokens "620,621" are queued
+        Given starknet mints fail
+        When the worker consumes the queue
+        Then the last batch should be "submission_failed" with 2 items
+        And the last batch should have been rejected because of "MintFailed"
+        And the last batch should have no transaction
+
+    Scenario: Batches are filtered by submission date
+        Given tokens "630" are queued
+        When the worker consumes the queue
+        When 2 days elapse
+        Given tokens "631" are queued
+        When the worker consumes the queue
+        Then 1 batch should be recorded over the last day
+
+    Scenario: Batches past retention are pruned
+        Given tokens "640" are queued
+        When the worker consumes the queue
+        When 31 days elapse
+        Given tokens "641" are queued
+        When the worker consumes the queue
+        And analytics are pruned
+        Then 1 batch should be recorded
@@ -0,0 +1,113 @@
+Feature: Worker adapts batch size of each project to recent outcomes
+    Rule:
+        - Without adaptive mode every fetched item of a project is minted in one batch
+        - Batch size grows by its step after enough consecutive accepted batches
+        - Batch size is halved after a rejected or failed batch
+        - Batch size stays within its bounds and survives worker restarts
+        - Batches over the calls a transaction holds are sent in several transactions
+        - Fetched items over the batch size go back to pending for the next batch
+        - Batches rejected because a mint reverted, on chain or while estimating fees, are bisected, only the offending item fails
+        - An item whose mint reverts while estimating fees goes straight to the dead letter queue
+        - Items of a paused project are left out of batches until its contract resumes
+
+    Scenario: Static batch size mints every fetched item at once
+        Given 10 tokens are queued
+        When the worker consumes the queue
+        Then the last batch should have minted 10 items
+
+    Scenario: Batch size grows after consecutive accepted batches
+        Given adaptive batch size starts at 2 between 1 and 8, growing by 1 after 2 accepted batches
+        Given 10 tokens are queued
+        When the worker consumes the queue
+        Then the last batch should have minted 2 items
+        And the project batch size should be 2
+        When the worker consumes the queue
+        Then the project batch size should be 3
+        When the worker consumes the queue
+        Then the last batch should have minted 3 items
+
+    Scenario: Items over the batch size wait for the next batch
+        Given adaptive batch size starts at 2 between 1 and 8, growing by 1 after 2 accepted batches
+        Given 10 tokens are queued
+        When the worker consumes the queue
+        Then the last batch should have minted 2 items
+        And 8 queued items should be pending with note "WaitingForNextBatch"
+
+    Scenario: Batch size is halved after rejections down to its minimum
+        Given adaptive batch size starts at 4 between 1 and 8, growing by 1 after 2 accepted batches
+        Given 10 tokens are queued
+        Given the sequencer rejects transactions with "INVALID_TRANSACTION_NONCE"
+        When the worker consumes the queue
+        Then the last batch should have minted 4 items
+        And the project batch size should be 2
+        When the worker consumes the queue 2 times
+        Then the project batch size should be 1
+
+    Scenario: Batch size is halved after a submission failure
+        Given adaptive batch size starts at 4 between 1 and 8, growing by 1 after 2 accepted batches
+        Given 10 tokens are queued
+        Given starknet mints fail
+        When the worker consumes the queue
+        Then the project batch size should be 2
+
+    Scenario: Batch size does not grow past its maximum
+        Given adaptive batch size starts at 3 between 1 and 4, growing by 2 after 1 accepted batches
+        Given 10 tokens are queued
+        When the worker consumes the queue 2 times
+        Then the project batch size should be 4
+
+    Scenario: Batch size survives worker restarts
+        Given adaptive batch size starts at 2 between 1 and 8, growing by 1 after 1 accepted batches
+        Given 10 tokens are queued
+        When the worker consumes the queue
+        And the worker restarts
+        Then the project batch size should be 3
+
+    Scenario: Large batch is sent in chunks of the calls a transaction holds
+        Given transactions hold at most 4 calls
+        Given 10 tokens are queued
+        When the worker consumes the queue
+        Then 3 batches should have been sent
+        And the last batch should have minted 2 items
+        And queued tokens should be minted in 3 transactions
+
+    Scenario: Adaptive batch is chunked too
+        Given adaptive batch size starts at 6 between 1 and 8, growing by 1 after 2 accepted batches
+        Given transactions hold at most 4 calls
+        Given 10 tokens are queued
+        When the worker consumes the queue
+        Then 2 batches should have been sent
+        And the last batch should have minted 2 items
+
+    Scenario: Batch with a mint reverting while estimating fees is bisected until the offending item
+        Given 8 tokens are queued
+        Given the mint of token "705" reverts
+        When the worker consumes the queue
+        Then 7 batches should have been sent
+        And 7 queued tokens should be minted
+        And 1 queued items should be dead lettered with note "MintReverted"
+
+    Scenario: Batch with a mint reverting on chain is bisected until the offending item
+        Given 8 tokens are queued
+        Given the mint of token "705" reverts on chain
+        When the worker consumes the queue
+        Then 7 batches should have been sent
+        And 7 queued tokens should be minted
+        And 1 queued items should be pending with note "TransactionRejected"
+
+    Scenario: Batches rejected for another reason are not bisected
+        Given 8 tokens are queued
+        Given the sequencer rejects transactions with "INVALID_TRANSACTION_NONCE"
+        When the worker consumes the queue
+        Then 1 batches should have been sent
+        And 0 queued tokens should be minted
+
+    Scenario: Items of a paused project are left out of batches
+        Given 3 tokens are queued
+        Given the project contract is paused
+        When the worker consumes the queue
+        Then 0 queued tokens should be minted
+        And 0 queued items should be pending with note "ContractPaused"
+        Given the project contract is resumed
+        When the worker consumes the queue
+        Then 3 queued tokens should be minted
@@ -0,0 +1,61 @@
+Feature: Breakglass mints need a timely confirmation from a second operator
+
+    Scenario: Second operator confirms within the confirmation window
+        Given "alice" requested a breakglass mint of tokens [12, 13] on "0x0b7a55"
+        When 200 seconds elapse
+        And "bob" confirms the breakglass mint
+        Then the breakglass mint should be "minted"
+        And tokens [12, 13] should be minted on "0x0b7a55"
+
+    Scenario: Confirmation comes after the confirmation window
+        Given "alice" requested a breakglass mint of tokens [12, 13] on "0x0b7a55"
+        When 301 seconds elapse
+        And "bob" confirms the breakglass mint
+        Then the breakglass mint should be "expired"
+        And tokens [12, 13] should not be minted on "0x0b7a55"
+
+    Scenario: Requester cannot confirm its own mint
+        Given "alice" requested a breakglass mint of tokens [12] on "0x0b7a55"
+        When "alice" confirms the breakglass mint
+        Then the breakglass mint should be "awaiting_confirmation"
+        And tokens [12] should not be minted on "0x0b7a55"
+
+    Scenario: Concurrent confirmations mint only once
+        Given "alice" requested a breakglass mint of tokens [14] on "0x0b7a55"
+        When "bob" and "carol" confirm the breakglass mint at the same time
+        Then only 1 confirmation should have been accepted
+        And the breakglass mint should be "minted"
+        And tokens [14] should be minted on "0x0b7a55"
+
+    Scenario: Retried confirmation does not mint again
+        Given "alice" requested a breakglass mint of tokens [15] on "0x0b7a55"
+        When "bob" confirms the breakglass mint
+        And "bob" confirms the breakglass mint
+        Then only 1 confirmation should have been accepted
+        And the breakglass mint should be "minted"
+
+    Scenario: Mint transaction rejected by Starknet is not recorded as minted
+        Given "alice" requested a breakglass mint of tokens [16] on "0x0b7a55"
+        And starknet rejects transactions with "TRANSACTION_FAILED"
+        When "bob" confirms the breakglass mint
+        Then the breakglass mint should be "failed"
+
+    Scenario Outline: Operators are authenticated by their own api key
+        When api key "<api_key>" is presented
+        Then operator "<operator>" should be authenticated
+
+        Examples:
+            | api_key   | operator |
+            | alice-key | alice    |
+            | carol-key | carol    |
+
+    Scenario Outline: Unknown api keys authenticate no operator
+        When api key "<api_key>" is presented
+        Then no operator should be authenticated
+
+        Examples:
+            | api_key    |
+            | bob-ke     |
+            | bob-key2   |
+            | BOB-KEY    |
+            |            |
@@ -2,10 +2,21 @@ Feature: Bridge between Juno and Starknet for carbonABLE NFT's
     Rule: 
         - Receive a signed hash, starknet wallet address, customer's keplr wallet public key, a list of token ids, project id.
         - Check the signed hash is correct
-        - Check customers keplr wallet was the last owner of tokens
+        - A signed bridge document has to list the requested tokens, project and starknet account
+        - Check customers keplr wallet was the last owner of tokens, moved with transfer_nft or send_nft
+        - Ignore transactions included on Juno but failed
         - Check customer balance is effectively 0
         - Check admin address on Juno is now owner of tokens for project id
+        - Check tokens were burnt instead on projects migrating by burn
+        - Check the transfer happened within the transfer window of the project, by block height or time
+        - Optionally ask the contract whether admin address still holds the tokens
+        - Keep the transfer to admin as a proof, later checks of the token skip the Juno node
+        - Reuse check results of a customer retrying within minutes, unless a new transfer or a node recovery can change them
+        - Resolve the starknet contract of the project from the registry
         - Enqueue the requested tokens 
+        - Refuse to enqueue while the queue is full, telling when to retry
+        - Enqueue tokens whose mint status starknet cannot tell, they are checked before minting
+        - Lock tokens while they are checked, workers and other requests leave them alone
 
     Scenario: Signed hash is incorrect
         Given the following transaction list
@@ -13,7 +24,7 @@ Feature: Bridge between Juno and Starknet for carbonABLE NFT's
             """
         Given a request with values:
             | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
-            | anInvalidHash | st4rkn3t-1 | k3plr-pk1 | projectId | [254, 255] |
+            | anInvalidHash | 0x5741 | k3plr-pk1 | projectId | [254, 255] |
         When I execute the request
         Then the signed hash should not be valid
 
@@ -65,7 +76,7 @@ Feature: Bridge between Juno and Starknet for carbonABLE NFT's
             """
         Given a request with values:
             | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
-            | aValidSignedHash | st4rkn3t-1 | k3plr-pk1 | projectId | [255] |
+            | aValidSignedHash | 0x5741 | k3plr-pk1 | projectId | [255] |
         When I execute the request
         Then I sould receive an error because provided keplr wallet was not the previous owner
 
@@ -97,7 +108,7 @@ Feature: Bridge between Juno and Starknet for carbonABLE NFT's
             """
         Given a request with values:
             | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
-            | aValidSignedHash | st4rkn3t-1 | k3plr-pk1 | projectId | [255] |
+            | aValidSignedHash | 0x5741 | k3plr-pk1 | projectId | [255] |
         When I execute the request
         Then I sould receive an error because current owner is not admin wallet
 
@@ -129,6 +140,692 @@ Feature: Bridge between Juno and Starknet for carbonABLE NFT's
             """
         Given a request with values:
             | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
-            | aValidSignedHash | st4rkn3t-1 | k3plr-pk1 | projectId | [254, 255] |
+            | aValidSignedHash | 0x5741 | k3plr-pk1 | projectId | [254, 255] |
         When I execute the request
         Then nfts migration request should have been enqueued and response should be ok
+
+    Scenario: Keplr wallet is already linked to another starknet account
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk9",
+                    "contract": "projectId",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "260"
+                        }
+                    }
+                }
+            ]
+            """
+        Given keplr wallet k3plr-pk9 is linked to starknet account 0x5749
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5742 | k3plr-pk9 | projectId | [260] |
+        When I execute the request
+        Then I should receive an error because starknet account is not the linked one
+
+    Scenario: Wallet link that cannot be read fails the request instead of relinking
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk9",
+                    "contract": "projectId",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "261"
+                        }
+                    }
+                }
+            ]
+            """
+        Given wallet links cannot be read
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5742 | k3plr-pk9 | projectId | [261] |
+        When I execute the request
+        Then the request should have been refused with code "wallet_link_issue"
+
+    Scenario: Token was transferred to admin by an account authorized by the customer
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "juno-multisig-1",
+                    "contract": "projectId",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "270"
+                        }
+                    }
+                }
+            ]
+            """
+        Given an empty queue
+        Given keplr wallet k3plr-pk7 authorized sender juno-multisig-1 for project projectId
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5747 | k3plr-pk7 | projectId | [270] |
+        When I execute the request
+        Then token 270 should have passed checks
+
+    Scenario: Proven transfer is checked again without the juno node
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk6",
+                    "contract": "projectId",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "290"
+                        }
+                    }
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5746 | k3plr-pk6 | projectId | [290] |
+        When I execute the request
+        Then token 290 should have passed checks
+        And transfer of token 290 by k3plr-pk6 should have been proven
+        Given the juno node is down
+        When I execute the request
+        Then token 290 should have passed checks
+        And the juno node should have been asked 1 time
+
+    Scenario: Request is aborted when the service is shutting down
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk8",
+                    "contract": "projectId",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "280"
+                        }
+                    }
+                }
+            ]
+            """
+        Given an empty queue
+        Given the service is shutting down
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5748 | k3plr-pk8 | projectId | [280] |
+        When I execute the request
+        Then the request should have been cancelled without enqueueing anything
+
+    Scenario: Request is refused while the queue is full
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk10",
+                    "contract": "projectId",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "300"
+                        }
+                    }
+                }
+            ]
+            """
+        Given an empty queue
+        Given 5 tokens are pending in the queue
+        Given the queue accepts at most 3 pending items, drained 2 every 60 seconds
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x574a | k3plr-pk10 | projectId | [300] |
+        When I execute the request
+        Then the request should have been refused with code "queue_full"
+        And the customer should be told to retry in 120 seconds
+        And 5 tokens should be pending in the queue
+
+    Scenario: Project missing from the registry is refused
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk6",
+                    "contract": "unknownProject",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "290"
+                        }
+                    }
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5746 | k3plr-pk6 | unknownProject | [290] |
+        When I execute the request
+        Then the request should have been refused because project unknownProject is not bridged
+
+    Scenario: Starknet contract sent by the customer is ignored
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk5",
+                    "contract": "projectId",
+                    "msg": {
+                        "transfer_nft": {
+                            "recipient": "juno-admin-account",
+                            "token_id": "291"
+                        }
+                    }
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5745 | k3plr-pk5 | projectId | [291] |
+        Given the request targets starknet contract 0x0bad
+        When I execute the request
+        Then tokens should have been enqueued for the registered starknet contract
+
+    Scenario: Tokens left when the request budget runs out are reported as incomplete
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk4",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "292" } }
+                },
+                {
+                    "sender": "k3plr-pk4",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "293" } }
+                },
+                {
+                    "sender": "k3plr-pk4",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "294" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given the juno node answers in 300 milliseconds
+        Given a bridge request budget of 450 milliseconds
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5744 | k3plr-pk4 | projectId | [292, 293, 294] |
+        When I execute the request
+        Then checks of tokens "293, 294" should be incomplete
+        And only token "292" should have been enqueued
+
+    Scenario: Juno history and starknet ownership are checked concurrently
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk3",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "295" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given the juno node answers in 300 milliseconds
+        Given the starknet node answers in 300 milliseconds
+        Given a bridge request budget of 450 milliseconds
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5743 | k3plr-pk3 | projectId | [295] |
+        When I execute the request
+        Then token 295 should have passed checks
+
+    Scenario: Signed bridge document authorizes the tokens it lists
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk7",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "301" } }
+                },
+                {
+                    "sender": "k3plr-pk7",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "302" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given sign docs are required
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5747 | k3plr-pk7 | projectId | [301, 302] |
+        And the request signs document
+            """
+            {"starknet_account_addr":"0x5747","project_id":"projectId","token_ids":["302","301"]}
+            """
+        When I execute the request
+        Then only tokens "301, 302" should have been enqueued
+
+    Scenario: Signed bridge document does not authorize other tokens
+        Given the following transaction list
+            """ []
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5747 | k3plr-pk7 | projectId | [301, 303] |
+        And the request signs document
+            """
+            {"starknet_account_addr":"0x5747","project_id":"projectId","token_ids":["301","302"]}
+            """
+        When I execute the request
+        Then the request should have been refused with code "sign_doc_mismatch"
+
+    Scenario: Requests without bridge document are refused once it is required
+        Given the following transaction list
+            """ []
+            """
+        Given an empty queue
+        Given sign docs are required
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5747 | k3plr-pk7 | projectId | [301] |
+        When I execute the request
+        Then the request should have been refused with code "sign_doc_required"
+
+    Scenario: Customer retrying after transferring the token to the admin is checked again
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk8",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "not-juno-admin-account", "token_id": "310" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given check results are reused for 180 seconds
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5748 | k3plr-pk8 | projectId | [310] |
+        When I execute the request
+        Then token 310 should have failed checks with code "not_transferred_to_admin"
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk8",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "310" } }
+                }
+            ]
+            """
+        When I execute the request
+        Then token 310 should have passed checks
+        And the juno node should have been asked 1 time
+
+    Scenario: Transient node failures are checked again on retry
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk8",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "311" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given check results are reused for 180 seconds
+        Given the juno node is down
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5748 | k3plr-pk8 | projectId | [311] |
+        When I execute the request
+        Then token 311 should have failed checks with code "juno_server_error"
+        When I execute the request
+        Then the juno node should have been asked 2 times
+
+    Scenario: Tokens are enqueued unchecked while starknet cannot be reached
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk11",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "320" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given the starknet node cannot be reached
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x574b | k3plr-pk11 | projectId | [320] |
+        When I execute the request
+        Then token 320 should have passed checks
+        And token 320 should have been enqueued with note "UncheckedMintStatus"
+
+    Scenario: Tokens checked by another request are left to it
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk12",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "321" } }
+                },
+                {
+                    "sender": "k3plr-pk12",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "322" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given token 321 is being checked by another request
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x574c | k3plr-pk12 | projectId | [321, 322] |
+        When I execute the request
+        Then checks of token "321" should be incomplete
+        And only token "322" should have been enqueued
+
+    Scenario: Workers leave queued tokens alone while a request checks them
+        Given an empty queue
+        Given 2 tokens are pending in the queue
+        Given token 900 is being checked by another request
+        Then only token "901" should be part of the next batch
+
+    Scenario: Resubmitted tokens keep their queue item
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk13",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "323" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given token 323 is queued for keplr wallet k3plr-pk13
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x574d | k3plr-pk13 | projectId | [323] |
+        When I execute the request
+        Then token 323 should have passed checks
+        And token 323 should have kept its queue item
+
+    Scenario: Tokens queued by another wallet are not queued twice
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk14",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "324" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given token 324 is queued for keplr wallet k3plr-former
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x574e | k3plr-pk14 | projectId | [324] |
+        When I execute the request
+        Then token 324 should have passed checks
+        And token 324 should have kept its queue item
+
+    Scenario: Token moved out of the admin wallet after its transfer is refused
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk15",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "325" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given the juno contract reports token 325 held by juno-another-account
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x574f | k3plr-pk15 | projectId | [325] |
+        When I execute the request
+        Then token 325 should have failed checks with code "not_held_by_admin"
+        And 0 tokens should be pending in the queue
+
+    Scenario: Token held by the admin wallet passes the ownership check
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk16",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "326" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given the juno contract reports token 326 held by juno-admin-account
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5750 | k3plr-pk16 | projectId | [326] |
+        When I execute the request
+        Then token 326 should have passed checks
+        And only token "326" should have been enqueued
+
+    Scenario: Token sent to the admin with send_nft passes checks
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk17",
+                    "contract": "projectId",
+                    "msg": { "send_nft": { "contract": "juno-admin-account", "token_id": "327", "msg": "e30=" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5751 | k3plr-pk17 | projectId | [327] |
+        When I execute the request
+        Then token 327 should have passed checks
+        And transfer of token 327 by k3plr-pk17 should have been proven
+
+    Scenario: Token sent to another contract with send_nft is refused
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk18",
+                    "contract": "projectId",
+                    "msg": { "send_nft": { "contract": "juno-marketplace", "token_id": "328", "msg": "e30=" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5752 | k3plr-pk18 | projectId | [328] |
+        When I execute the request
+        Then token 328 should have failed checks with code "not_transferred_to_admin"
+
+    Scenario: Token burnt on a burn project passes checks
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk19",
+                    "contract": "burnProject",
+                    "msg": { "burn": { "token_id": "329" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5753 | k3plr-pk19 | burnProject | [329] |
+        When I execute the request
+        Then token 329 should have passed checks
+        And burn of token 329 on burnProject by k3plr-pk19 should have been proven
+
+    Scenario: Token transferred to the admin on a burn project is refused
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk20",
+                    "contract": "burnProject",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "330" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5754 | k3plr-pk20 | burnProject | [330] |
+        When I execute the request
+        Then token 330 should have failed checks with code "not_burnt"
+
+    Scenario: Failed transfer to the admin is ignored
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk21",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "331" } },
+                    "code": 5
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5755 | k3plr-pk21 | projectId | [331] |
+        When I execute the request
+        Then token 331 should have failed checks with code "transaction_not_found"
+
+    Scenario: Failed transfer after a successful one leaves the successful one as last transfer
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk22",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-marketplace", "token_id": "332" } },
+                    "code": 11
+                },
+                {
+                    "sender": "k3plr-pk22",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "332" } },
+                    "code": 0
+                }
+            ]
+            """
+        Given an empty queue
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5756 | k3plr-pk22 | projectId | [332] |
+        When I execute the request
+        Then token 332 should have passed checks
+
+    Scenario: Transfer before the height window of the project is refused
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk23",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "333" } },
+                    "height": 90
+                }
+            ]
+            """
+        Given an empty queue
+        Given project projectId accepts transfers from 100 until 200
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5757 | k3plr-pk23 | projectId | [333] |
+        When I execute the request
+        Then token 333 should have failed checks with code "transfer_outside_window"
+
+    Scenario: Transfer within the height window of the project passes checks
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk24",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "334" } },
+                    "height": 150
+                }
+            ]
+            """
+        Given an empty queue
+        Given project projectId accepts transfers from 100 until 200
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5758 | k3plr-pk24 | projectId | [334] |
+        When I execute the request
+        Then token 334 should have passed checks
+
+    Scenario: Transfer after the time window of the project is refused
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk25",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "335" } },
+                    "height": 150,
+                    "block_time": 1706745600000
+                }
+            ]
+            """
+        Given an empty queue
+        Given project projectId accepts transfers from 2023-06-01 until 2024-01-01
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x5759 | k3plr-pk25 | projectId | [335] |
+        When I execute the request
+        Then token 335 should have failed checks with code "transfer_outside_window"
+
+    Scenario: Undated transfer is refused once the project has a transfer window
+        Given the following transaction list
+            """
+            [
+                {
+                    "sender": "k3plr-pk26",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "336" } }
+                }
+            ]
+            """
+        Given an empty queue
+        Given project projectId accepts transfers from 100 until 200
+        Given a request with values:
+            | signed_hash | starknet_account_addr | keplr_customer_pubkey | project_id | tokens_ids |
+            | aValidSignedHash | 0x575a | k3plr-pk26 | projectId | [336] |
+        When I execute the request
+        Then token 336 should have failed checks with code "transfer_outside_window"
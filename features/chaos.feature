@@ -0,0 +1,32 @@
+Feature: Failures are injected around infrastructure adapters to rehearse incidents
+    Rule:
+        - Only built with the chaos feature
+        - Each adapter fails with its own configured probability
+        - Injected failures go through the same retry and dead letter paths as real ones
+
+    Scenario: Juno LCD answers with server errors
+        Given a failure injector with rates juno 1, starknet 0 and database 0
+        When transactions of project "projectId" are fetched
+        Then the fetch should fail with a Juno server error 500
+
+    Scenario: Rejected mints are retried then dead lettered
+        Given a failure injector with rates juno 0, starknet 1 and database 0
+        Given token "800" is queued
+        When the worker consumes the queue 3 times
+        Then token "800" should be "dead_letter" after 3 attempt(s)
+
+    Scenario: Database timeouts fail the batch
+        Given a failure injector with rates juno 0, starknet 0 and database 1
+        Given token "810" is queued
+        When the worker consumes the queue
+        Then the worker should have failed to get its batch
+
+    Scenario: Adapters are left untouched without failure rates
+        Given a failure injector with rates juno 0, starknet 0 and database 0
+        Given token "820" is queued
+        When the worker consumes the queue
+        Then token "820" should be "success" after 0 attempt(s)
+
+    Scenario: Failure rates outside of 0 and 1 are refused
+        When a failure injector is configured with a juno rate of 1.5
+        Then the failure injector should be refused
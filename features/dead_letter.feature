@@ -0,0 +1,70 @@
+Feature: Items failing to mint are retried with backoff then parked in a dead letter queue
+    Rule:
+        - A failed mint puts the item back to pending and counts the attempt
+        - The item is not retried before its backoff delay elapsed
+        - Once its attempts are exhausted the item is parked as a dead letter
+        - Operators list dead letters and requeue them with a fresh attempt budget
+        - Dead letters are ticketed once per customer and project, with the support bundle attached
+
+    Scenario: Failed mint is retried once its backoff delay elapsed
+        Given token "500" is queued
+        Given starknet mints fail
+        When the worker consumes the queue
+        Then token "500" should be "pending" after 1 attempt(s)
+        When the worker consumes the queue
+        Then token "500" should be "pending" after 1 attempt(s)
+        When 60 seconds elapse
+        And the worker consumes the queue
+        Then token "500" should be "pending" after 2 attempt(s)
+
+    Scenario: Item is parked after its last attempt
+        Given token "501" is queued
+        Given starknet mints fail
+        When the worker consumes the queue 3 times
+        Then token "501" should be "dead_letter" after 3 attempt(s)
+        And the dead letter queue should list token "501"
+        When 1 hour elapses
+        And the worker consumes the queue
+        Then token "501" should be "dead_letter" after 3 attempt(s)
+
+    Scenario: Operator requeues a dead letter
+        Given token "502" is queued
+        Given starknet mints fail
+        When the worker consumes the queue 3 times
+        Given starknet mints succeed
+        When the operator requeues dead letter "502"
+        Then the requeue should be accepted
+        And token "502" should be "pending" after 0 attempt(s)
+        When the worker consumes the queue
+        Then token "502" should be "success" after 0 attempt(s)
+        And the dead letter queue should be empty
+
+    Scenario: Only dead letters can be requeued from the dead letter queue
+        Given token "503" is queued
+        When the operator requeues dead letter "503"
+        Then the requeue should be rejected because of its "pending" status
+
+    Scenario: Dead letters of a customer are ticketed once
+        Given token "504" is queued
+        And token "505" is queued
+        Given starknet mints fail
+        When the worker consumes the queue 3 times
+        And dead letters are reported
+        Then 1 issue(s) should have been opened
+        And the last issue should list token "504"
+        And the last issue should list token "505"
+        And the last issue should have the support bundle attached
+        When dead letters are reported
+        Then 1 issue(s) should have been opened
+
+    Scenario: Dead letters are ticketed once the tracker is reachable again
+        Given token "506" is queued
+        Given starknet mints fail
+        When the worker consumes the queue 3 times
+        Given the issue tracker is down
+        When dead letters are reported
+        Then 0 issue(s) should have been opened
+        Given the issue tracker is up
+        When dead letters are reported
+        Then 1 issue(s) should have been opened
+        And the last issue should list token "506"
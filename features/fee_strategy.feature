@@ -0,0 +1,60 @@
+Feature: Max fee of mint transactions is decided by the fee strategy of the network
+    Rule:
+        - Networks without a strategy pay the estimate times the project multiplier
+        - The moving average strategy pays at least the recent accepted fee per call
+        - The capped strategy never pays over its cap and sends nothing above it
+        - Items of a transaction above the cap are minted later, their attempts untouched
+
+    Background:
+        Given fee estimates are multiplied by 10
+        And project "0x0c4a" fee estimates are multiplied by 2.5
+
+    Scenario: Fee estimates are multiplied on networks without a strategy
+        Given the "testnet-1" network uses the "cap:1000" fee strategy
+        When the max fee of 2 calls on project "0x0bad" estimated to 1000 is computed on "mainnet"
+        Then the max fee should be 10000
+
+    Scenario: Project multipliers apply to zero padded addresses
+        When the max fee of 2 calls on project "0x00c4a" estimated to 1000 is computed on "mainnet"
+        Then the max fee should be 2500
+
+    Scenario: Moving average follows accepted fees per call
+        Given the "mainnet" network uses the "ewma:0.5" fee strategy
+        When a transaction of 1 call on project "0x0c4a" was accepted for 3000 on "mainnet"
+        And the max fee of 2 calls on project "0x0c4a" estimated to 1000 is computed on "mainnet"
+        Then the max fee should be 15000
+        When a transaction of 1 call on project "0x0c4a" was accepted for 1000 on "mainnet"
+        And the max fee of 1 call on project "0x0c4a" estimated to 1000 is computed on "mainnet"
+        Then the max fee should be 5000
+
+    Scenario: Moving average falls back to the estimate until a fee is accepted
+        Given the "mainnet" network uses the "ewma" fee strategy
+        When the max fee of 1 call on project "0x0c4a" estimated to 1000 is computed on "mainnet"
+        Then the max fee should be 2500
+
+    Scenario: Capped max fee
+        Given the "mainnet" network uses the "cap:5000" fee strategy
+        When the max fee of 1 call on project "0x0bad" estimated to 1000 is computed on "mainnet"
+        Then the max fee should be 5000
+        When the max fee of 1 call on project "0x0bad" estimated to 6000 is computed on "mainnet"
+        Then the fee should be above the cap
+
+    Scenario Outline: Invalid fee strategies are refused
+        Given the "mainnet" network uses the "<strategy>" fee strategy
+        Then the fee strategies should be refused
+
+        Examples:
+            | strategy   |
+            | cheapest   |
+            | ewma:0     |
+            | ewma:2     |
+            | cap        |
+            | cap:-1     |
+            | multiplier:2 |
+
+    Scenario: Items are deferred while fees are above the cap
+        Given the "mainnet" network uses the "cap:500000000000000" fee strategy
+        And tokens "900,901" are queued on project "0x0c4a"
+        When the worker consumes the queue on "mainnet"
+        Then token "900" should be "pending" with note "FeeAboveCap" after 0 attempts
+        And token "901" should be "pending" with note "FeeAboveCap" after 0 attempts
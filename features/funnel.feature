@@ -0,0 +1,28 @@
+Feature: Frontend funnel events are sampled per session and pruned after the retention
+    Rule:
+        - A sampled session keeps every one of its steps, other sessions keep none
+        - Events older than the retention are pruned
+
+    Scenario: Every session is kept without sampling
+        Given funnel events are sampled at 1
+        When 20 sessions connect their wallet and submit a bridge request
+        Then 40 funnel events should be recorded
+
+    Scenario: No session is kept when sampling is off
+        Given funnel events are sampled at 0
+        When 20 sessions connect their wallet and submit a bridge request
+        Then 0 funnel events should be recorded
+
+    Scenario: Sampled sessions keep all their steps
+        Given funnel events are sampled at 0.5
+        When 200 sessions connect their wallet and submit a bridge request
+        Then every recorded session should have 2 funnel events
+        And between 60 and 140 sessions should be recorded
+
+    Scenario: Events older than the retention are pruned
+        Given funnel events are kept 30 days
+        And 3 sessions connected their wallet 31 days ago
+        And 2 sessions connected their wallet 29 days ago
+        When funnel events are pruned
+        Then 3 funnel events should have been pruned
+        And 2 funnel events should be recorded
@@ -0,0 +1,692 @@
+Feature: HTTP layer answers with the response envelope
+    Rule:
+        - Handlers are served with in-memory ports
+        - Successful calls answer `{ "ok": true, "data": .. }`
+        - Failures answer `{ "ok": false, "error": { "code": .. } }` with the status of the error catalog
+        - Admin endpoints require an operator api key or a bearer token signed with the admin secret
+
+    Scenario: Health check
+        When I GET "/health"
+        Then the response status should be 200
+
+    Scenario: Instance is ready once every dependency answers
+        Given the "postgres" dependency answers
+        And the "juno lcd" dependency answers
+        And the "starknet gateway" dependency answers
+        When I GET "/ready"
+        Then the response status should be 200
+        And the response data should have "/dependencies/0/status" equal to "up"
+        And the response data should have "/dependencies/2/name" equal to "starknet gateway"
+
+    Scenario: Instance with a dead database connection is not ready
+        Given the "postgres" dependency fails with "connection closed"
+        And the "juno lcd" dependency answers
+        When I GET "/ready"
+        Then the response status should be 503
+        And the response should fail with code "not_ready"
+        And the response body should have "/error/details/dependencies/0/status" equal to "down"
+        And the response body should have "/error/details/dependencies/0/error" equal to "connection closed"
+        And the response body should have "/error/details/dependencies/1/status" equal to "up"
+
+    Scenario: Hanging dependency answers the probe as down within the timeout
+        Given the "starknet gateway" dependency fails with "hanging"
+        When I GET "/ready"
+        Then the response status should be 503
+        And the response should have taken between 0 and 1 seconds
+        And the response body should have "/error/details/dependencies/0/status" equal to "down"
+
+    Scenario: Responses carry the id their log lines are tagged with
+        When I GET "/health"
+        Then the response header "x-request-id" should be set
+
+    Scenario: Request id set by a proxy is kept
+        When I GET "/health" with request id "edge-4f1c.7"
+        Then the response header "x-request-id" should be "edge-4f1c.7"
+
+    Scenario: Request id unfit for log lines is replaced
+        When I GET "/health" with request id "id with spaces"
+        Then the response header "x-request-id" should be set
+        And the response header "x-request-id" should not be "id with spaces"
+
+    Scenario: Bridge request is enqueued
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "254" } }
+                },
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "255" } }
+                }
+            ]
+            """
+        When k3plr-pk1 bridges tokens "254, 255" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 200
+        And the response should be ok
+        And token "254" of k3plr-pk1 should be queued
+        And token "255" of k3plr-pk1 should be queued
+
+    Scenario: Queued tokens keep the id of their bridge request
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "254" } }
+                }
+            ]
+            """
+        When k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash and request id "edge-9b2e"
+        Then the response status should be 200
+        And token "254" of k3plr-pk1 should be queued for request "edge-9b2e"
+
+    Scenario: Bridge request after the project migration window is refused
+        Given migrations of the project are open from "2023-06-01" to "2023-09-01T12:00:00Z"
+        When k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 403
+        And the response should fail with code "migration_window_closed"
+        And the error details should have "/opens_at" equal to "2023-06-01T00:00:00.000Z"
+        And the error details should have "/closes_at" equal to "2023-09-01T12:00:00.000Z"
+
+    Scenario: Frontend polls the finality of a mint transaction
+        When I GET "/bridge/tx/0x0abc"
+        Then the response status should be 200
+        And the response data should have "/transaction_hash" equal to "0x0abc"
+        And the response data should have "/status" equal to "accepted"
+
+    Scenario: Mint transaction still waiting for Starknet
+        Given starknet holds transactions
+        When I GET "/bridge/tx/0x0abc"
+        Then the response status should be 200
+        And the response data should have "/status" equal to "pending"
+
+    Scenario: Rejected mint transaction comes with its failure reason
+        Given starknet rejects transactions with "INVALID_TRANSACTION_NONCE"
+        When I GET "/bridge/tx/0x0abc"
+        Then the response status should be 200
+        And the response data should have "/status" equal to "rejected"
+        And the response data should have "/failure_reason" equal to "INVALID_TRANSACTION_NONCE"
+
+    Scenario: Transaction hash that is not a felt
+        When I GET "/bridge/tx/not-a-hash"
+        Then the response status should be 404
+
+    Scenario: Invalid signature
+        When k3plr-pk1 bridges tokens "254" to 0x5741 with signature anInvalidHash
+        Then the response status should be 400
+        And the response should fail with code "invalid_sign"
+
+    Scenario: Keplr wallet linked to another starknet account
+        Given keplr wallet k3plr-pk9 is linked to starknet account 0x5749
+        When k3plr-pk9 bridges tokens "260" to 0x5742 with signature aValidSignedHash
+        Then the response status should be 409
+        And the response should fail with code "starknet_account_mismatch"
+
+    Scenario: No token to bridge
+        When k3plr-pk1 bridges tokens "" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 404
+        And the response should fail with code "fetch_token_error"
+
+    Scenario: Service shutting down
+        Given the service is shutting down
+        When k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 503
+        And the response should fail with code "cancelled"
+
+    Scenario: Token failing checks is reported in error details
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "not-the-customer",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "256" } }
+                }
+            ]
+            """
+        When k3plr-pk1 bridges tokens "256" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 400
+        And the response should fail with code "sender_mismatch"
+        And the error details should report token "256" as "Token sender didn't match customer wallet public key"
+
+    Scenario: Unknown token is reported as not found
+        When k3plr-pk1 bridges tokens "257" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 404
+        And the response should fail with code "transaction_not_found"
+
+    Scenario: Support reads bridge requests of a wallet with the checks customer got
+        Given an operator alice with api key alice-key
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "not-the-customer",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "256" } }
+                }
+            ]
+            """
+        When k3plr-pk1 bridges tokens "256" to 0x5741 with signature anInvalidHash
+        And k3plr-pk1 bridges tokens "256" to 0x5741 with signature aValidSignedHash
+        And I GET "/admin/bridge-requests/k3plr-pk1" with api key alice-key
+        Then the response status should be 200
+        And the response data should have 2 entries
+        And the response data should have "/0/outcome" equal to "sender_mismatch"
+        And the response data should have "/0/checks/0/token_id" equal to "256"
+        And the response data should have "/0/checks/0/failed_check" equal to "Token sender didn't match customer wallet public key"
+        And the response data should have "/1/outcome" equal to "invalid_sign"
+        And the response data should have "/1/starknet_account_addr" equal to "0x5741"
+
+    Scenario: Malformed JSON body
+        When I POST "/bridge" with:
+            """
+            { "signed_hash": 
+            """
+        Then the response status should be 400
+        And the response should fail with code "invalid_payload"
+
+    Scenario: Unknown field in body
+        When I POST "/customer/data" with:
+            """
+            { "keplr_wallet_pubkey": "k3plr-pk1", "project_id": "projectId", "token_ids": ["1"], "tokenIds": ["2"] }
+            """
+        Then the response status should be 400
+        And the response should fail with code "invalid_payload"
+        And the response text should contain "unknown field `tokenIds`"
+
+    Scenario: Payload built for the current schema version
+        Given schema versions are required
+        When I POST "/customer/data" with schema version "1" and:
+            """
+            { "keplr_wallet_pubkey": "k3plr-pk1", "project_id": "projectId", "token_ids": ["1"] }
+            """
+        Then the response status should be 201
+        And the response header "x-bridge-schema-version" should be "1"
+
+    Scenario: Payload built for another schema version
+        When I POST "/customer/data" with schema version "2" and:
+            """
+            { "keplr_wallet_pubkey": "k3plr-pk1", "project_id": "projectId", "token_ids": ["1"] }
+            """
+        Then the response status should be 400
+        And the response should fail with code "unsupported_schema_version"
+        And the response text should contain "Schema version 2 is not supported, this bridge accepts version 1"
+        And the response header "x-bridge-schema-version" should be "1"
+
+    Scenario: Payload without schema version once it is required
+        Given schema versions are required
+        When I POST "/customer/data" with:
+            """
+            { "keplr_wallet_pubkey": "k3plr-pk1", "project_id": "projectId", "token_ids": ["1"] }
+            """
+        Then the response status should be 400
+        And the response should fail with code "missing_schema_version"
+
+    Scenario: Reads do not need a schema version
+        Given schema versions are required
+        Given token "264" of k3plr-pk1 is queued
+        When I GET "/customer/data/k3plr-pk1/0x0d1e"
+        Then the response status should be 200
+        And the response header "x-bridge-schema-version" should be "1"
+
+    Scenario: Invalid typed identifier in body
+        When I POST "/bridge" with:
+            """
+            {
+                "signed_hash": { "pub_key": { "type": "tendermint/PubKeySecp256k1", "value": "key" }, "signature": "aValidSignedHash" },
+                "starknet_account_addr": "not-an-address",
+                "starknet_project_addr": "0x0d1e",
+                "keplr_wallet_pubkey": "k3plr-pk1",
+                "project_id": "projectId",
+                "tokens_id": ["254"]
+            }
+            """
+        Then the response status should be 400
+        And the response should fail with code "invalid_payload"
+
+    Scenario: Customer data is saved
+        When I POST "/customer/data" with:
+            """
+            { "keplr_wallet_pubkey": "k3plr-pk1", "project_id": "projectId", "token_ids": ["1", "2"] }
+            """
+        Then the response status should be 201
+        And the response should be ok
+        And the response data should have "/added" equal to 2
+        And the response data should have "/total" equal to 2
+
+    Scenario: Customer is told which saved tokens were already registered
+        Given customer k3plr-pk1 registered tokens "1, 2" on project projectId
+        When I POST "/customer/data" with:
+            """
+            { "keplr_wallet_pubkey": "k3plr-pk1", "project_id": "projectId", "token_ids": ["2", "3", "3"] }
+            """
+        Then the response status should be 201
+        And the response data should have "/added" equal to 1
+        And the response data should have "/duplicates" equal to 2
+        And the response data should have "/total" equal to 3
+
+    Scenario: Funnel event sent by the frontend is recorded
+        When I POST "/events" with:
+            """
+            { "event": "signature_shown", "session_id": "5b0e8a52-2c1e-4d7a-9d4c-0f7d1e3b6a11", "project_id": "projectId" }
+            """
+        Then the response status should be 202
+        And the response should be ok
+        And 1 funnel event should be recorded
+
+    Scenario: Funnel events cannot carry a wallet
+        When I POST "/events" with:
+            """
+            { "event": "wallet_connected", "session_id": "5b0e8a52", "keplr_wallet_pubkey": "k3plr-pk1" }
+            """
+        Then the response status should be 400
+        And the response should fail with code "invalid_payload"
+        And 0 funnel events should be recorded
+
+    Scenario: Funnel event with an invalid session is refused
+        When I POST "/events" with:
+            """
+            { "event": "bridge_submitted", "session_id": "not a session" }
+            """
+        Then the response status should be 400
+        And the response should fail with code "invalid_session"
+
+    Scenario: Migration state of an unknown customer
+        When I GET "/customer/data/k3plr-pk1/0x0d1e"
+        Then the response status should be 404
+        And the response should fail with code "migration_not_found"
+
+    Scenario: Migration state of a customer
+        Given token "254" of k3plr-pk1 is queued
+        When I GET "/customer/data/k3plr-pk1/0x0d1e"
+        Then the response status should be 200
+        And the response should be ok
+
+    Scenario: Migration state grouped by status
+        Given token "271" of k3plr-pk8 is queued
+        Given token "270" of k3plr-pk8 is queued
+        Given the queued token was minted in transaction 0x7e5
+        When I GET "/customer/data/k3plr-pk8/0x0d1e?group=status"
+        Then the response status should be 200
+        And the response data should have "/pending/0/token_id" equal to "271"
+        And the response data should have "/success/0/token_id" equal to "270"
+        And the response data should have "/counts/pending" equal to 1
+        And the response data should have "/counts/success" equal to 1
+        And the response data should have "/total" equal to 2
+
+    Scenario: Migration state filtered by status keeps the counts of the whole migration
+        Given token "273" of k3plr-pk8 is queued
+        Given token "272" of k3plr-pk8 is queued
+        Given the queued token was minted in transaction 0x7e6
+        When I GET "/customer/data/k3plr-pk8/0x0d1e?status=pending&group=status"
+        Then the response status should be 200
+        And the response data should have "/pending/0/token_id" equal to "273"
+        And the response data should not have "/success/0"
+        And the response data should have "/counts/success" equal to 1
+
+    Scenario: Migration state sorted by token
+        Given token "10" of k3plr-pk8 is queued
+        Given token "9" of k3plr-pk8 is queued
+        Given token "100" of k3plr-pk8 is queued
+        When I GET "/customer/data/k3plr-pk8/0x0d1e?sort=token_id"
+        Then the response status should be 200
+        And the response data should have "/0/token_id" equal to "9"
+        And the response data should have "/1/token_id" equal to "10"
+        And the response data should have "/2/token_id" equal to "100"
+
+    Scenario: Waiting migration state is answered once a status changes
+        Given token "262" of k3plr-pk4 is queued
+        When I GET "/customer/data/k3plr-pk4/0x0d1e?wait=30" while the queued token is minted in transaction 0x7e4
+        Then the response status should be 200
+        And the response data should have "/0/status" equal to "success"
+        And the response should have taken between 0 and 5 seconds
+
+    Scenario: Waiting migration state is answered as is once the wait elapsed
+        Given token "263" of k3plr-pk4 is queued
+        When I GET "/customer/data/k3plr-pk4/0x0d1e?wait=1"
+        Then the response status should be 200
+        And the response data should have "/0/status" equal to "pending"
+        And the response should have taken between 1 and 5 seconds
+
+    Scenario: Migration state explains notes in the customer language
+        Given token "258" of k3plr-pk2 is queued
+        Given the mint of the queued token was rejected in transaction 0x7e1 with reason OUT_OF_RESOURCES
+        When I GET "/customer/data/k3plr-pk2/0x0d1e" in language "fr-CH, en;q=0.8"
+        Then the response status should be 200
+        And the response data should have "/0/note" equal to "TransactionRejected"
+        And the response data should have "/0/message" equal to "La transaction 0x7e1 a été rejetée (OUT_OF_RESOURCES), une nouvelle tentative est prévue"
+
+    Scenario: Migration state falls back to english notes
+        Given token "259" of k3plr-pk2 is queued
+        Given the mint of the queued token was rejected in transaction 0x7e2 with reason OUT_OF_RESOURCES
+        When I GET "/customer/data/k3plr-pk2/0x0d1e" in language "de"
+        Then the response status should be 200
+        And the response data should have "/0/message" equal to "Transaction 0x7e2 was rejected (OUT_OF_RESOURCES), another attempt is scheduled"
+
+    Scenario: Migration state shows the proven Juno transfer of each token
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "257" } }
+                }
+            ]
+            """
+        When k3plr-pk1 bridges tokens "257" to 0x5741 with signature aValidSignedHash
+        And I GET "/customer/data/k3plr-pk1/0x0d1e"
+        Then the response status should be 200
+        And the response data should have "/0/token_id" equal to "257"
+        And the response data should have "/0/transfer_proof/sender" equal to "k3plr-pk1"
+        And the response data should have "/0/transfer_proof/recipient" equal to "juno-admin-account"
+
+    Scenario: Minted items come with a verifiable attestation
+        Given mint attestations are signed with 0x3c1e9550e66958296d11b60f8e8e7a7ad990d07fa65d5f7652c4a6c87d4e3cc
+        Given token "260" of k3plr-pk3 is queued
+        Given the queued token was minted in transaction 0x7e3
+        When I GET "/customer/data/k3plr-pk3/0x0d1e"
+        Then the response status should be 200
+        And the response data should have "/0/status" equal to "success"
+        And the attestation of item 0 should verify against the signer key
+
+    Scenario: Items not minted yet have no attestation
+        Given mint attestations are signed with 0x3c1e9550e66958296d11b60f8e8e7a7ad990d07fa65d5f7652c4a6c87d4e3cc
+        Given token "261" of k3plr-pk3 is queued
+        When I GET "/customer/data/k3plr-pk3/0x0d1e"
+        Then the response status should be 200
+        And the response data should not have "/0/attestation"
+
+    Scenario: Migrations of a wallet across every project
+        Given token "264" of k3plr-pk5 is queued
+        Given token "265" of k3plr-pk5 is queued on project 0x0f0e
+        Given token "266" of k3plr-pk6 is queued
+        When I GET "/customer/migrations/k3plr-pk5"
+        Then the response status should be 200
+        And the response data should have 2 entries
+        And the response data should have "/0/project_id" equal to "0x0d1e"
+        And the response data should have "/0/token_id" equal to "264"
+        And the response data should have "/1/project_id" equal to "0x0f0e"
+        And the response data should have "/1/token_id" equal to "265"
+
+    Scenario: Migrations of an unknown wallet
+        When I GET "/customer/migrations/k3plr-pk7"
+        Then the response status should be 404
+        And the response should fail with code "migration_not_found"
+
+    Scenario: Customer downloads the proof bundle of their migration
+        Given proof bundles are signed with "proof-secret"
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "257" } }
+                }
+            ]
+            """
+        When k3plr-pk1 bridges tokens "257" to 0x5741 with signature aValidSignedHash
+        And I GET "/customer/proofs/k3plr-pk1/0x0d1e"
+        Then the response status should be 200
+        And the response header "content-disposition" should be "attachment; filename=migration-proofs-0x0d1e-k3plr-pk1.json"
+        And the response body should have "/bundle/juno_contract" equal to "projectId"
+        And the response body should have "/bundle/tokens/0/token_id" equal to "257"
+        And the response body should have "/bundle/tokens/0/juno_sender" equal to "k3plr-pk1"
+        And the proof bundle signature should match its content
+
+    Scenario: Proof bundle of an unknown migration is not found
+        When I GET "/customer/proofs/k3plr-pk9/0x0d1e"
+        Then the response status should be 404
+        And the response should fail with code "migration_not_found"
+
+    Scenario: Frontend preflight is allowed
+        When "http://frontend.test" sends a preflight request for POST "/bridge"
+        Then the response should allow origin "http://frontend.test"
+
+    Scenario: Preflight from another origin is rejected
+        When "http://evil.test" sends a preflight request for POST "/bridge"
+        Then the response should not allow any origin
+
+    Scenario: Admin endpoints reject anonymous calls
+        Given an operator alice with api key alice-key
+        When I GET "/admin/queue?status=pending"
+        Then the response status should be 401
+        And the response should fail with code "unauthorized"
+
+    Scenario: Operator lists queue items by status with an api key
+        Given an operator alice with api key alice-key
+        Given token "254" of k3plr-pk1 is queued
+        When I GET "/admin/queue?status=pending" with api key alice-key
+        Then the response status should be 200
+        And the response data should have "/items/0/token_id" equal to "254"
+
+    Scenario: Unknown api key is rejected
+        Given an operator alice with api key alice-key
+        When I GET "/admin/queue" with api key mallory-key
+        Then the response status should be 401
+
+    Scenario: Operator inspects a queue item with a bearer token
+        Given token "254" of k3plr-pk1 is queued
+        When I GET "/admin/queue/{queued}" with a token of alice expiring in 300 seconds
+        Then the response status should be 200
+        And the response data should have "/item/status" equal to "pending"
+
+    Scenario: Expired token is rejected
+        Given token "254" of k3plr-pk1 is queued
+        When I GET "/admin/queue/{queued}" with a token of alice expiring in -1 seconds
+        Then the response status should be 401
+
+    Scenario: Token signed with another secret is rejected
+        Given token "254" of k3plr-pk1 is queued
+        When I GET "/admin/queue/{queued}" with a token of alice signed with "not-the-admin-secret"
+        Then the response status should be 401
+
+    Scenario: Operator cancels a pending queue item
+        Given token "254" of k3plr-pk1 is queued
+        When I POST "/admin/queue/{queued}/cancel" with a token of alice expiring in 300 seconds
+        Then the response status should be 200
+        And the response data should have "/status" equal to "error"
+        And the response data should have "/note" equal to "CancelledByOperator"
+
+    Scenario: Only failed queue items can be requeued
+        Given an operator alice with api key alice-key
+        Given token "254" of k3plr-pk1 is queued
+        When I POST "/admin/queue/{queued}/requeue" with api key alice-key
+        Then the response status should be 409
+        And the response should fail with code "invalid_status"
+
+    Scenario: Operator lists batch analytics within a date range
+        Given an operator alice with api key alice-key
+        Given a batch of 3 items was minted in transaction 0x01 at 1672531200000
+        Given a batch of 5 items was minted in transaction 0x02 at 1672617600000
+        When I GET "/admin/analytics/batches?from=1672600000000&to=1672700000000" with api key alice-key
+        Then the response status should be 200
+        And the response data should have 1 entries
+        And the response data should have "/0/transaction_hash" equal to "0x02"
+        And the response data should have "/0/fee" equal to "1200000000000000"
+
+    Scenario: Batch analytics range has to be ordered
+        Given an operator alice with api key alice-key
+        When I GET "/admin/analytics/batches?from=1672700000000&to=1672600000000" with api key alice-key
+        Then the response status should be 400
+
+    Scenario: Operator checks the growth of the database
+        Given an operator alice with api key alice-key
+        Given table migration_queue_history holds 6000 live and 4000 dead rows
+        Given table migration_queue holds 2000 live and 10 dead rows
+        When I GET "/admin/maintenance" with api key alice-key
+        Then the response status should be 200
+        And the response data should have "/tables/0/table" equal to "migration_queue"
+        And the response data should have "/tables/1/dead_rows" equal to 4000
+        And the response data should have "/hints/0/table" equal to "migration_queue_history"
+        And the response data should have "/hints/0/action" equal to "vacuum"
+        And the response data should not have "/hints/1"
+
+    Scenario: Migration state is exported as CSV
+        Given token "254" of k3plr-pk1 is queued
+        Given token "255" of k3plr-pk1 is queued
+        When I GET "/customer/data/k3plr-pk1/0x0d1e" accepting "text/csv"
+        Then the response status should be 200
+        And the response content type should be "text/csv; charset=utf-8"
+        And the CSV header should be "id,keplr_wallet_pubkey,starknet_wallet_pubkey,project_id,token_id,status,transaction_hash,note,attempts"
+        And the CSV should have 2 rows
+
+    Scenario: Admin queue search is exported as CSV
+        Given an operator alice with api key alice-key
+        Given token "254" of k3plr-pk1 is queued
+        When I GET "/admin/queue?status=pending" with api key alice-key accepting "text/csv, application/json;q=0.5"
+        Then the response status should be 200
+        And the CSV should have 1 row
+        And CSV row 1 should contain "254"
+        And CSV row 1 should contain "pending"
+
+    Scenario: JSON stays the default representation
+        Given token "254" of k3plr-pk1 is queued
+        When I GET "/customer/data/k3plr-pk1/0x0d1e" accepting "application/json"
+        Then the response status should be 200
+        And the response should be ok
+
+    Scenario: Customer lists the tokens they can still bridge
+        Given keplr wallet k3plr-pk20 holds tokens "401, 402, 403" on juno
+        And keplr wallet k3plr-pk21 holds tokens "404" on juno
+        And token 402 was already minted on starknet
+        When I GET "/customer/eligible/k3plr-pk20/projectId"
+        Then the response status should be 200
+        And the response data should have "/eligible/0" equal to "401"
+        And the response data should have "/eligible/1" equal to "403"
+        And the response data should not have "/eligible/2"
+        And the response data should have "/already_minted/0" equal to "402"
+        And the response data should have "/starknet_contract" equal to "0x0d1e"
+
+    Scenario: Eligible tokens of a project that is not bridged are refused
+        When I GET "/customer/eligible/k3plr-pk20/otherProject"
+        Then the response status should be 404
+        And the response should fail with code "unknown_project"
+
+    Scenario: OpenAPI document describes the public endpoints
+        When I GET "/v1/meta/openapi.json"
+        Then the response status should be 200
+        And the OpenAPI document should describe "/bridge"
+        And the OpenAPI document should describe "/customer/data"
+        And the OpenAPI document should describe "/customer/data/{keplr_wallet_pubkey}/{project_id}"
+        And the OpenAPI document should describe "/customer/proofs/{keplr_wallet_pubkey}/{project_id}"
+        And the OpenAPI document should describe "/customer/migrations/{keplr_wallet_pubkey}"
+        And the OpenAPI document should describe "/customer/eligible/{keplr_wallet_pubkey}/{project_id}"
+        And the OpenAPI document should describe "/health"
+        And the OpenAPI document should define schema "ErrorEnvelope"
+        And the OpenAPI document should list error code "token_already_minted"
+
+    Scenario: Challenge nonce cannot be replayed
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "254" } }
+                }
+            ]
+            """
+        Given signature challenges are required
+        When k3plr-pk1 requests a challenge
+        Then the response status should be 200
+        When k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash and the issued nonce
+        Then the response status should be 200
+        And the response should be ok
+        When k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash and the issued nonce
+        Then the response status should be 400
+        And the response should fail with code "invalid_challenge"
+
+    Scenario: Bridge request without challenge is refused once challenges are required
+        Given signature challenges are required
+        When k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 400
+        And the response should fail with code "invalid_challenge"
+
+    Scenario: Expired challenge nonce is refused
+        Given signature challenges expire after 0 seconds
+        When k3plr-pk1 requests a challenge
+        And k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash and the issued nonce
+        Then the response status should be 400
+        And the response should fail with code "challenge_expired"
+
+    Scenario: Challenge issued to another wallet is refused
+        When k3plr-pk2 requests a challenge
+        And k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash and the issued nonce
+        Then the response status should be 400
+        And the response should fail with code "invalid_challenge"
+
+    Scenario: Challenge refused to another wallet is left to the wallet it was issued to
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk2",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "254" } }
+                }
+            ]
+            """
+        Given signature challenges are required
+        When k3plr-pk2 requests a challenge
+        And k3plr-pk1 bridges tokens "254" to 0x5741 with signature aValidSignedHash and the issued nonce
+        Then the response status should be 400
+        And the response should fail with code "invalid_challenge"
+        When k3plr-pk2 bridges tokens "254" to 0x5741 with signature aValidSignedHash and the issued nonce
+        Then the response status should be 200
+        And the response should be ok
+
+    Scenario: Clients are told their remaining quota
+        Given a rate limit of 2 requests per minute
+        When I GET "/health"
+        Then the response status should be 200
+        And the response header "x-ratelimit-limit" should be "2"
+        And the response header "x-ratelimit-remaining" should be "1"
+
+    Scenario: Clients over the rate limit are told when to retry
+        Given a rate limit of 2 requests per minute
+        When I GET "/health"
+        And I GET "/health"
+        And I GET "/health"
+        Then the response status should be 429
+        And the response should fail with code "rate_limited"
+        And the response header "x-ratelimit-remaining" should be "0"
+        And the response header "retry-after" should be set
+
+    Scenario: Bridge requests are told when to retry while the queue is full
+        Given token "262" of k3plr-pk4 is queued
+        Given the queue accepts at most 1 pending items, drained 10 every minute
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "263" } }
+                }
+            ]
+            """
+        When k3plr-pk1 bridges tokens "263" to 0x5741 with signature aValidSignedHash
+        Then the response status should be 429
+        And the response should fail with code "queue_full"
+        And the response header "retry-after" should be "60"
+
+    Scenario: Request metrics are scraped in the Prometheus format
+        Given metrics are scraped with prefix bridge
+        When I GET "/health"
+        And I GET "/metrics"
+        Then the response status should be 200
+        And the response content type should be "text/plain; version=0.0.4"
+        And the response text should contain 'bridge_http_requests_total{method="GET",route="/health",status="200"} 1'
+        And the response text should contain "# TYPE bridge_http_request_duration_seconds summary"
+
+    Scenario: Metrics are not scraped when pushed to an agent
+        When I GET "/metrics"
+        Then the response status should be 404
+        And the response should fail with code "metrics_not_scraped"
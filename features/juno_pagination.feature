@@ -0,0 +1,76 @@
+Feature: Juno LCD transactions are fetched page by page
+    Rule:
+        - Transactions of a contract are requested 100 at a time
+        - Messages of transactions that failed on chain are left out
+        - Pages listing transactions without their result fail the fetch
+        - Pages are fetched until the LCD has no more transactions or the page limit is reached
+        - Pages larger than the response size limit fail the fetch instead of being buffered
+        - An unreachable LCD is retried with jittered exponential backoff, without blocking the thread
+
+    Scenario: Transfer on a later page is found
+        Given the Juno LCD holds 250 transactions for contract "projectId"
+        When I fetch the transactions of token "230" on contract "projectId"
+        Then 1 transaction(s) should have been found
+        And 3 page(s) should have been requested
+
+    Scenario: Single page listing is fetched once
+        Given the Juno LCD holds 40 transactions for contract "projectId"
+        When I fetch the transactions of token "12" on contract "projectId"
+        Then 1 transaction(s) should have been found
+        And 1 page(s) should have been requested
+
+    Scenario: Listing without a total stops on the first short page
+        Given the Juno LCD holds 150 transactions for contract "projectId" without counting them
+        When I fetch the transactions of token "149" on contract "projectId"
+        Then 1 transaction(s) should have been found
+        And 2 page(s) should have been requested
+
+    Scenario: Transfer of a failed transaction is left out
+        Given the Juno LCD holds 40 transactions for contract "projectId"
+        Given the Juno LCD transaction of token 12 failed
+        When I fetch the transactions of token "12" on contract "projectId"
+        Then 0 transaction(s) should have been found
+        And 1 page(s) should have been requested
+
+    Scenario: Page without transaction results is refused
+        Given the Juno LCD holds 40 transactions for contract "projectId"
+        Given the Juno LCD leaves out transaction results
+        When I fetch the transactions of token "12" on contract "projectId"
+        Then the fetch should fail to read the response
+        And 1 page(s) should have been requested
+
+    Scenario: Fetching stops at the page limit
+        Given the Juno LCD holds 250 transactions for contract "projectId"
+        Given at most 2 pages are fetched
+        When I fetch the transactions of token "230" on contract "projectId"
+        Then 0 transaction(s) should have been found
+        And 2 page(s) should have been requested
+
+    Scenario: Oversized page is refused
+        Given the Juno LCD holds 250 transactions for contract "projectId"
+        Given responses over 4096 bytes are refused
+        When I fetch the transactions of token "230" on contract "projectId"
+        Then the fetch should fail because the response is too large
+        And 1 page(s) should have been requested
+
+    Scenario: Unreachable LCD is retried then given up
+        Given the Juno LCD is unreachable
+        And unreachable LCD calls are tried 3 times, waiting 100 to 1000 milliseconds
+        When I fetch the transactions of token "12" on contract "projectId"
+        Then the fetch should fail to reach the LCD
+        And the fetch should have taken between 150 and 2000 milliseconds
+
+    Scenario: LCD answering after the timeout is retried then given up
+        Given the Juno LCD answers after 1000 milliseconds
+        And LCD calls time out after 200 milliseconds
+        And unreachable LCD calls are tried 2 times, waiting 100 to 1000 milliseconds
+        When I fetch the transactions of token "0" on contract "projectId"
+        Then the fetch should fail to reach the LCD
+        And the fetch should have taken between 450 and 1000 milliseconds
+
+    Scenario: Retry waits double and are jittered
+        Given unreachable LCD calls are tried 5 times, waiting 100 to 1000 milliseconds
+        Then the wait after 1 failed attempt should be between 50 and 100 milliseconds
+        And the wait after 2 failed attempts should be between 100 and 200 milliseconds
+        And the wait after 3 failed attempts should be between 200 and 400 milliseconds
+        And the wait after 5 failed attempts should be between 500 and 1000 milliseconds
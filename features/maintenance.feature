@@ -0,0 +1,49 @@
+Feature: Database maintenance
+    Table sizes are reported, vacuums and analyzes worth running are hinted and
+    expired rows are pruned when asked to.
+
+    Scenario: Tables with many dead rows are worth a vacuum
+        Given table migration_queue_history holds 6000 live and 4000 dead rows
+        When the maintenance overview is fetched
+        Then table migration_queue_history should be hinted a vacuum
+        And 1 maintenance hint should be given
+
+    Scenario: Small tables are not worth a hint
+        Given table token_locks holds 10 live and 90 dead rows
+        When the maintenance overview is fetched
+        Then 0 maintenance hints should be given
+
+    Scenario: Stale statistics are worth an analyze
+        Given table bridge_requests holds 5000 live and 0 dead rows analyzed 10 days ago
+        When the maintenance overview is fetched
+        Then table bridge_requests should be hinted an analyze
+
+    Scenario: Table sizes are reported as metrics
+        Given table migration_queue holds 5000 live and 200 dead rows
+        When database maintenance runs
+        Then the dead rows gauge of table migration_queue should be 200
+
+    Scenario: Expired rows are kept unless pruning is enabled
+        Given 2 rows expired
+        When database maintenance runs
+        Then 0 expired rows should have been pruned
+        And 2 expiring rows should be left
+
+    Scenario: Expired rows are pruned once enabled
+        Given expired rows are pruned
+        Given 2 rows expired
+        Given 1 row expires in 60 seconds
+        When database maintenance runs
+        Then 2 expired rows should have been pruned
+        And 1 expiring row should be left
+
+    Scenario: Maintenance runs at most once per interval
+        Given expired rows are pruned
+        Given 1 row expired
+        When database maintenance runs
+        Given 1 row expired
+        When database maintenance runs
+        Then 0 expired rows should have been pruned
+        When 300 seconds elapsed
+        And database maintenance runs
+        Then 1 expired row should have been pruned
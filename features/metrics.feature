@@ -0,0 +1,20 @@
+Feature: Metrics are pushed to a StatsD agent
+    Rule:
+        - Metric names are prefixed with the configured prefix
+        - Tags use the DogStatsD `|#name:value` extension
+
+    Scenario: Counter is pushed with its tags
+        Given a statsd agent
+        And metrics are pushed to the agent with prefix "bridge"
+        When counter "worker_batches_total" is incremented with tag "outcome" set to "ok"
+        Then the agent should receive "bridge.worker_batches_total:1|c|#outcome:ok"
+
+    Scenario: Timing is pushed in milliseconds
+        Given a statsd agent
+        And metrics are pushed to the agent with prefix "bridge"
+        When "worker_batch_duration_seconds" takes 1500 milliseconds
+        Then the agent should receive "bridge.worker_batch_duration_seconds:1500|ms"
+
+    Scenario: Unknown backend is refused
+        When metrics are configured with backend "graphite"
+        Then metrics configuration should fail
@@ -0,0 +1,30 @@
+Feature: Starknet account nonces are handed out locally
+    Rule:
+        - The account nonce is fetched once then incremented for each transaction
+        - Concurrent batches never receive the same nonce
+        - A rejection resynchronizes the nonce from chain
+
+    Scenario: Nonces are sequential after a single fetch
+        Given the starknet account nonce is 7
+        When 3 nonces are requested
+        Then nonces 7, 8, 9 should have been handed out
+        And the account nonce should have been fetched 1 time
+
+    Scenario: Concurrent batches receive distinct nonces
+        Given the starknet account nonce is 0
+        When 20 nonces are requested concurrently
+        Then 20 distinct nonces from 0 should have been handed out
+        And the account nonce should have been fetched 1 time
+
+    Scenario: Nonce is fetched again after a rejection
+        Given the starknet account nonce is 7
+        When 3 nonces are requested
+        And a transaction is rejected while the account nonce is 8
+        And 2 nonces are requested
+        Then nonces 7, 8, 9, 8, 9 should have been handed out
+        And the account nonce should have been fetched 2 times
+
+    Scenario: Failed fetch does not hand out a nonce
+        Given the starknet account nonce cannot be fetched
+        When 1 nonce is requested
+        Then no nonce should have been handed out
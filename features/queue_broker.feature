@@ -0,0 +1,21 @@
+Feature: Enqueued items are pushed to workers through a broker
+
+    Scenario: Enqueued items are published to workers
+        When tokens "700,701" of "k3plr-pk1" are enqueued
+        Then the broker should deliver tokens "700,701"
+
+    Scenario: Waiting workers are woken by published items
+        Given a worker is waiting for work
+        When tokens "702" of "k3plr-pk1" are enqueued
+        Then the worker should be woken before its next poll
+
+    Scenario: Statuses are written back to the store
+        When tokens "703" of "k3plr-pk1" are enqueued
+        And the worker mints its batch with transaction "0xabc"
+        Then token "703" should be "success" in the store
+
+    Scenario: Items stay queued when the broker is down
+        Given the broker refuses messages
+        When tokens "704" of "k3plr-pk1" are enqueued
+        Then token "704" should be "pending" in the store
+        And the next batch should contain tokens "704"
@@ -0,0 +1,36 @@
+Feature: Workers consume two queue backends while the queue moves between them
+
+    Scenario: New items go to the primary backend
+        Given tokens "600" of "k3plr-pk1" are queued in the legacy backend
+        When tokens "601,602" of "k3plr-pk1" are enqueued
+        Then the primary backend should hold tokens "601,602"
+        And the legacy backend should hold tokens "600"
+
+    Scenario: Tokens already queued in the legacy backend are refused
+        Given tokens "603" of "k3plr-pk1" are queued in the legacy backend
+        When tokens "603,604" of "k3plr-pk1" are enqueued
+        Then the enqueue should be refused
+        And the primary backend should hold tokens ""
+
+    Scenario: Batches drain the legacy backend first
+        Given tokens "605,606" of "k3plr-pk1" are queued in the legacy backend
+        And tokens "607,608" of "k3plr-pk1" are queued in the primary backend
+        Then a batch of 3 should contain tokens "605,606" and one more
+
+    Scenario: Items are updated in the backend holding them
+        Given tokens "609" of "k3plr-pk1" are queued in the legacy backend
+        And tokens "610" of "k3plr-pk1" are queued in the primary backend
+        When tokens "609,610" are minted with transaction "0xabc"
+        Then token "609" should be "success" in the legacy backend
+        And token "610" should be "success" in the primary backend
+
+    Scenario: Listing pages through both backends
+        Given tokens "611,612,613" of "k3plr-pk1" are queued in the legacy backend
+        And tokens "614,615" of "k3plr-pk1" are queued in the primary backend
+        Then listing the queue by pages of 2 should return tokens "611,612,613,614,615"
+
+    Scenario: Wallet migrations are listed from both backends
+        Given tokens "616" of "k3plr-pk1" are queued in the legacy backend
+        And tokens "617" of "k3plr-pk1" are queued in the primary backend
+        And tokens "618" of "k3plr-pk2" are queued in the primary backend
+        Then the migrations of "k3plr-pk1" should list tokens "616,617"
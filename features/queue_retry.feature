@@ -0,0 +1,16 @@
+Feature: Customers can retry their failed queue items
+
+    Scenario: Failed item goes back to pending
+        Given token "400" of "k3plr-pk1" failed to mint with transaction "0xfa11"
+        When the customer retries token "400"
+        Then the retry should be accepted
+        And token "400" should be "pending" without transaction hash
+
+    Scenario: Pending item cannot be retried
+        Given token "401" of "k3plr-pk1" is queued
+        When the customer retries token "401"
+        Then the retry should be rejected because of its "pending" status
+
+    Scenario: Unknown item cannot be retried
+        When the customer retries an unknown queue item
+        Then the retry should be rejected because the item does not exist
@@ -0,0 +1,39 @@
+Feature: Operators snapshot the migration queue and restore it in another environment
+    Rule:
+        - A snapshot holds every queue item with its status, history and minting batches
+        - A snapshot is checked before anything is restored
+        - A snapshot altered after it was taken is refused
+        - Restoring is all or nothing
+
+    Scenario: Queue is restored as it was snapshotted
+        Given tokens "600, 601, 602" are queued
+        Given the worker minted the queue
+        Given token "603" is queued
+        When the operator snapshots the queue
+        Then the snapshot should hold 4 items in 1 batch
+        When the operator restores the snapshot into an empty queue
+        Then the restore should succeed with 4 items
+        And the restored queue should match the snapshot
+
+    Scenario: Snapshot edited after it was taken is refused
+        Given tokens "610, 611" are queued
+        When the operator snapshots the queue
+        And token "611" is marked "success" in the snapshot
+        And the operator restores the snapshot into an empty queue
+        Then the restore should be refused because the checksum does not match
+        And the restored queue should be empty
+
+    Scenario: Snapshot with batches disagreeing with its items is refused
+        Given tokens "620, 621" are queued
+        Given the worker minted the queue
+        When the operator snapshots the queue
+        And batches are dropped from the snapshot
+        And the operator restores the snapshot into an empty queue
+        Then the restore should be refused because of an inconsistent batch
+        And the restored queue should be empty
+
+    Scenario: Snapshot cannot be restored over the queue it was taken from
+        Given tokens "630, 631" are queued
+        When the operator snapshots the queue
+        And the operator restores the snapshot into the same queue
+        Then the restore should be refused because items already exist
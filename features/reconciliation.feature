@@ -0,0 +1,87 @@
+Feature: Transactions of minted items are checked again until they are final
+    Rule:
+        - Minted items whose transaction was reverted are minted again
+        - Items stay minted when their token is on chain
+        - Items left processing by a gone worker are checked on the following run, and minted when their transaction was accepted
+        - Items stalled in processing without a mint transaction go back to pending once old enough
+        - Items whose mint status cannot be checked stay pending until starknet answers
+
+    Scenario: Item of a reverted transaction is minted again
+        Given token "700" is queued
+        When the worker consumes the queue
+        Then token "700" should be "success" after 0 attempt(s)
+        Given starknet reverts its mints with "REVERTED"
+        When transactions are reconciled
+        Then 1 item(s) should have been requeued
+        And token "700" should be "pending" after 1 attempt(s)
+        And token "700" should have note "TransactionRejected"
+        Given starknet accepts transactions
+        When 60 seconds elapse
+        And the worker consumes the queue
+        Then token "700" should be "success" after 1 attempt(s)
+
+    Scenario: Item whose token is on chain stays minted
+        Given token "701" is queued
+        When the worker consumes the queue
+        Given starknet rejects transactions with "REVERTED"
+        When transactions are reconciled
+        Then 0 item(s) should have been requeued
+        And token "701" should be "success" after 0 attempt(s)
+
+    Scenario: Item left processing is checked once its worker is gone
+        Given token "702" is queued
+        Given starknet holds transactions
+        When the worker is stopped while waiting for its transaction
+        Then token "702" should be "processing" after 0 attempt(s)
+        Given starknet reverts its mints with "REVERTED"
+        When transactions are reconciled
+        Then 0 item(s) should have been requeued
+        And token "702" should be "processing" after 0 attempt(s)
+        When transactions are reconciled
+        Then 1 item(s) should have been requeued
+        And token "702" should be "pending" after 1 attempt(s)
+
+    Scenario: Item is not minted while starknet cannot tell whether it already is
+        Given token "703" is queued
+        Given starknet cannot be reached
+        When the worker consumes the queue
+        Then token "703" should be "pending" after 0 attempt(s)
+        And token "703" should have note "UncheckedMintStatus"
+        Given starknet can be reached again
+        When the worker consumes the queue
+        Then token "703" should be "success" after 0 attempt(s)
+
+    Scenario: Item stalled in processing is queued again once old enough
+        Given token "704" is queued
+        Given the worker crashed after marking token "704" as processing
+        When 60 seconds elapse
+        And items stalled in processing for 300 seconds are requeued
+        Then 0 item(s) should have been requeued
+        And token "704" should be "processing" after 0 attempt(s)
+        When 300 seconds elapse
+        And items stalled in processing for 300 seconds are requeued
+        Then 1 item(s) should have been requeued
+        And token "704" should be "pending" after 0 attempt(s)
+        And token "704" should have note "ProcessingStalled"
+        When the worker consumes the queue
+        Then token "704" should be "success" after 0 attempt(s)
+
+    Scenario: Item stalled with a mint transaction is left to reconciliation
+        Given token "705" is queued
+        Given starknet holds transactions
+        When the worker is stopped while waiting for its transaction
+        And 600 seconds elapse
+        And items stalled in processing for 300 seconds are requeued
+        Then 0 item(s) should have been requeued
+        And token "705" should be "processing" after 0 attempt(s)
+
+    Scenario: Item left processing with an accepted transaction is minted once its worker is gone
+        Given token "706" is queued
+        Given starknet holds transactions
+        When the worker is stopped while waiting for its transaction
+        Given starknet releases transactions
+        When transactions are reconciled
+        Then token "706" should be "processing" after 0 attempt(s)
+        When transactions are reconciled
+        Then 0 item(s) should have been requeued
+        And token "706" should be "success" after 0 attempt(s)
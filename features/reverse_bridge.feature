@@ -0,0 +1,55 @@
+Feature: Tokens are bridged back from Starknet to Juno
+    Rule:
+        - Customer returns tokens to the starknet admin account (or burns them) and asks for them back on Juno
+        - Every token has to be transferred by the starknet account in the given accepted transaction
+        - Tokens only go back to the keplr wallet linked to the starknet account
+        - The worker transfers tokens back from the Juno admin wallet, one transaction per project and wallet
+
+    Background:
+        Given keplr wallet k3plr-pk1 is linked to starknet account 0x5741
+
+    Scenario: Returned tokens are transferred back on Juno
+        Given starknet transaction 0xabc transferred tokens "1, 2" from 0x5741 to the admin
+        When k3plr-pk1 asks tokens "1, 2" back with starknet transaction 0xabc from 0x5741
+        Then tokens "1, 2" should be pending
+        When the worker transfers tokens back
+        Then tokens "1, 2" should be transferred back to k3plr-pk1 in a single transaction
+        And tokens "1, 2" should be success
+
+    Scenario: Burnt tokens are transferred back on Juno
+        Given starknet transaction 0xabc burnt token "3" of 0x5741
+        When k3plr-pk1 asks tokens "3" back with starknet transaction 0xabc from 0x5741
+        Then tokens "3" should be pending
+
+    Scenario: Token missing from the transaction is refused
+        Given starknet transaction 0xabc transferred tokens "1" from 0x5741 to the admin
+        When k3plr-pk1 asks tokens "1, 2" back with starknet transaction 0xabc from 0x5741
+        Then the request should fail with "token_not_returned"
+        And nothing should be queued
+
+    Scenario: Token sent by another account is refused
+        Given starknet transaction 0xabc transferred tokens "1" from 0x5799 to the admin
+        When k3plr-pk1 asks tokens "1" back with starknet transaction 0xabc from 0x5741
+        Then the request should fail with "token_not_returned"
+
+    Scenario: Transaction not accepted yet is refused
+        When k3plr-pk1 asks tokens "1" back with starknet transaction 0xdef from 0x5741
+        Then the request should fail with "transaction_not_accepted"
+
+    Scenario: Wallet linked to another starknet account is refused
+        Given starknet transaction 0xabc transferred tokens "1" from 0x5742 to the admin
+        When k3plr-pk1 asks tokens "1" back with starknet transaction 0xabc from 0x5742
+        Then the request should fail with "starknet_account_mismatch"
+
+    Scenario: Same transaction cannot be used twice
+        Given starknet transaction 0xabc transferred tokens "1" from 0x5741 to the admin
+        When k3plr-pk1 asks tokens "1" back with starknet transaction 0xabc from 0x5741
+        And k3plr-pk1 asks tokens "1" back with starknet transaction 0xabc from 0x5741
+        Then the request should fail with "already_requested"
+
+    Scenario: Rejected Juno transfer is reported on the queue item
+        Given starknet transaction 0xabc transferred tokens "1" from 0x5741 to the admin
+        Given the juno node rejects transfers
+        When k3plr-pk1 asks tokens "1" back with starknet transaction 0xabc from 0x5741
+        And the worker transfers tokens back
+        Then tokens "1" should be error
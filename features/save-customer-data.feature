@@ -6,3 +6,32 @@ Feature: Save customer data when token transfer happen in frontend
             | k3plr-id        | proj3ct1d  | [344, 345, 346] |
         When I execute the request
         Then data should have been persisted to database
+
+    Scenario: Customer transfered more tokens frontend side
+        Given a request
+            | keplr-wallet-id | project_id | tokens     |
+            | k3plr-id2       | proj3ct1d  | [344, 345] |
+        When I execute the request
+        Given a request
+            | keplr-wallet-id | project_id | tokens          |
+            | k3plr-id2       | proj3ct1d  | [345, 346, 346] |
+        When I execute the request
+        Then 1 token should have been added, 2 duplicates and 3 registered
+
+    Scenario: Sender agreeing to migrate on behalf of the customer is authorized
+        When k3plr-id3 authorizes sender juno-multisig-3 on project proj3ct1d with sender signature aValidSignedHash
+        Then juno-multisig-3 should be an authorized sender of k3plr-id3 on project proj3ct1d
+
+    Scenario: Third party sender that did not sign is refused
+        When k3plr-id4 authorizes sender juno-victim-dao on project proj3ct1d with sender signature none
+        Then the authorization should be refused with code "missing_sender_sign"
+        And juno-victim-dao should not be an authorized sender of k3plr-id4 on project proj3ct1d
+
+    Scenario: Sender signature that does not verify is refused
+        When k3plr-id5 authorizes sender juno-multisig-5 on project proj3ct1d with sender signature anInvalidHash
+        Then the authorization should be refused with code "invalid_sender_sign"
+        And juno-multisig-5 should not be an authorized sender of k3plr-id5 on project proj3ct1d
+
+    Scenario: Operator approves a contract sender which cannot sign
+        When operator "alice" approves sender juno-dao-6 for k3plr-id6 on project proj3ct1d
+        Then juno-dao-6 should be an authorized sender of k3plr-id6 on project proj3ct1d
@@ -0,0 +1,21 @@
+Feature: Api and worker refuse to start on a database schema of another release
+    Rule:
+        - Migrations recorded in `schema_migrations` have to be exactly the ones the binary expects
+        - A schema missing expected migrations is older than the binary
+        - A schema with migrations unknown to the binary was migrated by a newer release
+
+    Scenario: Schema migrated by the same release is accepted
+        Given every expected migration is applied
+        When the schema version is checked
+        Then the schema should be accepted
+
+    Scenario: Schema missing the latest migration is refused
+        Given every expected migration but the latest one is applied
+        When the schema version is checked
+        Then the schema should be refused as older, missing the latest migration
+
+    Scenario: Schema migrated by a newer release is refused
+        Given every expected migration is applied
+        And migration "add_future_table" is applied
+        When the schema version is checked
+        Then the schema should be refused as newer because of "add_future_table"
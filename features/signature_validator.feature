@@ -0,0 +1,45 @@
+Feature: Signature validators are selected from configuration
+    Rule:
+        - keplr-adr36 only accepts signatures made by the customer keplr wallet
+        - evm-eip191 only accepts personal_sign signatures of an ethermint key matching the customer wallet
+        - test-permissive accepts any signature
+        - Several validators accept a signature as soon as one of them does
+        - Unknown or missing validators are refused at startup
+
+    Scenario: Strict validator rejects a forged signature
+        Given signature validators "keplr-adr36"
+        When a customer submits a forged signature
+        Then the signature should be rejected
+
+    Scenario: Permissive validator accepts a forged signature
+        Given signature validators "test-permissive"
+        When a customer submits a forged signature
+        Then the signature should be accepted
+
+    Scenario: Signature is accepted when one of the validators accepts it
+        Given signature validators "keplr-adr36,test-permissive"
+        When a customer submits a forged signature
+        Then the signature should be accepted
+
+    Scenario: Metamask signature is accepted by the evm validator
+        Given signature validators "keplr-adr36,evm-eip191"
+        When a customer submits a metamask signature
+        Then the metamask signature should be accepted
+
+    Scenario: Metamask signature is rejected for another wallet
+        Given signature validators "evm-eip191"
+        When a customer submits a metamask signature for wallet "juno1customer0000000000000000000000000000"
+        Then the signature should be rejected
+
+    Scenario: Evm validator rejects keplr keys
+        Given signature validators "evm-eip191"
+        When a customer submits a forged signature
+        Then the signature should be rejected
+
+    Scenario: Unknown validator is refused
+        Given signature validators "keplr-adr36,ethereum"
+        Then configuration should fail because validator "ethereum" is unknown
+
+    Scenario: At least one validator is required
+        Given signature validators ""
+        Then configuration should fail because no validator is configured
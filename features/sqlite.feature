@@ -0,0 +1,43 @@
+Feature: SQLite backend keeps customer data and the migration queue for local development
+
+    Scenario: Customer keys are replaced when saved again
+        Given customer "k3plr-pk1" saved tokens "1,2" of project "juno1project"
+        When customer "k3plr-pk1" saves tokens "2,3" of project "juno1project"
+        Then customer "k3plr-pk1" should have tokens "2,3" of project "juno1project"
+
+    Scenario: Minted items leave the batch with their history recorded
+        Given tokens "500,501" of "k3plr-pk1" are queued
+        Then the batch should contain 2 items
+        When token "500" is minted with transaction "0xabc"
+        Then token "500" should be "success" with transaction hash "0xabc"
+        And token "500" history should be "pending,success"
+        And the batch should contain 1 items
+
+    Scenario: Retried items wait for their delay
+        Given tokens "502" of "k3plr-pk1" are queued
+        When token "502" is retried in 3600 seconds
+        Then the batch should contain 0 items
+        And token "502" should be "pending" after 1 attempts
+
+    Scenario: Dead letters are requeued with a fresh attempt budget
+        Given tokens "503" of "k3plr-pk1" are queued
+        When token "503" is dead lettered
+        And token "503" is requeued
+        Then token "503" should be "pending" after 0 attempts
+        And the batch should contain 1 items
+
+    Scenario: Queue items are listed page by page
+        Given tokens "504,505,506" of "k3plr-pk1" are queued
+        Then listing the queue by pages of 2 should return 3 items
+
+    Scenario: Items of projects out of their migration window are left out of batches
+        Given tokens "507,508" of "k3plr-pk1" are queued
+        And tokens "509" of "k3plr-pk1" are queued on project 0x0c4b
+        Then the batch should contain 3 items
+        And the batch should contain 2 items once project 0x0c4b is closed
+
+    Scenario: Tokens queued by several wallets before the active token index keep their oldest item
+        Given a database created before the active token index where "k3plr-pk1" then "k3plr-pk2" queued token "600"
+        When the database is opened
+        Then token "600" of "k3plr-pk1" should be "pending"
+        And token "600" of "k3plr-pk2" should be "error" with note "DuplicateQueueItem"
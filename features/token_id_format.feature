@@ -0,0 +1,73 @@
+Feature: Token ids of each project are encoded to felts in their own format
+    Rule:
+        - Token ids are decimal unless their project says otherwise
+        - Decimal and hex ids decode back to the token id they were encoded from
+        - String ids are hashed, so any printable id can be minted
+        - Mint calldata carries the encoded token id
+        - Mint calldata follows the template of its project, u256 fields being split in low then high felts
+        - Templates without recipient, with unknown fields or without project are refused
+
+    Background:
+        Given project "0x0d1e" token ids are formatted as "hex"
+        And project "0x0f0e" token ids are formatted as "string"
+
+    Scenario Outline: Token ids survive a round trip through their felt
+        When token "<token_id>" of project "<project>" is encoded
+        Then the token felt should be "<felt>"
+        And the token felt should decode to "<token_id>"
+
+        Examples:
+            | project | token_id              | felt                  |
+            | 0x0bad  | 42                    | 0x2a                  |
+            | 0x0bad  | 340282366920938463463 | 0x12725dd1d243aba0e7  |
+            | 0x0d1e  | 0x2a                  | 0x2a                  |
+            | 0x0d1e  | 0xabcdef0123          | 0xabcdef0123          |
+
+    Scenario: String token ids are hashed
+        When token "forest-001" of project "0x0f0e" is encoded
+        Then the token felt should be the starknet keccak of "forest-001"
+        And the token felt should not decode
+        When token "forest-002" of project "0x0f0e" is encoded
+        Then the token felt should be the starknet keccak of "forest-002"
+
+    Scenario: Non numeric token id is refused by decimal projects
+        When token "forest-001" of project "0x0bad" is encoded
+        Then the token id should be invalid
+
+    Scenario: Mint calldata carries the encoded token id
+        When mint calldata of token "forest-001" of project "0x0f0e" is encoded for "0x5741"
+        Then the calldata should hold the recipient then the token felt as a Uint256
+
+    Scenario: Unknown token id format is refused
+        Then token id formats "0x0d1e=base58" should be refused
+
+    Scenario Outline: Mint calldata follows its template
+        When mint calldata of token "<token_id>" of project "<project>" is encoded for "0x5741" as "<template>"
+        Then the calldata should be "<calldata>"
+
+        Examples:
+            | project | token_id                            | template                    | calldata                |
+            | 0x0bad  | 42                                  | to token_id:u256            | 0x5741 0x2a 0x0         |
+            | 0x0bad  | 42                                  | to token_id                 | 0x5741 0x2a             |
+            | 0x0bad  | 42                                  | to token_id:u256 value:u256 | 0x5741 0x2a 0x0 0x1 0x0 |
+            | 0x0bad  | 42                                  | 0x1 to token_id:felt value  | 0x1 0x5741 0x2a 0x1     |
+            | 0x0bad  | 42                                  | to 7 token_id               | 0x5741 0x7 0x2a         |
+            | 0x0d1e  | 0x100000000000000000000000000000002 | to token_id:u256            | 0x5741 0x2 0x1          |
+
+    Scenario Outline: Mint calldata follows the template of its project
+        Given project "0x0c4a" mints with calldata "to token_id:felt"
+        When mint calldata of token "42" of project "<project>" is encoded for "0x5741" with its template
+        Then the calldata should be "<calldata>"
+
+        Examples:
+            | project | calldata        |
+            | 0x0c4a  | 0x5741 0x2a     |
+            | 0x00c4a | 0x5741 0x2a     |
+            | 0x0bad  | 0x5741 0x2a 0x0 |
+
+    Scenario: Calldata template with an unknown field is refused
+        Then calldata template "to token_id:bytes" should be refused
+
+    Scenario: Calldata templates without recipient or project are refused
+        Then calldata templates "0x0c4a=token_id:u256" should be refused
+        And calldata templates "to token_id:u256" should be refused
@@ -0,0 +1,54 @@
+Feature: Juno contract transactions are cached
+    Rule:
+        - Checking several tokens of a contract fetches its transactions once
+        - Listings are fetched again once the cache ttl elapsed
+        - Failed fetches are not cached
+
+    Background:
+        Given the following juno transactions
+            """
+            [
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "1" } }
+                },
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "projectId",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "2" } }
+                },
+                {
+                    "sender": "k3plr-pk1",
+                    "contract": "otherProject",
+                    "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": "1" } }
+                }
+            ]
+            """
+        Given transactions are cached for 10 seconds
+
+    Scenario: Tokens of a contract share a single fetch
+        When I fetch the transactions of tokens "1, 2, 3" on contract "projectId"
+        Then 1 transaction(s) should have been found for token "1"
+        And 1 transaction(s) should have been found for token "2"
+        And 0 transaction(s) should have been found for token "3"
+        And the juno node should have been called 1 time(s)
+
+    Scenario: Contracts are cached separately
+        When I fetch the transactions of tokens "1" on contract "projectId"
+        And I fetch the transactions of tokens "1" on contract "otherProject"
+        Then the juno node should have been called 2 time(s)
+
+    Scenario: Listing is fetched again once expired
+        When I fetch the transactions of tokens "1" on contract "projectId"
+        And 11 seconds elapse
+        And I fetch the transactions of tokens "2" on contract "projectId"
+        Then the juno node should have been called 2 time(s)
+
+    Scenario: Failed fetches are not cached
+        Given the juno node fails
+        When I fetch the transactions of tokens "1" on contract "projectId"
+        Given the juno node recovers
+        When I fetch the transactions of tokens "1" on contract "projectId"
+        Then 1 transaction(s) should have been found for token "1"
+        And the juno node should have been called 2 time(s)
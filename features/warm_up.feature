@@ -0,0 +1,30 @@
+Feature: Dependencies are warmed up before the api takes requests
+    Rule:
+        - Every dependency is warmed up once, all at the same time
+        - A failing dependency does not prevent the others from being warmed up
+        - A dependency slower than the timeout is given up so the api still starts
+
+    Scenario: Every dependency is warmed up
+        Given a "postgres" dependency answering
+        And a "juno lcd" dependency answering
+        And a "starknet gateway" dependency answering
+        When the api warms up with a timeout of 500 milliseconds
+        Then "postgres" should be warmed up
+        And "juno lcd" should be warmed up
+        And "starknet gateway" should be warmed up
+        And every dependency should have been called once
+
+    Scenario: A failing dependency does not stop the warm-up
+        Given a "postgres" dependency answering
+        And a "juno lcd" dependency failing
+        When the api warms up with a timeout of 500 milliseconds
+        Then "postgres" should be warmed up
+        And "juno lcd" should have failed
+
+    Scenario: A hanging dependency is given up after the timeout
+        Given a "postgres" dependency answering
+        And a "starknet gateway" dependency hanging
+        When the api warms up with a timeout of 100 milliseconds
+        Then "postgres" should be warmed up
+        And "starknet gateway" should have timed out
+        And the warm-up should have taken less than 1000 milliseconds
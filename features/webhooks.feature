@@ -0,0 +1,117 @@
+Feature: Subscribers are notified when queue items are minted or fail
+    Rule:
+        - Customers register a callback url signed with their keplr wallet
+        - Operators register callback urls notified about every wallet
+        - The worker posts a signed payload when an item reaches success or error
+        - Failed deliveries are retried with backoff and every attempt is logged
+        - Subscriptions filter the events they are notified about, item_succeeded and item_failed by default
+        - Registering a callback again gives back the existing subscription and its secret
+        - Customers list, delete and test their webhooks
+
+    Scenario: Customer is notified once its token is minted
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        Given token "700" of keplr wallet k3plr-pk1 is queued
+        When the worker consumes the queue
+        And the worker delivers webhooks
+        Then "https://customer.test/hook" should have received "item_succeeded" for token "700"
+
+    Scenario: Registration with an invalid signature is refused
+        When keplr wallet k3plr-pk1 registers webhook "https://customer.test/hook" with signature "anInvalidHash"
+        Then the registration should be refused because of "invalid_sign"
+
+    Scenario: Registration with a relative callback url is refused
+        When keplr wallet k3plr-pk1 registers webhook "/hook" with signature "aValidSignedHash"
+        Then the registration should be refused because of "invalid_callback_url"
+
+    Scenario: Customers are not notified about other wallets
+        Given keplr wallet k3plr-pk2 registered webhook "https://other.test/hook"
+        Given token "710" of keplr wallet k3plr-pk1 is queued
+        When the worker consumes the queue
+        And the worker delivers webhooks
+        Then "https://other.test/hook" should have received nothing
+
+    Scenario: Operator webhook is notified about cancelled items
+        Given an operator registered webhook "https://frontend.test/hook"
+        Given token "720" of keplr wallet k3plr-pk3 is queued
+        When the operator cancels token "720"
+        And the worker delivers webhooks
+        Then "https://frontend.test/hook" should have received "item_failed" for token "720"
+
+    Scenario: Failed delivery is retried with backoff
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        Given token "730" of keplr wallet k3plr-pk1 is queued
+        Given the callback fails
+        When the worker consumes the queue
+        And the worker delivers webhooks
+        And the worker delivers webhooks
+        Then the delivery should be "pending" after 1 attempt(s)
+        When 30 seconds elapse
+        And the worker delivers webhooks
+        Then the delivery should be "pending" after 2 attempt(s)
+        Given the callback recovers
+        When 60 seconds elapse
+        And the worker delivers webhooks
+        Then the delivery should be "delivered" after 3 attempt(s)
+        And 3 delivery attempts should have been logged
+        And "https://customer.test/hook" should have received "item_succeeded" for token "730"
+
+    Scenario: Delivery is given up after its last attempt
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        Given token "740" of keplr wallet k3plr-pk1 is queued
+        Given the callback fails
+        When the worker consumes the queue
+        And the worker delivers webhooks 3 times
+        Then the delivery should be "failed" after 3 attempt(s)
+
+    Scenario: Registering the same callback twice keeps one subscription
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        When keplr wallet k3plr-pk1 registers webhook "https://customer.test/hook" for "item_failed"
+        Then both registrations of "https://customer.test/hook" should have the same id and secret
+        And keplr wallet k3plr-pk1 should have 1 webhook without their secret
+
+    Scenario: Subscription is only notified about the events it filters
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/submitted" for "batch_submitted"
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/minted" for "item_succeeded"
+        Given token "750" of keplr wallet k3plr-pk1 is queued
+        When the worker consumes the queue
+        And the worker delivers webhooks
+        Then "https://customer.test/submitted" should only have received "batch_submitted"
+        And "https://customer.test/minted" should only have received "item_succeeded"
+
+    Scenario: Registration filtering on test payloads is refused
+        When keplr wallet k3plr-pk1 registers webhook "https://customer.test/hook" for "test"
+        Then the registration should be refused because of "invalid_event_filter"
+
+    Scenario: Deliveries are signed with the secret of their subscription
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        Given token "760" of keplr wallet k3plr-pk1 is queued
+        When the worker consumes the queue
+        Then deliveries to "https://customer.test/hook" should be signed with its own secret
+
+    Scenario: Deleted webhook is not notified anymore
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        When keplr wallet k3plr-pk1 deletes webhook "https://customer.test/hook"
+        Given token "770" of keplr wallet k3plr-pk1 is queued
+        When the worker consumes the queue
+        And the worker delivers webhooks
+        Then "https://customer.test/hook" should have received nothing
+        And keplr wallet k3plr-pk1 should have 0 webhooks without their secret
+
+    Scenario: Customers cannot delete webhooks of other wallets
+        Given keplr wallet k3plr-pk2 registered webhook "https://other.test/hook"
+        When keplr wallet k3plr-pk1 deletes webhook "https://other.test/hook"
+        Then the deletion should be refused because the webhook is not found
+        And keplr wallet k3plr-pk2 should have 1 webhook without their secret
+
+    Scenario: Test delivery reaches the callback right away
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        When keplr wallet k3plr-pk1 tests webhook "https://customer.test/hook"
+        Then the test delivery should have been delivered
+        And "https://customer.test/hook" should have received "test" for token "0"
+        And 1 delivery attempts should have been logged
+
+    Scenario: Test delivery reports a failing callback
+        Given keplr wallet k3plr-pk1 registered webhook "https://customer.test/hook"
+        Given the callback fails
+        When keplr wallet k3plr-pk1 tests webhook "https://customer.test/hook"
+        Then the test delivery should have failed with status 503
@@ -0,0 +1,31 @@
+Feature: Worker stops waiting on starknet as soon as it is asked to shut down
+
+    Scenario: Worker is cancelled while a batch transaction is pending
+        Given token "300" of "k3plr-pk1" is queued for "0x5741" on project "0x0c4a"
+        And starknet transactions stay pending
+        When the worker is cancelled 100 milliseconds into consuming the queue
+        Then the worker should have stopped within 1 second
+        And token "300" should still be "processing" with its transaction hash recorded
+
+    Scenario: Worker is cancelled while recovering a pending transaction
+        Given token "301" of "k3plr-pk1" is queued for "0x5741" on project "0x0c4a"
+        And starknet transactions stay pending
+        And a previous worker stopped after sending transaction "0x7e57" for the queue
+        When the worker is cancelled 100 milliseconds into recovering processing items
+        Then the worker should have stopped within 1 second
+        And token "301" should still be "processing" with its transaction hash recorded
+
+    Scenario: Worker finishes its in-flight batch within the shutdown grace period
+        Given token "302" of "k3plr-pk1" is queued for "0x5741" on project "0x0c4a"
+        And starknet transactions stay pending
+        And starknet transactions are accepted after 300 milliseconds
+        When the worker is asked to shut down 100 milliseconds into consuming the queue with a grace period of 2 seconds
+        Then the worker should have finished its batch
+        And token "302" should be "success"
+
+    Scenario: Worker interrupts its in-flight batch once the shutdown grace period is over
+        Given token "303" of "k3plr-pk1" is queued for "0x5741" on project "0x0c4a"
+        And starknet transactions stay pending
+        When the worker is asked to shut down 100 milliseconds into consuming the queue with a grace period of 0 seconds
+        Then the worker should have stopped within 1 second
+        And token "303" should still be "processing" with its transaction hash recorded
@@ -0,0 +1,38 @@
+Feature: Workers only mint the queue items they hold
+    Rule:
+        - Items are claimed by the worker right before being sent
+        - Items requeued meanwhile and claimed by another worker are left out of the transaction
+        - A restarting worker recovers its own processing items, the ones stalled past the stale age, and the ones any worker sent a transaction for
+
+    Scenario: Items claimed by another worker while a chunk was pending are left out
+        Given tokens "400,401" of "k3plr-pk1" are queued for "0x5741" on project "0x0c4a"
+        And starknet transactions hold a single mint
+        And starknet transactions stay pending
+        And worker "worker-b" claims the pending items after 100 milliseconds
+        And starknet transactions are accepted after 200 milliseconds
+        When worker "worker-a" consumes the queue
+        Then 1 token should have been minted
+        And 1 token should be "success"
+        And 1 token should still be "processing" without transaction hash
+
+    Scenario: Restarting worker leaves the items another worker is processing
+        Given tokens "410" of "k3plr-pk1" are queued for "0x5741" on project "0x0c4a"
+        And worker "worker-b" claimed the pending items
+        And tokens "411" of "k3plr-pk1" are queued for "0x5741" on project "0x0c4a"
+        And worker "worker-a" claimed the pending items
+        When worker "worker-a" recovers the items left processing
+        Then token "411" should be "pending"
+        And token "410" should be "processing"
+
+    Scenario: Restarting worker recovers the items another worker stalled on
+        Given tokens "412" of "k3plr-pk1" are queued for "0x5741" on project "0x0c4a"
+        And worker "worker-b" claimed the pending items
+        And 1000 seconds went by
+        When worker "worker-a" recovers the items left processing for over 900 seconds
+        Then token "412" should be "pending"
+
+    Scenario: Worker restarted under another id finalizes the items it had sent
+        Given tokens "413" of "k3plr-pk1" are queued for "0x5741" on project "0x0c4a"
+        And worker "worker-a" sent transaction "0x413" for the pending items
+        When worker "worker-c" recovers the items left processing
+        Then token "413" should be "success"
@@ -0,0 +1,127 @@
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        clock::SystemClock,
+        queue_snapshot::{
+            restore_queue_snapshot, take_queue_snapshot, QueueSnapshot, QueueSnapshotRepository,
+        },
+    },
+    infrastructure::{
+        logger::configure_logger,
+        migrations::MigrationMode,
+        postgresql::{get_connection, PostgresQueueSnapshotRepository},
+    },
+};
+use clap::{Parser, Subcommand};
+use log::{error, info};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::exit,
+    sync::Arc,
+};
+
+/// Operator commands run against the bridge database.
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct AdminArgs {
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: String,
+    /// Schema migrations run before the command : run, baseline or off
+    #[arg(long, env = "DATABASE_MIGRATIONS", default_value = "run")]
+    database_migrations: String,
+
+    #[command(subcommand)]
+    command: AdminCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum AdminCommand {
+    /// Migration queue maintenance
+    #[command(subcommand)]
+    Queue(QueueCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum QueueCommand {
+    /// Write every queue item, its history and batches to a file
+    Snapshot {
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Load a snapshot file into a database holding none of its items
+    Restore {
+        #[arg(long = "in")]
+        input: PathBuf,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    configure_logger();
+
+    let args = AdminArgs::parse();
+    let Some(migrations) = MigrationMode::parse(&args.database_migrations) else {
+        error!(
+            "Unsupported database migrations mode {}",
+            &args.database_migrations
+        );
+        exit(1);
+    };
+    let connection = match get_connection(&args.database_url, migrations).await {
+        Ok(c) => Arc::new(c),
+        Err(e) => panic!("Failed to connect to database error : {}", e),
+    };
+    let repository: Arc<dyn QueueSnapshotRepository> = Arc::new(
+        PostgresQueueSnapshotRepository::new(connection, "admin-cli"),
+    );
+
+    let result = match args.command {
+        AdminCommand::Queue(QueueCommand::Snapshot { out }) => {
+            snapshot_queue(repository, &out).await
+        }
+        AdminCommand::Queue(QueueCommand::Restore { input }) => {
+            restore_queue(repository, &input).await
+        }
+    };
+
+    if let Err(e) = result {
+        error!("{}", e);
+        exit(1);
+    }
+}
+
+async fn snapshot_queue(
+    repository: Arc<dyn QueueSnapshotRepository>,
+    out: &Path,
+) -> Result<(), String> {
+    let snapshot = take_queue_snapshot(repository, Arc::new(SystemClock))
+        .await
+        .map_err(|e| format!("Failed to snapshot queue {:?}", e))?;
+    let content = serde_json::to_vec_pretty(&snapshot)
+        .map_err(|e| format!("Failed to serialize queue snapshot {}", e))?;
+    fs::write(out, content).map_err(|e| format!("Failed to write {} : {}", out.display(), e))?;
+
+    info!(
+        "Wrote {} queue items to {}",
+        snapshot.entries.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+async fn restore_queue(
+    repository: Arc<dyn QueueSnapshotRepository>,
+    input: &Path,
+) -> Result<(), String> {
+    let content =
+        fs::read(input).map_err(|e| format!("Failed to read {} : {}", input.display(), e))?;
+    let snapshot: QueueSnapshot = serde_json::from_slice(&content)
+        .map_err(|e| format!("Invalid queue snapshot {} : {}", input.display(), e))?;
+
+    let restored = restore_queue_snapshot(&snapshot, repository)
+        .await
+        .map_err(|e| format!("Failed to restore queue {:?}", e))?;
+
+    info!("Restored {} queue items from {}", restored, input.display());
+    Ok(())
+}
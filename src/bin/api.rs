@@ -1,304 +1,547 @@
-use actix_cors::Cors;
-use actix_web::{get, http, post, web, App, HttpServer, Responder};
+use actix_web::{
+    get, http, middleware::from_fn, post, web, App, HttpRequest, HttpResponse, HttpServer,
+    Responder,
+};
 use bridge_juno_to_starknet_backend::{
     domain::{
-        bridge::{
-            handle_bridge_request, BridgeError, BridgeRequest, SignedHashValidator,
-            SignedHashValidatorError,
+        breakglass::{
+            handle_breakglass_confirmation, handle_breakglass_request, BreakglassError,
+            BreakglassMintRequest,
         },
+        bridge::check_codes_meta,
+        error_catalog::{error_catalog, CatalogedError},
+        export::handle_queue_export,
+        ids::{JunoAddress, ProjectId, QueueItemId, StarknetAddress},
+        queue_admin::handle_retry_queue_item,
+        report::{report_key, ReportError, ReportFormat},
         save_customer_data::{
-            handle_save_customer_data, SaveCustomerDataError, SaveCustomerDataRequest,
+            handle_approve_sender, handle_authorize_sender, ApproveSenderRequest,
+            AuthorizeSenderRequest,
         },
+        stats::{handle_public_stats, handle_stats_request, StatsError, StatsKind, StatsRequest},
+        support_bundle::{handle_support_bundle, SupportBundleRequest},
+        wallet_link::{handle_link_wallet, LinkWalletRequest, WalletLinkError},
+        warm_up::warm_up,
     },
     infrastructure::{
-        app::{configure_application, Args, Config},
-        juno::JunoLcd,
+        app::{cancel_on_shutdown_signal, configure_application, Args, Config},
+        http::{
+            admin::{
+                authenticated_operator, batch_analytics, bridge_requests, cancel_queue_item,
+                database_maintenance, dead_letters, inspect_queue_item, page_request,
+                queue_admin_error_response, queue_browser, queue_item_history,
+                register_operator_webhook, requeue_dead_letter, requeue_queue_item,
+                requeue_stale_processing_items, unauthorized, PageQuery,
+            },
+            csv::{accepts_csv, stats_csv},
+            handlers::{
+                bridge, challenge, cors, delete_webhook, get_customer_migration_state,
+                get_customer_proof_bundle, get_eligible_tokens, get_reverse_migration_state,
+                get_transaction_status, get_wallet_migrations, health, json_config, list_webhooks,
+                record_event, register_webhook, reverse_bridge, save_customer_tokens, test_webhook,
+            },
+            metrics::{record_metrics, scrape_metrics},
+            openapi::openapi_spec,
+            rate_limit::rate_limit,
+            request_id::request_id,
+            response::{self, ApiResponse},
+            schema_version::schema_version,
+        },
         logger::configure_logger,
-        starknet::OnChainStartknetManager,
     },
 };
 use clap::Parser;
-use futures::executor::block_on;
 use log::{error, info};
-use serde_derive::Serialize;
-use std::sync::Arc;
-
-#[derive(Serialize)]
-struct ApiResponse<T> {
-    error: Option<String>,
-    message: String,
-    code: u32,
-    body: Option<T>,
+use serde_derive::Deserialize;
+use uuid::Uuid;
+
+#[get("/v1/meta/check-codes")]
+async fn check_codes() -> impl Responder {
+    info!("GET - /v1/meta/check-codes");
+    response::ok(check_codes_meta())
 }
 
-impl<T> ApiResponse<T> {
-    fn create(error: Option<&str>, message: &str, code: u32, body: Option<T>) -> Self {
-        let err = match error {
-            Some(e) => Some(e.to_string()),
-            None => None,
-        };
-        Self {
-            error: err,
-            message: message.into(),
-            code,
-            body,
+#[get("/v1/meta/errors")]
+async fn errors_catalog() -> impl Responder {
+    info!("GET - /v1/meta/errors");
+    response::ok(error_catalog())
+}
+
+#[post("/customer/authorized-senders")]
+async fn authorize_sender(
+    request: web::Json<AuthorizeSenderRequest>,
+    config: web::Data<Config>,
+) -> impl Responder {
+    info!(
+        "POST - /customer/authorized-senders - {} - {} - {}",
+        &request.keplr_wallet_pubkey, &request.project_id, &request.sender
+    );
+
+    match handle_authorize_sender(
+        &request,
+        config.signed_hash_validator.clone(),
+        config.data_repository.clone(),
+    )
+    .await
+    {
+        Ok(_) => response::with_status(http::StatusCode::CREATED, ()),
+        Err(e) => response::catalog_error(&e.catalog_entry()),
+    }
+}
+
+#[post("/authorized-senders")]
+async fn approve_sender(
+    http_request: HttpRequest,
+    request: web::Json<ApproveSenderRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!(
+        "POST - /admin/authorized-senders - {} - {} - {} - {}",
+        &operator.name, &request.keplr_wallet_pubkey, &request.project_id, &request.sender
+    );
+
+    match handle_approve_sender(&request, &operator, data.data_repository.clone()).await {
+        Ok(_) => response::with_status(http::StatusCode::CREATED, ()),
+        Err(e) => response::catalog_error(&e.catalog_entry()),
+    }
+}
+
+async fn stats_response(
+    http_request: &HttpRequest,
+    kind: StatsKind,
+    query: &StatsRequest,
+    data: &Config,
+) -> HttpResponse {
+    match handle_stats_request(kind, query, data.stats_repository.clone()).await {
+        Ok(stats) if accepts_csv(http_request) => stats_csv(&stats),
+        Ok(stats) => response::ok(stats),
+        Err(StatsError::InvalidRange) => {
+            response::bad_request("Parameter 'from' must be lower than 'to'")
+        }
+        Err(StatsError::FailedToFetchStats) => {
+            response::internal_server_error("Failed to compute stats")
         }
     }
+}
+
+#[post("/wallet/link")]
+async fn link_wallet(req: web::Json<LinkWalletRequest>, data: web::Data<Config>) -> impl Responder {
+    info!(
+        "POST - /wallet/link - {} - {}",
+        &req.keplr_wallet_pubkey, &req.starknet_account_addr
+    );
+
+    match handle_link_wallet(
+        &req,
+        data.signed_hash_validator.clone(),
+        &data.challenges,
+        data.wallet_link_repository.clone(),
+    )
+    .await
+    {
+        Ok(link) => response::ok(link),
+        Err(WalletLinkError::InvalidSign) => response::error(
+            http::StatusCode::BAD_REQUEST,
+            "invalid_sign",
+            "Invalid sign",
+        ),
+        Err(WalletLinkError::InvalidChallenge) => response::error(
+            http::StatusCode::BAD_REQUEST,
+            "invalid_challenge",
+            "Challenge nonce is missing, unknown or already used",
+        ),
+        Err(WalletLinkError::ChallengeExpired) => response::error(
+            http::StatusCode::BAD_REQUEST,
+            "challenge_expired",
+            "Challenge nonce has expired, please request a new one",
+        ),
+        Err(_e) => response::internal_server_error("Error while linking wallets"),
+    }
+}
+
+#[get("/wallet/link/{keplr_wallet_pubkey}")]
+async fn get_wallet_link(path: web::Path<JunoAddress>, data: web::Data<Config>) -> impl Responder {
+    let keplr_wallet_pubkey = path.into_inner();
+    info!("GET - /wallet/link - {}", &keplr_wallet_pubkey);
 
-    fn bad_request(message: &str) -> Self {
-        ApiResponse::create(Some("Bad Request"), message, 400, None)
+    match data
+        .wallet_link_repository
+        .get_link(&keplr_wallet_pubkey)
+        .await
+    {
+        Ok(link) => response::ok(link),
+        Err(_e) => response::not_found("No starknet account linked to this wallet"),
     }
 }
 
-struct KeplrSignatureVeirfier {}
-impl SignedHashValidator for KeplrSignatureVeirfier {
-    fn verify(
-        &self,
-        signed_hash: &bridge_juno_to_starknet_backend::domain::bridge::SignedHash,
-        starknet_account_addrr: &str,
-        keplr_wallet_pubkey: &str,
-    ) -> Result<String, bridge_juno_to_starknet_backend::domain::bridge::SignedHashValidatorError>
+#[get("/stats/queue")]
+async fn queue_stats(
+    http_request: HttpRequest,
+    query: web::Query<StatsRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    info!("GET - /stats/queue - {} - {}", query.from, query.to);
+    stats_response(&http_request, StatsKind::Queue, &query, &data).await
+}
+
+#[get("/stats/throughput")]
+async fn throughput_stats(
+    http_request: HttpRequest,
+    query: web::Query<StatsRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    info!("GET - /stats/throughput - {} - {}", query.from, query.to);
+    stats_response(&http_request, StatsKind::Throughput, &query, &data).await
+}
+
+#[get("/stats/project")]
+async fn project_stats(
+    http_request: HttpRequest,
+    query: web::Query<StatsRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    info!("GET - /stats/project - {} - {}", query.from, query.to);
+    stats_response(&http_request, StatsKind::Project, &query, &data).await
+}
+
+#[get("/public/stats")]
+async fn public_stats(data: web::Data<Config>) -> impl Responder {
+    info!("GET - /public/stats");
+    match handle_public_stats(
+        data.stats_repository.clone(),
+        data.public_stats_cache.clone(),
+    )
+    .await
     {
-        let pubkey = signed_hash.pub_key.key_value.to_string();
-        let signature = verify_keplr_sign::Signature {
-            pub_key: verify_keplr_sign::PublicKey {
-                sig_type: signed_hash.pub_key.key_type.to_string(),
-                sig_value: pubkey.to_string(),
-            },
-            signature: signed_hash.signature.to_string(),
-        };
-
-        let is_signature_ok = verify_keplr_sign::verify_arbitrary(
-            keplr_wallet_pubkey,
-            &pubkey,
-            starknet_account_addrr.as_bytes(),
-            &signature,
-        );
-
-        if !is_signature_ok {
-            return Err(SignedHashValidatorError::FailedToVerifyHash);
-        }
+        Ok(stats) => HttpResponse::Ok()
+            .insert_header((
+                http::header::CACHE_CONTROL,
+                format!(
+                    "public, max-age={}",
+                    data.public_stats_cache.ttl().as_secs()
+                ),
+            ))
+            .json(ApiResponse::Ok(stats)),
+        Err(_e) => response::internal_server_error("Failed to compute stats"),
+    }
+}
 
-        Ok(signature.signature)
+fn breakglass_error_response(error: BreakglassError) -> HttpResponse {
+    match error {
+        BreakglassError::InvalidRequest(message) => response::bad_request(&message),
+        BreakglassError::NotFound => response::not_found("Breakglass mint request not found"),
+        BreakglassError::SameOperator => response::error(
+            http::StatusCode::FORBIDDEN,
+            "same_operator",
+            "Mint request has to be confirmed by another operator",
+        ),
+        BreakglassError::Expired => response::error(
+            http::StatusCode::GONE,
+            "confirmation_expired",
+            "Confirmation window has expired",
+        ),
+        BreakglassError::AlreadyHandled => response::error(
+            http::StatusCode::CONFLICT,
+            "already_handled",
+            "Mint request has already been handled",
+        ),
+        BreakglassError::MintFailed | BreakglassError::PersistenceIssue => {
+            response::internal_server_error("Error while handling breakglass mint")
+        }
     }
 }
 
-#[post("/bridge")]
-async fn bridge(req: web::Json<BridgeRequest>, data: web::Data<Config>) -> impl Responder {
+#[post("/mint")]
+async fn breakglass_mint(
+    http_request: HttpRequest,
+    req: web::Json<BreakglassMintRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
     info!(
-        "POST - /bridge - {} - {:#?}",
-        &req.keplr_wallet_pubkey, &req.tokens_id
+        "POST - /admin/mint - {} - {} - {:#?}",
+        &operator.name, &req.starknet_project_addr, &req.token_ids
     );
 
-    let provider = &data.clone().starknet_provider;
+    match handle_breakglass_request(
+        &req,
+        &operator,
+        data.breakglass_repository.clone(),
+        data.clock.clone(),
+    )
+    .await
+    {
+        Ok(mint) => response::with_status(http::StatusCode::ACCEPTED, mint),
+        Err(e) => breakglass_error_response(e),
+    }
+}
 
-    let transaction_repository = Arc::new(JunoLcd::new(&data.clone().juno_lcd));
-    let hash_validator = Arc::new(KeplrSignatureVeirfier {});
-    let starknet_manager = Arc::new(OnChainStartknetManager::new(
-        provider.clone(),
-        &data.clone().starknet_admin_address,
-        &data.clone().starknet_private_key,
-        data.chain_id,
-    ));
+#[post("/mint/{id}/confirm")]
+async fn confirm_breakglass_mint(
+    http_request: HttpRequest,
+    path: web::Path<Uuid>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let id = path.into_inner();
+    info!("POST - /admin/mint/{}/confirm - {}", &id, &operator.name);
 
-    let response = match handle_bridge_request(
-        &req,
-        &data.juno_admin_address,
-        &data.starknet_admin_address,
-        hash_validator.clone(),
-        transaction_repository.clone(),
-        starknet_manager.clone(),
-        data.data_repository.clone(),
-        data.queue_manager.clone(),
+    match handle_breakglass_confirmation(
+        &id,
+        &operator,
+        data.breakglass_confirmation_window,
+        data.breakglass_repository.clone(),
+        data.starknet_manager.clone(),
+        data.clock.clone(),
+        &data.shutdown,
     )
     .await
     {
-        Ok(r) => r,
-        Err(e) => match e {
-            BridgeError::InvalidSign => {
-                return (
-                    web::Json(ApiResponse::bad_request("Invalid sign")),
-                    http::StatusCode::BAD_REQUEST,
-                );
-            }
-            BridgeError::JunoBlockChainServerError(e) => {
-                return (
-                    web::Json(ApiResponse::bad_request(
-                        format!("Juno blockchain error {}", e.to_string().as_str()).as_str(),
-                    )),
-                    http::StatusCode::INTERNAL_SERVER_ERROR,
-                );
-            }
-            BridgeError::JunoBalanceIsNotZero => {
-                return (
-                    web::Json(ApiResponse::bad_request(
-                        "Juno tokens have not been transferred yet",
-                    )),
-                    http::StatusCode::BAD_REQUEST,
-                );
-            }
-            BridgeError::FetchTokenError(_) => {
-                return (
-                    web::Json(ApiResponse::bad_request(
-                        "Failed to fetch tokens from customer wallet",
-                    )),
-                    http::StatusCode::NOT_FOUND,
-                );
-            }
-            BridgeError::TokenNotTransferedToAdmin(_) => {
-                return (
-                    web::Json(ApiResponse::bad_request("Token not transferred to admin")),
-                    http::StatusCode::BAD_REQUEST,
-                );
-            }
-            BridgeError::TokenDidNotBelongToWallet(_) => {
-                return (
-                    web::Json(ApiResponse::bad_request(
-                        "Token did not belong to provided wallet.",
-                    )),
-                    http::StatusCode::BAD_REQUEST,
-                );
-            }
-            BridgeError::TokenAlreadyMinted(_) => {
-                return (
-                    web::Json(ApiResponse::bad_request("Token has already been minted")),
-                    http::StatusCode::BAD_REQUEST,
-                );
-            }
-            BridgeError::ErrorWhileMintingToken => {
-                return (
-                    web::Json(ApiResponse::bad_request("Error while minting token")),
-                    http::StatusCode::BAD_REQUEST,
-                );
-            }
-            BridgeError::EnqueueingIssue => {
-                return (
-                    web::Json(ApiResponse::bad_request(
-                        "Error while enqueing your token for minting",
-                    )),
-                    http::StatusCode::INTERNAL_SERVER_ERROR,
-                )
-            }
+        Ok(mint) => response::ok(mint),
+        Err(e) => breakglass_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+struct SupportBundleQuery {
+    starknet_project_addr: Option<StarknetAddress>,
+}
+
+#[get("/support-bundle/{keplr_wallet_pubkey}/{project_id}")]
+async fn support_bundle(
+    http_request: HttpRequest,
+    path: web::Path<(JunoAddress, ProjectId)>,
+    query: web::Query<SupportBundleQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let (keplr_wallet_pubkey, project_id) = path.into_inner();
+    info!(
+        "GET - /admin/support-bundle - {} - {} - {}",
+        &operator.name, &keplr_wallet_pubkey, &project_id
+    );
+
+    // Projects deployed at the same address on both chains do not need the query parameter
+    let starknet_project_addr = match query
+        .starknet_project_addr
+        .clone()
+        .or_else(|| StarknetAddress::new(project_id.as_str()).ok())
+    {
+        Some(addr) => addr,
+        None => {
+            return response::bad_request(
+                "Parameter 'starknet_project_addr' is required for this project",
+            )
+        }
+    };
+    let bundle = handle_support_bundle(
+        &SupportBundleRequest {
+            keplr_wallet_pubkey: &keplr_wallet_pubkey,
+            project_id: &project_id,
+            starknet_project_addr: &starknet_project_addr,
         },
+        data.data_repository.clone(),
+        data.queue_manager.clone(),
+        data.wallet_link_repository.clone(),
+        data.transaction_repository.clone(),
+        data.starknet_manager.clone(),
+        &data.shutdown,
+    )
+    .await;
+
+    response::ok(bundle)
+}
+
+#[post("/queue/{id}/retry")]
+async fn retry_queue_item(path: web::Path<QueueItemId>, data: web::Data<Config>) -> impl Responder {
+    let id = path.into_inner();
+    info!("POST - /queue/{}/retry", &id);
+
+    match handle_retry_queue_item(&id, data.queue_manager.clone()).await {
+        Ok(item) => response::ok(item),
+        Err(e) => queue_admin_error_response(e),
+    }
+}
+
+#[get("/reports")]
+async fn list_reports(http_request: HttpRequest, data: web::Data<Config>) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
     };
-    let mut http_status = http::StatusCode::OK;
-    for (_token, (_msg, err)) in response.checks.iter() {
-        http_status = match err {
-            None => break,
-            Some(s) => match s.as_str() {
-                "Failed to fecth token data from juno chain." => http::StatusCode::BAD_REQUEST,
-                "Juno node responded with an error status please try again later" => {
-                    http::StatusCode::INTERNAL_SERVER_ERROR
-                }
-                "Transaction not found on chain." => http::StatusCode::NOT_FOUND,
-                // Catching everything into BAD_REQUEST, only handle the other cases.
-                _ => http::StatusCode::BAD_REQUEST,
-            },
-        };
+    info!("GET - /admin/reports - {}", &operator.name);
+
+    match data.report_repository.list_report_days().await {
+        Ok(days) => response::ok(days),
+        Err(_e) => response::internal_server_error("Failed to list reports"),
     }
+}
 
-    (
-        web::Json(ApiResponse {
-            error: None,
-            message: "".into(),
-            code: match http_status {
-                http::StatusCode::OK => 200,
-                http::StatusCode::BAD_REQUEST => 400,
-                http::StatusCode::NOT_FOUND => 404,
-                http::StatusCode::INTERNAL_SERVER_ERROR => 500,
-                _ => 200,
-            },
-            body: Some(response),
-        }),
-        http_status,
-    )
+#[derive(Deserialize)]
+struct ReportQuery {
+    #[serde(default)]
+    format: ReportFormat,
+}
+
+#[get("/reports/{day}")]
+async fn get_report(
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<ReportQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let day = path.into_inner();
+    info!("GET - /admin/reports/{} - {}", &day, &operator.name);
+
+    match data.report_repository.get_report(&day).await {
+        Ok(report) => match query.format {
+            ReportFormat::Json => response::ok(report),
+            ReportFormat::Csv => HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .body(report.to_csv()),
+        },
+        Err(ReportError::NotFound) => response::not_found("No report for this day"),
+        Err(_e) => response::internal_server_error("Failed to fetch report"),
+    }
 }
 
-#[get("/health")]
-async fn health() -> impl Responder {
-    info!("GET - /health");
-    ("I'm ok !", http::StatusCode::OK)
+fn object_storage_not_configured() -> HttpResponse {
+    response::error(
+        http::StatusCode::NOT_IMPLEMENTED,
+        "object_storage_not_configured",
+        "Object storage is not configured",
+    )
 }
 
-#[post("/customer/data")]
-async fn save_customer_tokens(
-    request: web::Json<SaveCustomerDataRequest>,
-    config: web::Data<Config>,
+#[get("/reports/{day}/download")]
+async fn download_report(
+    http_request: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<ReportQuery>,
+    data: web::Data<Config>,
 ) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let day = path.into_inner();
     info!(
-        "POST - /customer/data - {} - {}",
-        &request.keplr_wallet_pubkey, &request.project_id
+        "GET - /admin/reports/{}/download - {}",
+        &day, &operator.name
     );
 
-    let _res = match handle_save_customer_data(&request, config.data_repository.clone()).await {
-        Ok(res) => res,
-        Err(e) => match e {
-            SaveCustomerDataError::NotImpled => {
-                return (
-                    web::Json(ApiResponse {
-                        error: Some("Internal Server Error".into()),
-                        message: "Unknown error".into(),
-                        code: 500,
-                        body: None,
-                    }),
-                    http::StatusCode::INTERNAL_SERVER_ERROR,
-                )
-            }
-            SaveCustomerDataError::NotFound => {
-                error!("Customer not found");
-                return (
-                    web::Json(ApiResponse {
-                        error: Some("Not Found".into()),
-                        message: "Customer not found".into(),
-                        code: 404,
-                        body: None,
-                    }),
-                    http::StatusCode::NOT_FOUND,
-                );
-            }
-            SaveCustomerDataError::FailedToPersistToDatabase => {
-                error!("Failed to persist to database");
-                return (
-                    web::Json(ApiResponse {
-                        error: Some("Internal Server Error".into()),
-                        message: "Error while saving customer to database".into(),
-                        code: 500,
-                        body: None,
-                    }),
-                    http::StatusCode::INTERNAL_SERVER_ERROR,
-                );
-            }
-        },
+    let Some(storage) = &data.object_storage else {
+        return object_storage_not_configured();
     };
+    if let Err(_e) = data.report_repository.get_report(&day).await {
+        return response::not_found("No report for this day");
+    }
+    match storage.presigned_url(&report_key(&day, query.format), data.object_storage_url_ttl) {
+        Ok(url) => response::ok(url),
+        Err(_e) => response::internal_server_error("Failed to presign report url"),
+    }
+}
 
-    (
-        web::Json(ApiResponse::<Vec<String>> {
-            error: None,
-            message: "Saved customer pubkey // tokens".into(),
-            code: 201,
-            body: None,
-        }),
-        http::StatusCode::CREATED,
+#[post("/export/artifact")]
+async fn export_queue_artifact(
+    http_request: HttpRequest,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("POST - /admin/export/artifact - {}", &operator.name);
+
+    let Some(storage) = &data.object_storage else {
+        return object_storage_not_configured();
+    };
+    match handle_queue_export(
+        data.queue_manager.clone(),
+        storage.clone(),
+        data.object_storage_url_ttl,
+        data.clock.clone(),
     )
+    .await
+    {
+        Ok(artifact) => response::ok(artifact),
+        Err(e) => {
+            error!("Queue export failed {:#?}", e);
+            response::internal_server_error("Failed to export queue")
+        }
+    }
 }
 
-#[get("/customer/data/{keplr_wallet_pubkey}/{project_id}")]
-async fn get_customer_migration_state(
-    path: web::Path<(String, String)>,
+const ADMIN_UI: &str = include_str!("../../static/admin/index.html");
+
+#[get("/ui")]
+async fn admin_ui(http_request: HttpRequest, data: web::Data<Config>) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return HttpResponse::Unauthorized()
+            .insert_header((
+                http::header::WWW_AUTHENTICATE,
+                "Basic realm=\"bridge admin\"",
+            ))
+            .finish();
+    };
+    info!("GET - /admin/ui - {}", &operator.name);
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(ADMIN_UI)
+}
+
+const EXPORT_MAX_PAGE_SIZE: i64 = 1000;
+
+#[get("/export")]
+async fn export_queue(
+    http_request: HttpRequest,
+    query: web::Query<PageQuery>,
     data: web::Data<Config>,
 ) -> impl Responder {
-    let (keplr_wallet_pubkey, project_id) = path.into_inner();
-    let queue_manager = data.clone().queue_manager.clone();
-    let res = queue_manager
-        .get_customer_migration_state(&keplr_wallet_pubkey, &project_id)
-        .await;
-
-    let mut status_code = http::StatusCode::OK;
-    if res.len() == 0 {
-        status_code = http::StatusCode::NOT_FOUND;
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("GET - /admin/export - {}", &operator.name);
+
+    let page = match page_request(&query, EXPORT_MAX_PAGE_SIZE) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    match data.queue_manager.list_queue_items(None, &page).await {
+        Ok(items) => response::ok(items),
+        Err(_e) => response::internal_server_error("Failed to export queue items"),
     }
+}
 
-    (web::Json(res), status_code)
+#[get("/events")]
+async fn queue_events(
+    http_request: HttpRequest,
+    query: web::Query<PageQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("GET - /admin/events - {}", &operator.name);
+
+    let page = match page_request(&query, EXPORT_MAX_PAGE_SIZE) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    match data.queue_manager.list_queue_events(&page).await {
+        Ok(events) => response::ok(events),
+        Err(_e) => response::internal_server_error("Failed to list queue events"),
+    }
 }
 
 #[actix_web::main]
@@ -307,22 +550,79 @@ async fn main() -> std::io::Result<()> {
     info!("Starting bridge application.");
 
     let args = Args::parse();
+    let config = web::Data::new(configure_application(&args).await);
+    cancel_on_shutdown_signal(config.shutdown.clone());
+    // Before binding, so the first requests after a deploy do not pay the cold start
+    warm_up(&config.warm_up_targets, config.warm_up_timeout).await;
 
     info!("Ready to handle requests.");
 
     HttpServer::new(move || {
-        let config = block_on(configure_application(&args));
-        let cors = Cors::default()
-            .allowed_origin(&args.frontend_uri.as_str())
-            .allowed_methods(vec!["POST"])
-            .allowed_headers(vec![http::header::CONTENT_TYPE]);
         App::new()
-            .app_data(web::Data::new(config))
-            .wrap(cors)
+            .app_data(config.clone())
+            .app_data(json_config())
+            // Innermost so CORS headers are added to rate limited answers as well
+            .wrap(from_fn(rate_limit))
+            .wrap(from_fn(schema_version))
+            .wrap(from_fn(record_metrics))
+            .wrap(cors(&config.frontend_uri))
+            // Outermost so every middleware logs with the request id
+            .wrap(from_fn(request_id))
             .service(health)
+            .service(ready)
+            .service(scrape_metrics)
+            .service(check_codes)
+            .service(errors_catalog)
+            .service(openapi_spec)
+            .service(challenge)
             .service(bridge)
+            .service(get_transaction_status)
             .service(save_customer_tokens)
+            .service(record_event)
             .service(get_customer_migration_state)
+            .service(get_wallet_migrations)
+            .service(get_customer_proof_bundle)
+            .service(get_eligible_tokens)
+            .service(reverse_bridge)
+            .service(get_reverse_migration_state)
+            .service(authorize_sender)
+            .service(queue_stats)
+            .service(throughput_stats)
+            .service(project_stats)
+            .service(public_stats)
+            .service(link_wallet)
+            .service(get_wallet_link)
+            .service(retry_queue_item)
+            .service(register_webhook)
+            .service(list_webhooks)
+            .service(delete_webhook)
+            .service(test_webhook)
+            .service(
+                web::scope("/admin")
+                    .service(breakglass_mint)
+                    .service(confirm_breakglass_mint)
+                    .service(approve_sender)
+                    .service(support_bundle)
+                    .service(queue_browser)
+                    .service(inspect_queue_item)
+                    .service(queue_item_history)
+                    .service(export_queue)
+                    .service(queue_events)
+                    .service(requeue_queue_item)
+                    .service(requeue_stale_processing_items)
+                    .service(cancel_queue_item)
+                    .service(dead_letters)
+                    .service(requeue_dead_letter)
+                    .service(register_operator_webhook)
+                    .service(batch_analytics)
+                    .service(bridge_requests)
+                    .service(database_maintenance)
+                    .service(admin_ui)
+                    .service(list_reports)
+                    .service(get_report)
+                    .service(download_report)
+                    .service(export_queue_artifact),
+            )
     })
     .bind(("0.0.0.0", 8080))?
     .run()
@@ -1,15 +1,26 @@
 use bridge_juno_to_starknet_backend::{
-    domain::consume_queue::consume_queue,
+    domain::{
+        clock::Clock,
+        consume_queue::{consume_queue, paused_projects, recover_processing_items, ConsumerError},
+        ids::StarknetAddress,
+        issue_tracker::report_dead_letters,
+        log_context::{with_log_context, LogFields},
+        post_mint::run_post_mint_hooks,
+        queue_admin::requeue_stale_processing_items,
+        queue_broker::wait_for_work,
+        reconciliation::ReconciliationError,
+        report::ensure_daily_report,
+        reverse_bridge::consume_reverse_queue,
+        webhook::run_webhook_deliveries,
+    },
     infrastructure::{
-        app::{configure_application, Args},
+        app::{cancel_after_grace_period, cancel_on_shutdown_signal, configure_application, Args},
         logger::configure_logger,
-        starknet::OnChainStartknetManager,
     },
 };
 use clap::Parser;
-use log::{error, info};
-use std::{sync::Arc, time::Instant};
-use tokio::time::{sleep, Duration};
+use log::{error, info, warn};
+use std::time::Instant;
 
 #[tokio::main]
 async fn main() {
@@ -18,26 +29,213 @@ async fn main() {
 
     let args = Args::parse();
     let config = configure_application(&args).await;
+    let shutdown = config.shutdown.clone();
+    cancel_on_shutdown_signal(shutdown.clone());
+    // Shutdown stops polling, in-flight mints are only interrupted once the grace period ends
+    let interrupt = cancel_after_grace_period(&shutdown, config.worker_shutdown_grace_period);
+
+    let starknet_manager = config.starknet_manager.clone();
+
+    info!("Recovering queue items left in processing.");
+    let stale_before = config
+        .stale_processing_age
+        .map(|age| config.clock.now_ms() - age.as_millis() as i64);
+    match recover_processing_items(
+        config.queue_manager.clone(),
+        starknet_manager.clone(),
+        config.post_mint_hooks.clone(),
+        config.post_mint_repository.clone(),
+        config.webhook_notifier.clone(),
+        &config.mint_retry_policy,
+        stale_before,
+        &interrupt,
+    )
+    .await
+    {
+        Ok(_) => (),
+        Err(ConsumerError::Cancelled) => {
+            warn!("Worker stopped while recovering queue items.");
+            return;
+        }
+        Err(_) => error!("Failed to recover queue items left in processing"),
+    }
 
-    let starknet_manager = Arc::new(OnChainStartknetManager::new(
-        config.starknet_provider.clone(),
-        &config.starknet_admin_address,
-        &config.starknet_private_key,
-        config.chain_id,
-    ));
+    while !shutdown.is_cancelled() {
+        // Other workers may have crashed mid batch since this one started
+        if let Some(max_age) = config.stale_processing_age {
+            if let Err(e) = requeue_stale_processing_items(
+                config.queue_manager.clone(),
+                config.clock.as_ref(),
+                max_age,
+            )
+            .await
+            {
+                error!("Failed to requeue items stalled in processing {:#?}", e);
+            }
+        }
 
-    loop {
         info!("Polling new NFT's migration requests.");
 
-        match consume_queue(config.queue_manager.clone(), starknet_manager.clone()).await {
+        let started_at = Instant::now();
+        let mut excluded_projects = config
+            .project_registry
+            .closed_projects(config.clock.now_ms());
+        let open_projects: Vec<StarknetAddress> = config
+            .project_registry
+            .projects()
+            .iter()
+            .map(|p| p.starknet_contract.clone())
+            .filter(|p| !excluded_projects.contains(p))
+            .collect();
+        excluded_projects.extend(paused_projects(starknet_manager.as_ref(), &open_projects).await);
+        // Each batch tags its log lines with the bridge requests it mints
+        let res = with_log_context(
+            LogFields::default(),
+            consume_queue(
+                config.queue_manager.clone(),
+                starknet_manager.clone(),
+                config.post_mint_hooks.clone(),
+                config.post_mint_repository.clone(),
+                config.webhook_notifier.clone(),
+                config.batch_analytics.clone(),
+                &excluded_projects,
+                &config.mint_retry_policy,
+                &interrupt,
+            ),
+        )
+        .await;
+        let outcome = match &res {
+            Ok(_) => "ok",
+            Err(ConsumerError::Cancelled) => "cancelled",
+            Err(_) => "error",
+        };
+        config
+            .metrics
+            .increment("worker_batches_total", &[("outcome", outcome)]);
+        config.metrics.timing(
+            "worker_batch_duration_seconds",
+            started_at.elapsed(),
+            &[("outcome", outcome)],
+        );
+        match res {
             Ok(_) => {
                 info!("Successfully handled tokens migration");
             }
+            Err(ConsumerError::Cancelled) => break,
             Err(_) => {
                 error!("Failed to migrate tokens");
             }
         }
+        if shutdown.is_cancelled() {
+            info!("In flight batch finished, stopping worker.");
+            break;
+        }
+
+        if let Some(broadcaster) = &config.juno_tx_broadcaster {
+            if let Err(e) = consume_reverse_queue(
+                config.reverse_queue_manager.clone(),
+                broadcaster.clone(),
+                config.reverse_batch_size,
+            )
+            .await
+            {
+                error!("Failed to transfer tokens back on Juno {:#?}", e);
+            }
+        }
+
+        if !config.post_mint_hooks.is_empty() {
+            if let Err(e) = run_post_mint_hooks(
+                &config.post_mint_hooks,
+                config.post_mint_repository.clone(),
+                config.post_mint_max_attempts,
+            )
+            .await
+            {
+                error!("Failed to run post mint hooks {:#?}", e);
+            }
+        }
+
+        if let Some(sender) = &config.webhook_sender {
+            if let Err(e) = run_webhook_deliveries(
+                config.webhook_repository.clone(),
+                sender.clone(),
+                config.clock.clone(),
+                &config.webhook_retry_policy,
+            )
+            .await
+            {
+                error!("Failed to deliver webhooks {:#?}", e);
+            }
+        }
+
+        if let Some(reconciler) = &config.transaction_reconciler {
+            match reconciler
+                .run(
+                    config.queue_manager.clone(),
+                    starknet_manager.clone(),
+                    &config.post_mint_hooks,
+                    config.post_mint_repository.clone(),
+                    &config.webhook_notifier,
+                    &config.mint_retry_policy,
+                    &interrupt,
+                )
+                .await
+            {
+                Ok(0) => (),
+                Ok(corrected) => warn!("Requeued {} items of reverted transactions", corrected),
+                Err(ReconciliationError::Cancelled) => break,
+                Err(e) => error!("Failed to reconcile transactions {:#?}", e),
+            }
+        }
+
+        if let Some(reporter) = &config.issue_reporter {
+            if let Err(e) = report_dead_letters(
+                reporter,
+                config.queue_manager.clone(),
+                &config.project_registry,
+                config.data_repository.clone(),
+                config.wallet_link_repository.clone(),
+                config.transaction_repository.clone(),
+                starknet_manager.clone(),
+                &interrupt,
+            )
+            .await
+            {
+                error!("Failed to open issues for dead letters {:#?}", e);
+            }
+        }
+
+        if let Err(e) = config.batch_analytics.prune().await {
+            error!("Failed to prune batch analytics {:#?}", e);
+        }
+        if let Err(e) = config.funnel_analytics.prune().await {
+            error!("Failed to prune funnel events {:#?}", e);
+        }
+        if let Some(maintenance) = &config.database_maintenance {
+            if let Err(e) = maintenance.run(config.metrics.as_ref()).await {
+                error!("Failed to run database maintenance {:#?}", e);
+            }
+        }
 
-        sleep(Duration::from_secs(60)).await;
+        if let Some(signer) = &config.report_signer {
+            if let Err(e) = ensure_daily_report(
+                config.report_repository.clone(),
+                signer.clone(),
+                config.report_publisher.clone(),
+                config.object_storage.clone(),
+                config.clock.clone(),
+            )
+            .await
+            {
+                error!("Failed to generate daily report {:#?}", e);
+            }
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = wait_for_work(&config.queue_broker, config.queue_poll_interval) => {}
+        };
     }
+
+    warn!("Worker stopped, items left processing are recovered on next start.");
 }
@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info};
+use serde_derive::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use uuid::Uuid;
+
+use super::{batch_size::BatchSizeTuner, clock::Clock, ids::StarknetAddress};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchOutcome {
+    Accepted,
+    Rejected,
+    SubmissionFailed,
+}
+
+/// One batch mint sent by the worker, kept to follow fees and sequencer latency over time.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchRecord {
+    pub id: Uuid,
+    pub project_id: StarknetAddress,
+    pub transaction_hash: Option<String>,
+    pub item_count: i32,
+    /// Actual fee paid in wei, as a decimal string since it does not fit in an i64
+    pub fee: Option<String>,
+    pub outcome: BatchOutcome,
+    pub rejection_reason: Option<String>,
+    // Epoch milliseconds
+    pub submitted_at: i64,
+    /// Milliseconds between submission and the transaction being accepted or rejected
+    pub latency_ms: Option<i64>,
+}
+
+/// How a batch ended, there is no transaction hash when submission failed.
+#[derive(Debug, Clone)]
+pub struct BatchSettlement {
+    pub transaction_hash: Option<String>,
+    pub fee: Option<String>,
+    pub outcome: BatchOutcome,
+    pub rejection_reason: Option<String>,
+}
+
+/// Epoch milliseconds range, both bounds are optional.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BatchAnalyticsQuery {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub project_id: Option<StarknetAddress>,
+}
+
+#[derive(Debug)]
+pub enum BatchAnalyticsError {
+    InvalidRange,
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait BatchAnalyticsRepository: Send + Sync {
+    async fn save_batch(&self, record: &BatchRecord) -> Result<(), BatchAnalyticsError>;
+    /// Batches submitted within the range, most recent first.
+    async fn get_batches(
+        &self,
+        query: &BatchAnalyticsQuery,
+    ) -> Result<Vec<BatchRecord>, BatchAnalyticsError>;
+    /// Deletes batches submitted before `before` (epoch milliseconds), returns how many were.
+    async fn delete_batches_before(&self, before: i64) -> Result<u64, BatchAnalyticsError>;
+}
+
+impl Debug for dyn BatchAnalyticsRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "BatchAnalyticsRepository{{}}")
+    }
+}
+
+pub struct BatchAnalytics {
+    repository: Arc<dyn BatchAnalyticsRepository>,
+    clock: Arc<dyn Clock>,
+    retention: Duration,
+    // Adaptive batch size mode, fed with every recorded outcome
+    tuner: Option<Arc<BatchSizeTuner>>,
+}
+
+impl BatchAnalytics {
+    pub fn new(
+        repository: Arc<dyn BatchAnalyticsRepository>,
+        clock: Arc<dyn Clock>,
+        retention: Duration,
+    ) -> Self {
+        Self::with_tuner(repository, clock, retention, None)
+    }
+
+    pub fn with_tuner(
+        repository: Arc<dyn BatchAnalyticsRepository>,
+        clock: Arc<dyn Clock>,
+        retention: Duration,
+        tuner: Option<Arc<BatchSizeTuner>>,
+    ) -> Self {
+        Self {
+            repository,
+            clock,
+            retention,
+            tuner,
+        }
+    }
+
+    pub fn now_ms(&self) -> i64 {
+        self.clock.now_ms()
+    }
+
+    /// Items of the project the next batch is limited to, `None` unless batch size is adaptive.
+    pub async fn batch_size(&self, project_id: &StarknetAddress) -> Option<usize> {
+        match &self.tuner {
+            Some(tuner) => Some(tuner.batch_size(project_id).await),
+            None => None,
+        }
+    }
+
+    /// Records a settled batch, analytics failures never fail the mint itself.
+    pub async fn record(
+        &self,
+        project_id: &StarknetAddress,
+        item_count: usize,
+        submitted_at: i64,
+        settlement: BatchSettlement,
+    ) {
+        let latency_ms = match settlement.outcome {
+            BatchOutcome::SubmissionFailed => None,
+            _ => Some(self.clock.now_ms() - submitted_at),
+        };
+        let record = BatchRecord {
+            id: Uuid::new_v4(),
+            project_id: project_id.clone(),
+            transaction_hash: settlement.transaction_hash,
+            item_count: item_count as i32,
+            fee: settlement.fee,
+            outcome: settlement.outcome,
+            rejection_reason: settlement.rejection_reason,
+            submitted_at,
+            latency_ms,
+        };
+        if let Err(e) = self.repository.save_batch(&record).await {
+            error!("Failed to record batch analytics {:#?}", e);
+        }
+        if let Some(tuner) = &self.tuner {
+            tuner.record_outcome(project_id, record.outcome).await;
+        }
+    }
+
+    pub async fn batches(
+        &self,
+        query: &BatchAnalyticsQuery,
+    ) -> Result<Vec<BatchRecord>, BatchAnalyticsError> {
+        if let (Some(from), Some(to)) = (query.from, query.to) {
+            if from > to {
+                return Err(BatchAnalyticsError::InvalidRange);
+            }
+        }
+        self.repository.get_batches(query).await
+    }
+
+    /// Drops batches older than the retention period.
+    pub async fn prune(&self) -> Result<u64, BatchAnalyticsError> {
+        let before = self.clock.now_ms() - self.retention.as_millis() as i64;
+        let deleted = self.repository.delete_batches_before(before).await?;
+        if 0 < deleted {
+            info!("Pruned {} batch analytics records", deleted);
+        }
+
+        Ok(deleted)
+    }
+}
+
+impl Debug for BatchAnalytics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "BatchAnalytics{{retention: {:?}, adaptive: {}}}",
+            self.retention,
+            self.tuner.is_some()
+        )
+    }
+}
@@ -0,0 +1,56 @@
+use core::fmt::{Debug, Formatter};
+use log::error;
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::bridge::{QueueItem, QueueStatus};
+
+/// Server signature stating a token was minted to a Starknet wallet, so Starknet dApps
+/// can trust a migration without querying the bridge. Felts are hex encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct MintAttestation {
+    /// Pedersen hash chain of the domain tag, chain id, project contract, Starknet
+    /// wallet, token id and mint transaction hash
+    pub message_hash: String,
+    pub signature_r: String,
+    pub signature_s: String,
+    /// Stark key the signature verifies against
+    pub public_key: String,
+}
+
+#[derive(Debug)]
+pub enum AttestationError {
+    InvalidValue(String),
+    FailedToSign,
+}
+
+pub trait AttestationSigner: Send + Sync {
+    fn attest(&self, item: &QueueItem) -> Result<MintAttestation, AttestationError>;
+}
+
+impl Debug for dyn AttestationSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "AttestationSigner{{}}")
+    }
+}
+
+/// Attestation of a minted item, none until the mint succeeded. Signatures are
+/// deterministic, so they are computed on demand rather than stored.
+pub fn attest_mint(
+    signer: &Option<Arc<dyn AttestationSigner>>,
+    item: &QueueItem,
+) -> Option<MintAttestation> {
+    let signer = signer.as_ref()?;
+    if !matches!(item.status, QueueStatus::Success) || item.transaction_hash.is_none() {
+        return None;
+    }
+
+    match signer.attest(item) {
+        Ok(attestation) => Some(attestation),
+        Err(e) => {
+            error!("Failed to attest mint of {} {:#?}", item.token_id, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use serde_derive::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    bridge::{BridgeRequest, BridgeResponse},
+    ids::{JunoAddress, ProjectId, StarknetAddress, TokenId},
+};
+
+/// Outcome of the checks of one token, `failed_check` is the message customer got.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditedCheck {
+    pub token_id: TokenId,
+    pub failed_check: Option<String>,
+}
+
+/// One `/bridge` call as the customer saw it, kept so support can tell what
+/// happened to a token reported as not migrated.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BridgeRequestAudit {
+    pub id: Uuid,
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub starknet_account_addr: StarknetAddress,
+    pub project_id: ProjectId,
+    pub token_ids: Vec<TokenId>,
+    pub checks: Vec<AuditedCheck>,
+    /// `ok` or the error code of the response
+    pub outcome: String,
+    pub status: u16,
+    // Epoch milliseconds
+    pub created_at: i64,
+}
+
+impl BridgeRequestAudit {
+    pub fn new(
+        req: &BridgeRequest,
+        response: Option<&BridgeResponse>,
+        outcome: &str,
+        status: u16,
+        created_at: i64,
+    ) -> Self {
+        let mut checks: Vec<AuditedCheck> = response
+            .map(|r| {
+                r.checks
+                    .iter()
+                    .map(|(token_id, (_, failed_check))| AuditedCheck {
+                        token_id: token_id.clone(),
+                        failed_check: failed_check.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        checks.sort_by(|a, b| a.token_id.as_str().cmp(b.token_id.as_str()));
+
+        Self {
+            id: Uuid::new_v4(),
+            keplr_wallet_pubkey: req.keplr_wallet_pubkey.clone(),
+            starknet_account_addr: req.starknet_account_addr.clone(),
+            project_id: req.project_id.clone(),
+            token_ids: req.tokens_id.clone().unwrap_or_default(),
+            checks,
+            outcome: outcome.to_string(),
+            status,
+            created_at,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AuditError {
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    async fn save_bridge_request(&self, audit: &BridgeRequestAudit) -> Result<(), AuditError>;
+    /// Requests of the wallet, most recent first.
+    async fn get_bridge_requests(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<BridgeRequestAudit>, AuditError>;
+}
+
+impl Debug for dyn AuditRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "AuditRepository{{}}")
+    }
+}
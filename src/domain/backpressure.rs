@@ -0,0 +1,47 @@
+use log::{error, warn};
+use std::time::Duration;
+
+use super::bridge::QueueManager;
+
+/// Refuses new items once `max_pending` are waiting, so a stampede of
+/// customers cannot outgrow what the worker drains. Workers mint at most `batch_size`
+/// items every `poll_interval`, which is how the retry time is estimated.
+#[derive(Debug, Clone)]
+pub struct QueueBackpressure {
+    max_pending: i64,
+    batch_size: i64,
+    poll_interval: Duration,
+}
+
+impl QueueBackpressure {
+    pub fn new(max_pending: i64, batch_size: u8, poll_interval: Duration) -> Self {
+        Self {
+            max_pending,
+            batch_size: i64::from(batch_size.max(1)),
+            poll_interval,
+        }
+    }
+
+    /// Seconds before the queue should be back under its limit, none while new items
+    /// are accepted. Counting failures never refuse customers.
+    pub async fn retry_after(&self, queue_manager: &dyn QueueManager) -> Option<u64> {
+        let pending = match queue_manager.count_pending_items().await {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to count pending queue items {:#?}", e);
+                return None;
+            }
+        };
+        if pending < self.max_pending {
+            return None;
+        }
+
+        let excess = pending - self.max_pending + 1;
+        let batches = (excess + self.batch_size - 1) / self.batch_size;
+        warn!(
+            "Queue holds {} pending items over the limit of {}",
+            pending, self.max_pending
+        );
+        Some(batches as u64 * self.poll_interval.as_secs().max(1))
+    }
+}
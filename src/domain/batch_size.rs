@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::{analytics::BatchOutcome, clock::Clock, ids::StarknetAddress};
+
+/// Bounds the adaptive batch size moves within. Size grows by `step` after
+/// `increase_after` consecutive accepted batches and is halved on any failure.
+#[derive(Debug, Clone)]
+pub struct AdaptiveBatchPolicy {
+    pub initial: u32,
+    pub min: u32,
+    pub max: u32,
+    pub step: u32,
+    pub increase_after: u32,
+}
+
+impl AdaptiveBatchPolicy {
+    pub fn next(&self, current: &ProjectBatchSize, outcome: BatchOutcome) -> ProjectBatchSize {
+        let (batch_size, consecutive_successes) = match outcome {
+            BatchOutcome::Accepted if current.consecutive_successes + 1 >= self.increase_after => {
+                (current.batch_size.saturating_add(self.step), 0)
+            }
+            BatchOutcome::Accepted => (current.batch_size, current.consecutive_successes + 1),
+            BatchOutcome::Rejected | BatchOutcome::SubmissionFailed => (current.batch_size / 2, 0),
+        };
+
+        ProjectBatchSize {
+            project_id: current.project_id.clone(),
+            batch_size: batch_size.clamp(self.min, self.max),
+            consecutive_successes,
+            updated_at: current.updated_at,
+        }
+    }
+}
+
+/// Batch size currently used to mint tokens of a project.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProjectBatchSize {
+    pub project_id: StarknetAddress,
+    pub batch_size: u32,
+    pub consecutive_successes: u32,
+    // Epoch milliseconds
+    pub updated_at: i64,
+}
+
+#[derive(Debug)]
+pub enum BatchSizeError {
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait BatchSizeRepository: Send + Sync {
+    async fn get_batch_size(
+        &self,
+        project_id: &StarknetAddress,
+    ) -> Result<Option<ProjectBatchSize>, BatchSizeError>;
+    async fn save_batch_size(&self, batch_size: &ProjectBatchSize) -> Result<(), BatchSizeError>;
+}
+
+impl Debug for dyn BatchSizeRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "BatchSizeRepository{{}}")
+    }
+}
+
+pub struct BatchSizeTuner {
+    repository: Arc<dyn BatchSizeRepository>,
+    clock: Arc<dyn Clock>,
+    policy: AdaptiveBatchPolicy,
+}
+
+impl BatchSizeTuner {
+    pub fn new(
+        repository: Arc<dyn BatchSizeRepository>,
+        clock: Arc<dyn Clock>,
+        policy: AdaptiveBatchPolicy,
+    ) -> Self {
+        Self {
+            repository,
+            clock,
+            policy,
+        }
+    }
+
+    async fn current(&self, project_id: &StarknetAddress) -> ProjectBatchSize {
+        match self.repository.get_batch_size(project_id).await {
+            Ok(Some(current)) => current,
+            Ok(None) => ProjectBatchSize {
+                project_id: project_id.clone(),
+                batch_size: self.policy.initial.clamp(self.policy.min, self.policy.max),
+                consecutive_successes: 0,
+                updated_at: self.clock.now_ms(),
+            },
+            // Smallest batches are the safest bet while the size is unknown
+            Err(e) => {
+                error!("Failed to fetch project {} batch size {:#?}", project_id, e);
+                ProjectBatchSize {
+                    project_id: project_id.clone(),
+                    batch_size: self.policy.min,
+                    consecutive_successes: 0,
+                    updated_at: self.clock.now_ms(),
+                }
+            }
+        }
+    }
+
+    /// How many queue items of the project go into the next batch.
+    pub async fn batch_size(&self, project_id: &StarknetAddress) -> usize {
+        self.current(project_id).await.batch_size as usize
+    }
+
+    pub async fn record_outcome(&self, project_id: &StarknetAddress, outcome: BatchOutcome) {
+        let current = self.current(project_id).await;
+        let mut next = self.policy.next(&current, outcome);
+        next.updated_at = self.clock.now_ms();
+        if next.batch_size != current.batch_size {
+            info!(
+                "Project {} batch size moves from {} to {} after a {:?} batch",
+                project_id, current.batch_size, next.batch_size, outcome
+            );
+        }
+        if let Err(e) = self.repository.save_batch_size(&next).await {
+            error!(
+                "Failed to persist project {} batch size {:#?}",
+                project_id, e
+            );
+        }
+    }
+}
+
+impl Debug for BatchSizeTuner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "BatchSizeTuner{{policy: {:?}}}", self.policy)
+    }
+}
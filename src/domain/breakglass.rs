@@ -0,0 +1,253 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use hmac::{Hmac, Mac};
+use log::{error, info, warn};
+use serde_derive::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{sync::Arc, time::Duration};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::{
+    bridge::{StarknetManager, TransactionOutcome},
+    clock::Clock,
+    ids::{StarknetAddress, TokenId},
+};
+
+#[derive(Debug, Clone)]
+pub struct Operator {
+    pub name: String,
+    pub api_key: String,
+}
+
+impl Operator {
+    /// Parses operator definition formatted as `name:api_key`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (name, api_key) = value.split_once(':')?;
+        if name.is_empty() || api_key.is_empty() {
+            return None;
+        }
+        Some(Self {
+            name: name.into(),
+            api_key: api_key.into(),
+        })
+    }
+}
+
+pub fn authenticate_operator<'a>(operators: &'a [Operator], api_key: &str) -> Option<&'a Operator> {
+    // Every key is compared so timings tell nothing of which one matched
+    let mut authenticated = None;
+    for operator in operators {
+        if api_keys_match(&operator.api_key, api_key) && authenticated.is_none() {
+            authenticated = Some(operator);
+        }
+    }
+
+    authenticated
+}
+
+// Keys are compared through their MAC, in constant time whatever their lengths
+fn api_keys_match(expected: &str, api_key: &str) -> bool {
+    let mac = |key: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(expected.as_bytes()).unwrap();
+        mac.update(key.as_bytes());
+        mac
+    };
+    mac(api_key)
+        .verify_slice(&mac(expected).finalize().into_bytes())
+        .is_ok()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakglassStatus {
+    AwaitingConfirmation,
+    /// Claimed by the confirming operator, mint is being sent
+    Confirming,
+    /// Mint transaction sent, its outcome is not known yet
+    Submitted,
+    Minted,
+    Failed,
+    Expired,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BreakglassMintRequest {
+    pub starknet_project_addr: StarknetAddress,
+    pub starknet_account_addr: StarknetAddress,
+    pub token_ids: Vec<TokenId>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BreakglassMint {
+    pub id: Uuid,
+    pub starknet_project_addr: StarknetAddress,
+    pub starknet_account_addr: StarknetAddress,
+    pub token_ids: Vec<TokenId>,
+    pub reason: String,
+    pub requested_by: String,
+    // Epoch seconds
+    pub requested_at: i64,
+    pub confirmed_by: Option<String>,
+    pub transaction_hash: Option<String>,
+    pub status: BreakglassStatus,
+}
+
+#[derive(Debug)]
+pub enum BreakglassError {
+    InvalidRequest(String),
+    NotFound,
+    SameOperator,
+    Expired,
+    AlreadyHandled,
+    MintFailed,
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait BreakglassRepository: Send + Sync {
+    async fn save(&self, mint: &BreakglassMint) -> Result<(), BreakglassError>;
+    async fn get(&self, id: &Uuid) -> Result<BreakglassMint, BreakglassError>;
+    /// Moves a mint awaiting confirmation to `Confirming` on behalf of `confirmed_by`,
+    /// fails with `AlreadyHandled` when another confirmation claimed it first.
+    async fn claim_confirmation(
+        &self,
+        id: &Uuid,
+        confirmed_by: &str,
+    ) -> Result<BreakglassMint, BreakglassError>;
+}
+
+impl Debug for dyn BreakglassRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "BreakglassRepository{{}}")
+    }
+}
+
+/// First operator proposes a manual mint, nothing happens on chain until a second
+/// operator confirms it.
+pub async fn handle_breakglass_request(
+    req: &BreakglassMintRequest,
+    operator: &Operator,
+    breakglass_repository: Arc<dyn BreakglassRepository>,
+    clock: Arc<dyn Clock>,
+) -> Result<BreakglassMint, BreakglassError> {
+    if req.token_ids.is_empty() {
+        return Err(BreakglassError::InvalidRequest(
+            "At least one token id is required".into(),
+        ));
+    }
+    if req.reason.trim().is_empty() {
+        return Err(BreakglassError::InvalidRequest(
+            "A reason is required for audit purposes".into(),
+        ));
+    }
+
+    let mint = BreakglassMint {
+        id: Uuid::new_v4(),
+        starknet_project_addr: req.starknet_project_addr.clone(),
+        starknet_account_addr: req.starknet_account_addr.clone(),
+        token_ids: req.token_ids.to_vec(),
+        reason: req.reason.to_string(),
+        requested_by: operator.name.to_string(),
+        requested_at: clock.now_secs(),
+        confirmed_by: None,
+        transaction_hash: None,
+        status: BreakglassStatus::AwaitingConfirmation,
+    };
+    breakglass_repository.save(&mint).await?;
+    warn!(
+        "BREAKGLASS - {} requested mint {} of tokens [{}] on {} to {} : {}",
+        mint.requested_by,
+        mint.id,
+        mint.token_ids
+            .iter()
+            .map(TokenId::as_str)
+            .collect::<Vec<&str>>()
+            .join(", "),
+        mint.starknet_project_addr,
+        mint.starknet_account_addr,
+        mint.reason
+    );
+
+    Ok(mint)
+}
+
+/// Second operator confirms the mint, which is sent once whatever the number of
+/// confirmations. Mint is left `Submitted` when its outcome is not known before `cancel`.
+pub async fn handle_breakglass_confirmation(
+    id: &Uuid,
+    operator: &Operator,
+    confirmation_window: Duration,
+    breakglass_repository: Arc<dyn BreakglassRepository>,
+    starknet_manager: Arc<dyn StarknetManager>,
+    clock: Arc<dyn Clock>,
+    cancel: &CancellationToken,
+) -> Result<BreakglassMint, BreakglassError> {
+    let mut mint = breakglass_repository.get(id).await?;
+    if BreakglassStatus::AwaitingConfirmation != mint.status {
+        return Err(BreakglassError::AlreadyHandled);
+    }
+    if mint.requested_by == operator.name {
+        error!(
+            "BREAKGLASS - {} tried to confirm its own mint request {}",
+            operator.name, mint.id
+        );
+        return Err(BreakglassError::SameOperator);
+    }
+    if clock.now_secs() - mint.requested_at > confirmation_window.as_secs() as i64 {
+        mint.status = BreakglassStatus::Expired;
+        breakglass_repository.save(&mint).await?;
+        warn!("BREAKGLASS - mint request {} expired", mint.id);
+        return Err(BreakglassError::Expired);
+    }
+
+    // Concurrent or retried confirmations passed the check above, only one claims the mint
+    let mut mint = breakglass_repository
+        .claim_confirmation(&mint.id, &operator.name)
+        .await?;
+    let result = starknet_manager
+        .mint_project_token(
+            &mint.starknet_project_addr,
+            &mint.token_ids,
+            &mint.starknet_account_addr,
+        )
+        .await;
+    match result {
+        Ok(tx_hash) => {
+            mint.status = BreakglassStatus::Submitted;
+            mint.transaction_hash = Some(tx_hash.clone());
+            breakglass_repository.save(&mint).await?;
+            match starknet_manager
+                .wait_for_transaction(&tx_hash, cancel)
+                .await
+            {
+                TransactionOutcome::Accepted => mint.status = BreakglassStatus::Minted,
+                TransactionOutcome::Rejected(reason) => {
+                    error!(
+                        "BREAKGLASS - mint transaction {} of request {} rejected : {:#?}",
+                        tx_hash, mint.id, reason
+                    );
+                    mint.status = BreakglassStatus::Failed;
+                }
+                _ => warn!(
+                    "BREAKGLASS - stopped waiting for mint transaction {} of request {}",
+                    tx_hash, mint.id
+                ),
+            };
+        }
+        Err(_e) => mint.status = BreakglassStatus::Failed,
+    };
+    breakglass_repository.save(&mint).await?;
+    warn!(
+        "BREAKGLASS - {} confirmed mint request {} from {} -> {:#?} {:#?}",
+        operator.name, mint.id, mint.requested_by, mint.status, mint.transaction_hash
+    );
+
+    if BreakglassStatus::Failed == mint.status {
+        return Err(BreakglassError::MintFailed);
+    }
+    info!("BREAKGLASS - mint request {} done", mint.id);
+
+    Ok(mint)
+}
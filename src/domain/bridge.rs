@@ -1,56 +1,127 @@
 use async_trait::async_trait;
 use core::fmt::{Debug, Formatter};
-use log::{error, info};
+use log::{error, info, warn};
 use serde_derive::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::{timeout_at, Instant};
+use tokio_util::sync::CancellationToken;
 
+use super::backpressure::QueueBackpressure;
+use super::challenge::{challenge_message, ChallengeError, ChallengeService};
+use super::check_cache::CheckResultCache;
+use super::clock::Clock;
+use super::ids::{JunoAddress, ProjectId, QueueItemId, StarknetAddress, TokenId, TransactionHash};
+use super::log_context::current_log_fields;
+use super::pagination::{Page, PageRequest};
+use super::project_registry::{MigrationPolicy, MigrationWindow, ProjectRegistry};
 use super::save_customer_data::DataRepository;
+use super::status_message::StatusNote;
+use super::transfer_proof::{TransferProof, TransferProofRepository};
+use super::wallet_link::{WalletLink, WalletLinkError, WalletLinkRepository};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Note of items enqueued while Starknet could not tell whether their token was minted,
+/// the worker checks them before minting.
+pub const UNCHECKED_MINT_STATUS_NOTE: &str = "UncheckedMintStatus";
+
+// Tokens stay locked past the request budget for the time it takes to enqueue them
+const TOKEN_LOCK_MARGIN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct PubKey {
-    #[serde(rename(serialize = "type", deserialize = "type"))]
+    #[serde(rename = "type")]
     pub key_type: String,
-    #[serde(rename(serialize = "value", deserialize = "value"))]
+    #[serde(rename = "value")]
     pub key_value: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct SignedHash {
     pub pub_key: PubKey,
     pub signature: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct BridgeRequest {
     pub signed_hash: SignedHash,
-    pub starknet_account_addr: String,
-    pub starknet_project_addr: String,
-    pub keplr_wallet_pubkey: String,
-    pub project_id: String,
-    pub tokens_id: Option<Vec<String>>,
+    #[schema(value_type = String)]
+    pub starknet_account_addr: StarknetAddress,
+    /// Ignored, the starknet contract is resolved from the project registry. Still
+    /// accepted as older frontends send it.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub starknet_project_addr: Option<StarknetAddress>,
+    #[schema(value_type = String)]
+    pub keplr_wallet_pubkey: JunoAddress,
+    #[schema(value_type = String)]
+    pub project_id: ProjectId,
+    #[schema(value_type = Option<Vec<String>>)]
+    pub tokens_id: Option<Vec<TokenId>>,
+    /// Challenge from `GET /challenge`, when set the signature has to cover
+    /// `{nonce}:{starknet_account_addr}:{project_id}:{comma separated token ids}`.
+    #[serde(default)]
+    pub nonce: Option<String>,
+    /// JSON `BridgeSignDoc` the customer signed with ADR-036 `signArbitrary`, exactly as
+    /// signed. Takes precedence over the nonce message.
+    #[serde(default)]
+    pub sign_doc: Option<String>,
+}
+
+/// Document signed by customers so a signature only authorizes the listed tokens of
+/// one project, for one starknet account.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+pub struct BridgeSignDoc {
+    #[schema(value_type = String)]
+    pub starknet_account_addr: StarknetAddress,
+    #[schema(value_type = String)]
+    pub project_id: ProjectId,
+    #[schema(value_type = Vec<String>)]
+    pub token_ids: Vec<TokenId>,
+    /// Challenge from `GET /challenge`, required along with the request nonce
+    #[serde(default)]
+    pub nonce: Option<String>,
+}
+
+impl BridgeSignDoc {
+    /// Signed document has to name the very tokens requested, in any order.
+    pub fn covers(&self, req: &BridgeRequest) -> bool {
+        let mut signed: Vec<&TokenId> = self.token_ids.iter().collect();
+        signed.sort();
+        signed.dedup();
+        let mut requested: Vec<&TokenId> = req.tokens_id.iter().flatten().collect();
+        requested.sort();
+        requested.dedup();
+
+        self.starknet_account_addr == req.starknet_account_addr
+            && self.project_id == req.project_id
+            && self.nonce == req.nonce
+            && signed == requested
+    }
 }
 
 impl BridgeRequest {
     pub fn new(
         signed_hash: SignedHash,
-        starknet_account_addr: &str,
-        starknet_project_addr: &str,
-        keplr_wallet_pubkey: &str,
-        project_id: &str,
-        tokens_id: Vec<&str>,
+        starknet_account_addr: StarknetAddress,
+        keplr_wallet_pubkey: JunoAddress,
+        project_id: ProjectId,
+        tokens_id: Vec<TokenId>,
     ) -> Self {
-        let mut tokens = vec![];
-        for t in tokens_id {
-            tokens.push(t.into());
-        }
         Self {
             signed_hash,
-            starknet_account_addr: starknet_account_addr.into(),
-            starknet_project_addr: starknet_project_addr.into(),
-            keplr_wallet_pubkey: keplr_wallet_pubkey.into(),
-            project_id: project_id.into(),
-            tokens_id: Some(tokens),
+            starknet_account_addr,
+            starknet_project_addr: None,
+            keplr_wallet_pubkey,
+            project_id,
+            tokens_id: Some(tokens_id),
+            nonce: None,
+            sign_doc: None,
         }
     }
 }
@@ -61,10 +132,45 @@ pub struct TransferNft {
     pub token_id: String,
 }
 
+/// Transfer to a contract, which is then called with `msg`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SendNft {
+    pub contract: String,
+    pub token_id: String,
+    #[serde(default)]
+    pub msg: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Burn {
+    pub token_id: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum MsgTypes {
     TransferNft(TransferNft),
+    SendNft(SendNft),
+    Burn(Burn),
+}
+
+impl MsgTypes {
+    pub fn token_id(&self) -> &str {
+        match self {
+            MsgTypes::TransferNft(t) => &t.token_id,
+            MsgTypes::SendNft(s) => &s.token_id,
+            MsgTypes::Burn(b) => &b.token_id,
+        }
+    }
+
+    /// Account or contract the token was moved to, none once burnt.
+    pub fn recipient(&self) -> Option<&str> {
+        match self {
+            MsgTypes::TransferNft(t) => Some(&t.recipient),
+            MsgTypes::SendNft(s) => Some(&s.contract),
+            MsgTypes::Burn(_) => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -72,6 +178,25 @@ pub struct Transaction {
     pub contract: String,
     pub msg: MsgTypes,
     pub sender: String,
+    // Not part of the message, filled from the LCD transaction responses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<i64>,
+    // Epoch milliseconds of the block including the transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_time: Option<i64>,
+    // Result code of the transaction, anything but 0 means it was included but failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<u32>,
+}
+
+impl Transaction {
+    /// Whether the transaction went through. Transactions fetched from the LCD always carry
+    /// their result, it is only missing from transactions recorded without it.
+    pub fn succeeded(&self) -> bool {
+        self.code.map_or(true, |code| code == 0)
+    }
 }
 
 #[derive(Debug)]
@@ -85,13 +210,26 @@ pub enum BridgeError {
     ErrorWhileMintingToken,
     JunoBlockChainServerError(u16),
     EnqueueingIssue,
+    StarknetAccountMismatch(String),
+    WalletLinkIssue,
+    Cancelled,
+    UnknownProject(String),
+    InvalidChallenge,
+    ChallengeExpired,
+    ChallengeIssue,
+    SignDocRequired,
+    SignDocMismatch,
+    /// Seconds the customer should wait before retrying
+    QueueFull(u64),
+    MigrationWindowClosed(MigrationWindow),
 }
 
+#[derive(Debug)]
 pub enum SignedHashValidatorError {
     FailedToVerifyHash,
 }
 
-pub trait SignedHashValidator {
+pub trait SignedHashValidator: Send + Sync {
     fn verify(
         &self,
         signed_hash: &SignedHash,
@@ -111,15 +249,34 @@ pub enum TransactionFetchError {
     FetchError(String),
     DeserializationFailed,
     JunoBlockchainServerError(u16),
+    /// Juno node answered with more bytes than we accept to hold in memory
+    ResponseTooLarge,
+    Cancelled,
 }
 
 #[async_trait]
-pub trait TransactionRepository {
-    async fn get_transactions_for_contract(
+pub trait TransactionRepository: Send + Sync {
+    /// Every transfer made on the contract, most recent first.
+    async fn get_contract_transactions(
         &self,
-        project_id: &str,
-        token_id: &str,
+        project_id: &ProjectId,
+        cancel: &CancellationToken,
     ) -> Result<Vec<Transaction>, TransactionFetchError>;
+
+    /// Successful transfers of a single token, most recent first.
+    async fn get_transactions_for_contract(
+        &self,
+        project_id: &ProjectId,
+        token_id: &TokenId,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Transaction>, TransactionFetchError> {
+        let transactions = self.get_contract_transactions(project_id, cancel).await?;
+
+        Ok(transactions
+            .into_iter()
+            .filter(|t| t.succeeded() && *token_id == t.msg.token_id())
+            .collect())
+    }
 }
 
 impl Debug for dyn TransactionRepository {
@@ -128,13 +285,41 @@ impl Debug for dyn TransactionRepository {
     }
 }
 
+/// Smart queries of the CW721 contracts of bridged projects.
+#[async_trait]
+pub trait JunoContractQuerier: Send + Sync {
+    /// Wallet holding the token right now, as the contract `owner_of` query answers.
+    async fn owner_of(
+        &self,
+        project_id: &ProjectId,
+        token_id: &TokenId,
+        cancel: &CancellationToken,
+    ) -> Result<JunoAddress, TransactionFetchError>;
+
+    /// Tokens `owner` holds on the contract, in the contract order.
+    async fn tokens_of(
+        &self,
+        project_id: &ProjectId,
+        owner: &JunoAddress,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TokenId>, TransactionFetchError>;
+}
+
+impl Debug for dyn JunoContractQuerier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "JunoContractQuerier{{}}")
+    }
+}
+
 #[derive(Debug)]
 pub enum QueueError {
     FailedToGetBatch,
     FailedToEnqueue,
+    NotFound,
+    FailedToLockTokens,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, ToSchema)]
 pub enum QueueStatus {
     #[serde(rename = "pending")]
     Pending,
@@ -144,59 +329,238 @@ pub enum QueueStatus {
     Success,
     #[serde(rename = "error")]
     Error,
+    // Mint attempts exhausted, waiting for an operator
+    #[serde(rename = "dead_letter")]
+    DeadLetter,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, ToSchema)]
 pub struct QueueItem {
-    pub id: Option<Uuid>,
-    pub keplr_wallet_pubkey: String,
-    pub starknet_wallet_pubkey: String,
-    pub project_id: String,
-    pub token_id: String,
+    #[schema(value_type = Option<Uuid>)]
+    pub id: Option<QueueItemId>,
+    #[schema(value_type = String)]
+    pub keplr_wallet_pubkey: JunoAddress,
+    #[schema(value_type = String)]
+    pub starknet_wallet_pubkey: StarknetAddress,
+    // Starknet project contract, tokens are queued on the minting side
+    #[schema(value_type = String)]
+    pub project_id: StarknetAddress,
+    #[schema(value_type = String)]
+    pub token_id: TokenId,
     pub status: QueueStatus,
     pub transaction_hash: Option<String>,
+    // Explains why an item is still waiting, e.g. target contract is paused. Message
+    // template id rendered for customers along with `note_params`
+    pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = Object)]
+    pub note_params: BTreeMap<String, String>,
+    // Failed mint attempts so far
+    pub attempts: i32,
+    // Fees of the mint transaction in wei as decimal strings, estimated before sending
+    // it and paid once settled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_fee: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual_fee: Option<String>,
+    // Bridge request that enqueued the item, tags the worker logs minting it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// Distinct bridge requests of `items`, comma separated, `None` when none is known.
+pub fn joined_request_ids(items: &[QueueItem]) -> Option<String> {
+    let mut request_ids: Vec<&str> = items
+        .iter()
+        .filter_map(|qi| qi.request_id.as_deref())
+        .collect();
+    request_ids.sort_unstable();
+    request_ids.dedup();
+    match request_ids.is_empty() {
+        true => None,
+        false => Some(request_ids.join(",")),
+    }
 }
 
 impl QueueItem {
-    pub fn new(pubkey: &str, starknet_pubkey: &str, project_id: &str, token: String) -> Self {
+    pub fn new(
+        pubkey: &JunoAddress,
+        starknet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token: TokenId,
+    ) -> Self {
         Self {
             id: None,
-            keplr_wallet_pubkey: pubkey.into(),
-            starknet_wallet_pubkey: starknet_pubkey.into(),
-            project_id: project_id.into(),
+            keplr_wallet_pubkey: pubkey.clone(),
+            starknet_wallet_pubkey: starknet_pubkey.clone(),
+            project_id: project_id.clone(),
             token_id: token,
             status: QueueStatus::Pending,
             transaction_hash: None,
+            note: None,
+            note_params: BTreeMap::new(),
+            attempts: 0,
+            estimated_fee: None,
+            actual_fee: None,
+            request_id: None,
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct QueueItemTransition {
+    pub status: QueueStatus,
+    pub transaction_hash: Option<String>,
+    pub worker_id: Option<String>,
+    // Epoch milliseconds
+    pub created_at: i64,
+}
+
+/// Status transition as recorded in queue history, for event tailing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueueEvent {
+    pub id: Uuid,
+    pub queue_item_id: QueueItemId,
+    pub status: QueueStatus,
+    pub transaction_hash: Option<String>,
+    pub worker_id: Option<String>,
+    // Epoch milliseconds
+    pub created_at: i64,
+}
+
 #[derive(Debug)]
 pub enum QueueUpdateError {
-    StatusUpdateFail(Vec<String>),
+    StatusUpdateFail(Vec<QueueItemId>),
 }
 
 #[async_trait]
-pub trait QueueManager {
+pub trait QueueManager: Send + Sync {
+    /// Queues the tokens, a token already pending or processing comes back with its
+    /// item instead of a new one.
     async fn enqueue(
         &self,
-        keplr_wallet_pubkey: &str,
-        starknet_wallet_pubkey: &str,
-        project_id: &str,
-        token_ids: Vec<String>,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
+    ) -> Result<Vec<QueueItem>, QueueError>;
+    /// Pending items due for a mint, items of `excluded_projects` stay in the queue.
+    /// Stores shared by several workers claim the items as processing, the caller
+    /// releases the ones it does not mint.
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
     ) -> Result<Vec<QueueItem>, QueueError>;
-    async fn get_batch(&self) -> Result<Vec<QueueItem>, QueueError>;
     async fn get_customer_migration_state(
         &self,
-        keplr_wallet_pubkey: &str,
-        project_id: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
     ) -> Vec<QueueItem>;
+    /// Items of every project a wallet bridged, grouped by project.
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError>;
     async fn update_queue_items_status(
         &self,
-        ids: &Vec<String>,
+        ids: &[QueueItemId],
         transaction_hash: String,
         status: QueueStatus,
     ) -> Result<(), QueueUpdateError>;
+    /// Marks items about to be sent as processing by this worker, unless they were
+    /// requeued meanwhile, e.g. as stale, and may be minted by another worker. Stores
+    /// whose batches are not claimed also claim items still pending. Returns the items
+    /// this worker holds.
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError>;
+    async fn get_queue_item_history(
+        &self,
+        id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError>;
+    /// Puts items back to pending so they are picked up by a later batch.
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError>;
+    /// Items a worker marked as processing, left over if it stopped mid batch.
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError>;
+    /// Items this worker left processing, items any worker sent a mint transaction for,
+    /// plus the ones any worker left processing since before `stale_before` (epoch
+    /// milliseconds) when given.
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError>;
+    /// Puts items processing since before `before` (epoch milliseconds) that have no
+    /// mint transaction back to pending, e.g. when their worker crashed. Returns them.
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError>;
+    /// Items waiting to be minted, including the ones whose retry is not due yet.
+    async fn count_pending_items(&self) -> Result<i64, QueueError>;
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError>;
+    /// Marks items as failed without minting, they are not picked up anymore.
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError>;
+    /// Puts items whose mint failed back to pending, counting the attempt. They are
+    /// not part of a batch before `delay` elapsed.
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError>;
+    /// Parks items out of the queue once their attempts are exhausted.
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError>;
+    /// Brings dead letters back to pending with a fresh attempt budget.
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError>;
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError>;
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError>;
+    /// Keeps the fees of the transaction minting items along with them.
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError>;
+    /// Keeps items of the tokens out of batches while a bridge request checks them,
+    /// until `holder` unlocks them or `ttl` elapsed. Returns the tokens it locked, the
+    /// other ones are held by another request.
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError>;
+    /// Releases tokens locked by `holder`, locks taken since by another one are kept.
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError>;
 }
 
 impl Debug for dyn QueueManager {
@@ -205,27 +569,110 @@ impl Debug for dyn QueueManager {
     }
 }
 
+#[derive(Debug)]
+pub enum MintStatusError {
+    /// Starknet could not be asked, e.g. during a gateway outage
+    Unavailable(String),
+}
+
 pub enum MintError {
     Failure,
+    ContractPaused,
+    /// Network fees are over what the fee strategy pays, nothing was sent
+    FeeAboveCap,
+    /// A call reverted while simulating the transaction, nothing was sent
+    Reverted,
+}
+
+/// Mint transaction sent to Starknet.
+#[derive(Debug, Clone)]
+pub struct MintSubmission {
+    pub transaction_hash: String,
+    /// Fee estimated before sending the transaction, in wei as a decimal string
+    pub estimated_fee: Option<String>,
 }
 
 // First string is transaction_hash while second is the optionnal error result
 pub type MintTransactionResult = (String, Option<String>);
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionOutcome {
+    Accepted,
+    // Optional failure reason code
+    Rejected(Option<String>),
+    Pending,
+    NotReceived,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionFinality {
+    Pending,
+    Accepted,
+    Rejected,
+    NotReceived,
+}
+
+/// Finality of a mint transaction, polled by the frontend until it settles.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct TransactionStatusResponse {
+    #[schema(value_type = String)]
+    pub transaction_hash: TransactionHash,
+    pub status: TransactionFinality,
+    /// Failure code of a rejected transaction, when Starknet gave one
+    pub failure_reason: Option<String>,
+}
+
+impl TransactionStatusResponse {
+    pub fn new(transaction_hash: TransactionHash, outcome: TransactionOutcome) -> Self {
+        let (status, failure_reason) = match outcome {
+            TransactionOutcome::Accepted => (TransactionFinality::Accepted, None),
+            TransactionOutcome::Rejected(reason) => (TransactionFinality::Rejected, reason),
+            TransactionOutcome::Pending => (TransactionFinality::Pending, None),
+            TransactionOutcome::NotReceived => (TransactionFinality::NotReceived, None),
+        };
+
+        Self {
+            transaction_hash,
+            status,
+            failure_reason,
+        }
+    }
+}
+
 #[async_trait]
-pub trait StarknetManager {
-    async fn project_has_token(&self, project_id: &str, token_id: &str) -> bool;
+pub trait StarknetManager: Send + Sync {
+    async fn project_has_token(
+        &self,
+        project_id: &StarknetAddress,
+        token_id: &TokenId,
+    ) -> Result<bool, MintStatusError>;
+    async fn project_is_paused(&self, project_id: &StarknetAddress) -> bool;
+    async fn get_transaction_status(&self, transaction_hash: &str) -> Option<String>;
+    async fn get_transaction_outcome(&self, transaction_hash: &str) -> TransactionOutcome;
+    /// Actual fee paid by a settled transaction in wei, as a decimal string.
+    async fn get_transaction_fee(&self, transaction_hash: &str) -> Option<String>;
+    /// Polls transaction until it is either accepted or rejected, gives up with
+    /// `Pending` as soon as `cancel` fires.
+    async fn wait_for_transaction(
+        &self,
+        transaction_hash: &str,
+        cancel: &CancellationToken,
+    ) -> TransactionOutcome;
+    /// Mint calls a single transaction may hold before hitting calldata or step limits,
+    /// larger batches are sent in several transactions.
+    fn max_calls_per_transaction(&self) -> usize;
     async fn mint_project_token(
         &self,
-        project_id: &str,
-        tokens: &[String],
-        starknet_account_addr: &str,
+        project_id: &StarknetAddress,
+        tokens: &[TokenId],
+        starknet_account_addr: &StarknetAddress,
     ) -> Result<String, MintError>;
     async fn batch_mint_tokens(
         &self,
-        project_id: &str,
+        project_id: &StarknetAddress,
         queue_items: Vec<QueueItem>,
-    ) -> Result<(String, QueueStatus), MintError>;
+    ) -> Result<MintSubmission, MintError>;
 }
 impl Debug for dyn StarknetManager {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
@@ -233,33 +680,282 @@ impl Debug for dyn StarknetManager {
     }
 }
 
-type MintPreChecks = HashMap<String, (String, Option<String>)>;
+/// Every reason a token can fail pre-mint checks. Messages are what customers get
+/// in `BridgeResponse.checks`, so changing one is a frontend breaking change.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenCheckCode {
+    JunoFetchFailed,
+    JunoDeserializationFailed,
+    JunoServerError,
+    JunoResponseTooLarge,
+    TransactionNotFound,
+    NotTransferredToAdmin,
+    NotHeldByAdmin,
+    NotBurnt,
+    TransferOutsideWindow,
+    SenderMismatch,
+    AlreadyMinted,
+    ChecksIncomplete,
+}
+
+impl TokenCheckCode {
+    pub const ALL: [TokenCheckCode; 12] = [
+        TokenCheckCode::JunoFetchFailed,
+        TokenCheckCode::JunoDeserializationFailed,
+        TokenCheckCode::JunoServerError,
+        TokenCheckCode::JunoResponseTooLarge,
+        TokenCheckCode::TransactionNotFound,
+        TokenCheckCode::NotTransferredToAdmin,
+        TokenCheckCode::NotHeldByAdmin,
+        TokenCheckCode::NotBurnt,
+        TokenCheckCode::TransferOutsideWindow,
+        TokenCheckCode::SenderMismatch,
+        TokenCheckCode::AlreadyMinted,
+        TokenCheckCode::ChecksIncomplete,
+    ];
+
+    pub fn default_message(&self) -> &'static str {
+        match self {
+            TokenCheckCode::JunoFetchFailed => "Failed to fecth token data from juno chain.",
+            TokenCheckCode::JunoDeserializationFailed => {
+                "Failed to deserialize data from juno blockchain"
+            }
+            TokenCheckCode::JunoServerError => {
+                "Juno node responded with an error status please try again later"
+            }
+            TokenCheckCode::JunoResponseTooLarge => {
+                "Juno node response is too large to check this token"
+            }
+            TokenCheckCode::TransactionNotFound => "Transaction not found on chain.",
+            TokenCheckCode::NotTransferredToAdmin => "Token was not transfered to admin",
+            TokenCheckCode::NotHeldByAdmin => "Token is not held by admin wallet",
+            TokenCheckCode::NotBurnt => "Token was not burnt",
+            TokenCheckCode::TransferOutsideWindow => {
+                "Token was transferred outside the project migration window"
+            }
+            TokenCheckCode::SenderMismatch => {
+                "Token sender didn't match customer wallet public key"
+            }
+            TokenCheckCode::AlreadyMinted => "Token has already been minted",
+            TokenCheckCode::ChecksIncomplete => "Checks incomplete, please retry",
+        }
+    }
+
+    pub fn from_message(message: &str) -> Option<Self> {
+        TokenCheckCode::ALL
+            .into_iter()
+            .find(|c| c.default_message() == message)
+    }
+}
+
+impl QueueStatus {
+    pub const ALL: [QueueStatus; 5] = [
+        QueueStatus::Pending,
+        QueueStatus::Processing,
+        QueueStatus::Success,
+        QueueStatus::Error,
+        QueueStatus::DeadLetter,
+    ];
+}
+
+#[derive(Serialize, Debug)]
+pub struct CheckCodeDescription {
+    pub code: TokenCheckCode,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CheckCodesMeta {
+    pub check_codes: Vec<CheckCodeDescription>,
+    pub queue_statuses: Vec<QueueStatus>,
+}
+
+pub fn check_codes_meta() -> CheckCodesMeta {
+    CheckCodesMeta {
+        check_codes: TokenCheckCode::ALL
+            .into_iter()
+            .map(|code| CheckCodeDescription {
+                code,
+                message: code.default_message().into(),
+            })
+            .collect(),
+        queue_statuses: QueueStatus::ALL.to_vec(),
+    }
+}
+
+type MintPreChecks = HashMap<TokenId, (TokenId, Option<String>)>;
+
+/// Why checks of a token stopped, cancellation aborts the whole request.
+enum CheckFailure {
+    Token(TokenCheckCode),
+    Cancelled,
+}
+
+fn juno_fetch_failure(e: TransactionFetchError) -> CheckFailure {
+    match e {
+        TransactionFetchError::FetchError(_) => {
+            CheckFailure::Token(TokenCheckCode::JunoFetchFailed)
+        }
+        TransactionFetchError::DeserializationFailed => {
+            CheckFailure::Token(TokenCheckCode::JunoDeserializationFailed)
+        }
+        TransactionFetchError::JunoBlockchainServerError(_e) => {
+            CheckFailure::Token(TokenCheckCode::JunoServerError)
+        }
+        TransactionFetchError::ResponseTooLarge => {
+            CheckFailure::Token(TokenCheckCode::JunoResponseTooLarge)
+        }
+        TransactionFetchError::Cancelled => CheckFailure::Cancelled,
+    }
+}
+
 // Represents the response as [token_ids], Transaction hash
-type MintResult = (Vec<String>, String);
+type MintResult = (Vec<TokenId>, String);
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct BridgeResponse {
+    /// Token id to `[token id, failed check message or null]`.
+    #[schema(value_type = Object)]
     pub checks: MintPreChecks,
+    /// `[[enqueued token ids], transaction hash]`.
+    #[schema(value_type = Object)]
     pub result: MintResult,
 }
-pub async fn handle_bridge_request<'a, 'b, 'c, 'd, 'e>(
+pub async fn handle_bridge_request<'a, 'b, 'c, 'd, 'e, 'f, 'g>(
     req: &BridgeRequest,
-    keplr_admin_wallet: &str,
+    project_registry: &ProjectRegistry,
+    clock: &dyn Clock,
     starknet_admin_address: &str,
     hash_validator: Arc<dyn SignedHashValidator + 'a>,
     transaction_repository: Arc<dyn TransactionRepository + 'b>,
+    ownership_querier: Option<&dyn JunoContractQuerier>,
+    transfer_proof_repository: Arc<dyn TransferProofRepository + 'g>,
     starknet_manager: Arc<dyn StarknetManager + 'c>,
     data_repository: Arc<dyn DataRepository + 'd>,
     queue_manager: Arc<dyn QueueManager + 'e>,
+    wallet_link_repository: Arc<dyn WalletLinkRepository + 'f>,
+    challenges: &ChallengeService,
+    require_sign_doc: bool,
+    check_cache: &CheckResultCache,
+    backpressure: Option<&QueueBackpressure>,
+    cancel: &CancellationToken,
+    budget: Duration,
 ) -> Result<BridgeResponse, BridgeError> {
+    // Upstream calls share the budget, tokens left when it runs out are reported as
+    // incomplete instead of holding the connection
+    let deadline = Instant::now() + budget;
+    let signed_message = match (&req.sign_doc, &req.nonce) {
+        (Some(sign_doc), _) => {
+            match serde_json::from_str::<BridgeSignDoc>(sign_doc) {
+                Ok(doc) if doc.covers(req) => (),
+                Ok(_) => return Err(BridgeError::SignDocMismatch),
+                Err(e) => {
+                    warn!(
+                        "Unreadable sign doc from {} : {:#?}",
+                        &req.keplr_wallet_pubkey, e
+                    );
+                    return Err(BridgeError::SignDocMismatch);
+                }
+            };
+            sign_doc.to_string()
+        }
+        (None, _) if require_sign_doc => return Err(BridgeError::SignDocRequired),
+        (None, Some(nonce)) => challenge_message(
+            nonce,
+            &req.starknet_account_addr,
+            &req.project_id,
+            req.tokens_id.as_deref().unwrap_or_default(),
+        ),
+        (None, None) => starknet_admin_address.to_string(),
+    };
     match hash_validator.verify(
         &req.signed_hash,
-        &starknet_admin_address,
-        &req.keplr_wallet_pubkey,
+        &signed_message,
+        req.keplr_wallet_pubkey.as_str(),
     ) {
         Ok(h) => h,
         Err(_err) => return Err(BridgeError::InvalidSign),
     };
+    // Nonce is only burnt once the signature proved the customer asked for it
+    match challenges
+        .redeem(req.nonce.as_deref(), &req.keplr_wallet_pubkey)
+        .await
+    {
+        Ok(_) => (),
+        Err(ChallengeError::Expired) => return Err(BridgeError::ChallengeExpired),
+        Err(ChallengeError::PersistenceIssue) => return Err(BridgeError::ChallengeIssue),
+        Err(e) => {
+            error!(
+                "Refusing challenge of {} : {:#?}",
+                &req.keplr_wallet_pubkey, e
+            );
+            return Err(BridgeError::InvalidChallenge);
+        }
+    };
+
+    let Some(project) = project_registry.get(&req.project_id) else {
+        error!("Project {} is not registered", &req.project_id);
+        return Err(BridgeError::UnknownProject(req.project_id.to_string()));
+    };
+    if !project.migration_window.is_open(clock.now_ms()) {
+        warn!(
+            "Refusing bridge request of {} on project {} outside its migration window",
+            &req.keplr_wallet_pubkey, &req.project_id
+        );
+        return Err(BridgeError::MigrationWindowClosed(project.migration_window));
+    }
+    if let Some(requested) = &req.starknet_project_addr {
+        if requested != &project.starknet_contract {
+            warn!(
+                "Request targets starknet contract {} for project {}, using registered {}",
+                requested, &req.project_id, project.starknet_contract
+            );
+        }
+    }
+    let keplr_admin_wallet = project.juno_admin_address.as_str();
+
+    // First verified request links the keplr wallet to the starknet account.
+    // Later requests cannot target another account unless customer re-links it.
+    let link = match wallet_link_repository
+        .get_link(&req.keplr_wallet_pubkey)
+        .await
+    {
+        Ok(link) => link,
+        // Never overwrites a link, a concurrent request may have saved another one
+        Err(WalletLinkError::NotFound) => match wallet_link_repository
+            .link_if_absent(WalletLink {
+                keplr_wallet_pubkey: req.keplr_wallet_pubkey.clone(),
+                starknet_account_addr: req.starknet_account_addr.clone(),
+            })
+            .await
+        {
+            Ok(link) => link,
+            Err(e) => {
+                error!(
+                    "Failed to link keplr wallet {} {:#?}",
+                    &req.keplr_wallet_pubkey, e
+                );
+                return Err(BridgeError::WalletLinkIssue);
+            }
+        },
+        Err(e) => {
+            error!(
+                "Failed to fetch link of keplr wallet {} {:#?}",
+                &req.keplr_wallet_pubkey, e
+            );
+            return Err(BridgeError::WalletLinkIssue);
+        }
+    };
+    if link.starknet_account_addr != req.starknet_account_addr {
+        error!(
+            "Keplr wallet {} is linked to {} but request targets {}",
+            &req.keplr_wallet_pubkey, link.starknet_account_addr, req.starknet_account_addr
+        );
+        return Err(BridgeError::StarknetAccountMismatch(
+            link.starknet_account_addr.to_string(),
+        ));
+    }
 
     // Fetch token from wallet id from database
     let tokens = match data_repository
@@ -286,143 +982,333 @@ pub async fn handle_bridge_request<'a, 'b, 'c, 'd, 'e>(
             _ => req_token.to_vec(),
         };
 
-        info!("Migrating tokens : [{}]", token_ids.join(", "));
-        let mut checked_tokens = HashMap::new();
-        for token in &token_ids {
-            let transactions = transaction_repository
-                .get_transactions_for_contract(&req.project_id, token.as_str())
+        info!(
+            "Migrating tokens : [{}]",
+            token_ids
+                .iter()
+                .map(TokenId::as_str)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        );
+        // Items of the tokens already queued stay out of batches while they are checked
+        // again, a worker would otherwise mint them as this request enqueues them
+        let lock_holder = Uuid::new_v4().to_string();
+        let locked_tokens = match queue_manager
+            .lock_tokens(
+                &project.starknet_contract,
+                &token_ids,
+                &lock_holder,
+                budget + TOKEN_LOCK_MARGIN,
+            )
+            .await
+        {
+            Ok(l) => l,
+            Err(e) => {
+                error!("Failed to lock tokens {:#?}", e);
+                return Err(BridgeError::EnqueueingIssue);
+            }
+        };
+        let response = async {
+            // Tokens may have been transferred from a multisig / DAO account on behalf of
+            // the customer
+            let authorized_senders = data_repository
+                .get_authorized_senders(&req.keplr_wallet_pubkey, &req.project_id)
+                .await
+                .unwrap_or_default();
+            // Customer retrying within minutes gets the results of its previous call
+            let cached_checks = check_cache
+                .lookup(&req.keplr_wallet_pubkey, &req.project_id, &token_ids)
                 .await;
-            if transactions.is_err() {
-                match transactions.unwrap_err() {
-                    TransactionFetchError::FetchError(_) => {
-                        checked_tokens.insert(
-                            token.to_string(),
-                            (
-                                token.to_string(),
-                                Some("Failed to fecth token data from juno chain.".into()),
+            let mut checks_to_cache = Vec::new();
+            let mut checked_tokens = HashMap::new();
+            let mut unchecked_tokens = Vec::new();
+            for token in &token_ids {
+                // Nothing is enqueued yet, customer can safely retry an aborted request
+                if cancel.is_cancelled() {
+                    return Err(BridgeError::Cancelled);
+                }
+                if !locked_tokens.contains(token) {
+                    warn!("Token {} is being checked by another request", token);
+                    let failed_check = TokenCheckCode::ChecksIncomplete.default_message().into();
+                    checked_tokens.insert(token.clone(), (token.clone(), Some(failed_check)));
+                    continue;
+                }
+                if let Some(cached) = cached_checks.get(token) {
+                    info!(
+                        "Reusing checks of token {} for {} made at {}",
+                        token, &req.keplr_wallet_pubkey, cached.checked_at
+                    );
+                    let failed_check = cached.failure.map(|code| code.default_message().into());
+                    checked_tokens.insert(token.clone(), (token.clone(), failed_check));
+                    continue;
+                }
+                let mut checked_height = None;
+                let history = async {
+                    // A proven transfer is final, no need to ask the node again
+                    let proof = match transfer_proof_repository
+                        .get_proof(&req.project_id, token)
+                        .await
+                    {
+                        Ok(p) => p,
+                        Err(e) => {
+                            warn!("Failed to fetch token {} transfer proof {:#?}", token, e);
+                            None
+                        }
+                    };
+                    let proven = proof.is_some();
+                    let transactions = match proof {
+                        Some(proof) => vec![proof.transaction()],
+                        None => match timeout_at(
+                            deadline,
+                            transaction_repository.get_transactions_for_contract(
+                                &req.project_id,
+                                token,
+                                cancel,
                             ),
+                        )
+                        .await
+                        {
+                            Ok(Ok(t)) => t,
+                            Ok(Err(e)) => return Err(juno_fetch_failure(e)),
+                            Err(_) => {
+                                warn!(
+                                    "Bridge request budget exhausted before checking token {}",
+                                    token
+                                );
+                                return Err(CheckFailure::Token(TokenCheckCode::ChecksIncomplete));
+                            }
+                        },
+                    };
+
+                    if transactions.is_empty() {
+                        error!(
+                            "No transactions found on juno chain for wallet {} and project {}",
+                            &req.keplr_wallet_pubkey, &req.project_id
                         );
-                        continue;
+                        return Err(CheckFailure::Token(TokenCheckCode::TransactionNotFound));
                     }
-                    TransactionFetchError::DeserializationFailed => {
-                        checked_tokens.insert(
-                            token.to_string(),
-                            (
-                                token.to_string(),
-                                Some("Failed to deserialize data from juno blockchain".into()),
-                            ),
+                    // Last transaction at index 0 should have admin wallet as recipient, or
+                    // burn the token on projects migrating by burn.
+                    // Only checking transaction at index 0 as this is the last transaction done
+                    // on given token.
+                    checked_height = transactions[0].height;
+                    match project.migration_policy {
+                        MigrationPolicy::TransferToAdmin
+                            if transactions[0].msg.recipient() != Some(keplr_admin_wallet) =>
+                        {
+                            error!(
+                                "Token id {} last owner is not admin : {}",
+                                token, keplr_admin_wallet
+                            );
+                            return Err(CheckFailure::Token(TokenCheckCode::NotTransferredToAdmin));
+                        }
+                        MigrationPolicy::Burn
+                            if !matches!(transactions[0].msg, MsgTypes::Burn(_)) =>
+                        {
+                            error!("Token id {} has not been burnt", token);
+                            return Err(CheckFailure::Token(TokenCheckCode::NotBurnt));
+                        }
+                        _ => (),
+                    }
+                    if !project
+                        .transfer_window
+                        .contains(transactions[0].height, transactions[0].block_time)
+                    {
+                        error!(
+                            "Token id {} was transferred outside the window of the project",
+                            token
                         );
-                        continue;
+                        return Err(CheckFailure::Token(TokenCheckCode::TransferOutsideWindow));
                     }
-                    TransactionFetchError::JunoBlockchainServerError(_e) => {
-                        checked_tokens.insert(token.to_string(),(
-                        token.to_string(),
-                        Some("Juno node responded with an error status please try again later".into()),
-                    ));
-                        continue;
+                    let sender = transactions[0].sender.as_str();
+                    if req.keplr_wallet_pubkey != sender
+                        && authorized_senders.iter().any(|s| s == sender)
+                    {
+                        info!(
+                            "AUDIT - token id {} transferred by authorized sender {} \
+                            on behalf of {}",
+                            token, sender, req.keplr_wallet_pubkey
+                        );
+                    } else if req.keplr_wallet_pubkey != sender {
+                        error!(
+                            "Token id {} sender does not match given wallet pubkey {}",
+                            token, req.keplr_wallet_pubkey
+                        );
+                        return Err(CheckFailure::Token(TokenCheckCode::SenderMismatch));
                     }
-                };
-            }
 
-            if let Ok(t) = transactions {
-                if 0 == t.len() {
-                    error!(
-                        "No transactions found on juno chain for wallet {} and project {}",
-                        &req.keplr_wallet_pubkey, &req.project_id
-                    );
-                    checked_tokens.insert(
-                        token.to_string(),
-                        (
-                            token.to_string(),
-                            Some("Transaction not found on chain.".into()),
-                        ),
-                    );
-                    continue;
-                }
-                // Last transaction at index 0 should have admin wallet as recipient
-                // Only checking transaction at index 0 as this is the last transaction done
-                // on given token.
-                let admin_transfert = match &t[0].msg {
-                    MsgTypes::TransferNft(t) => t,
+                    if !proven {
+                        let proof = TransferProof::new(
+                            &req.project_id,
+                            token,
+                            &transactions[0],
+                            clock.now_ms(),
+                        );
+                        if let Err(e) = transfer_proof_repository.save_proof(&proof).await {
+                            warn!("Failed to save token {} transfer proof {:#?}", token, e);
+                        }
+                    }
+
+                    Ok(())
                 };
 
-                if admin_transfert.recipient != keplr_admin_wallet {
-                    error!(
-                        "Token id {} last owner is not admin : {}",
-                        token, keplr_admin_wallet
-                    );
-                    checked_tokens.insert(
-                        token.to_string(),
-                        (
-                            token.to_string(),
-                            Some("Token was not transfered to admin".into()),
-                        ),
-                    );
-                    continue;
-                }
-                if t[0].sender != req.keplr_wallet_pubkey {
-                    error!(
-                        "Token id {} sender does not match given wallet pubkey {}",
-                        token, req.keplr_wallet_pubkey
-                    );
-                    checked_tokens.insert(
-                        token.to_string(),
-                        (
-                            token.to_string(),
-                            Some("Token sender didn't match customer wallet public key".into()),
-                        ),
-                    );
-                    continue;
-                }
+                // If token has already been minted, customer needs to know. Resolves to
+                // whether the check could not be made.
+                let minted = async {
+                    let Ok(already_minted) = timeout_at(
+                        deadline,
+                        starknet_manager.project_has_token(&project.starknet_contract, token),
+                    )
+                    .await
+                    else {
+                        warn!(
+                            "Bridge request budget exhausted before checking token {}",
+                            token
+                        );
+                        return Err(CheckFailure::Token(TokenCheckCode::ChecksIncomplete));
+                    };
+                    match already_minted {
+                        Ok(true) => {
+                            error!("Token id {} has already been minted", token);
+                            Err(CheckFailure::Token(TokenCheckCode::AlreadyMinted))
+                        }
+                        Ok(false) => Ok(false),
+                        // Degraded mode, the worker checks again before minting
+                        Err(e) => {
+                            warn!(
+                                "Mint status of token {} unavailable, \
+                                leaving the check to the worker : {:#?}",
+                                token, e
+                            );
+                            Ok(true)
+                        }
+                    }
+                };
 
-                // If token has already been minted, customer needs to know
-                if starknet_manager
-                    .project_has_token(&req.starknet_project_addr, token)
+                // Transfer history misses messages its parser does not model, the contract
+                // tells who holds the token now
+                // Burnt tokens have no owner left to ask about
+                let held = async {
+                    let Some(querier) = ownership_querier
+                        .filter(|_| project.migration_policy == MigrationPolicy::TransferToAdmin)
+                    else {
+                        return Ok(());
+                    };
+                    let owner = match timeout_at(
+                        deadline,
+                        querier.owner_of(&req.project_id, token, cancel),
+                    )
                     .await
-                {
-                    error!("Token id {} has already been minted", token);
-                    checked_tokens.insert(
-                        token.to_string(),
-                        (
-                            token.to_string(),
-                            Some("Token has already been minted".into()),
-                        ),
-                    );
-                    continue;
-                }
+                    {
+                        Ok(Ok(owner)) => owner,
+                        Ok(Err(e)) => return Err(juno_fetch_failure(e)),
+                        Err(_) => {
+                            warn!(
+                                "Bridge request budget exhausted before checking token {}",
+                                token
+                            );
+                            return Err(CheckFailure::Token(TokenCheckCode::ChecksIncomplete));
+                        }
+                    };
+                    if owner != keplr_admin_wallet {
+                        error!(
+                            "Token id {} is held by {} instead of admin {}",
+                            token, owner, keplr_admin_wallet
+                        );
+                        return Err(CheckFailure::Token(TokenCheckCode::NotHeldByAdmin));
+                    }
+                    Ok(())
+                };
 
-                checked_tokens.insert(token.to_string(), (token.to_string(), None));
+                // Calls are independent, the first failure drops the other ones
+                let failure = match tokio::try_join!(history, minted, held) {
+                    Ok((_, false, _)) => None,
+                    Ok((_, true, _)) => {
+                        unchecked_tokens.push(token.clone());
+                        None
+                    }
+                    Err(CheckFailure::Token(code)) => Some(code),
+                    Err(CheckFailure::Cancelled) => return Err(BridgeError::Cancelled),
+                };
+                // Unchecked tokens are checked again by the next request
+                if !unchecked_tokens.contains(token) {
+                    checks_to_cache.push((token.clone(), checked_height, failure));
+                }
+                let failed_check = failure.map(|code| code.default_message().into());
+                checked_tokens.insert(token.clone(), (token.clone(), failed_check));
             }
-        }
+            check_cache
+                .store(&req.keplr_wallet_pubkey, &req.project_id, &checks_to_cache)
+                .await;
 
-        let mut token_to_mint = Vec::new();
-        for (token, (_msg, err)) in checked_tokens.iter() {
-            if err.is_none() {
-                token_to_mint.push(token.to_string());
+            let mut token_to_mint = Vec::new();
+            for (token, (_msg, err)) in checked_tokens.iter() {
+                if err.is_none() {
+                    token_to_mint.push(token.clone());
+                }
+            }
+            // Checks are cached, the retry only waits for the queue
+            if let Some(backpressure) = backpressure.filter(|_| !token_to_mint.is_empty()) {
+                if let Some(retry_after) = backpressure.retry_after(queue_manager.as_ref()).await {
+                    return Err(BridgeError::QueueFull(retry_after));
+                }
+            }
+            let queue_items = match queue_manager
+                .enqueue(
+                    &req.keplr_wallet_pubkey,
+                    &req.starknet_account_addr,
+                    &project.starknet_contract,
+                    token_to_mint.clone(),
+                    current_log_fields().request_id.as_deref(),
+                )
+                .await
+            {
+                Ok(qi) => qi,
+                Err(e) => match e {
+                    _ => return Err(BridgeError::EnqueueingIssue),
+                },
+            };
+            // Tokens already queued come back with their item, a worker may be minting them
+            let unchecked_ids: Vec<QueueItemId> = queue_items
+                .iter()
+                .filter(|qi| unchecked_tokens.contains(&qi.token_id))
+                .filter(|qi| matches!(qi.status, QueueStatus::Pending))
+                .filter_map(|qi| qi.id)
+                .collect();
+            if !unchecked_ids.is_empty() {
+                warn!(
+                    "Enqueued {} tokens of {} without checking their mint status",
+                    unchecked_ids.len(),
+                    &req.keplr_wallet_pubkey
+                );
+                let note = StatusNote::new(UNCHECKED_MINT_STATUS_NOTE);
+                if let Err(e) = queue_manager.defer_queue_items(&unchecked_ids, &note).await {
+                    error!("Failed to flag unchecked queue items {:#?}", e);
+                }
             }
+
+            Ok(BridgeResponse {
+                checks: checked_tokens,
+                result: (
+                    token_to_mint.to_vec(),
+                    "Your token(s) migration have been queued in. \
+                    You can stay on this page to check the queueing status."
+                        .to_string(),
+                ),
+            })
         }
-        let _queue_items = match queue_manager
-            .enqueue(
-                &req.keplr_wallet_pubkey,
-                &req.starknet_account_addr,
-                &req.starknet_project_addr,
-                token_to_mint.clone(),
-            )
+        .await;
+        if let Err(e) = queue_manager
+            .unlock_tokens(&project.starknet_contract, &locked_tokens, &lock_holder)
             .await
         {
-            Ok(qi) => qi,
-            Err(e) => match e {
-                _ => return Err(BridgeError::EnqueueingIssue),
-            },
-        };
+            warn!(
+                "Failed to unlock tokens, their lock expires by itself {:#?}",
+                e
+            );
+        }
 
-        return Ok(BridgeResponse {
-            checks: checked_tokens,
-            result: (
-                token_to_mint.iter().map(|t| t.to_string()).collect(),
-                "Your token(s) migration have been queued in. You can stay on this page to check the queueing status.".to_string(),
-            ),
-        });
+        return response;
     }
 
     Err(BridgeError::FetchTokenError(
@@ -0,0 +1,98 @@
+/// Converts days elapsed since epoch to a (year, month, day) civil date.
+pub fn civil_from_days(days_since_epoch: i64) -> (i64, i64, i64) {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days_since_epoch + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// Formats days elapsed since epoch as YYYY-MM-DD.
+pub fn format_day(days_since_epoch: i64) -> String {
+    let (year, month, day) = civil_from_days(days_since_epoch);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats epoch milliseconds as an RFC 3339 UTC timestamp, e.g. 2023-11-14T22:13:20.000Z.
+pub fn format_timestamp(epoch_ms: i64) -> String {
+    let (year, month, day) = civil_from_days(epoch_ms.div_euclid(86_400_000));
+    let ms_of_day = epoch_ms.rem_euclid(86_400_000);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1_000 % 60,
+        ms_of_day % 1_000
+    )
+}
+
+/// Converts a (year, month, day) civil date to days elapsed since epoch.
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+/// Parses a UTC date (2024-01-31) or RFC 3339 UTC timestamp (2024-01-31T12:00:00Z,
+/// milliseconds optional) to epoch milliseconds.
+pub fn parse_timestamp(value: &str) -> Option<i64> {
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time.strip_suffix('Z')?)),
+        None => (value, None),
+    };
+    let mut date_parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (
+        date_parts.next()??,
+        date_parts.next()??,
+        date_parts.next()??,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    if civil_from_days(days) != (year, month, day) {
+        return None;
+    }
+
+    let ms_of_day = match time {
+        None => 0,
+        Some(time) => {
+            let (time, ms) = match time.split_once('.') {
+                Some((time, ms)) if ms.len() == 3 => (time, ms.parse::<i64>().ok()?),
+                Some(_) => return None,
+                None => (time, 0),
+            };
+            let mut time_parts = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+            let (hours, minutes, seconds) = (
+                time_parts.next()??,
+                time_parts.next()??,
+                time_parts.next()??,
+            );
+            if !(0..24).contains(&hours)
+                || !(0..60).contains(&minutes)
+                || !(0..60).contains(&seconds)
+            {
+                return None;
+            }
+            ((hours * 60 + minutes) * 60 + seconds) * 1_000 + ms
+        }
+    };
+
+    Some(days * 86_400_000 + ms_of_day)
+}
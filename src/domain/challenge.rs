@@ -0,0 +1,149 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::warn;
+use serde_derive::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    clock::Clock,
+    ids::{JunoAddress, ProjectId, StarknetAddress, TokenId},
+};
+
+/// One-time nonce a customer has to sign along with its request, so a captured
+/// signature cannot be replayed.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct Challenge {
+    pub nonce: String,
+    #[schema(value_type = String)]
+    pub keplr_wallet_pubkey: JunoAddress,
+    /// Epoch seconds
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChallengeRequest {
+    pub keplr_wallet_pubkey: JunoAddress,
+}
+
+#[derive(Debug)]
+pub enum ChallengeError {
+    Missing,
+    NotFound,
+    AlreadyUsed,
+    Expired,
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait ChallengeRepository: Send + Sync {
+    async fn save_challenge(&self, challenge: &Challenge) -> Result<(), ChallengeError>;
+    /// Marks the nonce as used and returns it, a nonce can only be consumed once and
+    /// only by the wallet it was issued to.
+    async fn consume_challenge(
+        &self,
+        nonce: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Challenge, ChallengeError>;
+}
+
+impl Debug for dyn ChallengeRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ChallengeRepository{{}}")
+    }
+}
+
+/// What the keplr wallet signs : nonce, starknet account, project and requested tokens.
+pub fn challenge_message(
+    nonce: &str,
+    starknet_account_addr: &StarknetAddress,
+    project_id: &ProjectId,
+    token_ids: &[TokenId],
+) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        nonce,
+        starknet_account_addr,
+        project_id,
+        token_ids
+            .iter()
+            .map(TokenId::as_str)
+            .collect::<Vec<&str>>()
+            .join(",")
+    )
+}
+
+pub struct ChallengeService {
+    repository: Arc<dyn ChallengeRepository>,
+    clock: Arc<dyn Clock>,
+    ttl: Duration,
+    // Requests without nonce are only accepted when legacy frontends are allowed
+    required: bool,
+}
+
+impl ChallengeService {
+    pub fn new(
+        repository: Arc<dyn ChallengeRepository>,
+        clock: Arc<dyn Clock>,
+        ttl: Duration,
+        required: bool,
+    ) -> Self {
+        Self {
+            repository,
+            clock,
+            ttl,
+            required,
+        }
+    }
+
+    pub async fn issue(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Challenge, ChallengeError> {
+        let challenge = Challenge {
+            nonce: Uuid::new_v4().simple().to_string(),
+            keplr_wallet_pubkey: keplr_wallet_pubkey.clone(),
+            expires_at: self.clock.now_secs() + self.ttl.as_secs() as i64,
+        };
+        self.repository.save_challenge(&challenge).await?;
+
+        Ok(challenge)
+    }
+
+    /// Consumes the nonce of a request whose signature has been verified. Returns
+    /// `Ok(false)` for legacy requests without nonce while challenges are optional.
+    pub async fn redeem(
+        &self,
+        nonce: Option<&str>,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<bool, ChallengeError> {
+        let Some(nonce) = nonce else {
+            if self.required {
+                return Err(ChallengeError::Missing);
+            }
+            warn!(
+                "Accepting request of {} without challenge nonce",
+                keplr_wallet_pubkey
+            );
+            return Ok(false);
+        };
+
+        // Nonces of another wallet are left untouched for their owner
+        let challenge = self
+            .repository
+            .consume_challenge(nonce, keplr_wallet_pubkey)
+            .await?;
+        if challenge.expires_at <= self.clock.now_secs() {
+            return Err(ChallengeError::Expired);
+        }
+
+        Ok(true)
+    }
+}
+
+impl Debug for ChallengeService {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ChallengeService{{required: {}}}", self.required)
+    }
+}
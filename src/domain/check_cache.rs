@@ -0,0 +1,145 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::warn;
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use super::{
+    bridge::TokenCheckCode,
+    clock::Clock,
+    ids::{JunoAddress, ProjectId, TokenId},
+};
+
+/// Outcome of the pre-mint checks of a token for one customer, computed against the
+/// Juno transfer found at `height` (unknown when no transfer was found).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckResult {
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub project_id: ProjectId,
+    pub token_id: TokenId,
+    pub height: Option<i64>,
+    pub failure: Option<TokenCheckCode>,
+    // Epoch milliseconds
+    pub checked_at: i64,
+}
+
+#[derive(Debug)]
+pub enum CheckCacheError {
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait CheckResultRepository: Send + Sync {
+    /// Latest result of each token checked at `since` or later.
+    async fn get_results(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        token_ids: &[TokenId],
+        since: i64,
+    ) -> Result<Vec<CheckResult>, CheckCacheError>;
+    /// Upserts results by wallet, project, token and height, dropping results older
+    /// than `expired_before`.
+    async fn save_results(
+        &self,
+        results: &[CheckResult],
+        expired_before: i64,
+    ) -> Result<(), CheckCacheError>;
+}
+
+impl Debug for dyn CheckResultRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "CheckResultRepository{{}}")
+    }
+}
+
+/// Remembers check results of `/bridge` calls for a few minutes, so customers retrying
+/// a request do not trigger the same LCD and Starknet calls again.
+#[derive(Debug)]
+pub struct CheckResultCache {
+    repository: Arc<dyn CheckResultRepository>,
+    clock: Arc<dyn Clock>,
+    // Zero disables the cache
+    ttl: Duration,
+}
+
+impl CheckResultCache {
+    pub fn new(
+        repository: Arc<dyn CheckResultRepository>,
+        clock: Arc<dyn Clock>,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            repository,
+            clock,
+            ttl,
+        }
+    }
+
+    /// Only outcomes that cannot change within minutes are kept. Failures a new Juno
+    /// transfer can fix, transient node failures and exhausted budgets are checked again.
+    pub fn is_cacheable(failure: Option<TokenCheckCode>) -> bool {
+        matches!(failure, None | Some(TokenCheckCode::AlreadyMinted))
+    }
+
+    /// Fresh results by token, failures to read the cache only cost a new check.
+    pub async fn lookup(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        token_ids: &[TokenId],
+    ) -> HashMap<TokenId, CheckResult> {
+        if self.ttl.is_zero() || token_ids.is_empty() {
+            return HashMap::new();
+        }
+        let since = self.clock.now_ms() - self.ttl.as_millis() as i64;
+        match self
+            .repository
+            .get_results(keplr_wallet_pubkey, project_id, token_ids, since)
+            .await
+        {
+            Ok(results) => results
+                .into_iter()
+                .map(|r| (r.token_id.clone(), r))
+                .collect(),
+            Err(e) => {
+                warn!("Failed to read cached check results {:#?}", e);
+                HashMap::new()
+            }
+        }
+    }
+
+    pub async fn store(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        checks: &[(TokenId, Option<i64>, Option<TokenCheckCode>)],
+    ) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let now = self.clock.now_ms();
+        let results: Vec<CheckResult> = checks
+            .iter()
+            .filter(|(_, _, failure)| Self::is_cacheable(*failure))
+            .map(|(token_id, height, failure)| CheckResult {
+                keplr_wallet_pubkey: keplr_wallet_pubkey.clone(),
+                project_id: project_id.clone(),
+                token_id: token_id.clone(),
+                height: *height,
+                failure: *failure,
+                checked_at: now,
+            })
+            .collect();
+        if results.is_empty() {
+            return;
+        }
+        if let Err(e) = self
+            .repository
+            .save_results(&results, now - self.ttl.as_millis() as i64)
+            .await
+        {
+            warn!("Failed to cache check results {:#?}", e);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use core::fmt::{Debug, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of current time, injected wherever expiry or scheduling is computed so
+/// tests can move time forward instead of sleeping.
+pub trait Clock: Send + Sync {
+    /// Epoch milliseconds
+    fn now_ms(&self) -> i64;
+
+    fn now_secs(&self) -> i64 {
+        self.now_ms().div_euclid(1000)
+    }
+}
+
+impl Debug for dyn Clock {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Clock{{}}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default()
+    }
+}
@@ -1,31 +1,110 @@
-use super::bridge::{QueueItem, QueueManager, StarknetManager};
-use log::{error, info};
-use std::{collections::HashMap, sync::Arc};
+use super::{
+    analytics::{BatchAnalytics, BatchOutcome, BatchSettlement},
+    bridge::{
+        joined_request_ids, MintError, QueueItem, QueueManager, QueueStatus, StarknetManager,
+        TransactionOutcome, UNCHECKED_MINT_STATUS_NOTE,
+    },
+    ids::{QueueItemId, StarknetAddress},
+    log_context::{set_log_fields, LogFields},
+    post_mint::{schedule_post_mint_hooks, PostMintExecutionRepository, PostMintHooks},
+    status_message::StatusNote,
+    webhook::WebhookNotifier,
+};
+use log::{error, info, warn};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+use tokio_util::sync::CancellationToken;
+
+pub const CONTRACT_PAUSED_NOTE: &str = "ContractPaused";
+pub const TRANSACTION_NOT_RECEIVED_NOTE: &str = "TransactionNotReceived";
+pub const MINT_FAILED_NOTE: &str = "MintFailed";
+pub const MINT_REVERTED_NOTE: &str = "MintReverted";
+pub const TRANSACTION_REJECTED_NOTE: &str = "TransactionRejected";
+pub const FEE_ABOVE_CAP_NOTE: &str = "FeeAboveCap";
+pub const WAITING_FOR_NEXT_BATCH_NOTE: &str = "WaitingForNextBatch";
+/// Sequencer rejection reason of a transaction in which one of the calls reverted, the
+/// other mints of its batch may succeed on their own.
+pub const REVERTED_CALL_REASON: &str = "TRANSACTION_FAILED";
+
+/// Bounds how many times an item is minted before it lands in the dead letter
+/// queue, and how long it waits between two attempts.
+#[derive(Debug, Clone)]
+pub struct MintRetryPolicy {
+    pub max_attempts: i32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl MintRetryPolicy {
+    /// Exponential backoff given the number of attempts that already failed.
+    pub fn delay(&self, failed_attempts: i32) -> Duration {
+        let factor = 2u32.saturating_pow(failed_attempts.clamp(0, 31) as u32);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for MintRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(60),
+            max_delay: Duration::from_secs(3600),
+        }
+    }
+}
 
 pub enum ConsumerError {
     FailedToGetNextBatch,
+    FailedToGetProcessingItems,
+    Cancelled,
 }
 pub async fn consume_queue(
     queue_manager: Arc<dyn QueueManager>,
     starknet_manager: Arc<dyn StarknetManager>,
+    post_mint_hooks: Arc<PostMintHooks>,
+    post_mint_repository: Arc<dyn PostMintExecutionRepository>,
+    webhooks: Arc<WebhookNotifier>,
+    analytics: Arc<BatchAnalytics>,
+    closed_projects: &[StarknetAddress],
+    retry_policy: &MintRetryPolicy,
+    cancel: &CancellationToken,
 ) -> Result<(), ConsumerError> {
-    let batch = match queue_manager.get_batch().await {
+    // Items of projects outside their migration window or paused wait in the queue
+    let batch = match queue_manager.get_batch(closed_projects).await {
         Ok(b) => b,
         Err(_e) => return Err(ConsumerError::FailedToGetNextBatch),
     };
 
-    let mut token_to_mint: HashMap<String, Vec<QueueItem>> = HashMap::new();
+    let mut token_to_mint: HashMap<StarknetAddress, Vec<QueueItem>> = HashMap::new();
+    let mut minted = Vec::new();
+    let mut unchecked = Vec::new();
     for qi in batch {
-        if starknet_manager
-            .project_has_token(&qi.project_id, &qi.token_id.as_str())
+        // Last check before minting, including items enqueued while it could not be made
+        match starknet_manager
+            .project_has_token(&qi.project_id, &qi.token_id)
             .await
         {
-            error!("Token id {} has already been minted", &qi.token_id);
-            continue;
-        }
+            Ok(true) => {
+                error!("Token id {} has already been minted", &qi.token_id);
+                minted.push(qi);
+                continue;
+            }
+            Ok(false) => (),
+            Err(e) => {
+                warn!(
+                    "Mint status of token {} unavailable, leaving it in the queue : {:#?}",
+                    &qi.token_id, e
+                );
+                unchecked.extend(qi.id);
+                continue;
+            }
+        };
 
         let project_id = qi.project_id.clone();
-        match token_to_mint.entry(project_id.to_string()) {
+        match token_to_mint.entry(project_id) {
             std::collections::hash_map::Entry::Vacant(e) => {
                 e.insert(vec![qi.clone()]);
             }
@@ -35,48 +114,609 @@ pub async fn consume_queue(
         };
     }
 
+    // The batch may have been claimed, items not minted here are released
+    if !minted.is_empty() {
+        let ids: Vec<QueueItemId> = minted.iter().filter_map(|q| q.id).collect();
+        match queue_manager
+            .update_queue_items_status(&ids, String::from(""), QueueStatus::Success)
+            .await
+        {
+            Ok(_) => webhooks.notify(&minted, QueueStatus::Success, None).await,
+            Err(e) => error!("Error while update queue items status {:#?}", e),
+        }
+    }
+    if !unchecked.is_empty() {
+        if let Err(e) = queue_manager
+            .defer_queue_items(&unchecked, &StatusNote::new(UNCHECKED_MINT_STATUS_NOTE))
+            .await
+        {
+            error!("Error while deferring unchecked queue items {:#?}", e);
+        }
+    }
+
     if 0 == token_to_mint.len() {
         info!("No token have been minted during this batch");
         return Ok(());
     }
 
+    // Items left when cancelled are released for the next worker to pick them up
+    let mut unsent: HashSet<QueueItemId> = token_to_mint
+        .values()
+        .flatten()
+        .filter_map(|q| q.id)
+        .collect();
     for (project_id, qi) in token_to_mint.iter() {
-        let ids = qi
-            .iter()
-            .map(|q| q.id.as_ref().unwrap().to_string())
+        if cancel.is_cancelled() {
+            release_unsent_items(queue_manager.clone(), &unsent).await;
+            return Err(ConsumerError::Cancelled);
+        }
+        // Items over the adaptive batch size wait for the next run
+        let qi = match analytics.batch_size(project_id).await {
+            Some(size) if size < qi.len() => {
+                info!(
+                    "Minting {} of {} queue items of project {}",
+                    size,
+                    qi.len(),
+                    project_id
+                );
+                let (minted_now, left) = qi.split_at(size);
+                let left: HashSet<QueueItemId> = left.iter().filter_map(|q| q.id).collect();
+                release_unsent_items(queue_manager.clone(), &left).await;
+                unsent.retain(|id| !left.contains(id));
+                minted_now
+            }
+            _ => &qi[..],
+        };
+        let ids: Vec<QueueItemId> = qi.iter().filter_map(|q| q.id).collect();
+
+        if starknet_manager.project_is_paused(project_id).await {
+            warn!(
+                "Project {} contract is paused, deferring {} queue items",
+                project_id,
+                qi.len()
+            );
+            defer_paused_items(queue_manager.clone(), &ids).await;
+            unsent.retain(|id| !ids.contains(id));
+            continue;
+        }
+
+        // Very large batches exceed transaction limits, they are sent in several transactions
+        let mut chunks: VecDeque<&[QueueItem]> = qi
+            .chunks(starknet_manager.max_calls_per_transaction().max(1))
             .collect();
+        let mut n = 0;
+        while let Some(chunk) = chunks.pop_front() {
+            if 0 < n && cancel.is_cancelled() {
+                release_unsent_items(queue_manager.clone(), &unsent).await;
+                return Err(ConsumerError::Cancelled);
+            }
+            n += 1;
+            let ids: Vec<QueueItemId> = chunk.iter().filter_map(|q| q.id).collect();
+            unsent.retain(|id| !ids.contains(id));
+            let request_ids = joined_request_ids(chunk);
+            set_log_fields(LogFields {
+                request_id: request_ids.clone(),
+                wallet_pubkey: single_wallet(chunk),
+                project_id: Some(project_id.to_string()),
+            });
+            info!(
+                "Minting {} queue items of project {} for requests {}",
+                chunk.len(),
+                project_id,
+                request_ids.as_deref().unwrap_or("unknown")
+            );
 
-        queue_manager
-            .update_queue_items_status(
-                &ids,
-                String::from(""),
-                super::bridge::QueueStatus::Processing,
-            )
-            .await;
+            // Items requeued meanwhile, e.g. as stale, may be minted by another worker
+            match queue_manager.claim_queue_items(&ids).await {
+                Ok(claimed) if claimed.len() == ids.len() => (),
+                Ok(claimed) => {
+                    warn!(
+                        "{} of {} queue items of project {} are not held by this worker anymore, \
+                        leaving the chunk out",
+                        ids.len() - claimed.len(),
+                        ids.len(),
+                        project_id
+                    );
+                    release_unsent_items(queue_manager.clone(), &claimed.into_iter().collect())
+                        .await;
+                    continue;
+                }
+                Err(e) => {
+                    // Items are left as they are, the stale sweep requeues claimed ones
+                    error!("Error while claiming queue items {:#?}", e);
+                    continue;
+                }
+            }
 
-        let _mint = match starknet_manager
-            .batch_mint_tokens(project_id, qi.to_vec())
-            .await
-        {
-            Ok((tx_hash, status)) => {
-                info!("Transaction {:#?} was handled successfully", tx_hash);
-                let res = queue_manager
-                    .update_queue_items_status(&ids, tx_hash, status)
-                    .await;
-                match res {
-                    Ok(_r) => {
-                        info!("Successfully updated queue item statuses");
+            let submitted_at = analytics.now_ms();
+            let _mint = match starknet_manager
+                .batch_mint_tokens(project_id, chunk.to_vec())
+                .await
+            {
+                Ok(submission) => {
+                    let tx_hash = submission.transaction_hash;
+                    if 1 < n + chunks.len() {
+                        info!(
+                            "Chunk {} of {} of project {} sent {} queue items in transaction {}",
+                            n,
+                            n + chunks.len(),
+                            project_id,
+                            chunk.len(),
+                            tx_hash
+                        );
+                    }
+                    // Record hash right away so a restart can resume from it
+                    if let Err(e) = queue_manager
+                        .update_queue_items_status(
+                            &ids,
+                            tx_hash.to_string(),
+                            QueueStatus::Processing,
+                        )
+                        .await
+                    {
+                        error!("Error while recording transaction hash {:#?}", e);
+                    }
+                    webhooks
+                        .notify(chunk, QueueStatus::Processing, Some(&tx_hash))
+                        .await;
+                    let outcome = starknet_manager
+                        .wait_for_transaction(&tx_hash, cancel)
+                        .await;
+                    if TransactionOutcome::Pending == outcome {
+                        warn!(
+                            "Stopped waiting for transaction {}, items are left processing",
+                            tx_hash
+                        );
+                        return Err(ConsumerError::Cancelled);
                     }
-                    Err(e) => {
-                        error!("Error while update queue items status {:#?}", e);
+                    info!("Transaction {:#?} was handled successfully", tx_hash);
+                    let fee = starknet_manager.get_transaction_fee(&tx_hash).await;
+                    info!(
+                        "Transaction {} estimated fee {} actual fee {}",
+                        tx_hash,
+                        submission.estimated_fee.as_deref().unwrap_or("unknown"),
+                        fee.as_deref().unwrap_or("unknown")
+                    );
+                    if let Err(e) = queue_manager
+                        .record_mint_fees(&ids, submission.estimated_fee.as_deref(), fee.as_deref())
+                        .await
+                    {
+                        error!("Error while recording mint fees {:#?}", e);
+                    }
+                    analytics
+                        .record(
+                            project_id,
+                            chunk.len(),
+                            submitted_at,
+                            BatchSettlement {
+                                transaction_hash: Some(tx_hash.to_string()),
+                                fee,
+                                outcome: match outcome {
+                                    TransactionOutcome::Accepted => BatchOutcome::Accepted,
+                                    _ => BatchOutcome::Rejected,
+                                },
+                                rejection_reason: match &outcome {
+                                    TransactionOutcome::Rejected(reason) => reason.clone(),
+                                    TransactionOutcome::NotReceived => {
+                                        Some(TRANSACTION_NOT_RECEIVED_NOTE.to_string())
+                                    }
+                                    _ => None,
+                                },
+                            },
+                        )
+                        .await;
+                    if let Some((left, right)) = split_reverted_chunk(chunk, &outcome) {
+                        warn!(
+                            "A mint of transaction {} reverted, sending its {} items in halves",
+                            tx_hash,
+                            chunk.len()
+                        );
+                        unsent.extend(ids.iter().copied());
+                        chunks.push_front(right);
+                        chunks.push_front(left);
+                        continue;
+                    }
+                    if finalize_queue_items(
+                        queue_manager.clone(),
+                        chunk,
+                        &tx_hash,
+                        outcome,
+                        &post_mint_hooks,
+                        post_mint_repository.clone(),
+                        retry_policy,
+                    )
+                    .await
+                    {
+                        webhooks
+                            .notify(chunk, QueueStatus::Success, Some(&tx_hash))
+                            .await;
                     }
                 }
+                Err(MintError::ContractPaused) => {
+                    warn!(
+                        "Project {} contract has been paused while minting, deferring queue items",
+                        project_id
+                    );
+                    defer_paused_items(queue_manager.clone(), &ids).await;
+                }
+                Err(MintError::FeeAboveCap) => {
+                    // Not the items' fault, their attempts are left untouched
+                    warn!(
+                        "Mint fees of project {} are above the cap, deferring queue items",
+                        project_id
+                    );
+                    if let Err(e) = queue_manager
+                        .defer_queue_items(&ids, &StatusNote::new(FEE_ABOVE_CAP_NOTE))
+                        .await
+                    {
+                        error!("Error while deferring queue items {:#?}", e);
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to create transaction");
+                    let reason = match e {
+                        MintError::Reverted => REVERTED_CALL_REASON,
+                        _ => MINT_FAILED_NOTE,
+                    };
+                    analytics
+                        .record(
+                            project_id,
+                            chunk.len(),
+                            submitted_at,
+                            BatchSettlement {
+                                transaction_hash: None,
+                                fee: None,
+                                outcome: BatchOutcome::SubmissionFailed,
+                                rejection_reason: Some(reason.to_string()),
+                            },
+                        )
+                        .await;
+                    // A mint reverting in simulation refuses the whole chunk before sending it
+                    if let MintError::Reverted = e {
+                        match halves(chunk) {
+                            Some((left, right)) => {
+                                warn!(
+                                    "A mint of project {} reverted while estimating fees, \
+                                    sending its {} items in halves",
+                                    project_id,
+                                    chunk.len()
+                                );
+                                unsent.extend(ids.iter().copied());
+                                chunks.push_front(right);
+                                chunks.push_front(left);
+                            }
+                            None => {
+                                // It reverts the same way on every attempt
+                                warn!(
+                                    "Mint of token {} of project {} reverted, moving it to \
+                                    the dead letter queue",
+                                    chunk[0].token_id, project_id
+                                );
+                                if let Err(e) = queue_manager
+                                    .dead_letter_queue_items(
+                                        &ids,
+                                        &StatusNote::new(MINT_REVERTED_NOTE),
+                                    )
+                                    .await
+                                {
+                                    error!(
+                                        "Error while moving queue items to dead letter queue {:#?}",
+                                        e
+                                    );
+                                }
+                            }
+                        }
+                        continue;
+                    }
+                    retry_failed_items(
+                        queue_manager.clone(),
+                        chunk,
+                        &StatusNote::new(MINT_FAILED_NOTE),
+                        retry_policy,
+                    )
+                    .await;
+                }
+            };
+        }
+    }
+
+    Ok(())
+}
+
+/// Projects whose contract is paused. Their items are left out of batches, claiming then
+/// deferring them on every poll would fill batches and starve the other projects.
+pub async fn paused_projects(
+    starknet_manager: &dyn StarknetManager,
+    projects: &[StarknetAddress],
+) -> Vec<StarknetAddress> {
+    let mut paused = Vec::new();
+    for project_id in projects {
+        if starknet_manager.project_is_paused(project_id).await {
+            info!(
+                "Project {} contract is paused, leaving its items queued",
+                project_id
+            );
+            paused.push(project_id.clone());
+        }
+    }
+
+    paused
+}
+
+/// Returns whether items were marked as minted.
+pub(crate) async fn finalize_queue_items(
+    queue_manager: Arc<dyn QueueManager>,
+    queue_items: &[QueueItem],
+    transaction_hash: &str,
+    outcome: TransactionOutcome,
+    post_mint_hooks: &PostMintHooks,
+    post_mint_repository: Arc<dyn PostMintExecutionRepository>,
+    retry_policy: &MintRetryPolicy,
+) -> bool {
+    if TransactionOutcome::Accepted != outcome {
+        warn!(
+            "Transaction {} was not accepted : {:#?}",
+            transaction_hash, outcome
+        );
+        // Sequencer gives no reason for some rejections, its status stands for it then
+        let reason = match &outcome {
+            TransactionOutcome::Rejected(Some(reason)) => reason.as_str(),
+            _ => "REJECTED",
+        };
+        let note = StatusNote::new(TRANSACTION_REJECTED_NOTE)
+            .with("tx_hash", transaction_hash)
+            .with("reason", reason);
+        retry_failed_items(queue_manager, queue_items, &note, retry_policy).await;
+        return false;
+    }
+
+    let ids: Vec<QueueItemId> = queue_items.iter().filter_map(|q| q.id).collect();
+    match queue_manager
+        .update_queue_items_status(&ids, transaction_hash.to_string(), QueueStatus::Success)
+        .await
+    {
+        Ok(_r) => {
+            info!("Successfully updated queue item statuses");
+            schedule_post_mint_hooks(
+                post_mint_hooks,
+                post_mint_repository,
+                queue_items,
+                transaction_hash,
+            )
+            .await;
+            true
+        }
+        Err(e) => {
+            error!("Error while update queue items status {:#?}", e);
+            false
+        }
+    }
+}
+
+/// Resolves items a previous run of this worker left in processing before consuming new
+/// batches, along with items any worker sent a mint transaction for, since a restarted
+/// worker may not get its previous id back, and items left processing since before
+/// `stale_before`.
+/// Items with a transaction hash are finalized from on chain status, items without one
+/// never reached the chain unless their token exists.
+pub async fn recover_processing_items(
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: Arc<dyn StarknetManager>,
+    post_mint_hooks: Arc<PostMintHooks>,
+    post_mint_repository: Arc<dyn PostMintExecutionRepository>,
+    webhooks: Arc<WebhookNotifier>,
+    retry_policy: &MintRetryPolicy,
+    stale_before: Option<i64>,
+    cancel: &CancellationToken,
+) -> Result<(), ConsumerError> {
+    // Unsent items other live workers are processing are theirs to finish
+    let items = match queue_manager.get_recoverable_items(stale_before).await {
+        Ok(i) => i,
+        Err(_e) => return Err(ConsumerError::FailedToGetProcessingItems),
+    };
+    if items.is_empty() {
+        return Ok(());
+    }
+    warn!("Recovering {} queue items left in processing", items.len());
+
+    let mut by_transaction: HashMap<String, Vec<QueueItem>> = HashMap::new();
+    for qi in items {
+        let tx_hash = qi.transaction_hash.clone().unwrap_or_default();
+        by_transaction.entry(tx_hash).or_default().push(qi);
+    }
+
+    for (tx_hash, queue_items) in by_transaction.iter() {
+        if cancel.is_cancelled() {
+            return Err(ConsumerError::Cancelled);
+        }
+        if tx_hash.is_empty() {
+            recover_unsent_items(
+                queue_manager.clone(),
+                starknet_manager.clone(),
+                &webhooks,
+                queue_items,
+            )
+            .await;
+            continue;
+        }
+
+        let outcome = match starknet_manager.get_transaction_outcome(tx_hash).await {
+            TransactionOutcome::Pending => {
+                starknet_manager.wait_for_transaction(tx_hash, cancel).await
             }
-            Err(_e) => {
-                error!("Failed to create transaction");
-            }
+            outcome => outcome,
         };
+        if TransactionOutcome::Pending == outcome {
+            return Err(ConsumerError::Cancelled);
+        }
+        if TransactionOutcome::NotReceived == outcome {
+            warn!(
+                "Transaction {} never reached the sequencer, requeuing its items",
+                tx_hash
+            );
+            let ids: Vec<QueueItemId> = queue_items.iter().filter_map(|q| q.id).collect();
+            let note = StatusNote::new(TRANSACTION_NOT_RECEIVED_NOTE).with("tx_hash", tx_hash);
+            if let Err(e) = queue_manager.defer_queue_items(&ids, &note).await {
+                error!("Error while requeuing queue items {:#?}", e);
+            }
+            continue;
+        }
+
+        if finalize_queue_items(
+            queue_manager.clone(),
+            queue_items,
+            tx_hash,
+            outcome,
+            &post_mint_hooks,
+            post_mint_repository.clone(),
+            retry_policy,
+        )
+        .await
+        {
+            webhooks
+                .notify(queue_items, QueueStatus::Success, Some(tx_hash))
+                .await;
+        }
     }
 
     Ok(())
 }
+
+async fn recover_unsent_items(
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: Arc<dyn StarknetManager>,
+    webhooks: &WebhookNotifier,
+    queue_items: &[QueueItem],
+) {
+    let mut minted = Vec::new();
+    let mut requeued = Vec::new();
+    for qi in queue_items {
+        let Some(id) = qi.id else {
+            continue;
+        };
+        // Requeued items are checked again before being minted
+        match starknet_manager
+            .project_has_token(&qi.project_id, &qi.token_id)
+            .await
+        {
+            Ok(true) => minted.push(qi.clone()),
+            Ok(false) | Err(_) => requeued.push(id),
+        };
+    }
+
+    if !minted.is_empty() {
+        let ids: Vec<QueueItemId> = minted.iter().filter_map(|q| q.id).collect();
+        match queue_manager
+            .update_queue_items_status(&ids, String::from(""), QueueStatus::Success)
+            .await
+        {
+            Ok(_) => webhooks.notify(&minted, QueueStatus::Success, None).await,
+            Err(e) => error!("Error while update queue items status {:#?}", e),
+        }
+    }
+    if !requeued.is_empty() {
+        if let Err(e) = queue_manager
+            .defer_queue_items(&requeued, &StatusNote::new(TRANSACTION_NOT_RECEIVED_NOTE))
+            .await
+        {
+            error!("Error while requeuing queue items {:#?}", e);
+        }
+    }
+}
+
+/// Schedules another attempt for each failed item, or parks it in the dead letter
+/// queue once it used all of its attempts.
+pub async fn retry_failed_items(
+    queue_manager: Arc<dyn QueueManager>,
+    queue_items: &[QueueItem],
+    note: &StatusNote,
+    retry_policy: &MintRetryPolicy,
+) {
+    let mut dead_letters = Vec::new();
+    for qi in queue_items {
+        let Some(id) = qi.id else {
+            continue;
+        };
+        if qi.attempts + 1 >= retry_policy.max_attempts {
+            dead_letters.push(id);
+            continue;
+        }
+        if let Err(e) = queue_manager
+            .schedule_retry(&[id], note, retry_policy.delay(qi.attempts))
+            .await
+        {
+            error!("Error while scheduling queue item retry {:#?}", e);
+        }
+    }
+
+    if dead_letters.is_empty() {
+        return;
+    }
+    warn!(
+        "Moving {} queue items to the dead letter queue after {} attempts",
+        dead_letters.len(),
+        retry_policy.max_attempts
+    );
+    if let Err(e) = queue_manager
+        .dead_letter_queue_items(&dead_letters, note)
+        .await
+    {
+        error!(
+            "Error while moving queue items to dead letter queue {:#?}",
+            e
+        );
+    }
+}
+
+async fn defer_paused_items(queue_manager: Arc<dyn QueueManager>, ids: &[QueueItemId]) {
+    if let Err(e) = queue_manager
+        .defer_queue_items(ids, &StatusNote::new(CONTRACT_PAUSED_NOTE))
+        .await
+    {
+        error!("Error while deferring queue items {:#?}", e);
+    }
+}
+
+// Bisects a batch rejected because one of its mints reverted so the other mints still
+// go through, until the offending item is failed on its own
+fn split_reverted_chunk<'a>(
+    chunk: &'a [QueueItem],
+    outcome: &TransactionOutcome,
+) -> Option<(&'a [QueueItem], &'a [QueueItem])> {
+    match outcome {
+        TransactionOutcome::Rejected(Some(reason)) if REVERTED_CALL_REASON == reason.as_str() => {
+            halves(chunk)
+        }
+        _ => None,
+    }
+}
+
+fn halves(chunk: &[QueueItem]) -> Option<(&[QueueItem], &[QueueItem])> {
+    match 1 < chunk.len() {
+        true => Some(chunk.split_at(chunk.len() / 2)),
+        false => None,
+    }
+}
+
+// Claimed items that were not sent go back to pending, attempts left untouched
+async fn release_unsent_items(queue_manager: Arc<dyn QueueManager>, ids: &HashSet<QueueItemId>) {
+    if ids.is_empty() {
+        return;
+    }
+    let ids: Vec<QueueItemId> = ids.iter().copied().collect();
+    if let Err(e) = queue_manager
+        .defer_queue_items(&ids, &StatusNote::new(WAITING_FOR_NEXT_BATCH_NOTE))
+        .await
+    {
+        error!("Error while releasing queue items {:#?}", e);
+    }
+}
+
+// Chunks of a single customer are logged with their wallet
+fn single_wallet(items: &[QueueItem]) -> Option<String> {
+    let wallet = &items.first()?.keplr_wallet_pubkey;
+    match items.iter().all(|qi| qi.keplr_wallet_pubkey == *wallet) {
+        true => Some(wallet.to_string()),
+        false => None,
+    }
+}
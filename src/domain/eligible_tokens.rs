@@ -0,0 +1,93 @@
+use futures::future::join_all;
+use log::{error, warn};
+use serde_derive::Serialize;
+use tokio_util::sync::CancellationToken;
+use utoipa::ToSchema;
+
+use super::{
+    bridge::{JunoContractQuerier, StarknetManager, TransactionFetchError},
+    ids::{JunoAddress, ProjectId, StarknetAddress, TokenId},
+    project_registry::ProjectRegistry,
+};
+
+/// Tokens a wallet holds on a Juno contract, split by whether they can still be bridged.
+#[derive(Serialize, Debug, ToSchema)]
+pub struct EligibleTokens {
+    #[schema(value_type = String)]
+    pub keplr_wallet_pubkey: JunoAddress,
+    #[schema(value_type = String)]
+    pub juno_contract: ProjectId,
+    #[schema(value_type = String)]
+    pub starknet_contract: StarknetAddress,
+    /// Not minted on Starknet yet, ready to be transferred and bridged
+    #[schema(value_type = Vec<String>)]
+    pub eligible: Vec<TokenId>,
+    #[schema(value_type = Vec<String>)]
+    pub already_minted: Vec<TokenId>,
+    /// Mint status Starknet could not tell, bridge requests check them again
+    #[schema(value_type = Vec<String>)]
+    pub unchecked: Vec<TokenId>,
+}
+
+#[derive(Debug)]
+pub enum EligibleTokensError {
+    UnknownProject,
+    JunoUnavailable,
+    Cancelled,
+}
+
+/// Lists the tokens `keplr_wallet_pubkey` holds on the project contract and asks
+/// Starknet which of them were minted already.
+pub async fn handle_eligible_tokens(
+    keplr_wallet_pubkey: &JunoAddress,
+    project_id: &ProjectId,
+    project_registry: &ProjectRegistry,
+    contract_querier: &dyn JunoContractQuerier,
+    starknet_manager: &dyn StarknetManager,
+    cancel: &CancellationToken,
+) -> Result<EligibleTokens, EligibleTokensError> {
+    let Some(project) = project_registry.get(project_id) else {
+        return Err(EligibleTokensError::UnknownProject);
+    };
+    let token_ids = match contract_querier
+        .tokens_of(project_id, keplr_wallet_pubkey, cancel)
+        .await
+    {
+        Ok(t) => t,
+        Err(TransactionFetchError::Cancelled) => return Err(EligibleTokensError::Cancelled),
+        Err(e) => {
+            error!(
+                "Failed to list tokens of {} on {} : {:#?}",
+                keplr_wallet_pubkey, project_id, e
+            );
+            return Err(EligibleTokensError::JunoUnavailable);
+        }
+    };
+
+    let minted = join_all(
+        token_ids
+            .iter()
+            .map(|token| starknet_manager.project_has_token(&project.starknet_contract, token)),
+    )
+    .await;
+    let mut tokens = EligibleTokens {
+        keplr_wallet_pubkey: keplr_wallet_pubkey.clone(),
+        juno_contract: project.juno_contract.clone(),
+        starknet_contract: project.starknet_contract.clone(),
+        eligible: Vec::new(),
+        already_minted: Vec::new(),
+        unchecked: Vec::new(),
+    };
+    for (token, minted) in token_ids.into_iter().zip(minted) {
+        match minted {
+            Ok(false) => tokens.eligible.push(token),
+            Ok(true) => tokens.already_minted.push(token),
+            Err(e) => {
+                warn!("Mint status of token {} unavailable : {:#?}", token, e);
+                tokens.unchecked.push(token);
+            }
+        }
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,363 @@
+use serde_derive::Serialize;
+
+use super::{
+    bridge::{BridgeError, TokenCheckCode},
+    eligible_tokens::EligibleTokensError,
+    funnel::FunnelEventError,
+    reverse_bridge::ReverseBridgeError,
+    save_customer_data::SaveCustomerDataError,
+    schema_version::SchemaVersionError,
+};
+
+/// How an error surfaces to API clients. `message` may hold `{placeholders}`
+/// filled in from the error payload.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ErrorDescription {
+    pub source: &'static str,
+    pub code: &'static str,
+    pub http_status: u16,
+    pub retriable: bool,
+    pub message: &'static str,
+}
+
+pub trait CatalogedError {
+    /// Every variant, in declaration order.
+    fn catalog() -> Vec<ErrorDescription>;
+    fn catalog_entry(&self) -> ErrorDescription;
+}
+
+/// Declares the catalog entry of every variant of an error enum. The generated
+/// match is exhaustive, so a new variant does not compile until it is described.
+macro_rules! error_catalog {
+    ($error:ty, $source:literal, {
+        $($variant:ident $(($($fields:tt)*))? => ($code:literal, $status:literal, $retriable:literal, $message:expr)),* $(,)?
+    }) => {
+        impl CatalogedError for $error {
+            fn catalog() -> Vec<ErrorDescription> {
+                vec![$(ErrorDescription {
+                    source: $source,
+                    code: $code,
+                    http_status: $status,
+                    retriable: $retriable,
+                    message: $message,
+                }),*]
+            }
+
+            fn catalog_entry(&self) -> ErrorDescription {
+                match self {
+                    $(Self::$variant $(($($fields)*))? => ErrorDescription {
+                        source: $source,
+                        code: $code,
+                        http_status: $status,
+                        retriable: $retriable,
+                        message: $message,
+                    }),*
+                }
+            }
+        }
+    };
+}
+
+error_catalog!(BridgeError, "bridge", {
+    InvalidSign => ("invalid_sign", 400, false, "Invalid sign"),
+    JunoBalanceIsNotZero => (
+        "juno_balance_is_not_zero",
+        400,
+        true,
+        "Juno tokens have not been transferred yet"
+    ),
+    FetchTokenError(_) => (
+        "fetch_token_error",
+        404,
+        false,
+        "Failed to fetch tokens from customer wallet"
+    ),
+    TokenNotTransferedToAdmin(_) => (
+        "token_not_transferred_to_admin",
+        400,
+        false,
+        "Token not transferred to admin"
+    ),
+    TokenDidNotBelongToWallet(_) => (
+        "token_did_not_belong_to_wallet",
+        400,
+        false,
+        "Token did not belong to provided wallet."
+    ),
+    TokenAlreadyMinted(_) => ("token_already_minted", 400, false, "Token has already been minted"),
+    ErrorWhileMintingToken => ("error_while_minting_token", 400, true, "Error while minting token"),
+    JunoBlockChainServerError(_) => (
+        "juno_blockchain_server_error",
+        500,
+        true,
+        "Juno blockchain error {status}"
+    ),
+    EnqueueingIssue => (
+        "enqueueing_issue",
+        500,
+        true,
+        "Error while enqueing your token for minting"
+    ),
+    StarknetAccountMismatch(_) => (
+        "starknet_account_mismatch",
+        409,
+        false,
+        "Keplr wallet is linked to another starknet account"
+    ),
+    WalletLinkIssue => (
+        "wallet_link_issue",
+        500,
+        true,
+        "Error while checking keplr and starknet wallet link"
+    ),
+    Cancelled => ("cancelled", 503, true, "Service is shutting down, please try again later"),
+    UnknownProject(_) => ("unknown_project", 404, false, "Project is not bridged"),
+    InvalidChallenge => (
+        "invalid_challenge",
+        400,
+        false,
+        "Challenge nonce is missing, unknown or already used"
+    ),
+    ChallengeExpired => (
+        "challenge_expired",
+        400,
+        true,
+        "Challenge nonce has expired, please request a new one"
+    ),
+    ChallengeIssue => ("challenge_issue", 500, true, "Error while checking challenge nonce"),
+    SignDocRequired => (
+        "sign_doc_required",
+        400,
+        false,
+        "Please sign the bridge document listing your tokens"
+    ),
+    SignDocMismatch => (
+        "sign_doc_mismatch",
+        400,
+        false,
+        "Signed document does not match the requested tokens"
+    ),
+    QueueFull(_) => (
+        "queue_full",
+        429,
+        true,
+        "Too many migrations are waiting, please retry in {retry_after} seconds"
+    ),
+    MigrationWindowClosed(_) => (
+        "migration_window_closed",
+        403,
+        false,
+        "Migrations of this project are not open, see the migration window in details"
+    ),
+});
+
+error_catalog!(SaveCustomerDataError, "save_customer_data", {
+    NotImpled => ("not_implemented", 500, false, "Unknown error"),
+    NotFound => ("not_found", 404, false, "Customer not found"),
+    FailedToPersistToDatabase => (
+        "failed_to_persist_to_database",
+        500,
+        true,
+        "Error while saving customer to database"
+    ),
+    InvalidSign => ("invalid_sign", 400, false, "Invalid sign"),
+    MissingSenderSign => (
+        "missing_sender_sign",
+        400,
+        false,
+        "Sender has to sign the authorization, or an operator approve it"
+    ),
+    InvalidSenderSign => ("invalid_sender_sign", 400, false, "Invalid sender sign"),
+    InvalidRequest => (
+        "invalid_request",
+        400,
+        false,
+        "A reason is required for audit purposes"
+    ),
+});
+
+// Codes match the serialized `TokenCheckCode`, statuses the one `/bridge` answers with
+error_catalog!(TokenCheckCode, "token_check", {
+    JunoFetchFailed => (
+        "juno_fetch_failed",
+        400,
+        true,
+        TokenCheckCode::JunoFetchFailed.default_message()
+    ),
+    JunoDeserializationFailed => (
+        "juno_deserialization_failed",
+        400,
+        false,
+        TokenCheckCode::JunoDeserializationFailed.default_message()
+    ),
+    JunoServerError => (
+        "juno_server_error",
+        500,
+        true,
+        TokenCheckCode::JunoServerError.default_message()
+    ),
+    JunoResponseTooLarge => (
+        "juno_response_too_large",
+        500,
+        false,
+        TokenCheckCode::JunoResponseTooLarge.default_message()
+    ),
+    TransactionNotFound => (
+        "transaction_not_found",
+        404,
+        true,
+        TokenCheckCode::TransactionNotFound.default_message()
+    ),
+    NotTransferredToAdmin => (
+        "not_transferred_to_admin",
+        400,
+        false,
+        TokenCheckCode::NotTransferredToAdmin.default_message()
+    ),
+    NotHeldByAdmin => (
+        "not_held_by_admin",
+        400,
+        true,
+        TokenCheckCode::NotHeldByAdmin.default_message()
+    ),
+    NotBurnt => (
+        "not_burnt",
+        400,
+        false,
+        TokenCheckCode::NotBurnt.default_message()
+    ),
+    TransferOutsideWindow => (
+        "transfer_outside_window",
+        400,
+        false,
+        TokenCheckCode::TransferOutsideWindow.default_message()
+    ),
+    SenderMismatch => (
+        "sender_mismatch",
+        400,
+        false,
+        TokenCheckCode::SenderMismatch.default_message()
+    ),
+    AlreadyMinted => (
+        "already_minted",
+        400,
+        false,
+        TokenCheckCode::AlreadyMinted.default_message()
+    ),
+    ChecksIncomplete => (
+        "checks_incomplete",
+        503,
+        true,
+        TokenCheckCode::ChecksIncomplete.default_message()
+    ),
+});
+
+error_catalog!(ReverseBridgeError, "reverse_bridge", {
+    UnknownProject(_) => (
+        "unknown_project",
+        404,
+        false,
+        "Starknet contract is not bridged"
+    ),
+    NoTokens => ("no_tokens", 400, false, "No token ids to bridge back"),
+    WalletNotLinked => (
+        "wallet_not_linked",
+        400,
+        false,
+        "Keplr wallet has never been linked to a starknet account"
+    ),
+    StarknetAccountMismatch(_) => (
+        "starknet_account_mismatch",
+        409,
+        false,
+        "Keplr wallet is linked to another starknet account"
+    ),
+    WalletLinkIssue => (
+        "wallet_link_issue",
+        500,
+        true,
+        "Error while checking wallet link"
+    ),
+    TransactionNotAccepted => (
+        "transaction_not_accepted",
+        400,
+        true,
+        "Starknet transaction has not been accepted yet"
+    ),
+    StarknetFetchFailed => (
+        "starknet_fetch_failed",
+        500,
+        true,
+        "Failed to fetch transaction from starknet"
+    ),
+    TokenNotReturned(_) => (
+        "token_not_returned",
+        400,
+        false,
+        "Token was not transferred to admin or burnt by the starknet account in this transaction"
+    ),
+    AlreadyRequested(_) => (
+        "already_requested",
+        409,
+        false,
+        "Token has already been requested back with this transaction"
+    ),
+    QueueIssue => (
+        "queue_issue",
+        500,
+        true,
+        "Error while enqueueing tokens"
+    ),
+});
+
+error_catalog!(EligibleTokensError, "eligible_tokens", {
+    UnknownProject => ("unknown_project", 404, false, "Project is not bridged"),
+    JunoUnavailable => (
+        "juno_unavailable",
+        503,
+        true,
+        "Tokens of the wallet could not be listed by the Juno node, please try again later"
+    ),
+    Cancelled => ("cancelled", 503, true, "Service is shutting down, please try again later"),
+});
+
+error_catalog!(SchemaVersionError, "schema_version", {
+    Missing => (
+        "missing_schema_version",
+        400,
+        false,
+        "X-Bridge-Schema-Version header is required, this bridge accepts version {version}"
+    ),
+    Unsupported(_) => (
+        "unsupported_schema_version",
+        400,
+        false,
+        "Schema version {received} is not supported, this bridge accepts version {version} : upgrade the frontend"
+    ),
+});
+
+error_catalog!(FunnelEventError, "funnel_event", {
+    InvalidSession => (
+        "invalid_session",
+        400,
+        false,
+        "Session id has to be 1 to 64 letters, digits, dashes or underscores"
+    ),
+    PersistenceIssue => (
+        "persistence_issue",
+        500,
+        true,
+        "Error while recording event"
+    ),
+});
+
+pub fn error_catalog() -> Vec<ErrorDescription> {
+    let mut errors = BridgeError::catalog();
+    errors.extend(SaveCustomerDataError::catalog());
+    errors.extend(ReverseBridgeError::catalog());
+    errors.extend(TokenCheckCode::catalog());
+    errors.extend(EligibleTokensError::catalog());
+    errors.extend(SchemaVersionError::catalog());
+    errors.extend(FunnelEventError::catalog());
+    errors
+}
@@ -0,0 +1,104 @@
+use log::{error, info};
+use serde_derive::Serialize;
+use std::{sync::Arc, time::Duration};
+
+use super::{
+    bridge::{QueueError, QueueItem, QueueManager},
+    clock::Clock,
+    pagination::{Cursor, PageRequest},
+    storage::{ObjectStorage, StorageError},
+};
+
+const EXPORT_PAGE_SIZE: i64 = 1000;
+
+#[derive(Debug)]
+pub enum ExportError {
+    FailedToFetch(QueueError),
+    FailedToStore(StorageError),
+}
+
+#[derive(Serialize, Debug)]
+pub struct ExportArtifact {
+    pub key: String,
+    pub rows: usize,
+    pub url: String,
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        return format!("\"{}\"", value.replace('"', "\"\""));
+    }
+    value.to_string()
+}
+
+fn queue_item_csv_row(qi: &QueueItem) -> String {
+    [
+        qi.id.map(|id| id.to_string()).unwrap_or_default(),
+        qi.keplr_wallet_pubkey.to_string(),
+        qi.starknet_wallet_pubkey.to_string(),
+        qi.project_id.to_string(),
+        qi.token_id.to_string(),
+        format!("{:?}", qi.status).to_lowercase(),
+        qi.transaction_hash.clone().unwrap_or_default(),
+        qi.note.clone().unwrap_or_default(),
+    ]
+    .iter()
+    .map(|v| csv_field(v))
+    .collect::<Vec<String>>()
+    .join(",")
+}
+
+/// Dumps the whole migration queue as CSV into object storage and hands back a
+/// presigned url, the API never streams the file itself.
+pub async fn handle_queue_export(
+    queue_manager: Arc<dyn QueueManager>,
+    object_storage: Arc<dyn ObjectStorage>,
+    url_ttl: Duration,
+    clock: Arc<dyn Clock>,
+) -> Result<ExportArtifact, ExportError> {
+    let mut csv = String::from(
+        "id,keplr_wallet_pubkey,starknet_wallet_pubkey,project_id,token_id,status,transaction_hash,note\n",
+    );
+    let mut rows = 0;
+    let mut page = PageRequest {
+        after: None,
+        limit: EXPORT_PAGE_SIZE,
+    };
+    loop {
+        let items = queue_manager
+            .list_queue_items(None, &page)
+            .await
+            .map_err(ExportError::FailedToFetch)?;
+        for qi in &items.items {
+            csv.push_str(&queue_item_csv_row(qi));
+            csv.push('\n');
+        }
+        rows += items.items.len();
+
+        match items.next_cursor {
+            Some(cursor) => {
+                page.after = Some(
+                    Cursor::decode(&cursor)
+                        .map_err(|_| ExportError::FailedToFetch(QueueError::FailedToGetBatch))?,
+                )
+            }
+            None => break,
+        };
+    }
+
+    let key = format!("exports/migration-queue-{}.csv", clock.now_secs());
+    object_storage
+        .put_object(&key, "text/csv", csv.into_bytes())
+        .await
+        .map_err(|e| {
+            error!("Failed to upload export {} : {:#?}", key, e);
+            ExportError::FailedToStore(e)
+        })?;
+    info!("Exported {} queue items to {}", rows, key);
+
+    let url = object_storage
+        .presigned_url(&key, url_ttl)
+        .map_err(ExportError::FailedToStore)?;
+
+    Ok(ExportArtifact { key, rows, url })
+}
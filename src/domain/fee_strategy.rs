@@ -0,0 +1,268 @@
+use core::fmt::{Debug, Formatter};
+use log::{info, warn};
+use std::sync::{Arc, Mutex};
+
+use super::ids::StarknetAddress;
+
+// Weight of the latest accepted fee when none is given
+const DEFAULT_EWMA_WEIGHT: f64 = 0.2;
+
+#[derive(Debug)]
+pub enum FeeStrategyError {
+    InvalidDefinition(String),
+}
+
+#[derive(Debug)]
+pub enum FeeError {
+    /// Network fees are over what the bridge pays, the transaction waits for them to drop
+    AboveCap {
+        estimated_fee: u128,
+        cap: u128,
+    },
+    Overflow,
+}
+
+/// Decides the max fee of mint transactions, amounts are in wei.
+pub trait FeeStrategy: Send + Sync {
+    /// Max fee of a transaction of `calls` mint calls on `project_id` estimated to cost
+    /// `estimated_fee`.
+    fn max_fee(
+        &self,
+        project_id: &StarknetAddress,
+        estimated_fee: u128,
+        calls: usize,
+    ) -> Result<u128, FeeError>;
+
+    /// Fee an accepted transaction of `calls` mint calls paid, strategies following the
+    /// network learn from it.
+    fn record_accepted_fee(&self, project_id: &StarknetAddress, actual_fee: u128, calls: usize);
+}
+
+impl Debug for dyn FeeStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "FeeStrategy{{}}")
+    }
+}
+
+/// Factor applied to fee estimates to get the max fee of mint transactions, so they
+/// still go through when fees spike between estimation and inclusion.
+#[derive(Debug, Clone)]
+pub struct FeeMultipliers {
+    default: f64,
+    projects: Vec<(String, f64)>,
+}
+
+impl FeeMultipliers {
+    /// Parses project overrides formatted as `project_address=multiplier`.
+    pub fn parse(default: f64, definitions: &[String]) -> Result<Self, FeeStrategyError> {
+        let mut projects = Vec::new();
+        for definition in definitions {
+            let invalid = || FeeStrategyError::InvalidDefinition(definition.to_string());
+            let Some((project, multiplier)) = definition.split_once('=') else {
+                return Err(invalid());
+            };
+            let project: StarknetAddress = project.trim().parse().map_err(|_| invalid())?;
+            let multiplier = multiplier.trim().parse::<f64>().map_err(|_| invalid())?;
+            if !is_valid_multiplier(multiplier) {
+                return Err(invalid());
+            }
+            projects.push((address_key(&project), multiplier));
+        }
+        if !is_valid_multiplier(default) {
+            return Err(FeeStrategyError::InvalidDefinition(default.to_string()));
+        }
+
+        Ok(Self { default, projects })
+    }
+
+    pub fn for_project(&self, project_id: &StarknetAddress) -> f64 {
+        let project = address_key(project_id);
+        self.projects
+            .iter()
+            .find(|(p, _)| *p == project)
+            .map_or(self.default, |(_, m)| *m)
+    }
+}
+
+// Paying less than the estimate gets transactions rejected
+fn is_valid_multiplier(multiplier: f64) -> bool {
+    multiplier.is_finite() && 1.0 <= multiplier
+}
+
+// Zero padded and unpadded addresses are the same felt
+fn address_key(address: &StarknetAddress) -> String {
+    let digits = &address.as_str()[2..];
+    match digits.trim_start_matches('0') {
+        "" => "0".into(),
+        d => d.to_ascii_lowercase(),
+    }
+}
+
+fn multiply(fee: f64, multiplier: f64) -> Result<u128, FeeError> {
+    let max_fee = (fee * multiplier).ceil();
+    match max_fee.is_finite() && max_fee < u128::MAX as f64 {
+        true => Ok(max_fee as u128),
+        false => Err(FeeError::Overflow),
+    }
+}
+
+/// Pays the fee estimate times the project multiplier.
+pub struct MultiplierFeeStrategy {
+    multipliers: FeeMultipliers,
+}
+
+impl MultiplierFeeStrategy {
+    pub fn new(multipliers: FeeMultipliers) -> Self {
+        Self { multipliers }
+    }
+}
+
+impl FeeStrategy for MultiplierFeeStrategy {
+    fn max_fee(
+        &self,
+        project_id: &StarknetAddress,
+        estimated_fee: u128,
+        _calls: usize,
+    ) -> Result<u128, FeeError> {
+        multiply(
+            estimated_fee as f64,
+            self.multipliers.for_project(project_id),
+        )
+    }
+
+    fn record_accepted_fee(&self, _project_id: &StarknetAddress, _actual_fee: u128, _calls: usize) {
+        // The estimate alone decides the max fee
+    }
+}
+
+/// Follows the fees the network actually charged : the multiplier is applied to the
+/// estimate or, when larger, to the moving average per call of recently accepted fees,
+/// so estimates lagging behind a rising network do not get transactions stuck.
+pub struct EwmaFeeStrategy {
+    multipliers: FeeMultipliers,
+    weight: f64,
+    per_call: Mutex<Option<f64>>,
+}
+
+impl EwmaFeeStrategy {
+    pub fn new(multipliers: FeeMultipliers, weight: f64) -> Self {
+        Self {
+            multipliers,
+            weight,
+            per_call: Mutex::new(None),
+        }
+    }
+}
+
+impl FeeStrategy for EwmaFeeStrategy {
+    fn max_fee(
+        &self,
+        project_id: &StarknetAddress,
+        estimated_fee: u128,
+        calls: usize,
+    ) -> Result<u128, FeeError> {
+        let average = self.per_call.lock().unwrap().unwrap_or_default() * calls as f64;
+        multiply(
+            average.max(estimated_fee as f64),
+            self.multipliers.for_project(project_id),
+        )
+    }
+
+    fn record_accepted_fee(&self, _project_id: &StarknetAddress, actual_fee: u128, calls: usize) {
+        if 0 == calls {
+            return;
+        }
+        let fee = actual_fee as f64 / calls as f64;
+        let mut per_call = self.per_call.lock().unwrap();
+        *per_call = Some(match *per_call {
+            Some(average) => self.weight * fee + (1.0 - self.weight) * average,
+            None => fee,
+        });
+    }
+}
+
+/// Pays the fee estimate times the project multiplier, up to `cap`. Transactions
+/// estimated over the cap are not sent, they are tried again once fees went down.
+pub struct CappedFeeStrategy {
+    multipliers: FeeMultipliers,
+    cap: u128,
+}
+
+impl CappedFeeStrategy {
+    pub fn new(multipliers: FeeMultipliers, cap: u128) -> Self {
+        Self { multipliers, cap }
+    }
+}
+
+impl FeeStrategy for CappedFeeStrategy {
+    fn max_fee(
+        &self,
+        project_id: &StarknetAddress,
+        estimated_fee: u128,
+        _calls: usize,
+    ) -> Result<u128, FeeError> {
+        if self.cap < estimated_fee {
+            warn!(
+                "Mint fee estimated to {}, over the {} cap",
+                estimated_fee, self.cap
+            );
+            return Err(FeeError::AboveCap {
+                estimated_fee,
+                cap: self.cap,
+            });
+        }
+        let max_fee = multiply(
+            estimated_fee as f64,
+            self.multipliers.for_project(project_id),
+        )?;
+
+        Ok(max_fee.min(self.cap))
+    }
+
+    fn record_accepted_fee(&self, _project_id: &StarknetAddress, _actual_fee: u128, _calls: usize) {
+        // The estimate alone decides the max fee
+    }
+}
+
+/// Strategy of `network` among definitions formatted as `network=multiplier`,
+/// `network=ewma[:weight]` or `network=cap:max_fee`. Networks without one pay the
+/// estimate times the multiplier.
+pub fn fee_strategy_for_network(
+    network: &str,
+    definitions: &[String],
+    multipliers: FeeMultipliers,
+) -> Result<Arc<dyn FeeStrategy>, FeeStrategyError> {
+    let mut strategy = None;
+    for definition in definitions {
+        let invalid = || FeeStrategyError::InvalidDefinition(definition.to_string());
+        let (definition_network, kind) = definition.split_once('=').ok_or_else(invalid)?;
+        let (kind, parameter) = match kind.split_once(':') {
+            Some((kind, parameter)) => (kind.trim(), Some(parameter.trim())),
+            None => (kind.trim(), None),
+        };
+        let parsed: Arc<dyn FeeStrategy> = match (kind, parameter) {
+            ("multiplier", None) => Arc::new(MultiplierFeeStrategy::new(multipliers.clone())),
+            ("ewma", weight) => {
+                let weight = match weight {
+                    Some(w) => w.parse::<f64>().map_err(|_| invalid())?,
+                    None => DEFAULT_EWMA_WEIGHT,
+                };
+                if !(0.0 < weight && weight <= 1.0) {
+                    return Err(invalid());
+                }
+                Arc::new(EwmaFeeStrategy::new(multipliers.clone(), weight))
+            }
+            ("cap", Some(cap)) => Arc::new(CappedFeeStrategy::new(
+                multipliers.clone(),
+                cap.parse::<u128>().map_err(|_| invalid())?,
+            )),
+            _ => return Err(invalid()),
+        };
+        if definition_network.trim() == network {
+            info!("Using the {} fee strategy on {}", kind, network);
+            strategy = Some(parsed);
+        }
+    }
+
+    Ok(strategy.unwrap_or_else(|| Arc::new(MultiplierFeeStrategy::new(multipliers))))
+}
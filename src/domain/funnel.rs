@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{sync::Arc, time::Duration};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{clock::Clock, ids::ProjectId};
+
+// Frontends generate a uuid, anything longer is not a session id
+const MAX_SESSION_ID_LENGTH: usize = 64;
+
+/// Step of the migration funnel reached by a frontend session.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FunnelStep {
+    WalletConnected,
+    SignatureShown,
+    BridgeSubmitted,
+}
+
+impl FunnelStep {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WalletConnected => "wallet_connected",
+            Self::SignatureShown => "signature_shown",
+            Self::BridgeSubmitted => "bridge_submitted",
+        }
+    }
+}
+
+/// Telemetry sent by the frontend. Sessions are random ids the frontend keeps for the
+/// browser tab, wallets are never sent so events cannot be tied back to a customer.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FunnelEventRequest {
+    pub event: FunnelStep,
+    pub session_id: String,
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    pub project_id: Option<ProjectId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FunnelEvent {
+    pub id: Uuid,
+    pub session_id: String,
+    pub event: FunnelStep,
+    pub project_id: Option<ProjectId>,
+    // Epoch milliseconds
+    pub created_at: i64,
+}
+
+#[derive(Debug)]
+pub enum FunnelEventError {
+    InvalidSession,
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait FunnelEventRepository: Send + Sync {
+    async fn save_event(&self, event: &FunnelEvent) -> Result<(), FunnelEventError>;
+    /// Deletes events recorded before `before` (epoch milliseconds), returns how many were.
+    async fn delete_events_before(&self, before: i64) -> Result<u64, FunnelEventError>;
+}
+
+impl Debug for dyn FunnelEventRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "FunnelEventRepository{{}}")
+    }
+}
+
+/// Keeps a sample of the funnel events for `retention`. Sampling is decided per session,
+/// so a kept session has every one of its steps and drop offs stay comparable.
+pub struct FunnelAnalytics {
+    repository: Arc<dyn FunnelEventRepository>,
+    clock: Arc<dyn Clock>,
+    sample_rate: f64,
+    retention: Duration,
+}
+
+impl FunnelAnalytics {
+    pub fn new(
+        repository: Arc<dyn FunnelEventRepository>,
+        clock: Arc<dyn Clock>,
+        sample_rate: f64,
+        retention: Duration,
+    ) -> Self {
+        Self {
+            repository,
+            clock,
+            sample_rate,
+            retention,
+        }
+    }
+
+    /// Records the event when its session is sampled, returns whether it was.
+    pub async fn record(&self, request: &FunnelEventRequest) -> Result<bool, FunnelEventError> {
+        if !is_valid_session_id(&request.session_id) {
+            return Err(FunnelEventError::InvalidSession);
+        }
+        if !self.is_sampled(&request.session_id) {
+            return Ok(false);
+        }
+
+        let event = FunnelEvent {
+            id: Uuid::new_v4(),
+            session_id: request.session_id.to_string(),
+            event: request.event,
+            project_id: request.project_id.clone(),
+            created_at: self.clock.now_ms(),
+        };
+        if let Err(e) = self.repository.save_event(&event).await {
+            error!("Failed to record funnel event {:#?}", e);
+            return Err(e);
+        }
+
+        Ok(true)
+    }
+
+    fn is_sampled(&self, session_id: &str) -> bool {
+        if 1.0 <= self.sample_rate {
+            return true;
+        }
+        let digest = Sha256::digest(session_id.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+
+        (u64::from_be_bytes(bytes) as f64 / u64::MAX as f64) < self.sample_rate
+    }
+
+    /// Drops events older than the retention period.
+    pub async fn prune(&self) -> Result<u64, FunnelEventError> {
+        let before = self.clock.now_ms() - self.retention.as_millis() as i64;
+        let deleted = self.repository.delete_events_before(before).await?;
+        if 0 < deleted {
+            info!("Pruned {} funnel events", deleted);
+        }
+
+        Ok(deleted)
+    }
+}
+
+impl Debug for FunnelAnalytics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "FunnelAnalytics{{sample_rate: {}, retention: {:?}}}",
+            self.sample_rate, self.retention
+        )
+    }
+}
+
+fn is_valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id.len() <= MAX_SESSION_ID_LENGTH
+        && session_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
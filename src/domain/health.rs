@@ -0,0 +1,94 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use futures::future::join_all;
+use log::warn;
+use serde_derive::Serialize;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use utoipa::ToSchema;
+
+#[derive(Debug)]
+pub enum HealthCheckError {
+    Failed(String),
+    TimedOut,
+}
+
+/// Dependency the api cannot serve customers without, checked by the readiness probe.
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    fn name(&self) -> &str;
+    /// Cheapest call proving the dependency answers.
+    async fn check(&self) -> Result<(), HealthCheckError>;
+}
+
+impl Debug for dyn HealthCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "HealthCheck{{{}}}", self.name())
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyState {
+    Up,
+    Down,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub status: DependencyState,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Answer of the readiness probe, ready once every dependency is up.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct Readiness {
+    pub ready: bool,
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+/// Checks every dependency concurrently, giving each at most `timeout` so a hanging one
+/// answers the probe as down rather than making it time out.
+pub async fn check_readiness(checks: &[Arc<dyn HealthCheck>], timeout: Duration) -> Readiness {
+    let dependencies: Vec<DependencyStatus> = join_all(checks.iter().map(|check| async move {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(timeout, check.check()).await {
+            Ok(r) => r,
+            Err(_elapsed) => Err(HealthCheckError::TimedOut),
+        };
+        let latency_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok(()) => DependencyStatus {
+                name: check.name().to_string(),
+                status: DependencyState::Up,
+                latency_ms,
+                error: None,
+            },
+            Err(e) => {
+                warn!("Health check of {} failed : {:#?}", check.name(), e);
+                DependencyStatus {
+                    name: check.name().to_string(),
+                    status: DependencyState::Down,
+                    latency_ms,
+                    error: Some(match e {
+                        HealthCheckError::Failed(reason) => reason,
+                        HealthCheckError::TimedOut => {
+                            format!("no answer within {}ms", timeout.as_millis())
+                        }
+                    }),
+                }
+            }
+        }
+    }))
+    .await;
+
+    Readiness {
+        ready: dependencies.iter().all(|d| DependencyState::Up == d.status),
+        dependencies,
+    }
+}
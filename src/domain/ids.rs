@@ -0,0 +1,202 @@
+use core::fmt::{Display, Formatter};
+use serde_derive::{Deserialize, Serialize};
+use std::{borrow::Borrow, str::FromStr};
+use uuid::Uuid;
+
+const MAX_ID_LENGTH: usize = 256;
+// Felts are 252 bits, at most 63 hex digits but zero padded addresses are common
+const MAX_STARKNET_ADDRESS_DIGITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdError {
+    Empty(&'static str),
+    TooLong(&'static str),
+    InvalidFormat(&'static str, String),
+}
+
+impl Display for IdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            IdError::Empty(kind) => write!(f, "{} cannot be empty", kind),
+            IdError::TooLong(kind) => {
+                write!(f, "{} cannot exceed {} characters", kind, MAX_ID_LENGTH)
+            }
+            IdError::InvalidFormat(kind, value) => write!(f, "invalid {} '{}'", kind, value),
+        }
+    }
+}
+
+fn validate_printable(kind: &'static str, value: &str) -> Result<(), IdError> {
+    if value.is_empty() {
+        return Err(IdError::Empty(kind));
+    }
+    if value.len() > MAX_ID_LENGTH {
+        return Err(IdError::TooLong(kind));
+    }
+    if !value.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(IdError::InvalidFormat(kind, value.into()));
+    }
+    Ok(())
+}
+
+fn validate_starknet_address(kind: &'static str, value: &str) -> Result<(), IdError> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .ok_or_else(|| IdError::InvalidFormat(kind, value.into()))?;
+    if digits.is_empty()
+        || digits.len() > MAX_STARKNET_ADDRESS_DIGITS
+        || !digits.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(IdError::InvalidFormat(kind, value.into()));
+    }
+    Ok(())
+}
+
+/// Declares a validated string identifier, serialized as a plain string so the
+/// wire format does not change.
+macro_rules! string_id {
+    ($(#[$meta:meta])* $name:ident, $kind:literal, $validate:path) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn new(value: &str) -> Result<Self, IdError> {
+                $validate($kind, value)?;
+                Ok(Self(value.to_string()))
+            }
+
+            /// Wraps a value read back from storage, it was validated before being written.
+            pub fn unchecked(value: impl Into<String>) -> Self {
+                Self(value.into())
+            }
+
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = IdError;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Self::new(value)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = IdError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $validate($kind, &value)?;
+                Ok(Self(value))
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+    };
+}
+
+string_id!(
+    /// Juno CW721 contract address of a project, key of customer registrations.
+    ProjectId,
+    "project id",
+    validate_printable
+);
+string_id!(
+    /// Juno account, i.e. the customer keplr wallet.
+    JunoAddress,
+    "juno address",
+    validate_printable
+);
+string_id!(
+    /// Starknet account or contract address, hex encoded felt.
+    StarknetAddress,
+    "starknet address",
+    validate_starknet_address
+);
+string_id!(TokenId, "token id", validate_printable);
+string_id!(
+    /// Starknet transaction hash, hex encoded felt.
+    TransactionHash,
+    "transaction hash",
+    validate_starknet_address
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct QueueItemId(Uuid);
+
+impl QueueItemId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+
+    pub fn as_uuid(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl Default for QueueItemId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Uuid> for QueueItemId {
+    fn from(value: Uuid) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for QueueItemId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for QueueItemId {
+    type Err = IdError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Uuid::parse_str(value)
+            .map(Self)
+            .map_err(|_| IdError::InvalidFormat("queue item id", value.into()))
+    }
+}
@@ -0,0 +1,275 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    bridge::{QueueItem, QueueManager, QueueStatus, StarknetManager, TransactionRepository},
+    clock::Clock,
+    ids::{JunoAddress, StarknetAddress},
+    pagination::PageRequest,
+    project_registry::ProjectRegistry,
+    save_customer_data::DataRepository,
+    support_bundle::{handle_support_bundle, SupportBundleRequest},
+    wallet_link::WalletLinkRepository,
+};
+
+pub const DEAD_LETTER_LABEL: &str = "dead-letter";
+
+// Dead letters listed per call to the queue manager
+const DEAD_LETTER_PAGE_SIZE: i64 = 100;
+
+/// JSON document joined to an issue, e.g. the support bundle of the customer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssueAttachment {
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Issue {
+    pub title: String,
+    pub body: String,
+    pub labels: Vec<String>,
+    pub attachment: Option<IssueAttachment>,
+}
+
+/// Remembers a failure has been ticketed, `subject` identifies the failure (e.g.
+/// `dead_letter:{queue_item_id}`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IssueRecord {
+    pub subject: String,
+    pub issue_url: String,
+    // Epoch milliseconds
+    pub created_at: i64,
+}
+
+#[derive(Debug)]
+pub enum IssueTrackerError {
+    Rejected(u16),
+    Unreachable(String),
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait IssueTracker: Send + Sync {
+    /// Opens the issue, returns its url.
+    async fn create_issue(&self, issue: &Issue) -> Result<String, IssueTrackerError>;
+}
+
+impl Debug for dyn IssueTracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "IssueTracker{{}}")
+    }
+}
+
+#[async_trait]
+pub trait IssueRecordRepository: Send + Sync {
+    /// Subjects among the given ones an issue was already opened for.
+    async fn get_reported_subjects(
+        &self,
+        subjects: &[String],
+    ) -> Result<Vec<String>, IssueTrackerError>;
+    async fn save_records(&self, records: &[IssueRecord]) -> Result<(), IssueTrackerError>;
+}
+
+impl Debug for dyn IssueRecordRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "IssueRecordRepository{{}}")
+    }
+}
+
+/// Opens one issue per failure, failures already ticketed are not reported again.
+#[derive(Debug)]
+pub struct IssueReporter {
+    tracker: Arc<dyn IssueTracker>,
+    repository: Arc<dyn IssueRecordRepository>,
+    clock: Arc<dyn Clock>,
+}
+
+impl IssueReporter {
+    pub fn new(
+        tracker: Arc<dyn IssueTracker>,
+        repository: Arc<dyn IssueRecordRepository>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            tracker,
+            repository,
+            clock,
+        }
+    }
+
+    /// Subjects no issue was opened for yet.
+    pub async fn unreported(&self, subjects: &[String]) -> Result<Vec<String>, IssueTrackerError> {
+        let reported = self.repository.get_reported_subjects(subjects).await?;
+
+        Ok(subjects
+            .iter()
+            .filter(|s| !reported.contains(s))
+            .cloned()
+            .collect())
+    }
+
+    /// Opens the issue and records it for every subject it covers, returns its url.
+    pub async fn report(
+        &self,
+        subjects: &[String],
+        issue: &Issue,
+    ) -> Result<String, IssueTrackerError> {
+        let issue_url = self.tracker.create_issue(issue).await?;
+        let created_at = self.clock.now_ms();
+        let records: Vec<IssueRecord> = subjects
+            .iter()
+            .map(|subject| IssueRecord {
+                subject: subject.to_string(),
+                issue_url: issue_url.to_string(),
+                created_at,
+            })
+            .collect();
+        // Issue exists anyway, it would only be opened again on next run
+        if let Err(e) = self.repository.save_records(&records).await {
+            error!("Failed to record issue {} {:#?}", &issue_url, e);
+        }
+
+        Ok(issue_url)
+    }
+}
+
+pub fn dead_letter_subject(item: &QueueItem) -> Option<String> {
+    item.id.map(|id| format!("dead_letter:{}", id))
+}
+
+/// Opens an issue per customer and project with dead letters not ticketed yet, the
+/// support bundle of the customer attached. Returns the number of issues opened.
+pub async fn report_dead_letters(
+    reporter: &IssueReporter,
+    queue_manager: Arc<dyn QueueManager>,
+    project_registry: &ProjectRegistry,
+    data_repository: Arc<dyn DataRepository>,
+    wallet_link_repository: Arc<dyn WalletLinkRepository>,
+    transaction_repository: Arc<dyn TransactionRepository>,
+    starknet_manager: Arc<dyn StarknetManager>,
+    cancel: &CancellationToken,
+) -> Result<usize, IssueTrackerError> {
+    let mut dead_letters = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        // Cursor comes from the previous page, it is always valid
+        let page = PageRequest::new(
+            cursor.as_deref(),
+            Some(DEAD_LETTER_PAGE_SIZE),
+            DEAD_LETTER_PAGE_SIZE,
+        )
+        .unwrap();
+        let page = match queue_manager
+            .list_queue_items(Some(QueueStatus::DeadLetter), &page)
+            .await
+        {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to list dead letters {:#?}", e);
+                return Err(IssueTrackerError::PersistenceIssue);
+            }
+        };
+        dead_letters.extend(page.items);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    let mut groups: Vec<((JunoAddress, StarknetAddress), Vec<QueueItem>)> = Vec::new();
+    for item in dead_letters {
+        let key = (item.keplr_wallet_pubkey.clone(), item.project_id.clone());
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, items)) => items.push(item),
+            None => groups.push((key, vec![item])),
+        }
+    }
+
+    let mut opened = 0;
+    for ((keplr_wallet_pubkey, starknet_project_addr), items) in groups {
+        if cancel.is_cancelled() {
+            break;
+        }
+        let subjects: Vec<String> = items.iter().filter_map(dead_letter_subject).collect();
+        let unreported = reporter.unreported(&subjects).await?;
+        if unreported.is_empty() {
+            continue;
+        }
+        let items: Vec<&QueueItem> = items
+            .iter()
+            .filter(|i| dead_letter_subject(i).map_or(false, |s| unreported.contains(&s)))
+            .collect();
+
+        let attachment = match project_registry.find_by_starknet_contract(&starknet_project_addr) {
+            Some(project) => {
+                let bundle = handle_support_bundle(
+                    &SupportBundleRequest {
+                        keplr_wallet_pubkey: &keplr_wallet_pubkey,
+                        project_id: &project.juno_contract,
+                        starknet_project_addr: &starknet_project_addr,
+                    },
+                    data_repository.clone(),
+                    queue_manager.clone(),
+                    wallet_link_repository.clone(),
+                    transaction_repository.clone(),
+                    starknet_manager.clone(),
+                    cancel,
+                )
+                .await;
+                serde_json::to_string_pretty(&bundle)
+                    .ok()
+                    .map(|content| IssueAttachment {
+                        name: format!(
+                            "support-bundle-{}-{}.json",
+                            keplr_wallet_pubkey, project.juno_contract
+                        ),
+                        content,
+                    })
+            }
+            None => None,
+        };
+
+        let mut body = format!(
+            "Tokens of {} on project {} used all of their mint attempts and were parked in the dead letter queue.\n\n",
+            keplr_wallet_pubkey, starknet_project_addr
+        );
+        for item in &items {
+            body.push_str(&format!(
+                "- token {} (queue item {}) : {} attempts, last note {}\n",
+                item.token_id,
+                item.id.map(|id| id.to_string()).unwrap_or_default(),
+                item.attempts,
+                item.note.as_deref().unwrap_or("none")
+            ));
+        }
+        let issue = Issue {
+            title: format!(
+                "{} dead letter(s) for {} on project {}",
+                items.len(),
+                keplr_wallet_pubkey,
+                starknet_project_addr
+            ),
+            body,
+            labels: vec!["bridge".into(), DEAD_LETTER_LABEL.into()],
+            attachment,
+        };
+
+        match reporter.report(&unreported, &issue).await {
+            Ok(url) => {
+                info!("Opened issue {} for {} dead letters", url, items.len());
+                opened += 1;
+            }
+            Err(e) => error!(
+                "Failed to open issue for dead letters of {} {:#?}",
+                keplr_wallet_pubkey, e
+            ),
+        }
+    }
+
+    Ok(opened)
+}
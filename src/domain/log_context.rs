@@ -0,0 +1,44 @@
+use std::{cell::RefCell, future::Future};
+
+/// Fields attached to every log line written while handling one request or queue batch.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogFields {
+    pub request_id: Option<String>,
+    pub wallet_pubkey: Option<String>,
+    pub project_id: Option<String>,
+}
+
+tokio::task_local! {
+    // Task local rather than thread local, tasks move between threads at every await
+    static LOG_CONTEXT: RefCell<LogFields>;
+}
+
+/// Runs `future` with `fields` attached to its log lines.
+pub async fn with_log_context<F: Future>(fields: LogFields, future: F) -> F::Output {
+    LOG_CONTEXT.scope(RefCell::new(fields), future).await
+}
+
+/// Attaches the customer wallet and project to the following log lines of the current
+/// request, nothing happens outside of a log context.
+pub fn annotate_log_context(wallet_pubkey: &str, project_id: Option<&str>) {
+    let _ = LOG_CONTEXT.try_with(|context| {
+        let mut fields = context.borrow_mut();
+        fields.wallet_pubkey = Some(wallet_pubkey.to_string());
+        if let Some(project_id) = project_id {
+            fields.project_id = Some(project_id.to_string());
+        }
+    });
+}
+
+/// Replaces the fields of the current log context, e.g. when a worker moves on to the
+/// next batch. Nothing happens outside of a log context.
+pub fn set_log_fields(fields: LogFields) {
+    let _ = LOG_CONTEXT.try_with(|context| *context.borrow_mut() = fields);
+}
+
+/// Fields of the current log context, empty outside of one.
+pub fn current_log_fields() -> LogFields {
+    LOG_CONTEXT
+        .try_with(|context| context.borrow().clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,207 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{info, warn};
+use serde_derive::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+
+use super::{clock::Clock, metrics::Metrics};
+
+/// Tables expected to grow along the migration : the queue, its history and the audit
+/// trails, then the ones holding short lived rows.
+pub const MONITORED_TABLES: &[&str] = &[
+    "migration_queue",
+    "migration_queue_history",
+    "bridge_requests",
+    "breakglass_mints",
+    "webhook_delivery_log",
+    "signature_challenges",
+    "token_locks",
+];
+
+// Tables with too few rows are not worth a hint whatever their share of dead rows
+const MIN_ROWS_FOR_HINT: i64 = 1_000;
+// Statistics older than this are worth refreshing on tables that keep changing
+const STALE_ANALYZE: Duration = Duration::from_secs(7 * 86_400);
+
+/// Size of a table as the database sees it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TableStats {
+    pub table: String,
+    pub live_rows: i64,
+    pub dead_rows: i64,
+    // Table, indexes and toast
+    pub total_bytes: i64,
+    // Epoch milliseconds of the last vacuum and analyze, manual or automatic
+    pub last_vacuum: Option<i64>,
+    pub last_analyze: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceAction {
+    Vacuum,
+    Analyze,
+}
+
+/// Statement an operator should run on a table, and why.
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct MaintenanceHint {
+    pub table: String,
+    pub action: MaintenanceAction,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct MaintenanceOverview {
+    pub tables: Vec<TableStats>,
+    pub hints: Vec<MaintenanceHint>,
+    // Whether the worker deletes expired challenges and token locks
+    pub prune_expired_rows: bool,
+}
+
+#[derive(Debug)]
+pub enum MaintenanceError {
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait MaintenanceRepository: Send + Sync {
+    /// Stats of the `tables` that exist, in no particular order.
+    async fn table_stats(&self, tables: &[&str]) -> Result<Vec<TableStats>, MaintenanceError>;
+    /// Deletes signature challenges and token locks expired at `now` (epoch
+    /// milliseconds), returns how many rows were.
+    async fn delete_expired_rows(&self, now: i64) -> Result<u64, MaintenanceError>;
+}
+
+impl Debug for dyn MaintenanceRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "MaintenanceRepository{{}}")
+    }
+}
+
+/// Watches the growth of the database so it does not surprise anyone mid migration :
+/// reports table sizes as metrics, hints at vacuums and analyzes worth running and
+/// optionally deletes expired rows. Runs at most once per `interval`.
+pub struct DatabaseMaintenance {
+    repository: Arc<dyn MaintenanceRepository>,
+    clock: Arc<dyn Clock>,
+    interval: Duration,
+    dead_row_ratio: f64,
+    prune_expired_rows: bool,
+    // Epoch milliseconds of the last run
+    last_run: Mutex<Option<i64>>,
+}
+
+impl DatabaseMaintenance {
+    pub fn new(
+        repository: Arc<dyn MaintenanceRepository>,
+        clock: Arc<dyn Clock>,
+        interval: Duration,
+        dead_row_ratio: f64,
+        prune_expired_rows: bool,
+    ) -> Self {
+        Self {
+            repository,
+            clock,
+            interval,
+            dead_row_ratio,
+            prune_expired_rows,
+            last_run: Mutex::new(None),
+        }
+    }
+
+    pub async fn overview(&self) -> Result<MaintenanceOverview, MaintenanceError> {
+        let mut tables = self.repository.table_stats(MONITORED_TABLES).await?;
+        tables.sort_by(|a, b| a.table.cmp(&b.table));
+        let hints = tables.iter().flat_map(|t| self.hints(t)).collect();
+
+        Ok(MaintenanceOverview {
+            tables,
+            hints,
+            prune_expired_rows: self.prune_expired_rows,
+        })
+    }
+
+    fn hints(&self, stats: &TableStats) -> Vec<MaintenanceHint> {
+        let mut hints = Vec::new();
+        let rows = stats.live_rows + stats.dead_rows;
+        if rows < MIN_ROWS_FOR_HINT {
+            return hints;
+        }
+        let dead_ratio = stats.dead_rows as f64 / rows as f64;
+        if self.dead_row_ratio <= dead_ratio {
+            hints.push(MaintenanceHint {
+                table: stats.table.to_string(),
+                action: MaintenanceAction::Vacuum,
+                reason: format!("{:.0}% of the rows are dead", dead_ratio * 100.0),
+            });
+        }
+        let stale_before = self.clock.now_ms() - STALE_ANALYZE.as_millis() as i64;
+        match stats.last_analyze {
+            None => hints.push(MaintenanceHint {
+                table: stats.table.to_string(),
+                action: MaintenanceAction::Analyze,
+                reason: "Statistics were never collected".into(),
+            }),
+            Some(at) if at < stale_before => hints.push(MaintenanceHint {
+                table: stats.table.to_string(),
+                action: MaintenanceAction::Analyze,
+                reason: format!(
+                    "Statistics are {} days old",
+                    (self.clock.now_ms() - at) / 86_400_000
+                ),
+            }),
+            Some(_) => (),
+        }
+        hints
+    }
+
+    /// Reports table sizes and prunes expired rows when due, returns the number of rows
+    /// deleted.
+    pub async fn run(&self, metrics: &dyn Metrics) -> Result<u64, MaintenanceError> {
+        let now = self.clock.now_ms();
+        {
+            let mut last_run = self.last_run.lock().await;
+            if last_run.map_or(false, |at| now < at + self.interval.as_millis() as i64) {
+                return Ok(0);
+            }
+            *last_run = Some(now);
+        }
+
+        let overview = self.overview().await?;
+        for stats in &overview.tables {
+            let tags = [("table", stats.table.as_str())];
+            metrics.gauge("db_table_live_rows", stats.live_rows as f64, &tags);
+            metrics.gauge("db_table_dead_rows", stats.dead_rows as f64, &tags);
+            metrics.gauge("db_table_bytes", stats.total_bytes as f64, &tags);
+        }
+        for hint in &overview.hints {
+            warn!(
+                "Table {} needs {:?} : {}",
+                hint.table, hint.action, hint.reason
+            );
+        }
+
+        if !self.prune_expired_rows {
+            return Ok(0);
+        }
+        let deleted = self.repository.delete_expired_rows(now).await?;
+        if 0 < deleted {
+            info!("Pruned {} expired challenges and token locks", deleted);
+        }
+
+        Ok(deleted)
+    }
+}
+
+impl Debug for DatabaseMaintenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "DatabaseMaintenance{{interval: {:?}, prune_expired_rows: {}}}",
+            self.interval, self.prune_expired_rows
+        )
+    }
+}
@@ -0,0 +1,33 @@
+use core::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+/// Metric labels, as `(name, value)` pairs.
+pub type Tags<'a> = &'a [(&'a str, &'a str)];
+
+/// Where operational metrics go, backends either expose them to be scraped or push
+/// them to an agent.
+pub trait Metrics: Send + Sync {
+    fn increment(&self, name: &str, tags: Tags);
+    fn gauge(&self, name: &str, value: f64, tags: Tags);
+    fn timing(&self, name: &str, duration: Duration, tags: Tags);
+    /// Text exposition of the collected metrics, for backends scraped over HTTP.
+    fn render(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Debug for dyn Metrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Metrics{{}}")
+    }
+}
+
+/// Drops every metric, used when no backend is configured.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {
+    fn increment(&self, _name: &str, _tags: Tags) {}
+    fn gauge(&self, _name: &str, _value: f64, _tags: Tags) {}
+    fn timing(&self, _name: &str, _duration: Duration, _tags: Tags) {}
+}
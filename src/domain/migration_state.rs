@@ -0,0 +1,122 @@
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use utoipa::ToSchema;
+
+use super::{
+    bridge::{QueueItem, QueueStatus},
+    ids::TokenId,
+    transfer_proof::ProvenQueueItem,
+};
+
+/// Order of the items of a migration, queue order when none is asked.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStateSort {
+    /// Numeric token ids first in ascending order
+    TokenId,
+    /// Pending, processing, success, error then dead letter items
+    Status,
+}
+
+/// How the items of a migration are answered, a flat list when none is asked.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStateGrouping {
+    Status,
+}
+
+pub fn sort_items(items: &mut [QueueItem], sort: MigrationStateSort) {
+    match sort {
+        MigrationStateSort::TokenId => {
+            items.sort_by(|a, b| compare_token_ids(&a.token_id, &b.token_id))
+        }
+        MigrationStateSort::Status => items.sort_by(|a, b| {
+            status_rank(&a.status)
+                .cmp(&status_rank(&b.status))
+                .then_with(|| compare_token_ids(&a.token_id, &b.token_id))
+        }),
+    }
+}
+
+// Token ids are numbers on every bridged project, anything else goes last
+fn compare_token_ids(a: &TokenId, b: &TokenId) -> Ordering {
+    match (a.as_str().parse::<u64>(), b.as_str().parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.as_str().cmp(b.as_str()),
+    }
+}
+
+fn status_rank(status: &QueueStatus) -> u8 {
+    match status {
+        QueueStatus::Pending => 0,
+        QueueStatus::Processing => 1,
+        QueueStatus::Success => 2,
+        QueueStatus::Error => 3,
+        QueueStatus::DeadLetter => 4,
+    }
+}
+
+/// Items of a migration per status.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, ToSchema)]
+pub struct StatusCounts {
+    pub pending: usize,
+    pub processing: usize,
+    pub success: usize,
+    pub error: usize,
+    pub dead_letter: usize,
+}
+
+impl StatusCounts {
+    pub fn of(items: &[QueueItem]) -> Self {
+        let mut counts = Self::default();
+        for item in items {
+            *match item.status {
+                QueueStatus::Pending => &mut counts.pending,
+                QueueStatus::Processing => &mut counts.processing,
+                QueueStatus::Success => &mut counts.success,
+                QueueStatus::Error => &mut counts.error,
+                QueueStatus::DeadLetter => &mut counts.dead_letter,
+            } += 1;
+        }
+        counts
+    }
+}
+
+/// Items of a migration grouped by status, so frontends render them as is. `counts`
+/// and `total` cover the whole migration, even when only some statuses are listed.
+#[derive(Serialize, Debug, Default, ToSchema)]
+pub struct GroupedMigrationState {
+    pub pending: Vec<ProvenQueueItem>,
+    pub processing: Vec<ProvenQueueItem>,
+    pub success: Vec<ProvenQueueItem>,
+    pub error: Vec<ProvenQueueItem>,
+    pub dead_letter: Vec<ProvenQueueItem>,
+    pub counts: StatusCounts,
+    pub total: usize,
+}
+
+impl GroupedMigrationState {
+    pub fn new(items: Vec<ProvenQueueItem>, counts: StatusCounts) -> Self {
+        let mut state = Self {
+            total: counts.pending
+                + counts.processing
+                + counts.success
+                + counts.error
+                + counts.dead_letter,
+            counts,
+            ..Default::default()
+        };
+        for item in items {
+            match item.item.status {
+                QueueStatus::Pending => state.pending.push(item),
+                QueueStatus::Processing => state.processing.push(item),
+                QueueStatus::Success => state.success.push(item),
+                QueueStatus::Error => state.error.push(item),
+                QueueStatus::DeadLetter => state.dead_letter.push(item),
+            }
+        }
+        state
+    }
+}
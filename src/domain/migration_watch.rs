@@ -0,0 +1,80 @@
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    bridge::{QueueItem, QueueManager},
+    ids::{JunoAddress, StarknetAddress},
+    stats::status_label,
+};
+
+/// Holds customer status requests open until their migration changes, for clients that
+/// cannot keep a stream open. Items are updated by workers in another process, so the
+/// queue is read again every `poll_interval`.
+#[derive(Debug, Clone)]
+pub struct MigrationWatch {
+    poll_interval: Duration,
+    max_wait: Duration,
+}
+
+impl MigrationWatch {
+    pub fn new(poll_interval: Duration, max_wait: Duration) -> Self {
+        Self {
+            poll_interval,
+            max_wait,
+        }
+    }
+
+    /// Migration state once it differs from the one at call time, or as it is after
+    /// `wait` (capped to `max_wait`) elapsed or `cancel` fired.
+    pub async fn wait_for_change(
+        &self,
+        queue_manager: &dyn QueueManager,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
+        wait: Duration,
+        cancel: &CancellationToken,
+    ) -> Vec<QueueItem> {
+        let known = queue_manager
+            .get_customer_migration_state(keplr_wallet_pubkey, project_id)
+            .await;
+        let deadline = tokio::time::Instant::now() + wait.min(self.max_wait);
+
+        let mut current = known.clone();
+        while fingerprint(&current) == fingerprint(&known) {
+            let now = tokio::time::Instant::now();
+            if deadline <= now {
+                break;
+            }
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(self.poll_interval.min(deadline - now)) => {}
+            }
+            current = queue_manager
+                .get_customer_migration_state(keplr_wallet_pubkey, project_id)
+                .await;
+        }
+
+        current
+    }
+}
+
+// Token, status, transaction hash and note of an item
+type ItemFingerprint<'a> = (&'a str, &'static str, Option<&'a str>, Option<&'a str>);
+
+// What customers see changing, backends return items in no particular order
+fn fingerprint(items: &[QueueItem]) -> Vec<ItemFingerprint<'_>> {
+    let mut fingerprint: Vec<ItemFingerprint<'_>> = items
+        .iter()
+        .map(|qi| {
+            (
+                qi.token_id.as_str(),
+                status_label(&qi.status),
+                qi.transaction_hash.as_deref(),
+                qi.note.as_deref(),
+            )
+        })
+        .collect();
+    fingerprint.sort();
+
+    fingerprint
+}
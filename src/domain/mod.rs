@@ -1,3 +1,47 @@
+pub mod analytics;
+pub mod attestation;
+pub mod audit;
+pub mod backpressure;
+pub mod batch_size;
+pub mod breakglass;
 pub mod bridge;
+pub mod calendar;
+pub mod challenge;
+pub mod check_cache;
+pub mod clock;
 pub mod consume_queue;
+pub mod eligible_tokens;
+pub mod error_catalog;
+pub mod export;
+pub mod fee_strategy;
+pub mod funnel;
+pub mod health;
+pub mod ids;
+pub mod issue_tracker;
+pub mod log_context;
+pub mod maintenance;
+pub mod metrics;
+pub mod migration_state;
+pub mod migration_watch;
+pub mod pagination;
+pub mod post_mint;
+pub mod project_registry;
+pub mod proof_bundle;
+pub mod queue_admin;
+pub mod queue_broker;
+pub mod queue_migration;
+pub mod queue_snapshot;
+pub mod reconciliation;
+pub mod report;
+pub mod reverse_bridge;
 pub mod save_customer_data;
+pub mod schema_version;
+pub mod stats;
+pub mod status_message;
+pub mod storage;
+pub mod support_bundle;
+pub mod transaction_cache;
+pub mod transfer_proof;
+pub mod wallet_link;
+pub mod warm_up;
+pub mod webhook;
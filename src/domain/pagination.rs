@@ -0,0 +1,126 @@
+use serde_derive::Serialize;
+use uuid::Uuid;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug)]
+pub enum PaginationError {
+    InvalidCursor,
+    InvalidLimit,
+}
+
+/// Keyset position, rows are ordered by (created_at, id) so pages stay stable while
+/// new rows keep being written.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    // Epoch microseconds
+    pub created_at: i64,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: i64, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    /// Opaque representation handed to clients.
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}:{}", self.created_at, self.id))
+    }
+
+    pub fn decode(value: &str) -> Result<Self, PaginationError> {
+        let decoded = hex::decode(value).map_err(|_| PaginationError::InvalidCursor)?;
+        let decoded = String::from_utf8(decoded).map_err(|_| PaginationError::InvalidCursor)?;
+        let (created_at, id) = decoded
+            .split_once(':')
+            .ok_or(PaginationError::InvalidCursor)?;
+
+        Ok(Self {
+            created_at: created_at
+                .parse()
+                .map_err(|_| PaginationError::InvalidCursor)?,
+            id: Uuid::parse_str(id).map_err(|_| PaginationError::InvalidCursor)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PageRequest {
+    pub after: Option<Cursor>,
+    pub limit: i64,
+}
+
+impl PageRequest {
+    pub fn new(
+        cursor: Option<&str>,
+        limit: Option<i64>,
+        max_limit: i64,
+    ) -> Result<Self, PaginationError> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE.min(max_limit));
+        if limit < 1 || limit > max_limit {
+            return Err(PaginationError::InvalidLimit);
+        }
+        let after = match cursor {
+            Some(c) => Some(Cursor::decode(c)?),
+            None => None,
+        };
+
+        Ok(Self { after, limit })
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    // None once the last page has been reached
+    pub next_cursor: Option<String>,
+    // Position of each item, to merge pages of several sources
+    #[serde(skip)]
+    pub cursors: Vec<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from rows fetched with `limit + 1`, the extra row only tells
+    /// whether another page exists.
+    pub fn from_rows(mut rows: Vec<(Cursor, T)>, limit: i64) -> Self {
+        let has_more = rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+        Self::with_rows(rows, has_more)
+    }
+
+    /// Merges pages of several sources fetched with the same request, in cursor order.
+    /// Items past the last one of a source with more pages are left for the next page,
+    /// so none is skipped.
+    pub fn merge(pages: Vec<Page<T>>, limit: i64) -> Self {
+        let bound = pages
+            .iter()
+            .filter(|p| p.next_cursor.is_some())
+            .filter_map(|p| p.cursors.last().cloned())
+            .min();
+        let mut rows: Vec<(Cursor, T)> = pages
+            .into_iter()
+            .flat_map(|p| p.cursors.into_iter().zip(p.items))
+            .collect();
+        rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if let Some(bound) = &bound {
+            rows.retain(|(c, _)| c <= bound);
+        }
+        let has_more = bound.is_some() || rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+        Self::with_rows(rows, has_more)
+    }
+
+    fn with_rows(rows: Vec<(Cursor, T)>, has_more: bool) -> Self {
+        let next_cursor = match has_more {
+            true => rows.last().map(|(c, _)| c.encode()),
+            false => None,
+        };
+        let (cursors, items) = rows.into_iter().unzip();
+
+        Self {
+            items,
+            next_cursor,
+            cursors,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info, warn};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::bridge::QueueItem;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PostMintStatus {
+    Pending,
+    Done,
+    Failed,
+}
+
+/// One hook run for one minted queue item, retried by the worker until it succeeds
+/// or reaches the maximum attempts.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostMintExecution {
+    pub id: Uuid,
+    pub queue_item_id: Uuid,
+    pub hook: String,
+    pub project_id: String,
+    pub token_id: String,
+    pub starknet_account_addr: String,
+    pub transaction_hash: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub status: PostMintStatus,
+}
+
+#[derive(Debug)]
+pub enum PostMintHookError {
+    Failure(String),
+}
+
+#[derive(Debug)]
+pub enum PostMintError {
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait PostMintHook: Send + Sync {
+    async fn execute(&self, execution: &PostMintExecution) -> Result<(), PostMintHookError>;
+}
+
+impl Debug for dyn PostMintHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PostMintHook{{}}")
+    }
+}
+
+#[async_trait]
+pub trait PostMintExecutionRepository: Send + Sync {
+    async fn save(&self, execution: &PostMintExecution) -> Result<(), PostMintError>;
+    async fn get_pending(&self) -> Result<Vec<PostMintExecution>, PostMintError>;
+}
+
+impl Debug for dyn PostMintExecutionRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PostMintExecutionRepository{{}}")
+    }
+}
+
+struct RegisteredHook {
+    project_id: String,
+    name: String,
+    hook: Arc<dyn PostMintHook>,
+}
+
+/// Hooks configured per project, identified by name so pending executions can be
+/// matched back to their hook across worker restarts.
+#[derive(Default)]
+pub struct PostMintHooks {
+    hooks: Vec<RegisteredHook>,
+}
+
+impl PostMintHooks {
+    pub fn new() -> Self {
+        Self { hooks: Vec::new() }
+    }
+
+    pub fn register(&mut self, project_id: &str, name: &str, hook: Arc<dyn PostMintHook>) {
+        self.hooks.push(RegisteredHook {
+            project_id: project_id.to_lowercase(),
+            name: name.into(),
+            hook,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    fn for_project(&self, project_id: &str) -> impl Iterator<Item = &RegisteredHook> {
+        let project_id = project_id.to_lowercase();
+        self.hooks
+            .iter()
+            .filter(move |h| h.project_id == project_id)
+    }
+
+    fn get(&self, project_id: &str, name: &str) -> Option<Arc<dyn PostMintHook>> {
+        self.for_project(project_id)
+            .find(|h| h.name == name)
+            .map(|h| h.hook.clone())
+    }
+}
+
+/// Records one pending execution per configured hook for every successfully minted item.
+pub async fn schedule_post_mint_hooks(
+    hooks: &PostMintHooks,
+    execution_repository: Arc<dyn PostMintExecutionRepository>,
+    queue_items: &[QueueItem],
+    transaction_hash: &str,
+) {
+    for qi in queue_items {
+        let Some(queue_item_id) = qi.id.map(|id| *id.as_uuid()) else {
+            continue;
+        };
+        for registered in hooks.for_project(qi.project_id.as_str()) {
+            let execution = PostMintExecution {
+                id: Uuid::new_v4(),
+                queue_item_id,
+                hook: registered.name.to_string(),
+                project_id: qi.project_id.to_string(),
+                token_id: qi.token_id.to_string(),
+                starknet_account_addr: qi.starknet_wallet_pubkey.to_string(),
+                transaction_hash: transaction_hash.to_string(),
+                attempts: 0,
+                last_error: None,
+                status: PostMintStatus::Pending,
+            };
+            if let Err(e) = execution_repository.save(&execution).await {
+                error!(
+                    "Failed to schedule post mint hook {} for token {} : {:#?}",
+                    registered.name, qi.token_id, e
+                );
+            }
+        }
+    }
+}
+
+pub async fn run_post_mint_hooks(
+    hooks: &PostMintHooks,
+    execution_repository: Arc<dyn PostMintExecutionRepository>,
+    max_attempts: i32,
+) -> Result<(), PostMintError> {
+    let pending = execution_repository.get_pending().await?;
+
+    for mut execution in pending {
+        execution.attempts += 1;
+        match hooks.get(&execution.project_id, &execution.hook) {
+            None => {
+                warn!(
+                    "Post mint hook {} is not configured anymore for project {}",
+                    execution.hook, execution.project_id
+                );
+                execution.last_error = Some("Hook is not configured anymore".into());
+                execution.status = PostMintStatus::Failed;
+            }
+            Some(hook) => match hook.execute(&execution).await {
+                Ok(_) => {
+                    info!(
+                        "Post mint hook {} done for token {}",
+                        execution.hook, execution.token_id
+                    );
+                    execution.last_error = None;
+                    execution.status = PostMintStatus::Done;
+                }
+                Err(PostMintHookError::Failure(reason)) => {
+                    error!(
+                        "Post mint hook {} failed for token {} (attempt {}) : {}",
+                        execution.hook, execution.token_id, execution.attempts, reason
+                    );
+                    execution.last_error = Some(reason);
+                    if execution.attempts >= max_attempts {
+                        execution.status = PostMintStatus::Failed;
+                    }
+                }
+            },
+        };
+        execution_repository.save(&execution).await?;
+    }
+
+    Ok(())
+}
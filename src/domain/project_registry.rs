@@ -0,0 +1,314 @@
+use serde_derive::Serialize;
+
+use super::{
+    calendar::parse_timestamp,
+    ids::{JunoAddress, ProjectId, StarknetAddress},
+};
+
+const DEFAULT_MINT_SELECTOR: &str = "mint";
+
+/// Bridged collection, tokens transferred to `juno_admin_address` on the Juno contract
+/// (or burnt, depending on `migration_policy`) are minted on the Starknet contract
+/// through `mint_selector`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Project {
+    pub juno_contract: ProjectId,
+    pub starknet_contract: StarknetAddress,
+    pub juno_admin_address: JunoAddress,
+    pub mint_selector: String,
+    pub migration_window: MigrationWindow,
+    pub migration_policy: MigrationPolicy,
+    pub transfer_window: TransferWindow,
+}
+
+/// How customers hand their Juno tokens over before they are minted on Starknet.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationPolicy {
+    /// Last transfer of the token went to the project admin wallet
+    #[default]
+    TransferToAdmin,
+    /// Last message on the token burnt it
+    Burn,
+}
+
+impl MigrationPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "transfer_to_admin" => Some(Self::TransferToAdmin),
+            "burn" => Some(Self::Burn),
+            _ => None,
+        }
+    }
+}
+
+/// Contractual period migrations of a project are accepted and minted in, epoch
+/// milliseconds. An unset bound leaves the window open on that side.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct MigrationWindow {
+    pub opens_at: Option<i64>,
+    pub closes_at: Option<i64>,
+}
+
+impl MigrationWindow {
+    pub fn is_open(&self, now_ms: i64) -> bool {
+        self.opens_at.map_or(true, |opens_at| opens_at <= now_ms)
+            && self.closes_at.map_or(true, |closes_at| now_ms < closes_at)
+    }
+}
+
+/// Bound of a transfer window, a Juno block height or epoch milliseconds.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferBound {
+    Height(i64),
+    Timestamp(i64),
+}
+
+impl TransferBound {
+    // Plain numbers are block heights, anything else a date or timestamp
+    fn parse(value: &str) -> Option<Self> {
+        match value.parse::<i64>() {
+            Ok(height) => Some(Self::Height(height)),
+            Err(_) => parse_timestamp(value).map(Self::Timestamp),
+        }
+    }
+
+    // Unknown when the transfer lacks the height or block time the bound is expressed in
+    fn position(&self, height: Option<i64>, block_time: Option<i64>) -> Option<(i64, i64)> {
+        match self {
+            Self::Height(bound) => height.map(|h| (h, *bound)),
+            Self::Timestamp(bound) => block_time.map(|t| (t, *bound)),
+        }
+    }
+}
+
+/// Period the Juno transfers (or burns) of a project count in, from `starts` included
+/// to `ends` excluded. An unset bound leaves the window open on that side.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransferWindow {
+    pub starts: Option<TransferBound>,
+    pub ends: Option<TransferBound>,
+}
+
+impl TransferWindow {
+    /// Whether a transfer included at `height` and `block_time` is in the window, transfers
+    /// the node did not date are not once the window is bounded.
+    pub fn contains(&self, height: Option<i64>, block_time: Option<i64>) -> bool {
+        self.starts.map_or(true, |starts| {
+            starts
+                .position(height, block_time)
+                .map_or(false, |(at, bound)| bound <= at)
+        }) && self.ends.map_or(true, |ends| {
+            ends.position(height, block_time)
+                .map_or(false, |(at, bound)| at < bound)
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ProjectRegistryError {
+    InvalidDefinition(String),
+    DuplicateProject(String),
+    UnknownProject(String),
+}
+
+/// Projects the bridge accepts requests for, Starknet targets are resolved from here
+/// and never taken from the customer request.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectRegistry {
+    projects: Vec<Project>,
+}
+
+impl ProjectRegistry {
+    pub fn new(projects: Vec<Project>) -> Self {
+        Self { projects }
+    }
+
+    /// Parses definitions formatted as
+    /// `juno_contract=starknet_contract[:juno_admin_address[:mint_selector]]`, admin
+    /// defaults to `default_juno_admin` and selector to `mint`.
+    pub fn parse(
+        definitions: &[String],
+        default_juno_admin: &JunoAddress,
+    ) -> Result<Self, ProjectRegistryError> {
+        let mut projects: Vec<Project> = Vec::new();
+        for definition in definitions {
+            let invalid = || ProjectRegistryError::InvalidDefinition(definition.to_string());
+            let (juno_contract, target) = definition.split_once('=').ok_or_else(invalid)?;
+            let mut parts = target.split(':');
+            let starknet_contract = parts.next().unwrap_or_default();
+            let juno_admin_address = parts.next().filter(|a| !a.is_empty());
+            let mint_selector = parts.next().filter(|s| !s.is_empty());
+            if parts.next().is_some() {
+                return Err(invalid());
+            }
+
+            let project = Project {
+                juno_contract: juno_contract.trim().parse().map_err(|_| invalid())?,
+                starknet_contract: starknet_contract.trim().parse().map_err(|_| invalid())?,
+                juno_admin_address: match juno_admin_address {
+                    Some(a) => a.trim().parse().map_err(|_| invalid())?,
+                    None => default_juno_admin.clone(),
+                },
+                mint_selector: mint_selector
+                    .map_or(DEFAULT_MINT_SELECTOR, str::trim)
+                    .to_string(),
+                migration_window: MigrationWindow::default(),
+                migration_policy: MigrationPolicy::default(),
+                transfer_window: TransferWindow::default(),
+            };
+            if projects
+                .iter()
+                .any(|p| p.juno_contract == project.juno_contract)
+            {
+                return Err(ProjectRegistryError::DuplicateProject(
+                    project.juno_contract.to_string(),
+                ));
+            }
+            projects.push(project);
+        }
+
+        Ok(Self { projects })
+    }
+
+    /// Sets migration windows from definitions formatted as `juno_contract=opens_at/closes_at`,
+    /// bounds being UTC dates or RFC 3339 UTC timestamps, either one may be left empty.
+    pub fn with_migration_windows(
+        mut self,
+        definitions: &[String],
+    ) -> Result<Self, ProjectRegistryError> {
+        for definition in definitions {
+            let invalid = || ProjectRegistryError::InvalidDefinition(definition.to_string());
+            let (juno_contract, window) = definition.split_once('=').ok_or_else(invalid)?;
+            let (opens_at, closes_at) = window.split_once('/').ok_or_else(invalid)?;
+            let bound = |value: &str| match value.trim() {
+                "" => Ok(None),
+                value => parse_timestamp(value).map(Some).ok_or_else(invalid),
+            };
+            let window = MigrationWindow {
+                opens_at: bound(opens_at)?,
+                closes_at: bound(closes_at)?,
+            };
+            if let (Some(opens_at), Some(closes_at)) = (window.opens_at, window.closes_at) {
+                if closes_at <= opens_at {
+                    return Err(invalid());
+                }
+            }
+
+            let juno_contract = juno_contract.trim();
+            let Some(project) = self
+                .projects
+                .iter_mut()
+                .find(|p| p.juno_contract == juno_contract)
+            else {
+                return Err(ProjectRegistryError::UnknownProject(
+                    juno_contract.to_string(),
+                ));
+            };
+            project.migration_window = window;
+        }
+
+        Ok(self)
+    }
+
+    /// Sets migration policies from definitions formatted as `juno_contract=policy`, policy
+    /// being `transfer_to_admin` or `burn`.
+    pub fn with_migration_policies(
+        mut self,
+        definitions: &[String],
+    ) -> Result<Self, ProjectRegistryError> {
+        for definition in definitions {
+            let invalid = || ProjectRegistryError::InvalidDefinition(definition.to_string());
+            let (juno_contract, policy) = definition.split_once('=').ok_or_else(invalid)?;
+            let policy = MigrationPolicy::parse(policy).ok_or_else(invalid)?;
+
+            let juno_contract = juno_contract.trim();
+            let Some(project) = self
+                .projects
+                .iter_mut()
+                .find(|p| p.juno_contract == juno_contract)
+            else {
+                return Err(ProjectRegistryError::UnknownProject(
+                    juno_contract.to_string(),
+                ));
+            };
+            project.migration_policy = policy;
+        }
+
+        Ok(self)
+    }
+
+    /// Sets transfer windows from definitions formatted as `juno_contract=starts/ends`, bounds
+    /// being block heights, UTC dates or RFC 3339 UTC timestamps, either one may be left
+    /// empty.
+    pub fn with_transfer_windows(
+        mut self,
+        definitions: &[String],
+    ) -> Result<Self, ProjectRegistryError> {
+        for definition in definitions {
+            let invalid = || ProjectRegistryError::InvalidDefinition(definition.to_string());
+            let (juno_contract, window) = definition.split_once('=').ok_or_else(invalid)?;
+            let (starts, ends) = window.split_once('/').ok_or_else(invalid)?;
+            let bound = |value: &str| match value.trim() {
+                "" => Ok(None),
+                value => TransferBound::parse(value).map(Some).ok_or_else(invalid),
+            };
+            let window = TransferWindow {
+                starts: bound(starts)?,
+                ends: bound(ends)?,
+            };
+            match (window.starts, window.ends) {
+                (Some(TransferBound::Height(starts)), Some(TransferBound::Height(ends)))
+                | (Some(TransferBound::Timestamp(starts)), Some(TransferBound::Timestamp(ends)))
+                    if ends <= starts =>
+                {
+                    return Err(invalid());
+                }
+                _ => (),
+            }
+
+            let juno_contract = juno_contract.trim();
+            let Some(project) = self
+                .projects
+                .iter_mut()
+                .find(|p| p.juno_contract == juno_contract)
+            else {
+                return Err(ProjectRegistryError::UnknownProject(
+                    juno_contract.to_string(),
+                ));
+            };
+            project.transfer_window = window;
+        }
+
+        Ok(self)
+    }
+
+    /// Starknet contracts of projects whose migration window is not open at `now_ms`.
+    pub fn closed_projects(&self, now_ms: i64) -> Vec<StarknetAddress> {
+        self.projects
+            .iter()
+            .filter(|p| !p.migration_window.is_open(now_ms))
+            .map(|p| p.starknet_contract.clone())
+            .collect()
+    }
+
+    pub fn get(&self, juno_contract: &ProjectId) -> Option<&Project> {
+        self.projects
+            .iter()
+            .find(|p| &p.juno_contract == juno_contract)
+    }
+
+    pub fn find_by_starknet_contract(
+        &self,
+        starknet_contract: &StarknetAddress,
+    ) -> Option<&Project> {
+        self.projects
+            .iter()
+            .find(|p| &p.starknet_contract == starknet_contract)
+    }
+
+    pub fn projects(&self) -> &[Project] {
+        &self.projects
+    }
+}
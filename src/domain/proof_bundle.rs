@@ -0,0 +1,130 @@
+use log::error;
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use super::{
+    bridge::{QueueManager, QueueStatus},
+    ids::{JunoAddress, ProjectId, StarknetAddress, TokenId},
+    project_registry::ProjectRegistry,
+    report::ReportSigner,
+    transfer_proof::TransferProofRepository,
+};
+
+/// Both sides of the migration of one token.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct TokenMigrationProof {
+    #[schema(value_type = String)]
+    pub token_id: TokenId,
+    #[schema(value_type = String)]
+    pub starknet_account_addr: StarknetAddress,
+    pub status: QueueStatus,
+    /// Juno transfer to the admin wallet, unknown until the token passed its checks
+    pub juno_sender: Option<String>,
+    pub juno_transaction_hash: Option<String>,
+    pub juno_height: Option<i64>,
+    /// Set once the mint has been sent
+    pub starknet_transaction_hash: Option<String>,
+}
+
+/// Evidence of a customer migration, meant to be downloaded and kept by the customer.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ProofBundle {
+    #[schema(value_type = String)]
+    pub keplr_wallet_pubkey: JunoAddress,
+    #[schema(value_type = String)]
+    pub juno_contract: ProjectId,
+    #[schema(value_type = String)]
+    pub starknet_contract: StarknetAddress,
+    pub tokens: Vec<TokenMigrationProof>,
+    // Epoch milliseconds
+    pub generated_at: i64,
+}
+
+/// Bundle along with the server signature of its JSON serialization (keys sorted, no
+/// whitespace), when the server has a signing key.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SignedProofBundle {
+    pub bundle: ProofBundle,
+    pub signature: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ProofBundleError {
+    UnknownProject,
+    MigrationNotFound,
+    PersistenceIssue,
+    FailedToSign,
+}
+
+/// Builds the proof bundle of a customer migration from its queue items and the
+/// transfer proofs of their tokens.
+pub async fn handle_proof_bundle(
+    keplr_wallet_pubkey: &JunoAddress,
+    starknet_contract: &StarknetAddress,
+    project_registry: &ProjectRegistry,
+    queue_manager: Arc<dyn QueueManager>,
+    transfer_proof_repository: Arc<dyn TransferProofRepository>,
+    signer: Option<Arc<dyn ReportSigner>>,
+    generated_at: i64,
+) -> Result<SignedProofBundle, ProofBundleError> {
+    let Some(project) = project_registry.find_by_starknet_contract(starknet_contract) else {
+        return Err(ProofBundleError::UnknownProject);
+    };
+    let items = queue_manager
+        .get_customer_migration_state(keplr_wallet_pubkey, starknet_contract)
+        .await;
+    if items.is_empty() {
+        return Err(ProofBundleError::MigrationNotFound);
+    }
+
+    let token_ids: Vec<TokenId> = items.iter().map(|i| i.token_id.clone()).collect();
+    let proofs = match transfer_proof_repository
+        .get_proofs(&project.juno_contract, &token_ids)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Failed to fetch transfer proofs {:#?}", e);
+            return Err(ProofBundleError::PersistenceIssue);
+        }
+    };
+
+    let tokens = items
+        .into_iter()
+        .map(|item| {
+            let proof = proofs.iter().find(|p| p.token_id == item.token_id);
+            TokenMigrationProof {
+                token_id: item.token_id,
+                starknet_account_addr: item.starknet_wallet_pubkey,
+                status: item.status,
+                juno_sender: proof.map(|p| p.sender.clone()),
+                juno_transaction_hash: proof.and_then(|p| p.transaction_hash.clone()),
+                juno_height: proof.and_then(|p| p.height),
+                starknet_transaction_hash: item.transaction_hash,
+            }
+        })
+        .collect();
+    let bundle = ProofBundle {
+        keplr_wallet_pubkey: keplr_wallet_pubkey.clone(),
+        juno_contract: project.juno_contract.clone(),
+        starknet_contract: project.starknet_contract.clone(),
+        tokens,
+        generated_at,
+    };
+
+    let signature = match &signer {
+        // Going through a `Value` sorts keys, so the payload can be rebuilt from the file
+        Some(signer) => match serde_json::to_value(&bundle).and_then(|v| serde_json::to_string(&v))
+        {
+            Ok(payload) => Some(signer.sign(&payload)),
+            Err(e) => {
+                error!("Failed to serialize proof bundle {:#?}", e);
+                return Err(ProofBundleError::FailedToSign);
+            }
+        },
+        None => None,
+    };
+
+    Ok(SignedProofBundle { bundle, signature })
+}
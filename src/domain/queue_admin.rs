@@ -0,0 +1,203 @@
+use log::{error, info, warn};
+use serde_derive::Serialize;
+use std::{sync::Arc, time::Duration};
+
+use super::{
+    breakglass::Operator,
+    bridge::{QueueError, QueueItem, QueueItemTransition, QueueManager, QueueStatus},
+    clock::Clock,
+    ids::QueueItemId,
+    status_message::StatusNote,
+    webhook::WebhookNotifier,
+};
+
+pub const REQUEUED_BY_OPERATOR_NOTE: &str = "RequeuedByOperator";
+pub const CANCELLED_BY_OPERATOR_NOTE: &str = "CancelledByOperator";
+pub const RETRIED_BY_CUSTOMER_NOTE: &str = "RetriedByCustomer";
+pub const REQUEUED_DEAD_LETTER_NOTE: &str = "DeadLetterRequeuedByOperator";
+pub const PROCESSING_STALLED_NOTE: &str = "ProcessingStalled";
+
+#[derive(Debug)]
+pub enum QueueAdminError {
+    NotFound,
+    InvalidStatus(QueueStatus),
+    PersistenceIssue,
+}
+
+impl From<QueueError> for QueueAdminError {
+    fn from(e: QueueError) -> Self {
+        match e {
+            QueueError::NotFound => QueueAdminError::NotFound,
+            _ => QueueAdminError::PersistenceIssue,
+        }
+    }
+}
+
+/// Puts a failed item back to pending so the next batch retries it.
+pub async fn handle_requeue_queue_item(
+    id: &QueueItemId,
+    operator: &Operator,
+    queue_manager: Arc<dyn QueueManager>,
+) -> Result<QueueItem, QueueAdminError> {
+    let item = requeue_failed_item(id, REQUEUED_BY_OPERATOR_NOTE, queue_manager.clone()).await?;
+    warn!(
+        "ADMIN - {} requeued queue item {} (token {} on {})",
+        operator.name, id, item.token_id, item.project_id
+    );
+
+    Ok(queue_manager.get_queue_item(id).await?)
+}
+
+/// Customer facing counterpart of the operator requeue, limited to failed items.
+pub async fn handle_retry_queue_item(
+    id: &QueueItemId,
+    queue_manager: Arc<dyn QueueManager>,
+) -> Result<QueueItem, QueueAdminError> {
+    let item = requeue_failed_item(id, RETRIED_BY_CUSTOMER_NOTE, queue_manager.clone()).await?;
+    info!(
+        "{} retried queue item {} (token {} on {})",
+        item.keplr_wallet_pubkey, id, item.token_id, item.project_id
+    );
+
+    Ok(queue_manager.get_queue_item(id).await?)
+}
+
+// Moves an errored item back to pending, its transaction hash is cleared on the way.
+async fn requeue_failed_item(
+    id: &QueueItemId,
+    note: &str,
+    queue_manager: Arc<dyn QueueManager>,
+) -> Result<QueueItem, QueueAdminError> {
+    let item = queue_manager.get_queue_item(id).await?;
+    if !matches!(item.status, QueueStatus::Error) {
+        return Err(QueueAdminError::InvalidStatus(item.status));
+    }
+
+    if let Err(e) = queue_manager
+        .defer_queue_items(&[*id], &StatusNote::new(note))
+        .await
+    {
+        error!("Failed to requeue queue item {} : {:#?}", id, e);
+        return Err(QueueAdminError::PersistenceIssue);
+    }
+
+    Ok(item)
+}
+
+/// Takes an item out of the dead letter queue with a fresh attempt budget.
+pub async fn handle_requeue_dead_letter(
+    id: &QueueItemId,
+    operator: &Operator,
+    queue_manager: Arc<dyn QueueManager>,
+) -> Result<QueueItem, QueueAdminError> {
+    let item = queue_manager.get_queue_item(id).await?;
+    if !matches!(item.status, QueueStatus::DeadLetter) {
+        return Err(QueueAdminError::InvalidStatus(item.status));
+    }
+
+    if let Err(e) = queue_manager
+        .requeue_dead_letters(&[*id], &StatusNote::new(REQUEUED_DEAD_LETTER_NOTE))
+        .await
+    {
+        error!("Failed to requeue dead letter {} : {:#?}", id, e);
+        return Err(QueueAdminError::PersistenceIssue);
+    }
+    warn!(
+        "ADMIN - {} requeued dead letter {} after {} attempts (token {} on {})",
+        operator.name, id, item.attempts, item.token_id, item.project_id
+    );
+
+    Ok(queue_manager.get_queue_item(id).await?)
+}
+
+/// Puts items left processing for over `max_age` without a mint transaction back to
+/// pending, their worker is gone. Batches check tokens are not minted yet before
+/// minting them again.
+pub async fn requeue_stale_processing_items(
+    queue_manager: Arc<dyn QueueManager>,
+    clock: &dyn Clock,
+    max_age: Duration,
+) -> Result<Vec<QueueItem>, QueueAdminError> {
+    let before = clock.now_ms() - max_age.as_millis() as i64;
+    let items = queue_manager
+        .requeue_stale_processing_items(before, &StatusNote::new(PROCESSING_STALLED_NOTE))
+        .await?;
+    for qi in &items {
+        warn!(
+            "Queue item {} (token {} on {}) stalled in processing, queued again",
+            qi.id.map(|id| id.to_string()).unwrap_or_default(),
+            qi.token_id,
+            qi.project_id
+        );
+    }
+
+    Ok(items)
+}
+
+/// Operator triggered sweep of the items stalled in processing for over `max_age`.
+pub async fn handle_requeue_stale_processing_items(
+    operator: &Operator,
+    queue_manager: Arc<dyn QueueManager>,
+    clock: &dyn Clock,
+    max_age: Duration,
+) -> Result<Vec<QueueItem>, QueueAdminError> {
+    let items = requeue_stale_processing_items(queue_manager, clock, max_age).await?;
+    warn!(
+        "ADMIN - {} requeued {} items stalled in processing for over {:?}",
+        operator.name,
+        items.len(),
+        max_age
+    );
+
+    Ok(items)
+}
+
+/// Withdraws a pending item from the queue before any worker picks it up.
+pub async fn handle_cancel_queue_item(
+    id: &QueueItemId,
+    operator: &Operator,
+    queue_manager: Arc<dyn QueueManager>,
+    webhooks: &WebhookNotifier,
+) -> Result<QueueItem, QueueAdminError> {
+    let item = queue_manager.get_queue_item(id).await?;
+    if !matches!(item.status, QueueStatus::Pending) {
+        return Err(QueueAdminError::InvalidStatus(item.status));
+    }
+
+    if let Err(e) = queue_manager
+        .cancel_queue_items(&[*id], &StatusNote::new(CANCELLED_BY_OPERATOR_NOTE))
+        .await
+    {
+        error!("Failed to cancel queue item {} : {:#?}", id, e);
+        return Err(QueueAdminError::PersistenceIssue);
+    }
+    warn!(
+        "ADMIN - {} cancelled queue item {} (token {} on {})",
+        operator.name, id, item.token_id, item.project_id
+    );
+
+    let cancelled = queue_manager.get_queue_item(id).await?;
+    webhooks
+        .notify(&[cancelled.clone()], QueueStatus::Error, None)
+        .await;
+
+    Ok(cancelled)
+}
+
+/// Queue item with every status it went through, what operators need to understand
+/// why a mint failed.
+#[derive(Serialize, Debug)]
+pub struct QueueItemInspection {
+    pub item: QueueItem,
+    pub history: Vec<QueueItemTransition>,
+}
+
+pub async fn handle_inspect_queue_item(
+    id: &QueueItemId,
+    queue_manager: Arc<dyn QueueManager>,
+) -> Result<QueueItemInspection, QueueAdminError> {
+    let item = queue_manager.get_queue_item(id).await?;
+    let history = queue_manager.get_queue_item_history(id).await?;
+
+    Ok(QueueItemInspection { item, history })
+}
@@ -0,0 +1,282 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info, warn};
+use std::{sync::Arc, time::Duration};
+
+use super::{
+    bridge::{
+        QueueError, QueueEvent, QueueItem, QueueItemTransition, QueueManager, QueueStatus,
+        QueueUpdateError,
+    },
+    ids::{JunoAddress, QueueItemId, StarknetAddress, TokenId},
+    pagination::{Page, PageRequest},
+    status_message::StatusNote,
+};
+
+#[derive(Debug)]
+pub enum QueueBrokerError {
+    PublishFailed,
+    ReceiveFailed,
+    Closed,
+}
+
+/// Message broker pushing queue items to workers as soon as they can be minted.
+#[async_trait]
+pub trait QueueBroker: Send + Sync {
+    async fn publish(&self, ids: &[QueueItemId]) -> Result<(), QueueBrokerError>;
+    /// Waits for the next published items, each message is delivered to a single worker.
+    async fn next_delivery(&self) -> Result<Vec<QueueItemId>, QueueBrokerError>;
+}
+
+impl Debug for dyn QueueBroker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "QueueBroker{{}}")
+    }
+}
+
+/// Queue whose items are pushed to workers through a broker. The wrapped queue stays
+/// the record of every item : batches are claimed and statuses written back there, so
+/// workers never mint the same item twice and the customer status endpoint is unchanged.
+pub struct BrokerQueueManager {
+    store: Arc<dyn QueueManager>,
+    broker: Arc<dyn QueueBroker>,
+}
+
+impl BrokerQueueManager {
+    pub fn new(store: Arc<dyn QueueManager>, broker: Arc<dyn QueueBroker>) -> Self {
+        Self { store, broker }
+    }
+
+    // Items are already stored, the next poll picks them up when the broker is down
+    async fn publish(&self, ids: &[QueueItemId]) {
+        if ids.is_empty() {
+            return;
+        }
+        if let Err(e) = self.broker.publish(ids).await {
+            warn!(
+                "Failed to publish queue items {:#?}, they wait for the next poll {:#?}",
+                ids, e
+            );
+        }
+    }
+}
+
+/// Returns once the broker delivered items, or after `poll_interval` so items missed by
+/// the broker and scheduled retries are still picked up.
+pub async fn wait_for_work(broker: &Option<Arc<dyn QueueBroker>>, poll_interval: Duration) {
+    let Some(broker) = broker else {
+        return tokio::time::sleep(poll_interval).await;
+    };
+
+    tokio::select! {
+        delivery = broker.next_delivery() => match delivery {
+            Ok(ids) => info!("Broker delivered {} queue items", ids.len()),
+            Err(e) => {
+                error!("Failed to receive queue items from broker {:#?}", e);
+                tokio::time::sleep(poll_interval).await;
+            }
+        },
+        _ = tokio::time::sleep(poll_interval) => {}
+    }
+}
+
+#[async_trait]
+impl QueueManager for BrokerQueueManager {
+    async fn enqueue(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let items = self
+            .store
+            .enqueue(
+                keplr_wallet_pubkey,
+                starknet_wallet_pubkey,
+                project_id,
+                token_ids,
+                request_id,
+            )
+            .await?;
+        let ids: Vec<QueueItemId> = items.iter().filter_map(|qi| qi.id).collect();
+        self.publish(&ids).await;
+
+        Ok(items)
+    }
+
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        self.store.get_batch(excluded_projects).await
+    }
+
+    async fn get_customer_migration_state(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
+    ) -> Vec<QueueItem> {
+        self.store
+            .get_customer_migration_state(keplr_wallet_pubkey, project_id)
+            .await
+    }
+
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        self.store.get_wallet_migrations(keplr_wallet_pubkey).await
+    }
+
+    async fn update_queue_items_status(
+        &self,
+        ids: &[QueueItemId],
+        transaction_hash: String,
+        status: QueueStatus,
+    ) -> Result<(), QueueUpdateError> {
+        self.store
+            .update_queue_items_status(ids, transaction_hash, status)
+            .await
+    }
+
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError> {
+        self.store.claim_queue_items(ids).await
+    }
+
+    async fn get_queue_item_history(
+        &self,
+        id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError> {
+        self.store.get_queue_item_history(id).await
+    }
+
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        self.store.defer_queue_items(ids, note).await
+    }
+
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError> {
+        self.store.get_processing_items().await
+    }
+
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        self.store.get_recoverable_items(stale_before).await
+    }
+
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let items = self
+            .store
+            .requeue_stale_processing_items(before, note)
+            .await?;
+        let ids: Vec<QueueItemId> = items.iter().filter_map(|qi| qi.id).collect();
+        self.publish(&ids).await;
+
+        Ok(items)
+    }
+
+    async fn count_pending_items(&self) -> Result<i64, QueueError> {
+        self.store.count_pending_items().await
+    }
+
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError> {
+        self.store.get_queue_item(id).await
+    }
+
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        self.store.cancel_queue_items(ids, note).await
+    }
+
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError> {
+        // Retries become eligible later, polling picks them up
+        self.store.schedule_retry(ids, note, delay).await
+    }
+
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        self.store.dead_letter_queue_items(ids, note).await
+    }
+
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        self.store.requeue_dead_letters(ids, note).await?;
+        self.publish(ids).await;
+
+        Ok(())
+    }
+
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError> {
+        self.store.list_queue_items(status, page).await
+    }
+
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError> {
+        self.store.list_queue_events(page).await
+    }
+
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError> {
+        self.store
+            .record_mint_fees(ids, estimated_fee, actual_fee)
+            .await
+    }
+
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError> {
+        self.store
+            .lock_tokens(project_id, token_ids, holder, ttl)
+            .await
+    }
+
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError> {
+        self.store
+            .unlock_tokens(project_id, token_ids, holder)
+            .await
+    }
+}
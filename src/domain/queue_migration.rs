@@ -0,0 +1,418 @@
+use async_trait::async_trait;
+use log::error;
+use std::{sync::Arc, time::Duration};
+
+use super::{
+    bridge::{
+        QueueError, QueueEvent, QueueItem, QueueItemTransition, QueueManager, QueueStatus,
+        QueueUpdateError,
+    },
+    ids::{JunoAddress, QueueItemId, StarknetAddress, TokenId},
+    pagination::{Page, PageRequest},
+    status_message::StatusNote,
+};
+
+/// Queue spread over two backends while moving from `legacy` to `primary`, so the
+/// backend can be switched without pausing the bridge. New items go to the primary
+/// backend, batches drain the legacy one first and every item is claimed and updated
+/// in the backend holding it.
+pub struct MigratingQueueManager {
+    primary: Arc<dyn QueueManager>,
+    legacy: Arc<dyn QueueManager>,
+    batch_size: usize,
+}
+
+impl MigratingQueueManager {
+    pub fn new(
+        primary: Arc<dyn QueueManager>,
+        legacy: Arc<dyn QueueManager>,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            primary,
+            legacy,
+            batch_size,
+        }
+    }
+
+    /// Ids held by the legacy backend, then the other ones. Items are never moved
+    /// between backends, so an id missing from the legacy one is in the primary one.
+    async fn split_by_backend(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<(Vec<QueueItemId>, Vec<QueueItemId>), QueueUpdateError> {
+        let (mut legacy_ids, mut primary_ids) = (Vec::new(), Vec::new());
+        for id in ids {
+            match self.legacy.get_queue_item(id).await {
+                Ok(_) => legacy_ids.push(*id),
+                Err(QueueError::NotFound) => primary_ids.push(*id),
+                Err(e) => {
+                    error!("Failed to find the queue backend of {} {:#?}", id, e);
+                    return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+                }
+            }
+        }
+
+        Ok((legacy_ids, primary_ids))
+    }
+
+    async fn backend_of(&self, id: &QueueItemId) -> Result<&Arc<dyn QueueManager>, QueueError> {
+        match self.legacy.get_queue_item(id).await {
+            Ok(_) => Ok(&self.legacy),
+            Err(QueueError::NotFound) => Ok(&self.primary),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn merge_updates(
+    legacy: Result<(), QueueUpdateError>,
+    primary: Result<(), QueueUpdateError>,
+) -> Result<(), QueueUpdateError> {
+    match (legacy, primary) {
+        (Ok(()), Ok(())) => Ok(()),
+        (Err(QueueUpdateError::StatusUpdateFail(ids)), Ok(()))
+        | (Ok(()), Err(QueueUpdateError::StatusUpdateFail(ids))) => {
+            Err(QueueUpdateError::StatusUpdateFail(ids))
+        }
+        (
+            Err(QueueUpdateError::StatusUpdateFail(mut legacy_ids)),
+            Err(QueueUpdateError::StatusUpdateFail(primary_ids)),
+        ) => {
+            legacy_ids.extend(primary_ids);
+            Err(QueueUpdateError::StatusUpdateFail(legacy_ids))
+        }
+    }
+}
+
+#[async_trait]
+impl QueueManager for MigratingQueueManager {
+    async fn enqueue(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        // A token lives in a single backend, so it can never be claimed twice. Tokens
+        // still waiting in the legacy one keep their item there
+        let queued = self
+            .legacy
+            .get_customer_migration_state(keplr_wallet_pubkey, project_id)
+            .await;
+        let mut legacy_items = Vec::new();
+        let mut new_tokens = Vec::new();
+        for token in token_ids {
+            match queued.iter().find(|qi| qi.token_id == token) {
+                Some(qi) if matches!(qi.status, QueueStatus::Pending | QueueStatus::Processing) => {
+                    legacy_items.push(qi.clone())
+                }
+                Some(_) => {
+                    error!(
+                        "Token {} of {} is already queued in the legacy backend",
+                        token, keplr_wallet_pubkey
+                    );
+                    return Err(QueueError::FailedToEnqueue);
+                }
+                None => new_tokens.push(token),
+            }
+        }
+
+        let mut queue_items = self
+            .primary
+            .enqueue(
+                keplr_wallet_pubkey,
+                starknet_wallet_pubkey,
+                project_id,
+                new_tokens,
+                request_id,
+            )
+            .await?;
+        queue_items.extend(legacy_items);
+
+        Ok(queue_items)
+    }
+
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let mut batch = self.legacy.get_batch(excluded_projects).await?;
+        if batch.len() < self.batch_size {
+            batch.extend(self.primary.get_batch(excluded_projects).await?);
+        }
+        batch.truncate(self.batch_size);
+
+        Ok(batch)
+    }
+
+    async fn get_customer_migration_state(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
+    ) -> Vec<QueueItem> {
+        let mut items = self
+            .legacy
+            .get_customer_migration_state(keplr_wallet_pubkey, project_id)
+            .await;
+        items.extend(
+            self.primary
+                .get_customer_migration_state(keplr_wallet_pubkey, project_id)
+                .await,
+        );
+
+        items
+    }
+
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let mut items = self
+            .legacy
+            .get_wallet_migrations(keplr_wallet_pubkey)
+            .await?;
+        items.extend(
+            self.primary
+                .get_wallet_migrations(keplr_wallet_pubkey)
+                .await?,
+        );
+        // Legacy items are older, the stable sort keeps them first within a project
+        items.sort_by(|a, b| a.project_id.as_str().cmp(b.project_id.as_str()));
+
+        Ok(items)
+    }
+
+    async fn update_queue_items_status(
+        &self,
+        ids: &[QueueItemId],
+        transaction_hash: String,
+        status: QueueStatus,
+    ) -> Result<(), QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        merge_updates(
+            self.legacy
+                .update_queue_items_status(&legacy_ids, transaction_hash.clone(), status.clone())
+                .await,
+            self.primary
+                .update_queue_items_status(&primary_ids, transaction_hash, status)
+                .await,
+        )
+    }
+
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        let mut claimed = self.legacy.claim_queue_items(&legacy_ids).await?;
+        claimed.extend(self.primary.claim_queue_items(&primary_ids).await?);
+
+        Ok(claimed)
+    }
+
+    async fn get_queue_item_history(
+        &self,
+        id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError> {
+        self.backend_of(id).await?.get_queue_item_history(id).await
+    }
+
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        merge_updates(
+            self.legacy.defer_queue_items(&legacy_ids, note).await,
+            self.primary.defer_queue_items(&primary_ids, note).await,
+        )
+    }
+
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError> {
+        let mut items = self.legacy.get_processing_items().await?;
+        items.extend(self.primary.get_processing_items().await?);
+
+        Ok(items)
+    }
+
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let mut items = self.legacy.get_recoverable_items(stale_before).await?;
+        items.extend(self.primary.get_recoverable_items(stale_before).await?);
+
+        Ok(items)
+    }
+
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let mut items = self
+            .legacy
+            .requeue_stale_processing_items(before, note)
+            .await?;
+        items.extend(
+            self.primary
+                .requeue_stale_processing_items(before, note)
+                .await?,
+        );
+
+        Ok(items)
+    }
+
+    async fn count_pending_items(&self) -> Result<i64, QueueError> {
+        Ok(self.legacy.count_pending_items().await? + self.primary.count_pending_items().await?)
+    }
+
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError> {
+        match self.legacy.get_queue_item(id).await {
+            Err(QueueError::NotFound) => self.primary.get_queue_item(id).await,
+            result => result,
+        }
+    }
+
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        merge_updates(
+            self.legacy.cancel_queue_items(&legacy_ids, note).await,
+            self.primary.cancel_queue_items(&primary_ids, note).await,
+        )
+    }
+
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        merge_updates(
+            self.legacy.schedule_retry(&legacy_ids, note, delay).await,
+            self.primary.schedule_retry(&primary_ids, note, delay).await,
+        )
+    }
+
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        merge_updates(
+            self.legacy.dead_letter_queue_items(&legacy_ids, note).await,
+            self.primary
+                .dead_letter_queue_items(&primary_ids, note)
+                .await,
+        )
+    }
+
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        merge_updates(
+            self.legacy.requeue_dead_letters(&legacy_ids, note).await,
+            self.primary.requeue_dead_letters(&primary_ids, note).await,
+        )
+    }
+
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError> {
+        let legacy = self.legacy.list_queue_items(status.clone(), page).await?;
+        let primary = self.primary.list_queue_items(status, page).await?;
+
+        Ok(Page::merge(vec![legacy, primary], page.limit))
+    }
+
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError> {
+        let legacy = self.legacy.list_queue_events(page).await?;
+        let primary = self.primary.list_queue_events(page).await?;
+
+        Ok(Page::merge(vec![legacy, primary], page.limit))
+    }
+
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError> {
+        let (legacy_ids, primary_ids) = self.split_by_backend(ids).await?;
+        merge_updates(
+            self.legacy
+                .record_mint_fees(&legacy_ids, estimated_fee, actual_fee)
+                .await,
+            self.primary
+                .record_mint_fees(&primary_ids, estimated_fee, actual_fee)
+                .await,
+        )
+    }
+
+    // Pending items of the tokens may sit in either backend, both keep them out of batches
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError> {
+        let locked = self
+            .primary
+            .lock_tokens(project_id, token_ids, holder, ttl)
+            .await?;
+        let locked_in_both = match self
+            .legacy
+            .lock_tokens(project_id, &locked, holder, ttl)
+            .await
+        {
+            Ok(l) => l,
+            Err(e) => {
+                self.primary
+                    .unlock_tokens(project_id, &locked, holder)
+                    .await?;
+                return Err(e);
+            }
+        };
+        let released: Vec<TokenId> = locked
+            .into_iter()
+            .filter(|t| !locked_in_both.contains(t))
+            .collect();
+        self.primary
+            .unlock_tokens(project_id, &released, holder)
+            .await?;
+
+        Ok(locked_in_both)
+    }
+
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError> {
+        let legacy = self
+            .legacy
+            .unlock_tokens(project_id, token_ids, holder)
+            .await;
+        self.primary
+            .unlock_tokens(project_id, token_ids, holder)
+            .await?;
+
+        legacy
+    }
+}
@@ -0,0 +1,163 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info};
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use super::{
+    bridge::{QueueItem, QueueItemTransition},
+    clock::Clock,
+    ids::QueueItemId,
+};
+
+pub const QUEUE_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueueSnapshotEntry {
+    pub item: QueueItem,
+    pub history: Vec<QueueItemTransition>,
+}
+
+/// Items minted by the same transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QueueBatch {
+    pub transaction_hash: String,
+    pub item_ids: Vec<QueueItemId>,
+}
+
+/// Whole queue as written to disk, `checksum` covers entries and batches so a
+/// truncated or hand edited file is refused on restore.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueueSnapshot {
+    pub version: u32,
+    // Epoch milliseconds
+    pub created_at: i64,
+    pub entries: Vec<QueueSnapshotEntry>,
+    pub batches: Vec<QueueBatch>,
+    pub checksum: String,
+}
+
+#[derive(Debug)]
+pub enum QueueSnapshotError {
+    UnsupportedVersion(u32),
+    ChecksumMismatch,
+    MissingItemId(usize),
+    DuplicateItem(QueueItemId),
+    InconsistentBatch(String),
+    PersistenceIssue(String),
+}
+
+#[async_trait]
+pub trait QueueSnapshotRepository: Send + Sync {
+    async fn load_queue(&self) -> Result<Vec<QueueSnapshotEntry>, QueueSnapshotError>;
+    /// Writes every entry with its id, status and history, or none of them.
+    async fn restore_queue(&self, entries: &[QueueSnapshotEntry])
+        -> Result<(), QueueSnapshotError>;
+}
+
+impl Debug for dyn QueueSnapshotRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "QueueSnapshotRepository{{}}")
+    }
+}
+
+pub async fn take_queue_snapshot(
+    repository: Arc<dyn QueueSnapshotRepository>,
+    clock: Arc<dyn Clock>,
+) -> Result<QueueSnapshot, QueueSnapshotError> {
+    let entries = repository.load_queue().await?;
+    let batches = queue_batches(&entries);
+    let checksum = snapshot_checksum(&entries, &batches);
+    info!(
+        "Snapshot of {} queue items in {} batches",
+        entries.len(),
+        batches.len()
+    );
+
+    Ok(QueueSnapshot {
+        version: QUEUE_SNAPSHOT_VERSION,
+        created_at: clock.now_ms(),
+        entries,
+        batches,
+        checksum,
+    })
+}
+
+/// Checks a snapshot before anything is written, restore refuses it otherwise.
+pub fn verify_queue_snapshot(snapshot: &QueueSnapshot) -> Result<(), QueueSnapshotError> {
+    if QUEUE_SNAPSHOT_VERSION != snapshot.version {
+        return Err(QueueSnapshotError::UnsupportedVersion(snapshot.version));
+    }
+    if snapshot_checksum(&snapshot.entries, &snapshot.batches) != snapshot.checksum {
+        return Err(QueueSnapshotError::ChecksumMismatch);
+    }
+
+    let mut ids = HashSet::new();
+    for (index, entry) in snapshot.entries.iter().enumerate() {
+        let Some(id) = entry.item.id else {
+            return Err(QueueSnapshotError::MissingItemId(index));
+        };
+        if !ids.insert(id) {
+            return Err(QueueSnapshotError::DuplicateItem(id));
+        }
+    }
+
+    if queue_batches(&snapshot.entries) != snapshot.batches {
+        return Err(QueueSnapshotError::InconsistentBatch(
+            "Batches do not match queue items transaction hashes".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn restore_queue_snapshot(
+    snapshot: &QueueSnapshot,
+    repository: Arc<dyn QueueSnapshotRepository>,
+) -> Result<usize, QueueSnapshotError> {
+    verify_queue_snapshot(snapshot)?;
+    if let Err(e) = repository.restore_queue(&snapshot.entries).await {
+        error!("Failed to restore queue snapshot {:#?}", e);
+        return Err(e);
+    }
+    info!(
+        "Restored {} queue items from snapshot taken at {}",
+        snapshot.entries.len(),
+        snapshot.created_at
+    );
+
+    Ok(snapshot.entries.len())
+}
+
+// Sorted by transaction hash then item id, so batches compare whatever the load order
+fn queue_batches(entries: &[QueueSnapshotEntry]) -> Vec<QueueBatch> {
+    let mut batches: BTreeMap<&str, Vec<QueueItemId>> = BTreeMap::new();
+    for entry in entries {
+        if let (Some(id), Some(hash)) = (entry.item.id, entry.item.transaction_hash.as_deref()) {
+            if !hash.is_empty() {
+                batches.entry(hash).or_default().push(id);
+            }
+        }
+    }
+
+    batches
+        .into_iter()
+        .map(|(hash, mut item_ids)| {
+            item_ids.sort();
+            QueueBatch {
+                transaction_hash: hash.to_string(),
+                item_ids,
+            }
+        })
+        .collect()
+}
+
+fn snapshot_checksum(entries: &[QueueSnapshotEntry], batches: &[QueueBatch]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&(entries, batches)).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
@@ -0,0 +1,177 @@
+use log::warn;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    bridge::{QueueItem, QueueManager, QueueStatus, StarknetManager, TransactionOutcome},
+    consume_queue::{
+        finalize_queue_items, retry_failed_items, MintRetryPolicy, TRANSACTION_NOT_RECEIVED_NOTE,
+        TRANSACTION_REJECTED_NOTE,
+    },
+    ids::QueueItemId,
+    pagination::{Cursor, PageRequest},
+    post_mint::{PostMintExecutionRepository, PostMintHooks},
+    status_message::StatusNote,
+    webhook::WebhookNotifier,
+};
+
+#[derive(Debug)]
+pub enum ReconciliationError {
+    FailedToListItems,
+    Cancelled,
+}
+
+#[derive(Debug, Default)]
+struct ReconcilerState {
+    // Next minted item to check, none to start over from the oldest one
+    after: Option<Cursor>,
+    // Items processing on the previous run
+    processing: HashSet<QueueItemId>,
+}
+
+/// Checks the transactions of minted items again, so items whose transaction was
+/// reverted or dropped by a reorg after being accepted are minted again. Minted items
+/// are checked `batch_size` at a time, each run resuming where the previous one stopped.
+#[derive(Debug)]
+pub struct TransactionReconciler {
+    batch_size: i64,
+    state: Mutex<ReconcilerState>,
+}
+
+impl TransactionReconciler {
+    pub fn new(batch_size: i64) -> Self {
+        Self {
+            batch_size,
+            state: Mutex::new(ReconcilerState::default()),
+        }
+    }
+
+    /// Returns how many items were put back in the queue. Items still processing since
+    /// the previous run are checked too, the worker minting them is gone by then, and
+    /// are finalized when their transaction was accepted.
+    pub async fn run(
+        &self,
+        queue_manager: Arc<dyn QueueManager>,
+        starknet_manager: Arc<dyn StarknetManager>,
+        post_mint_hooks: &PostMintHooks,
+        post_mint_repository: Arc<dyn PostMintExecutionRepository>,
+        webhooks: &WebhookNotifier,
+        retry_policy: &MintRetryPolicy,
+        cancel: &CancellationToken,
+    ) -> Result<usize, ReconciliationError> {
+        let mut state = self.state.lock().await;
+
+        let page = PageRequest {
+            after: state.after.clone(),
+            limit: self.batch_size,
+        };
+        let minted = match queue_manager
+            .list_queue_items(Some(QueueStatus::Success), &page)
+            .await
+        {
+            Ok(p) => p,
+            Err(_e) => return Err(ReconciliationError::FailedToListItems),
+        };
+        let processing = match queue_manager.get_processing_items().await {
+            Ok(i) => i,
+            Err(_e) => return Err(ReconciliationError::FailedToListItems),
+        };
+
+        let seen = std::mem::replace(
+            &mut state.processing,
+            processing.iter().filter_map(|qi| qi.id).collect(),
+        );
+        state.after = minted
+            .next_cursor
+            .as_deref()
+            .and_then(|c| Cursor::decode(c).ok());
+
+        let stale = processing
+            .into_iter()
+            .filter(|qi| qi.id.map_or(false, |id| seen.contains(&id)));
+        let mut by_transaction: HashMap<String, Vec<QueueItem>> = HashMap::new();
+        for qi in minted.items.into_iter().chain(stale) {
+            // Items recovered from token ownership have no transaction to check
+            let Some(tx_hash) = qi.transaction_hash.clone().filter(|h| !h.is_empty()) else {
+                continue;
+            };
+            by_transaction.entry(tx_hash).or_default().push(qi);
+        }
+
+        let mut corrected = 0;
+        for (tx_hash, queue_items) in by_transaction.iter() {
+            if cancel.is_cancelled() {
+                return Err(ReconciliationError::Cancelled);
+            }
+            let note = match starknet_manager.get_transaction_outcome(tx_hash).await {
+                TransactionOutcome::Rejected(reason) => StatusNote::new(TRANSACTION_REJECTED_NOTE)
+                    .with("tx_hash", tx_hash)
+                    .with("reason", reason.as_deref().unwrap_or("REJECTED")),
+                TransactionOutcome::NotReceived => {
+                    StatusNote::new(TRANSACTION_NOT_RECEIVED_NOTE).with("tx_hash", tx_hash)
+                }
+                TransactionOutcome::Accepted => {
+                    let stale: Vec<QueueItem> = queue_items
+                        .iter()
+                        .filter(|qi| matches!(qi.status, QueueStatus::Processing))
+                        .cloned()
+                        .collect();
+                    if !stale.is_empty()
+                        && finalize_queue_items(
+                            queue_manager.clone(),
+                            &stale,
+                            tx_hash,
+                            TransactionOutcome::Accepted,
+                            post_mint_hooks,
+                            post_mint_repository.clone(),
+                            retry_policy,
+                        )
+                        .await
+                    {
+                        warn!(
+                            "Transaction {} was accepted, finalizing its {} queue items left processing",
+                            tx_hash,
+                            stale.len()
+                        );
+                        webhooks
+                            .notify(&stale, QueueStatus::Success, Some(tx_hash))
+                            .await;
+                    }
+                    continue;
+                }
+                TransactionOutcome::Pending => continue,
+            };
+
+            // Tokens minted since by another transaction stay minted, the others are
+            // checked again before being minted
+            let mut reverted = Vec::new();
+            for qi in queue_items {
+                if !matches!(
+                    starknet_manager
+                        .project_has_token(&qi.project_id, &qi.token_id)
+                        .await,
+                    Ok(true)
+                ) {
+                    reverted.push(qi.clone());
+                }
+            }
+            if reverted.is_empty() {
+                continue;
+            }
+
+            warn!(
+                "Transaction {} was reverted, minting its {} queue items again",
+                tx_hash,
+                reverted.len()
+            );
+            retry_failed_items(queue_manager.clone(), &reverted, &note, retry_policy).await;
+            corrected += reverted.len();
+        }
+
+        Ok(corrected)
+    }
+}
@@ -0,0 +1,196 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::{calendar::format_day, clock::Clock, stats::TimeRange, storage::ObjectStorage};
+
+const DAY_MS: i64 = 86_400_000;
+
+#[derive(Debug)]
+pub enum ReportError {
+    NotFound,
+    FailedToCompute,
+    PersistenceIssue,
+    PublicationFailed,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    Csv,
+}
+
+impl Default for ReportFormat {
+    fn default() -> Self {
+        ReportFormat::Json
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DailyActivity {
+    pub new_registrations: i64,
+    pub tokens_queued: i64,
+    pub tokens_minted: i64,
+    pub tokens_failed: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DailyReport {
+    // UTC day formatted as YYYY-MM-DD
+    pub day: String,
+    pub new_registrations: i64,
+    pub tokens_queued: i64,
+    pub tokens_minted: i64,
+    pub tokens_failed: i64,
+    // Not tracked yet, mint transaction fees are not persisted
+    pub fees_spent: Option<String>,
+    // Epoch milliseconds
+    pub generated_at: i64,
+}
+
+/// Report along with the signature of its JSON serialization, so stakeholders can
+/// check numbers were not edited after generation.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignedDailyReport {
+    pub report: DailyReport,
+    pub signature: String,
+}
+
+impl SignedDailyReport {
+    pub fn to_csv(&self) -> String {
+        let r = &self.report;
+        format!(
+            "day,new_registrations,tokens_queued,tokens_minted,tokens_failed,fees_spent,generated_at,signature\n{},{},{},{},{},{},{},{}\n",
+            r.day,
+            r.new_registrations,
+            r.tokens_queued,
+            r.tokens_minted,
+            r.tokens_failed,
+            r.fees_spent.as_deref().unwrap_or(""),
+            r.generated_at,
+            self.signature
+        )
+    }
+}
+
+#[async_trait]
+pub trait ReportRepository: Send + Sync {
+    async fn get_daily_activity(&self, range: &TimeRange) -> Result<DailyActivity, ReportError>;
+    async fn save_report(&self, report: &SignedDailyReport) -> Result<(), ReportError>;
+    async fn get_report(&self, day: &str) -> Result<SignedDailyReport, ReportError>;
+    /// Days with a generated report, most recent first.
+    async fn list_report_days(&self) -> Result<Vec<String>, ReportError>;
+}
+
+impl Debug for dyn ReportRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ReportRepository{{}}")
+    }
+}
+
+pub trait ReportSigner: Send + Sync {
+    fn sign(&self, payload: &str) -> String;
+}
+
+impl Debug for dyn ReportSigner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ReportSigner{{}}")
+    }
+}
+
+#[async_trait]
+pub trait ReportPublisher: Send + Sync {
+    async fn publish(&self, report: &SignedDailyReport) -> Result<(), ReportError>;
+}
+
+impl Debug for dyn ReportPublisher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ReportPublisher{{}}")
+    }
+}
+
+/// Object storage key of a report artifact.
+pub fn report_key(day: &str, format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Json => format!("reports/{}.json", day),
+        ReportFormat::Csv => format!("reports/{}.csv", day),
+    }
+}
+
+/// Generates the report of the last complete UTC day unless it already exists.
+pub async fn ensure_daily_report(
+    report_repository: Arc<dyn ReportRepository>,
+    report_signer: Arc<dyn ReportSigner>,
+    report_publisher: Option<Arc<dyn ReportPublisher>>,
+    object_storage: Option<Arc<dyn ObjectStorage>>,
+    clock: Arc<dyn Clock>,
+) -> Result<(), ReportError> {
+    let today = clock.now_ms().div_euclid(DAY_MS);
+    let day_index = today - 1;
+    let day = format_day(day_index);
+
+    match report_repository.get_report(&day).await {
+        Ok(_) => return Ok(()),
+        Err(ReportError::NotFound) => {}
+        Err(e) => return Err(e),
+    };
+
+    let range = TimeRange {
+        from: day_index * DAY_MS,
+        to: today * DAY_MS,
+    };
+    let activity = report_repository.get_daily_activity(&range).await?;
+    let report = DailyReport {
+        day: day.to_string(),
+        new_registrations: activity.new_registrations,
+        tokens_queued: activity.tokens_queued,
+        tokens_minted: activity.tokens_minted,
+        tokens_failed: activity.tokens_failed,
+        fees_spent: None,
+        generated_at: clock.now_ms(),
+    };
+    let payload = match serde_json::to_string(&report) {
+        Ok(p) => p,
+        Err(e) => {
+            error!("Failed to serialize daily report {} : {:#?}", day, e);
+            return Err(ReportError::FailedToCompute);
+        }
+    };
+    let signed = SignedDailyReport {
+        signature: report_signer.sign(&payload),
+        report,
+    };
+    report_repository.save_report(&signed).await?;
+    info!("Daily report {} generated", day);
+
+    if let Some(storage) = object_storage {
+        let artifacts = [
+            (
+                report_key(&day, ReportFormat::Json),
+                "application/json",
+                serde_json::to_vec(&signed).unwrap_or_default(),
+            ),
+            (
+                report_key(&day, ReportFormat::Csv),
+                "text/csv",
+                signed.to_csv().into_bytes(),
+            ),
+        ];
+        for (key, content_type, body) in artifacts {
+            if let Err(e) = storage.put_object(&key, content_type, body).await {
+                error!("Failed to upload daily report {} : {:#?}", key, e);
+            }
+        }
+    }
+
+    if let Some(publisher) = report_publisher {
+        if let Err(e) = publisher.publish(&signed).await {
+            error!("Failed to publish daily report {} : {:#?}", day, e);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,313 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info, warn};
+use serde_derive::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+use super::{
+    ids::{JunoAddress, ProjectId, StarknetAddress, TokenId},
+    project_registry::ProjectRegistry,
+    wallet_link::{WalletLinkError, WalletLinkRepository},
+};
+
+/// Customer asks for tokens sent back to the admin on Starknet (or burnt) in
+/// `transaction_hash` to be returned to their keplr wallet on Juno.
+#[derive(Debug, Deserialize)]
+pub struct ReverseBridgeRequest {
+    pub starknet_account_addr: StarknetAddress,
+    pub keplr_wallet_pubkey: JunoAddress,
+    // Starknet project contract tokens were returned on
+    pub project_id: StarknetAddress,
+    pub token_ids: Vec<TokenId>,
+    pub transaction_hash: String,
+}
+
+/// ERC721 `Transfer` event read from an accepted Starknet transaction.
+#[derive(Debug, Clone)]
+pub struct StarknetTokenTransfer {
+    pub contract: StarknetAddress,
+    pub from: StarknetAddress,
+    pub to: StarknetAddress,
+    pub token_id: TokenId,
+}
+
+#[derive(Debug)]
+pub enum StarknetTransferError {
+    NotAccepted,
+    FetchFailed,
+}
+
+#[async_trait]
+pub trait StarknetTransferVerifier: Send + Sync {
+    /// Token transfers emitted by the transaction, only once it has been accepted.
+    async fn get_token_transfers(
+        &self,
+        transaction_hash: &str,
+    ) -> Result<Vec<StarknetTokenTransfer>, StarknetTransferError>;
+}
+
+impl Debug for dyn StarknetTransferVerifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "StarknetTransferVerifier{{}}")
+    }
+}
+
+#[derive(Debug)]
+pub enum JunoBroadcastError {
+    Rejected(String),
+    Failure(String),
+}
+
+#[async_trait]
+pub trait JunoTxBroadcaster: Send + Sync {
+    /// Transfers tokens of the Juno contract from the admin wallet to `recipient`,
+    /// returns the Juno transaction hash.
+    async fn transfer_tokens(
+        &self,
+        contract: &ProjectId,
+        recipient: &JunoAddress,
+        token_ids: &[TokenId],
+    ) -> Result<String, JunoBroadcastError>;
+}
+
+impl Debug for dyn JunoTxBroadcaster {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "JunoTxBroadcaster{{}}")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReverseQueueStatus {
+    Pending,
+    Processing,
+    Success,
+    Error,
+}
+
+/// Token waiting to be transferred back on Juno.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReverseQueueItem {
+    pub id: Uuid,
+    pub starknet_account_addr: StarknetAddress,
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub starknet_project_id: StarknetAddress,
+    // Juno contract the token is transferred on
+    pub project_id: ProjectId,
+    pub token_id: TokenId,
+    pub starknet_transaction_hash: String,
+    pub status: ReverseQueueStatus,
+    pub juno_transaction_hash: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ReverseQueueError {
+    /// Token has already been requested back with this transaction
+    AlreadyRequested(TokenId),
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait ReverseQueueManager: Send + Sync {
+    /// Fails without enqueueing anything if one of the tokens was already requested
+    /// back with the same Starknet transaction.
+    async fn enqueue(
+        &self,
+        items: Vec<ReverseQueueItem>,
+    ) -> Result<Vec<ReverseQueueItem>, ReverseQueueError>;
+    /// Oldest pending items, at most `limit`.
+    async fn get_batch(&self, limit: usize) -> Result<Vec<ReverseQueueItem>, ReverseQueueError>;
+    async fn update_status(
+        &self,
+        ids: &[Uuid],
+        status: ReverseQueueStatus,
+        juno_transaction_hash: Option<String>,
+        note: Option<String>,
+    ) -> Result<(), ReverseQueueError>;
+    async fn get_customer_state(
+        &self,
+        starknet_account_addr: &StarknetAddress,
+        starknet_project_id: &StarknetAddress,
+    ) -> Result<Vec<ReverseQueueItem>, ReverseQueueError>;
+}
+
+impl Debug for dyn ReverseQueueManager {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ReverseQueueManager{{}}")
+    }
+}
+
+#[derive(Debug)]
+pub enum ReverseBridgeError {
+    UnknownProject(String),
+    NoTokens,
+    WalletNotLinked,
+    StarknetAccountMismatch(String),
+    WalletLinkIssue,
+    TransactionNotAccepted,
+    StarknetFetchFailed,
+    TokenNotReturned(String),
+    AlreadyRequested(String),
+    QueueIssue,
+}
+
+/// Felts compared by value, addresses are not always zero padded the same way.
+fn same_felt(a: &str, b: &str) -> bool {
+    let digits = |v: &str| {
+        v.trim_start_matches("0x")
+            .trim_start_matches("0X")
+            .trim_start_matches('0')
+            .to_ascii_lowercase()
+    };
+    digits(a) == digits(b)
+}
+
+/// Checks every token left the customer account for the admin (or was burnt) in the
+/// given Starknet transaction, then enqueues their transfer back on Juno.
+pub async fn handle_reverse_bridge_request(
+    req: &ReverseBridgeRequest,
+    project_registry: &ProjectRegistry,
+    starknet_admin_address: &str,
+    verifier: Arc<dyn StarknetTransferVerifier>,
+    wallet_link_repository: Arc<dyn WalletLinkRepository>,
+    queue: Arc<dyn ReverseQueueManager>,
+) -> Result<Vec<ReverseQueueItem>, ReverseBridgeError> {
+    let Some(project) = project_registry.find_by_starknet_contract(&req.project_id) else {
+        error!("Starknet contract {} is not bridged", &req.project_id);
+        return Err(ReverseBridgeError::UnknownProject(
+            req.project_id.to_string(),
+        ));
+    };
+    if req.token_ids.is_empty() {
+        return Err(ReverseBridgeError::NoTokens);
+    }
+
+    // Starknet transaction does not say which keplr wallet tokens go back to, only
+    // the wallet linked by a previous bridge request can receive them.
+    match wallet_link_repository
+        .get_link(&req.keplr_wallet_pubkey)
+        .await
+    {
+        Ok(link) if link.starknet_account_addr == req.starknet_account_addr => (),
+        Ok(link) => {
+            return Err(ReverseBridgeError::StarknetAccountMismatch(
+                link.starknet_account_addr.to_string(),
+            ))
+        }
+        Err(WalletLinkError::NotFound) => return Err(ReverseBridgeError::WalletNotLinked),
+        Err(_e) => return Err(ReverseBridgeError::WalletLinkIssue),
+    };
+
+    let transfers = match verifier.get_token_transfers(&req.transaction_hash).await {
+        Ok(t) => t,
+        Err(StarknetTransferError::NotAccepted) => {
+            return Err(ReverseBridgeError::TransactionNotAccepted)
+        }
+        Err(StarknetTransferError::FetchFailed) => {
+            return Err(ReverseBridgeError::StarknetFetchFailed)
+        }
+    };
+    for token in &req.token_ids {
+        let returned = transfers.iter().any(|t| {
+            same_felt(t.contract.as_str(), project.starknet_contract.as_str())
+                && same_felt(t.from.as_str(), req.starknet_account_addr.as_str())
+                && (same_felt(t.to.as_str(), starknet_admin_address)
+                    || same_felt(t.to.as_str(), "0x0"))
+                && &t.token_id == token
+        });
+        if !returned {
+            warn!(
+                "Token {} was not returned by {} in transaction {}",
+                token, &req.starknet_account_addr, &req.transaction_hash
+            );
+            return Err(ReverseBridgeError::TokenNotReturned(token.to_string()));
+        }
+    }
+
+    let items = req
+        .token_ids
+        .iter()
+        .map(|token| ReverseQueueItem {
+            id: Uuid::new_v4(),
+            starknet_account_addr: req.starknet_account_addr.clone(),
+            keplr_wallet_pubkey: req.keplr_wallet_pubkey.clone(),
+            starknet_project_id: project.starknet_contract.clone(),
+            project_id: project.juno_contract.clone(),
+            token_id: token.clone(),
+            starknet_transaction_hash: req.transaction_hash.clone(),
+            status: ReverseQueueStatus::Pending,
+            juno_transaction_hash: None,
+            note: None,
+        })
+        .collect();
+    match queue.enqueue(items).await {
+        Ok(items) => Ok(items),
+        Err(ReverseQueueError::AlreadyRequested(token)) => {
+            Err(ReverseBridgeError::AlreadyRequested(token.to_string()))
+        }
+        Err(_e) => Err(ReverseBridgeError::QueueIssue),
+    }
+}
+
+/// Transfers pending tokens back on Juno, one transaction per contract and recipient.
+pub async fn consume_reverse_queue(
+    queue: Arc<dyn ReverseQueueManager>,
+    broadcaster: Arc<dyn JunoTxBroadcaster>,
+    batch_size: usize,
+) -> Result<(), ReverseQueueError> {
+    let items = queue.get_batch(batch_size).await?;
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let mut batches: HashMap<(ProjectId, JunoAddress), Vec<ReverseQueueItem>> = HashMap::new();
+    for item in items {
+        batches
+            .entry((item.project_id.clone(), item.keplr_wallet_pubkey.clone()))
+            .or_default()
+            .push(item);
+    }
+
+    for ((contract, recipient), items) in batches {
+        let ids: Vec<Uuid> = items.iter().map(|i| i.id).collect();
+        let token_ids: Vec<TokenId> = items.iter().map(|i| i.token_id.clone()).collect();
+        queue
+            .update_status(&ids, ReverseQueueStatus::Processing, None, None)
+            .await?;
+
+        match broadcaster
+            .transfer_tokens(&contract, &recipient, &token_ids)
+            .await
+        {
+            Ok(tx_hash) => {
+                info!(
+                    "Transferred {} tokens of {} back to {} in {}",
+                    token_ids.len(),
+                    contract,
+                    recipient,
+                    tx_hash
+                );
+                queue
+                    .update_status(&ids, ReverseQueueStatus::Success, Some(tx_hash), None)
+                    .await?;
+            }
+            Err(e) => {
+                error!(
+                    "Failed to transfer tokens of {} back to {} : {:#?}",
+                    contract, recipient, e
+                );
+                let note = match e {
+                    JunoBroadcastError::Rejected(reason) => reason,
+                    JunoBroadcastError::Failure(reason) => reason,
+                };
+                queue
+                    .update_status(&ids, ReverseQueueStatus::Error, None, Some(note))
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,44 +1,109 @@
 use async_trait::async_trait;
 use core::fmt::{Debug, Formatter};
-use serde_derive::Deserialize;
+use log::{error, info, warn};
+use serde_derive::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize)]
+use super::{
+    breakglass::Operator,
+    bridge::{SignedHash, SignedHashValidator},
+    ids::{JunoAddress, ProjectId, TokenId},
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct SaveCustomerDataRequest {
-    pub keplr_wallet_pubkey: String,
-    pub project_id: String,
-    pub token_ids: Vec<String>,
+    #[schema(value_type = String)]
+    pub keplr_wallet_pubkey: JunoAddress,
+    #[schema(value_type = String)]
+    pub project_id: ProjectId,
+    #[schema(value_type = Vec<String>)]
+    pub token_ids: Vec<TokenId>,
 }
 
 impl SaveCustomerDataRequest {
-    pub fn new(keplr_wallet_pubkey: &str, project_id: &str, token_ids: Vec<&str>) -> Self {
-        let mut tokens = vec![];
-        for t in token_ids {
-            tokens.push(t.into());
-        }
+    pub fn new(
+        keplr_wallet_pubkey: JunoAddress,
+        project_id: ProjectId,
+        token_ids: Vec<TokenId>,
+    ) -> Self {
         Self {
-            keplr_wallet_pubkey: keplr_wallet_pubkey.into(),
-            project_id: project_id.into(),
-            token_ids: tokens,
+            keplr_wallet_pubkey,
+            project_id,
+            token_ids,
         }
     }
 }
 
+/// What a save did to the tokens registered for the customer on the project.
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SaveCustomerDataOutcome {
+    /// Tokens of the request that were not registered yet
+    pub added: usize,
+    /// Tokens of the request already registered, or repeated in it
+    pub duplicates: usize,
+    /// Tokens registered once saved
+    pub total: usize,
+}
+
+/// Registers a Juno account (multisig, DAO...) allowed to transfer tokens to admin
+/// on behalf of the customer. Customer signs the sender address with its keplr wallet,
+/// the sender signs `{keplr_wallet_pubkey}:{project_id}` to agree to it.
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeSenderRequest {
+    pub signed_hash: SignedHash,
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub project_id: ProjectId,
+    pub sender: JunoAddress,
+    /// Missing for contract accounts which cannot sign, an operator approves them instead
+    #[serde(default)]
+    pub sender_signed_hash: Option<SignedHash>,
+}
+
+/// Sender that cannot sign, e.g. a DAO contract, checked by an operator beforehand.
+#[derive(Debug, Deserialize)]
+pub struct ApproveSenderRequest {
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub project_id: ProjectId,
+    pub sender: JunoAddress,
+    pub reason: String,
+}
+
+/// What the sender signs to migrate tokens on behalf of the customer.
+pub fn sender_authorization_message(
+    keplr_wallet_pubkey: &JunoAddress,
+    project_id: &ProjectId,
+) -> String {
+    format!("{}:{}", keplr_wallet_pubkey, project_id)
+}
+
 #[derive(Debug)]
 pub struct CustomerKeys {
-    pub keplr_wallet_pubkey: String,
-    pub project_id: String,
-    pub token_ids: Vec<String>,
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub project_id: ProjectId,
+    pub token_ids: Vec<TokenId>,
 }
 
 #[async_trait]
-pub trait DataRepository {
+pub trait DataRepository: Send + Sync {
     async fn save_customer_keys(&self, keys: CustomerKeys) -> Result<(), SaveCustomerDataError>;
     async fn get_customer_keys(
         &self,
-        keplr_wallet_pubkey: &str,
-        project_id: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
     ) -> Result<CustomerKeys, SaveCustomerDataError>;
+    async fn save_authorized_sender(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        sender: &JunoAddress,
+    ) -> Result<(), SaveCustomerDataError>;
+    async fn get_authorized_senders(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+    ) -> Result<Vec<JunoAddress>, SaveCustomerDataError>;
 }
 
 impl Debug for dyn DataRepository {
@@ -51,23 +116,125 @@ pub enum SaveCustomerDataError {
     NotImpled,
     NotFound,
     FailedToPersistToDatabase,
+    InvalidSign,
+    MissingSenderSign,
+    InvalidSenderSign,
+    InvalidRequest,
 }
 
+/// Merges the tokens of the request into the ones already registered, so tokens
+/// transferred in several times are all kept.
 pub async fn handle_save_customer_data(
     req: &SaveCustomerDataRequest,
     data_repository: Arc<dyn DataRepository>,
-) -> Result<(), SaveCustomerDataError> {
-    match data_repository
+) -> Result<SaveCustomerDataOutcome, SaveCustomerDataError> {
+    let mut token_ids = match data_repository
+        .get_customer_keys(&req.keplr_wallet_pubkey, &req.project_id)
+        .await
+    {
+        Ok(keys) => keys.token_ids,
+        Err(SaveCustomerDataError::NotFound) => Vec::new(),
+        Err(_e) => return Err(SaveCustomerDataError::FailedToPersistToDatabase),
+    };
+
+    let mut added = 0;
+    for token_id in &req.token_ids {
+        if !token_ids.contains(token_id) {
+            token_ids.push(token_id.clone());
+            added += 1;
+        }
+    }
+    let outcome = SaveCustomerDataOutcome {
+        added,
+        duplicates: req.token_ids.len() - added,
+        total: token_ids.len(),
+    };
+
+    if data_repository
         .save_customer_keys(CustomerKeys {
             keplr_wallet_pubkey: req.keplr_wallet_pubkey.clone(),
             project_id: req.project_id.clone(),
-            token_ids: req.token_ids.clone(),
+            token_ids,
         })
         .await
+        .is_err()
     {
-        Err(_e) => return Err(SaveCustomerDataError::FailedToPersistToDatabase),
-        Ok(_) => (),
+        return Err(SaveCustomerDataError::FailedToPersistToDatabase);
+    }
+
+    Ok(outcome)
+}
+
+pub async fn handle_authorize_sender(
+    req: &AuthorizeSenderRequest,
+    hash_validator: Arc<dyn SignedHashValidator>,
+    data_repository: Arc<dyn DataRepository>,
+) -> Result<(), SaveCustomerDataError> {
+    if hash_validator
+        .verify(
+            &req.signed_hash,
+            req.sender.as_str(),
+            req.keplr_wallet_pubkey.as_str(),
+        )
+        .is_err()
+    {
+        error!(
+            "AUDIT - invalid signature while authorizing sender {} for {} on project {}",
+            req.sender, req.keplr_wallet_pubkey, req.project_id
+        );
+        return Err(SaveCustomerDataError::InvalidSign);
+    }
+    // Otherwise anyone could claim the tokens another account sends to admin
+    let Some(sender_signed_hash) = &req.sender_signed_hash else {
+        error!(
+            "AUDIT - sender {} did not sign its authorization for {} on project {}",
+            req.sender, req.keplr_wallet_pubkey, req.project_id
+        );
+        return Err(SaveCustomerDataError::MissingSenderSign);
     };
+    if hash_validator
+        .verify(
+            sender_signed_hash,
+            &sender_authorization_message(&req.keplr_wallet_pubkey, &req.project_id),
+            req.sender.as_str(),
+        )
+        .is_err()
+    {
+        error!(
+            "AUDIT - invalid sender signature while authorizing sender {} for {} on project {}",
+            req.sender, req.keplr_wallet_pubkey, req.project_id
+        );
+        return Err(SaveCustomerDataError::InvalidSenderSign);
+    }
+
+    data_repository
+        .save_authorized_sender(&req.keplr_wallet_pubkey, &req.project_id, &req.sender)
+        .await?;
+    info!(
+        "AUDIT - sender {} authorized for {} on project {}",
+        req.sender, req.keplr_wallet_pubkey, req.project_id
+    );
+
+    Ok(())
+}
+
+/// Authorizes a sender on the word of an operator, for accounts which cannot sign.
+pub async fn handle_approve_sender(
+    req: &ApproveSenderRequest,
+    operator: &Operator,
+    data_repository: Arc<dyn DataRepository>,
+) -> Result<(), SaveCustomerDataError> {
+    if req.reason.trim().is_empty() {
+        return Err(SaveCustomerDataError::InvalidRequest);
+    }
+
+    data_repository
+        .save_authorized_sender(&req.keplr_wallet_pubkey, &req.project_id, &req.sender)
+        .await?;
+    warn!(
+        "AUDIT - {} approved sender {} for {} on project {} : {}",
+        operator.name, req.sender, req.keplr_wallet_pubkey, req.project_id, req.reason
+    );
 
     Ok(())
 }
@@ -0,0 +1,23 @@
+/// Version of the request and response payloads, bumped whenever a change breaks
+/// released frontends.
+pub const BRIDGE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum SchemaVersionError {
+    Missing,
+    Unsupported(String),
+}
+
+/// Accepts requests built for the current schema version. Clients that do not send a
+/// version are trusted unless `required`, so frontends can start sending it first.
+pub fn check_schema_version(
+    version: Option<&str>,
+    required: bool,
+) -> Result<(), SchemaVersionError> {
+    match version.map(str::trim) {
+        None if required => Err(SchemaVersionError::Missing),
+        None => Ok(()),
+        Some(v) if v.parse::<u32>().ok() == Some(BRIDGE_SCHEMA_VERSION) => Ok(()),
+        Some(v) => Err(SchemaVersionError::Unsupported(v.to_string())),
+    }
+}
@@ -0,0 +1,389 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use super::{bridge::QueueStatus, clock::Clock};
+
+const DEFAULT_INTERVAL_MS: i64 = 3_600_000;
+
+#[derive(Debug)]
+pub enum StatsError {
+    InvalidRange,
+    FailedToFetchStats,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsFormat {
+    Timeserie,
+    Table,
+}
+
+impl Default for StatsFormat {
+    fn default() -> Self {
+        StatsFormat::Timeserie
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StatsKind {
+    Queue,
+    Throughput,
+    Project,
+}
+
+/// Query params as sent by the Grafana JSON API datasource (`${__from}` / `${__to}`
+/// expand to epoch milliseconds).
+#[derive(Debug, Deserialize)]
+pub struct StatsRequest {
+    pub from: i64,
+    pub to: i64,
+    #[serde(default)]
+    pub format: StatsFormat,
+    pub interval_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TimeRange {
+    pub from: i64,
+    pub to: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusTransition {
+    pub project_id: String,
+    pub status: QueueStatus,
+    // Bucket start as epoch milliseconds
+    pub bucket: i64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectProgress {
+    pub project_id: String,
+    pub pending: i64,
+    pub processing: i64,
+    pub success: i64,
+    pub error: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectCompletion {
+    pub project_id: String,
+    pub total: i64,
+    pub migrated: i64,
+    pub average_completion_seconds: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PublicProjectStats {
+    pub project_id: String,
+    pub progress_percentage: f64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PublicStats {
+    pub total_tokens_migrated: i64,
+    pub average_completion_seconds: Option<f64>,
+    pub projects: Vec<PublicProjectStats>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TimeSerie {
+    pub target: String,
+    // Grafana expects [value, timestamp_ms] pairs
+    pub datapoints: Vec<(i64, i64)>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TableColumn {
+    pub text: String,
+    #[serde(rename = "type")]
+    pub column_type: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Table {
+    #[serde(rename = "type")]
+    pub table_type: String,
+    pub columns: Vec<TableColumn>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn new(columns: Vec<(&str, &str)>, rows: Vec<Vec<Value>>) -> Self {
+        Self {
+            table_type: "table".into(),
+            columns: columns
+                .iter()
+                .map(|(text, column_type)| TableColumn {
+                    text: text.to_string(),
+                    column_type: column_type.to_string(),
+                })
+                .collect(),
+            rows,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum StatsResponse {
+    TimeSeries(Vec<TimeSerie>),
+    Tables(Vec<Table>),
+}
+
+#[async_trait]
+pub trait StatsRepository: Send + Sync {
+    async fn get_status_transitions(
+        &self,
+        range: &TimeRange,
+        interval_ms: i64,
+    ) -> Result<Vec<StatusTransition>, StatsError>;
+    async fn get_project_progress(
+        &self,
+        range: &TimeRange,
+    ) -> Result<Vec<ProjectProgress>, StatsError>;
+    async fn get_project_completions(&self) -> Result<Vec<ProjectCompletion>, StatsError>;
+}
+
+impl Debug for dyn StatsRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "StatsRepository{{}}")
+    }
+}
+
+pub fn status_label(status: &QueueStatus) -> &'static str {
+    match status {
+        QueueStatus::Pending => "pending",
+        QueueStatus::Processing => "processing",
+        QueueStatus::Success => "success",
+        QueueStatus::Error => "error",
+        QueueStatus::DeadLetter => "dead_letter",
+    }
+}
+
+fn group_series(entries: Vec<(String, i64, i64)>) -> Vec<TimeSerie> {
+    let mut series: BTreeMap<String, BTreeMap<i64, i64>> = BTreeMap::new();
+    for (target, bucket, count) in entries {
+        *series.entry(target).or_default().entry(bucket).or_default() += count;
+    }
+
+    series
+        .into_iter()
+        .map(|(target, points)| TimeSerie {
+            target,
+            datapoints: points
+                .into_iter()
+                .map(|(bucket, count)| (count, bucket))
+                .collect(),
+        })
+        .collect()
+}
+
+pub async fn handle_stats_request(
+    kind: StatsKind,
+    req: &StatsRequest,
+    stats_repository: Arc<dyn StatsRepository>,
+) -> Result<StatsResponse, StatsError> {
+    if req.from >= req.to {
+        return Err(StatsError::InvalidRange);
+    }
+    let range = TimeRange {
+        from: req.from,
+        to: req.to,
+    };
+    let interval_ms = match req.interval_ms {
+        Some(i) if 0 < i => i,
+        _ => DEFAULT_INTERVAL_MS,
+    };
+
+    match (kind, req.format) {
+        (StatsKind::Queue, StatsFormat::Timeserie) => {
+            let transitions = stats_repository
+                .get_status_transitions(&range, interval_ms)
+                .await?;
+            Ok(StatsResponse::TimeSeries(group_series(
+                transitions
+                    .into_iter()
+                    .map(|t| (status_label(&t.status).to_string(), t.bucket, t.count))
+                    .collect(),
+            )))
+        }
+        (StatsKind::Queue, StatsFormat::Table) => {
+            let progress = stats_repository.get_project_progress(&range).await?;
+            let mut totals: BTreeMap<&str, i64> = BTreeMap::new();
+            for p in &progress {
+                *totals.entry("pending").or_default() += p.pending;
+                *totals.entry("processing").or_default() += p.processing;
+                *totals.entry("success").or_default() += p.success;
+                *totals.entry("error").or_default() += p.error;
+            }
+            Ok(StatsResponse::Tables(vec![Table::new(
+                vec![("status", "string"), ("count", "number")],
+                totals
+                    .into_iter()
+                    .map(|(status, count)| vec![json!(status), json!(count)])
+                    .collect(),
+            )]))
+        }
+        (StatsKind::Throughput, StatsFormat::Timeserie) => {
+            let transitions = stats_repository
+                .get_status_transitions(&range, interval_ms)
+                .await?;
+            Ok(StatsResponse::TimeSeries(group_series(
+                transitions
+                    .into_iter()
+                    .filter(|t| {
+                        matches!(
+                            t.status,
+                            QueueStatus::Success | QueueStatus::Error | QueueStatus::DeadLetter
+                        )
+                    })
+                    .map(|t| (status_label(&t.status).to_string(), t.bucket, t.count))
+                    .collect(),
+            )))
+        }
+        (StatsKind::Throughput, StatsFormat::Table) => {
+            let transitions = stats_repository
+                .get_status_transitions(&range, interval_ms)
+                .await?;
+            let minted: i64 = transitions
+                .iter()
+                .filter(|t| matches!(t.status, QueueStatus::Success))
+                .map(|t| t.count)
+                .sum();
+            let hours = (req.to - req.from) as f64 / 3_600_000.0;
+            Ok(StatsResponse::Tables(vec![Table::new(
+                vec![("minted", "number"), ("minted_per_hour", "number")],
+                vec![vec![json!(minted), json!(minted as f64 / hours)]],
+            )]))
+        }
+        (StatsKind::Project, StatsFormat::Timeserie) => {
+            let transitions = stats_repository
+                .get_status_transitions(&range, interval_ms)
+                .await?;
+            Ok(StatsResponse::TimeSeries(group_series(
+                transitions
+                    .into_iter()
+                    .filter(|t| matches!(t.status, QueueStatus::Success))
+                    .map(|t| (t.project_id, t.bucket, t.count))
+                    .collect(),
+            )))
+        }
+        (StatsKind::Project, StatsFormat::Table) => {
+            let progress = stats_repository.get_project_progress(&range).await?;
+            Ok(StatsResponse::Tables(vec![Table::new(
+                vec![
+                    ("project_id", "string"),
+                    ("pending", "number"),
+                    ("processing", "number"),
+                    ("success", "number"),
+                    ("error", "number"),
+                ],
+                progress
+                    .into_iter()
+                    .map(|p| {
+                        vec![
+                            json!(p.project_id),
+                            json!(p.pending),
+                            json!(p.processing),
+                            json!(p.success),
+                            json!(p.error),
+                        ]
+                    })
+                    .collect(),
+            )]))
+        }
+    }
+}
+
+/// Keeps the last computed public stats around so the public website cannot
+/// hammer the database.
+pub struct PublicStatsCache {
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+    // Computation time as epoch milliseconds
+    entry: Mutex<Option<(i64, PublicStats)>>,
+}
+
+impl PublicStatsCache {
+    pub fn new(ttl: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            ttl,
+            clock,
+            entry: Mutex::new(None),
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn get(&self) -> Option<PublicStats> {
+        let lock = match self.entry.lock() {
+            Ok(l) => l,
+            Err(_) => return None,
+        };
+        match &*lock {
+            Some((at, stats)) if self.clock.now_ms() - at < self.ttl.as_millis() as i64 => {
+                Some(stats.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn set(&self, stats: PublicStats) {
+        if let Ok(mut lock) = self.entry.lock() {
+            *lock = Some((self.clock.now_ms(), stats));
+        }
+    }
+}
+
+pub async fn handle_public_stats(
+    stats_repository: Arc<dyn StatsRepository>,
+    cache: Arc<PublicStatsCache>,
+) -> Result<PublicStats, StatsError> {
+    if let Some(stats) = cache.get() {
+        return Ok(stats);
+    }
+
+    let completions = stats_repository.get_project_completions().await?;
+
+    let mut total_tokens_migrated = 0;
+    let mut weighted_seconds = 0.0;
+    let mut timed_tokens = 0;
+    let mut projects = Vec::new();
+    for c in completions {
+        total_tokens_migrated += c.migrated;
+        if let Some(avg) = c.average_completion_seconds {
+            weighted_seconds += avg * c.migrated as f64;
+            timed_tokens += c.migrated;
+        }
+        projects.push(PublicProjectStats {
+            project_id: c.project_id,
+            progress_percentage: match c.total {
+                0 => 0.0,
+                total => (c.migrated as f64 * 10000.0 / total as f64).round() / 100.0,
+            },
+        });
+    }
+
+    let stats = PublicStats {
+        total_tokens_migrated,
+        average_completion_seconds: match timed_tokens {
+            0 => None,
+            n => Some(weighted_seconds / n as f64),
+        },
+        projects,
+    };
+    cache.set(stats.clone());
+
+    Ok(stats)
+}
@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Status update of a queue item as the worker records it: a message template id, kept
+/// in the item note, and the values the message is rendered with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusNote {
+    pub template: String,
+    pub params: BTreeMap<String, String>,
+}
+
+impl StatusNote {
+    pub fn new(template: &str) -> Self {
+        Self {
+            template: template.into(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn with(mut self, name: &str, value: &str) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+}
+
+// (template, language, text), `{name}` placeholders are replaced by note parameters
+const MESSAGES: &[(&str, &str, &str)] = &[
+    (
+        "ContractPaused",
+        "en",
+        "Project contract is paused, your token will be minted once it resumes",
+    ),
+    (
+        "ContractPaused",
+        "fr",
+        "Le contrat du projet est en pause, votre jeton sera créé dès sa reprise",
+    ),
+    (
+        "TransactionNotReceived",
+        "en",
+        "Transaction {tx_hash} did not reach Starknet, your token will be minted again",
+    ),
+    (
+        "TransactionNotReceived",
+        "fr",
+        "La transaction {tx_hash} n'a pas atteint Starknet, votre jeton sera créé à nouveau",
+    ),
+    (
+        "MintFailed",
+        "en",
+        "Mint could not be sent, another attempt is scheduled",
+    ),
+    (
+        "MintFailed",
+        "fr",
+        "La création n'a pas pu être envoyée, une nouvelle tentative est prévue",
+    ),
+    (
+        "MintReverted",
+        "en",
+        "Mint of this token is refused by the project contract, support will look into it",
+    ),
+    (
+        "MintReverted",
+        "fr",
+        "La création de ce jeton est refusée par le contrat du projet, le support va l'examiner",
+    ),
+    (
+        "FeeAboveCap",
+        "en",
+        "Starknet fees are unusually high, your token will be minted once they go down",
+    ),
+    (
+        "FeeAboveCap",
+        "fr",
+        "Les frais Starknet sont anormalement élevés, votre jeton sera créé dès leur baisse",
+    ),
+    (
+        "WaitingForNextBatch",
+        "en",
+        "Your token is queued for the next mint",
+    ),
+    (
+        "WaitingForNextBatch",
+        "fr",
+        "Votre jeton est en attente de la prochaine création",
+    ),
+    (
+        "TransactionRejected",
+        "en",
+        "Transaction {tx_hash} was rejected ({reason}), another attempt is scheduled",
+    ),
+    (
+        "TransactionRejected",
+        "fr",
+        "La transaction {tx_hash} a été rejetée ({reason}), une nouvelle tentative est prévue",
+    ),
+    (
+        "UncheckedMintStatus",
+        "en",
+        "Starknet could not be reached to check your token, it will be checked before being minted",
+    ),
+    (
+        "UncheckedMintStatus",
+        "fr",
+        "Starknet n'a pas pu être joint pour vérifier votre jeton, il le sera avant sa création",
+    ),
+    (
+        "RequeuedByOperator",
+        "en",
+        "Support queued your token again",
+    ),
+    (
+        "RequeuedByOperator",
+        "fr",
+        "Le support a remis votre jeton en file d'attente",
+    ),
+    (
+        "CancelledByOperator",
+        "en",
+        "Support cancelled the migration of this token",
+    ),
+    (
+        "CancelledByOperator",
+        "fr",
+        "Le support a annulé la migration de ce jeton",
+    ),
+    (
+        "RetriedByCustomer",
+        "en",
+        "Your token is queued again as requested",
+    ),
+    (
+        "RetriedByCustomer",
+        "fr",
+        "Votre jeton a été remis en file d'attente à votre demande",
+    ),
+    (
+        "DeadLetterRequeuedByOperator",
+        "en",
+        "Support queued your token again after repeated failures",
+    ),
+    (
+        "DeadLetterRequeuedByOperator",
+        "fr",
+        "Le support a remis votre jeton en file d'attente après plusieurs échecs",
+    ),
+    (
+        "ProcessingStalled",
+        "en",
+        "Minting your token stalled, it is queued again",
+    ),
+    (
+        "ProcessingStalled",
+        "fr",
+        "La création de votre jeton s'est interrompue, il a été remis en file d'attente",
+    ),
+    (
+        "DuplicateQueueItem",
+        "en",
+        "This token was already queued by another wallet, this migration was dropped",
+    ),
+    (
+        "DuplicateQueueItem",
+        "fr",
+        "Ce jeton était déjà en file d'attente pour un autre portefeuille, cette migration a été abandonnée",
+    ),
+];
+
+fn is_supported(language: &str) -> bool {
+    MESSAGES.iter().any(|(_, l, _)| *l == language)
+}
+
+/// First supported language of an `Accept-Language` header, quality values aside.
+pub fn negotiate_language(accept_language: Option<&str>) -> String {
+    let mut ranges: Vec<(String, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.trim().split(";q=");
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .next()
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            // Only the primary subtag is translated, `fr-CH` reads french messages
+            let language = tag.split('-').next()?.to_ascii_lowercase();
+            Some((language, quality))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranges
+        .into_iter()
+        .map(|(language, _)| language)
+        .find(|language| is_supported(language))
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+/// Customer message of a note in the given language, english when it is not
+/// translated. Unknown templates have no message.
+pub fn render_status_message(
+    template: &str,
+    params: &BTreeMap<String, String>,
+    language: &str,
+) -> Option<String> {
+    let text = MESSAGES
+        .iter()
+        .find(|(t, l, _)| *t == template && *l == language)
+        .or_else(|| {
+            MESSAGES
+                .iter()
+                .find(|(t, l, _)| *t == template && *l == DEFAULT_LANGUAGE)
+        })
+        .map(|(_, _, text)| *text)?;
+
+    let mut message = text.to_string();
+    for (name, value) in params {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+
+    Some(message)
+}
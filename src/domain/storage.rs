@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum StorageError {
+    InvalidKey(String),
+    UploadFailed(String),
+}
+
+/// Bucket large artifacts (exports, reports) are uploaded to, clients download them
+/// through presigned urls instead of going through the API.
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    async fn put_object(
+        &self,
+        key: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<(), StorageError>;
+    fn presigned_url(&self, key: &str, expires_in: Duration) -> Result<String, StorageError>;
+}
+
+impl Debug for dyn ObjectStorage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ObjectStorage{{}}")
+    }
+}
@@ -0,0 +1,131 @@
+use log::error;
+use serde_derive::Serialize;
+use std::{collections::HashSet, sync::Arc};
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    bridge::{QueueItem, QueueManager, StarknetManager, Transaction, TransactionRepository},
+    ids::{JunoAddress, ProjectId, StarknetAddress, TokenId},
+    save_customer_data::DataRepository,
+    wallet_link::{WalletLink, WalletLinkRepository},
+};
+
+#[derive(Serialize, Debug)]
+pub struct TokenTransfers {
+    pub token_id: TokenId,
+    pub transactions: Vec<Transaction>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StarknetTransaction {
+    pub transaction_hash: String,
+    pub status: Option<String>,
+}
+
+/// Everything support needs to investigate a customer migration, in one document.
+#[derive(Serialize, Debug)]
+pub struct SupportBundle {
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub project_id: ProjectId,
+    pub registered_token_ids: Vec<TokenId>,
+    pub wallet_link: Option<WalletLink>,
+    pub authorized_senders: Vec<JunoAddress>,
+    pub queue_items: Vec<QueueItem>,
+    pub juno_transfers: Vec<TokenTransfers>,
+    pub starknet_transactions: Vec<StarknetTransaction>,
+}
+
+pub struct SupportBundleRequest<'a> {
+    pub keplr_wallet_pubkey: &'a JunoAddress,
+    pub project_id: &'a ProjectId,
+    pub starknet_project_addr: &'a StarknetAddress,
+}
+
+pub async fn handle_support_bundle(
+    req: &SupportBundleRequest<'_>,
+    data_repository: Arc<dyn DataRepository>,
+    queue_manager: Arc<dyn QueueManager>,
+    wallet_link_repository: Arc<dyn WalletLinkRepository>,
+    transaction_repository: Arc<dyn TransactionRepository>,
+    starknet_manager: Arc<dyn StarknetManager>,
+    cancel: &CancellationToken,
+) -> SupportBundle {
+    let registered_token_ids = match data_repository
+        .get_customer_keys(req.keplr_wallet_pubkey, req.project_id)
+        .await
+    {
+        Ok(keys) => keys.token_ids,
+        Err(_) => Vec::new(),
+    };
+    let authorized_senders = data_repository
+        .get_authorized_senders(req.keplr_wallet_pubkey, req.project_id)
+        .await
+        .unwrap_or_default();
+    let wallet_link = wallet_link_repository
+        .get_link(req.keplr_wallet_pubkey)
+        .await
+        .ok();
+    let queue_items = queue_manager
+        .get_customer_migration_state(req.keplr_wallet_pubkey, req.starknet_project_addr)
+        .await;
+
+    let mut token_ids: Vec<TokenId> = registered_token_ids.to_vec();
+    for qi in &queue_items {
+        if !token_ids.contains(&qi.token_id) {
+            token_ids.push(qi.token_id.clone());
+        }
+    }
+
+    let mut juno_transfers = Vec::new();
+    for token_id in &token_ids {
+        let transfers = match transaction_repository
+            .get_transactions_for_contract(req.project_id, token_id, cancel)
+            .await
+        {
+            Ok(transactions) => TokenTransfers {
+                token_id: token_id.clone(),
+                transactions,
+                error: None,
+            },
+            Err(e) => {
+                error!(
+                    "Support bundle failed to fetch token {} : {:#?}",
+                    token_id, e
+                );
+                TokenTransfers {
+                    token_id: token_id.clone(),
+                    transactions: Vec::new(),
+                    error: Some(format!("{:?}", e)),
+                }
+            }
+        };
+        juno_transfers.push(transfers);
+    }
+
+    let mut transaction_hashes = HashSet::new();
+    let mut starknet_transactions = Vec::new();
+    for qi in &queue_items {
+        let Some(tx_hash) = &qi.transaction_hash else {
+            continue;
+        };
+        if tx_hash.is_empty() || !transaction_hashes.insert(tx_hash.to_string()) {
+            continue;
+        }
+        starknet_transactions.push(StarknetTransaction {
+            transaction_hash: tx_hash.to_string(),
+            status: starknet_manager.get_transaction_status(tx_hash).await,
+        });
+    }
+
+    SupportBundle {
+        keplr_wallet_pubkey: req.keplr_wallet_pubkey.clone(),
+        project_id: req.project_id.clone(),
+        registered_token_ids,
+        wallet_link,
+        authorized_senders,
+        queue_items,
+        juno_transfers,
+        starknet_transactions,
+    }
+}
@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    bridge::{Transaction, TransactionFetchError, TransactionRepository},
+    clock::Clock,
+    ids::ProjectId,
+};
+
+/// Keeps contract transaction listings around for a short while, so checking every
+/// token of a bridge request costs a single LCD call per contract.
+pub struct CachedTransactionRepository {
+    inner: Arc<dyn TransactionRepository>,
+    ttl: Duration,
+    clock: Arc<dyn Clock>,
+    // Fetch time as epoch milliseconds
+    entries: RwLock<HashMap<ProjectId, (i64, Vec<Transaction>)>>,
+}
+
+impl CachedTransactionRepository {
+    pub fn new(
+        inner: Arc<dyn TransactionRepository>,
+        ttl: Duration,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            inner,
+            ttl,
+            clock,
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn is_fresh(&self, fetched_at: i64, now: i64) -> bool {
+        now - fetched_at < self.ttl.as_millis() as i64
+    }
+}
+
+#[async_trait]
+impl TransactionRepository for CachedTransactionRepository {
+    async fn get_contract_transactions(
+        &self,
+        project_id: &ProjectId,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Transaction>, TransactionFetchError> {
+        let now = self.clock.now_ms();
+        if let Some((fetched_at, transactions)) = self.entries.read().await.get(project_id) {
+            if self.is_fresh(*fetched_at, now) {
+                return Ok(transactions.clone());
+            }
+        }
+
+        // Failures are not cached, next check asks the node again
+        let transactions = self
+            .inner
+            .get_contract_transactions(project_id, cancel)
+            .await?;
+
+        let mut lock = self.entries.write().await;
+        lock.retain(|_, (fetched_at, _)| self.is_fresh(*fetched_at, now));
+        lock.insert(project_id.clone(), (now, transactions.clone()));
+
+        Ok(transactions)
+    }
+}
@@ -0,0 +1,111 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use serde_derive::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{
+    attestation::MintAttestation,
+    bridge::{Burn, MsgTypes, QueueItem, Transaction, TransferNft},
+    ids::{ProjectId, TokenId},
+};
+
+/// Juno transfer proving a token was handed to the admin wallet, or burnt, by `sender`.
+/// Once established it cannot change, so later checks of the token skip the LCD.
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct TransferProof {
+    #[schema(value_type = String)]
+    pub project_id: ProjectId,
+    #[schema(value_type = String)]
+    pub token_id: TokenId,
+    pub sender: String,
+    /// None when the token was burnt
+    pub recipient: Option<String>,
+    /// Unknown when the node did not return transaction responses along with the transfer
+    pub transaction_hash: Option<String>,
+    pub height: Option<i64>,
+    // Epoch milliseconds of the block including the transfer
+    pub block_time: Option<i64>,
+    // Epoch milliseconds
+    pub created_at: i64,
+}
+
+impl TransferProof {
+    pub fn new(
+        project_id: &ProjectId,
+        token_id: &TokenId,
+        transaction: &Transaction,
+        created_at: i64,
+    ) -> Self {
+        Self {
+            project_id: project_id.clone(),
+            token_id: token_id.clone(),
+            sender: transaction.sender.clone(),
+            recipient: transaction.msg.recipient().map(String::from),
+            transaction_hash: transaction.tx_hash.clone(),
+            height: transaction.height,
+            block_time: transaction.block_time,
+            created_at,
+        }
+    }
+
+    /// The transfer as the LCD listed it, to run the usual checks on it.
+    pub fn transaction(&self) -> Transaction {
+        Transaction {
+            contract: self.project_id.to_string(),
+            msg: match &self.recipient {
+                Some(recipient) => MsgTypes::TransferNft(TransferNft {
+                    recipient: recipient.clone(),
+                    token_id: self.token_id.to_string(),
+                }),
+                None => MsgTypes::Burn(Burn {
+                    token_id: self.token_id.to_string(),
+                }),
+            },
+            sender: self.sender.clone(),
+            tx_hash: self.transaction_hash.clone(),
+            height: self.height,
+            block_time: self.block_time,
+            code: Some(0),
+        }
+    }
+}
+
+/// Queue item as shown on the status endpoint, with the transfer it was bridged from.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct ProvenQueueItem {
+    #[serde(flatten)]
+    pub item: QueueItem,
+    pub transfer_proof: Option<TransferProof>,
+    /// Note rendered in the customer language
+    pub message: Option<String>,
+    /// Server signature of the mint, once it succeeded and when the server has an
+    /// attestation key
+    pub attestation: Option<MintAttestation>,
+}
+
+#[derive(Debug)]
+pub enum TransferProofError {
+    PersistenceIssue,
+}
+
+#[async_trait]
+pub trait TransferProofRepository: Send + Sync {
+    async fn get_proof(
+        &self,
+        project_id: &ProjectId,
+        token_id: &TokenId,
+    ) -> Result<Option<TransferProof>, TransferProofError>;
+    async fn get_proofs(
+        &self,
+        project_id: &ProjectId,
+        token_ids: &[TokenId],
+    ) -> Result<Vec<TransferProof>, TransferProofError>;
+    /// Keeps the first proof of a token, saving it again is a no-op.
+    async fn save_proof(&self, proof: &TransferProof) -> Result<(), TransferProofError>;
+}
+
+impl Debug for dyn TransferProofRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "TransferProofRepository{{}}")
+    }
+}
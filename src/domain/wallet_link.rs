@@ -0,0 +1,116 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info};
+use serde_derive::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::{
+    bridge::{SignedHash, SignedHashValidator},
+    challenge::{ChallengeError, ChallengeService},
+    ids::{JunoAddress, StarknetAddress},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WalletLink {
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub starknet_account_addr: StarknetAddress,
+}
+
+#[derive(Debug)]
+pub enum WalletLinkError {
+    InvalidSign,
+    InvalidChallenge,
+    ChallengeExpired,
+    ChallengeIssue,
+    NotFound,
+    FailedToFetch,
+    FailedToPersist,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinkWalletRequest {
+    pub signed_hash: SignedHash,
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub starknet_account_addr: StarknetAddress,
+    /// Challenge from `GET /challenge`, when set the signature has to cover
+    /// `{nonce}:{starknet_account_addr}`.
+    #[serde(default)]
+    pub nonce: Option<String>,
+}
+
+/// What the keplr wallet signs to (re)link itself to a starknet account.
+pub fn link_message(nonce: Option<&str>, starknet_account_addr: &StarknetAddress) -> String {
+    match nonce {
+        Some(nonce) => format!("{}:{}", nonce, starknet_account_addr),
+        None => starknet_account_addr.to_string(),
+    }
+}
+
+#[async_trait]
+pub trait WalletLinkRepository: Send + Sync {
+    async fn get_link(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<WalletLink, WalletLinkError>;
+    async fn save_link(&self, link: WalletLink) -> Result<(), WalletLinkError>;
+    /// Saves the link unless the wallet is already linked, returns the stored link.
+    async fn link_if_absent(&self, link: WalletLink) -> Result<WalletLink, WalletLinkError>;
+}
+
+impl Debug for dyn WalletLinkRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "WalletLinkRepository{{}}")
+    }
+}
+
+/// (Re)links a keplr wallet to a starknet account. Customer has to sign its own
+/// starknet account address so a leaked bridge signature cannot be used to move the link,
+/// along with a challenge nonce so the signature cannot be replayed.
+pub async fn handle_link_wallet(
+    req: &LinkWalletRequest,
+    hash_validator: Arc<dyn SignedHashValidator>,
+    challenges: &ChallengeService,
+    wallet_link_repository: Arc<dyn WalletLinkRepository>,
+) -> Result<WalletLink, WalletLinkError> {
+    if hash_validator
+        .verify(
+            &req.signed_hash,
+            &link_message(req.nonce.as_deref(), &req.starknet_account_addr),
+            req.keplr_wallet_pubkey.as_str(),
+        )
+        .is_err()
+    {
+        error!(
+            "Invalid signature while linking wallet {} to {}",
+            req.keplr_wallet_pubkey, req.starknet_account_addr
+        );
+        return Err(WalletLinkError::InvalidSign);
+    }
+    match challenges
+        .redeem(req.nonce.as_deref(), &req.keplr_wallet_pubkey)
+        .await
+    {
+        Ok(_) => (),
+        Err(ChallengeError::Expired) => return Err(WalletLinkError::ChallengeExpired),
+        Err(ChallengeError::PersistenceIssue) => return Err(WalletLinkError::ChallengeIssue),
+        Err(e) => {
+            error!(
+                "Refusing challenge of {} while linking wallet : {:#?}",
+                req.keplr_wallet_pubkey, e
+            );
+            return Err(WalletLinkError::InvalidChallenge);
+        }
+    };
+
+    let link = WalletLink {
+        keplr_wallet_pubkey: req.keplr_wallet_pubkey.clone(),
+        starknet_account_addr: req.starknet_account_addr.clone(),
+    };
+    wallet_link_repository.save_link(link.clone()).await?;
+    info!(
+        "Linked keplr wallet {} to starknet account {}",
+        link.keplr_wallet_pubkey, link.starknet_account_addr
+    );
+
+    Ok(link)
+}
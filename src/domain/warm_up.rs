@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use futures::future::join_all;
+use log::{info, warn};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+pub enum WarmUpError {
+    Failed(String),
+    TimedOut,
+}
+
+/// Dependency whose first use pays a cold start, connection setup, DNS resolution or
+/// TLS handshake, warmed up once before the api takes traffic.
+#[async_trait]
+pub trait WarmUp: Send + Sync {
+    fn name(&self) -> &str;
+    async fn warm_up(&self) -> Result<(), WarmUpError>;
+}
+
+impl Debug for dyn WarmUp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "WarmUp{{{}}}", self.name())
+    }
+}
+
+#[derive(Debug)]
+pub struct WarmUpOutcome {
+    pub name: String,
+    pub elapsed: Duration,
+    pub result: Result<(), WarmUpError>,
+}
+
+/// Warms every dependency up concurrently, giving each at most `timeout`. Failures only
+/// leave the first customer request to pay the cold start, they never prevent boot.
+pub async fn warm_up(targets: &[Arc<dyn WarmUp>], timeout: Duration) -> Vec<WarmUpOutcome> {
+    let outcomes = join_all(targets.iter().map(|target| async move {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(timeout, target.warm_up()).await {
+            Ok(r) => r,
+            Err(_elapsed) => Err(WarmUpError::TimedOut),
+        };
+        WarmUpOutcome {
+            name: target.name().to_string(),
+            elapsed: started.elapsed(),
+            result,
+        }
+    }))
+    .await;
+
+    for outcome in &outcomes {
+        match &outcome.result {
+            Ok(()) => info!(
+                "Warmed up {} in {}ms",
+                outcome.name,
+                outcome.elapsed.as_millis()
+            ),
+            Err(e) => warn!("Failed to warm up {} : {:#?}", outcome.name, e),
+        }
+    }
+
+    outcomes
+}
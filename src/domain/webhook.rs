@@ -0,0 +1,656 @@
+use async_trait::async_trait;
+use core::fmt::{Debug, Formatter};
+use log::{error, info, warn};
+use serde_derive::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use uuid::Uuid;
+
+use super::{
+    breakglass::Operator,
+    bridge::{QueueItem, QueueStatus, SignedHash, SignedHashValidator},
+    clock::Clock,
+    ids::{JunoAddress, QueueItemId, StarknetAddress, TokenId},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    ItemSucceeded,
+    ItemFailed,
+    /// Item sent to Starknet in a mint transaction, not accepted yet
+    BatchSubmitted,
+    /// Sample payload sent on demand, whatever the subscription filter
+    Test,
+}
+
+/// Events of subscriptions registered without a filter.
+pub const DEFAULT_WEBHOOK_EVENTS: [WebhookEvent; 2] =
+    [WebhookEvent::ItemSucceeded, WebhookEvent::ItemFailed];
+
+impl WebhookEvent {
+    /// Statuses subscribers are told about, other transitions are internal to the bridge.
+    pub fn for_status(status: &QueueStatus) -> Option<Self> {
+        match status {
+            QueueStatus::Success => Some(WebhookEvent::ItemSucceeded),
+            QueueStatus::Error => Some(WebhookEvent::ItemFailed),
+            QueueStatus::Processing => Some(WebhookEvent::BatchSubmitted),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ItemSucceeded => "item_succeeded",
+            Self::ItemFailed => "item_failed",
+            Self::BatchSubmitted => "batch_submitted",
+            Self::Test => "test",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "item_succeeded" => Some(Self::ItemSucceeded),
+            "item_failed" => Some(Self::ItemFailed),
+            "batch_submitted" => Some(Self::BatchSubmitted),
+            "test" => Some(Self::Test),
+            _ => None,
+        }
+    }
+}
+
+/// Callback notified about items of one keplr wallet, or of every wallet when
+/// registered by an operator (e.g. for the frontend backend).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub keplr_wallet_pubkey: Option<JunoAddress>,
+    pub callback_url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Key of the payload signatures, only given back to whoever registered the callback.
+    /// Subscriptions registered before secrets were per subscriber have none and are
+    /// signed with the instance secret.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl WebhookSubscription {
+    pub fn is_subscribed(&self, event: WebhookEvent) -> bool {
+        self.events.contains(&event)
+    }
+}
+
+/// Customer signs the callback url with its keplr wallet. Without `events`, the
+/// callback is notified about `item_succeeded` and `item_failed`.
+#[derive(Debug, Deserialize)]
+pub struct RegisterWebhookRequest {
+    pub signed_hash: SignedHash,
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub callback_url: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterOperatorWebhookRequest {
+    pub callback_url: String,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+/// Customer signs the subscription id with its keplr wallet to delete or test it.
+#[derive(Debug, Deserialize)]
+pub struct SignedWebhookRequest {
+    pub signed_hash: SignedHash,
+    pub keplr_wallet_pubkey: JunoAddress,
+}
+
+/// Outcome of a test delivery, sent right away rather than through the outbox.
+#[derive(Serialize, Debug, Clone)]
+pub struct WebhookTestResult {
+    pub delivery_id: Uuid,
+    pub delivered: bool,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// JSON body posted to subscribers.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub queue_item_id: QueueItemId,
+    pub keplr_wallet_pubkey: JunoAddress,
+    pub starknet_account_addr: StarknetAddress,
+    pub project_id: StarknetAddress,
+    pub token_id: TokenId,
+    pub status: QueueStatus,
+    pub transaction_hash: Option<String>,
+    // Epoch milliseconds
+    pub occurred_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+/// One payload for one subscription, retried with backoff until the callback
+/// answers with a success status or attempts are exhausted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    pub callback_url: String,
+    pub payload: WebhookPayload,
+    pub attempts: i32,
+    // Epoch milliseconds
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub status: WebhookDeliveryStatus,
+    /// Secret of the subscription, read along with due deliveries rather than stored
+    #[serde(skip)]
+    pub signing_secret: Option<String>,
+}
+
+/// Delivery log entry, one per call to the callback url.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookDeliveryAttempt {
+    pub delivery_id: Uuid,
+    pub attempt: i32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    // Epoch milliseconds
+    pub created_at: i64,
+}
+
+#[derive(Debug)]
+pub enum WebhookError {
+    InvalidSign,
+    InvalidCallbackUrl,
+    InvalidEventFilter,
+    NotFound,
+    PersistenceIssue,
+}
+
+#[derive(Debug)]
+pub enum WebhookSendError {
+    Rejected(u16),
+    Unreachable(String),
+}
+
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    /// Inserts the subscription or updates the events of an existing one.
+    async fn save_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+    ) -> Result<(), WebhookError>;
+    /// Subscriptions of the wallet along with the ones registered for every wallet.
+    async fn get_subscriptions(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<WebhookSubscription>, WebhookError>;
+    async fn get_subscription(&self, id: Uuid)
+        -> Result<Option<WebhookSubscription>, WebhookError>;
+    /// Subscription of the owner to `callback_url`, `None` owning the ones of every wallet.
+    async fn find_subscription(
+        &self,
+        keplr_wallet_pubkey: Option<&JunoAddress>,
+        callback_url: &str,
+    ) -> Result<Option<WebhookSubscription>, WebhookError>;
+    /// Subscriptions registered by the wallet only, oldest first.
+    async fn list_subscriptions(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<WebhookSubscription>, WebhookError>;
+    /// Deletes the subscription along with its deliveries.
+    async fn delete_subscription(&self, id: Uuid) -> Result<(), WebhookError>;
+    async fn save_delivery(&self, delivery: &WebhookDelivery) -> Result<(), WebhookError>;
+    /// Pending deliveries due at `now_ms`, along with the secret of their subscription.
+    async fn get_due_deliveries(&self, now_ms: i64) -> Result<Vec<WebhookDelivery>, WebhookError>;
+    async fn log_attempt(&self, attempt: &WebhookDeliveryAttempt) -> Result<(), WebhookError>;
+}
+
+impl Debug for dyn WebhookRepository {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "WebhookRepository{{}}")
+    }
+}
+
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    /// Posts the signed payload, `timestamp` is epoch seconds and part of the signature.
+    async fn send(
+        &self,
+        delivery: &WebhookDelivery,
+        timestamp: i64,
+    ) -> Result<(), WebhookSendError>;
+}
+
+impl Debug for dyn WebhookSender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "WebhookSender{{}}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WebhookRetryPolicy {
+    pub max_attempts: i32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl WebhookRetryPolicy {
+    /// Exponential backoff given the number of attempts that already failed.
+    pub fn delay(&self, failed_attempts: i32) -> Duration {
+        let factor = 2u32.saturating_pow(failed_attempts.clamp(0, 31) as u32);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+impl Default for WebhookRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            base_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Schedules deliveries for queue items reaching a status subscribers are told about.
+#[derive(Debug)]
+pub struct WebhookNotifier {
+    repository: Arc<dyn WebhookRepository>,
+    clock: Arc<dyn Clock>,
+}
+
+impl WebhookNotifier {
+    pub fn new(repository: Arc<dyn WebhookRepository>, clock: Arc<dyn Clock>) -> Self {
+        Self { repository, clock }
+    }
+
+    /// Failures are logged only, a missed notification must not fail the transition.
+    pub async fn notify(
+        &self,
+        queue_items: &[QueueItem],
+        status: QueueStatus,
+        transaction_hash: Option<&str>,
+    ) {
+        let Some(event) = WebhookEvent::for_status(&status) else {
+            return;
+        };
+        let now = self.clock.now_ms();
+
+        for qi in queue_items {
+            let Some(queue_item_id) = qi.id else {
+                continue;
+            };
+            let subscriptions = match self
+                .repository
+                .get_subscriptions(&qi.keplr_wallet_pubkey)
+                .await
+            {
+                Ok(s) => s,
+                Err(e) => {
+                    error!(
+                        "Failed to fetch webhook subscriptions of {} : {:#?}",
+                        qi.keplr_wallet_pubkey, e
+                    );
+                    continue;
+                }
+            };
+
+            for subscription in subscriptions {
+                if !subscription.is_subscribed(event) {
+                    continue;
+                }
+                let delivery = WebhookDelivery {
+                    id: Uuid::new_v4(),
+                    subscription_id: subscription.id,
+                    callback_url: subscription.callback_url.to_string(),
+                    payload: WebhookPayload {
+                        event,
+                        queue_item_id,
+                        keplr_wallet_pubkey: qi.keplr_wallet_pubkey.clone(),
+                        starknet_account_addr: qi.starknet_wallet_pubkey.clone(),
+                        project_id: qi.project_id.clone(),
+                        token_id: qi.token_id.clone(),
+                        status: status.clone(),
+                        transaction_hash: transaction_hash
+                            .filter(|h| !h.is_empty())
+                            .map(String::from),
+                        occurred_at: now,
+                    },
+                    attempts: 0,
+                    next_attempt_at: now,
+                    last_error: None,
+                    status: WebhookDeliveryStatus::Pending,
+                    signing_secret: subscription.secret.clone(),
+                };
+                if let Err(e) = self.repository.save_delivery(&delivery).await {
+                    error!(
+                        "Failed to schedule webhook to {} for token {} : {:#?}",
+                        subscription.callback_url, qi.token_id, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn is_valid_callback_url(url: &str) -> bool {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"));
+    matches!(rest, Some(host) if !host.is_empty() && !host.starts_with('/'))
+}
+
+// 256 random bits, hex encoded
+fn generate_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().as_simple())
+}
+
+fn event_filter(events: &[WebhookEvent]) -> Result<Vec<WebhookEvent>, WebhookError> {
+    if events.contains(&WebhookEvent::Test) {
+        return Err(WebhookError::InvalidEventFilter);
+    }
+    if events.is_empty() {
+        return Ok(DEFAULT_WEBHOOK_EVENTS.to_vec());
+    }
+    let mut filter = Vec::new();
+    for event in events {
+        if !filter.contains(event) {
+            filter.push(*event);
+        }
+    }
+
+    Ok(filter)
+}
+
+/// Registering the same callback twice gives back the existing subscription with its
+/// events replaced, along with whether it was created.
+async fn register_subscription(
+    keplr_wallet_pubkey: Option<&JunoAddress>,
+    callback_url: &str,
+    events: &[WebhookEvent],
+    repository: Arc<dyn WebhookRepository>,
+) -> Result<(WebhookSubscription, bool), WebhookError> {
+    if !is_valid_callback_url(callback_url) {
+        return Err(WebhookError::InvalidCallbackUrl);
+    }
+    let events = event_filter(events)?;
+
+    let (subscription, created) = match repository
+        .find_subscription(keplr_wallet_pubkey, callback_url)
+        .await?
+    {
+        Some(existing) => (WebhookSubscription { events, ..existing }, false),
+        None => (
+            WebhookSubscription {
+                id: Uuid::new_v4(),
+                keplr_wallet_pubkey: keplr_wallet_pubkey.cloned(),
+                callback_url: callback_url.to_string(),
+                events,
+                secret: Some(generate_secret()),
+            },
+            true,
+        ),
+    };
+    repository.save_subscription(&subscription).await?;
+
+    Ok((subscription, created))
+}
+
+/// Subscription of the wallet that signed its id, others are reported as not found.
+async fn owned_subscription(
+    id: Uuid,
+    req: &SignedWebhookRequest,
+    hash_validator: Arc<dyn SignedHashValidator>,
+    repository: &dyn WebhookRepository,
+) -> Result<WebhookSubscription, WebhookError> {
+    if hash_validator
+        .verify(
+            &req.signed_hash,
+            &id.to_string(),
+            req.keplr_wallet_pubkey.as_str(),
+        )
+        .is_err()
+    {
+        error!(
+            "Invalid signature on webhook {} of {}",
+            id, req.keplr_wallet_pubkey
+        );
+        return Err(WebhookError::InvalidSign);
+    }
+
+    match repository.get_subscription(id).await? {
+        Some(s) if s.keplr_wallet_pubkey.as_ref() == Some(&req.keplr_wallet_pubkey) => Ok(s),
+        _ => Err(WebhookError::NotFound),
+    }
+}
+
+pub async fn handle_register_webhook(
+    req: &RegisterWebhookRequest,
+    hash_validator: Arc<dyn SignedHashValidator>,
+    repository: Arc<dyn WebhookRepository>,
+) -> Result<(WebhookSubscription, bool), WebhookError> {
+    if hash_validator
+        .verify(
+            &req.signed_hash,
+            &req.callback_url,
+            req.keplr_wallet_pubkey.as_str(),
+        )
+        .is_err()
+    {
+        error!(
+            "Invalid signature while registering webhook for {}",
+            req.keplr_wallet_pubkey
+        );
+        return Err(WebhookError::InvalidSign);
+    }
+
+    let (subscription, created) = register_subscription(
+        Some(&req.keplr_wallet_pubkey),
+        &req.callback_url,
+        &req.events,
+        repository,
+    )
+    .await?;
+    info!(
+        "Registered webhook {} for keplr wallet {}",
+        subscription.callback_url, req.keplr_wallet_pubkey
+    );
+
+    Ok((subscription, created))
+}
+
+pub async fn handle_register_operator_webhook(
+    req: &RegisterOperatorWebhookRequest,
+    operator: &Operator,
+    repository: Arc<dyn WebhookRepository>,
+) -> Result<(WebhookSubscription, bool), WebhookError> {
+    let (subscription, created) =
+        register_subscription(None, &req.callback_url, &req.events, repository).await?;
+    warn!(
+        "ADMIN - {} registered webhook {} for every wallet",
+        operator.name, subscription.callback_url
+    );
+
+    Ok((subscription, created))
+}
+
+/// Subscriptions of the wallet, without their secrets.
+pub async fn handle_list_webhooks(
+    keplr_wallet_pubkey: &JunoAddress,
+    repository: Arc<dyn WebhookRepository>,
+) -> Result<Vec<WebhookSubscription>, WebhookError> {
+    Ok(repository
+        .list_subscriptions(keplr_wallet_pubkey)
+        .await?
+        .into_iter()
+        .map(|s| WebhookSubscription { secret: None, ..s })
+        .collect())
+}
+
+pub async fn handle_delete_webhook(
+    id: Uuid,
+    req: &SignedWebhookRequest,
+    hash_validator: Arc<dyn SignedHashValidator>,
+    repository: Arc<dyn WebhookRepository>,
+) -> Result<(), WebhookError> {
+    let subscription = owned_subscription(id, req, hash_validator, repository.as_ref()).await?;
+    repository.delete_subscription(subscription.id).await?;
+    info!(
+        "Deleted webhook {} of keplr wallet {}",
+        subscription.callback_url, req.keplr_wallet_pubkey
+    );
+
+    Ok(())
+}
+
+/// Posts a sample `test` payload to the callback once, so integrators can check they
+/// receive and authenticate payloads. The attempt is logged like any other delivery.
+pub async fn handle_test_webhook(
+    id: Uuid,
+    req: &SignedWebhookRequest,
+    hash_validator: Arc<dyn SignedHashValidator>,
+    repository: Arc<dyn WebhookRepository>,
+    sender: Arc<dyn WebhookSender>,
+    clock: Arc<dyn Clock>,
+) -> Result<WebhookTestResult, WebhookError> {
+    let subscription = owned_subscription(id, req, hash_validator, repository.as_ref()).await?;
+    let now = clock.now_ms();
+    let mut delivery = WebhookDelivery {
+        id: Uuid::new_v4(),
+        subscription_id: subscription.id,
+        callback_url: subscription.callback_url.to_string(),
+        payload: WebhookPayload {
+            event: WebhookEvent::Test,
+            queue_item_id: QueueItemId::new(),
+            keplr_wallet_pubkey: req.keplr_wallet_pubkey.clone(),
+            starknet_account_addr: StarknetAddress::unchecked("0x0"),
+            project_id: StarknetAddress::unchecked("0x0"),
+            token_id: TokenId::unchecked("0"),
+            status: QueueStatus::Success,
+            transaction_hash: None,
+            occurred_at: now,
+        },
+        attempts: 1,
+        next_attempt_at: now,
+        last_error: None,
+        status: WebhookDeliveryStatus::Pending,
+        signing_secret: subscription.secret.clone(),
+    };
+
+    let (status_code, reason) = send_delivery(sender.as_ref(), &delivery, clock.now_secs()).await;
+    delivery.status = match reason {
+        None => WebhookDeliveryStatus::Delivered,
+        Some(_) => WebhookDeliveryStatus::Failed,
+    };
+    delivery.last_error = reason.clone();
+    // Saved first, attempts belong to a delivery
+    repository.save_delivery(&delivery).await?;
+    if let Err(e) = repository
+        .log_attempt(&WebhookDeliveryAttempt {
+            delivery_id: delivery.id,
+            attempt: delivery.attempts,
+            status_code,
+            error: reason.clone(),
+            created_at: clock.now_ms(),
+        })
+        .await
+    {
+        error!("Failed to log webhook attempt {:#?}", e);
+    }
+    info!(
+        "Test webhook {} to {} : {}",
+        delivery.id,
+        delivery.callback_url,
+        reason.as_deref().unwrap_or("delivered")
+    );
+
+    Ok(WebhookTestResult {
+        delivery_id: delivery.id,
+        delivered: reason.is_none(),
+        status_code,
+        error: reason,
+    })
+}
+
+/// Status code answered by the callback and why the delivery failed, if it did.
+async fn send_delivery(
+    sender: &dyn WebhookSender,
+    delivery: &WebhookDelivery,
+    timestamp: i64,
+) -> (Option<u16>, Option<String>) {
+    match sender.send(delivery, timestamp).await {
+        Ok(_) => (None, None),
+        Err(WebhookSendError::Rejected(status)) => (
+            Some(status),
+            Some(format!("Callback responded with status {}", status)),
+        ),
+        Err(WebhookSendError::Unreachable(reason)) => (None, Some(reason)),
+    }
+}
+
+/// Posts every due delivery once, failed ones are rescheduled with backoff until
+/// they run out of attempts.
+pub async fn run_webhook_deliveries(
+    repository: Arc<dyn WebhookRepository>,
+    sender: Arc<dyn WebhookSender>,
+    clock: Arc<dyn Clock>,
+    retry_policy: &WebhookRetryPolicy,
+) -> Result<(), WebhookError> {
+    let due = repository.get_due_deliveries(clock.now_ms()).await?;
+
+    for mut delivery in due {
+        delivery.attempts += 1;
+        let (status_code, reason) =
+            send_delivery(sender.as_ref(), &delivery, clock.now_secs()).await;
+
+        match &reason {
+            None => {
+                info!(
+                    "Webhook {} delivered to {}",
+                    delivery.id, delivery.callback_url
+                );
+                delivery.status = WebhookDeliveryStatus::Delivered;
+            }
+            Some(reason) if delivery.attempts >= retry_policy.max_attempts => {
+                error!(
+                    "Giving up webhook {} to {} after {} attempts : {}",
+                    delivery.id, delivery.callback_url, delivery.attempts, reason
+                );
+                delivery.status = WebhookDeliveryStatus::Failed;
+            }
+            Some(reason) => {
+                warn!(
+                    "Webhook {} to {} failed (attempt {}) : {}",
+                    delivery.id, delivery.callback_url, delivery.attempts, reason
+                );
+                delivery.next_attempt_at =
+                    clock.now_ms() + retry_policy.delay(delivery.attempts - 1).as_millis() as i64;
+            }
+        };
+        delivery.last_error = reason.clone();
+
+        if let Err(e) = repository
+            .log_attempt(&WebhookDeliveryAttempt {
+                delivery_id: delivery.id,
+                attempt: delivery.attempts,
+                status_code,
+                error: reason,
+                created_at: clock.now_ms(),
+            })
+            .await
+        {
+            error!("Failed to log webhook attempt {:#?}", e);
+        }
+        repository.save_delivery(&delivery).await?;
+    }
+
+    Ok(())
+}
@@ -1,18 +1,224 @@
-use super::postgresql::{get_connection, PostgresDataRepository, PostgresQueueManager};
-use crate::domain::{bridge::QueueManager, save_customer_data::DataRepository};
+use super::{
+    http::{rate_limit::RateLimiter, HttpClientConfig},
+    issue_tracker::{GithubIssueTracker, LinearIssueTracker, GITHUB_API_URL, LINEAR_API_URL},
+    juno::{JunoLcd, JunoRetryPolicy, SignerJunoTxBroadcaster},
+    jwt::HmacJwtVerifier,
+    metrics::configure_metrics,
+    migrations::MigrationMode,
+    nats::NatsQueueBroker,
+    object_storage::PresignedObjectStorage,
+    post_mint::configure_post_mint_hooks,
+    postgresql::{
+        get_connection, PostgresAuditRepository, PostgresBatchAnalyticsRepository,
+        PostgresBatchSizeRepository, PostgresBreakglassRepository, PostgresChallengeRepository,
+        PostgresCheckResultRepository, PostgresDataRepository, PostgresFunnelEventRepository,
+        PostgresHealthCheck, PostgresIssueRecordRepository, PostgresMaintenanceRepository,
+        PostgresPoolWarmUp, PostgresPostMintExecutionRepository, PostgresQueueManager,
+        PostgresReportRepository, PostgresReverseQueueManager, PostgresStatsRepository,
+        PostgresTransferProofRepository, PostgresWalletLinkRepository, PostgresWebhookRepository,
+    },
+    redis_queue::{get_redis_connection, RedisQueueManager},
+    report::{HmacReportSigner, WebhookReportPublisher},
+    signature::configure_signed_hash_validator,
+    starknet::{
+        CalldataTemplates, OnChainStartknetManager, OnChainTransferVerifier,
+        StarkAttestationSigner, TokenIdFormats,
+    },
+    webhook::HttpWebhookSender,
+};
+use crate::domain::{
+    analytics::{BatchAnalytics, BatchAnalyticsRepository},
+    attestation::AttestationSigner,
+    audit::AuditRepository,
+    backpressure::QueueBackpressure,
+    batch_size::{AdaptiveBatchPolicy, BatchSizeRepository, BatchSizeTuner},
+    breakglass::{BreakglassRepository, Operator},
+    bridge::{
+        JunoContractQuerier, QueueManager, SignedHashValidator, StarknetManager,
+        TransactionRepository,
+    },
+    challenge::{ChallengeRepository, ChallengeService},
+    check_cache::{CheckResultCache, CheckResultRepository},
+    clock::{Clock, SystemClock},
+    consume_queue::MintRetryPolicy,
+    fee_strategy::{fee_strategy_for_network, FeeMultipliers},
+    funnel::{FunnelAnalytics, FunnelEventRepository},
+    health::HealthCheck,
+    issue_tracker::{IssueRecordRepository, IssueReporter, IssueTracker},
+    maintenance::{DatabaseMaintenance, MaintenanceRepository},
+    metrics::Metrics,
+    migration_watch::MigrationWatch,
+    post_mint::{PostMintExecutionRepository, PostMintHooks},
+    project_registry::ProjectRegistry,
+    queue_broker::{BrokerQueueManager, QueueBroker},
+    queue_migration::MigratingQueueManager,
+    reconciliation::TransactionReconciler,
+    report::{ReportPublisher, ReportRepository, ReportSigner},
+    reverse_bridge::{JunoTxBroadcaster, ReverseQueueManager, StarknetTransferVerifier},
+    save_customer_data::DataRepository,
+    stats::{PublicStatsCache, StatsRepository},
+    storage::ObjectStorage,
+    transaction_cache::CachedTransactionRepository,
+    transfer_proof::TransferProofRepository,
+    wallet_link::WalletLinkRepository,
+    warm_up::WarmUp,
+    webhook::{WebhookNotifier, WebhookRepository, WebhookRetryPolicy, WebhookSender},
+};
 use clap::Parser;
-use starknet::{core::types::FieldElement, providers::SequencerGatewayProvider};
-use std::sync::Arc;
+use log::{error, info, warn};
+use reqwest::Url;
+use starknet::{
+    core::{types::FieldElement, utils::cairo_short_string_to_felt},
+    providers::SequencerGatewayProvider,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::signal::unix::{signal, SignalKind};
+use tokio_util::sync::CancellationToken;
 
 #[derive(Parser, Debug, Clone)]
 pub struct Args {
     /// Blockchain REST endpoint
     #[arg(long, env = "JUNO_LCD")]
     pub juno_lcd: String,
+    /// Maximum number of transaction pages fetched per contract from the Juno LCD
+    #[arg(long, env = "JUNO_LCD_MAX_PAGES", default_value_t = 20)]
+    pub juno_lcd_max_pages: u32,
+    /// Largest Juno LCD transaction page read, in bytes, bigger pages fail the token checks
+    #[arg(long, env = "JUNO_LCD_MAX_RESPONSE_BYTES", default_value_t = 8 * 1024 * 1024)]
+    pub juno_lcd_max_response_bytes: usize,
+    /// Attempts of a Juno LCD call that cannot reach it before giving up
+    #[arg(long, env = "JUNO_LCD_MAX_ATTEMPTS", default_value_t = 5)]
+    pub juno_lcd_max_attempts: u32,
+    /// Milliseconds before retrying an unreachable Juno LCD, doubled on each attempt and jittered
+    #[arg(long, env = "JUNO_LCD_RETRY_BASE_DELAY_MS", default_value_t = 2000)]
+    pub juno_lcd_retry_base_delay_ms: u64,
+    /// Upper bound in milliseconds of the delay between two Juno LCD attempts
+    #[arg(long, env = "JUNO_LCD_RETRY_MAX_DELAY_MS", default_value_t = 30_000)]
+    pub juno_lcd_retry_max_delay_ms: u64,
+    /// Seconds a Juno LCD call has to answer before it is tried again
+    #[arg(long, env = "JUNO_LCD_TIMEOUT_SECS", default_value_t = 120)]
+    pub juno_lcd_timeout_secs: u64,
+    /// Comma separated signature schemes accepted on customer requests (keplr-adr36,
+    /// evm-eip191, test-permissive), several schemes are tried in order
+    #[arg(
+        long,
+        env = "SIGNATURE_VALIDATORS",
+        value_delimiter = ',',
+        default_value = "keplr-adr36"
+    )]
+    pub signature_validators: Vec<String>,
+    /// Seconds contract transactions fetched from the Juno LCD are reused, 0 disables caching
+    #[arg(long, env = "JUNO_TRANSACTIONS_CACHE_TTL", default_value_t = 10)]
+    pub juno_transactions_cache_ttl: u64,
+    /// Asks the CW721 contract who holds each token before queueing it, on top of its transfers
+    #[arg(long, env = "VERIFY_JUNO_OWNERSHIP")]
+    pub verify_juno_ownership: bool,
+    /// Seconds a bridge request may spend checking tokens against Juno and Starknet
+    #[arg(long, env = "BRIDGE_REQUEST_BUDGET", default_value_t = 25)]
+    pub bridge_request_budget: u64,
+    /// Metrics backend, either none, prometheus (scraped on /metrics) or statsd
+    #[arg(long, env = "METRICS_BACKEND", default_value = "none")]
+    pub metrics_backend: String,
+    /// StatsD / DogStatsD agent metrics are pushed to
+    #[arg(long, env = "STATSD_ADDRESS", default_value = "127.0.0.1:8125")]
+    pub statsd_address: String,
+    /// Prefix of every metric name
+    #[arg(long, env = "METRICS_PREFIX", default_value = "bridge")]
+    pub metrics_prefix: String,
+    /// Seconds the worker keeps finishing its in-flight batch once asked to shut down
+    #[arg(long, env = "WORKER_SHUTDOWN_GRACE_PERIOD", default_value_t = 25)]
+    pub worker_shutdown_grace_period: u64,
+    /// Days batch analytics (fees, latency, rejections) are kept before being pruned
+    #[arg(long, env = "ANALYTICS_RETENTION_DAYS", default_value_t = 90)]
+    pub analytics_retention_days: u64,
+    /// Share of frontend sessions whose funnel events are kept, from 0 to 1
+    #[arg(long, env = "ANALYTICS_EVENTS_SAMPLE_RATE", default_value_t = 1.0)]
+    pub analytics_events_sample_rate: f64,
+    /// Days frontend funnel events are kept before being pruned
+    #[arg(long, env = "ANALYTICS_EVENTS_RETENTION_DAYS", default_value_t = 30)]
+    pub analytics_events_retention_days: u64,
+    /// Seconds between two database maintenance runs of the worker, reporting table
+    /// sizes and pruning expired rows
+    #[arg(long, env = "DATABASE_MAINTENANCE_INTERVAL", default_value_t = 300)]
+    pub database_maintenance_interval: u64,
+    /// Share of dead rows from which a table is worth a vacuum hint, from 0 to 1
+    #[arg(long, env = "DEAD_ROW_HINT_RATIO", default_value_t = 0.2)]
+    pub dead_row_hint_ratio: f64,
+    /// Deletes expired signature challenges and token locks during database maintenance
+    #[arg(long, env = "PRUNE_EXPIRED_ROWS")]
+    pub prune_expired_rows: bool,
+    /// Juno signing service broadcasting admin wallet transactions, the worker only
+    /// transfers tokens bridged back from Starknet when it is set
+    #[arg(long, env = "JUNO_SIGNER_URL")]
+    pub juno_signer_url: Option<String>,
+    /// Tokens transferred back on Juno per worker loop
+    #[arg(long, env = "REVERSE_BATCH_SIZE", default_value_t = 20)]
+    pub reverse_batch_size: usize,
+    /// Seconds after which items left processing without a mint transaction, e.g. by a
+    /// crashed worker, are put back to pending by the worker. 0 disables the sweep
+    #[arg(long, env = "STALE_PROCESSING_AGE", default_value_t = 900)]
+    pub stale_processing_age: u64,
+    /// Minted items whose transaction is checked again per worker loop, so items of
+    /// transactions reverted after being accepted are minted again. 0 disables it
+    #[arg(long, env = "RECONCILIATION_BATCH_SIZE", default_value_t = 100)]
+    pub reconciliation_batch_size: u32,
+    /// Requests a client may send per rate limit window, 0 disables rate limiting
+    #[arg(long, env = "RATE_LIMIT_REQUESTS", default_value_t = 0)]
+    pub rate_limit_requests: u32,
+    /// Seconds of a rate limit window
+    #[arg(long, env = "RATE_LIMIT_WINDOW", default_value_t = 60)]
+    pub rate_limit_window: u64,
     /// Database url to connect to
     #[arg(long, env = "DATABASE_URL")]
     pub database_url: String,
-    /// Juno admin wallet address
+    /// Schema migrations run on connection : run, baseline (record a schema applied by hand) or off
+    #[arg(long, env = "DATABASE_MIGRATIONS", default_value = "run")]
+    pub database_migrations: String,
+    /// Database connections the api opens before taking requests
+    #[arg(long, env = "DATABASE_WARM_CONNECTIONS", default_value_t = 4)]
+    pub database_warm_connections: usize,
+    /// Seconds the api waits for database, Juno LCD and Starknet gateway connections to
+    /// be warmed up before taking requests, 0 disables the warm-up
+    #[arg(long, env = "WARM_UP_TIMEOUT", default_value_t = 10)]
+    pub warm_up_timeout: u64,
+    /// Milliseconds each dependency is given to answer the `/ready` probe, keep it below
+    /// the probe timeout
+    #[arg(long, env = "HEALTH_CHECK_TIMEOUT_MS", default_value_t = 800)]
+    pub health_check_timeout_ms: u64,
+    /// Where the migration queue is kept : database (the one of DATABASE_URL) or redis
+    #[arg(long, env = "QUEUE_BACKEND", default_value = "database")]
+    pub queue_backend: String,
+    /// Backend being moved away from, during the move workers drain it while new items go to QUEUE_BACKEND
+    #[arg(long, env = "QUEUE_LEGACY_BACKEND")]
+    pub queue_legacy_backend: Option<String>,
+    /// Redis url of the migration queue when QUEUE_BACKEND is redis
+    #[arg(long, env = "REDIS_URL")]
+    pub redis_url: Option<String>,
+    /// Prefix of the keys of the redis migration queue
+    #[arg(long, env = "REDIS_QUEUE_PREFIX", default_value = "bridge:queue")]
+    pub redis_queue_prefix: String,
+    /// Broker pushing enqueued items to workers : nats. Workers only poll the queue without it
+    #[arg(long, env = "QUEUE_BROKER")]
+    pub queue_broker: Option<String>,
+    /// Nats url when QUEUE_BROKER is nats
+    #[arg(long, env = "NATS_URL")]
+    pub nats_url: Option<String>,
+    /// Nats subject enqueued items are published on
+    #[arg(long, env = "NATS_QUEUE_SUBJECT", default_value = "bridge.queue")]
+    pub nats_queue_subject: String,
+    /// Seconds between two polls of the queue, also how late items missed by the broker are picked up
+    #[arg(long, env = "QUEUE_POLL_INTERVAL", default_value_t = 60)]
+    pub queue_poll_interval: u64,
+    /// Pending items over which bridge requests are refused until the worker catches up, unlimited without it
+    #[arg(long, env = "MAX_PENDING_QUEUE_DEPTH")]
+    pub max_pending_queue_depth: Option<i64>,
+    /// Milliseconds between two reads of the queue while a status request waits for a change
+    #[arg(long, env = "STATUS_WAIT_POLL_INTERVAL_MS", default_value_t = 1000)]
+    pub status_wait_poll_interval_ms: u64,
+    /// Longest a status request waits for a change, in seconds, whatever `wait` asks for
+    #[arg(long, env = "STATUS_MAX_WAIT", default_value_t = 60)]
+    pub status_max_wait: u64,
+    /// Juno admin wallet address, used by projects not defining their own
     #[arg(long, env = "JUNO_ADMIN_ADDRESS")]
     pub juno_admin_address: String,
     /// Starknet admin wallet address
@@ -21,65 +227,1083 @@ pub struct Args {
     /// Starknet admin wallet private key
     #[arg(long, env = "STARKNET_ADMIN_PRIVATE_KEY")]
     pub starknet_admin_private_key: String,
-    /// Starknet network id
+    /// Starknet network id : mainnet, testnet-1, devnet-1, or any name along with STARKNET_GATEWAY_URL and STARKNET_CHAIN_ID
     #[arg(long, env = "STARKNET_NETWORK_ID")]
     pub starknet_network_id: String,
+    /// Sequencer base url (without /gateway), overrides the one of STARKNET_NETWORK_ID
+    #[arg(long, env = "STARKNET_GATEWAY_URL")]
+    pub starknet_gateway_url: Option<String>,
+    /// Chain id as a short string (SN_SEPOLIA) or a hex felt, overrides the one of STARKNET_NETWORK_ID
+    #[arg(long, env = "STARKNET_CHAIN_ID")]
+    pub starknet_chain_id: Option<String>,
     /// Starknet network id
     #[arg(long, env = "FRONTEND_URI")]
     pub frontend_uri: String,
     /// Queue batch size
     #[arg(long, env = "BATCH_SIZE")]
     pub batch_size: u8,
+    /// Tunes batch size per project from recent outcomes, starting from BATCH_SIZE
+    #[arg(long, env = "ADAPTIVE_BATCH_SIZE")]
+    pub adaptive_batch_size: bool,
+    /// Smallest batch size adaptive mode shrinks to
+    #[arg(long, env = "ADAPTIVE_BATCH_MIN", default_value_t = 1)]
+    pub adaptive_batch_min: u8,
+    /// Largest batch size adaptive mode grows to
+    #[arg(long, env = "ADAPTIVE_BATCH_MAX", default_value_t = 50)]
+    pub adaptive_batch_max: u8,
+    /// Items added to the batch size after enough consecutive accepted batches
+    #[arg(long, env = "ADAPTIVE_BATCH_STEP", default_value_t = 5)]
+    pub adaptive_batch_step: u8,
+    /// Consecutive accepted batches before the batch size grows
+    #[arg(long, env = "ADAPTIVE_BATCH_INCREASE_AFTER", default_value_t = 3)]
+    pub adaptive_batch_increase_after: u32,
+    /// Proxy used for outbound plain HTTP requests
+    #[arg(long, env = "OUTBOUND_HTTP_PROXY")]
+    pub http_proxy: Option<String>,
+    /// Proxy used for outbound HTTPS requests
+    #[arg(long, env = "OUTBOUND_HTTPS_PROXY")]
+    pub https_proxy: Option<String>,
+    /// Comma separated list of PEM encoded CA certificates to trust
+    #[arg(long, env = "EXTRA_CA_CERTIFICATES", value_delimiter = ',')]
+    pub extra_ca_certificates: Vec<String>,
+    /// Seconds public stats are cached for
+    #[arg(long, env = "PUBLIC_STATS_TTL", default_value_t = 300)]
+    pub public_stats_ttl: u64,
+    /// Comma separated list of operators allowed on admin endpoints, formatted as name:api_key
+    #[arg(long, env = "OPERATOR_API_KEYS", value_delimiter = ',')]
+    pub operator_api_keys: Vec<String>,
+    /// Secret of the HS256 tokens accepted as `Authorization: Bearer` on admin endpoints,
+    /// operators can only use api keys without it
+    #[arg(long, env = "ADMIN_JWT_SECRET")]
+    pub admin_jwt_secret: Option<String>,
+    /// Seconds a second operator has to confirm a breakglass mint
+    #[arg(long, env = "BREAKGLASS_CONFIRMATION_WINDOW", default_value_t = 900)]
+    pub breakglass_confirmation_window: u64,
+    /// Identifier recorded on queue status transitions, defaults to the host name
+    #[arg(long, env = "WORKER_ID")]
+    pub worker_id: Option<String>,
+    /// Comma separated list of bridged projects, formatted as
+    /// juno_contract=starknet_contract[:juno_admin_address[:mint_selector]]
+    #[arg(long, env = "PROJECTS", value_delimiter = ',')]
+    pub projects: Vec<String>,
+    /// Comma separated list of per project migration windows, formatted as
+    /// juno_contract=opens_at/closes_at, either bound may be left empty
+    /// (e.g. `juno1abc=2023-06-01/2023-09-01T12:00:00Z`)
+    #[arg(long, env = "PROJECT_MIGRATION_WINDOWS", value_delimiter = ',')]
+    pub project_migration_windows: Vec<String>,
+    /// Comma separated list of per project migration policies, formatted as
+    /// juno_contract=policy, policy being transfer_to_admin (default) or burn
+    #[arg(long, env = "PROJECT_MIGRATION_POLICIES", value_delimiter = ',')]
+    pub project_migration_policies: Vec<String>,
+    /// Comma separated list of per project transfer windows, formatted as
+    /// juno_contract=starts/ends, bounds being block heights, UTC dates or RFC 3339 UTC
+    /// timestamps, either one may be left empty
+    #[arg(long, env = "PROJECT_TRANSFER_WINDOWS", value_delimiter = ',')]
+    pub project_transfer_windows: Vec<String>,
+    /// Comma separated list of per project mint calldata layouts, formatted as
+    /// project_address=template (e.g. `0x123=token_id:felt to value:u256`)
+    #[arg(long, env = "CALLDATA_TEMPLATES", value_delimiter = ',')]
+    pub calldata_templates: Vec<String>,
+    /// Factor applied to fee estimates of mint transactions on the starknet network, to
+    /// get their max fee
+    #[arg(long, env = "FEE_ESTIMATE_MULTIPLIER", default_value_t = 10.0)]
+    pub fee_estimate_multiplier: f64,
+    /// Comma separated list of per project fee estimate multipliers, formatted as
+    /// project_address=multiplier
+    #[arg(long, env = "FEE_ESTIMATE_MULTIPLIERS", value_delimiter = ',')]
+    pub fee_estimate_multipliers: Vec<String>,
+    /// Comma separated list of per network fee strategies, formatted as
+    /// network=multiplier, network=ewma[:weight] or network=cap:max_fee_in_wei.
+    /// Networks without one pay the estimate times the multiplier
+    #[arg(long, env = "FEE_STRATEGIES", value_delimiter = ',')]
+    pub fee_strategies: Vec<String>,
+    /// Comma separated list of per project token id formats, formatted as
+    /// project_address=decimal|hex|string, token ids are decimal by default
+    #[arg(long, env = "TOKEN_ID_FORMATS", value_delimiter = ',')]
+    pub token_id_formats: Vec<String>,
+    /// Mint calls sent in a single transaction, larger batches are split in several
+    /// transactions sent one after the other
+    #[arg(long, env = "MAX_CALLS_PER_TRANSACTION", default_value_t = 50)]
+    pub max_calls_per_transaction: usize,
+    /// Comma separated list of per project post mint hooks, formatted as
+    /// project_address=webhook:url or project_address=invoke:entrypoint template
+    #[arg(long, env = "POST_MINT_HOOKS", value_delimiter = ',')]
+    pub post_mint_hooks: Vec<String>,
+    /// Attempts after which a failing post mint hook is given up
+    #[arg(long, env = "POST_MINT_MAX_ATTEMPTS", default_value_t = 5)]
+    pub post_mint_max_attempts: i32,
+    /// Mint attempts after which a queue item is moved to the dead letter queue
+    #[arg(long, env = "MINT_MAX_ATTEMPTS", default_value_t = 5)]
+    pub mint_max_attempts: i32,
+    /// Seconds before the first retry of a failed mint, doubled on each attempt
+    #[arg(long, env = "MINT_RETRY_BASE_DELAY", default_value_t = 60)]
+    pub mint_retry_base_delay: u64,
+    /// Upper bound in seconds of the delay between two mint attempts
+    #[arg(long, env = "MINT_RETRY_MAX_DELAY", default_value_t = 3600)]
+    pub mint_retry_max_delay: u64,
+    /// Secret used to sign webhook payloads, registered webhooks are not called without it
+    #[arg(long, env = "WEBHOOK_SIGNING_SECRET")]
+    pub webhook_signing_secret: Option<String>,
+    /// Attempts after which a webhook delivery is given up
+    #[arg(long, env = "WEBHOOK_MAX_ATTEMPTS", default_value_t = 8)]
+    pub webhook_max_attempts: i32,
+    /// Seconds before the first retry of a failed webhook delivery, doubled on each attempt
+    #[arg(long, env = "WEBHOOK_RETRY_BASE_DELAY", default_value_t = 30)]
+    pub webhook_retry_base_delay: u64,
+    /// Upper bound in seconds of the delay between two webhook delivery attempts
+    #[arg(long, env = "WEBHOOK_RETRY_MAX_DELAY", default_value_t = 3600)]
+    pub webhook_retry_max_delay: u64,
+    /// Seconds a signature challenge nonce can be used after being issued
+    #[arg(long, env = "SIGNATURE_CHALLENGE_TTL", default_value_t = 300)]
+    pub signature_challenge_ttl: u64,
+    /// Accepts bridge requests without challenge nonce, only for legacy frontends that do not sign challenges yet
+    #[arg(long, env = "ALLOW_MISSING_SIGNATURE_CHALLENGE")]
+    pub allow_missing_signature_challenge: bool,
+    /// Seconds check results of a bridge request are reused when the customer retries, 0 disables it
+    #[arg(long, env = "BRIDGE_CHECK_CACHE_TTL", default_value_t = 180)]
+    pub bridge_check_cache_ttl: u64,
+    /// Accepts bridge requests without sign doc, only for legacy frontends that do not sign the bridge document yet
+    #[arg(long, env = "ALLOW_MISSING_SIGN_DOC")]
+    pub allow_missing_sign_doc: bool,
+    /// Refuses payloads without X-Bridge-Schema-Version header, set once every frontend sends it
+    #[arg(long, env = "REQUIRE_SCHEMA_VERSION")]
+    pub require_schema_version: bool,
+    /// Secret used to sign daily reports, reports are not generated without it
+    #[arg(long, env = "REPORT_SIGNING_KEY")]
+    pub report_signing_key: Option<String>,
+    /// Endpoint generated daily reports are posted to
+    #[arg(long, env = "REPORT_WEBHOOK_URL")]
+    pub report_webhook_url: Option<String>,
+    /// Secret used to sign customer proof bundles, bundles are served unsigned without it
+    #[arg(long, env = "PROOF_BUNDLE_SIGNING_KEY")]
+    pub proof_bundle_signing_key: Option<String>,
+    /// Stark private key signing mint attestations, the status endpoint serves none without it
+    #[arg(long, env = "ATTESTATION_PRIVATE_KEY")]
+    pub attestation_private_key: Option<String>,
+    /// Tracker issues are opened in for dead letters, either github or linear. None are opened without it
+    #[arg(long, env = "ISSUE_TRACKER")]
+    pub issue_tracker: Option<String>,
+    /// GitHub repository as owner/repo, or Linear team id
+    #[arg(long, env = "ISSUE_TRACKER_PROJECT", default_value = "")]
+    pub issue_tracker_project: String,
+    /// GitHub token or Linear api key
+    #[arg(long, env = "ISSUE_TRACKER_TOKEN", default_value = "")]
+    pub issue_tracker_token: String,
+    /// Custom tracker api url (e.g. GitHub Enterprise), provider default otherwise
+    #[arg(long, env = "ISSUE_TRACKER_API_URL")]
+    pub issue_tracker_api_url: Option<String>,
+    /// Object storage provider used for exports and reports, either s3 or gcs
+    #[arg(long, env = "OBJECT_STORAGE_PROVIDER")]
+    pub object_storage_provider: Option<String>,
+    /// Custom object storage endpoint (e.g. minio), provider default otherwise
+    #[arg(long, env = "OBJECT_STORAGE_ENDPOINT")]
+    pub object_storage_endpoint: Option<String>,
+    /// Object storage bucket
+    #[arg(long, env = "OBJECT_STORAGE_BUCKET", default_value = "")]
+    pub object_storage_bucket: String,
+    /// Object storage region, ignored by gcs
+    #[arg(long, env = "OBJECT_STORAGE_REGION", default_value = "us-east-1")]
+    pub object_storage_region: String,
+    /// Object storage (HMAC) access key
+    #[arg(long, env = "OBJECT_STORAGE_ACCESS_KEY", default_value = "")]
+    pub object_storage_access_key: String,
+    /// Object storage (HMAC) secret key
+    #[arg(long, env = "OBJECT_STORAGE_SECRET_KEY", default_value = "")]
+    pub object_storage_secret_key: String,
+    /// Seconds presigned download urls stay valid
+    #[arg(long, env = "OBJECT_STORAGE_URL_TTL", default_value_t = 3600)]
+    pub object_storage_url_ttl: u64,
+    /// Probability (0 to 1) a Juno LCD call fails with a server error
+    #[cfg(feature = "chaos")]
+    #[arg(long, env = "CHAOS_JUNO_ERROR_RATE", default_value_t = 0.0)]
+    pub chaos_juno_error_rate: f64,
+    /// Probability (0 to 1) a Starknet mint is rejected before being sent
+    #[cfg(feature = "chaos")]
+    #[arg(long, env = "CHAOS_STARKNET_REJECTION_RATE", default_value_t = 0.0)]
+    pub chaos_starknet_rejection_rate: f64,
+    /// Probability (0 to 1) a queue database call times out
+    #[cfg(feature = "chaos")]
+    #[arg(long, env = "CHAOS_DATABASE_TIMEOUT_RATE", default_value_t = 0.0)]
+    pub chaos_database_timeout_rate: f64,
+    /// Milliseconds an injected database timeout hangs before failing
+    #[cfg(feature = "chaos")]
+    #[arg(long, env = "CHAOS_DATABASE_TIMEOUT", default_value_t = 5000)]
+    pub chaos_database_timeout: u64,
 }
 
 pub struct Config {
     pub juno_lcd: String,
+    pub juno_lcd_max_pages: u32,
+    pub juno_retry_policy: JunoRetryPolicy,
     pub database_url: String,
     pub data_repository: Arc<dyn DataRepository>,
     pub queue_manager: Arc<dyn QueueManager>,
+    pub queue_broker: Option<Arc<dyn QueueBroker>>,
+    pub queue_poll_interval: Duration,
+    pub queue_backpressure: Option<QueueBackpressure>,
+    pub migration_watch: MigrationWatch,
+    pub transaction_repository: Arc<dyn TransactionRepository>,
+    pub juno_contract_querier: Arc<dyn JunoContractQuerier>,
+    /// Whether bridge requests ask the contract who holds each token
+    pub verify_juno_ownership: bool,
+    pub transfer_proof_repository: Arc<dyn TransferProofRepository>,
+    pub signed_hash_validator: Arc<dyn SignedHashValidator>,
+    pub starknet_manager: Arc<dyn StarknetManager>,
+    pub stats_repository: Arc<dyn StatsRepository>,
+    pub public_stats_cache: Arc<PublicStatsCache>,
+    pub wallet_link_repository: Arc<dyn WalletLinkRepository>,
+    pub challenges: Arc<ChallengeService>,
+    pub require_sign_doc: bool,
+    pub require_schema_version: bool,
+    pub check_cache: Arc<CheckResultCache>,
+    pub audit_repository: Arc<dyn AuditRepository>,
+    pub breakglass_repository: Arc<dyn BreakglassRepository>,
+    pub operators: Vec<Operator>,
+    pub admin_jwt_verifier: Option<Arc<HmacJwtVerifier>>,
+    pub breakglass_confirmation_window: Duration,
+    pub bridge_request_budget: Duration,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
     pub starknet_provider: Arc<SequencerGatewayProvider>,
-    pub juno_admin_address: String,
+    pub project_registry: Arc<ProjectRegistry>,
     pub starknet_admin_address: String,
     pub starknet_private_key: String,
     pub frontend_uri: String,
     pub chain_id: FieldElement,
+    pub calldata_templates: Arc<CalldataTemplates>,
+    pub post_mint_hooks: Arc<PostMintHooks>,
+    pub post_mint_repository: Arc<dyn PostMintExecutionRepository>,
+    pub post_mint_max_attempts: i32,
+    pub mint_retry_policy: MintRetryPolicy,
+    pub webhook_repository: Arc<dyn WebhookRepository>,
+    pub webhook_notifier: Arc<WebhookNotifier>,
+    pub webhook_sender: Option<Arc<dyn WebhookSender>>,
+    pub webhook_retry_policy: WebhookRetryPolicy,
+    pub batch_analytics: Arc<BatchAnalytics>,
+    pub funnel_analytics: Arc<FunnelAnalytics>,
+    /// Table size reports and expired rows pruning, Postgres only
+    pub database_maintenance: Option<Arc<DatabaseMaintenance>>,
+    pub report_repository: Arc<dyn ReportRepository>,
+    pub report_signer: Option<Arc<dyn ReportSigner>>,
+    pub report_publisher: Option<Arc<dyn ReportPublisher>>,
+    pub proof_bundle_signer: Option<Arc<dyn ReportSigner>>,
+    pub attestation_signer: Option<Arc<dyn AttestationSigner>>,
+    pub issue_reporter: Option<Arc<IssueReporter>>,
+    pub object_storage: Option<Arc<dyn ObjectStorage>>,
+    pub object_storage_url_ttl: Duration,
+    pub http_client: HttpClientConfig,
+    pub metrics: Arc<dyn Metrics>,
+    pub clock: Arc<dyn Clock>,
+    /// Cancelled on shutdown, long running operations stop waiting when it fires.
+    pub shutdown: CancellationToken,
+    pub worker_shutdown_grace_period: Duration,
+    pub reverse_queue_manager: Arc<dyn ReverseQueueManager>,
+    pub starknet_transfer_verifier: Arc<dyn StarknetTransferVerifier>,
+    pub juno_tx_broadcaster: Option<Arc<dyn JunoTxBroadcaster>>,
+    pub reverse_batch_size: usize,
+    pub transaction_reconciler: Option<Arc<TransactionReconciler>>,
+    // None when the worker does not sweep items stalled in processing
+    pub stale_processing_age: Option<Duration>,
+    /// Dependencies warmed up before the api binds its port
+    pub warm_up_targets: Vec<Arc<dyn WarmUp>>,
+    pub warm_up_timeout: Duration,
+    /// Dependencies checked by the readiness probe
+    pub health_checks: Vec<Arc<dyn HealthCheck>>,
+    pub health_check_timeout: Duration,
 }
 
 pub async fn configure_application(args: &Args) -> Config {
-    let connection = match get_connection(&args.database_url).await {
-        Ok(c) => Arc::new(c),
-        Err(e) => panic!("Failed to connect to database error : {}", e),
+    let http_client = match HttpClientConfig::new(
+        args.http_proxy.as_deref(),
+        args.https_proxy.as_deref(),
+        &args.extra_ca_certificates,
+    ) {
+        Ok(c) => c,
+        Err(e) => panic!("Failed to configure outbound http client : {:#?}", e),
     };
 
-    let provider = match args.starknet_network_id.as_str() {
-        "mainnet" => Arc::new(SequencerGatewayProvider::starknet_alpha_mainnet()),
-        "testnet-1" => Arc::new(SequencerGatewayProvider::starknet_alpha_goerli()),
-        "devnet-1" => Arc::new(SequencerGatewayProvider::starknet_nile_localhost()),
-        _ => panic!("Starknet provider is not allowed"),
-    };
-    let chain_id = match args.starknet_network_id.as_str() {
-        "mainnet" => starknet::core::chain_id::MAINNET,
-        "testnet-1" => starknet::core::chain_id::TESTNET,
-        "devnet-1" => starknet::core::chain_id::TESTNET2,
-        _ => panic!("Starknet chain_id is not allowed"),
+    let (gateway_base_url, chain_id) = configure_starknet_network(args);
+    let starknet_client = match http_client.client_builder().build() {
+        Ok(c) => c,
+        Err(e) => panic!("Failed to build starknet http client : {:#?}", e),
     };
+    let provider = Arc::new(SequencerGatewayProvider::new_with_client(
+        Url::parse(format!("{}/gateway", gateway_base_url).as_str()).unwrap(),
+        Url::parse(format!("{}/feeder_gateway", gateway_base_url).as_str()).unwrap(),
+        starknet_client,
+    ));
 
-    let data_repository = Arc::new(PostgresDataRepository::new(connection.clone()));
-    let queue_manager = Arc::new(PostgresQueueManager::new(
-        connection.clone(),
-        args.batch_size,
+    let calldata_templates = match CalldataTemplates::parse(&args.calldata_templates) {
+        Ok(t) => Arc::new(t),
+        Err(e) => panic!("Failed to parse calldata templates : {:#?}", e),
+    };
+    let fee_multipliers =
+        match FeeMultipliers::parse(args.fee_estimate_multiplier, &args.fee_estimate_multipliers) {
+            Ok(m) => m,
+            Err(e) => panic!("Failed to parse fee estimate multipliers : {:#?}", e),
+        };
+    let fee_strategy = match fee_strategy_for_network(
+        &args.starknet_network_id,
+        &args.fee_strategies,
+        fee_multipliers,
+    ) {
+        Ok(s) => s,
+        Err(e) => panic!("Failed to parse fee strategies : {:#?}", e),
+    };
+    if !(0.0..=1.0).contains(&args.analytics_events_sample_rate) {
+        panic!(
+            "Invalid ANALYTICS_EVENTS_SAMPLE_RATE {}, it has to be between 0 and 1",
+            args.analytics_events_sample_rate
+        );
+    }
+    if !(0.0 < args.dead_row_hint_ratio && args.dead_row_hint_ratio <= 1.0) {
+        panic!(
+            "Invalid DEAD_ROW_HINT_RATIO {}, it has to be above 0 and at most 1",
+            args.dead_row_hint_ratio
+        );
+    }
+    let token_id_formats = match TokenIdFormats::parse(&args.token_id_formats) {
+        Ok(f) => Arc::new(f),
+        Err(e) => panic!("Failed to parse token id formats : {:#?}", e),
+    };
+    let default_juno_admin = match args.juno_admin_address.parse() {
+        Ok(a) => a,
+        Err(e) => panic!("Invalid juno admin address : {:#?}", e),
+    };
+    let project_registry = match ProjectRegistry::parse(&args.projects, &default_juno_admin)
+        .and_then(|r| r.with_migration_windows(&args.project_migration_windows))
+        .and_then(|r| r.with_migration_policies(&args.project_migration_policies))
+        .and_then(|r| r.with_transfer_windows(&args.project_transfer_windows))
+    {
+        Ok(r) => Arc::new(r),
+        Err(e) => panic!("Failed to parse projects : {:#?}", e),
+    };
+    if project_registry.projects().is_empty() {
+        warn!("No project configured, bridge requests will be refused");
+    }
+    let starknet_manager = Arc::new(OnChainStartknetManager::new(
+        provider.clone(),
+        &args.starknet_admin_address,
+        &args.starknet_admin_private_key,
+        chain_id,
+        calldata_templates.clone(),
+        fee_strategy,
+        token_id_formats.clone(),
+        project_registry.clone(),
+        args.max_calls_per_transaction,
+    ));
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let juno_retry_policy = JunoRetryPolicy {
+        max_attempts: args.juno_lcd_max_attempts,
+        base_delay: Duration::from_millis(args.juno_lcd_retry_base_delay_ms),
+        max_delay: Duration::from_millis(args.juno_lcd_retry_max_delay_ms),
+        timeout: Duration::from_secs(args.juno_lcd_timeout_secs),
+    };
+    let juno_client = match http_client.client_builder().build() {
+        Ok(c) => c,
+        Err(e) => panic!("Failed to build juno http client : {:#?}", e),
+    };
+    let juno_lcd = Arc::new(JunoLcd::new(
+        &args.juno_lcd,
+        args.juno_lcd_max_pages,
+        args.juno_lcd_max_response_bytes,
+        juno_client.clone(),
+        juno_retry_policy.clone(),
     ));
+    let metrics = match configure_metrics(
+        &args.metrics_backend,
+        &args.statsd_address,
+        &args.metrics_prefix,
+    ) {
+        Ok(m) => m,
+        Err(e) => panic!("Failed to configure metrics : {:#?}", e),
+    };
+    let signed_hash_validator = match configure_signed_hash_validator(&args.signature_validators) {
+        Ok(v) => v,
+        Err(e) => panic!("Failed to configure signature validator : {:#?}", e),
+    };
+    let transaction_repository: Arc<dyn TransactionRepository> =
+        match args.juno_transactions_cache_ttl {
+            0 => juno_lcd.clone(),
+            ttl => Arc::new(CachedTransactionRepository::new(
+                juno_lcd.clone(),
+                Duration::from_secs(ttl),
+                clock.clone(),
+            )),
+        };
+
+    let post_mint_hooks = match configure_post_mint_hooks(
+        &args.post_mint_hooks,
+        &http_client,
+        provider.clone(),
+        &args.starknet_admin_address,
+        &args.starknet_admin_private_key,
+        chain_id,
+        &token_id_formats,
+    ) {
+        Ok(h) => Arc::new(h),
+        Err(e) => panic!("Failed to configure post mint hooks : {:#?}", e),
+    };
+
+    let worker_id = match &args.worker_id {
+        Some(id) => id.to_string(),
+        None => std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".into()),
+    };
+    if args.adaptive_batch_size
+        && (0 == args.adaptive_batch_min || args.adaptive_batch_min > args.adaptive_batch_max)
+    {
+        panic!("Adaptive batch size bounds have to satisfy 0 < ADAPTIVE_BATCH_MIN <= ADAPTIVE_BATCH_MAX");
+    }
+    // Adaptive mode trims batches per project, so enough items have to be fetched for the largest one
+    let queue_batch_size = match args.adaptive_batch_size {
+        true => args.batch_size.max(args.adaptive_batch_max),
+        false => args.batch_size,
+    };
+    let stores = configure_stores(args, queue_batch_size, &worker_id).await;
+    let data_repository = stores.data_repository.clone();
+    let mut warm_up_targets: Vec<Arc<dyn WarmUp>> = Vec::new();
+    if 0 < args.warm_up_timeout {
+        warm_up_targets.extend(stores.database_warm_up.clone());
+        warm_up_targets.push(juno_lcd.clone());
+        warm_up_targets.push(starknet_manager.clone());
+    }
+    let mut health_checks: Vec<Arc<dyn HealthCheck>> = Vec::new();
+    health_checks.extend(stores.database_health_check.clone());
+    health_checks.push(juno_lcd.clone());
+    health_checks.push(starknet_manager.clone());
+    let queue_manager = configure_queue_manager(
+        &args.queue_backend,
+        args,
+        &stores,
+        queue_batch_size,
+        &worker_id,
+    )
+    .await;
+    let queue_manager: Arc<dyn QueueManager> = match &args.queue_legacy_backend {
+        None => queue_manager,
+        Some(legacy) if *legacy == args.queue_backend => {
+            panic!("QUEUE_LEGACY_BACKEND has to differ from QUEUE_BACKEND")
+        }
+        Some(legacy) => {
+            warn!(
+                "Migrating queue from {} to {}, legacy items are drained first",
+                legacy, &args.queue_backend
+            );
+            Arc::new(MigratingQueueManager::new(
+                queue_manager,
+                configure_queue_manager(legacy, args, &stores, queue_batch_size, &worker_id).await,
+                queue_batch_size as usize,
+            ))
+        }
+    };
+    let queue_broker = configure_queue_broker(args).await;
+    let queue_manager: Arc<dyn QueueManager> = match &queue_broker {
+        Some(broker) => Arc::new(BrokerQueueManager::new(queue_manager, broker.clone())),
+        None => queue_manager,
+    };
+    let stats_repository = stores.stats_repository.clone();
+    let wallet_link_repository = stores.wallet_link_repository.clone();
+    let breakglass_repository = stores.breakglass_repository.clone();
+    let post_mint_repository = stores.post_mint_repository.clone();
+
+    let webhook_repository = stores.webhook_repository.clone();
+    let webhook_sender: Option<Arc<dyn WebhookSender>> = match &args.webhook_signing_secret {
+        Some(secret) => match http_client.client_builder().build() {
+            Ok(client) => Some(Arc::new(HttpWebhookSender::new(secret, client))),
+            Err(e) => panic!("Failed to build webhook http client : {:#?}", e),
+        },
+        None => None,
+    };
+    let juno_tx_broadcaster: Option<Arc<dyn JunoTxBroadcaster>> = args
+        .juno_signer_url
+        .as_deref()
+        .map(|url| Arc::new(SignerJunoTxBroadcaster::new(url, juno_client.clone())) as _);
+
+    let report_repository = stores.report_repository.clone();
+    let report_signer: Option<Arc<dyn ReportSigner>> = match &args.report_signing_key {
+        Some(key) => Some(Arc::new(HmacReportSigner::new(key))),
+        None => None,
+    };
+    let proof_bundle_signer: Option<Arc<dyn ReportSigner>> = match &args.proof_bundle_signing_key {
+        Some(key) => Some(Arc::new(HmacReportSigner::new(key))),
+        None => None,
+    };
+    let attestation_signer: Option<Arc<dyn AttestationSigner>> =
+        args.attestation_private_key.as_deref().map(|key| {
+            Arc::new(StarkAttestationSigner::new(
+                key,
+                chain_id,
+                token_id_formats.clone(),
+            )) as _
+        });
+    let report_publisher: Option<Arc<dyn ReportPublisher>> = match &args.report_webhook_url {
+        Some(url) => match http_client.client_builder().build() {
+            Ok(client) => Some(Arc::new(WebhookReportPublisher::new(url, client))),
+            Err(e) => panic!("Failed to build report webhook http client : {:#?}", e),
+        },
+        None => None,
+    };
+
+    let object_storage: Option<Arc<dyn ObjectStorage>> =
+        match args.object_storage_provider.as_deref() {
+            None => None,
+            Some(provider) => {
+                let client = match http_client.client_builder().build() {
+                    Ok(c) => c,
+                    Err(e) => panic!("Failed to build object storage http client : {:#?}", e),
+                };
+                let storage = match provider {
+                    "s3" => PresignedObjectStorage::s3(
+                        args.object_storage_endpoint.as_deref(),
+                        &args.object_storage_bucket,
+                        &args.object_storage_region,
+                        &args.object_storage_access_key,
+                        &args.object_storage_secret_key,
+                        client,
+                    ),
+                    "gcs" => PresignedObjectStorage::gcs(
+                        args.object_storage_endpoint.as_deref(),
+                        &args.object_storage_bucket,
+                        &args.object_storage_access_key,
+                        &args.object_storage_secret_key,
+                        client,
+                    ),
+                    _ => panic!("Object storage provider is not allowed"),
+                };
+                match storage {
+                    Ok(s) => Some(Arc::new(s)),
+                    Err(e) => panic!("Failed to configure object storage : {:#?}", e),
+                }
+            }
+        };
+
+    let issue_reporter: Option<Arc<IssueReporter>> = match args.issue_tracker.as_deref() {
+        None => None,
+        Some(provider) => {
+            let client = match http_client.client_builder().build() {
+                Ok(c) => c,
+                Err(e) => panic!("Failed to build issue tracker http client : {:#?}", e),
+            };
+            let tracker: Arc<dyn IssueTracker> = match provider {
+                "github" => Arc::new(GithubIssueTracker::new(
+                    args.issue_tracker_api_url
+                        .as_deref()
+                        .unwrap_or(GITHUB_API_URL),
+                    &args.issue_tracker_project,
+                    &args.issue_tracker_token,
+                    client,
+                )),
+                "linear" => Arc::new(LinearIssueTracker::new(
+                    args.issue_tracker_api_url
+                        .as_deref()
+                        .unwrap_or(LINEAR_API_URL),
+                    &args.issue_tracker_project,
+                    &args.issue_tracker_token,
+                    client,
+                )),
+                _ => panic!("Issue tracker is not allowed"),
+            };
+            Some(Arc::new(IssueReporter::new(
+                tracker,
+                stores.issue_record_repository.clone(),
+                clock.clone(),
+            )))
+        }
+    };
+
+    #[cfg(feature = "chaos")]
+    let (transaction_repository, starknet_manager, queue_manager) = inject_failures(
+        args,
+        transaction_repository,
+        starknet_manager,
+        queue_manager,
+    );
+
+    let mut operators = Vec::new();
+    for operator in &args.operator_api_keys {
+        match Operator::parse(operator) {
+            Some(o) => operators.push(o),
+            None => panic!("Operator api keys have to be formatted as name:api_key"),
+        };
+    }
 
     Config {
         juno_lcd: String::from(&args.juno_lcd),
+        juno_lcd_max_pages: args.juno_lcd_max_pages,
+        juno_retry_policy,
         database_url: String::from(&args.database_url),
         data_repository: data_repository.clone(),
         queue_manager: queue_manager.clone(),
-        juno_admin_address: String::from(&args.juno_admin_address),
+        queue_broker,
+        queue_poll_interval: Duration::from_secs(args.queue_poll_interval),
+        queue_backpressure: args.max_pending_queue_depth.map(|max_pending| {
+            QueueBackpressure::new(
+                max_pending,
+                args.batch_size,
+                Duration::from_secs(args.queue_poll_interval),
+            )
+        }),
+        migration_watch: MigrationWatch::new(
+            Duration::from_millis(args.status_wait_poll_interval_ms),
+            Duration::from_secs(args.status_max_wait),
+        ),
+        transaction_repository,
+        juno_contract_querier: juno_lcd.clone(),
+        verify_juno_ownership: args.verify_juno_ownership,
+        transfer_proof_repository: stores.transfer_proof_repository.clone(),
+        signed_hash_validator,
+        starknet_manager,
+        stats_repository: stats_repository.clone(),
+        public_stats_cache: Arc::new(PublicStatsCache::new(
+            Duration::from_secs(args.public_stats_ttl),
+            clock.clone(),
+        )),
+        wallet_link_repository: wallet_link_repository.clone(),
+        challenges: Arc::new(ChallengeService::new(
+            stores.challenge_repository.clone(),
+            clock.clone(),
+            Duration::from_secs(args.signature_challenge_ttl),
+            !args.allow_missing_signature_challenge,
+        )),
+        require_sign_doc: !args.allow_missing_sign_doc,
+        require_schema_version: args.require_schema_version,
+        check_cache: Arc::new(CheckResultCache::new(
+            stores.check_result_repository.clone(),
+            clock.clone(),
+            Duration::from_secs(args.bridge_check_cache_ttl),
+        )),
+        audit_repository: stores.audit_repository.clone(),
+        breakglass_repository: breakglass_repository.clone(),
+        operators,
+        admin_jwt_verifier: args
+            .admin_jwt_secret
+            .as_deref()
+            .map(|secret| Arc::new(HmacJwtVerifier::new(secret))),
+        breakglass_confirmation_window: Duration::from_secs(args.breakglass_confirmation_window),
+        bridge_request_budget: Duration::from_secs(args.bridge_request_budget),
+        rate_limiter: match args.rate_limit_requests {
+            0 => None,
+            limit => Some(Arc::new(RateLimiter::new(
+                limit,
+                Duration::from_secs(args.rate_limit_window),
+                clock.clone(),
+            ))),
+        },
+        project_registry,
         starknet_admin_address: String::from(&args.starknet_admin_address),
         starknet_private_key: String::from(&args.starknet_admin_private_key),
         starknet_provider: provider.clone(),
         frontend_uri: String::from(&args.frontend_uri),
         chain_id,
+        calldata_templates,
+        post_mint_hooks,
+        post_mint_repository,
+        post_mint_max_attempts: args.post_mint_max_attempts,
+        mint_retry_policy: MintRetryPolicy {
+            max_attempts: args.mint_max_attempts,
+            base_delay: Duration::from_secs(args.mint_retry_base_delay),
+            max_delay: Duration::from_secs(args.mint_retry_max_delay),
+        },
+        webhook_notifier: Arc::new(WebhookNotifier::new(
+            webhook_repository.clone(),
+            clock.clone(),
+        )),
+        webhook_repository,
+        webhook_sender,
+        webhook_retry_policy: WebhookRetryPolicy {
+            max_attempts: args.webhook_max_attempts,
+            base_delay: Duration::from_secs(args.webhook_retry_base_delay),
+            max_delay: Duration::from_secs(args.webhook_retry_max_delay),
+        },
+        batch_analytics: Arc::new(BatchAnalytics::with_tuner(
+            stores.batch_analytics_repository.clone(),
+            clock.clone(),
+            Duration::from_secs(args.analytics_retention_days * 86_400),
+            match args.adaptive_batch_size {
+                true => Some(Arc::new(BatchSizeTuner::new(
+                    stores.batch_size_repository.clone(),
+                    clock.clone(),
+                    AdaptiveBatchPolicy {
+                        initial: args.batch_size as u32,
+                        min: args.adaptive_batch_min as u32,
+                        max: args.adaptive_batch_max as u32,
+                        step: args.adaptive_batch_step as u32,
+                        increase_after: args.adaptive_batch_increase_after,
+                    },
+                ))),
+                false => None,
+            },
+        )),
+        funnel_analytics: Arc::new(FunnelAnalytics::new(
+            stores.funnel_event_repository.clone(),
+            clock.clone(),
+            args.analytics_events_sample_rate,
+            Duration::from_secs(args.analytics_events_retention_days * 86_400),
+        )),
+        database_maintenance: stores.maintenance_repository.clone().map(|repository| {
+            Arc::new(DatabaseMaintenance::new(
+                repository,
+                clock.clone(),
+                Duration::from_secs(args.database_maintenance_interval),
+                args.dead_row_hint_ratio,
+                args.prune_expired_rows,
+            ))
+        }),
+        report_repository,
+        report_signer,
+        report_publisher,
+        proof_bundle_signer,
+        attestation_signer,
+        issue_reporter,
+        object_storage,
+        object_storage_url_ttl: Duration::from_secs(args.object_storage_url_ttl),
+        http_client,
+        metrics,
+        clock,
+        shutdown: CancellationToken::new(),
+        worker_shutdown_grace_period: Duration::from_secs(args.worker_shutdown_grace_period),
+        reverse_queue_manager: stores.reverse_queue_manager.clone(),
+        starknet_transfer_verifier: Arc::new(OnChainTransferVerifier::new(
+            provider.clone(),
+            token_id_formats.clone(),
+        )),
+        juno_tx_broadcaster,
+        reverse_batch_size: args.reverse_batch_size,
+        transaction_reconciler: match args.reconciliation_batch_size {
+            0 => None,
+            size => Some(Arc::new(TransactionReconciler::new(i64::from(size)))),
+        },
+        stale_processing_age: match args.stale_processing_age {
+            0 => None,
+            age => Some(Duration::from_secs(age)),
+        },
+        warm_up_targets,
+        warm_up_timeout: Duration::from_secs(args.warm_up_timeout),
+        health_checks,
+        health_check_timeout: Duration::from_millis(args.health_check_timeout_ms),
+    }
+}
+
+/// Persistence adapters, picked from the scheme of `DATABASE_URL`.
+struct Stores {
+    data_repository: Arc<dyn DataRepository>,
+    queue_manager: Arc<dyn QueueManager>,
+    stats_repository: Arc<dyn StatsRepository>,
+    wallet_link_repository: Arc<dyn WalletLinkRepository>,
+    breakglass_repository: Arc<dyn BreakglassRepository>,
+    post_mint_repository: Arc<dyn PostMintExecutionRepository>,
+    webhook_repository: Arc<dyn WebhookRepository>,
+    report_repository: Arc<dyn ReportRepository>,
+    issue_record_repository: Arc<dyn IssueRecordRepository>,
+    transfer_proof_repository: Arc<dyn TransferProofRepository>,
+    challenge_repository: Arc<dyn ChallengeRepository>,
+    check_result_repository: Arc<dyn CheckResultRepository>,
+    audit_repository: Arc<dyn AuditRepository>,
+    batch_analytics_repository: Arc<dyn BatchAnalyticsRepository>,
+    funnel_event_repository: Arc<dyn FunnelEventRepository>,
+    batch_size_repository: Arc<dyn BatchSizeRepository>,
+    reverse_queue_manager: Arc<dyn ReverseQueueManager>,
+    database_warm_up: Option<Arc<dyn WarmUp>>,
+    database_health_check: Option<Arc<dyn HealthCheck>>,
+    maintenance_repository: Option<Arc<dyn MaintenanceRepository>>,
+}
+
+async fn configure_stores(args: &Args, queue_batch_size: u8, worker_id: &str) -> Stores {
+    if args.database_url.starts_with("sqlite:") {
+        return sqlite_stores(args, queue_batch_size, worker_id);
+    }
+
+    let migrations = match MigrationMode::parse(&args.database_migrations) {
+        Some(m) => m,
+        None => panic!(
+            "Unsupported database migrations mode {}",
+            &args.database_migrations
+        ),
+    };
+    let connection = match get_connection(&args.database_url, migrations).await {
+        Ok(c) => Arc::new(c),
+        Err(e) => panic!("Failed to connect to database error : {}", e),
+    };
+
+    Stores {
+        data_repository: Arc::new(PostgresDataRepository::new(connection.clone())),
+        queue_manager: Arc::new(PostgresQueueManager::new(
+            connection.clone(),
+            queue_batch_size,
+            worker_id,
+        )),
+        stats_repository: Arc::new(PostgresStatsRepository::new(connection.clone())),
+        wallet_link_repository: Arc::new(PostgresWalletLinkRepository::new(connection.clone())),
+        breakglass_repository: Arc::new(PostgresBreakglassRepository::new(connection.clone())),
+        post_mint_repository: Arc::new(PostgresPostMintExecutionRepository::new(
+            connection.clone(),
+        )),
+        webhook_repository: Arc::new(PostgresWebhookRepository::new(connection.clone())),
+        report_repository: Arc::new(PostgresReportRepository::new(connection.clone())),
+        issue_record_repository: Arc::new(PostgresIssueRecordRepository::new(connection.clone())),
+        transfer_proof_repository: Arc::new(PostgresTransferProofRepository::new(
+            connection.clone(),
+        )),
+        challenge_repository: Arc::new(PostgresChallengeRepository::new(connection.clone())),
+        check_result_repository: Arc::new(PostgresCheckResultRepository::new(connection.clone())),
+        audit_repository: Arc::new(PostgresAuditRepository::new(connection.clone())),
+        batch_analytics_repository: Arc::new(PostgresBatchAnalyticsRepository::new(
+            connection.clone(),
+        )),
+        funnel_event_repository: Arc::new(PostgresFunnelEventRepository::new(connection.clone())),
+        batch_size_repository: Arc::new(PostgresBatchSizeRepository::new(connection.clone())),
+        reverse_queue_manager: Arc::new(PostgresReverseQueueManager::new(connection.clone())),
+        database_warm_up: Some(Arc::new(PostgresPoolWarmUp::new(
+            connection.clone(),
+            args.database_warm_connections,
+        ))),
+        maintenance_repository: Some(Arc::new(PostgresMaintenanceRepository::new(
+            connection.clone(),
+        ))),
+        database_health_check: Some(Arc::new(PostgresHealthCheck::new(connection))),
+    }
+}
+
+async fn configure_queue_manager(
+    backend: &str,
+    args: &Args,
+    stores: &Stores,
+    queue_batch_size: u8,
+    worker_id: &str,
+) -> Arc<dyn QueueManager> {
+    match backend {
+        "database" => stores.queue_manager.clone(),
+        "redis" => {
+            let redis_url = match &args.redis_url {
+                Some(url) => url,
+                None => panic!("REDIS_URL is required with the redis queue backend"),
+            };
+            match get_redis_connection(redis_url).await {
+                Ok(connection) => Arc::new(RedisQueueManager::new(
+                    connection,
+                    &args.redis_queue_prefix,
+                    queue_batch_size,
+                    worker_id,
+                )),
+                Err(e) => panic!("Failed to connect to redis error : {}", e),
+            }
+        }
+        _ => panic!("Queue backend {} is not allowed", backend),
     }
 }
+
+/// Sequencer base url and chain id of the network, known networks can have either
+/// overridden while other ones need both.
+fn configure_starknet_network(args: &Args) -> (String, FieldElement) {
+    let (known_url, known_chain_id) = match args.starknet_network_id.as_str() {
+        "mainnet" => (
+            Some("https://alpha-mainnet.starknet.io"),
+            Some(starknet::core::chain_id::MAINNET),
+        ),
+        "testnet-1" => (
+            Some("https://alpha4.starknet.io"),
+            Some(starknet::core::chain_id::TESTNET),
+        ),
+        "devnet-1" => (
+            Some("http://127.0.0.1:5050"),
+            Some(starknet::core::chain_id::TESTNET2),
+        ),
+        _ => (None, None),
+    };
+
+    let gateway_url = match (&args.starknet_gateway_url, known_url) {
+        (Some(url), _) => match Url::parse(url) {
+            Ok(_) => url.trim_end_matches('/').to_string(),
+            Err(e) => panic!("Invalid STARKNET_GATEWAY_URL {} : {:#?}", url, e),
+        },
+        (None, Some(url)) => url.to_string(),
+        (None, None) => panic!(
+            "Starknet network {} is unknown, STARKNET_GATEWAY_URL is required",
+            &args.starknet_network_id
+        ),
+    };
+    let chain_id = match (&args.starknet_chain_id, known_chain_id) {
+        (Some(chain_id), _) => match parse_chain_id(chain_id) {
+            Some(c) => c,
+            None => panic!("Invalid STARKNET_CHAIN_ID {}", chain_id),
+        },
+        (None, Some(chain_id)) => chain_id,
+        (None, None) => panic!(
+            "Starknet network {} is unknown, STARKNET_CHAIN_ID is required",
+            &args.starknet_network_id
+        ),
+    };
+    info!(
+        "Using starknet network {} through {}",
+        &args.starknet_network_id, &gateway_url
+    );
+
+    (gateway_url, chain_id)
+}
+
+fn parse_chain_id(value: &str) -> Option<FieldElement> {
+    let value = value.trim();
+    match value.starts_with("0x") {
+        true => FieldElement::from_hex_be(value).ok(),
+        false => cairo_short_string_to_felt(value).ok(),
+    }
+}
+
+async fn configure_queue_broker(args: &Args) -> Option<Arc<dyn QueueBroker>> {
+    match args.queue_broker.as_deref() {
+        None => None,
+        Some("nats") => {
+            let nats_url = match &args.nats_url {
+                Some(url) => url,
+                None => panic!("NATS_URL is required with the nats queue broker"),
+            };
+            match NatsQueueBroker::connect(nats_url, &args.nats_queue_subject).await {
+                Ok(broker) => Some(Arc::new(broker)),
+                Err(e) => panic!("Failed to connect to nats error : {:#?}", e),
+            }
+        }
+        Some(broker) => panic!("Queue broker {} is not allowed", broker),
+    }
+}
+
+/// Customer data and the migration queue in a SQLite file, so the api and worker run
+/// without a Postgres instance. Other stores are kept in memory and lost on restart.
+#[cfg(feature = "sqlite")]
+fn sqlite_stores(args: &Args, queue_batch_size: u8, worker_id: &str) -> Stores {
+    use super::{
+        in_memory::{
+            InMemoryAuditRepository, InMemoryBatchAnalyticsRepository, InMemoryBatchSizeRepository,
+            InMemoryBreakglassRepository, InMemoryChallengeRepository,
+            InMemoryCheckResultRepository, InMemoryFunnelEventRepository,
+            InMemoryIssueRecordRepository, InMemoryPostMintExecutionRepository,
+            InMemoryReportRepository, InMemoryReverseQueueManager, InMemoryStatsRepository,
+            InMemoryTransferProofRepository, InMemoryWalletLinkRepository,
+            InMemoryWebhookRepository,
+        },
+        sqlite::{SqliteDataRepository, SqliteDatabase, SqliteQueueManager},
+    };
+
+    let database = match SqliteDatabase::open(&args.database_url) {
+        Ok(d) => d,
+        Err(e) => panic!("Failed to open sqlite database : {:#?}", e),
+    };
+    warn!("Using a SQLite database, it is meant for local development only");
+
+    Stores {
+        data_repository: Arc::new(SqliteDataRepository::new(database.clone())),
+        queue_manager: Arc::new(SqliteQueueManager::new(
+            database,
+            queue_batch_size,
+            worker_id,
+        )),
+        stats_repository: Arc::new(InMemoryStatsRepository::default()),
+        wallet_link_repository: Arc::new(InMemoryWalletLinkRepository::new()),
+        breakglass_repository: Arc::new(InMemoryBreakglassRepository::new()),
+        post_mint_repository: Arc::new(InMemoryPostMintExecutionRepository::new()),
+        webhook_repository: Arc::new(InMemoryWebhookRepository::new()),
+        report_repository: Arc::new(InMemoryReportRepository::new()),
+        issue_record_repository: Arc::new(InMemoryIssueRecordRepository::new()),
+        transfer_proof_repository: Arc::new(InMemoryTransferProofRepository::new()),
+        challenge_repository: Arc::new(InMemoryChallengeRepository::new()),
+        check_result_repository: Arc::new(InMemoryCheckResultRepository::new()),
+        audit_repository: Arc::new(InMemoryAuditRepository::new()),
+        batch_analytics_repository: Arc::new(InMemoryBatchAnalyticsRepository::new()),
+        funnel_event_repository: Arc::new(InMemoryFunnelEventRepository::new()),
+        batch_size_repository: Arc::new(InMemoryBatchSizeRepository::new()),
+        reverse_queue_manager: Arc::new(InMemoryReverseQueueManager::new()),
+        // A local file has no connection to open, nor one that could die
+        database_warm_up: None,
+        database_health_check: None,
+        // Table stats come from the Postgres statistics collector
+        maintenance_repository: None,
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn sqlite_stores(_args: &Args, _queue_batch_size: u8, _worker_id: &str) -> Stores {
+    panic!("SQLite databases need a build with the sqlite feature, cargo run --features sqlite")
+}
+
+/// Wraps adapters with the failure injector when any failure rate is configured.
+#[cfg(feature = "chaos")]
+fn inject_failures(
+    args: &Args,
+    transaction_repository: Arc<dyn TransactionRepository>,
+    starknet_manager: Arc<dyn StarknetManager>,
+    queue_manager: Arc<dyn QueueManager>,
+) -> (
+    Arc<dyn TransactionRepository>,
+    Arc<dyn StarknetManager>,
+    Arc<dyn QueueManager>,
+) {
+    use super::chaos::{
+        ChaosQueueManager, ChaosStarknetManager, ChaosTransactionRepository, FailureInjector,
+        FailureRates,
+    };
+
+    let injector = match FailureInjector::new(
+        FailureRates {
+            juno_server_error: args.chaos_juno_error_rate,
+            starknet_rejection: args.chaos_starknet_rejection_rate,
+            database_timeout: args.chaos_database_timeout_rate,
+        },
+        Duration::from_millis(args.chaos_database_timeout),
+    ) {
+        Ok(i) => Arc::new(i),
+        Err(e) => panic!("Failed to configure failure injection : {:#?}", e),
+    };
+    if !injector.is_active() {
+        return (transaction_repository, starknet_manager, queue_manager);
+    }
+    warn!("CHAOS - failure injection is enabled {:#?}", injector);
+
+    (
+        Arc::new(ChaosTransactionRepository::new(
+            transaction_repository,
+            injector.clone(),
+        )),
+        Arc::new(ChaosStarknetManager::new(
+            starknet_manager,
+            injector.clone(),
+        )),
+        Arc::new(ChaosQueueManager::new(queue_manager, injector)),
+    )
+}
+
+/// Cancels `shutdown` once the process receives SIGINT or SIGTERM.
+/// Returns a token cancelled `grace_period` after `shutdown`, so in-flight work can
+/// finish updating statuses before being interrupted.
+pub fn cancel_after_grace_period(
+    shutdown: &CancellationToken,
+    grace_period: Duration,
+) -> CancellationToken {
+    let drain = CancellationToken::new();
+    let (shutdown, trigger) = (shutdown.clone(), drain.clone());
+    tokio::spawn(async move {
+        shutdown.cancelled().await;
+        info!(
+            "Finishing in flight work, interrupting it in {} seconds.",
+            grace_period.as_secs()
+        );
+        tokio::time::sleep(grace_period).await;
+        trigger.cancel();
+    });
+    drain
+}
+
+pub fn cancel_on_shutdown_signal(shutdown: CancellationToken) {
+    tokio::spawn(async move {
+        let mut terminate = match signal(SignalKind::terminate()) {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to listen for SIGTERM : {:#?}", e);
+                return;
+            }
+        };
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        };
+        info!("Shutdown requested, cancelling in flight operations.");
+        shutdown.cancel();
+    });
+}
@@ -0,0 +1,470 @@
+//! Failure injection wrapped around infrastructure adapters, only compiled with the
+//! `chaos` feature so production builds cannot enable it by mistake.
+
+use async_trait::async_trait;
+use log::warn;
+use std::{sync::Arc, time::Duration};
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::domain::{
+    bridge::{
+        MintError, MintStatusError, MintSubmission, QueueError, QueueEvent, QueueItem,
+        QueueItemTransition, QueueManager, QueueStatus, QueueUpdateError, StarknetManager,
+        Transaction, TransactionFetchError, TransactionOutcome, TransactionRepository,
+    },
+    ids::{JunoAddress, ProjectId, QueueItemId, StarknetAddress, TokenId},
+    pagination::{Page, PageRequest},
+    status_message::StatusNote,
+};
+
+#[derive(Debug)]
+pub enum FailureInjectorError {
+    InvalidRate(f64),
+}
+
+/// Probability, between 0 and 1, each call of an adapter fails.
+#[derive(Debug, Clone, Default)]
+pub struct FailureRates {
+    pub juno_server_error: f64,
+    pub starknet_rejection: f64,
+    pub database_timeout: f64,
+}
+
+#[derive(Debug)]
+pub struct FailureInjector {
+    rates: FailureRates,
+    // How long a call hangs before its injected database timeout
+    database_timeout: Duration,
+}
+
+impl FailureInjector {
+    pub fn new(
+        rates: FailureRates,
+        database_timeout: Duration,
+    ) -> Result<Self, FailureInjectorError> {
+        for rate in [
+            rates.juno_server_error,
+            rates.starknet_rejection,
+            rates.database_timeout,
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(FailureInjectorError::InvalidRate(rate));
+            }
+        }
+
+        Ok(Self {
+            rates,
+            database_timeout,
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.rates.juno_server_error > 0.0
+            || self.rates.starknet_rejection > 0.0
+            || self.rates.database_timeout > 0.0
+    }
+
+    pub fn juno_fails(&self) -> bool {
+        let fails = roll(self.rates.juno_server_error);
+        if fails {
+            warn!("CHAOS - injecting Juno server error");
+        }
+        fails
+    }
+
+    pub fn starknet_rejects(&self) -> bool {
+        let rejects = roll(self.rates.starknet_rejection);
+        if rejects {
+            warn!("CHAOS - injecting Starknet rejection");
+        }
+        rejects
+    }
+
+    /// Hangs for the configured timeout before telling the caller to fail.
+    pub async fn database_times_out(&self) -> bool {
+        if !roll(self.rates.database_timeout) {
+            return false;
+        }
+        warn!("CHAOS - injecting database timeout");
+        sleep(self.database_timeout).await;
+        true
+    }
+}
+
+// Random draws come from v4 uuids, good enough to spread failures without another dependency
+fn roll(rate: f64) -> bool {
+    if rate <= 0.0 {
+        return false;
+    }
+    let draw = (Uuid::new_v4().as_u128() >> 64) as u64;
+    (draw as f64 / u64::MAX as f64) < rate
+}
+
+pub struct ChaosTransactionRepository {
+    inner: Arc<dyn TransactionRepository>,
+    injector: Arc<FailureInjector>,
+}
+
+impl ChaosTransactionRepository {
+    pub fn new(inner: Arc<dyn TransactionRepository>, injector: Arc<FailureInjector>) -> Self {
+        Self { inner, injector }
+    }
+}
+
+#[async_trait]
+impl TransactionRepository for ChaosTransactionRepository {
+    async fn get_contract_transactions(
+        &self,
+        project_id: &ProjectId,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Transaction>, TransactionFetchError> {
+        if self.injector.juno_fails() {
+            return Err(TransactionFetchError::JunoBlockchainServerError(500));
+        }
+        self.inner
+            .get_contract_transactions(project_id, cancel)
+            .await
+    }
+}
+
+/// Rejects mints before they are sent, so nothing reaches the chain when a failure
+/// is injected.
+pub struct ChaosStarknetManager {
+    inner: Arc<dyn StarknetManager>,
+    injector: Arc<FailureInjector>,
+}
+
+impl ChaosStarknetManager {
+    pub fn new(inner: Arc<dyn StarknetManager>, injector: Arc<FailureInjector>) -> Self {
+        Self { inner, injector }
+    }
+}
+
+#[async_trait]
+impl StarknetManager for ChaosStarknetManager {
+    async fn project_has_token(
+        &self,
+        project_id: &StarknetAddress,
+        token_id: &TokenId,
+    ) -> Result<bool, MintStatusError> {
+        self.inner.project_has_token(project_id, token_id).await
+    }
+
+    async fn project_is_paused(&self, project_id: &StarknetAddress) -> bool {
+        self.inner.project_is_paused(project_id).await
+    }
+
+    async fn get_transaction_status(&self, transaction_hash: &str) -> Option<String> {
+        self.inner.get_transaction_status(transaction_hash).await
+    }
+
+    async fn get_transaction_outcome(&self, transaction_hash: &str) -> TransactionOutcome {
+        self.inner.get_transaction_outcome(transaction_hash).await
+    }
+
+    async fn get_transaction_fee(&self, transaction_hash: &str) -> Option<String> {
+        self.inner.get_transaction_fee(transaction_hash).await
+    }
+
+    async fn wait_for_transaction(
+        &self,
+        transaction_hash: &str,
+        cancel: &CancellationToken,
+    ) -> TransactionOutcome {
+        self.inner
+            .wait_for_transaction(transaction_hash, cancel)
+            .await
+    }
+
+    fn max_calls_per_transaction(&self) -> usize {
+        self.inner.max_calls_per_transaction()
+    }
+
+    async fn mint_project_token(
+        &self,
+        project_id: &StarknetAddress,
+        tokens: &[TokenId],
+        starknet_account_addr: &StarknetAddress,
+    ) -> Result<String, MintError> {
+        if self.injector.starknet_rejects() {
+            return Err(MintError::Failure);
+        }
+        self.inner
+            .mint_project_token(project_id, tokens, starknet_account_addr)
+            .await
+    }
+
+    async fn batch_mint_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        queue_items: Vec<QueueItem>,
+    ) -> Result<MintSubmission, MintError> {
+        if self.injector.starknet_rejects() {
+            return Err(MintError::Failure);
+        }
+        self.inner.batch_mint_tokens(project_id, queue_items).await
+    }
+}
+
+pub struct ChaosQueueManager {
+    inner: Arc<dyn QueueManager>,
+    injector: Arc<FailureInjector>,
+}
+
+impl ChaosQueueManager {
+    pub fn new(inner: Arc<dyn QueueManager>, injector: Arc<FailureInjector>) -> Self {
+        Self { inner, injector }
+    }
+}
+
+#[async_trait]
+impl QueueManager for ChaosQueueManager {
+    async fn enqueue(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToEnqueue);
+        }
+        self.inner
+            .enqueue(
+                keplr_wallet_pubkey,
+                starknet_wallet_pubkey,
+                project_id,
+                token_ids,
+                request_id,
+            )
+            .await
+    }
+
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.get_batch(excluded_projects).await
+    }
+
+    async fn get_customer_migration_state(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
+    ) -> Vec<QueueItem> {
+        if self.injector.database_times_out().await {
+            return Vec::new();
+        }
+        self.inner
+            .get_customer_migration_state(keplr_wallet_pubkey, project_id)
+            .await
+    }
+
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.get_wallet_migrations(keplr_wallet_pubkey).await
+    }
+
+    async fn update_queue_items_status(
+        &self,
+        ids: &[QueueItemId],
+        transaction_hash: String,
+        status: QueueStatus,
+    ) -> Result<(), QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner
+            .update_queue_items_status(ids, transaction_hash, status)
+            .await
+    }
+
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner.claim_queue_items(ids).await
+    }
+
+    async fn get_queue_item_history(
+        &self,
+        id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.get_queue_item_history(id).await
+    }
+
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner.defer_queue_items(ids, note).await
+    }
+
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.get_processing_items().await
+    }
+
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.get_recoverable_items(stale_before).await
+    }
+
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner
+            .requeue_stale_processing_items(before, note)
+            .await
+    }
+
+    async fn count_pending_items(&self) -> Result<i64, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.count_pending_items().await
+    }
+
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.get_queue_item(id).await
+    }
+
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner.cancel_queue_items(ids, note).await
+    }
+
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner.schedule_retry(ids, note, delay).await
+    }
+
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner.dead_letter_queue_items(ids, note).await
+    }
+
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner.requeue_dead_letters(ids, note).await
+    }
+
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.list_queue_items(status, page).await
+    }
+
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToGetBatch);
+        }
+        self.inner.list_queue_events(page).await
+    }
+
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+        self.inner
+            .record_mint_fees(ids, estimated_fee, actual_fee)
+            .await
+    }
+
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError> {
+        if self.injector.database_times_out().await {
+            return Err(QueueError::FailedToLockTokens);
+        }
+        self.inner
+            .lock_tokens(project_id, token_ids, holder, ttl)
+            .await
+    }
+
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError> {
+        self.inner
+            .unlock_tokens(project_id, token_ids, holder)
+            .await
+    }
+}
@@ -0,0 +1,406 @@
+use actix_web::{get, http, post, web, HttpRequest, HttpResponse, Responder};
+use base64::{engine::general_purpose, Engine};
+use log::{info, warn};
+use serde_derive::Deserialize;
+use std::time::Duration;
+
+use super::{csv, handlers::registered_webhook_response, response};
+use crate::{
+    domain::{
+        analytics::{BatchAnalyticsError, BatchAnalyticsQuery},
+        audit::AuditError,
+        breakglass::{authenticate_operator, Operator},
+        bridge::QueueStatus,
+        ids::{JunoAddress, QueueItemId},
+        maintenance::MaintenanceError,
+        pagination::{PageRequest, PaginationError},
+        queue_admin::{
+            handle_cancel_queue_item, handle_inspect_queue_item, handle_requeue_dead_letter,
+            handle_requeue_queue_item, handle_requeue_stale_processing_items, QueueAdminError,
+        },
+        webhook::{handle_register_operator_webhook, RegisterOperatorWebhookRequest},
+    },
+    infrastructure::app::Config,
+};
+
+pub const QUEUE_BROWSER_MAX_PAGE_SIZE: i64 = 100;
+const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// Operator calling an admin endpoint, identified by `X-Api-Key`, a bearer JWT when a
+/// secret is configured, or basic auth for the admin UI.
+pub fn authenticated_operator(req: &HttpRequest, config: &Config) -> Option<Operator> {
+    if let Some(api_key) = req.headers().get("X-Api-Key") {
+        return authenticate_operator(&config.operators, api_key.to_str().ok()?).cloned();
+    }
+
+    let authorization = req
+        .headers()
+        .get(http::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?;
+    if let Some(token) = authorization.strip_prefix("Bearer ") {
+        let verifier = config.admin_jwt_verifier.as_ref()?;
+        return match verifier.verify(token, config.clock.now_secs()) {
+            Ok(claims) => Some(Operator {
+                name: claims.sub,
+                api_key: String::new(),
+            }),
+            Err(e) => {
+                warn!("Rejected admin token : {:#?}", e);
+                None
+            }
+        };
+    }
+
+    // Browsers only speak basic auth, admin UI sends `name:api_key` that way
+    let credentials = general_purpose::STANDARD
+        .decode(authorization.strip_prefix("Basic ")?)
+        .ok()?;
+    let credentials = String::from_utf8(credentials).ok()?;
+    let (name, api_key) = credentials.split_once(':')?;
+    authenticate_operator(&config.operators, api_key)
+        .filter(|o| o.name == name)
+        .cloned()
+}
+
+pub fn unauthorized() -> HttpResponse {
+    response::error(
+        http::StatusCode::UNAUTHORIZED,
+        "unauthorized",
+        "A valid operator api key or token is required",
+    )
+}
+
+#[get("/queue/{id}")]
+pub async fn inspect_queue_item(
+    http_request: HttpRequest,
+    path: web::Path<QueueItemId>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let id = path.into_inner();
+    info!("GET - /admin/queue/{} - {}", &id, &operator.name);
+
+    match handle_inspect_queue_item(&id, data.queue_manager.clone()).await {
+        Ok(inspection) => response::ok(inspection),
+        Err(e) => queue_admin_error_response(e),
+    }
+}
+
+#[get("/queue/{id}/history")]
+pub async fn queue_item_history(
+    http_request: HttpRequest,
+    path: web::Path<QueueItemId>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let id = path.into_inner();
+    info!("GET - /admin/queue/{}/history - {}", &id, &operator.name);
+
+    match data.queue_manager.get_queue_item_history(&id).await {
+        Ok(history) if !history.is_empty() => response::ok(history),
+        _ => response::not_found("No history found for this queue item"),
+    }
+}
+
+pub fn queue_admin_error_response(error: QueueAdminError) -> HttpResponse {
+    match error {
+        QueueAdminError::NotFound => response::not_found("Queue item not found"),
+        QueueAdminError::InvalidStatus(status) => response::error(
+            http::StatusCode::CONFLICT,
+            "invalid_status",
+            &format!(
+                "Action not allowed on a queue item with status {:?}",
+                status
+            ),
+        ),
+        QueueAdminError::PersistenceIssue => {
+            response::internal_server_error("Error while updating queue item")
+        }
+    }
+}
+
+#[post("/queue/{id}/requeue")]
+pub async fn requeue_queue_item(
+    http_request: HttpRequest,
+    path: web::Path<QueueItemId>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let id = path.into_inner();
+    info!("POST - /admin/queue/{}/requeue - {}", &id, &operator.name);
+
+    match handle_requeue_queue_item(&id, &operator, data.queue_manager.clone()).await {
+        Ok(item) => response::ok(item),
+        Err(e) => queue_admin_error_response(e),
+    }
+}
+
+#[post("/queue/{id}/cancel")]
+pub async fn cancel_queue_item(
+    http_request: HttpRequest,
+    path: web::Path<QueueItemId>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let id = path.into_inner();
+    info!("POST - /admin/queue/{}/cancel - {}", &id, &operator.name);
+
+    match handle_cancel_queue_item(
+        &id,
+        &operator,
+        data.queue_manager.clone(),
+        &data.webhook_notifier,
+    )
+    .await
+    {
+        Ok(item) => response::ok(item),
+        Err(e) => queue_admin_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct StaleProcessingQuery {
+    // Seconds, `STALE_PROCESSING_AGE` when missing
+    pub max_age: Option<u64>,
+}
+
+/// Puts items stalled in processing without a mint transaction back to pending.
+#[post("/queue/requeue-stale")]
+pub async fn requeue_stale_processing_items(
+    http_request: HttpRequest,
+    query: web::Query<StaleProcessingQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("POST - /admin/queue/requeue-stale - {}", &operator.name);
+
+    let Some(max_age) = query
+        .max_age
+        .map(Duration::from_secs)
+        .or(data.stale_processing_age)
+    else {
+        return response::bad_request("max_age is required while STALE_PROCESSING_AGE is 0");
+    };
+    match handle_requeue_stale_processing_items(
+        &operator,
+        data.queue_manager.clone(),
+        data.clock.as_ref(),
+        max_age,
+    )
+    .await
+    {
+        Ok(items) => response::ok(items),
+        Err(e) => queue_admin_error_response(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    pub status: Option<QueueStatus>,
+}
+
+pub fn page_request(query: &PageQuery, max_limit: i64) -> Result<PageRequest, HttpResponse> {
+    PageRequest::new(query.cursor.as_deref(), query.limit, max_limit).map_err(|e| {
+        let message = match e {
+            PaginationError::InvalidCursor => "Invalid cursor".to_string(),
+            PaginationError::InvalidLimit => format!("Limit must be between 1 and {}", max_limit),
+        };
+        response::bad_request(&message)
+    })
+}
+
+#[get("/queue")]
+pub async fn queue_browser(
+    http_request: HttpRequest,
+    query: web::Query<PageQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("GET - /admin/queue - {}", &operator.name);
+
+    let page = match page_request(&query, QUEUE_BROWSER_MAX_PAGE_SIZE) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    match data
+        .queue_manager
+        .list_queue_items(query.status.clone(), &page)
+        .await
+    {
+        // Next page cursor travels in a header as CSV has no room for it
+        Ok(items) if csv::accepts_csv(&http_request) => {
+            let mut csv = csv::csv_records(&items.items);
+            if let Some(cursor) = &items.next_cursor {
+                if let Ok(value) = http::header::HeaderValue::from_str(cursor) {
+                    csv.headers_mut().insert(
+                        http::header::HeaderName::from_static(NEXT_CURSOR_HEADER),
+                        value,
+                    );
+                }
+            }
+            csv
+        }
+        Ok(items) => response::ok(items),
+        Err(_e) => response::internal_server_error("Failed to list queue items"),
+    }
+}
+
+#[get("/dead-letters")]
+pub async fn dead_letters(
+    http_request: HttpRequest,
+    query: web::Query<PageQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("GET - /admin/dead-letters - {}", &operator.name);
+
+    let page = match page_request(&query, QUEUE_BROWSER_MAX_PAGE_SIZE) {
+        Ok(p) => p,
+        Err(response) => return response,
+    };
+    match data
+        .queue_manager
+        .list_queue_items(Some(QueueStatus::DeadLetter), &page)
+        .await
+    {
+        Ok(items) => response::ok(items),
+        Err(_e) => response::internal_server_error("Failed to list dead letters"),
+    }
+}
+
+#[post("/dead-letters/{id}/requeue")]
+pub async fn requeue_dead_letter(
+    http_request: HttpRequest,
+    path: web::Path<QueueItemId>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let id = path.into_inner();
+    info!(
+        "POST - /admin/dead-letters/{}/requeue - {}",
+        &id, &operator.name
+    );
+
+    match handle_requeue_dead_letter(&id, &operator, data.queue_manager.clone()).await {
+        Ok(item) => response::ok(item),
+        Err(e) => queue_admin_error_response(e),
+    }
+}
+
+/// Batches sent by the worker, `from` and `to` are epoch milliseconds.
+#[get("/analytics/batches")]
+pub async fn batch_analytics(
+    http_request: HttpRequest,
+    query: web::Query<BatchAnalyticsQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("GET - /admin/analytics/batches - {}", &operator.name);
+
+    match data.batch_analytics.batches(&query).await {
+        Ok(batches) => response::ok(batches),
+        Err(BatchAnalyticsError::InvalidRange) => {
+            response::bad_request("Range start must be before its end")
+        }
+        Err(BatchAnalyticsError::PersistenceIssue) => {
+            response::internal_server_error("Failed to fetch batch analytics")
+        }
+    }
+}
+
+/// Sizes of the tables growing along the migration, with the vacuums and analyzes worth
+/// running.
+#[get("/maintenance")]
+pub async fn database_maintenance(
+    http_request: HttpRequest,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!("GET - /admin/maintenance - {}", &operator.name);
+
+    let Some(maintenance) = &data.database_maintenance else {
+        return response::error(
+            http::StatusCode::NOT_FOUND,
+            "maintenance_unavailable",
+            "Table stats are only available on Postgres databases",
+        );
+    };
+    match maintenance.overview().await {
+        Ok(overview) => response::ok(overview),
+        Err(MaintenanceError::PersistenceIssue) => {
+            response::internal_server_error("Failed to fetch table stats")
+        }
+    }
+}
+
+/// Every `/bridge` call of a wallet with the checks it got, most recent first.
+#[get("/bridge-requests/{keplr_wallet_pubkey}")]
+pub async fn bridge_requests(
+    http_request: HttpRequest,
+    path: web::Path<JunoAddress>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    let keplr_wallet_pubkey = path.into_inner();
+    info!(
+        "GET - /admin/bridge-requests/{} - {}",
+        &keplr_wallet_pubkey, &operator.name
+    );
+
+    match data
+        .audit_repository
+        .get_bridge_requests(&keplr_wallet_pubkey)
+        .await
+    {
+        Ok(audits) => response::ok(audits),
+        Err(AuditError::PersistenceIssue) => {
+            response::internal_server_error("Failed to fetch bridge requests")
+        }
+    }
+}
+
+/// Registers a callback notified about items of every wallet.
+#[post("/webhooks")]
+pub async fn register_operator_webhook(
+    http_request: HttpRequest,
+    request: web::Json<RegisterOperatorWebhookRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let Some(operator) = authenticated_operator(&http_request, &data) else {
+        return unauthorized();
+    };
+    info!(
+        "POST - /admin/webhooks - {} - {}",
+        &request.callback_url, &operator.name
+    );
+
+    registered_webhook_response(
+        handle_register_operator_webhook(&request, &operator, data.webhook_repository.clone())
+            .await,
+    )
+}
@@ -0,0 +1,146 @@
+use actix_web::{http::header, web::Bytes, HttpRequest, HttpResponse};
+use futures::stream;
+use serde::Serialize;
+use serde_json::Value;
+use std::convert::Infallible;
+
+use crate::domain::{
+    bridge::QueueItem,
+    stats::{StatsResponse, Table, TimeSerie},
+};
+
+const CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+
+/// Row layout of a type exported as CSV.
+pub trait CsvRecord {
+    fn csv_header() -> Vec<String>;
+    fn csv_record(&self) -> Vec<String>;
+}
+
+impl CsvRecord for QueueItem {
+    fn csv_header() -> Vec<String> {
+        [
+            "id",
+            "keplr_wallet_pubkey",
+            "starknet_wallet_pubkey",
+            "project_id",
+            "token_id",
+            "status",
+            "transaction_hash",
+            "note",
+            "attempts",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.id.map(|id| id.to_string()).unwrap_or_default(),
+            self.keplr_wallet_pubkey.to_string(),
+            self.starknet_wallet_pubkey.to_string(),
+            self.project_id.to_string(),
+            self.token_id.to_string(),
+            label(&self.status),
+            self.transaction_hash.clone().unwrap_or_default(),
+            self.note.clone().unwrap_or_default(),
+            self.attempts.to_string(),
+        ]
+    }
+}
+
+/// Clients asking for `text/csv` get spreadsheets, everyone else the JSON envelope.
+pub fn accepts_csv(req: &HttpRequest) -> bool {
+    req.headers()
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .map_or(false, |accept| {
+            accept
+                .split(',')
+                .any(|media| media.split(';').next().map(str::trim) == Some("text/csv"))
+        })
+}
+
+/// Streams rows one line at a time, after the header line.
+pub fn csv_response(header: Vec<String>, records: Vec<Vec<String>>) -> HttpResponse {
+    let lines = std::iter::once(header)
+        .chain(records)
+        .map(|record| Ok::<_, Infallible>(Bytes::from(csv_line(&record))));
+
+    HttpResponse::Ok()
+        .content_type(CSV_CONTENT_TYPE)
+        .streaming(stream::iter(lines))
+}
+
+pub fn csv_records<T: CsvRecord>(items: &[T]) -> HttpResponse {
+    csv_response(T::csv_header(), items.iter().map(T::csv_record).collect())
+}
+
+/// Time series are flattened to one row per datapoint, tables keep their columns.
+pub fn stats_csv(stats: &StatsResponse) -> HttpResponse {
+    match stats {
+        StatsResponse::TimeSeries(series) => csv_response(
+            ["target", "timestamp", "value"].map(String::from).to_vec(),
+            series.iter().flat_map(time_serie_records).collect(),
+        ),
+        StatsResponse::Tables(tables) => {
+            let header = tables.first().map_or_else(Vec::new, |t| {
+                t.columns.iter().map(|c| c.text.to_string()).collect()
+            });
+            csv_response(header, tables.iter().flat_map(table_records).collect())
+        }
+    }
+}
+
+fn time_serie_records(serie: &TimeSerie) -> Vec<Vec<String>> {
+    serie
+        .datapoints
+        .iter()
+        .map(|(value, timestamp)| {
+            vec![
+                serie.target.to_string(),
+                timestamp.to_string(),
+                value.to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn table_records(table: &Table) -> Vec<Vec<String>> {
+    table
+        .rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| match value {
+                    Value::String(s) => s.to_string(),
+                    Value::Null => String::new(),
+                    v => v.to_string(),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Serialized name of an enum variant, e.g. queue status
+fn label<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(s)) => s,
+        _ => String::new(),
+    }
+}
+
+fn csv_line(record: &[String]) -> String {
+    let fields: Vec<String> = record
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+
+    format!("{}\n", fields.join(","))
+}
@@ -0,0 +1,825 @@
+use actix_cors::Cors;
+use actix_web::{
+    delete, error::InternalError, get, http, post, web, HttpRequest, HttpResponse, Responder,
+};
+use log::{error, info};
+use serde_derive::Deserialize;
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::{
+    csv,
+    openapi::{
+        BridgeEnvelope, ChallengeEnvelope, CreatedEnvelope, EligibleTokensEnvelope, ErrorEnvelope,
+        MigrationStateEnvelope, ReadinessEnvelope, SaveCustomerDataEnvelope,
+        TransactionStatusEnvelope,
+    },
+    rate_limit, request_id, response, schema_version,
+};
+use crate::{
+    domain::{
+        attestation::attest_mint,
+        audit::BridgeRequestAudit,
+        bridge::{
+            handle_bridge_request, BridgeError, BridgeRequest, BridgeResponse, QueueItem,
+            QueueStatus, TokenCheckCode, TransactionStatusResponse,
+        },
+        calendar::format_timestamp,
+        challenge::ChallengeRequest,
+        eligible_tokens::handle_eligible_tokens,
+        error_catalog::CatalogedError,
+        funnel::FunnelEventRequest,
+        health::check_readiness,
+        ids::{JunoAddress, ProjectId, StarknetAddress, TokenId, TransactionHash},
+        log_context::annotate_log_context,
+        migration_state::{
+            sort_items, GroupedMigrationState, MigrationStateGrouping, MigrationStateSort,
+            StatusCounts,
+        },
+        proof_bundle::{handle_proof_bundle, ProofBundleError},
+        reverse_bridge::{handle_reverse_bridge_request, ReverseBridgeError, ReverseBridgeRequest},
+        save_customer_data::{handle_save_customer_data, SaveCustomerDataRequest},
+        status_message::{negotiate_language, render_status_message},
+        transfer_proof::ProvenQueueItem,
+        webhook::{
+            handle_delete_webhook, handle_list_webhooks, handle_register_webhook,
+            handle_test_webhook, RegisterWebhookRequest, SignedWebhookRequest, WebhookError,
+            WebhookSubscription,
+        },
+    },
+    infrastructure::app::Config,
+};
+
+/// Only the frontend may call the API from a browser.
+pub fn cors(frontend_uri: &str) -> Cors {
+    Cors::default()
+        .allowed_origin(frontend_uri)
+        .allowed_methods(vec!["POST"])
+        .allowed_headers(vec![
+            http::header::CONTENT_TYPE,
+            http::header::HeaderName::from_static(schema_version::X_BRIDGE_SCHEMA_VERSION),
+            http::header::HeaderName::from_static(request_id::X_REQUEST_ID),
+        ])
+        .expose_headers(vec![
+            http::header::HeaderName::from_static(schema_version::X_BRIDGE_SCHEMA_VERSION),
+            http::header::HeaderName::from_static(request_id::X_REQUEST_ID),
+            http::header::HeaderName::from_static(rate_limit::X_RATELIMIT_LIMIT),
+            http::header::HeaderName::from_static(rate_limit::X_RATELIMIT_REMAINING),
+            http::header::RETRY_AFTER,
+        ])
+}
+
+/// Answers malformed or invalid JSON bodies with the response envelope instead of
+/// actix plain text errors.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(|err, _req| {
+        let message = err.to_string();
+        InternalError::from_response(
+            err,
+            response::error(http::StatusCode::BAD_REQUEST, "invalid_payload", &message),
+        )
+        .into()
+    })
+}
+
+#[utoipa::path(
+    request_body = BridgeRequest,
+    responses(
+        (status = 200, description = "Every token passed checks and has been queued", body = BridgeEnvelope),
+        (status = 400, description = "Request or a token check failed, failed checks are in `error.details`", body = ErrorEnvelope),
+        (status = 403, description = "Project migration window is not open, the window is in `error.details`", body = ErrorEnvelope),
+        (status = 404, description = "Project is not bridged or a transaction was not found", body = ErrorEnvelope),
+        (status = 409, description = "Keplr wallet is linked to another starknet account", body = ErrorEnvelope),
+        (status = 429, description = "Queue is full, retry after the `Retry-After` seconds", body = ErrorEnvelope),
+        (status = 500, description = "Juno node or database failure", body = ErrorEnvelope),
+        (status = 503, description = "Checks did not complete in time, retry later", body = ErrorEnvelope),
+    )
+)]
+#[post("/bridge")]
+pub async fn bridge(req: web::Json<BridgeRequest>, data: web::Data<Config>) -> impl Responder {
+    annotate_log_context(
+        req.keplr_wallet_pubkey.as_str(),
+        Some(req.project_id.as_str()),
+    );
+    info!(
+        "POST - /bridge - {} - {:#?}",
+        &req.keplr_wallet_pubkey, &req.tokens_id
+    );
+
+    let bridge_response = match handle_bridge_request(
+        &req,
+        &data.project_registry,
+        data.clock.as_ref(),
+        &data.starknet_admin_address,
+        data.signed_hash_validator.clone(),
+        data.transaction_repository.clone(),
+        data.verify_juno_ownership
+            .then_some(data.juno_contract_querier.as_ref()),
+        data.transfer_proof_repository.clone(),
+        data.starknet_manager.clone(),
+        data.data_repository.clone(),
+        data.queue_manager.clone(),
+        data.wallet_link_repository.clone(),
+        &data.challenges,
+        data.require_sign_doc,
+        &data.check_cache,
+        data.queue_backpressure.as_ref(),
+        &data.shutdown,
+        data.bridge_request_budget,
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => {
+            let entry = e.catalog_entry();
+            let message = match &e {
+                BridgeError::JunoBlockChainServerError(status) => {
+                    entry.message.replace("{status}", &status.to_string())
+                }
+                BridgeError::QueueFull(retry_after) => entry
+                    .message
+                    .replace("{retry_after}", &retry_after.to_string()),
+                _ => entry.message.to_string(),
+            };
+            let status = response::catalog_status(&entry);
+            audit_bridge_request(&data, &req, None, entry.code, status).await;
+            let mut res = match &e {
+                BridgeError::MigrationWindowClosed(window) => response::error_with_details(
+                    status,
+                    entry.code,
+                    &message,
+                    &serde_json::json!({
+                        "opens_at": window.opens_at.map(format_timestamp),
+                        "closes_at": window.closes_at.map(format_timestamp),
+                    }),
+                ),
+                _ => response::error(status, entry.code, &message),
+            };
+            if let BridgeError::QueueFull(retry_after) = e {
+                res.headers_mut().insert(
+                    http::header::RETRY_AFTER,
+                    http::header::HeaderValue::from(retry_after),
+                );
+            }
+            return res;
+        }
+    };
+
+    // Tokens failing checks are reported in details, the others have been queued anyway
+    let mut failed_check = None;
+    for (_token, (_msg, err)) in bridge_response.checks.iter() {
+        match err {
+            None => break,
+            Some(s) => failed_check = TokenCheckCode::from_message(s).map(|c| c.catalog_entry()),
+        };
+    }
+    // Customer has to retry for tokens left unchecked, whatever happened to the others
+    let incomplete = TokenCheckCode::ChecksIncomplete.default_message();
+    if bridge_response
+        .checks
+        .values()
+        .any(|(_, err)| err.as_deref() == Some(incomplete))
+    {
+        failed_check = Some(TokenCheckCode::ChecksIncomplete.catalog_entry());
+    }
+
+    match failed_check {
+        None => {
+            audit_bridge_request(
+                &data,
+                &req,
+                Some(&bridge_response),
+                "ok",
+                http::StatusCode::OK,
+            )
+            .await;
+            response::ok(bridge_response)
+        }
+        Some(entry) => {
+            let status = response::catalog_status(&entry);
+            audit_bridge_request(&data, &req, Some(&bridge_response), entry.code, status).await;
+            response::error_with_details(status, entry.code, entry.message, &bridge_response)
+        }
+    }
+}
+
+/// Keeps what the customer got, audit failures never change the response.
+async fn audit_bridge_request(
+    data: &Config,
+    req: &BridgeRequest,
+    bridge_response: Option<&BridgeResponse>,
+    outcome: &str,
+    status: http::StatusCode,
+) {
+    let audit = BridgeRequestAudit::new(
+        req,
+        bridge_response,
+        outcome,
+        status.as_u16(),
+        data.clock.now_ms(),
+    );
+    if let Err(e) = data.audit_repository.save_bridge_request(&audit).await {
+        error!("Failed to audit bridge request {:#?}", e);
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("transaction_hash" = String, Path, description = "Starknet mint transaction hash"),
+    ),
+    responses(
+        (status = 200, description = "Starknet status of the transaction, `pending` while the gateway cannot be reached", body = TransactionStatusEnvelope),
+    )
+)]
+#[get("/bridge/tx/{transaction_hash}")]
+pub async fn get_transaction_status(
+    path: web::Path<TransactionHash>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let transaction_hash = path.into_inner();
+    let outcome = data
+        .starknet_manager
+        .get_transaction_outcome(transaction_hash.as_str())
+        .await;
+
+    response::ok(TransactionStatusResponse::new(transaction_hash, outcome))
+}
+
+#[utoipa::path(
+    params(("keplr_wallet_pubkey" = String, Query, description = "Wallet signing the next request")),
+    responses(
+        (status = 200, description = "One-time nonce to sign along with the next bridge request", body = ChallengeEnvelope),
+        (status = 500, description = "Database failure", body = ErrorEnvelope),
+    )
+)]
+#[get("/challenge")]
+pub async fn challenge(
+    query: web::Query<ChallengeRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    annotate_log_context(query.keplr_wallet_pubkey.as_str(), None);
+    info!("GET - /challenge - {}", &query.keplr_wallet_pubkey);
+
+    match data.challenges.issue(&query.keplr_wallet_pubkey).await {
+        Ok(challenge) => response::ok(challenge),
+        Err(_e) => response::internal_server_error("Error while issuing challenge"),
+    }
+}
+
+#[utoipa::path(
+    responses((status = 200, description = "Service is up", body = String, content_type = "text/plain"))
+)]
+#[get("/health")]
+pub async fn health() -> impl Responder {
+    info!("GET - /health");
+    ("I'm ok !", http::StatusCode::OK)
+}
+
+/// Readiness probe, `/health` only tells the process is alive.
+#[utoipa::path(
+    responses(
+        (status = 200, description = "Every dependency answers", body = ReadinessEnvelope),
+        (status = 503, description = "A dependency is down, `details` holds the status of each", body = ErrorEnvelope),
+    )
+)]
+#[get("/ready")]
+pub async fn ready(config: web::Data<Config>) -> impl Responder {
+    let readiness = check_readiness(&config.health_checks, config.health_check_timeout).await;
+    if !readiness.ready {
+        return response::error_with_details(
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            "not_ready",
+            "A dependency of the service is unavailable",
+            &readiness,
+        );
+    }
+
+    response::ok(readiness)
+}
+
+#[utoipa::path(
+    request_body = SaveCustomerDataRequest,
+    responses(
+        (status = 201, description = "Customer tokens saved, with how many were registered", body = SaveCustomerDataEnvelope),
+        (status = 400, description = "Invalid payload", body = ErrorEnvelope),
+        (status = 404, description = "Customer not found", body = ErrorEnvelope),
+        (status = 500, description = "Database failure", body = ErrorEnvelope),
+    )
+)]
+#[post("/customer/data")]
+pub async fn save_customer_tokens(
+    request: web::Json<SaveCustomerDataRequest>,
+    config: web::Data<Config>,
+) -> impl Responder {
+    annotate_log_context(
+        request.keplr_wallet_pubkey.as_str(),
+        Some(request.project_id.as_str()),
+    );
+    info!(
+        "POST - /customer/data - {} - {}",
+        &request.keplr_wallet_pubkey, &request.project_id
+    );
+
+    match handle_save_customer_data(&request, config.data_repository.clone()).await {
+        Ok(outcome) => {
+            info!(
+                "Saved customer tokens, {} added, {} duplicates, {} registered",
+                outcome.added, outcome.duplicates, outcome.total
+            );
+            response::with_status(http::StatusCode::CREATED, outcome)
+        }
+        Err(e) => {
+            let entry = e.catalog_entry();
+            if response::catalog_status(&entry).is_server_error() {
+                error!("Failed to save customer data : {}", entry.code);
+            }
+            response::catalog_error(&entry)
+        }
+    }
+}
+
+#[utoipa::path(
+    request_body = FunnelEventRequest,
+    responses(
+        (status = 202, description = "Event accepted, it is only kept for sampled sessions", body = CreatedEnvelope),
+        (status = 400, description = "Invalid payload", body = ErrorEnvelope),
+        (status = 500, description = "Database failure", body = ErrorEnvelope),
+    )
+)]
+#[post("/events")]
+pub async fn record_event(
+    request: web::Json<FunnelEventRequest>,
+    config: web::Data<Config>,
+) -> impl Responder {
+    info!("POST - /events - {}", request.event.as_str());
+
+    match config.funnel_analytics.record(&request).await {
+        Ok(_) => response::with_status(http::StatusCode::ACCEPTED, ()),
+        Err(e) => response::catalog_error(&e.catalog_entry()),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MigrationStateQuery {
+    pub wait: Option<u64>,
+    pub status: Option<QueueStatus>,
+    pub sort: Option<MigrationStateSort>,
+    pub group: Option<MigrationStateGrouping>,
+}
+
+#[utoipa::path(
+    params(
+        ("keplr_wallet_pubkey" = String, Path, description = "Customer keplr wallet"),
+        ("project_id" = String, Path, description = "Starknet project contract"),
+        ("wait" = Option<u64>, Query, description = "Seconds to hold the request until a status changes, answered right away without it"),
+        ("status" = Option<String>, Query, description = "Only list items of this status: pending, processing, success, error or dead_letter"),
+        ("sort" = Option<String>, Query, description = "`token_id` or `status`, queue order without it"),
+        ("group" = Option<String>, Query, description = "`status` to answer items grouped by status along with their counts"),
+    ),
+    responses(
+        (status = 200, description = "Queue items of the migration, as CSV when `Accept: text/csv` and as a `GroupedMigrationStateEnvelope` with `group=status`", body = MigrationStateEnvelope),
+        (status = 404, description = "No migration for this wallet and project", body = ErrorEnvelope),
+    )
+)]
+#[get("/customer/data/{keplr_wallet_pubkey}/{project_id}")]
+pub async fn get_customer_migration_state(
+    http_request: HttpRequest,
+    path: web::Path<(JunoAddress, StarknetAddress)>,
+    query: web::Query<MigrationStateQuery>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let (keplr_wallet_pubkey, project_id) = path.into_inner();
+    annotate_log_context(keplr_wallet_pubkey.as_str(), Some(project_id.as_str()));
+    let queue_manager = data.clone().queue_manager.clone();
+    let mut res = match query.wait {
+        Some(wait) if 0 < wait => {
+            data.migration_watch
+                .wait_for_change(
+                    queue_manager.as_ref(),
+                    &keplr_wallet_pubkey,
+                    &project_id,
+                    Duration::from_secs(wait),
+                    &data.shutdown,
+                )
+                .await
+        }
+        _ => {
+            queue_manager
+                .get_customer_migration_state(&keplr_wallet_pubkey, &project_id)
+                .await
+        }
+    };
+
+    if res.is_empty() {
+        return response::error(
+            http::StatusCode::NOT_FOUND,
+            "migration_not_found",
+            "No migration found for this wallet and project",
+        );
+    }
+
+    // Counted before filtering so every status tab shows its count
+    let counts = StatusCounts::of(&res);
+    if let Some(status) = &query.status {
+        res.retain(|qi| qi.status == *status);
+    }
+    if let Some(sort) = query.sort {
+        sort_items(&mut res, sort);
+    }
+
+    if csv::accepts_csv(&http_request) {
+        return csv::csv_records(&res);
+    }
+
+    let items = proven_items(&http_request, &data, res).await;
+    match query.group {
+        Some(MigrationStateGrouping::Status) => {
+            response::ok(GroupedMigrationState::new(items, counts))
+        }
+        None => response::ok(items),
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("keplr_wallet_pubkey" = String, Path, description = "Customer keplr wallet"),
+    ),
+    responses(
+        (status = 200, description = "Queue items of every project the wallet bridged, grouped by project, as CSV when `Accept: text/csv`", body = MigrationStateEnvelope),
+        (status = 404, description = "No migration for this wallet", body = ErrorEnvelope),
+    )
+)]
+#[get("/customer/migrations/{keplr_wallet_pubkey}")]
+pub async fn get_wallet_migrations(
+    http_request: HttpRequest,
+    path: web::Path<JunoAddress>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let keplr_wallet_pubkey = path.into_inner();
+    annotate_log_context(keplr_wallet_pubkey.as_str(), None);
+    let res = match data
+        .queue_manager
+        .get_wallet_migrations(&keplr_wallet_pubkey)
+        .await
+    {
+        Ok(items) => items,
+        Err(e) => {
+            error!("Failed to list wallet migrations {:#?}", e);
+            return response::internal_server_error("Migrations could not be listed");
+        }
+    };
+
+    if res.is_empty() {
+        return response::error(
+            http::StatusCode::NOT_FOUND,
+            "migration_not_found",
+            "No migration found for this wallet",
+        );
+    }
+
+    if csv::accepts_csv(&http_request) {
+        return csv::csv_records(&res);
+    }
+
+    response::ok(proven_items(&http_request, &data, res).await)
+}
+
+/// Items as customers see them, with their transfer proof, status message in the
+/// request language and mint attestation.
+async fn proven_items(
+    http_request: &HttpRequest,
+    data: &Config,
+    items: Vec<QueueItem>,
+) -> Vec<ProvenQueueItem> {
+    // Proofs are keyed by Juno contract, items by the Starknet one
+    let mut proofs = Vec::new();
+    let mut project_ids: Vec<&StarknetAddress> = items.iter().map(|i| &i.project_id).collect();
+    project_ids.dedup();
+    for project_id in project_ids {
+        let Some(project) = data.project_registry.find_by_starknet_contract(project_id) else {
+            continue;
+        };
+        let token_ids: Vec<TokenId> = items
+            .iter()
+            .filter(|i| &i.project_id == project_id)
+            .map(|i| i.token_id.clone())
+            .collect();
+        match data
+            .transfer_proof_repository
+            .get_proofs(&project.juno_contract, &token_ids)
+            .await
+        {
+            Ok(p) => proofs.extend(p.into_iter().map(|p| (project_id.clone(), p))),
+            Err(e) => error!("Failed to fetch transfer proofs {:#?}", e),
+        }
+    }
+    let language = negotiate_language(
+        http_request
+            .headers()
+            .get(http::header::ACCEPT_LANGUAGE)
+            .and_then(|h| h.to_str().ok()),
+    );
+
+    items
+        .into_iter()
+        .map(|item| ProvenQueueItem {
+            transfer_proof: proofs
+                .iter()
+                .find(|(project_id, p)| {
+                    *project_id == item.project_id && p.token_id == item.token_id
+                })
+                .map(|(_, p)| p.clone()),
+            message: item
+                .note
+                .as_deref()
+                .and_then(|note| render_status_message(note, &item.note_params, &language)),
+            attestation: attest_mint(&data.attestation_signer, &item),
+            item,
+        })
+        .collect()
+}
+
+#[utoipa::path(
+    params(
+        ("keplr_wallet_pubkey" = String, Path, description = "Customer keplr wallet"),
+        ("project_id" = String, Path, description = "Starknet project contract"),
+    ),
+    responses(
+        (status = 200, description = "Juno and Starknet transaction hashes of every token, as a file to keep", body = SignedProofBundle),
+        (status = 404, description = "No migration for this wallet and project", body = ErrorEnvelope),
+    )
+)]
+#[get("/customer/proofs/{keplr_wallet_pubkey}/{project_id}")]
+pub async fn get_customer_proof_bundle(
+    path: web::Path<(JunoAddress, StarknetAddress)>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let (keplr_wallet_pubkey, project_id) = path.into_inner();
+    annotate_log_context(keplr_wallet_pubkey.as_str(), Some(project_id.as_str()));
+    info!(
+        "GET - /customer/proofs/{}/{}",
+        &keplr_wallet_pubkey, &project_id
+    );
+
+    match handle_proof_bundle(
+        &keplr_wallet_pubkey,
+        &project_id,
+        &data.project_registry,
+        data.queue_manager.clone(),
+        data.transfer_proof_repository.clone(),
+        data.proof_bundle_signer.clone(),
+        data.clock.now_ms(),
+    )
+    .await
+    {
+        Ok(bundle) => HttpResponse::Ok()
+            .insert_header((
+                http::header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=migration-proofs-{}-{}.json",
+                    &project_id, &keplr_wallet_pubkey
+                ),
+            ))
+            .json(bundle),
+        Err(ProofBundleError::UnknownProject) | Err(ProofBundleError::MigrationNotFound) => {
+            response::error(
+                http::StatusCode::NOT_FOUND,
+                "migration_not_found",
+                "No migration found for this wallet and project",
+            )
+        }
+        Err(_e) => response::internal_server_error("Failed to build proof bundle"),
+    }
+}
+
+#[utoipa::path(
+    params(
+        ("keplr_wallet_pubkey" = String, Path, description = "Customer keplr wallet"),
+        ("project_id" = String, Path, description = "Juno project contract"),
+    ),
+    responses(
+        (status = 200, description = "Tokens the wallet holds on Juno, split by whether they can still be bridged", body = EligibleTokensEnvelope),
+        (status = 404, description = "Project is not bridged", body = ErrorEnvelope),
+        (status = 503, description = "Juno node could not list the tokens, retry later", body = ErrorEnvelope),
+    )
+)]
+#[get("/customer/eligible/{keplr_wallet_pubkey}/{project_id}")]
+pub async fn get_eligible_tokens(
+    path: web::Path<(JunoAddress, ProjectId)>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let (keplr_wallet_pubkey, project_id) = path.into_inner();
+    annotate_log_context(keplr_wallet_pubkey.as_str(), Some(project_id.as_str()));
+    info!(
+        "GET - /customer/eligible/{}/{}",
+        &keplr_wallet_pubkey, &project_id
+    );
+
+    match handle_eligible_tokens(
+        &keplr_wallet_pubkey,
+        &project_id,
+        &data.project_registry,
+        data.juno_contract_querier.as_ref(),
+        data.starknet_manager.as_ref(),
+        &data.shutdown,
+    )
+    .await
+    {
+        Ok(tokens) => response::ok(tokens),
+        Err(e) => response::catalog_error(&e.catalog_entry()),
+    }
+}
+
+pub fn webhook_error_response(error: WebhookError) -> HttpResponse {
+    match error {
+        WebhookError::InvalidSign => response::error(
+            http::StatusCode::BAD_REQUEST,
+            "invalid_sign",
+            "Invalid sign",
+        ),
+        WebhookError::InvalidCallbackUrl => response::error(
+            http::StatusCode::BAD_REQUEST,
+            "invalid_callback_url",
+            "Callback url has to be an absolute http(s) url",
+        ),
+        WebhookError::InvalidEventFilter => response::error(
+            http::StatusCode::BAD_REQUEST,
+            "invalid_event_filter",
+            "Webhooks subscribe to item_succeeded, item_failed or batch_submitted",
+        ),
+        WebhookError::NotFound => response::error(
+            http::StatusCode::NOT_FOUND,
+            "webhook_not_found",
+            "No webhook with this id was registered by this wallet",
+        ),
+        WebhookError::PersistenceIssue => {
+            response::internal_server_error("Error while handling webhook")
+        }
+    }
+}
+
+/// Registering a callback twice answers the existing subscription.
+pub fn registered_webhook_response(
+    result: Result<(WebhookSubscription, bool), WebhookError>,
+) -> HttpResponse {
+    match result {
+        Ok((subscription, true)) => response::with_status(http::StatusCode::CREATED, subscription),
+        Ok((subscription, false)) => response::ok(subscription),
+        Err(e) => webhook_error_response(e),
+    }
+}
+
+#[post("/webhooks")]
+pub async fn register_webhook(
+    request: web::Json<RegisterWebhookRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    info!(
+        "POST - /webhooks - {} - {}",
+        &request.keplr_wallet_pubkey, &request.callback_url
+    );
+
+    registered_webhook_response(
+        handle_register_webhook(
+            &request,
+            data.signed_hash_validator.clone(),
+            data.webhook_repository.clone(),
+        )
+        .await,
+    )
+}
+
+/// Webhooks registered by a wallet, without their secrets.
+#[get("/webhooks/{keplr_wallet_pubkey}")]
+pub async fn list_webhooks(
+    path: web::Path<JunoAddress>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let keplr_wallet_pubkey = path.into_inner();
+    info!("GET - /webhooks/{}", &keplr_wallet_pubkey);
+
+    match handle_list_webhooks(&keplr_wallet_pubkey, data.webhook_repository.clone()).await {
+        Ok(subscriptions) => response::ok(subscriptions),
+        Err(e) => webhook_error_response(e),
+    }
+}
+
+#[delete("/webhooks/{id}")]
+pub async fn delete_webhook(
+    path: web::Path<Uuid>,
+    request: web::Json<SignedWebhookRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let id = path.into_inner();
+    info!(
+        "DELETE - /webhooks/{} - {}",
+        id, &request.keplr_wallet_pubkey
+    );
+
+    match handle_delete_webhook(
+        id,
+        &request,
+        data.signed_hash_validator.clone(),
+        data.webhook_repository.clone(),
+    )
+    .await
+    {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(e) => webhook_error_response(e),
+    }
+}
+
+/// Posts a sample payload to the callback right away and answers how it went.
+#[post("/webhooks/{id}/test")]
+pub async fn test_webhook(
+    path: web::Path<Uuid>,
+    request: web::Json<SignedWebhookRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let id = path.into_inner();
+    info!(
+        "POST - /webhooks/{}/test - {}",
+        id, &request.keplr_wallet_pubkey
+    );
+    let Some(sender) = &data.webhook_sender else {
+        return response::error(
+            http::StatusCode::SERVICE_UNAVAILABLE,
+            "webhooks_disabled",
+            "Webhook deliveries are not enabled on this instance",
+        );
+    };
+
+    match handle_test_webhook(
+        id,
+        &request,
+        data.signed_hash_validator.clone(),
+        data.webhook_repository.clone(),
+        sender.clone(),
+        data.clock.clone(),
+    )
+    .await
+    {
+        Ok(result) => response::ok(result),
+        Err(e) => webhook_error_response(e),
+    }
+}
+
+#[post("/reverse-bridge")]
+pub async fn reverse_bridge(
+    req: web::Json<ReverseBridgeRequest>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    info!(
+        "POST - /reverse-bridge - {} - {:#?}",
+        &req.starknet_account_addr, &req.token_ids
+    );
+
+    match handle_reverse_bridge_request(
+        &req,
+        &data.project_registry,
+        &data.starknet_admin_address,
+        data.starknet_transfer_verifier.clone(),
+        data.wallet_link_repository.clone(),
+        data.reverse_queue_manager.clone(),
+    )
+    .await
+    {
+        Ok(items) => response::ok(items),
+        Err(e) => {
+            let entry = e.catalog_entry();
+            let details = match &e {
+                ReverseBridgeError::TokenNotReturned(token)
+                | ReverseBridgeError::AlreadyRequested(token) => {
+                    Some(serde_json::json!({ "token_id": token }))
+                }
+                _ => None,
+            };
+            let status = response::catalog_status(&entry);
+            match details {
+                Some(d) => response::error_with_details(status, entry.code, entry.message, &d),
+                None => response::error(status, entry.code, entry.message),
+            }
+        }
+    }
+}
+
+#[get("/reverse-bridge/{starknet_account_addr}/{project_id}")]
+pub async fn get_reverse_migration_state(
+    path: web::Path<(StarknetAddress, StarknetAddress)>,
+    data: web::Data<Config>,
+) -> impl Responder {
+    let (starknet_account_addr, project_id) = path.into_inner();
+    match data
+        .reverse_queue_manager
+        .get_customer_state(&starknet_account_addr, &project_id)
+        .await
+    {
+        Ok(items) if items.is_empty() => response::error(
+            http::StatusCode::NOT_FOUND,
+            "migration_not_found",
+            "No migration found for this account and project",
+        ),
+        Ok(items) => response::ok(items),
+        Err(_e) => response::internal_server_error("Failed to fetch reverse migration state"),
+    }
+}
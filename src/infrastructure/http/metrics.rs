@@ -0,0 +1,56 @@
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    get, http,
+    middleware::Next,
+    web, Error, HttpResponse, Responder,
+};
+use std::time::Instant;
+
+use super::response;
+use crate::infrastructure::app::Config;
+
+/// Counts and times every request by route pattern, so path parameters such as
+/// wallets do not end up in metric labels.
+pub async fn record_metrics(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(config) = req.app_data::<web::Data<Config>>().cloned() else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+    let method = req.method().to_string();
+    let started_at = Instant::now();
+
+    let res = next.call(req).await?.map_into_boxed_body();
+    let route = res
+        .request()
+        .match_pattern()
+        .unwrap_or_else(|| "unmatched".into());
+    let status = res.status().as_u16().to_string();
+    let tags = [
+        ("method", method.as_str()),
+        ("route", route.as_str()),
+        ("status", status.as_str()),
+    ];
+    config.metrics.increment("http_requests_total", &tags);
+    config
+        .metrics
+        .timing("http_request_duration_seconds", started_at.elapsed(), &tags);
+
+    Ok(res)
+}
+
+#[get("/metrics")]
+pub async fn scrape_metrics(config: web::Data<Config>) -> impl Responder {
+    match config.metrics.render() {
+        Some(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(body),
+        None => response::error(
+            http::StatusCode::NOT_FOUND,
+            "metrics_not_scraped",
+            "Metrics are pushed to the configured backend",
+        ),
+    }
+}
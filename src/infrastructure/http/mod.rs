@@ -0,0 +1,83 @@
+use log::error;
+use reqwest::{Certificate, Client, ClientBuilder, Proxy};
+use std::fs;
+
+pub mod admin;
+pub mod csv;
+pub mod handlers;
+pub mod metrics;
+pub mod openapi;
+pub mod rate_limit;
+pub mod request_id;
+pub mod response;
+pub mod schema_version;
+
+#[derive(Debug)]
+pub enum HttpClientConfigError {
+    InvalidProxy(String),
+    CertificateNotReadable(String),
+    InvalidCertificate(String),
+}
+
+/// Outbound HTTP settings shared by every reqwest client the bridge builds
+/// (Juno LCD, Starknet gateway, webhooks).
+#[derive(Clone, Default)]
+pub struct HttpClientConfig {
+    proxies: Vec<Proxy>,
+    root_certificates: Vec<Certificate>,
+}
+
+impl HttpClientConfig {
+    pub fn new(
+        http_proxy: Option<&str>,
+        https_proxy: Option<&str>,
+        ca_certificate_paths: &[String],
+    ) -> Result<Self, HttpClientConfigError> {
+        let mut proxies = Vec::new();
+        if let Some(url) = http_proxy {
+            match Proxy::http(url) {
+                Ok(p) => proxies.push(p),
+                Err(_e) => return Err(HttpClientConfigError::InvalidProxy(url.into())),
+            };
+        }
+        if let Some(url) = https_proxy {
+            match Proxy::https(url) {
+                Ok(p) => proxies.push(p),
+                Err(_e) => return Err(HttpClientConfigError::InvalidProxy(url.into())),
+            };
+        }
+
+        let mut root_certificates = Vec::new();
+        for path in ca_certificate_paths {
+            let pem = match fs::read(path) {
+                Ok(p) => p,
+                Err(e) => {
+                    error!("Failed to read CA certificate {} : {:#?}", path, e);
+                    return Err(HttpClientConfigError::CertificateNotReadable(path.into()));
+                }
+            };
+            match Certificate::from_pem(&pem) {
+                Ok(c) => root_certificates.push(c),
+                Err(_e) => return Err(HttpClientConfigError::InvalidCertificate(path.into())),
+            };
+        }
+
+        Ok(Self {
+            proxies,
+            root_certificates,
+        })
+    }
+
+    /// Returns a client builder with configured proxies and CA certificates applied.
+    pub fn client_builder(&self) -> ClientBuilder {
+        let mut builder = Client::builder();
+        for proxy in &self.proxies {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+
+        builder
+    }
+}
@@ -0,0 +1,183 @@
+use actix_web::{get, HttpResponse, Responder};
+use log::info;
+use serde_derive::Serialize;
+use utoipa::{OpenApi, ToSchema};
+
+use super::{handlers, response::ApiError};
+use crate::domain::{
+    attestation::MintAttestation,
+    bridge::{
+        BridgeRequest, BridgeResponse, BridgeSignDoc, PubKey, QueueItem, QueueStatus, SignedHash,
+        TransactionFinality, TransactionStatusResponse,
+    },
+    challenge::Challenge,
+    eligible_tokens::EligibleTokens,
+    error_catalog::error_catalog,
+    funnel::{FunnelEventRequest, FunnelStep},
+    health::{DependencyState, DependencyStatus, Readiness},
+    migration_state::{GroupedMigrationState, StatusCounts},
+    proof_bundle::{ProofBundle, SignedProofBundle, TokenMigrationProof},
+    save_customer_data::{SaveCustomerDataOutcome, SaveCustomerDataRequest},
+    transfer_proof::{ProvenQueueItem, TransferProof},
+};
+
+// `ApiResponse<T>` is serialized by hand, these mirror it once per payload so the
+// document shows the actual bodies.
+
+/// `{ "ok": true, "data": .. }` answered by `/bridge`.
+#[derive(Serialize, ToSchema)]
+pub struct BridgeEnvelope {
+    pub ok: bool,
+    pub data: BridgeResponse,
+}
+
+/// `{ "ok": true, "data": .. }` answered by `/bridge/tx/{transaction_hash}`.
+#[derive(Serialize, ToSchema)]
+pub struct TransactionStatusEnvelope {
+    pub ok: bool,
+    pub data: TransactionStatusResponse,
+}
+
+/// `{ "ok": true, "data": .. }` answered by `/customer/eligible`.
+#[derive(Serialize, ToSchema)]
+pub struct EligibleTokensEnvelope {
+    pub ok: bool,
+    pub data: EligibleTokens,
+}
+
+/// `{ "ok": true, "data": .. }` answered by `/challenge`.
+#[derive(Serialize, ToSchema)]
+pub struct ChallengeEnvelope {
+    pub ok: bool,
+    pub data: Challenge,
+}
+
+/// `{ "ok": true, "data": .. }` answered by `/customer/data`.
+#[derive(Serialize, ToSchema)]
+pub struct SaveCustomerDataEnvelope {
+    pub ok: bool,
+    pub data: SaveCustomerDataOutcome,
+}
+
+/// `{ "ok": true, "data": .. }` answered by `/ready`.
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessEnvelope {
+    pub ok: bool,
+    pub data: Readiness,
+}
+
+/// `{ "ok": true, "data": null }` answered when a resource has been created.
+#[derive(Serialize, ToSchema)]
+pub struct CreatedEnvelope {
+    pub ok: bool,
+    #[schema(value_type = Option<Object>)]
+    pub data: Option<()>,
+}
+
+/// `{ "ok": true, "data": [..] }` listing the queue items of a migration, with the
+/// Juno transfer of each token once it has been proven.
+#[derive(Serialize, ToSchema)]
+pub struct MigrationStateEnvelope {
+    pub ok: bool,
+    pub data: Vec<ProvenQueueItem>,
+}
+
+/// `{ "ok": true, "data": { "pending": [..], .., "counts": { .. }, "total": .. } }`
+/// listing the queue items of a migration per status.
+#[derive(Serialize, ToSchema)]
+pub struct GroupedMigrationStateEnvelope {
+    pub ok: bool,
+    pub data: GroupedMigrationState,
+}
+
+/// `{ "ok": false, "error": { "code": .., "message": .., "details": .. } }`, codes are
+/// listed in the document description.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorEnvelope {
+    pub ok: bool,
+    pub error: ApiError,
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Juno to Starknet bridge",
+        description = "Migrates carbonABLE NFTs from Juno to Starknet."
+    ),
+    paths(
+        handlers::bridge,
+        handlers::get_transaction_status,
+        handlers::challenge,
+        handlers::health,
+        handlers::ready,
+        handlers::save_customer_tokens,
+        handlers::record_event,
+        handlers::get_customer_migration_state,
+        handlers::get_wallet_migrations,
+        handlers::get_customer_proof_bundle,
+        handlers::get_eligible_tokens,
+    ),
+    components(schemas(
+        BridgeRequest,
+        BridgeSignDoc,
+        SignedHash,
+        PubKey,
+        BridgeResponse,
+        TransactionStatusResponse,
+        TransactionFinality,
+        TransactionStatusEnvelope,
+        Challenge,
+        SaveCustomerDataRequest,
+        SaveCustomerDataOutcome,
+        SaveCustomerDataEnvelope,
+        FunnelEventRequest,
+        FunnelStep,
+        QueueItem,
+        ProvenQueueItem,
+        TransferProof,
+        MintAttestation,
+        SignedProofBundle,
+        ProofBundle,
+        TokenMigrationProof,
+        EligibleTokens,
+        EligibleTokensEnvelope,
+        QueueStatus,
+        ApiError,
+        BridgeEnvelope,
+        ChallengeEnvelope,
+        ReadinessEnvelope,
+        Readiness,
+        DependencyStatus,
+        DependencyState,
+        CreatedEnvelope,
+        MigrationStateEnvelope,
+        GroupedMigrationState,
+        StatusCounts,
+        GroupedMigrationStateEnvelope,
+        ErrorEnvelope,
+    ))
+)]
+pub struct ApiDoc;
+
+/// Generated document, with the error catalog appended to the description so error
+/// codes cannot drift from what the handlers answer.
+pub fn openapi_document() -> utoipa::openapi::OpenApi {
+    let mut document = ApiDoc::openapi();
+    let mut description = document.info.description.take().unwrap_or_default();
+    description.push_str("\n\n## Error codes\n\n| source | code | status | retriable | message |\n|---|---|---|---|---|\n");
+    for entry in error_catalog() {
+        description.push_str(&format!(
+            "| {} | `{}` | {} | {} | {} |\n",
+            entry.source, entry.code, entry.http_status, entry.retriable, entry.message
+        ));
+    }
+    document.info.description = Some(description);
+
+    document
+}
+
+#[get("/v1/meta/openapi.json")]
+pub async fn openapi_spec() -> impl Responder {
+    info!("GET - /v1/meta/openapi.json");
+    HttpResponse::Ok().json(openapi_document())
+}
@@ -0,0 +1,135 @@
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::{
+        header::{HeaderMap, HeaderName, HeaderValue, RETRY_AFTER},
+        StatusCode,
+    },
+    middleware::Next,
+    web, Error,
+};
+use core::fmt::{Debug, Formatter};
+use log::warn;
+use std::{collections::HashMap, sync::Arc, sync::Mutex, time::Duration};
+
+use super::response;
+use crate::{domain::clock::Clock, infrastructure::app::Config};
+
+pub const X_RATELIMIT_LIMIT: &str = "x-ratelimit-limit";
+pub const X_RATELIMIT_REMAINING: &str = "x-ratelimit-remaining";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the current window ends
+    pub reset_after: u64,
+}
+
+/// Fixed window limiter counting requests per client.
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    // Client to window start as epoch milliseconds and requests counted in it
+    windows: Mutex<HashMap<String, (i64, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            limit,
+            window,
+            clock,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(&self, client: &str) -> RateLimitDecision {
+        let now = self.clock.now_ms();
+        let window = self.window.as_millis() as i64;
+        let mut lock = match self.windows.lock() {
+            Ok(l) => l,
+            Err(p) => p.into_inner(),
+        };
+        // Stale windows are dropped so the map does not grow with every client ever seen
+        lock.retain(|_, (start, _)| now - *start < window);
+
+        let (start, count) = lock.entry(client.to_string()).or_insert((now, 0));
+        let allowed = *count < self.limit;
+        if allowed {
+            *count += 1;
+        }
+        let elapsed = now - *start;
+
+        RateLimitDecision {
+            allowed,
+            limit: self.limit,
+            remaining: self.limit - *count,
+            reset_after: ((window - elapsed).max(0) as u64).div_ceil(1000),
+        }
+    }
+}
+
+impl Debug for RateLimiter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "RateLimiter{{limit: {}, window: {:?}}}",
+            self.limit, self.window
+        )
+    }
+}
+
+fn insert_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    headers.insert(
+        HeaderName::from_static(X_RATELIMIT_LIMIT),
+        HeaderValue::from(decision.limit),
+    );
+    headers.insert(
+        HeaderName::from_static(X_RATELIMIT_REMAINING),
+        HeaderValue::from(decision.remaining),
+    );
+    if !decision.allowed {
+        headers.insert(RETRY_AFTER, HeaderValue::from(decision.reset_after));
+    }
+}
+
+/// Refuses clients over the configured limit with `rate_limited` and tells every
+/// client its quota, so the frontend can show a countdown.
+pub async fn rate_limit(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let limiter = req
+        .app_data::<web::Data<Config>>()
+        .and_then(|c| c.rate_limiter.clone());
+    let Some(limiter) = limiter else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+
+    let client = req
+        .connection_info()
+        .realip_remote_addr()
+        .unwrap_or("unknown")
+        .to_string();
+    let decision = limiter.check(&client);
+    if !decision.allowed {
+        warn!("Rate limiting {} on {}", client, req.path());
+        let mut res = req.into_response(response::error(
+            StatusCode::TOO_MANY_REQUESTS,
+            "rate_limited",
+            &format!(
+                "Too many requests, please retry in {} seconds",
+                decision.reset_after
+            ),
+        ));
+        insert_headers(res.headers_mut(), &decision);
+        return Ok(res);
+    }
+
+    let mut res = next.call(req).await?.map_into_boxed_body();
+    insert_headers(res.headers_mut(), &decision);
+    Ok(res)
+}
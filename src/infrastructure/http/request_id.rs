@@ -0,0 +1,52 @@
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    Error,
+};
+use uuid::Uuid;
+
+use crate::domain::log_context::{with_log_context, LogFields};
+
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+// Ids set by a proxy or the frontend are kept when they cannot break a log line
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// Attaches a request id to every log line written while handling the request, and
+/// answers it so a customer report can be matched with the logs.
+pub async fn request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let request_id = req
+        .headers()
+        .get(X_REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| is_valid_request_id(v))
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let fields = LogFields {
+        request_id: Some(request_id.to_string()),
+        ..LogFields::default()
+    };
+
+    let mut res = with_log_context(fields, next.call(req))
+        .await?
+        .map_into_boxed_body();
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        res.headers_mut()
+            .insert(HeaderName::from_static(X_REQUEST_ID), value);
+    }
+
+    Ok(res)
+}
+
+fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LENGTH
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
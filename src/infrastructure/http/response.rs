@@ -0,0 +1,96 @@
+use actix_web::{http::StatusCode, HttpResponse};
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_derive::Serialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use crate::domain::error_catalog::ErrorDescription;
+
+/// Failure half of the envelope, `code` is stable and meant to be matched on by clients.
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct ApiError {
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+}
+
+/// Body of every JSON response, serialized as `{ "ok": true, "data": .. }` or
+/// `{ "ok": false, "error": { "code": .., "message": .., "details": .. } }`.
+#[derive(Debug)]
+pub enum ApiResponse<T> {
+    Ok(T),
+    Err(ApiError),
+}
+
+impl<T: Serialize> Serialize for ApiResponse<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        match self {
+            ApiResponse::Ok(data) => {
+                map.serialize_entry("ok", &true)?;
+                map.serialize_entry("data", data)?;
+            }
+            ApiResponse::Err(error) => {
+                map.serialize_entry("ok", &false)?;
+                map.serialize_entry("error", error)?;
+            }
+        }
+        map.end()
+    }
+}
+
+pub fn ok<T: Serialize>(data: T) -> HttpResponse {
+    with_status(StatusCode::OK, data)
+}
+
+pub fn with_status<T: Serialize>(status: StatusCode, data: T) -> HttpResponse {
+    HttpResponse::build(status).json(ApiResponse::Ok(data))
+}
+
+pub fn error(status: StatusCode, code: &str, message: &str) -> HttpResponse {
+    failure(status, code, message, None)
+}
+
+pub fn error_with_details<D: Serialize>(
+    status: StatusCode,
+    code: &str,
+    message: &str,
+    details: &D,
+) -> HttpResponse {
+    failure(status, code, message, serde_json::to_value(details).ok())
+}
+
+fn failure(status: StatusCode, code: &str, message: &str, details: Option<Value>) -> HttpResponse {
+    HttpResponse::build(status).json(ApiResponse::<()>::Err(ApiError {
+        code: code.into(),
+        message: message.into(),
+        details,
+    }))
+}
+
+pub fn catalog_status(entry: &ErrorDescription) -> StatusCode {
+    StatusCode::from_u16(entry.http_status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Answers with the status, code and message the error catalog documents.
+pub fn catalog_error(entry: &ErrorDescription) -> HttpResponse {
+    error(catalog_status(entry), entry.code, entry.message)
+}
+
+pub fn bad_request(message: &str) -> HttpResponse {
+    error(StatusCode::BAD_REQUEST, "bad_request", message)
+}
+
+pub fn not_found(message: &str) -> HttpResponse {
+    error(StatusCode::NOT_FOUND, "not_found", message)
+}
+
+pub fn internal_server_error(message: &str) -> HttpResponse {
+    error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "internal_server_error",
+        message,
+    )
+}
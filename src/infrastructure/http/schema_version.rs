@@ -0,0 +1,68 @@
+use actix_web::{
+    body::{BoxBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    http::{
+        header::{HeaderName, HeaderValue},
+        Method,
+    },
+    middleware::Next,
+    web, Error,
+};
+use log::warn;
+
+use super::response;
+use crate::{
+    domain::{
+        error_catalog::CatalogedError,
+        schema_version::{check_schema_version, SchemaVersionError, BRIDGE_SCHEMA_VERSION},
+    },
+    infrastructure::app::Config,
+};
+
+pub const X_BRIDGE_SCHEMA_VERSION: &str = "x-bridge-schema-version";
+
+/// Refuses payloads built for another schema version with an upgrade error instead of
+/// reading them, and tells every client which version it talks to.
+pub async fn schema_version(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let required = req
+        .app_data::<web::Data<Config>>()
+        .map_or(false, |c| c.require_schema_version);
+    // Only requests with a payload can drift, reads are answered in the current version
+    let has_payload = matches!(*req.method(), Method::POST | Method::PUT | Method::PATCH);
+    let version = req
+        .headers()
+        .get(X_BRIDGE_SCHEMA_VERSION)
+        .map(|v| v.to_str().unwrap_or_default().to_string());
+
+    let mut res = match check_schema_version(version.as_deref(), required && has_payload) {
+        Ok(()) => next.call(req).await?.map_into_boxed_body(),
+        Err(e) => {
+            warn!("Refusing {} {} : {:#?}", req.method(), req.path(), e);
+            let entry = e.catalog_entry();
+            let message = entry
+                .message
+                .replace("{version}", &BRIDGE_SCHEMA_VERSION.to_string())
+                .replace(
+                    "{received}",
+                    match &e {
+                        SchemaVersionError::Unsupported(v) => v,
+                        SchemaVersionError::Missing => "",
+                    },
+                );
+            req.into_response(response::error(
+                response::catalog_status(&entry),
+                entry.code,
+                &message,
+            ))
+        }
+    };
+    res.headers_mut().insert(
+        HeaderName::from_static(X_BRIDGE_SCHEMA_VERSION),
+        HeaderValue::from(BRIDGE_SCHEMA_VERSION),
+    );
+
+    Ok(res)
+}
@@ -1,15 +1,63 @@
 use async_trait::async_trait;
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
 use crate::domain::{
+    analytics::{BatchAnalyticsError, BatchAnalyticsQuery, BatchAnalyticsRepository, BatchRecord},
+    audit::{AuditError, AuditRepository, BridgeRequestAudit},
+    batch_size::{BatchSizeError, BatchSizeRepository, ProjectBatchSize},
+    breakglass::{BreakglassError, BreakglassMint, BreakglassRepository, BreakglassStatus},
     bridge::{
-        MintError, MsgTypes, QueueError, QueueItem, QueueManager, QueueStatus, QueueUpdateError,
-        SignedHash, SignedHashValidator, SignedHashValidatorError, StarknetManager, Transaction,
-        TransactionFetchError, TransactionRepository,
+        JunoContractQuerier, MintError, MintStatusError, MintSubmission, QueueError, QueueEvent,
+        QueueItem, QueueItemTransition, QueueManager, QueueStatus, QueueUpdateError, SignedHash,
+        SignedHashValidator, SignedHashValidatorError, StarknetManager, Transaction,
+        TransactionFetchError, TransactionOutcome, TransactionRepository,
+    },
+    challenge::{Challenge, ChallengeError, ChallengeRepository},
+    check_cache::{CheckCacheError, CheckResult, CheckResultRepository},
+    clock::{Clock, SystemClock},
+    consume_queue::REVERTED_CALL_REASON,
+    fee_strategy::{FeeError, FeeStrategy},
+    funnel::{FunnelEvent, FunnelEventError, FunnelEventRepository},
+    ids::{JunoAddress, ProjectId, QueueItemId, StarknetAddress, TokenId},
+    issue_tracker::{Issue, IssueRecord, IssueRecordRepository, IssueTracker, IssueTrackerError},
+    maintenance::{MaintenanceError, MaintenanceRepository, TableStats},
+    pagination::{Cursor, Page, PageRequest},
+    post_mint::{PostMintError, PostMintExecution, PostMintExecutionRepository, PostMintStatus},
+    queue_broker::{QueueBroker, QueueBrokerError},
+    queue_snapshot::{QueueSnapshotEntry, QueueSnapshotError, QueueSnapshotRepository},
+    report::{DailyActivity, ReportError, ReportRepository, SignedDailyReport},
+    reverse_bridge::{
+        JunoBroadcastError, JunoTxBroadcaster, ReverseQueueError, ReverseQueueItem,
+        ReverseQueueManager, ReverseQueueStatus, StarknetTokenTransfer, StarknetTransferError,
+        StarknetTransferVerifier,
     },
     save_customer_data::{CustomerKeys, DataRepository, SaveCustomerDataError},
+    stats::{
+        ProjectCompletion, ProjectProgress, StatsError, StatsRepository, StatusTransition,
+        TimeRange,
+    },
+    status_message::StatusNote,
+    transfer_proof::{TransferProof, TransferProofError, TransferProofRepository},
+    wallet_link::{WalletLink, WalletLinkError, WalletLinkRepository},
+    webhook::{
+        WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryStatus, WebhookError,
+        WebhookPayload, WebhookRepository, WebhookSendError, WebhookSender, WebhookSubscription,
+    },
 };
 
+// All in-memory adapters share their state through an Arc so clones are cheap and
+// can be handed to concurrently running scenarios.
+
 #[derive(Debug, Clone)]
 pub struct TestSignedHashValidator {}
 
@@ -27,35 +75,37 @@ impl SignedHashValidator for TestSignedHashValidator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InMemoryTransactionRepository {
-    pub transactions: Mutex<Vec<Transaction>>,
+    transactions: Arc<RwLock<Vec<Transaction>>>,
+    fetches: Arc<AtomicUsize>,
+    failing: Arc<AtomicBool>,
+    latency_ms: Arc<AtomicU64>,
 }
 
 #[async_trait]
 impl TransactionRepository for InMemoryTransactionRepository {
-    async fn get_transactions_for_contract(
+    async fn get_contract_transactions(
         &self,
-        project_id: &str,
-        token_id: &str,
+        project_id: &ProjectId,
+        cancel: &CancellationToken,
     ) -> Result<Vec<Transaction>, TransactionFetchError> {
-        let lock = match self.transactions.lock() {
-            Ok(l) => l,
-            _ => {
-                return Err(TransactionFetchError::FetchError(
-                    "Failed to acquire lock on the requested resource".into(),
-                ))
-            }
-        };
+        if cancel.is_cancelled() {
+            return Err(TransactionFetchError::Cancelled);
+        }
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        let latency = self.latency_ms.load(Ordering::SeqCst);
+        if 0 < latency {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(TransactionFetchError::JunoBlockchainServerError(502));
+        }
+        let lock = self.transactions.read().await;
         let filtered_transactions: Vec<Transaction> = lock
-            .clone()
-            .into_iter()
-            .filter(|t| {
-                let transfert = match &t.msg {
-                    MsgTypes::TransferNft(tt) => tt,
-                };
-                t.contract == project_id && token_id == transfert.token_id
-            })
+            .iter()
+            .filter(|t| *project_id == t.contract.as_str())
+            .cloned()
             .collect::<Vec<Transaction>>();
         Ok(filtered_transactions)
     }
@@ -64,47 +114,199 @@ impl TransactionRepository for InMemoryTransactionRepository {
 impl InMemoryTransactionRepository {
     pub fn new(transactions: Vec<Transaction>) -> Self {
         Self {
-            transactions: Mutex::new(transactions),
+            transactions: Arc::new(RwLock::new(transactions)),
+            fetches: Arc::new(AtomicUsize::new(0)),
+            failing: Arc::new(AtomicBool::new(false)),
+            latency_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Number of contract listings fetched so far, as many LCD calls would have been made.
+    pub fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+
+    /// Makes the fake node answer with a server error.
+    pub fn fail_fetches(&self, fail: bool) {
+        self.failing.store(fail, Ordering::SeqCst);
+    }
+
+    /// Delays every answer of the fake node, as a slow LCD would.
+    pub fn slow_down(&self, latency: Duration) {
+        self.latency_ms
+            .store(latency.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+/// Holders of the tokens of fake CW721 contracts, unknown tokens fail the query as
+/// the contract would.
+#[derive(Debug, Clone)]
+pub struct InMemoryJunoContractQuerier {
+    owners: Arc<RwLock<HashMap<(ProjectId, TokenId), JunoAddress>>>,
+}
+
+#[async_trait]
+impl JunoContractQuerier for InMemoryJunoContractQuerier {
+    async fn owner_of(
+        &self,
+        project_id: &ProjectId,
+        token_id: &TokenId,
+        cancel: &CancellationToken,
+    ) -> Result<JunoAddress, TransactionFetchError> {
+        if cancel.is_cancelled() {
+            return Err(TransactionFetchError::Cancelled);
+        }
+        self.owners
+            .read()
+            .await
+            .get(&(project_id.clone(), token_id.clone()))
+            .cloned()
+            .ok_or(TransactionFetchError::JunoBlockchainServerError(500))
+    }
+
+    async fn tokens_of(
+        &self,
+        project_id: &ProjectId,
+        owner: &JunoAddress,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TokenId>, TransactionFetchError> {
+        if cancel.is_cancelled() {
+            return Err(TransactionFetchError::Cancelled);
+        }
+        let mut token_ids: Vec<TokenId> = self
+            .owners
+            .read()
+            .await
+            .iter()
+            .filter(|((project, _), holder)| project == project_id && *holder == owner)
+            .map(|((_, token_id), _)| token_id.clone())
+            .collect();
+        token_ids.sort();
+        Ok(token_ids)
+    }
+}
+
+impl InMemoryJunoContractQuerier {
+    pub fn new() -> Self {
+        Self {
+            owners: Arc::new(RwLock::new(HashMap::new())),
         }
     }
+
+    pub async fn set_owner(&self, project_id: &ProjectId, token_id: &TokenId, owner: &JunoAddress) {
+        self.owners
+            .write()
+            .await
+            .insert((project_id.clone(), token_id.clone()), owner.clone());
+    }
 }
 
+/// Fee every in-memory transaction pays, in wei.
+pub const IN_MEMORY_TRANSACTION_FEE: &str = "1200000000000000";
+/// Fee every in-memory mint is estimated to, in wei.
+pub const IN_MEMORY_ESTIMATED_FEE: &str = "1000000000000000";
+
+#[derive(Debug, Clone)]
 pub struct InMemoryStarknetTransactionManager {
-    nfts: Mutex<HashMap<String, HashMap<String, String>>>,
+    nfts: Arc<RwLock<HashMap<StarknetAddress, HashMap<TokenId, StarknetAddress>>>>,
+    paused_projects: Arc<RwLock<HashSet<StarknetAddress>>>,
+    holding_transactions: Arc<AtomicBool>,
+    failing_mints: Arc<AtomicBool>,
+    latency_ms: Arc<AtomicU64>,
+    rejection_reason: Arc<RwLock<Option<String>>>,
+    max_calls: Arc<AtomicUsize>,
+    sent_batches: Arc<AtomicUsize>,
+    fee_strategy: Arc<RwLock<Option<Arc<dyn FeeStrategy>>>>,
+    unreachable: Arc<AtomicBool>,
+    reverting_tokens: Arc<RwLock<HashSet<TokenId>>>,
+    // Tokens whose mint passes fee estimation but reverts once sent
+    failing_tokens: Arc<RwLock<HashSet<TokenId>>>,
+    failed_transactions: Arc<RwLock<HashSet<String>>>,
 }
 
 #[async_trait]
 impl StarknetManager for InMemoryStarknetTransactionManager {
-    async fn project_has_token(&self, project_id: &str, token_id: &str) -> bool {
-        let lock = match self.nfts.lock() {
-            Ok(l) => l,
-            _ => {
-                return false;
+    async fn project_has_token(
+        &self,
+        project_id: &StarknetAddress,
+        token_id: &TokenId,
+    ) -> Result<bool, MintStatusError> {
+        let latency = self.latency_ms.load(Ordering::SeqCst);
+        if 0 < latency {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+        if self.unreachable.load(Ordering::SeqCst) {
+            return Err(MintStatusError::Unavailable("Gateway unreachable".into()));
+        }
+        let lock = self.nfts.read().await;
+
+        Ok(lock
+            .get(project_id)
+            .map_or(false, |tokens| tokens.contains_key(token_id)))
+    }
+
+    async fn get_transaction_status(&self, _transaction_hash: &str) -> Option<String> {
+        Some("AcceptedOnL2".into())
+    }
+
+    async fn get_transaction_outcome(&self, transaction_hash: &str) -> TransactionOutcome {
+        if self.holding_transactions.load(Ordering::SeqCst) {
+            return TransactionOutcome::Pending;
+        }
+        if self
+            .failed_transactions
+            .read()
+            .await
+            .contains(transaction_hash)
+        {
+            return TransactionOutcome::Rejected(Some(REVERTED_CALL_REASON.into()));
+        }
+        match self.rejection_reason.read().await.as_ref() {
+            Some(reason) => TransactionOutcome::Rejected(Some(reason.clone())),
+            None => TransactionOutcome::Accepted,
+        }
+    }
+
+    async fn get_transaction_fee(&self, _transaction_hash: &str) -> Option<String> {
+        Some(IN_MEMORY_TRANSACTION_FEE.to_string())
+    }
+
+    async fn wait_for_transaction(
+        &self,
+        transaction_hash: &str,
+        cancel: &CancellationToken,
+    ) -> TransactionOutcome {
+        loop {
+            let outcome = self.get_transaction_outcome(transaction_hash).await;
+            if TransactionOutcome::Pending != outcome {
+                return outcome;
             }
-        };
+            tokio::select! {
+                _ = cancel.cancelled() => return TransactionOutcome::Pending,
+                _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+            };
+        }
+    }
+
+    async fn project_is_paused(&self, project_id: &StarknetAddress) -> bool {
+        self.paused_projects.read().await.contains(project_id)
+    }
 
-        lock.contains_key(project_id) && lock[project_id].contains_key(token_id)
+    fn max_calls_per_transaction(&self) -> usize {
+        self.max_calls.load(Ordering::SeqCst)
     }
 
     async fn mint_project_token(
         &self,
-        project_id: &str,
-        tokens: &[String],
-        starknet_account_addr: &str,
-    ) -> Result<String, crate::domain::bridge::MintError> {
-        let mut lock = match self.nfts.lock() {
-            Ok(l) => l,
-            _ => return Err(MintError::Failure),
-        };
-
-        if lock.contains_key(project_id) {
-            lock.insert(project_id.to_string(), HashMap::new());
-        }
+        project_id: &StarknetAddress,
+        tokens: &[TokenId],
+        starknet_account_addr: &StarknetAddress,
+    ) -> Result<String, MintError> {
+        let mut lock = self.nfts.write().await;
 
+        let project = lock.entry(project_id.clone()).or_default();
         for token_id in tokens {
-            lock.get_mut(project_id)
-                .unwrap()
-                .insert(token_id.to_string(), starknet_account_addr.into());
+            project.insert(token_id.clone(), starknet_account_addr.clone());
         }
 
         Ok("0xHExaD3c1m4lTr4ns4ct10nH4sH".to_string())
@@ -112,114 +314,263 @@ impl StarknetManager for InMemoryStarknetTransactionManager {
 
     async fn batch_mint_tokens(
         &self,
-        project_id: &str,
+        project_id: &StarknetAddress,
         queue_items: Vec<QueueItem>,
-    ) -> Result<(String, QueueStatus), MintError> {
-        Ok((
-            "0xHExaD3c1m4lTr4ns4ct10nH4sH".to_string(),
-            QueueStatus::Success,
-        ))
+    ) -> Result<MintSubmission, MintError> {
+        if self.failing_mints.load(Ordering::SeqCst) {
+            return Err(MintError::Failure);
+        }
+        // A single reverted mint fails the fee estimation of the whole transaction
+        let reverting = self.reverting_tokens.read().await;
+        if queue_items
+            .iter()
+            .any(|qi| reverting.contains(&qi.token_id))
+        {
+            return Err(MintError::Reverted);
+        }
+        drop(reverting);
+        if let Some(strategy) = self.fee_strategy.read().await.as_ref() {
+            let estimated_fee = IN_MEMORY_ESTIMATED_FEE.parse::<u128>().unwrap();
+            match strategy.max_fee(project_id, estimated_fee, queue_items.len()) {
+                Ok(_) => {}
+                Err(FeeError::AboveCap { .. }) => return Err(MintError::FeeAboveCap),
+                Err(FeeError::Overflow) => return Err(MintError::Failure),
+            }
+        }
+        // Every batch is its own transaction
+        let batch = self.sent_batches.fetch_add(1, Ordering::SeqCst);
+        let transaction_hash = format!("0xHExaD3c1m4lTr4ns4ct10nH4sH{batch}");
+
+        // A single failing mint reverts the whole transaction on chain
+        let failing = self.failing_tokens.read().await;
+        if queue_items.iter().any(|qi| failing.contains(&qi.token_id)) {
+            self.failed_transactions
+                .write()
+                .await
+                .insert(transaction_hash.clone());
+        } else {
+            let mut lock = self.nfts.write().await;
+            let project = lock.entry(project_id.clone()).or_default();
+            for qi in queue_items {
+                project.insert(qi.token_id, qi.starknet_wallet_pubkey);
+            }
+        }
+
+        Ok(MintSubmission {
+            transaction_hash,
+            estimated_fee: Some(IN_MEMORY_ESTIMATED_FEE.to_string()),
+        })
     }
 }
 
 impl InMemoryStarknetTransactionManager {
     pub fn new() -> Self {
         Self {
-            nfts: Mutex::new(HashMap::new()),
+            nfts: Arc::new(RwLock::new(HashMap::new())),
+            paused_projects: Arc::new(RwLock::new(HashSet::new())),
+            holding_transactions: Arc::new(AtomicBool::new(false)),
+            failing_mints: Arc::new(AtomicBool::new(false)),
+            latency_ms: Arc::new(AtomicU64::new(0)),
+            rejection_reason: Arc::new(RwLock::new(None)),
+            max_calls: Arc::new(AtomicUsize::new(usize::MAX)),
+            sent_batches: Arc::new(AtomicUsize::new(0)),
+            fee_strategy: Arc::new(RwLock::new(None)),
+            unreachable: Arc::new(AtomicBool::new(false)),
+            reverting_tokens: Arc::new(RwLock::new(HashSet::new())),
+            failing_tokens: Arc::new(RwLock::new(HashSet::new())),
+            failed_transactions: Arc::new(RwLock::new(HashSet::new())),
+        }
+    }
+
+    /// Makes the mint of `token_id` revert, failing the fee estimation of every batch it
+    /// is part of before anything is sent.
+    pub async fn revert_mints_of(&self, token_id: &TokenId) {
+        self.reverting_tokens.write().await.insert(token_id.clone());
+    }
+
+    /// Makes the mint of `token_id` revert once sent, the sequencer rejecting every
+    /// transaction it is part of with `TRANSACTION_FAILED`.
+    pub async fn fail_mints_on_chain_of(&self, token_id: &TokenId) {
+        self.failing_tokens.write().await.insert(token_id.clone());
+    }
+
+    /// Makes ownership lookups fail, as an unreachable starknet gateway would.
+    pub fn make_unreachable(&self, unreachable: bool) {
+        self.unreachable.store(unreachable, Ordering::SeqCst);
+    }
+
+    /// Caps the mint calls a single transaction holds, as calldata and step limits do.
+    pub fn limit_calls_per_transaction(&self, max_calls: usize) {
+        self.max_calls.store(max_calls, Ordering::SeqCst);
+    }
+
+    /// Prices batches with `strategy` against the in-memory fee estimate.
+    pub async fn use_fee_strategy(&self, strategy: Arc<dyn FeeStrategy>) {
+        *self.fee_strategy.write().await = Some(strategy);
+    }
+
+    /// Delays ownership lookups, as a slow starknet gateway would.
+    pub fn slow_down(&self, latency: Duration) {
+        self.latency_ms
+            .store(latency.as_millis() as u64, Ordering::SeqCst);
+    }
+
+    /// Makes every batch mint fail before reaching the chain.
+    pub fn fail_mints(&self, fail: bool) {
+        self.failing_mints.store(fail, Ordering::SeqCst);
+    }
+
+    /// Keeps every transaction pending until released, as a congested sequencer would.
+    pub fn hold_transactions(&self, hold: bool) {
+        self.holding_transactions.store(hold, Ordering::SeqCst);
+    }
+
+    /// Makes the sequencer reject every transaction with the given reason, until reset with `None`.
+    pub async fn reject_transactions(&self, reason: Option<&str>) {
+        *self.rejection_reason.write().await = reason.map(String::from);
+    }
+
+    /// Drops every token minted so far and rejects their transactions, as a reorg would.
+    pub async fn revert_mints(&self, reason: &str) {
+        self.nfts.write().await.clear();
+        self.reject_transactions(Some(reason)).await;
+    }
+
+    pub async fn pause_project(&self, project_id: &StarknetAddress, paused: bool) {
+        let mut lock = self.paused_projects.write().await;
+        if paused {
+            lock.insert(project_id.clone());
+        } else {
+            lock.remove(project_id);
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct InMemoryDataRepository {
-    data: Mutex<HashMap<String, HashMap<String, Vec<String>>>>,
+    data: Arc<RwLock<HashMap<JunoAddress, HashMap<ProjectId, Vec<TokenId>>>>>,
+    authorized_senders: Arc<RwLock<HashMap<String, Vec<JunoAddress>>>>,
 }
 
 impl InMemoryDataRepository {
     pub fn new() -> Self {
         Self {
-            data: Mutex::new(HashMap::new()),
+            data: Arc::new(RwLock::new(HashMap::new())),
+            authorized_senders: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
+
 #[async_trait]
 impl DataRepository for InMemoryDataRepository {
     async fn save_customer_keys(&self, keys: CustomerKeys) -> Result<(), SaveCustomerDataError> {
-        let mut lock = match self.data.lock() {
-            Ok(l) => l,
-            Err(_) => panic!("Failed to acquire lock on data repository"),
-        };
+        let mut lock = self.data.write().await;
 
-        if !lock.contains_key(&keys.keplr_wallet_pubkey) {
-            let mut content: HashMap<String, Vec<String>> = HashMap::new();
-            content.insert(keys.project_id.into(), keys.token_ids);
-            lock.insert(keys.keplr_wallet_pubkey.into(), content);
-            return Ok(());
-        }
-        if !lock[&keys.keplr_wallet_pubkey].contains_key(&keys.project_id) {
-            lock.get_mut(&keys.keplr_wallet_pubkey)
-                .expect("Failed to get data for customer keplr wallet")
-                .insert(keys.project_id.into(), keys.token_ids);
-            return Ok(());
-        }
-
-        let tokens = lock
-            .get_mut(&keys.keplr_wallet_pubkey)
-            .expect("Failed to get data for customer keplr wallet")
-            .get_mut(&keys.project_id)
-            .expect("Failed to get data from customer keplr wallet for project");
-        for t in &keys.token_ids {
-            tokens.push(t.into());
-        }
+        // Saved keys replace the previous ones, as in the database
+        lock.entry(keys.keplr_wallet_pubkey)
+            .or_default()
+            .insert(keys.project_id, keys.token_ids);
 
         Ok(())
     }
 
     async fn get_customer_keys(
         &self,
-        keplr_wallet_pubkey: &str,
-        project_id: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
     ) -> Result<CustomerKeys, SaveCustomerDataError> {
-        let lock = match self.data.lock() {
-            Ok(l) => l,
-            Err(e) => panic!("Failed to acquire lock on data repository: {:#?}", e),
-        };
+        let lock = self.data.read().await;
 
-        if !lock.contains_key(keplr_wallet_pubkey)
-            || !lock
-                .get(keplr_wallet_pubkey)
-                .unwrap()
-                .contains_key(project_id)
+        match lock
+            .get(keplr_wallet_pubkey)
+            .and_then(|projects| projects.get(project_id))
         {
-            return Err(SaveCustomerDataError::NotFound);
+            Some(tokens) => Ok(CustomerKeys {
+                keplr_wallet_pubkey: keplr_wallet_pubkey.clone(),
+                project_id: project_id.clone(),
+                token_ids: tokens.to_vec(),
+            }),
+            None => Err(SaveCustomerDataError::NotFound),
+        }
+    }
+
+    async fn save_authorized_sender(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        sender: &JunoAddress,
+    ) -> Result<(), SaveCustomerDataError> {
+        let mut lock = self.authorized_senders.write().await;
+
+        let senders = lock
+            .entry(format!("{keplr_wallet_pubkey}//{project_id}"))
+            .or_default();
+        if !senders.contains(sender) {
+            senders.push(sender.clone());
         }
 
-        let tokens = lock
-            .get(keplr_wallet_pubkey)
-            .unwrap()
-            .get(project_id)
-            .unwrap();
+        Ok(())
+    }
 
-        Ok(CustomerKeys {
-            keplr_wallet_pubkey: keplr_wallet_pubkey.into(),
-            project_id: project_id.into(),
-            token_ids: tokens.to_vec(),
-        })
+    async fn get_authorized_senders(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+    ) -> Result<Vec<JunoAddress>, SaveCustomerDataError> {
+        let lock = self.authorized_senders.read().await;
+
+        Ok(lock
+            .get(&format!("{keplr_wallet_pubkey}//{project_id}"))
+            .cloned()
+            .unwrap_or_default())
     }
 }
 
+#[derive(Clone)]
 pub struct InMemoryQueueManager {
-    pub queue: Mutex<HashMap<String, QueueItem>>,
+    queue: Arc<RwLock<HashMap<String, QueueItem>>>,
+    // Epoch milliseconds before which a retried item stays out of batches
+    retry_at: Arc<RwLock<HashMap<QueueItemId, i64>>>,
+    // Holder and epoch milliseconds the lock expires at, per project and token
+    token_locks: Arc<RwLock<HashMap<(StarknetAddress, TokenId), (String, i64)>>>,
+    // Epoch milliseconds an item was last marked as processing
+    processing_since: Arc<RwLock<HashMap<QueueItemId, i64>>>,
+    // Worker that last marked an item as processing
+    processing_by: Arc<RwLock<HashMap<QueueItemId, String>>>,
+    worker_id: String,
+    clock: Arc<dyn Clock>,
 }
 
 impl InMemoryQueueManager {
     pub fn new() -> Self {
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            queue: Arc::new(RwLock::new(HashMap::new())),
+            retry_at: Arc::new(RwLock::new(HashMap::new())),
+            token_locks: Arc::new(RwLock::new(HashMap::new())),
+            processing_since: Arc::new(RwLock::new(HashMap::new())),
+            processing_by: Arc::new(RwLock::new(HashMap::new())),
+            worker_id: "worker".into(),
+            clock,
+        }
+    }
+
+    /// Same queue, seen from another worker.
+    pub fn for_worker(&self, worker_id: &str) -> Self {
         Self {
-            queue: Mutex::new(HashMap::new()),
+            worker_id: worker_id.into(),
+            ..self.clone()
         }
     }
 
-    fn get_queue_identifier(pubkey: &str, project_id: &str, token: &str) -> String {
+    fn get_queue_identifier(
+        pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
+        token: &TokenId,
+    ) -> String {
         format!("{pubkey}//{project_id}//{token}")
     }
 }
@@ -228,78 +579,1580 @@ impl InMemoryQueueManager {
 impl QueueManager for InMemoryQueueManager {
     async fn enqueue(
         &self,
-        keplr_wallet_pubkey: &str,
-        starknet_wallet_pubkey: &str,
-        project_id: &str,
-        token_ids: Vec<String>,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
     ) -> Result<Vec<QueueItem>, QueueError> {
-        let mut lock = match self.queue.lock() {
-            Ok(l) => l,
-            Err(_) => panic!("Failed to acquire lock on queue"),
-        };
+        let mut lock = self.queue.write().await;
 
         let mut inserted_queue_items = Vec::new();
         for token in token_ids {
-            let qi = QueueItem::new(
+            // A token waiting to be minted keeps its item, as the postgres unique index does
+            if let Some(existing) = lock.values().find(|qi| {
+                qi.project_id == *project_id
+                    && qi.token_id == token
+                    && matches!(qi.status, QueueStatus::Pending | QueueStatus::Processing)
+            }) {
+                inserted_queue_items.push(existing.clone());
+                continue;
+            }
+            let mut qi = QueueItem::new(
                 keplr_wallet_pubkey,
                 starknet_wallet_pubkey,
                 project_id,
-                token.to_string(),
+                token.clone(),
             );
+            qi.id = Some(QueueItemId::new());
+            qi.request_id = request_id.map(String::from);
             lock.insert(
-                Self::get_queue_identifier(keplr_wallet_pubkey, project_id, token.as_str()),
+                Self::get_queue_identifier(keplr_wallet_pubkey, project_id, &token),
                 qi.clone(),
             );
-            inserted_queue_items.push(qi.clone());
+            inserted_queue_items.push(qi);
         }
 
         Ok(inserted_queue_items)
     }
 
-    async fn get_batch(&self) -> Result<Vec<QueueItem>, QueueError> {
-        let lock = match self.queue.lock() {
-            Ok(l) => l,
-            Err(_) => panic!("Failed to get lock on batch"),
-        };
-
-        let mut queue_items = Vec::new();
-        for (_keplr_pubkey, qi) in lock.iter() {
-            queue_items.push(qi.clone());
-        }
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let lock = self.queue.read().await;
+        let retry_at = self.retry_at.read().await;
+        let token_locks = self.token_locks.read().await;
+        let now = self.clock.now_ms();
 
-        Ok(queue_items)
+        Ok(lock
+            .values()
+            .filter(|qi| qi.transaction_hash.is_none())
+            .filter(|qi| !excluded_projects.contains(&qi.project_id))
+            .filter(|qi| {
+                qi.id
+                    .and_then(|id| retry_at.get(&id))
+                    .map_or(true, |at| *at <= now)
+            })
+            .filter(|qi| {
+                token_locks
+                    .get(&(qi.project_id.clone(), qi.token_id.clone()))
+                    .map_or(true, |(_, until)| *until <= now)
+            })
+            .cloned()
+            .collect())
     }
+
     async fn get_customer_migration_state(
         &self,
-        project_id: &str,
-        keplr_wallet_pubkey: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
     ) -> Vec<QueueItem> {
-        let lock = match self.queue.lock() {
-            Ok(l) => l,
-            Err(_) => panic!("Failed to acquire lock on resource"),
-        };
+        let lock = self.queue.read().await;
 
-        let mut queue_items = Vec::new();
-        for (id, qi) in lock.iter() {
-            if id
-                .as_str()
-                .starts_with(format!("{keplr_wallet_pubkey}//{project_id}").as_str())
-            {
-                continue;
-            }
+        lock.values()
+            .filter(|qi| {
+                qi.keplr_wallet_pubkey == *keplr_wallet_pubkey && qi.project_id == *project_id
+            })
+            .cloned()
+            .collect()
+    }
 
-            queue_items.push(qi.clone());
-        }
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let lock = self.queue.read().await;
+
+        // Items carry no creation date here, token ids keep the order stable
+        let mut items: Vec<QueueItem> = lock
+            .values()
+            .filter(|qi| qi.keplr_wallet_pubkey == *keplr_wallet_pubkey)
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| {
+            (a.project_id.as_str(), a.token_id.as_str())
+                .cmp(&(b.project_id.as_str(), b.token_id.as_str()))
+        });
 
-        queue_items
+        Ok(items)
     }
 
     async fn update_queue_items_status(
         &self,
-        ids: &Vec<String>,
+        ids: &[QueueItemId],
         transaction_hash: String,
         status: QueueStatus,
     ) -> Result<(), QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+        let mut processing_since = self.processing_since.write().await;
+        let mut processing_by = self.processing_by.write().await;
+
+        let mut updated = 0;
+        for qi in lock.values_mut() {
+            if qi.id.map_or(false, |id| ids.contains(&id)) {
+                qi.status = status.clone();
+                qi.transaction_hash = Some(transaction_hash.to_string());
+                qi.note = None;
+                qi.note_params.clear();
+                if let (Some(id), QueueStatus::Processing) = (qi.id, &status) {
+                    processing_since.insert(id, self.clock.now_ms());
+                    processing_by.insert(id, self.worker_id.clone());
+                }
+                updated += 1;
+            }
+        }
+
+        if updated != ids.len() {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+
+        Ok(())
+    }
+
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+        let mut processing_since = self.processing_since.write().await;
+        let mut processing_by = self.processing_by.write().await;
+
+        let mut claimed = Vec::new();
+        for qi in lock.values_mut() {
+            let Some(id) = qi.id.filter(|id| ids.contains(id)) else {
+                continue;
+            };
+            let held = match qi.status {
+                QueueStatus::Processing => processing_by.get(&id) == Some(&self.worker_id),
+                _ => qi.transaction_hash.is_none(),
+            };
+            if held {
+                qi.status = QueueStatus::Processing;
+                qi.transaction_hash = Some(String::new());
+                qi.note = None;
+                qi.note_params.clear();
+                processing_since.insert(id, self.clock.now_ms());
+                processing_by.insert(id, self.worker_id.clone());
+                claimed.push(id);
+            }
+        }
+
+        Ok(claimed)
+    }
+
+    async fn get_queue_item_history(
+        &self,
+        _id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError> {
+        Ok(Vec::new())
+    }
+
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+
+        for qi in lock.values_mut() {
+            if qi.id.map_or(false, |id| ids.contains(&id)) {
+                qi.status = QueueStatus::Pending;
+                qi.transaction_hash = None;
+                qi.note = Some(note.template.clone());
+                qi.note_params = note.params.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError> {
+        let lock = self.queue.read().await;
+
+        Ok(lock
+            .values()
+            .filter(|qi| matches!(qi.status, QueueStatus::Processing))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let lock = self.queue.read().await;
+        let processing_since = self.processing_since.read().await;
+        let processing_by = self.processing_by.read().await;
+
+        Ok(lock
+            .values()
+            .filter(|qi| matches!(qi.status, QueueStatus::Processing))
+            .filter(|qi| {
+                qi.id.map_or(false, |id| {
+                    processing_by.get(&id) == Some(&self.worker_id)
+                        || qi
+                            .transaction_hash
+                            .as_deref()
+                            .map_or(false, |h| !h.is_empty())
+                        || stale_before
+                            .zip(processing_since.get(&id))
+                            .map_or(false, |(before, since)| *since < before)
+                })
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let mut lock = self.queue.write().await;
+        let processing_since = self.processing_since.read().await;
+
+        let mut requeued = Vec::new();
+        for qi in lock.values_mut() {
+            let stale = matches!(qi.status, QueueStatus::Processing)
+                && qi.transaction_hash.as_deref().map_or(true, str::is_empty)
+                && qi
+                    .id
+                    .and_then(|id| processing_since.get(&id))
+                    .map_or(false, |since| *since < before);
+            if stale {
+                qi.status = QueueStatus::Pending;
+                qi.transaction_hash = None;
+                qi.note = Some(note.template.clone());
+                qi.note_params = note.params.clone();
+                requeued.push(qi.clone());
+            }
+        }
+
+        Ok(requeued)
+    }
+
+    async fn count_pending_items(&self) -> Result<i64, QueueError> {
+        let lock = self.queue.read().await;
+
+        Ok(lock
+            .values()
+            .filter(|qi| matches!(qi.status, QueueStatus::Pending))
+            .count() as i64)
+    }
+
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError> {
+        let lock = self.queue.read().await;
+
+        lock.values()
+            .find(|qi| qi.id.as_ref() == Some(id))
+            .cloned()
+            .ok_or(QueueError::NotFound)
+    }
+
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+
+        for qi in lock.values_mut() {
+            if qi.id.map_or(false, |id| ids.contains(&id)) {
+                qi.status = QueueStatus::Error;
+                qi.transaction_hash = qi.transaction_hash.take().or(Some(String::new()));
+                qi.note = Some(note.template.clone());
+                qi.note_params = note.params.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+        let mut retry_at = self.retry_at.write().await;
+        let at = self.clock.now_ms() + delay.as_millis() as i64;
+
+        for qi in lock.values_mut() {
+            if let Some(id) = qi.id.filter(|id| ids.contains(id)) {
+                qi.status = QueueStatus::Pending;
+                qi.transaction_hash = None;
+                qi.note = Some(note.template.clone());
+                qi.note_params = note.params.clone();
+                qi.attempts += 1;
+                retry_at.insert(id, at);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+        let mut retry_at = self.retry_at.write().await;
+
+        for qi in lock.values_mut() {
+            if let Some(id) = qi.id.filter(|id| ids.contains(id)) {
+                qi.status = QueueStatus::DeadLetter;
+                qi.transaction_hash = qi.transaction_hash.take().or(Some(String::new()));
+                qi.note = Some(note.template.clone());
+                qi.note_params = note.params.clone();
+                qi.attempts += 1;
+                retry_at.remove(&id);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+
+        let mut updated = 0;
+        for qi in lock.values_mut() {
+            if qi.id.map_or(false, |id| ids.contains(&id))
+                && matches!(qi.status, QueueStatus::DeadLetter)
+            {
+                qi.status = QueueStatus::Pending;
+                qi.transaction_hash = None;
+                qi.note = Some(note.template.clone());
+                qi.note_params = note.params.clone();
+                qi.attempts = 0;
+                updated += 1;
+            }
+        }
+
+        if updated != ids.len() {
+            return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+        }
+
         Ok(())
     }
+
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError> {
+        let lock = self.queue.read().await;
+
+        // Items carry no creation date here, ordering by id is enough for keyset paging
+        let mut rows: Vec<(Cursor, QueueItem)> = lock
+            .values()
+            .filter(|qi| match &status {
+                Some(s) => std::mem::discriminant(s) == std::mem::discriminant(&qi.status),
+                None => true,
+            })
+            .filter_map(|qi| qi.id.map(|id| (Cursor::new(0, *id.as_uuid()), qi.clone())))
+            .filter(|(c, _)| page.after.as_ref().map_or(true, |after| c.id > after.id))
+            .collect();
+        rows.sort_by(|(a, _), (b, _)| a.id.cmp(&b.id));
+        rows.truncate(page.limit as usize + 1);
+
+        Ok(Page::from_rows(rows, page.limit))
+    }
+
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError> {
+        Ok(Page::from_rows(Vec::new(), page.limit))
+    }
+
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError> {
+        let mut lock = self.queue.write().await;
+
+        for qi in lock.values_mut() {
+            if qi.id.map_or(false, |id| ids.contains(&id)) {
+                qi.estimated_fee = estimated_fee.map(String::from);
+                qi.actual_fee = actual_fee.map(String::from);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError> {
+        let mut token_locks = self.token_locks.write().await;
+        let now = self.clock.now_ms();
+
+        let mut locked = Vec::new();
+        for token in token_ids {
+            let key = (project_id.clone(), token.clone());
+            if token_locks
+                .get(&key)
+                .map_or(false, |(h, until)| h != holder && now < *until)
+            {
+                continue;
+            }
+            token_locks.insert(key, (holder.to_string(), now + ttl.as_millis() as i64));
+            locked.push(token.clone());
+        }
+
+        Ok(locked)
+    }
+
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError> {
+        let mut token_locks = self.token_locks.write().await;
+        for token in token_ids {
+            let key = (project_id.clone(), token.clone());
+            if token_locks.get(&key).map_or(false, |(h, _)| h == holder) {
+                token_locks.remove(&key);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl QueueSnapshotRepository for InMemoryQueueManager {
+    async fn load_queue(&self) -> Result<Vec<QueueSnapshotEntry>, QueueSnapshotError> {
+        let lock = self.queue.read().await;
+
+        // History is not kept in memory, only items are captured
+        let mut entries: Vec<QueueSnapshotEntry> = lock
+            .values()
+            .map(|qi| QueueSnapshotEntry {
+                item: qi.clone(),
+                history: Vec::new(),
+            })
+            .collect();
+        entries.sort_by_key(|e| e.item.id);
+
+        Ok(entries)
+    }
+
+    async fn restore_queue(
+        &self,
+        entries: &[QueueSnapshotEntry],
+    ) -> Result<(), QueueSnapshotError> {
+        let mut lock = self.queue.write().await;
+
+        let known: HashSet<QueueItemId> = lock.values().filter_map(|qi| qi.id).collect();
+        for entry in entries {
+            match entry.item.id {
+                Some(id) if known.contains(&id) => {
+                    return Err(QueueSnapshotError::PersistenceIssue(format!(
+                        "Queue item {id} already exists"
+                    )))
+                }
+                Some(_) => {}
+                None => {
+                    return Err(QueueSnapshotError::PersistenceIssue(
+                        "Queue item without id".into(),
+                    ))
+                }
+            }
+        }
+
+        for entry in entries {
+            let qi = &entry.item;
+            lock.insert(
+                Self::get_queue_identifier(&qi.keplr_wallet_pubkey, &qi.project_id, &qi.token_id),
+                qi.clone(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryWalletLinkRepository {
+    links: Arc<RwLock<HashMap<JunoAddress, StarknetAddress>>>,
+    unavailable: Arc<AtomicBool>,
+}
+
+impl InMemoryWalletLinkRepository {
+    pub fn new() -> Self {
+        Self {
+            links: Arc::new(RwLock::new(HashMap::new())),
+            unavailable: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Simulates a database outage, reads and implicit links fail.
+    pub fn make_unavailable(&self, unavailable: bool) {
+        self.unavailable.store(unavailable, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl WalletLinkRepository for InMemoryWalletLinkRepository {
+    async fn get_link(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<WalletLink, WalletLinkError> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            return Err(WalletLinkError::FailedToFetch);
+        }
+        let lock = self.links.read().await;
+
+        match lock.get(keplr_wallet_pubkey) {
+            Some(addr) => Ok(WalletLink {
+                keplr_wallet_pubkey: keplr_wallet_pubkey.clone(),
+                starknet_account_addr: addr.clone(),
+            }),
+            None => Err(WalletLinkError::NotFound),
+        }
+    }
+
+    async fn save_link(&self, link: WalletLink) -> Result<(), WalletLinkError> {
+        let mut lock = self.links.write().await;
+        lock.insert(link.keplr_wallet_pubkey, link.starknet_account_addr);
+
+        Ok(())
+    }
+
+    async fn link_if_absent(&self, link: WalletLink) -> Result<WalletLink, WalletLinkError> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            return Err(WalletLinkError::FailedToPersist);
+        }
+        let mut lock = self.links.write().await;
+        let starknet_account_addr = lock
+            .entry(link.keplr_wallet_pubkey.clone())
+            .or_insert(link.starknet_account_addr)
+            .clone();
+
+        Ok(WalletLink {
+            keplr_wallet_pubkey: link.keplr_wallet_pubkey,
+            starknet_account_addr,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryChallengeRepository {
+    // Nonce to challenge and whether it has been consumed
+    challenges: Arc<RwLock<HashMap<String, (Challenge, bool)>>>,
+}
+
+impl InMemoryChallengeRepository {
+    pub fn new() -> Self {
+        Self {
+            challenges: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl ChallengeRepository for InMemoryChallengeRepository {
+    async fn save_challenge(&self, challenge: &Challenge) -> Result<(), ChallengeError> {
+        let mut lock = self.challenges.write().await;
+        lock.insert(challenge.nonce.clone(), (challenge.clone(), false));
+
+        Ok(())
+    }
+
+    async fn consume_challenge(
+        &self,
+        nonce: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Challenge, ChallengeError> {
+        let mut lock = self.challenges.write().await;
+        match lock.get_mut(nonce) {
+            None => Err(ChallengeError::NotFound),
+            Some((challenge, _)) if &challenge.keplr_wallet_pubkey != keplr_wallet_pubkey => {
+                Err(ChallengeError::NotFound)
+            }
+            Some((_, true)) => Err(ChallengeError::AlreadyUsed),
+            Some((challenge, used)) => {
+                *used = true;
+                Ok(challenge.clone())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryBatchAnalyticsRepository {
+    batches: Arc<RwLock<Vec<BatchRecord>>>,
+}
+
+impl InMemoryBatchAnalyticsRepository {
+    pub fn new() -> Self {
+        Self {
+            batches: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchAnalyticsRepository for InMemoryBatchAnalyticsRepository {
+    async fn save_batch(&self, record: &BatchRecord) -> Result<(), BatchAnalyticsError> {
+        self.batches.write().await.push(record.clone());
+
+        Ok(())
+    }
+
+    async fn get_batches(
+        &self,
+        query: &BatchAnalyticsQuery,
+    ) -> Result<Vec<BatchRecord>, BatchAnalyticsError> {
+        let lock = self.batches.read().await;
+
+        Ok(lock
+            .iter()
+            .rev()
+            .filter(|b| query.from.map_or(true, |from| from <= b.submitted_at))
+            .filter(|b| query.to.map_or(true, |to| b.submitted_at < to))
+            .filter(|b| {
+                query
+                    .project_id
+                    .as_ref()
+                    .map_or(true, |p| p == &b.project_id)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_batches_before(&self, before: i64) -> Result<u64, BatchAnalyticsError> {
+        let mut lock = self.batches.write().await;
+        let count = lock.len();
+        lock.retain(|b| before <= b.submitted_at);
+
+        Ok((count - lock.len()) as u64)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryFunnelEventRepository {
+    events: Arc<RwLock<Vec<FunnelEvent>>>,
+}
+
+impl InMemoryFunnelEventRepository {
+    pub fn new() -> Self {
+        Self {
+            events: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub async fn events(&self) -> Vec<FunnelEvent> {
+        self.events.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl FunnelEventRepository for InMemoryFunnelEventRepository {
+    async fn save_event(&self, event: &FunnelEvent) -> Result<(), FunnelEventError> {
+        self.events.write().await.push(event.clone());
+
+        Ok(())
+    }
+
+    async fn delete_events_before(&self, before: i64) -> Result<u64, FunnelEventError> {
+        let mut lock = self.events.write().await;
+        let count = lock.len();
+        lock.retain(|e| before <= e.created_at);
+
+        Ok((count - lock.len()) as u64)
+    }
+}
+
+/// Table stats as set by tests, with rows expiring at given epoch milliseconds.
+#[derive(Debug, Clone)]
+pub struct InMemoryMaintenanceRepository {
+    tables: Arc<RwLock<Vec<TableStats>>>,
+    expiring_rows: Arc<RwLock<Vec<i64>>>,
+}
+
+impl InMemoryMaintenanceRepository {
+    pub fn new() -> Self {
+        Self {
+            tables: Arc::new(RwLock::new(Vec::new())),
+            expiring_rows: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub async fn set_table_stats(&self, stats: TableStats) {
+        let mut lock = self.tables.write().await;
+        lock.retain(|t| t.table != stats.table);
+        lock.push(stats);
+    }
+
+    pub async fn add_expiring_row(&self, expires_at: i64) {
+        self.expiring_rows.write().await.push(expires_at);
+    }
+
+    pub async fn rows_left(&self) -> usize {
+        self.expiring_rows.read().await.len()
+    }
+}
+
+#[async_trait]
+impl MaintenanceRepository for InMemoryMaintenanceRepository {
+    async fn table_stats(&self, tables: &[&str]) -> Result<Vec<TableStats>, MaintenanceError> {
+        Ok(self
+            .tables
+            .read()
+            .await
+            .iter()
+            .filter(|t| tables.contains(&t.table.as_str()))
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_expired_rows(&self, now: i64) -> Result<u64, MaintenanceError> {
+        let mut lock = self.expiring_rows.write().await;
+        let count = lock.len();
+        lock.retain(|expires_at| now <= *expires_at);
+
+        Ok((count - lock.len()) as u64)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryBatchSizeRepository {
+    batch_sizes: Arc<RwLock<HashMap<StarknetAddress, ProjectBatchSize>>>,
+}
+
+impl InMemoryBatchSizeRepository {
+    pub fn new() -> Self {
+        Self {
+            batch_sizes: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl BatchSizeRepository for InMemoryBatchSizeRepository {
+    async fn get_batch_size(
+        &self,
+        project_id: &StarknetAddress,
+    ) -> Result<Option<ProjectBatchSize>, BatchSizeError> {
+        Ok(self.batch_sizes.read().await.get(project_id).cloned())
+    }
+
+    async fn save_batch_size(&self, batch_size: &ProjectBatchSize) -> Result<(), BatchSizeError> {
+        let mut lock = self.batch_sizes.write().await;
+        lock.insert(batch_size.project_id.clone(), batch_size.clone());
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryAuditRepository {
+    bridge_requests: Arc<RwLock<Vec<BridgeRequestAudit>>>,
+}
+
+impl InMemoryAuditRepository {
+    pub fn new() -> Self {
+        Self {
+            bridge_requests: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryAuditRepository {
+    async fn save_bridge_request(&self, audit: &BridgeRequestAudit) -> Result<(), AuditError> {
+        self.bridge_requests.write().await.push(audit.clone());
+
+        Ok(())
+    }
+
+    async fn get_bridge_requests(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<BridgeRequestAudit>, AuditError> {
+        let lock = self.bridge_requests.read().await;
+
+        Ok(lock
+            .iter()
+            .rev()
+            .filter(|a| &a.keplr_wallet_pubkey == keplr_wallet_pubkey)
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryBreakglassRepository {
+    mints: Arc<RwLock<HashMap<Uuid, BreakglassMint>>>,
+}
+
+impl InMemoryBreakglassRepository {
+    pub fn new() -> Self {
+        Self {
+            mints: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl BreakglassRepository for InMemoryBreakglassRepository {
+    async fn save(&self, mint: &BreakglassMint) -> Result<(), BreakglassError> {
+        let mut lock = self.mints.write().await;
+        lock.insert(mint.id, mint.clone());
+
+        Ok(())
+    }
+
+    async fn get(&self, id: &Uuid) -> Result<BreakglassMint, BreakglassError> {
+        let lock = self.mints.read().await;
+
+        match lock.get(id) {
+            Some(m) => Ok(m.clone()),
+            None => Err(BreakglassError::NotFound),
+        }
+    }
+
+    async fn claim_confirmation(
+        &self,
+        id: &Uuid,
+        confirmed_by: &str,
+    ) -> Result<BreakglassMint, BreakglassError> {
+        let mut lock = self.mints.write().await;
+
+        match lock.get_mut(id) {
+            Some(m) if BreakglassStatus::AwaitingConfirmation == m.status => {
+                m.status = BreakglassStatus::Confirming;
+                m.confirmed_by = Some(confirmed_by.to_string());
+                Ok(m.clone())
+            }
+            Some(_) => Err(BreakglassError::AlreadyHandled),
+            None => Err(BreakglassError::NotFound),
+        }
+    }
+}
+
+/// Stats computed over an always empty history.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryStatsRepository {}
+
+#[async_trait]
+impl StatsRepository for InMemoryStatsRepository {
+    async fn get_status_transitions(
+        &self,
+        _range: &TimeRange,
+        _interval_ms: i64,
+    ) -> Result<Vec<StatusTransition>, StatsError> {
+        Ok(Vec::new())
+    }
+
+    async fn get_project_progress(
+        &self,
+        _range: &TimeRange,
+    ) -> Result<Vec<ProjectProgress>, StatsError> {
+        Ok(Vec::new())
+    }
+
+    async fn get_project_completions(&self) -> Result<Vec<ProjectCompletion>, StatsError> {
+        Ok(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryReportRepository {
+    reports: Arc<RwLock<HashMap<String, SignedDailyReport>>>,
+}
+
+impl InMemoryReportRepository {
+    pub fn new() -> Self {
+        Self {
+            reports: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl ReportRepository for InMemoryReportRepository {
+    async fn get_daily_activity(&self, _range: &TimeRange) -> Result<DailyActivity, ReportError> {
+        Ok(DailyActivity::default())
+    }
+
+    async fn save_report(&self, report: &SignedDailyReport) -> Result<(), ReportError> {
+        let mut lock = self.reports.write().await;
+        lock.insert(report.report.day.to_string(), report.clone());
+
+        Ok(())
+    }
+
+    async fn get_report(&self, day: &str) -> Result<SignedDailyReport, ReportError> {
+        let lock = self.reports.read().await;
+
+        lock.get(day).cloned().ok_or(ReportError::NotFound)
+    }
+
+    async fn list_report_days(&self) -> Result<Vec<String>, ReportError> {
+        let lock = self.reports.read().await;
+
+        let mut days: Vec<String> = lock.keys().cloned().collect();
+        days.sort_by(|a, b| b.cmp(a));
+        Ok(days)
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryPostMintExecutionRepository {
+    executions: Arc<RwLock<HashMap<Uuid, PostMintExecution>>>,
+}
+
+impl InMemoryPostMintExecutionRepository {
+    pub fn new() -> Self {
+        Self {
+            executions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl PostMintExecutionRepository for InMemoryPostMintExecutionRepository {
+    async fn save(&self, execution: &PostMintExecution) -> Result<(), PostMintError> {
+        let mut lock = self.executions.write().await;
+        lock.insert(execution.id, execution.clone());
+
+        Ok(())
+    }
+
+    async fn get_pending(&self) -> Result<Vec<PostMintExecution>, PostMintError> {
+        let lock = self.executions.read().await;
+
+        Ok(lock
+            .values()
+            .filter(|e| PostMintStatus::Pending == e.status)
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryWebhookRepository {
+    subscriptions: Arc<RwLock<Vec<WebhookSubscription>>>,
+    deliveries: Arc<RwLock<HashMap<Uuid, WebhookDelivery>>>,
+    attempts: Arc<RwLock<Vec<WebhookDeliveryAttempt>>>,
+}
+
+impl InMemoryWebhookRepository {
+    pub fn new() -> Self {
+        Self {
+            subscriptions: Arc::new(RwLock::new(Vec::new())),
+            deliveries: Arc::new(RwLock::new(HashMap::new())),
+            attempts: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub async fn deliveries(&self) -> Vec<WebhookDelivery> {
+        self.deliveries.read().await.values().cloned().collect()
+    }
+
+    pub async fn attempts(&self) -> Vec<WebhookDeliveryAttempt> {
+        self.attempts.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for InMemoryWebhookRepository {
+    async fn save_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+    ) -> Result<(), WebhookError> {
+        let mut lock = self.subscriptions.write().await;
+        match lock.iter_mut().find(|s| s.id == subscription.id) {
+            Some(existing) => existing.events = subscription.events.clone(),
+            None => lock.push(subscription.clone()),
+        }
+
+        Ok(())
+    }
+
+    async fn get_subscriptions(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<WebhookSubscription>, WebhookError> {
+        let lock = self.subscriptions.read().await;
+
+        Ok(lock
+            .iter()
+            .filter(|s| {
+                s.keplr_wallet_pubkey
+                    .as_ref()
+                    .map_or(true, |k| k == keplr_wallet_pubkey)
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_subscription(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<WebhookSubscription>, WebhookError> {
+        let lock = self.subscriptions.read().await;
+
+        Ok(lock.iter().find(|s| s.id == id).cloned())
+    }
+
+    async fn find_subscription(
+        &self,
+        keplr_wallet_pubkey: Option<&JunoAddress>,
+        callback_url: &str,
+    ) -> Result<Option<WebhookSubscription>, WebhookError> {
+        let lock = self.subscriptions.read().await;
+
+        Ok(lock
+            .iter()
+            .find(|s| {
+                s.keplr_wallet_pubkey.as_ref() == keplr_wallet_pubkey
+                    && s.callback_url == callback_url
+            })
+            .cloned())
+    }
+
+    async fn list_subscriptions(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<WebhookSubscription>, WebhookError> {
+        let lock = self.subscriptions.read().await;
+
+        Ok(lock
+            .iter()
+            .filter(|s| s.keplr_wallet_pubkey.as_ref() == Some(keplr_wallet_pubkey))
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_subscription(&self, id: Uuid) -> Result<(), WebhookError> {
+        self.subscriptions.write().await.retain(|s| s.id != id);
+        self.deliveries
+            .write()
+            .await
+            .retain(|_, d| d.subscription_id != id);
+
+        Ok(())
+    }
+
+    async fn save_delivery(&self, delivery: &WebhookDelivery) -> Result<(), WebhookError> {
+        let mut lock = self.deliveries.write().await;
+        lock.insert(delivery.id, delivery.clone());
+
+        Ok(())
+    }
+
+    async fn get_due_deliveries(&self, now_ms: i64) -> Result<Vec<WebhookDelivery>, WebhookError> {
+        let subscriptions = self.subscriptions.read().await;
+        let lock = self.deliveries.read().await;
+
+        Ok(lock
+            .values()
+            .filter(|d| WebhookDeliveryStatus::Pending == d.status && d.next_attempt_at <= now_ms)
+            .map(|d| WebhookDelivery {
+                signing_secret: subscriptions
+                    .iter()
+                    .find(|s| s.id == d.subscription_id)
+                    .and_then(|s| s.secret.clone()),
+                ..d.clone()
+            })
+            .collect())
+    }
+
+    async fn log_attempt(&self, attempt: &WebhookDeliveryAttempt) -> Result<(), WebhookError> {
+        self.attempts.write().await.push(attempt.clone());
+
+        Ok(())
+    }
+}
+
+/// Records payloads instead of posting them, callbacks can be made to fail.
+#[derive(Debug, Clone)]
+pub struct InMemoryWebhookSender {
+    sent: Arc<RwLock<Vec<(String, WebhookPayload)>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl InMemoryWebhookSender {
+    pub fn new() -> Self {
+        Self {
+            sent: Arc::new(RwLock::new(Vec::new())),
+            failing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn fail_deliveries(&self, fail: bool) {
+        self.failing.store(fail, Ordering::SeqCst);
+    }
+
+    /// Callback url and payload of every successful call.
+    pub async fn sent(&self) -> Vec<(String, WebhookPayload)> {
+        self.sent.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl WebhookSender for InMemoryWebhookSender {
+    async fn send(
+        &self,
+        delivery: &WebhookDelivery,
+        _timestamp: i64,
+    ) -> Result<(), WebhookSendError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(WebhookSendError::Rejected(503));
+        }
+        self.sent
+            .write()
+            .await
+            .push((delivery.callback_url.to_string(), delivery.payload.clone()));
+
+        Ok(())
+    }
+}
+
+/// Clock only moving when told to, so expiry can be exercised without sleeping.
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now_ms: Arc<AtomicI64>,
+}
+
+impl ManualClock {
+    pub fn new(now_ms: i64) -> Self {
+        Self {
+            now_ms: Arc::new(AtomicI64::new(now_ms)),
+        }
+    }
+
+    pub fn set(&self, now_ms: i64) {
+        self.now_ms.store(now_ms, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now_ms
+            .fetch_add(duration.as_millis() as i64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ms(&self) -> i64 {
+        self.now_ms.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryTransferProofRepository {
+    proofs: Arc<RwLock<HashMap<(ProjectId, TokenId), TransferProof>>>,
+}
+
+impl InMemoryTransferProofRepository {
+    pub fn new() -> Self {
+        Self {
+            proofs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl TransferProofRepository for InMemoryTransferProofRepository {
+    async fn get_proof(
+        &self,
+        project_id: &ProjectId,
+        token_id: &TokenId,
+    ) -> Result<Option<TransferProof>, TransferProofError> {
+        let lock = self.proofs.read().await;
+
+        Ok(lock.get(&(project_id.clone(), token_id.clone())).cloned())
+    }
+
+    async fn get_proofs(
+        &self,
+        project_id: &ProjectId,
+        token_ids: &[TokenId],
+    ) -> Result<Vec<TransferProof>, TransferProofError> {
+        let lock = self.proofs.read().await;
+
+        Ok(token_ids
+            .iter()
+            .filter_map(|t| lock.get(&(project_id.clone(), t.clone())).cloned())
+            .collect())
+    }
+
+    async fn save_proof(&self, proof: &TransferProof) -> Result<(), TransferProofError> {
+        self.proofs
+            .write()
+            .await
+            .entry((proof.project_id.clone(), proof.token_id.clone()))
+            .or_insert_with(|| proof.clone());
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryReverseQueueManager {
+    items: Arc<RwLock<Vec<ReverseQueueItem>>>,
+}
+
+impl InMemoryReverseQueueManager {
+    pub fn new() -> Self {
+        Self {
+            items: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl ReverseQueueManager for InMemoryReverseQueueManager {
+    async fn enqueue(
+        &self,
+        items: Vec<ReverseQueueItem>,
+    ) -> Result<Vec<ReverseQueueItem>, ReverseQueueError> {
+        let mut lock = self.items.write().await;
+        for item in &items {
+            if lock.iter().any(|i| {
+                i.project_id == item.project_id
+                    && i.token_id == item.token_id
+                    && i.starknet_transaction_hash == item.starknet_transaction_hash
+            }) {
+                return Err(ReverseQueueError::AlreadyRequested(item.token_id.clone()));
+            }
+        }
+        lock.extend(items.iter().cloned());
+
+        Ok(items)
+    }
+
+    async fn get_batch(&self, limit: usize) -> Result<Vec<ReverseQueueItem>, ReverseQueueError> {
+        let lock = self.items.read().await;
+
+        Ok(lock
+            .iter()
+            .filter(|i| i.status == ReverseQueueStatus::Pending)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_status(
+        &self,
+        ids: &[Uuid],
+        status: ReverseQueueStatus,
+        juno_transaction_hash: Option<String>,
+        note: Option<String>,
+    ) -> Result<(), ReverseQueueError> {
+        let mut lock = self.items.write().await;
+        for item in lock.iter_mut().filter(|i| ids.contains(&i.id)) {
+            item.status = status;
+            item.juno_transaction_hash = juno_transaction_hash.clone();
+            item.note = note.clone();
+        }
+
+        Ok(())
+    }
+
+    async fn get_customer_state(
+        &self,
+        starknet_account_addr: &StarknetAddress,
+        starknet_project_id: &StarknetAddress,
+    ) -> Result<Vec<ReverseQueueItem>, ReverseQueueError> {
+        let lock = self.items.read().await;
+
+        Ok(lock
+            .iter()
+            .filter(|i| {
+                &i.starknet_account_addr == starknet_account_addr
+                    && &i.starknet_project_id == starknet_project_id
+            })
+            .cloned()
+            .collect())
+    }
+}
+
+/// Fake sequencer, only transactions registered here are accepted.
+#[derive(Debug, Clone)]
+pub struct InMemoryStarknetTransferVerifier {
+    transfers: Arc<RwLock<HashMap<String, Vec<StarknetTokenTransfer>>>>,
+}
+
+impl InMemoryStarknetTransferVerifier {
+    pub fn new() -> Self {
+        Self {
+            transfers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn add_transfer(&self, transaction_hash: &str, transfer: StarknetTokenTransfer) {
+        self.transfers
+            .write()
+            .await
+            .entry(transaction_hash.into())
+            .or_default()
+            .push(transfer);
+    }
+}
+
+#[async_trait]
+impl StarknetTransferVerifier for InMemoryStarknetTransferVerifier {
+    async fn get_token_transfers(
+        &self,
+        transaction_hash: &str,
+    ) -> Result<Vec<StarknetTokenTransfer>, StarknetTransferError> {
+        match self.transfers.read().await.get(transaction_hash) {
+            Some(t) => Ok(t.clone()),
+            None => Err(StarknetTransferError::NotAccepted),
+        }
+    }
+}
+
+/// Juno transfer sent by the fake broadcaster.
+#[derive(Debug, Clone)]
+pub struct BroadcastTransfer {
+    pub contract: ProjectId,
+    pub recipient: JunoAddress,
+    pub token_ids: Vec<TokenId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryJunoTxBroadcaster {
+    transfers: Arc<RwLock<Vec<BroadcastTransfer>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl InMemoryJunoTxBroadcaster {
+    pub fn new() -> Self {
+        Self {
+            transfers: Arc::new(RwLock::new(Vec::new())),
+            failing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Makes the fake node reject every transfer.
+    pub fn fail_transfers(&self, fail: bool) {
+        self.failing.store(fail, Ordering::SeqCst);
+    }
+
+    pub async fn transfers(&self) -> Vec<BroadcastTransfer> {
+        self.transfers.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl JunoTxBroadcaster for InMemoryJunoTxBroadcaster {
+    async fn transfer_tokens(
+        &self,
+        contract: &ProjectId,
+        recipient: &JunoAddress,
+        token_ids: &[TokenId],
+    ) -> Result<String, JunoBroadcastError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(JunoBroadcastError::Rejected("out of gas".into()));
+        }
+        let mut lock = self.transfers.write().await;
+        lock.push(BroadcastTransfer {
+            contract: contract.clone(),
+            recipient: recipient.clone(),
+            token_ids: token_ids.to_vec(),
+        });
+
+        Ok(format!("JUNOTX{}", lock.len()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryIssueRecordRepository {
+    records: Arc<RwLock<Vec<IssueRecord>>>,
+}
+
+impl InMemoryIssueRecordRepository {
+    pub fn new() -> Self {
+        Self {
+            records: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl IssueRecordRepository for InMemoryIssueRecordRepository {
+    async fn get_reported_subjects(
+        &self,
+        subjects: &[String],
+    ) -> Result<Vec<String>, IssueTrackerError> {
+        let lock = self.records.read().await;
+
+        Ok(lock
+            .iter()
+            .filter(|r| subjects.contains(&r.subject))
+            .map(|r| r.subject.to_string())
+            .collect())
+    }
+
+    async fn save_records(&self, records: &[IssueRecord]) -> Result<(), IssueTrackerError> {
+        let mut lock = self.records.write().await;
+        for record in records {
+            if !lock.iter().any(|r| r.subject == record.subject) {
+                lock.push(record.clone());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps opened issues instead of calling a tracker, the tracker can be made to fail.
+#[derive(Debug, Clone)]
+pub struct InMemoryIssueTracker {
+    issues: Arc<RwLock<Vec<Issue>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl InMemoryIssueTracker {
+    pub fn new() -> Self {
+        Self {
+            issues: Arc::new(RwLock::new(Vec::new())),
+            failing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn fail_issues(&self, fail: bool) {
+        self.failing.store(fail, Ordering::SeqCst);
+    }
+
+    pub async fn issues(&self) -> Vec<Issue> {
+        self.issues.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl IssueTracker for InMemoryIssueTracker {
+    async fn create_issue(&self, issue: &Issue) -> Result<String, IssueTrackerError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(IssueTrackerError::Rejected(503));
+        }
+        let mut lock = self.issues.write().await;
+        lock.push(issue.clone());
+
+        Ok(format!("https://issues.test/{}", lock.len()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InMemoryCheckResultRepository {
+    results: Arc<RwLock<Vec<CheckResult>>>,
+}
+
+impl InMemoryCheckResultRepository {
+    pub fn new() -> Self {
+        Self {
+            results: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl CheckResultRepository for InMemoryCheckResultRepository {
+    async fn get_results(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        token_ids: &[TokenId],
+        since: i64,
+    ) -> Result<Vec<CheckResult>, CheckCacheError> {
+        let lock = self.results.read().await;
+        let mut latest: HashMap<TokenId, CheckResult> = HashMap::new();
+        for result in lock.iter().filter(|r| {
+            &r.keplr_wallet_pubkey == keplr_wallet_pubkey
+                && &r.project_id == project_id
+                && token_ids.contains(&r.token_id)
+                && since <= r.checked_at
+        }) {
+            match latest.get(&result.token_id) {
+                Some(l) if result.checked_at <= l.checked_at => (),
+                _ => {
+                    latest.insert(result.token_id.clone(), result.clone());
+                }
+            }
+        }
+
+        Ok(latest.into_values().collect())
+    }
+
+    async fn save_results(
+        &self,
+        results: &[CheckResult],
+        expired_before: i64,
+    ) -> Result<(), CheckCacheError> {
+        let mut lock = self.results.write().await;
+        lock.retain(|r| expired_before <= r.checked_at);
+        for result in results {
+            lock.retain(|r| {
+                r.keplr_wallet_pubkey != result.keplr_wallet_pubkey
+                    || r.project_id != result.project_id
+                    || r.token_id != result.token_id
+                    || r.height != result.height
+            });
+            lock.push(result.clone());
+        }
+
+        Ok(())
+    }
+}
+
+/// Broker delivering published messages in order through a channel.
+#[derive(Debug, Clone)]
+pub struct InMemoryQueueBroker {
+    sender: mpsc::UnboundedSender<Vec<QueueItemId>>,
+    receiver: Arc<Mutex<mpsc::UnboundedReceiver<Vec<QueueItemId>>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl InMemoryQueueBroker {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            receiver: Arc::new(Mutex::new(receiver)),
+            failing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Makes the fake broker refuse every message.
+    pub fn fail_publishing(&self, fail: bool) {
+        self.failing.store(fail, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl QueueBroker for InMemoryQueueBroker {
+    async fn publish(&self, ids: &[QueueItemId]) -> Result<(), QueueBrokerError> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err(QueueBrokerError::PublishFailed);
+        }
+        self.sender
+            .send(ids.to_vec())
+            .map_err(|_| QueueBrokerError::Closed)
+    }
+
+    async fn next_delivery(&self) -> Result<Vec<QueueItemId>, QueueBrokerError> {
+        self.receiver
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(QueueBrokerError::Closed)
+    }
 }
@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{json, Value};
+
+use crate::domain::issue_tracker::{Issue, IssueTracker, IssueTrackerError};
+
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+pub const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+
+// Both trackers cap descriptions around 65k characters, larger bundles are cut
+const MAX_ATTACHMENT_CHARS: usize = 50_000;
+
+/// Issue body with its attachment inlined as a JSON block, neither API accepts files.
+fn body_with_attachment(issue: &Issue) -> String {
+    let Some(attachment) = &issue.attachment else {
+        return issue.body.to_string();
+    };
+    let mut content: String = attachment
+        .content
+        .chars()
+        .take(MAX_ATTACHMENT_CHARS)
+        .collect();
+    if content.len() < attachment.content.len() {
+        content.push_str("\n... truncated, download the full bundle from the admin api");
+    }
+
+    format!(
+        "{}\n<details><summary>{}</summary>\n\n```json\n{}\n```\n</details>\n",
+        issue.body, attachment.name, content
+    )
+}
+
+/// Opens GitHub issues on `owner/repo` with a token allowed to write issues.
+pub struct GithubIssueTracker {
+    api_url: String,
+    repository: String,
+    token: String,
+    client: Client,
+}
+
+impl GithubIssueTracker {
+    pub fn new(api_url: &str, repository: &str, token: &str, client: Client) -> Self {
+        Self {
+            api_url: api_url.trim_end_matches('/').into(),
+            repository: repository.into(),
+            token: token.into(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl IssueTracker for GithubIssueTracker {
+    async fn create_issue(&self, issue: &Issue) -> Result<String, IssueTrackerError> {
+        let response = self
+            .client
+            .post(format!("{}/repos/{}/issues", self.api_url, self.repository))
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, "bridge-juno-to-starknet")
+            .json(&json!({
+                "title": issue.title,
+                "body": body_with_attachment(issue),
+                "labels": issue.labels,
+            }))
+            .send()
+            .await
+            .map_err(|e| IssueTrackerError::Unreachable(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(IssueTrackerError::Rejected(response.status().as_u16()));
+        }
+
+        let created: Value = response
+            .json()
+            .await
+            .map_err(|e| IssueTrackerError::Unreachable(e.to_string()))?;
+        match created["html_url"].as_str() {
+            Some(url) => Ok(url.to_string()),
+            None => Err(IssueTrackerError::Unreachable(
+                "GitHub response has no issue url".into(),
+            )),
+        }
+    }
+}
+
+/// Opens Linear issues in the team `team_id` with a personal or OAuth api key. Labels
+/// are Linear ids, they are not set.
+pub struct LinearIssueTracker {
+    api_url: String,
+    team_id: String,
+    api_key: String,
+    client: Client,
+}
+
+impl LinearIssueTracker {
+    pub fn new(api_url: &str, team_id: &str, api_key: &str, client: Client) -> Self {
+        Self {
+            api_url: api_url.into(),
+            team_id: team_id.into(),
+            api_key: api_key.into(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl IssueTracker for LinearIssueTracker {
+    async fn create_issue(&self, issue: &Issue) -> Result<String, IssueTrackerError> {
+        let response = self
+            .client
+            .post(&self.api_url)
+            .header(reqwest::header::AUTHORIZATION, &self.api_key)
+            .json(&json!({
+                "query": "mutation IssueCreate($input: IssueCreateInput!) { issueCreate(input: $input) { success issue { url } } }",
+                "variables": {
+                    "input": {
+                        "teamId": self.team_id,
+                        "title": issue.title,
+                        "description": body_with_attachment(issue),
+                    }
+                }
+            }))
+            .send()
+            .await
+            .map_err(|e| IssueTrackerError::Unreachable(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(IssueTrackerError::Rejected(response.status().as_u16()));
+        }
+
+        // GraphQL errors come with a 200 status
+        let created: Value = response
+            .json()
+            .await
+            .map_err(|e| IssueTrackerError::Unreachable(e.to_string()))?;
+        match created["data"]["issueCreate"]["issue"]["url"].as_str() {
+            Some(url) => Ok(url.to_string()),
+            None => Err(IssueTrackerError::Unreachable(format!(
+                "Linear did not create the issue {}",
+                created["errors"]
+            ))),
+        }
+    }
+}
@@ -1,84 +1,311 @@
 use async_trait::async_trait;
-use log::error;
-use reqwest::Response;
+use base64::{engine::general_purpose, Engine};
+use log::{error, info, warn};
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
 use serde_derive::{Deserialize, Serialize};
-use std::thread::sleep;
+use serde_json::{json, Value};
 use std::time::Duration;
+use tokio::time::sleep;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
-use crate::domain::bridge::{MsgTypes, Transaction, TransactionFetchError, TransactionRepository};
+use crate::domain::{
+    bridge::{JunoContractQuerier, Transaction, TransactionFetchError, TransactionRepository},
+    calendar::parse_timestamp,
+    health::{HealthCheck, HealthCheckError},
+    ids::{JunoAddress, ProjectId, TokenId},
+    reverse_bridge::{JunoBroadcastError, JunoTxBroadcaster},
+    warm_up::{WarmUp, WarmUpError},
+};
 
-const MAX_RETRY: i32 = 5;
+const PAGE_SIZE: usize = 100;
+// Largest page CW721 contracts answer to a tokens query
+const TOKENS_PAGE_SIZE: usize = 30;
+
+/// Bounds retries of Juno LCD calls that could not reach it. Waits grow exponentially
+/// and are jittered, so instances do not all hit a recovering LCD at once.
+#[derive(Debug, Clone)]
+pub struct JunoRetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Time an attempt has to get its response, slower ones count as unreachable
+    pub timeout: Duration,
+}
+
+impl JunoRetryPolicy {
+    /// Wait given the number of attempts that already failed, between half and all of
+    /// the exponential backoff.
+    pub fn delay(&self, failed_attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(failed_attempts.saturating_sub(1).min(31));
+        let backoff = self.base_delay.saturating_mul(factor).min(self.max_delay);
+
+        backoff / 2 + backoff.mul_f64(jitter()) / 2
+    }
+}
+
+impl Default for JunoRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(30),
+            timeout: Duration::from_secs(120),
+        }
+    }
+}
+
+// Random draw in [0, 1] from a v4 uuid, good enough to spread retries without another dependency
+fn jitter() -> f64 {
+    let draw = (Uuid::new_v4().as_u128() >> 64) as u64;
+    draw as f64 / u64::MAX as f64
+}
 
 #[derive(Debug)]
 pub enum JunoLcdError {
     ApiGetFailure(String),
-    Reqwest(String),
+    Cancelled,
 }
 
 pub struct JunoLcd {
     lcd_address: String,
+    max_pages: u32,
+    max_response_bytes: usize,
+    // Built once so connections to the LCD are pooled across requests
+    client: Client,
+    retry_policy: JunoRetryPolicy,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+// Only the fields we read are declared, serde skips the rest of the page
+// (events, logs, signatures, memos) instead of allocating it.
+#[derive(Deserialize, Debug)]
 struct Pagination {
-    next_key: Option<String>,
     total: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Deserialize, Debug)]
 struct TransactionItem {
     body: Body,
-    signatures: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Deserialize, Debug)]
 struct Body {
     messages: Vec<Transaction>,
-    memo: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct TransactionResponseItem {
-    messages: Vec<Transaction>,
-    memo: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct TransactionResponse {
+// Listed in the same order as `txs`
+#[derive(Deserialize, Debug)]
+struct TransactionResponse {
     height: String,
     txhash: String,
-    codespace: String,
-    code: u64,
-    data: String,
-    raw_log: String,
-    info: String,
-    gas_wanted: String,
-    gas_used: String,
+    #[serde(default)]
     timestamp: String,
+    // Failed transactions are included in blocks too, with a non zero code
+    #[serde(default)]
+    code: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Deserialize, Debug)]
 pub struct TransactionApiResponse {
     txs: Vec<TransactionItem>,
+    #[serde(default)]
     tx_responses: Vec<TransactionResponse>,
     pagination: Pagination,
 }
 
 #[async_trait]
 impl TransactionRepository for JunoLcd {
-    async fn get_transactions_for_contract(
+    async fn get_contract_transactions(
         &self,
-        project_id: &str,
-        token_id: &str,
-    ) -> Result<Vec<crate::domain::bridge::Transaction>, crate::domain::bridge::TransactionFetchError>
-    {
-        // Hard limitting limit and offset as this is not relevant here to use it as a param.
+        project_id: &ProjectId,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Transaction>, TransactionFetchError> {
+        let mut domain_tx: Vec<Transaction> = Vec::new();
+        let mut offset = 0;
+        for page in 0..self.max_pages {
+            let txs = self
+                .get_transaction_page(project_id, offset, cancel)
+                .await?;
+            let fetched = txs.txs.len();
+            // Messages are matched to their result by position, a transaction without one
+            // could have failed on chain
+            if txs.tx_responses.len() != fetched {
+                error!(
+                    "LCD listed {} transactions of {} but {} results",
+                    fetched,
+                    project_id,
+                    txs.tx_responses.len()
+                );
+                return Err(TransactionFetchError::DeserializationFailed);
+            }
+            for (transaction_item, response) in txs.txs.iter().zip(txs.tx_responses.iter()) {
+                domain_tx.extend(transaction_item.body.messages.iter().map(|m| Transaction {
+                    tx_hash: Some(response.txhash.clone()),
+                    height: response.height.parse().ok(),
+                    block_time: parse_timestamp(&response.timestamp),
+                    code: Some(response.code),
+                    ..m.clone()
+                }));
+            }
+            offset += fetched;
+
+            // Total is only known when the LCD counted it, a short page ends the listing otherwise
+            let total = txs
+                .pagination
+                .total
+                .parse::<usize>()
+                .ok()
+                .filter(|t| *t > 0);
+            if fetched < PAGE_SIZE || total.map_or(false, |t| offset >= t) {
+                return Ok(domain_tx);
+            }
+            if page + 1 == self.max_pages {
+                warn!(
+                    "Stopped fetching transactions of {} after {} pages, {} of {:?} fetched",
+                    project_id, self.max_pages, offset, total
+                );
+            }
+        }
+
+        Ok(domain_tx)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct OwnerOf {
+    owner: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Tokens {
+    tokens: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SmartQueryResponse<T> {
+    data: T,
+}
+
+#[async_trait]
+impl JunoContractQuerier for JunoLcd {
+    async fn owner_of(
+        &self,
+        project_id: &ProjectId,
+        token_id: &TokenId,
+        cancel: &CancellationToken,
+    ) -> Result<JunoAddress, TransactionFetchError> {
+        let query = json!({ "owner_of": { "token_id": token_id.as_str() } });
+        let owner_of: OwnerOf = self.smart_query(project_id, query, cancel).await?;
+
+        owner_of
+            .owner
+            .parse()
+            .map_err(|_| TransactionFetchError::DeserializationFailed)
+    }
+
+    async fn tokens_of(
+        &self,
+        project_id: &ProjectId,
+        owner: &JunoAddress,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TokenId>, TransactionFetchError> {
+        let mut token_ids: Vec<TokenId> = Vec::new();
+        for _ in 0..self.max_pages {
+            let mut query = json!({ "owner": owner.as_str(), "limit": TOKENS_PAGE_SIZE });
+            if let Some(last) = token_ids.last() {
+                query["start_after"] = json!(last.as_str());
+            }
+            let page: Tokens = self
+                .smart_query(project_id, json!({ "tokens": query }), cancel)
+                .await?;
+            let fetched = page.tokens.len();
+            for token in page.tokens {
+                match token.parse() {
+                    Ok(t) => token_ids.push(t),
+                    Err(_e) => return Err(TransactionFetchError::DeserializationFailed),
+                }
+            }
+            if fetched < TOKENS_PAGE_SIZE {
+                return Ok(token_ids);
+            }
+        }
+
+        warn!(
+            "Stopped listing tokens of {} on {} after {} pages",
+            owner, project_id, self.max_pages
+        );
+        Ok(token_ids)
+    }
+}
+
+impl JunoLcd {
+    pub fn new(
+        lcd_address: &str,
+        max_pages: u32,
+        max_response_bytes: usize,
+        client: Client,
+        retry_policy: JunoRetryPolicy,
+    ) -> Self {
+        Self {
+            lcd_address: lcd_address.into(),
+            max_pages,
+            max_response_bytes,
+            client,
+            retry_policy,
+        }
+    }
+
+    async fn smart_query<T: DeserializeOwned>(
+        &self,
+        project_id: &ProjectId,
+        query: Value,
+        cancel: &CancellationToken,
+    ) -> Result<T, TransactionFetchError> {
+        // Smart queries are base64 encoded JSON, the url safe alphabet keeps them a single
+        // path segment
         let endpoint = format!(
-            "/cosmos/tx/v1beta1/txs?events=execute._contract_address=%27{}%27&pagination.limit=10&pagination.offset=0&pagination.count_total=true&order_by=ORDER_BY_DESC",
-            project_id
+            "/cosmwasm/wasm/v1/contract/{}/smart/{}",
+            project_id,
+            general_purpose::URL_SAFE.encode(query.to_string())
         );
-        let response = match self.get(endpoint).await {
+        let response = match self.get(endpoint, cancel).await {
+            Ok(r) => r,
+            Err(JunoLcdError::Cancelled) => return Err(TransactionFetchError::Cancelled),
+            Err(e) => {
+                error!("querying Juno contract {} : {:#?}", project_id, e);
+                return Err(TransactionFetchError::FetchError(
+                    "Failed to call smart query API".into(),
+                ));
+            }
+        };
+        // Contract errors, such as unknown tokens, come back as server errors
+        if !response.status().is_success() {
+            return Err(TransactionFetchError::JunoBlockchainServerError(
+                response.status().into(),
+            ));
+        }
+
+        let body = self.read_body(response, cancel).await?;
+        match serde_json::from_slice::<SmartQueryResponse<T>>(&body) {
+            Ok(r) => Ok(r.data),
+            Err(_e) => Err(TransactionFetchError::DeserializationFailed),
+        }
+    }
+
+    async fn get_transaction_page(
+        &self,
+        project_id: &ProjectId,
+        offset: usize,
+        cancel: &CancellationToken,
+    ) -> Result<TransactionApiResponse, TransactionFetchError> {
+        let endpoint = format!(
+            "/cosmos/tx/v1beta1/txs?events=execute._contract_address=%27{}%27&pagination.limit={}&pagination.offset={}&pagination.count_total=true&order_by=ORDER_BY_DESC",
+            project_id, PAGE_SIZE, offset
+        );
+        let response = match self.get(endpoint, cancel).await {
             Ok(t) => t,
+            Err(JunoLcdError::Cancelled) => return Err(TransactionFetchError::Cancelled),
             Err(e) => {
                 error!("fetching Juno blockchain transactions : {:#?}", e);
                 return Err(TransactionFetchError::FetchError(
@@ -92,62 +319,238 @@ impl TransactionRepository for JunoLcd {
             ));
         }
 
-        let txs = match response.json::<TransactionApiResponse>().await {
-            Ok(t) => t,
-            Err(_e) => return Err(TransactionFetchError::DeserializationFailed),
-        };
+        let body = self.read_body(response, cancel).await?;
+        match serde_json::from_slice::<TransactionApiResponse>(&body) {
+            Ok(t) => Ok(t),
+            Err(_e) => Err(TransactionFetchError::DeserializationFailed),
+        }
+    }
 
-        let mut domain_tx: Vec<Transaction> = Vec::new();
-        for transaction_item in txs.txs.iter() {
-            for msg in transaction_item.body.messages.iter() {
-                let transfer = match &msg.msg {
-                    MsgTypes::TransferNft(t) => t,
-                };
-
-                if transfer.token_id == token_id {
-                    domain_tx.push(msg.clone());
+    /// Reads the body chunk by chunk, giving up as soon as it exceeds the size limit
+    /// rather than buffering a multi-megabyte page first.
+    async fn read_body(
+        &self,
+        mut response: Response,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<u8>, TransactionFetchError> {
+        if let Some(length) = response.content_length() {
+            if length > self.max_response_bytes as u64 {
+                warn!(
+                    "Juno LCD announced a {} bytes response, over the {} bytes limit",
+                    length, self.max_response_bytes
+                );
+                return Err(TransactionFetchError::ResponseTooLarge);
+            }
+        }
+
+        let mut body = Vec::new();
+        loop {
+            let chunk = tokio::select! {
+                _ = cancel.cancelled() => return Err(TransactionFetchError::Cancelled),
+                chunk = response.chunk() => chunk,
+            };
+            match chunk {
+                Ok(Some(chunk)) => {
+                    if body.len() + chunk.len() > self.max_response_bytes {
+                        warn!(
+                            "Juno LCD response exceeds the {} bytes limit",
+                            self.max_response_bytes
+                        );
+                        return Err(TransactionFetchError::ResponseTooLarge);
+                    }
+                    body.extend_from_slice(&chunk);
                 }
+                Ok(None) => return Ok(body),
+                Err(e) => {
+                    error!("reading Juno blockchain transactions : {:#?}", e);
+                    return Err(TransactionFetchError::FetchError(
+                        "Failed to read transaction API response".into(),
+                    ));
+                }
+            }
+        }
+    }
+
+    async fn get(
+        &self,
+        endpoint: String,
+        cancel: &CancellationToken,
+    ) -> Result<Response, JunoLcdError> {
+        let url = format!("{}{}", self.lcd_address, endpoint);
+        let max_attempts = self.retry_policy.max_attempts.max(1);
+
+        for attempt in 1..=max_attempts {
+            let request = tokio::select! {
+                _ = cancel.cancelled() => return Err(JunoLcdError::Cancelled),
+                request = self
+                    .client
+                    .get(&url)
+                    .timeout(self.retry_policy.timeout)
+                    .send() => request,
+            };
+            let e = match request {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            if attempt == max_attempts {
+                error!(
+                    "Juno LCD unreachable after {} attempts : {}",
+                    max_attempts, e
+                );
+                break;
             }
+
+            let delay = self.retry_policy.delay(attempt);
+            warn!(
+                "Juno LCD unreachable, attempt {} of {}, retrying in {}ms : {}",
+                attempt,
+                max_attempts,
+                delay.as_millis(),
+                e
+            );
+            tokio::select! {
+                _ = cancel.cancelled() => return Err(JunoLcdError::Cancelled),
+                _ = sleep(delay) => {}
+            };
         }
 
-        Ok(domain_tx)
+        // Add notification here.
+        Err(JunoLcdError::ApiGetFailure(endpoint))
     }
 }
 
+#[derive(Deserialize, Debug)]
+struct DefaultNodeInfo {
+    network: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct NodeInfoResponse {
+    default_node_info: DefaultNodeInfo,
+}
+
 impl JunoLcd {
-    pub fn new(lcd_address: &str) -> Self {
+    async fn node_info(&self) -> Result<NodeInfoResponse, String> {
+        let response = self
+            .get(
+                "/cosmos/base/tendermint/v1beta1/node_info".into(),
+                &CancellationToken::new(),
+            )
+            .await
+            .map_err(|e| format!("{:#?}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("node info answered {}", response.status()));
+        }
+
+        response
+            .json::<NodeInfoResponse>()
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl WarmUp for JunoLcd {
+    fn name(&self) -> &str {
+        "juno lcd"
+    }
+
+    async fn warm_up(&self) -> Result<(), WarmUpError> {
+        let node_info = self.node_info().await.map_err(WarmUpError::Failed)?;
+        info!(
+            "Juno LCD serves network {}",
+            node_info.default_node_info.network
+        );
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HealthCheck for JunoLcd {
+    fn name(&self) -> &str {
+        "juno lcd"
+    }
+
+    async fn check(&self) -> Result<(), HealthCheckError> {
+        self.node_info()
+            .await
+            .map(|_| ())
+            .map_err(HealthCheckError::Failed)
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ExecuteRequest<'a> {
+    contract: &'a str,
+    msgs: Vec<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ExecuteResponse {
+    txhash: String,
+    code: u32,
+    #[serde(default)]
+    raw_log: String,
+}
+
+/// Broadcasts admin wallet transactions through a signing service holding the Juno
+/// admin key, so it never reaches the bridge. The service signs and broadcasts the
+/// `MsgExecuteContract` messages posted to `/execute` and answers with the LCD
+/// broadcast result.
+pub struct SignerJunoTxBroadcaster {
+    signer_url: String,
+    client: Client,
+}
+
+impl SignerJunoTxBroadcaster {
+    pub fn new(signer_url: &str, client: Client) -> Self {
         Self {
-            lcd_address: lcd_address.into(),
+            signer_url: signer_url.trim_end_matches('/').into(),
+            client,
         }
     }
+}
 
-    async fn get(&self, endpoint: String) -> Result<Response, JunoLcdError> {
-        for i in 0..MAX_RETRY {
-            let addr = self.lcd_address.clone();
-            if let Ok(client) = reqwest::Client::builder()
-                .timeout(Duration::from_secs(120))
-                .build()
-            {
-                let request = client
-                    .get(format!("{}{}", addr, endpoint.clone()))
-                    .send()
-                    .await;
-
-                if request.is_err() {
-                    if i < MAX_RETRY {
-                        sleep(Duration::from_secs(15));
-                        continue;
-                    }
-                    return Err(JunoLcdError::ApiGetFailure(endpoint));
-                }
+#[async_trait]
+impl JunoTxBroadcaster for SignerJunoTxBroadcaster {
+    async fn transfer_tokens(
+        &self,
+        contract: &ProjectId,
+        recipient: &JunoAddress,
+        token_ids: &[TokenId],
+    ) -> Result<String, JunoBroadcastError> {
+        let body = ExecuteRequest {
+            contract: contract.as_str(),
+            msgs: token_ids
+                .iter()
+                .map(|t| {
+                    json!({ "transfer_nft": { "recipient": recipient.as_str(), "token_id": t.as_str() } })
+                })
+                .collect(),
+        };
 
-                return Ok(request.unwrap());
-            } else {
-                return Err(JunoLcdError::Reqwest("Failed to build client".into()));
+        let response = match self
+            .client
+            .post(format!("{}/execute", self.signer_url))
+            .timeout(Duration::from_secs(120))
+            .json(&body)
+            .send()
+            .await
+        {
+            Ok(r) if r.status().is_success() => r,
+            Ok(r) => {
+                return Err(JunoBroadcastError::Failure(format!(
+                    "Signer answered with status {}",
+                    r.status()
+                )))
             }
+            Err(e) => return Err(JunoBroadcastError::Failure(e.to_string())),
+        };
+        match response.json::<ExecuteResponse>().await {
+            Ok(r) if 0 == r.code => Ok(r.txhash),
+            Ok(r) => Err(JunoBroadcastError::Rejected(r.raw_log)),
+            Err(e) => Err(JunoBroadcastError::Failure(e.to_string())),
         }
-
-        // Add notification here.
-        Err(JunoLcdError::ApiGetFailure(endpoint))
     }
 }
@@ -1,14 +1,105 @@
-use log::LevelFilter;
+use log::{LevelFilter, Log, Metadata, Record};
 use log4rs::{
     append::console::ConsoleAppender,
     config::{Appender, Root},
 };
+use serde_derive::Serialize;
+use std::io::Write;
 
+use crate::domain::{
+    calendar::format_timestamp,
+    clock::{Clock, SystemClock},
+    log_context::current_log_fields,
+};
+
+const LOG_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// How log lines are written to stdout, from `LOG_FORMAT`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line, for log pipelines such as Loki or Datadog
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "text" => Some(Self::Text),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+// Read from the environment rather than the arguments, logs are set up before parsing them
 pub fn configure_logger() {
-    let stdout: ConsoleAppender = ConsoleAppender::builder().build();
-    let log_config = log4rs::config::Config::builder()
-        .appender(Appender::builder().build("stdout", Box::new(stdout)))
-        .build(Root::builder().appender("stdout").build(LevelFilter::Info))
-        .unwrap();
-    log4rs::init_config(log_config).unwrap();
+    let format = match std::env::var("LOG_FORMAT") {
+        Ok(value) => match LogFormat::parse(&value) {
+            Some(f) => f,
+            None => panic!("Unsupported LOG_FORMAT {}, expected text or json", value),
+        },
+        Err(_) => LogFormat::Text,
+    };
+
+    match format {
+        LogFormat::Text => {
+            let stdout: ConsoleAppender = ConsoleAppender::builder().build();
+            let log_config = log4rs::config::Config::builder()
+                .appender(Appender::builder().build("stdout", Box::new(stdout)))
+                .build(Root::builder().appender("stdout").build(LOG_LEVEL))
+                .unwrap();
+            log4rs::init_config(log_config).unwrap();
+        }
+        LogFormat::Json => {
+            log::set_boxed_logger(Box::new(JsonLogger)).unwrap();
+            log::set_max_level(LOG_LEVEL);
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    timestamp: String,
+    level: &'a str,
+    module: &'a str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wallet_pubkey: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_id: Option<String>,
+}
+
+/// Writes every record as a JSON line along with the fields of the current log context.
+struct JsonLogger;
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= LOG_LEVEL
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let fields = current_log_fields();
+        let line = JsonLine {
+            timestamp: format_timestamp(SystemClock.now_ms()),
+            level: record.level().as_str(),
+            module: record.module_path().unwrap_or(record.target()),
+            message: record.args().to_string(),
+            request_id: fields.request_id,
+            wallet_pubkey: fields.wallet_pubkey,
+            project_id: fields.project_id,
+        };
+        if let Ok(json) = serde_json::to_string(&line) {
+            let _ = writeln!(std::io::stdout().lock(), "{}", json);
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stdout().flush();
+    }
 }
@@ -0,0 +1,203 @@
+use log::{error, warn};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    net::UdpSocket,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::domain::metrics::{Metrics, NoopMetrics, Tags};
+
+#[derive(Debug)]
+pub enum MetricsConfigError {
+    UnknownBackend(String),
+    InvalidStatsdAddress(String),
+}
+
+// Metric name and its sorted labels
+type SeriesKey = (String, Vec<(String, String)>);
+
+fn series_key(name: &str, tags: Tags) -> SeriesKey {
+    let mut labels: Vec<(String, String)> = tags
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    labels.sort();
+    (name.to_string(), labels)
+}
+
+fn prometheus_labels(labels: &[(String, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    format!("{{{}}}", labels.join(","))
+}
+
+#[derive(Default)]
+struct Series {
+    counters: BTreeMap<SeriesKey, u64>,
+    gauges: BTreeMap<SeriesKey, f64>,
+    // Sum in seconds and count of observations
+    timings: BTreeMap<SeriesKey, (f64, u64)>,
+}
+
+/// Keeps metrics in process and renders them in the Prometheus text format on
+/// `GET /metrics`.
+pub struct PrometheusMetrics {
+    prefix: String,
+    series: Mutex<Series>,
+}
+
+impl PrometheusMetrics {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            series: Mutex::new(Series::default()),
+        }
+    }
+
+    fn name(&self, name: &str) -> String {
+        match self.prefix.is_empty() {
+            true => name.to_string(),
+            false => format!("{}_{}", self.prefix, name),
+        }
+    }
+}
+
+impl Metrics for PrometheusMetrics {
+    fn increment(&self, name: &str, tags: Tags) {
+        if let Ok(mut series) = self.series.lock() {
+            *series
+                .counters
+                .entry(series_key(&self.name(name), tags))
+                .or_default() += 1;
+        }
+    }
+
+    fn gauge(&self, name: &str, value: f64, tags: Tags) {
+        if let Ok(mut series) = self.series.lock() {
+            series
+                .gauges
+                .insert(series_key(&self.name(name), tags), value);
+        }
+    }
+
+    fn timing(&self, name: &str, duration: Duration, tags: Tags) {
+        if let Ok(mut series) = self.series.lock() {
+            let (sum, count) = series
+                .timings
+                .entry(series_key(&self.name(name), tags))
+                .or_default();
+            *sum += duration.as_secs_f64();
+            *count += 1;
+        }
+    }
+
+    fn render(&self) -> Option<String> {
+        let series = self.series.lock().ok()?;
+        let mut out = String::new();
+        let mut last_name = "";
+        for ((name, labels), value) in series.counters.iter() {
+            if name.as_str() != last_name {
+                let _ = writeln!(out, "# TYPE {} counter", name);
+                last_name = name.as_str();
+            }
+            let _ = writeln!(out, "{}{} {}", name, prometheus_labels(labels), value);
+        }
+        for ((name, labels), value) in series.gauges.iter() {
+            if name.as_str() != last_name {
+                let _ = writeln!(out, "# TYPE {} gauge", name);
+                last_name = name.as_str();
+            }
+            let _ = writeln!(out, "{}{} {}", name, prometheus_labels(labels), value);
+        }
+        for ((name, labels), (sum, count)) in series.timings.iter() {
+            if name.as_str() != last_name {
+                let _ = writeln!(out, "# TYPE {} summary", name);
+                last_name = name.as_str();
+            }
+            let labels = prometheus_labels(labels);
+            let _ = writeln!(out, "{}_sum{} {}", name, labels, sum);
+            let _ = writeln!(out, "{}_count{} {}", name, labels, count);
+        }
+
+        Some(out)
+    }
+}
+
+/// Pushes metrics to a StatsD agent over UDP, tags use the DogStatsD `|#k:v` extension
+/// so a Datadog agent keeps them.
+pub struct StatsdMetrics {
+    prefix: String,
+    socket: UdpSocket,
+}
+
+impl StatsdMetrics {
+    pub fn new(address: &str, prefix: &str) -> Result<Self, MetricsConfigError> {
+        let socket = match UdpSocket::bind("0.0.0.0:0") {
+            Ok(s) => s,
+            Err(e) => {
+                error!("Failed to bind statsd socket : {:#?}", e);
+                return Err(MetricsConfigError::InvalidStatsdAddress(address.into()));
+            }
+        };
+        if let Err(e) = socket.connect(address) {
+            error!("Failed to resolve statsd agent {} : {:#?}", address, e);
+            return Err(MetricsConfigError::InvalidStatsdAddress(address.into()));
+        }
+        // A missing agent must never slow down requests
+        let _ = socket.set_nonblocking(true);
+
+        Ok(Self {
+            prefix: prefix.to_string(),
+            socket,
+        })
+    }
+
+    fn send(&self, name: &str, value: &str, kind: &str, tags: Tags) {
+        let mut packet = match self.prefix.is_empty() {
+            true => format!("{}:{}|{}", name, value, kind),
+            false => format!("{}.{}:{}|{}", self.prefix, name, value, kind),
+        };
+        if !tags.is_empty() {
+            let tags: Vec<String> = tags.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+            packet.push_str(&format!("|#{}", tags.join(",")));
+        }
+        if let Err(e) = self.socket.send(packet.as_bytes()) {
+            warn!("Failed to push metric {} : {}", name, e);
+        }
+    }
+}
+
+impl Metrics for StatsdMetrics {
+    fn increment(&self, name: &str, tags: Tags) {
+        self.send(name, "1", "c", tags);
+    }
+
+    fn gauge(&self, name: &str, value: f64, tags: Tags) {
+        self.send(name, &value.to_string(), "g", tags);
+    }
+
+    fn timing(&self, name: &str, duration: Duration, tags: Tags) {
+        self.send(name, &duration.as_millis().to_string(), "ms", tags);
+    }
+}
+
+/// Builds the metrics backend from its name (`none`, `prometheus`, `statsd`).
+pub fn configure_metrics(
+    backend: &str,
+    statsd_address: &str,
+    prefix: &str,
+) -> Result<Arc<dyn Metrics>, MetricsConfigError> {
+    match backend.trim() {
+        "" | "none" => Ok(Arc::new(NoopMetrics)),
+        "prometheus" => Ok(Arc::new(PrometheusMetrics::new(prefix))),
+        "statsd" => Ok(Arc::new(StatsdMetrics::new(statsd_address, prefix)?)),
+        other => Err(MetricsConfigError::UnknownBackend(other.to_string())),
+    }
+}
@@ -0,0 +1,346 @@
+use deadpool_postgres::Pool;
+use log::{info, warn};
+use std::fmt::{Display, Formatter};
+
+// Schema files in the order they were introduced, never edit or reorder an applied one
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("init", include_str!("../../data/postgresql/init.sql")),
+    (
+        "add_migration_queue",
+        include_str!("../../data/postgresql/add_migration_queue.sql"),
+    ),
+    (
+        "add_migration_queue_history",
+        include_str!("../../data/postgresql/add_migration_queue_history.sql"),
+    ),
+    (
+        "add_wallet_links",
+        include_str!("../../data/postgresql/add_wallet_links.sql"),
+    ),
+    (
+        "add_breakglass_mints",
+        include_str!("../../data/postgresql/add_breakglass_mints.sql"),
+    ),
+    (
+        "add_migration_queue_note",
+        include_str!("../../data/postgresql/add_migration_queue_note.sql"),
+    ),
+    (
+        "add_authorized_senders",
+        include_str!("../../data/postgresql/add_authorized_senders.sql"),
+    ),
+    (
+        "add_migration_queue_history_worker",
+        include_str!("../../data/postgresql/add_migration_queue_history_worker.sql"),
+    ),
+    (
+        "add_post_mint_executions",
+        include_str!("../../data/postgresql/add_post_mint_executions.sql"),
+    ),
+    (
+        "add_queue_pagination_indexes",
+        include_str!("../../data/postgresql/add_queue_pagination_indexes.sql"),
+    ),
+    (
+        "add_daily_reports",
+        include_str!("../../data/postgresql/add_daily_reports.sql"),
+    ),
+    (
+        "add_migration_queue_dead_letter",
+        include_str!("../../data/postgresql/add_migration_queue_dead_letter.sql"),
+    ),
+    (
+        "add_webhooks",
+        include_str!("../../data/postgresql/add_webhooks.sql"),
+    ),
+    (
+        "add_signature_challenges",
+        include_str!("../../data/postgresql/add_signature_challenges.sql"),
+    ),
+    (
+        "add_batch_analytics",
+        include_str!("../../data/postgresql/add_batch_analytics.sql"),
+    ),
+    (
+        "add_project_batch_sizes",
+        include_str!("../../data/postgresql/add_project_batch_sizes.sql"),
+    ),
+    (
+        "add_bridge_requests",
+        include_str!("../../data/postgresql/add_bridge_requests.sql"),
+    ),
+    (
+        "add_transfer_proofs",
+        include_str!("../../data/postgresql/add_transfer_proofs.sql"),
+    ),
+    (
+        "add_reverse_migration_queue",
+        include_str!("../../data/postgresql/add_reverse_migration_queue.sql"),
+    ),
+    (
+        "add_migration_queue_note_params",
+        include_str!("../../data/postgresql/add_migration_queue_note_params.sql"),
+    ),
+    (
+        "add_issue_records",
+        include_str!("../../data/postgresql/add_issue_records.sql"),
+    ),
+    (
+        "add_bridge_check_results",
+        include_str!("../../data/postgresql/add_bridge_check_results.sql"),
+    ),
+    (
+        "add_migration_queue_fees",
+        include_str!("../../data/postgresql/add_migration_queue_fees.sql"),
+    ),
+    (
+        "add_analytics_events",
+        include_str!("../../data/postgresql/add_analytics_events.sql"),
+    ),
+    (
+        "add_migration_queue_request_id",
+        include_str!("../../data/postgresql/add_migration_queue_request_id.sql"),
+    ),
+    (
+        "add_webhook_subscription_filters",
+        include_str!("../../data/postgresql/add_webhook_subscription_filters.sql"),
+    ),
+    (
+        "add_token_locks",
+        include_str!("../../data/postgresql/add_token_locks.sql"),
+    ),
+    (
+        "add_migration_queue_active_token_idx",
+        include_str!("../../data/postgresql/add_migration_queue_active_token_idx.sql"),
+    ),
+    (
+        "allow_burnt_transfer_proofs",
+        include_str!("../../data/postgresql/allow_burnt_transfer_proofs.sql"),
+    ),
+    (
+        "add_transfer_proof_block_time",
+        include_str!("../../data/postgresql/add_transfer_proof_block_time.sql"),
+    ),
+];
+
+// Arbitrary key serializing migrations of api and worker instances starting together
+const MIGRATION_LOCK_KEY: i64 = 7_349_201_588;
+
+/// What happens to the schema when connecting, from `DATABASE_MIGRATIONS`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MigrationMode {
+    /// Applies pending migrations
+    Run,
+    /// Records every migration as applied without running it when none is recorded yet,
+    /// for databases whose schema was applied by hand. Runs pending ones afterwards
+    Baseline,
+    /// Leaves the schema alone
+    Off,
+}
+
+impl MigrationMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "run" => Some(Self::Run),
+            "baseline" => Some(Self::Baseline),
+            "off" => Some(Self::Off),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Connection(String),
+    /// Tables exist but no migration was ever recorded, the schema has to be baselined
+    UnrecordedSchema,
+    Failed(&'static str, String),
+    /// Migrations this binary expects are not applied, e.g. it runs with migrations off
+    /// against a database nobody migrated yet
+    SchemaBehind(Vec<&'static str>),
+    /// Migrations unknown to this binary were applied, a newer release migrated the
+    /// database while this one was still being deployed
+    SchemaAhead(Vec<String>),
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connection(e) => write!(f, "cannot reach database : {}", e),
+            Self::UnrecordedSchema => write!(
+                f,
+                "schema was applied by hand, start once with DATABASE_MIGRATIONS=baseline after checking it is up to date"
+            ),
+            Self::Failed(name, e) => write!(f, "migration {} failed : {}", name, e),
+            Self::SchemaBehind(missing) => write!(
+                f,
+                "database schema is older than this release, migrations {} are not applied",
+                missing.join(", ")
+            ),
+            Self::SchemaAhead(unknown) => write!(
+                f,
+                "database schema is newer than this release, migrations {} are unknown to it. Deploy the same release everywhere",
+                unknown.join(", ")
+            ),
+        }
+    }
+}
+
+/// Brings the schema up to date, returns the migrations applied (or recorded when
+/// baselining).
+pub async fn migrate(
+    pool: &Pool,
+    mode: MigrationMode,
+) -> Result<Vec<&'static str>, MigrationError> {
+    if MigrationMode::Off == mode {
+        return Ok(Vec::new());
+    }
+
+    let mut client = pool
+        .get()
+        .await
+        .map_err(|e| MigrationError::Connection(e.to_string()))?;
+    let connection_error = |e: tokio_postgres::Error| MigrationError::Connection(e.to_string());
+
+    client
+        .batch_execute("CREATE TABLE IF NOT EXISTS schema_migrations (name VARCHAR PRIMARY KEY NOT NULL, applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW());")
+        .await
+        .map_err(connection_error)?;
+    // Session lock, released below or when the connection drops
+    client
+        .execute("SELECT pg_advisory_lock($1);", &[&MIGRATION_LOCK_KEY])
+        .await
+        .map_err(connection_error)?;
+
+    let result = apply_migrations(&mut client, mode).await;
+
+    if let Err(e) = client
+        .execute("SELECT pg_advisory_unlock($1);", &[&MIGRATION_LOCK_KEY])
+        .await
+    {
+        warn!("Failed to release migration lock {:#?}", e);
+    }
+
+    result
+}
+
+/// Names of the migrations this binary expects, in order.
+pub fn expected_migrations() -> Vec<&'static str> {
+    MIGRATIONS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Checks migrations recorded as `applied` are exactly the ones this binary expects, so
+/// api and worker never run against a schema they were not built for.
+pub fn check_schema_version(applied: &[String]) -> Result<(), MigrationError> {
+    let unknown: Vec<String> = applied
+        .iter()
+        .filter(|a| !MIGRATIONS.iter().any(|(name, _)| name == a))
+        .cloned()
+        .collect();
+    if !unknown.is_empty() {
+        return Err(MigrationError::SchemaAhead(unknown));
+    }
+    let missing: Vec<&'static str> = MIGRATIONS
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| !applied.iter().any(|a| a == name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(MigrationError::SchemaBehind(missing));
+    }
+
+    Ok(())
+}
+
+/// Refuses a schema this binary was not built for, whatever the migration mode.
+pub async fn verify_schema(pool: &Pool) -> Result<(), MigrationError> {
+    let client = pool
+        .get()
+        .await
+        .map_err(|e| MigrationError::Connection(e.to_string()))?;
+    let connection_error = |e: tokio_postgres::Error| MigrationError::Connection(e.to_string());
+    let tracked = client
+        .query_one(
+            "SELECT to_regclass('public.schema_migrations') IS NOT NULL AS tracked;",
+            &[],
+        )
+        .await
+        .map_err(connection_error)?;
+    // Only reachable with migrations off, the schema is managed by hand
+    if !tracked.get::<&str, bool>("tracked") {
+        warn!("No schema_migrations table, cannot check the database schema version");
+        return Ok(());
+    }
+
+    let applied: Vec<String> = client
+        .query("SELECT name FROM schema_migrations;", &[])
+        .await
+        .map_err(connection_error)?
+        .iter()
+        .map(|row| row.get("name"))
+        .collect();
+
+    check_schema_version(&applied)
+}
+
+async fn apply_migrations(
+    client: &mut deadpool_postgres::Client,
+    mode: MigrationMode,
+) -> Result<Vec<&'static str>, MigrationError> {
+    let connection_error = |e: tokio_postgres::Error| MigrationError::Connection(e.to_string());
+    let applied: Vec<String> = client
+        .query("SELECT name FROM schema_migrations;", &[])
+        .await
+        .map_err(connection_error)?
+        .iter()
+        .map(|row| row.get("name"))
+        .collect();
+
+    // Baselining an already tracked schema would skip its pending migrations
+    let record_only = MigrationMode::Baseline == mode && applied.is_empty();
+    if applied.is_empty() && MigrationMode::Run == mode {
+        let existing = client
+            .query_one(
+                "SELECT to_regclass('public.customer_keys') IS NOT NULL AS existing;",
+                &[],
+            )
+            .await
+            .map_err(connection_error)?;
+        if existing.get::<&str, bool>("existing") {
+            return Err(MigrationError::UnrecordedSchema);
+        }
+    }
+
+    let mut done = Vec::new();
+    for (name, sql) in MIGRATIONS {
+        if applied.iter().any(|a| a == name) {
+            continue;
+        }
+
+        // Each migration is recorded along with its changes, or not at all
+        let tx = client
+            .transaction()
+            .await
+            .map_err(|e| MigrationError::Failed(*name, e.to_string()))?;
+        if !record_only {
+            tx.batch_execute(sql)
+                .await
+                .map_err(|e| MigrationError::Failed(*name, e.to_string()))?;
+        }
+        tx.execute("INSERT INTO schema_migrations (name) VALUES ($1);", &[name])
+            .await
+            .map_err(|e| MigrationError::Failed(*name, e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| MigrationError::Failed(*name, e.to_string()))?;
+
+        if record_only {
+            info!("Recorded migration {} as applied", name);
+        } else {
+            info!("Applied migration {}", name);
+        }
+        done.push(*name);
+    }
+
+    Ok(done)
+}
@@ -1,6 +1,22 @@
 pub mod app;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod http;
 pub mod in_memory;
+pub mod issue_tracker;
 pub mod juno;
+pub mod jwt;
 pub mod logger;
+pub mod metrics;
+pub mod migrations;
+pub mod nats;
+pub mod object_storage;
+pub mod post_mint;
 pub mod postgresql;
+pub mod redis_queue;
+pub mod report;
+pub mod signature;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 pub mod starknet;
+pub mod webhook;
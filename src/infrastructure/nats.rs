@@ -0,0 +1,80 @@
+use async_nats::{Client, Subscriber};
+use async_trait::async_trait;
+use futures::StreamExt;
+use log::error;
+use tokio::sync::Mutex;
+
+use crate::domain::{
+    ids::QueueItemId,
+    queue_broker::{QueueBroker, QueueBrokerError},
+};
+
+// Workers share a queue group so each message wakes a single one of them
+const WORKER_QUEUE_GROUP: &str = "bridge-workers";
+
+/// Publishes enqueued item ids on a NATS subject, as a JSON array.
+pub struct NatsQueueBroker {
+    client: Client,
+    subject: String,
+    // Only workers receive, so the api never joins the queue group
+    subscriber: Mutex<Option<Subscriber>>,
+}
+
+impl NatsQueueBroker {
+    pub async fn connect(url: &str, subject: &str) -> Result<Self, QueueBrokerError> {
+        let client = async_nats::connect(url).await.map_err(|e| {
+            error!("Failed to connect to nats {:#?}", e);
+            QueueBrokerError::Closed
+        })?;
+
+        Ok(Self {
+            client,
+            subject: subject.into(),
+            subscriber: Mutex::new(None),
+        })
+    }
+}
+
+#[async_trait]
+impl QueueBroker for NatsQueueBroker {
+    async fn publish(&self, ids: &[QueueItemId]) -> Result<(), QueueBrokerError> {
+        let payload = serde_json::to_vec(ids).map_err(|_| QueueBrokerError::PublishFailed)?;
+        self.client
+            .publish(self.subject.clone(), payload.into())
+            .await
+            .map_err(|e| {
+                error!("Failed to publish on {} {:#?}", &self.subject, e);
+                QueueBrokerError::PublishFailed
+            })
+    }
+
+    async fn next_delivery(&self) -> Result<Vec<QueueItemId>, QueueBrokerError> {
+        let mut lock = self.subscriber.lock().await;
+        if lock.is_none() {
+            let subscriber = self
+                .client
+                .queue_subscribe(self.subject.clone(), WORKER_QUEUE_GROUP.into())
+                .await
+                .map_err(|e| {
+                    error!("Failed to subscribe to {} {:#?}", &self.subject, e);
+                    QueueBrokerError::ReceiveFailed
+                })?;
+            *lock = Some(subscriber);
+        }
+
+        let Some(subscriber) = lock.as_mut() else {
+            return Err(QueueBrokerError::Closed);
+        };
+        match subscriber.next().await {
+            Some(message) => serde_json::from_slice(&message.payload).map_err(|e| {
+                error!("Invalid message on {} {:#?}", &self.subject, e);
+                QueueBrokerError::ReceiveFailed
+            }),
+            None => {
+                // Subscription is gone, the next call subscribes again
+                *lock = None;
+                Err(QueueBrokerError::Closed)
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use log::error;
+use reqwest::{Client, Url};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::domain::{
+    calendar::civil_from_days,
+    storage::{ObjectStorage, StorageError},
+};
+
+const UPLOAD_URL_TTL: Duration = Duration::from_secs(900);
+// Both S3 and GCS refuse presigned urls living longer than a week
+const MAX_URL_TTL: Duration = Duration::from_secs(604_800);
+
+/// Query string signing flavour, GCS XML API implements AWS signature v4 with its own names.
+struct SignatureScheme {
+    algorithm: &'static str,
+    key_prefix: &'static str,
+    request_type: &'static str,
+    param_prefix: &'static str,
+    service: &'static str,
+}
+
+const S3_SCHEME: SignatureScheme = SignatureScheme {
+    algorithm: "AWS4-HMAC-SHA256",
+    key_prefix: "AWS4",
+    request_type: "aws4_request",
+    param_prefix: "X-Amz-",
+    service: "s3",
+};
+
+const GCS_SCHEME: SignatureScheme = SignatureScheme {
+    algorithm: "GOOG4-HMAC-SHA256",
+    key_prefix: "GOOG4",
+    request_type: "goog4_request",
+    param_prefix: "X-Goog-",
+    service: "storage",
+};
+
+/// S3 or GCS (HMAC keys) bucket, uploads go through presigned PUT urls so both
+/// providers share the same signing code.
+pub struct PresignedObjectStorage {
+    scheme: SignatureScheme,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    client: Client,
+}
+
+impl PresignedObjectStorage {
+    pub fn s3(
+        endpoint: Option<&str>,
+        bucket: &str,
+        region: &str,
+        access_key: &str,
+        secret_key: &str,
+        client: Client,
+    ) -> Result<Self, StorageError> {
+        let default_endpoint = format!("https://s3.{}.amazonaws.com", region);
+        Self::new(
+            S3_SCHEME,
+            endpoint.unwrap_or(&default_endpoint),
+            bucket,
+            region,
+            access_key,
+            secret_key,
+            client,
+        )
+    }
+
+    pub fn gcs(
+        endpoint: Option<&str>,
+        bucket: &str,
+        access_key: &str,
+        secret_key: &str,
+        client: Client,
+    ) -> Result<Self, StorageError> {
+        Self::new(
+            GCS_SCHEME,
+            endpoint.unwrap_or("https://storage.googleapis.com"),
+            bucket,
+            "auto",
+            access_key,
+            secret_key,
+            client,
+        )
+    }
+
+    fn new(
+        scheme: SignatureScheme,
+        endpoint: &str,
+        bucket: &str,
+        region: &str,
+        access_key: &str,
+        secret_key: &str,
+        client: Client,
+    ) -> Result<Self, StorageError> {
+        let endpoint = Url::parse(endpoint)
+            .map_err(|_| StorageError::InvalidKey(format!("Invalid endpoint {}", endpoint)))?;
+
+        Ok(Self {
+            scheme,
+            endpoint,
+            bucket: bucket.into(),
+            region: region.into(),
+            access_key: access_key.into(),
+            secret_key: secret_key.into(),
+            client,
+        })
+    }
+
+    fn presign(
+        &self,
+        method: &str,
+        key: &str,
+        expires_in: Duration,
+        now: SystemTime,
+    ) -> Result<String, StorageError> {
+        if key.is_empty() || key.starts_with('/') {
+            return Err(StorageError::InvalidKey(key.into()));
+        }
+        let expires_in = expires_in.min(MAX_URL_TTL).as_secs();
+
+        let seconds = now
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+        let time_of_day = seconds.rem_euclid(86_400);
+        let date = format!("{:04}{:02}{:02}", year, month, day);
+        let datetime = format!(
+            "{}T{:02}{:02}{:02}Z",
+            date,
+            time_of_day / 3_600,
+            time_of_day % 3_600 / 60,
+            time_of_day % 60
+        );
+
+        let host = match (self.endpoint.host_str(), self.endpoint.port()) {
+            (Some(h), Some(p)) => format!("{}:{}", h, p),
+            (Some(h), None) => h.to_string(),
+            (None, _) => return Err(StorageError::InvalidKey("Endpoint has no host".into())),
+        };
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            uri_encode(&self.bucket, true),
+            uri_encode(key, false)
+        );
+        let scope = format!(
+            "{}/{}/{}/{}",
+            date, self.region, self.scheme.service, self.scheme.request_type
+        );
+
+        let prefix = self.scheme.param_prefix;
+        // Already in lexicographic order as canonical query requires
+        let query = [
+            ("Algorithm", self.scheme.algorithm.to_string()),
+            ("Credential", format!("{}/{}", self.access_key, scope)),
+            ("Date", datetime.to_string()),
+            ("Expires", expires_in.to_string()),
+            ("SignedHeaders", "host".to_string()),
+        ]
+        .iter()
+        .map(|(name, value)| format!("{}{}={}", prefix, name, uri_encode(value, true)))
+        .collect::<Vec<String>>()
+        .join("&");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD",
+            method, path, query, host
+        );
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            self.scheme.algorithm,
+            datetime,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut signing_key = hmac_sha256(
+            format!("{}{}", self.scheme.key_prefix, self.secret_key).as_bytes(),
+            &date,
+        );
+        for part in [
+            self.region.as_str(),
+            self.scheme.service,
+            self.scheme.request_type,
+        ] {
+            signing_key = hmac_sha256(&signing_key, part);
+        }
+        let signature = hex::encode(hmac_sha256(&signing_key, &string_to_sign));
+
+        Ok(format!(
+            "{}://{}{}?{}&{}Signature={}",
+            self.endpoint.scheme(),
+            host,
+            path,
+            query,
+            prefix,
+            signature
+        ))
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// RFC 3986 encoding as expected by signature v4 canonical requests.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        };
+    }
+    encoded
+}
+
+#[async_trait]
+impl ObjectStorage for PresignedObjectStorage {
+    async fn put_object(
+        &self,
+        key: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<(), StorageError> {
+        let url = self.presign("PUT", key, UPLOAD_URL_TTL, SystemTime::now())?;
+        let response = self
+            .client
+            .put(url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| StorageError::UploadFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            error!(
+                "Object storage rejected upload of {} with status {}",
+                key,
+                response.status()
+            );
+            return Err(StorageError::UploadFailed(response.status().to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn presigned_url(&self, key: &str, expires_in: Duration) -> Result<String, StorageError> {
+        self.presign("GET", key, expires_in, SystemTime::now())
+    }
+}
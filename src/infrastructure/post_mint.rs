@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use log::info;
+use reqwest::Client;
+use starknet::{
+    accounts::{Account, AccountCall, Call, SingleOwnerAccount},
+    core::{types::FieldElement, utils::get_selector_from_name},
+    providers::SequencerGatewayProvider,
+    signers::{LocalWallet, SigningKey},
+};
+use std::sync::Arc;
+
+use super::{
+    http::HttpClientConfig,
+    starknet::{CalldataTemplate, CalldataTemplateError, TokenIdFormat, TokenIdFormats},
+};
+use crate::domain::post_mint::{PostMintExecution, PostMintHook, PostMintHookError, PostMintHooks};
+
+#[derive(Debug)]
+pub enum PostMintHookConfigError {
+    InvalidDefinition(String),
+    InvalidTemplate(CalldataTemplateError),
+    HttpClient(String),
+}
+
+/// Notifies an off-chain service that a token has been minted.
+pub struct WebhookPostMintHook {
+    url: String,
+    client: Client,
+}
+
+impl WebhookPostMintHook {
+    pub fn new(url: &str, client: Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl PostMintHook for WebhookPostMintHook {
+    async fn execute(&self, execution: &PostMintExecution) -> Result<(), PostMintHookError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(execution)
+            .send()
+            .await
+            .map_err(|e| PostMintHookError::Failure(e.to_string()))?;
+
+        match response.status().is_success() {
+            true => Ok(()),
+            false => Err(PostMintHookError::Failure(format!(
+                "Webhook responded with status {}",
+                response.status()
+            ))),
+        }
+    }
+}
+
+/// Invokes an entrypoint of the project contract once the token exists, e.g. a metadata setter.
+pub struct InvokeEntrypointPostMintHook {
+    provider: Arc<SequencerGatewayProvider>,
+    account_address: String,
+    account_private_key: String,
+    chain_id: FieldElement,
+    entrypoint: String,
+    calldata_template: CalldataTemplate,
+    token_id_format: TokenIdFormat,
+}
+
+impl InvokeEntrypointPostMintHook {
+    pub fn new(
+        provider: Arc<SequencerGatewayProvider>,
+        account_addr: &str,
+        account_pk: &str,
+        chain_id: FieldElement,
+        entrypoint: &str,
+        calldata_template: CalldataTemplate,
+        token_id_format: TokenIdFormat,
+    ) -> Self {
+        Self {
+            provider,
+            account_address: account_addr.to_string(),
+            account_private_key: account_pk.to_string(),
+            chain_id,
+            entrypoint: entrypoint.into(),
+            calldata_template,
+            token_id_format,
+        }
+    }
+}
+
+#[async_trait]
+impl PostMintHook for InvokeEntrypointPostMintHook {
+    async fn execute(&self, execution: &PostMintExecution) -> Result<(), PostMintHookError> {
+        let felt = |value: &str| {
+            FieldElement::from_hex_be(value)
+                .map_err(|_| PostMintHookError::Failure(format!("Invalid address {}", value)))
+        };
+        let signer = LocalWallet::from(SigningKey::from_secret_scalar(felt(
+            &self.account_private_key,
+        )?));
+        let account = SingleOwnerAccount::new(
+            self.provider.clone(),
+            signer,
+            felt(&self.account_address)?,
+            self.chain_id,
+        );
+
+        let calldata = self
+            .calldata_template
+            .encode(
+                felt(&execution.starknet_account_addr)?,
+                &execution.token_id,
+                self.token_id_format,
+            )
+            .map_err(|e| PostMintHookError::Failure(format!("{:?}", e)))?;
+        let selector = get_selector_from_name(&self.entrypoint)
+            .map_err(|e| PostMintHookError::Failure(e.to_string()))?;
+        let calls = vec![Call {
+            to: felt(&execution.project_id)?,
+            selector,
+            calldata,
+        }];
+
+        let tx = account
+            .execute(&calls)
+            .send()
+            .await
+            .map_err(|e| PostMintHookError::Failure(e.to_string()))?;
+        info!(
+            "Post mint {} invoked for token {} -> #{}",
+            self.entrypoint,
+            execution.token_id,
+            hex::encode(tx.transaction_hash.to_bytes_be())
+        );
+
+        Ok(())
+    }
+}
+
+/// Builds hooks from definitions formatted as `project_address=webhook:url` or
+/// `project_address=invoke:entrypoint calldata template`.
+pub fn configure_post_mint_hooks(
+    definitions: &[String],
+    http_client: &HttpClientConfig,
+    provider: Arc<SequencerGatewayProvider>,
+    account_addr: &str,
+    account_pk: &str,
+    chain_id: FieldElement,
+    token_id_formats: &TokenIdFormats,
+) -> Result<PostMintHooks, PostMintHookConfigError> {
+    let mut hooks = PostMintHooks::new();
+    for definition in definitions {
+        let invalid = || PostMintHookConfigError::InvalidDefinition(definition.to_string());
+        let (project_id, hook) = definition.split_once('=').ok_or_else(invalid)?;
+        let (kind, target) = hook.split_once(':').ok_or_else(invalid)?;
+
+        let post_mint_hook: Arc<dyn PostMintHook> = match kind {
+            "webhook" => {
+                let client = http_client
+                    .client_builder()
+                    .build()
+                    .map_err(|e| PostMintHookConfigError::HttpClient(e.to_string()))?;
+                Arc::new(WebhookPostMintHook::new(target, client))
+            }
+            "invoke" => {
+                let (entrypoint, template) = target.trim().split_once(' ').ok_or_else(invalid)?;
+                let calldata_template = CalldataTemplate::parse(template)
+                    .map_err(PostMintHookConfigError::InvalidTemplate)?;
+                Arc::new(InvokeEntrypointPostMintHook::new(
+                    provider.clone(),
+                    account_addr,
+                    account_pk,
+                    chain_id,
+                    entrypoint,
+                    calldata_template,
+                    token_id_formats.for_project(project_id.trim()),
+                ))
+            }
+            _ => return Err(invalid()),
+        };
+        hooks.register(project_id.trim(), hook, post_mint_hook);
+    }
+
+    Ok(hooks)
+}
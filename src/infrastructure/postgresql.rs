@@ -1,26 +1,153 @@
 use crate::domain::{
-    bridge::{QueueError, QueueItem, QueueManager, QueueStatus, QueueUpdateError},
+    analytics::{
+        BatchAnalyticsError, BatchAnalyticsQuery, BatchAnalyticsRepository, BatchOutcome,
+        BatchRecord,
+    },
+    audit::{AuditError, AuditRepository, BridgeRequestAudit},
+    batch_size::{BatchSizeError, BatchSizeRepository, ProjectBatchSize},
+    breakglass::{BreakglassError, BreakglassMint, BreakglassRepository, BreakglassStatus},
+    bridge::{
+        QueueError, QueueEvent, QueueItem, QueueItemTransition, QueueManager, QueueStatus,
+        QueueUpdateError,
+    },
+    challenge::{Challenge, ChallengeError, ChallengeRepository},
+    check_cache::{CheckCacheError, CheckResult, CheckResultRepository},
+    funnel::{FunnelEvent, FunnelEventError, FunnelEventRepository},
+    health::{HealthCheck, HealthCheckError},
+    ids::{JunoAddress, ProjectId, QueueItemId, StarknetAddress, TokenId},
+    issue_tracker::{IssueRecord, IssueRecordRepository, IssueTrackerError},
+    maintenance::{MaintenanceError, MaintenanceRepository, TableStats},
+    pagination::{Cursor, Page, PageRequest},
+    post_mint::{PostMintError, PostMintExecution, PostMintExecutionRepository, PostMintStatus},
+    queue_snapshot::{QueueSnapshotEntry, QueueSnapshotError, QueueSnapshotRepository},
+    report::{DailyActivity, ReportError, ReportRepository, SignedDailyReport},
+    reverse_bridge::{
+        ReverseQueueError, ReverseQueueItem, ReverseQueueManager, ReverseQueueStatus,
+    },
     save_customer_data::{CustomerKeys, DataRepository, SaveCustomerDataError},
+    stats::{
+        ProjectCompletion, ProjectProgress, StatsError, StatsRepository, StatusTransition,
+        TimeRange,
+    },
+    status_message::StatusNote,
+    transfer_proof::{TransferProof, TransferProofError, TransferProofRepository},
+    wallet_link::{WalletLink, WalletLinkError, WalletLinkRepository},
+    warm_up::{WarmUp, WarmUpError},
+    webhook::{
+        WebhookDelivery, WebhookDeliveryAttempt, WebhookDeliveryStatus, WebhookError, WebhookEvent,
+        WebhookRepository, WebhookSubscription,
+    },
 };
+use crate::infrastructure::migrations::{migrate, verify_schema, MigrationError, MigrationMode};
 use async_trait::async_trait;
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
-use log::error;
+use futures::future::try_join_all;
+use log::{error, info};
 use postgres_types::{FromSql, ToSql};
-use std::sync::Arc;
-use tokio_postgres::{Config, Error, NoTls, Row};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_postgres::{Config, NoTls, Row};
 use uuid::Uuid;
 
-pub async fn get_connection(database_uri: &str) -> core::result::Result<Pool, Error> {
-    let config = database_uri.parse::<Config>()?;
+/// Builds the connection pool, brings the schema up to date according to `migrations`
+/// and refuses a schema of another release.
+pub async fn get_connection(
+    database_uri: &str,
+    migrations: MigrationMode,
+) -> core::result::Result<Pool, MigrationError> {
+    let config = database_uri
+        .parse::<Config>()
+        .map_err(|e| MigrationError::Connection(e.to_string()))?;
     let manager_config = ManagerConfig {
         recycling_method: RecyclingMethod::Verified,
     };
     let manager = Manager::from_config(config, NoTls, manager_config);
     let pool = Pool::builder(manager).max_size(16).build().unwrap();
 
+    let applied = migrate(&pool, migrations).await?;
+    if !applied.is_empty() {
+        info!(
+            "Database schema is up to date, {} migrations run",
+            applied.len()
+        );
+    }
+    verify_schema(&pool).await?;
+
     Ok(pool)
 }
 
+/// Opens `connections` pool connections at once and hands them back to the pool, so
+/// concurrent first requests do not each wait for a connection handshake.
+pub struct PostgresPoolWarmUp {
+    connection_pool: Arc<Pool>,
+    connections: usize,
+}
+
+impl PostgresPoolWarmUp {
+    pub fn new(connection_pool: Arc<Pool>, connections: usize) -> Self {
+        Self {
+            connection_pool,
+            connections,
+        }
+    }
+}
+
+#[async_trait]
+impl WarmUp for PostgresPoolWarmUp {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    async fn warm_up(&self) -> Result<(), WarmUpError> {
+        // Held together, a connection released early would only be checked out again,
+        // and never more than the pool holds or the last ones would wait forever
+        let connections = self.connections.min(self.connection_pool.status().max_size);
+        let clients = try_join_all((0..connections).map(|_| self.connection_pool.get()))
+            .await
+            .map_err(|e| WarmUpError::Failed(e.to_string()))?;
+        info!("Opened {} database connections", clients.len());
+
+        Ok(())
+    }
+}
+
+/// Readiness of the database, a pool whose connections died cannot serve anything.
+pub struct PostgresHealthCheck {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresHealthCheck {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for PostgresHealthCheck {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    async fn check(&self) -> Result<(), HealthCheckError> {
+        // Checked out connections are verified by the pool, the query proves the server
+        // still answers on them
+        let client = self
+            .connection_pool
+            .get()
+            .await
+            .map_err(|e| HealthCheckError::Failed(e.to_string()))?;
+        client
+            .simple_query("SELECT 1;")
+            .await
+            .map_err(|e| HealthCheckError::Failed(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
 pub struct PostgresDataRepository {
     connection_pool: Arc<Pool>,
 }
@@ -34,16 +161,17 @@ impl PostgresDataRepository {
 impl DataRepository for PostgresDataRepository {
     async fn save_customer_keys(&self, keys: CustomerKeys) -> Result<(), SaveCustomerDataError> {
         let client = self.connection_pool.clone().get().await.unwrap();
+        let token_ids = token_ids_to_strings(&keys.token_ids);
 
         let insert = client.execute(
             "INSERT INTO customer_keys (keplr_wallet_pubkey, project_id, token_ids) VALUES ($1, $2, $3)",
-            &[&keys.keplr_wallet_pubkey, &keys.project_id, &keys.token_ids]
+            &[&keys.keplr_wallet_pubkey.as_str(), &keys.project_id.as_str(), &token_ids]
             ).await;
         if insert.is_err() {
             error!("Error while inserting customer to database {:#?}", insert);
             let update = client.execute(
                 "UPDATE customer_keys SET token_ids = $1 WHERE keplr_wallet_pubkey = $2 AND project_id = $3",
-                &[&keys.token_ids, &keys.keplr_wallet_pubkey, &keys.project_id]).await;
+                &[&token_ids, &keys.keplr_wallet_pubkey.as_str(), &keys.project_id.as_str()]).await;
 
             if update.is_err() {
                 error!("Error while saving customer to database {:#?}", update);
@@ -62,15 +190,18 @@ impl DataRepository for PostgresDataRepository {
 
     async fn get_customer_keys(
         &self,
-        keplr_wallet_pubkey: &str,
-        project_id: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
     ) -> Result<CustomerKeys, SaveCustomerDataError> {
         let client = self.connection_pool.clone().get().await.unwrap();
 
         let query = client.prepare("SELECT * FROM customer_keys ck WHERE ck.keplr_wallet_pubkey = $1 AND ck.project_id = $2").await.unwrap();
 
         let rows = match client
-            .query(&query, &[&keplr_wallet_pubkey, &project_id])
+            .query(
+                &query,
+                &[&keplr_wallet_pubkey.as_str(), &project_id.as_str()],
+            )
             .await
         {
             Ok(r) => r,
@@ -81,13 +212,65 @@ impl DataRepository for PostgresDataRepository {
         }
         let row = &rows[0];
         let customer_keys = CustomerKeys {
-            keplr_wallet_pubkey: row.get::<usize, String>(1).into(),
-            project_id: row.get::<usize, String>(2).into(),
-            token_ids: row.get::<usize, Vec<String>>(3).into(),
+            keplr_wallet_pubkey: JunoAddress::unchecked(row.get::<usize, String>(1)),
+            project_id: ProjectId::unchecked(row.get::<usize, String>(2)),
+            token_ids: row
+                .get::<usize, Vec<String>>(3)
+                .into_iter()
+                .map(TokenId::unchecked)
+                .collect(),
         };
 
         Ok(customer_keys)
     }
+
+    async fn save_authorized_sender(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        sender: &JunoAddress,
+    ) -> Result<(), SaveCustomerDataError> {
+        let client = self.connection_pool.clone().get().await.unwrap();
+
+        match client
+            .execute(
+                "INSERT INTO authorized_senders (keplr_wallet_pubkey, project_id, sender) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+                &[&keplr_wallet_pubkey.as_str(), &project_id.as_str(), &sender.as_str()],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error while saving authorized sender to database {:#?}", e);
+                Err(SaveCustomerDataError::FailedToPersistToDatabase)
+            }
+        }
+    }
+
+    async fn get_authorized_senders(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+    ) -> Result<Vec<JunoAddress>, SaveCustomerDataError> {
+        let client = self.connection_pool.clone().get().await.unwrap();
+
+        match client
+            .query(
+                "SELECT sender FROM authorized_senders WHERE keplr_wallet_pubkey = $1 AND project_id = $2",
+                &[&keplr_wallet_pubkey.as_str(), &project_id.as_str()],
+            )
+            .await
+        {
+            Ok(rows) => Ok(rows
+                .iter()
+                .map(|r| JunoAddress::unchecked(r.get::<&str, String>("sender")))
+                .collect()),
+            Err(e) => {
+                error!("Error while fetching authorized senders {:#?}", e);
+                Err(SaveCustomerDataError::NotFound)
+            }
+        }
+    }
 }
 
 #[derive(FromSql, ToSql, Debug)]
@@ -101,6 +284,8 @@ pub enum PostgresQueueStatus {
     Success,
     #[postgres(name = "error")]
     Error,
+    #[postgres(name = "dead_letter")]
+    DeadLetter,
 }
 
 impl From<PostgresQueueStatus> for QueueStatus {
@@ -110,6 +295,7 @@ impl From<PostgresQueueStatus> for QueueStatus {
             PostgresQueueStatus::Processing => QueueStatus::Processing,
             PostgresQueueStatus::Success => QueueStatus::Success,
             PostgresQueueStatus::Error => QueueStatus::Error,
+            PostgresQueueStatus::DeadLetter => QueueStatus::DeadLetter,
         }
     }
 }
@@ -121,6 +307,7 @@ impl Into<PostgresQueueStatus> for QueueStatus {
             QueueStatus::Processing => PostgresQueueStatus::Processing,
             QueueStatus::Success => PostgresQueueStatus::Success,
             QueueStatus::Error => PostgresQueueStatus::Error,
+            QueueStatus::DeadLetter => PostgresQueueStatus::DeadLetter,
         }
     }
 }
@@ -128,16 +315,18 @@ impl Into<PostgresQueueStatus> for QueueStatus {
 pub struct PostgresQueueManager {
     connection_pool: Arc<Pool>,
     batch_size: u8,
+    worker_id: String,
 }
 
 #[async_trait]
 impl QueueManager for PostgresQueueManager {
     async fn enqueue(
         &self,
-        keplr_wallet_pubkey: &str,
-        starknet_wallet_pubkey: &str,
-        project_id: &str,
-        token_ids: Vec<String>,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
     ) -> Result<Vec<QueueItem>, QueueError> {
         let mut client = self.connection_pool.clone().get().await.unwrap();
 
@@ -145,24 +334,47 @@ impl QueueManager for PostgresQueueManager {
         let tx_builder = client.build_transaction();
         let tx = tx_builder.start().await.unwrap();
         for token in &token_ids {
-            let insert = match tx.execute(
-                "INSERT INTO migration_queue (keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id) VALUES ($1, $2, $3, $4)",
-                &[&keplr_wallet_pubkey, &starknet_wallet_pubkey, &project_id, &token]
+            // A token waiting to be minted keeps its item, resubmitting it cannot mint it twice
+            let inserted = match tx.query_opt(
+                "INSERT INTO migration_queue (keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, updated_by, request_id) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (project_id, token_id) WHERE migration_status IN ('pending', 'processing') DO NOTHING RETURNING id",
+                &[&keplr_wallet_pubkey.as_str(), &starknet_wallet_pubkey.as_str(), &project_id.as_str(), &token.as_str(), &self.worker_id, &request_id]
             ).await {
-                Ok(i) => i,
+                Ok(Some(row)) => row,
+                Ok(None) => {
+                    let existing = match tx.query(
+                        "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id FROM migration_queue WHERE project_id = $1 AND token_id = $2 AND migration_status IN ('pending', 'processing');",
+                        &[&project_id.as_str(), &token.as_str()],
+                    ).await {
+                        Ok(rows) => hydrate_queue_items(rows),
+                        Err(e) => {
+                            error!("{:#?}", e);
+                            return Err(QueueError::FailedToEnqueue);
+                        },
+                    };
+                    match existing.into_iter().next() {
+                        Some(item) => {
+                            info!("Token {} is already queued as {:?}", token, item.id);
+                            queue_items.push(item);
+                            continue;
+                        }
+                        None => return Err(QueueError::FailedToEnqueue),
+                    }
+                },
                 Err(e) => {
                     error!("{:#?}", e);
                     return Err(QueueError::FailedToEnqueue);
                 },
             };
-            println!("{:#?}", insert);
 
-            queue_items.push(QueueItem::new(
+            let mut item = QueueItem::new(
                 keplr_wallet_pubkey,
                 starknet_wallet_pubkey,
                 project_id,
-                token.to_string(),
-            ));
+                token.clone(),
+            );
+            item.id = Some(QueueItemId::from(inserted.get::<&str, Uuid>("id")));
+            item.request_id = request_id.map(String::from);
+            queue_items.push(item);
         }
 
         match tx.commit().await {
@@ -174,12 +386,26 @@ impl QueueManager for PostgresQueueManager {
         }
     }
 
-    async fn get_batch(&self) -> Result<Vec<QueueItem>, QueueError> {
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
+    ) -> Result<Vec<QueueItem>, QueueError> {
         let client = self.connection_pool.get().await.unwrap();
+        let excluded_projects: Vec<&str> = excluded_projects
+            .iter()
+            .map(StarknetAddress::as_str)
+            .collect();
+        // Rows are claimed as processing in one statement, rows another worker is
+        // claiming are skipped rather than waited for so replicas never share a batch
         let rows = match client
             .query(
-                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status FROM migration_queue WHERE transaction_hash IS NULL LIMIT $1;",
-                &[&(self.batch_size as i64)],
+                "UPDATE migration_queue SET migration_status = $3, transaction_hash = '', updated_by = $4 WHERE id IN (SELECT id FROM migration_queue WHERE transaction_hash IS NULL AND (next_attempt_at IS NULL OR next_attempt_at <= NOW()) AND NOT (project_id = ANY($2)) AND NOT EXISTS (SELECT 1 FROM token_locks l WHERE l.project_id = migration_queue.project_id AND l.token_id = migration_queue.token_id AND NOW() < l.locked_until) ORDER BY created_at, id LIMIT $1 FOR UPDATE SKIP LOCKED) RETURNING id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id;",
+                &[
+                    &(self.batch_size as i64),
+                    &excluded_projects,
+                    &PostgresQueueStatus::Processing,
+                    &self.worker_id,
+                ],
             )
             .await
         {
@@ -190,20 +416,20 @@ impl QueueManager for PostgresQueueManager {
             },
         };
 
-        let queue_items = self.hydrate_queue_items(rows);
+        let queue_items = hydrate_queue_items(rows);
         Ok(queue_items)
     }
 
     async fn get_customer_migration_state(
         &self,
-        keplr_wallet_pubkey: &str,
-        project_id: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
     ) -> Vec<QueueItem> {
         let client = self.connection_pool.get().await.unwrap();
         let rows = match client
             .query(
-                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status FROM migration_queue WHERE keplr_wallet_pubkey = $1 AND project_id = $2;",
-                &[&keplr_wallet_pubkey, &project_id],
+                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id FROM migration_queue WHERE keplr_wallet_pubkey = $1 AND project_id = $2;",
+                &[&keplr_wallet_pubkey.as_str(), &project_id.as_str()],
             )
             .await
         {
@@ -214,23 +440,40 @@ impl QueueManager for PostgresQueueManager {
             }
         };
 
-        let queue_items = self.hydrate_queue_items(rows);
+        let queue_items = hydrate_queue_items(rows);
         queue_items
     }
 
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id FROM migration_queue WHERE keplr_wallet_pubkey = $1 ORDER BY project_id, created_at, id;",
+                &[&keplr_wallet_pubkey.as_str()],
+            )
+            .await
+        {
+            Ok(rows) => Ok(hydrate_queue_items(rows)),
+            Err(err) => {
+                error!("Error while fetching wallet migrations : {:#?}", err);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
     async fn update_queue_items_status(
         &self,
-        ids: &Vec<String>,
+        ids: &[QueueItemId],
         transaction_hash: String,
         status: QueueStatus,
     ) -> Result<(), QueueUpdateError> {
         let client = self.connection_pool.get().await.unwrap();
 
-        let uuids = ids
-            .iter()
-            .map(|id| Uuid::parse_str(id.as_str()).unwrap())
-            .collect::<Vec<Uuid>>();
-        match client.execute("UPDATE migration_queue SET migration_status = $1, transaction_hash = $2 WHERE id = ANY($3);", &[&<QueueStatus as Into<PostgresQueueStatus>>::into(status), &transaction_hash, &uuids]).await {
+        let uuids = queue_item_uuids(ids);
+        match client.execute("UPDATE migration_queue SET migration_status = $1, transaction_hash = $2, note = NULL, note_params = NULL, updated_by = $4 WHERE id = ANY($3);", &[&<QueueStatus as Into<PostgresQueueStatus>>::into(status), &transaction_hash, &uuids, &self.worker_id]).await {
             Ok(num_rows) =>  {
                 if usize::try_from(num_rows).unwrap() == ids.len() {
                     return Ok(());
@@ -245,30 +488,2321 @@ impl QueueManager for PostgresQueueManager {
             }
         };
     }
-}
 
-impl PostgresQueueManager {
-    pub fn new(connection_pool: Arc<Pool>, batch_size: u8) -> Self {
-        Self {
-            connection_pool,
-            batch_size,
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        // Batches are claimed by get_batch, rows requeued since then are left alone
+        let uuids = queue_item_uuids(ids);
+        match client
+            .query(
+                "UPDATE migration_queue SET transaction_hash = '', note = NULL, note_params = NULL, updated_by = $2 WHERE id = ANY($1) AND migration_status = $3 AND updated_by = $2 RETURNING id;",
+                &[&uuids, &self.worker_id, &PostgresQueueStatus::Processing],
+            )
+            .await
+        {
+            Ok(rows) => Ok(rows
+                .iter()
+                .map(|row| QueueItemId::from(row.get::<&str, Uuid>("id")))
+                .collect()),
+            Err(e) => {
+                error!("Failed to claim queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
         }
     }
 
-    fn hydrate_queue_items(&self, rows: Vec<Row>) -> Vec<QueueItem> {
-        let mut queue_items = Vec::new();
-        for row in rows {
-            let tx_hash: Option<String> = row.get("transaction_hash");
-            queue_items.push(QueueItem {
-                id: row.get("id"),
-                keplr_wallet_pubkey: row.get::<&str, String>("keplr_wallet_pubkey").into(),
-                starknet_wallet_pubkey: row.get::<&str, String>("starknet_wallet_pubkey").into(),
-                project_id: row.get::<&str, String>("project_id").into(),
-                token_id: row.get::<&str, String>("token_id").into(),
-                transaction_hash: tx_hash,
+    async fn get_queue_item_history(
+        &self,
+        id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT migration_status, transaction_hash, worker_id, (EXTRACT(EPOCH FROM created_at) * 1000)::BIGINT AS created_at FROM migration_queue_history WHERE queue_item_id = $1 ORDER BY created_at ASC;",
+                &[id.as_uuid()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch queue item history {:#?}", e);
+                return Err(QueueError::NotFound);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| QueueItemTransition {
                 status: QueueStatus::from(row.get::<&str, PostgresQueueStatus>("migration_status")),
-            });
+                transaction_hash: row.get("transaction_hash"),
+                worker_id: row.get("worker_id"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        let uuids = queue_item_uuids(ids);
+        match client
+            .execute(
+                "UPDATE migration_queue SET migration_status = $1, transaction_hash = NULL, note = $2, note_params = $5, updated_by = $4 WHERE id = ANY($3);",
+                &[&PostgresQueueStatus::Pending, &note.template, &uuids, &self.worker_id, &note_params_json(note)],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to defer queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
         }
-        queue_items
+    }
+
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id FROM migration_queue WHERE migration_status = $1;",
+                &[&PostgresQueueStatus::Processing],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch processing queue items {:#?}", e);
+                return Err(QueueError::FailedToGetBatch);
+            }
+        };
+
+        Ok(hydrate_queue_items(rows))
+    }
+
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id FROM migration_queue WHERE migration_status = $1 AND (updated_by = $2 OR COALESCE(transaction_hash, '') <> '' OR updated_at < TO_TIMESTAMP($3::BIGINT / 1000.0));",
+                &[&PostgresQueueStatus::Processing, &self.worker_id, &stale_before],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch recoverable queue items {:#?}", e);
+                return Err(QueueError::FailedToGetBatch);
+            }
+        };
+
+        Ok(hydrate_queue_items(rows))
+    }
+
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "UPDATE migration_queue SET migration_status = $1, transaction_hash = NULL, note = $2, note_params = $3, updated_by = $4 WHERE migration_status = $5 AND COALESCE(transaction_hash, '') = '' AND updated_at < TO_TIMESTAMP($6::BIGINT / 1000.0) RETURNING id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id;",
+                &[&PostgresQueueStatus::Pending, &note.template, &note_params_json(note), &self.worker_id, &PostgresQueueStatus::Processing, &before],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to requeue stale processing items in database {:#?}", e);
+                return Err(QueueError::FailedToGetBatch);
+            }
+        };
+
+        Ok(hydrate_queue_items(rows))
+    }
+
+    async fn count_pending_items(&self) -> Result<i64, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query_one(
+                "SELECT COUNT(*) AS pending FROM migration_queue WHERE migration_status = $1;",
+                &[&PostgresQueueStatus::Pending],
+            )
+            .await
+        {
+            Ok(row) => Ok(row.get::<&str, i64>("pending")),
+            Err(e) => {
+                error!("Failed to count pending queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id FROM migration_queue WHERE id = $1;",
+                &[id.as_uuid()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch queue item {:#?}", e);
+                return Err(QueueError::FailedToGetBatch);
+            }
+        };
+
+        match hydrate_queue_items(rows).pop() {
+            Some(qi) => Ok(qi),
+            None => Err(QueueError::NotFound),
+        }
+    }
+
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        let uuids = queue_item_uuids(ids);
+        // Empty hash keeps cancelled items out of get_batch
+        match client
+            .execute(
+                "UPDATE migration_queue SET migration_status = $1, transaction_hash = COALESCE(transaction_hash, ''), note = $2, note_params = $5, updated_by = $4 WHERE id = ANY($3);",
+                &[&PostgresQueueStatus::Error, &note.template, &uuids, &self.worker_id, &note_params_json(note)],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to cancel queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        let uuids = queue_item_uuids(ids);
+        match client
+            .execute(
+                "UPDATE migration_queue SET migration_status = $1, transaction_hash = NULL, note = $2, note_params = $6, attempts = attempts + 1, next_attempt_at = NOW() + make_interval(secs => $5), updated_by = $4 WHERE id = ANY($3);",
+                &[&PostgresQueueStatus::Pending, &note.template, &uuids, &self.worker_id, &delay.as_secs_f64(), &note_params_json(note)],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to schedule queue items retry in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        let uuids = queue_item_uuids(ids);
+        // Empty hash keeps dead letters out of get_batch, same as cancelled items
+        match client
+            .execute(
+                "UPDATE migration_queue SET migration_status = $1, transaction_hash = COALESCE(transaction_hash, ''), note = $2, note_params = $5, attempts = attempts + 1, next_attempt_at = NULL, updated_by = $4 WHERE id = ANY($3);",
+                &[&PostgresQueueStatus::DeadLetter, &note.template, &uuids, &self.worker_id, &note_params_json(note)],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to dead letter queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        let uuids = queue_item_uuids(ids);
+        match client
+            .execute(
+                "UPDATE migration_queue SET migration_status = $1, transaction_hash = NULL, note = $2, note_params = $6, attempts = 0, next_attempt_at = NULL, updated_by = $4 WHERE id = ANY($3) AND migration_status = $5;",
+                &[&PostgresQueueStatus::Pending, &note.template, &uuids, &self.worker_id, &PostgresQueueStatus::DeadLetter, &note_params_json(note)],
+            )
+            .await
+        {
+            Ok(num_rows) if usize::try_from(num_rows).unwrap() == ids.len() => Ok(()),
+            Ok(_) => Err(QueueUpdateError::StatusUpdateFail(ids.to_vec())),
+            Err(e) => {
+                error!("Failed to requeue dead letters in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError> {
+        let (after_created_at, after_id) = cursor_params(&page.after);
+        let status = status.map(<QueueStatus as Into<PostgresQueueStatus>>::into);
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id, created_at FROM migration_queue WHERE ($1::TIMESTAMPTZ IS NULL OR (created_at, id) > ($1, $2)) AND ($3::migration_status_values IS NULL OR migration_status = $3) ORDER BY created_at ASC, id ASC LIMIT $4;",
+                &[&after_created_at, &after_id, &status, &(page.limit + 1)],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to list queue items {:#?}", e);
+                return Err(QueueError::FailedToGetBatch);
+            }
+        };
+
+        let cursors: Vec<Cursor> = rows.iter().map(row_cursor).collect();
+        let queue_items = hydrate_queue_items(rows);
+        Ok(Page::from_rows(
+            cursors.into_iter().zip(queue_items).collect(),
+            page.limit,
+        ))
+    }
+
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError> {
+        let (after_created_at, after_id) = cursor_params(&page.after);
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, queue_item_id, migration_status, transaction_hash, worker_id, created_at FROM migration_queue_history WHERE $1::TIMESTAMPTZ IS NULL OR (created_at, id) > ($1, $2) ORDER BY created_at ASC, id ASC LIMIT $3;",
+                &[&after_created_at, &after_id, &(page.limit + 1)],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to list queue events {:#?}", e);
+                return Err(QueueError::FailedToGetBatch);
+            }
+        };
+
+        Ok(Page::from_rows(
+            rows.iter()
+                .map(|row| {
+                    let cursor = row_cursor(row);
+                    let event = QueueEvent {
+                        id: row.get("id"),
+                        queue_item_id: QueueItemId::from(row.get::<&str, Uuid>("queue_item_id")),
+                        status: QueueStatus::from(
+                            row.get::<&str, PostgresQueueStatus>("migration_status"),
+                        ),
+                        transaction_hash: row.get("transaction_hash"),
+                        worker_id: row.get("worker_id"),
+                        created_at: cursor.created_at / 1000,
+                    };
+                    (cursor, event)
+                })
+                .collect(),
+            page.limit,
+        ))
+    }
+
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        let uuids = queue_item_uuids(ids);
+        match client
+            .execute(
+                "UPDATE migration_queue SET estimated_fee = $1::TEXT::NUMERIC, actual_fee = $2::TEXT::NUMERIC WHERE id = ANY($3);",
+                &[&estimated_fee, &actual_fee, &uuids],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to record mint fees in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        // A row cannot be upserted twice by the same statement
+        let mut tokens: Vec<&str> = token_ids.iter().map(TokenId::as_str).collect();
+        tokens.sort_unstable();
+        tokens.dedup();
+        let rows = match client
+            .query(
+                "INSERT INTO token_locks (project_id, token_id, holder, locked_until) SELECT $1, t, $3, NOW() + make_interval(secs => $4) FROM UNNEST($2::VARCHAR[]) AS t ON CONFLICT (project_id, token_id) DO UPDATE SET holder = EXCLUDED.holder, locked_until = EXCLUDED.locked_until WHERE token_locks.holder = EXCLUDED.holder OR token_locks.locked_until <= NOW() RETURNING token_id;",
+                &[&project_id.as_str(), &tokens, &holder, &ttl.as_secs_f64()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to lock tokens in database {:#?}", e);
+                return Err(QueueError::FailedToLockTokens);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| TokenId::unchecked(row.get::<&str, String>("token_id")))
+            .collect())
+    }
+
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+
+        let tokens: Vec<&str> = token_ids.iter().map(TokenId::as_str).collect();
+        match client
+            .execute(
+                "DELETE FROM token_locks WHERE project_id = $1 AND token_id = ANY($2) AND holder = $3;",
+                &[&project_id.as_str(), &tokens, &holder],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to unlock tokens in database {:#?}", e);
+                Err(QueueError::FailedToLockTokens)
+            }
+        }
+    }
+}
+
+impl PostgresQueueManager {
+    pub fn new(connection_pool: Arc<Pool>, batch_size: u8, worker_id: &str) -> Self {
+        Self {
+            connection_pool,
+            batch_size,
+            worker_id: worker_id.into(),
+        }
+    }
+}
+
+fn hydrate_queue_items(rows: Vec<Row>) -> Vec<QueueItem> {
+    let mut queue_items = Vec::new();
+    for row in rows {
+        let tx_hash: Option<String> = row.get("transaction_hash");
+        queue_items.push(QueueItem {
+            id: Some(QueueItemId::from(row.get::<&str, Uuid>("id"))),
+            keplr_wallet_pubkey: JunoAddress::unchecked(
+                row.get::<&str, String>("keplr_wallet_pubkey"),
+            ),
+            starknet_wallet_pubkey: StarknetAddress::unchecked(
+                row.get::<&str, String>("starknet_wallet_pubkey"),
+            ),
+            project_id: StarknetAddress::unchecked(row.get::<&str, String>("project_id")),
+            token_id: TokenId::unchecked(row.get::<&str, String>("token_id")),
+            transaction_hash: tx_hash,
+            status: QueueStatus::from(row.get::<&str, PostgresQueueStatus>("migration_status")),
+            note: row.get("note"),
+            note_params: row
+                .get::<&str, Option<String>>("note_params")
+                .and_then(|p| serde_json::from_str(&p).ok())
+                .unwrap_or_default(),
+            attempts: row.get("attempts"),
+            estimated_fee: row.get("estimated_fee"),
+            actual_fee: row.get("actual_fee"),
+            request_id: row.get("request_id"),
+        });
+    }
+    queue_items
+}
+
+// Parameters are kept as a JSON object, NULL when the message has none
+fn params_json(params: &BTreeMap<String, String>) -> Option<String> {
+    match params.is_empty() {
+        true => None,
+        false => serde_json::to_string(params).ok(),
+    }
+}
+
+fn note_params_json(note: &StatusNote) -> Option<String> {
+    params_json(&note.params)
+}
+
+fn queue_item_uuids(ids: &[QueueItemId]) -> Vec<Uuid> {
+    ids.iter().map(|id| *id.as_uuid()).collect()
+}
+
+fn token_ids_to_strings(token_ids: &[TokenId]) -> Vec<String> {
+    token_ids.iter().map(|t| t.to_string()).collect()
+}
+
+fn cursor_params(after: &Option<Cursor>) -> (Option<SystemTime>, Option<Uuid>) {
+    match after {
+        Some(c) => (
+            Some(UNIX_EPOCH + Duration::from_micros(c.created_at.max(0) as u64)),
+            Some(c.id),
+        ),
+        None => (None, None),
+    }
+}
+
+fn row_cursor(row: &Row) -> Cursor {
+    let created_at = row
+        .get::<&str, SystemTime>("created_at")
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as i64)
+        .unwrap_or_default();
+    Cursor::new(created_at, row.get("id"))
+}
+
+pub struct PostgresQueueSnapshotRepository {
+    connection_pool: Arc<Pool>,
+    worker_id: String,
+}
+
+impl PostgresQueueSnapshotRepository {
+    pub fn new(connection_pool: Arc<Pool>, worker_id: &str) -> Self {
+        Self {
+            connection_pool,
+            worker_id: worker_id.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl QueueSnapshotRepository for PostgresQueueSnapshotRepository {
+    async fn load_queue(&self) -> Result<Vec<QueueSnapshotEntry>, QueueSnapshotError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let items = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee::TEXT AS estimated_fee, actual_fee::TEXT AS actual_fee, request_id FROM migration_queue ORDER BY created_at ASC, id ASC;",
+                &[],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to load queue for snapshot {:#?}", e);
+                return Err(QueueSnapshotError::PersistenceIssue(e.to_string()));
+            }
+        };
+        let history = match client
+            .query(
+                "SELECT queue_item_id, migration_status, transaction_hash, worker_id, (EXTRACT(EPOCH FROM created_at) * 1000)::BIGINT AS created_at FROM migration_queue_history ORDER BY created_at ASC, id ASC;",
+                &[],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to load queue history for snapshot {:#?}", e);
+                return Err(QueueSnapshotError::PersistenceIssue(e.to_string()));
+            }
+        };
+
+        let mut transitions: HashMap<Uuid, Vec<QueueItemTransition>> = HashMap::new();
+        for row in history {
+            transitions
+                .entry(row.get("queue_item_id"))
+                .or_default()
+                .push(QueueItemTransition {
+                    status: QueueStatus::from(
+                        row.get::<&str, PostgresQueueStatus>("migration_status"),
+                    ),
+                    transaction_hash: row.get("transaction_hash"),
+                    worker_id: row.get("worker_id"),
+                    created_at: row.get("created_at"),
+                });
+        }
+
+        Ok(hydrate_queue_items(items)
+            .into_iter()
+            .map(|item| QueueSnapshotEntry {
+                history: item
+                    .id
+                    .and_then(|id| transitions.remove(id.as_uuid()))
+                    .unwrap_or_default(),
+                item,
+            })
+            .collect())
+    }
+
+    async fn restore_queue(
+        &self,
+        entries: &[QueueSnapshotEntry],
+    ) -> Result<(), QueueSnapshotError> {
+        let mut client = self.connection_pool.get().await.unwrap();
+        let tx = match client.build_transaction().start().await {
+            Ok(tx) => tx,
+            Err(e) => return Err(QueueSnapshotError::PersistenceIssue(e.to_string())),
+        };
+
+        for entry in entries {
+            let item = &entry.item;
+            let id = match item.id {
+                Some(id) => *id.as_uuid(),
+                None => {
+                    return Err(QueueSnapshotError::PersistenceIssue(
+                        "Queue item without id".into(),
+                    ))
+                }
+            };
+            let status: PostgresQueueStatus = item.status.clone().into();
+            // Item keeps the date of its first recorded transition, restore time otherwise
+            let created_at = entry.history.first().map(|t| t.created_at);
+
+            // Any failure drops the transaction, so nothing is partially restored
+            if let Err(e) = tx
+                .execute(
+                    "INSERT INTO migration_queue (id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, migration_status, transaction_hash, attempts, note, updated_by, created_at, note_params, estimated_fee, actual_fee, request_id) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, COALESCE(TO_TIMESTAMP($11::BIGINT / 1000.0), NOW()), $12, $13::TEXT::NUMERIC, $14::TEXT::NUMERIC, $15);",
+                    &[&id, &item.keplr_wallet_pubkey.as_str(), &item.starknet_wallet_pubkey.as_str(), &item.project_id.as_str(), &item.token_id.as_str(), &status, &item.transaction_hash, &item.attempts, &item.note, &self.worker_id, &created_at, &params_json(&item.note_params), &item.estimated_fee, &item.actual_fee, &item.request_id],
+                )
+                .await
+            {
+                error!("Failed to restore queue item {} {:#?}", id, e);
+                return Err(QueueSnapshotError::PersistenceIssue(e.to_string()));
+            }
+
+            // History written by the insert trigger is replaced with the recorded one
+            if let Err(e) = tx
+                .execute(
+                    "DELETE FROM migration_queue_history WHERE queue_item_id = $1;",
+                    &[&id],
+                )
+                .await
+            {
+                return Err(QueueSnapshotError::PersistenceIssue(e.to_string()));
+            }
+            for transition in &entry.history {
+                let status: PostgresQueueStatus = transition.status.clone().into();
+                if let Err(e) = tx
+                    .execute(
+                        "INSERT INTO migration_queue_history (queue_item_id, migration_status, transaction_hash, worker_id, created_at) VALUES ($1, $2, $3, $4, TO_TIMESTAMP($5::BIGINT / 1000.0));",
+                        &[&id, &status, &transition.transaction_hash, &transition.worker_id, &transition.created_at],
+                    )
+                    .await
+                {
+                    error!("Failed to restore queue item history {} {:#?}", id, e);
+                    return Err(QueueSnapshotError::PersistenceIssue(e.to_string()));
+                }
+            }
+        }
+
+        match tx.commit().await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to commit queue restore {:#?}", e);
+                Err(QueueSnapshotError::PersistenceIssue(e.to_string()))
+            }
+        }
+    }
+}
+
+pub struct PostgresStatsRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresStatsRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl StatsRepository for PostgresStatsRepository {
+    async fn get_status_transitions(
+        &self,
+        range: &TimeRange,
+        interval_ms: i64,
+    ) -> Result<Vec<StatusTransition>, StatsError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT mq.project_id, h.migration_status, (FLOOR(EXTRACT(EPOCH FROM h.created_at) * 1000 / $3::BIGINT) * $3::BIGINT)::BIGINT AS bucket, COUNT(*) AS count FROM migration_queue_history h INNER JOIN migration_queue mq ON mq.id = h.queue_item_id WHERE h.created_at BETWEEN TO_TIMESTAMP($1::BIGINT / 1000.0) AND TO_TIMESTAMP($2::BIGINT / 1000.0) GROUP BY mq.project_id, h.migration_status, bucket ORDER BY bucket;",
+                &[&range.from, &range.to, &interval_ms],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch status transitions {:#?}", e);
+                return Err(StatsError::FailedToFetchStats);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| StatusTransition {
+                project_id: row.get("project_id"),
+                status: QueueStatus::from(row.get::<&str, PostgresQueueStatus>("migration_status")),
+                bucket: row.get("bucket"),
+                count: row.get("count"),
+            })
+            .collect())
+    }
+
+    async fn get_project_progress(
+        &self,
+        range: &TimeRange,
+    ) -> Result<Vec<ProjectProgress>, StatsError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT project_id, COUNT(*) FILTER (WHERE migration_status = 'pending') AS pending, COUNT(*) FILTER (WHERE migration_status = 'processing') AS processing, COUNT(*) FILTER (WHERE migration_status = 'success') AS success, COUNT(*) FILTER (WHERE migration_status = 'error') AS error FROM migration_queue WHERE created_at BETWEEN TO_TIMESTAMP($1::BIGINT / 1000.0) AND TO_TIMESTAMP($2::BIGINT / 1000.0) GROUP BY project_id ORDER BY project_id;",
+                &[&range.from, &range.to],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch project progress {:#?}", e);
+                return Err(StatsError::FailedToFetchStats);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| ProjectProgress {
+                project_id: row.get("project_id"),
+                pending: row.get("pending"),
+                processing: row.get("processing"),
+                success: row.get("success"),
+                error: row.get("error"),
+            })
+            .collect())
+    }
+
+    async fn get_project_completions(&self) -> Result<Vec<ProjectCompletion>, StatsError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT project_id, COUNT(*) AS total, COUNT(*) FILTER (WHERE migration_status = 'success') AS migrated, (AVG(EXTRACT(EPOCH FROM (updated_at - created_at))) FILTER (WHERE migration_status = 'success'))::FLOAT8 AS average_completion_seconds FROM migration_queue GROUP BY project_id ORDER BY project_id;",
+                &[],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch project completions {:#?}", e);
+                return Err(StatsError::FailedToFetchStats);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| ProjectCompletion {
+                project_id: row.get("project_id"),
+                total: row.get("total"),
+                migrated: row.get("migrated"),
+                average_completion_seconds: row.get("average_completion_seconds"),
+            })
+            .collect())
+    }
+}
+
+pub struct PostgresWalletLinkRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresWalletLinkRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl WalletLinkRepository for PostgresWalletLinkRepository {
+    async fn get_link(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<WalletLink, WalletLinkError> {
+        let client = match self.connection_pool.get().await {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Failed to get connection to fetch wallet link {:#?}", e);
+                return Err(WalletLinkError::FailedToFetch);
+            }
+        };
+        let rows = match client
+            .query(
+                "SELECT keplr_wallet_pubkey, starknet_account_addr FROM wallet_links WHERE keplr_wallet_pubkey = $1;",
+                &[&keplr_wallet_pubkey.as_str()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch wallet link {:#?}", e);
+                return Err(WalletLinkError::FailedToFetch);
+            }
+        };
+        if 0 == rows.len() {
+            return Err(WalletLinkError::NotFound);
+        }
+
+        Ok(WalletLink {
+            keplr_wallet_pubkey: JunoAddress::unchecked(
+                rows[0].get::<&str, String>("keplr_wallet_pubkey"),
+            ),
+            starknet_account_addr: StarknetAddress::unchecked(
+                rows[0].get::<&str, String>("starknet_account_addr"),
+            ),
+        })
+    }
+
+    async fn save_link(&self, link: WalletLink) -> Result<(), WalletLinkError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO wallet_links (keplr_wallet_pubkey, starknet_account_addr) VALUES ($1, $2) ON CONFLICT (keplr_wallet_pubkey) DO UPDATE SET starknet_account_addr = EXCLUDED.starknet_account_addr, verified_at = NOW();",
+                &[
+                    &link.keplr_wallet_pubkey.as_str(),
+                    &link.starknet_account_addr.as_str(),
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist wallet link {:#?}", e);
+                Err(WalletLinkError::FailedToPersist)
+            }
+        }
+    }
+
+    async fn link_if_absent(&self, link: WalletLink) -> Result<WalletLink, WalletLinkError> {
+        let client = match self.connection_pool.get().await {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Failed to get connection to persist wallet link {:#?}", e);
+                return Err(WalletLinkError::FailedToPersist);
+            }
+        };
+        // Concurrent first requests race on the insert, the link that won is read back
+        if let Err(e) = client
+            .execute(
+                "INSERT INTO wallet_links (keplr_wallet_pubkey, starknet_account_addr) VALUES ($1, $2) ON CONFLICT (keplr_wallet_pubkey) DO NOTHING;",
+                &[
+                    &link.keplr_wallet_pubkey.as_str(),
+                    &link.starknet_account_addr.as_str(),
+                ],
+            )
+            .await
+        {
+            error!("Failed to persist wallet link {:#?}", e);
+            return Err(WalletLinkError::FailedToPersist);
+        }
+
+        self.get_link(&link.keplr_wallet_pubkey).await
+    }
+}
+
+pub struct PostgresChallengeRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresChallengeRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl ChallengeRepository for PostgresChallengeRepository {
+    async fn save_challenge(&self, challenge: &Challenge) -> Result<(), ChallengeError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO signature_challenges (nonce, keplr_wallet_pubkey, expires_at) VALUES ($1, $2, $3);",
+                &[
+                    &challenge.nonce,
+                    &challenge.keplr_wallet_pubkey.as_str(),
+                    &challenge.expires_at,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist challenge {:#?}", e);
+                Err(ChallengeError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn consume_challenge(
+        &self,
+        nonce: &str,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Challenge, ChallengeError> {
+        let client = self.connection_pool.get().await.unwrap();
+        // Conditional update so two concurrent requests cannot both consume the nonce, nor
+        // another wallet burn it
+        let rows = match client
+            .query(
+                "UPDATE signature_challenges SET used_at = NOW() WHERE nonce = $1 AND keplr_wallet_pubkey = $2 AND used_at IS NULL RETURNING keplr_wallet_pubkey, expires_at;",
+                &[&nonce, &keplr_wallet_pubkey.as_str()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to consume challenge {:#?}", e);
+                return Err(ChallengeError::PersistenceIssue);
+            }
+        };
+        if let Some(row) = rows.first() {
+            return Ok(Challenge {
+                nonce: nonce.to_string(),
+                keplr_wallet_pubkey: JunoAddress::unchecked(
+                    row.get::<&str, String>("keplr_wallet_pubkey"),
+                ),
+                expires_at: row.get::<&str, i64>("expires_at"),
+            });
+        }
+
+        match client
+            .query(
+                "SELECT nonce FROM signature_challenges WHERE nonce = $1 AND keplr_wallet_pubkey = $2;",
+                &[&nonce, &keplr_wallet_pubkey.as_str()],
+            )
+            .await
+        {
+            Ok(r) if r.is_empty() => {
+                error!(
+                    "Challenge {} was not issued to {}",
+                    nonce, keplr_wallet_pubkey
+                );
+                Err(ChallengeError::NotFound)
+            }
+            Ok(_) => Err(ChallengeError::AlreadyUsed),
+            Err(e) => {
+                error!("Failed to fetch challenge {:#?}", e);
+                Err(ChallengeError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresBreakglassRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresBreakglassRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+fn breakglass_status_to_str(status: &BreakglassStatus) -> &'static str {
+    match status {
+        BreakglassStatus::AwaitingConfirmation => "awaiting_confirmation",
+        BreakglassStatus::Confirming => "confirming",
+        BreakglassStatus::Submitted => "submitted",
+        BreakglassStatus::Minted => "minted",
+        BreakglassStatus::Failed => "failed",
+        BreakglassStatus::Expired => "expired",
+    }
+}
+
+fn row_to_breakglass_mint(row: &Row) -> BreakglassMint {
+    BreakglassMint {
+        id: row.get("id"),
+        starknet_project_addr: StarknetAddress::unchecked(
+            row.get::<&str, String>("starknet_project_addr"),
+        ),
+        starknet_account_addr: StarknetAddress::unchecked(
+            row.get::<&str, String>("starknet_account_addr"),
+        ),
+        token_ids: row
+            .get::<&str, Vec<String>>("token_ids")
+            .into_iter()
+            .map(TokenId::unchecked)
+            .collect(),
+        reason: row.get("reason"),
+        requested_by: row.get("requested_by"),
+        requested_at: row.get("requested_at"),
+        confirmed_by: row.get("confirmed_by"),
+        transaction_hash: row.get("transaction_hash"),
+        status: match row.get::<&str, &str>("status") {
+            "confirming" => BreakglassStatus::Confirming,
+            "submitted" => BreakglassStatus::Submitted,
+            "minted" => BreakglassStatus::Minted,
+            "failed" => BreakglassStatus::Failed,
+            "expired" => BreakglassStatus::Expired,
+            _ => BreakglassStatus::AwaitingConfirmation,
+        },
+    }
+}
+
+#[async_trait]
+impl BreakglassRepository for PostgresBreakglassRepository {
+    async fn save(&self, mint: &BreakglassMint) -> Result<(), BreakglassError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO breakglass_mints (id, starknet_project_addr, starknet_account_addr, token_ids, reason, requested_by, requested_at, confirmed_by, transaction_hash, status) VALUES ($1, $2, $3, $4, $5, $6, TO_TIMESTAMP($7::BIGINT), $8, $9, $10) ON CONFLICT (id) DO UPDATE SET confirmed_by = EXCLUDED.confirmed_by, transaction_hash = EXCLUDED.transaction_hash, status = EXCLUDED.status, updated_at = NOW();",
+                &[
+                    &mint.id,
+                    &mint.starknet_project_addr.as_str(),
+                    &mint.starknet_account_addr.as_str(),
+                    &token_ids_to_strings(&mint.token_ids),
+                    &mint.reason,
+                    &mint.requested_by,
+                    &mint.requested_at,
+                    &mint.confirmed_by,
+                    &mint.transaction_hash,
+                    &breakglass_status_to_str(&mint.status),
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist breakglass mint {:#?}", e);
+                Err(BreakglassError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get(&self, id: &Uuid) -> Result<BreakglassMint, BreakglassError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, starknet_project_addr, starknet_account_addr, token_ids, reason, requested_by, EXTRACT(EPOCH FROM requested_at)::BIGINT AS requested_at, confirmed_by, transaction_hash, status FROM breakglass_mints WHERE id = $1;",
+                &[id],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch breakglass mint {:#?}", e);
+                return Err(BreakglassError::PersistenceIssue);
+            }
+        };
+        if 0 == rows.len() {
+            return Err(BreakglassError::NotFound);
+        }
+
+        Ok(row_to_breakglass_mint(&rows[0]))
+    }
+
+    async fn claim_confirmation(
+        &self,
+        id: &Uuid,
+        confirmed_by: &str,
+    ) -> Result<BreakglassMint, BreakglassError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "UPDATE breakglass_mints SET status = 'confirming', confirmed_by = $2, updated_at = NOW() WHERE id = $1 AND status = 'awaiting_confirmation' RETURNING id, starknet_project_addr, starknet_account_addr, token_ids, reason, requested_by, EXTRACT(EPOCH FROM requested_at)::BIGINT AS requested_at, confirmed_by, transaction_hash, status;",
+                &[id, &confirmed_by],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to claim breakglass mint {:#?}", e);
+                return Err(BreakglassError::PersistenceIssue);
+            }
+        };
+        match rows.first() {
+            Some(row) => Ok(row_to_breakglass_mint(row)),
+            None => Err(BreakglassError::AlreadyHandled),
+        }
+    }
+}
+
+pub struct PostgresPostMintExecutionRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresPostMintExecutionRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+fn post_mint_status_to_str(status: &PostMintStatus) -> &'static str {
+    match status {
+        PostMintStatus::Pending => "pending",
+        PostMintStatus::Done => "done",
+        PostMintStatus::Failed => "failed",
+    }
+}
+
+#[async_trait]
+impl PostMintExecutionRepository for PostgresPostMintExecutionRepository {
+    async fn save(&self, execution: &PostMintExecution) -> Result<(), PostMintError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO post_mint_executions (id, queue_item_id, hook, project_id, token_id, starknet_account_addr, transaction_hash, attempts, last_error, status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) ON CONFLICT (id) DO UPDATE SET attempts = EXCLUDED.attempts, last_error = EXCLUDED.last_error, status = EXCLUDED.status, updated_at = NOW();",
+                &[
+                    &execution.id,
+                    &execution.queue_item_id,
+                    &execution.hook,
+                    &execution.project_id,
+                    &execution.token_id,
+                    &execution.starknet_account_addr,
+                    &execution.transaction_hash,
+                    &execution.attempts,
+                    &execution.last_error,
+                    &post_mint_status_to_str(&execution.status),
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist post mint execution {:#?}", e);
+                Err(PostMintError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_pending(&self) -> Result<Vec<PostMintExecution>, PostMintError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, queue_item_id, hook, project_id, token_id, starknet_account_addr, transaction_hash, attempts, last_error FROM post_mint_executions WHERE status = 'pending' ORDER BY created_at ASC;",
+                &[],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch pending post mint executions {:#?}", e);
+                return Err(PostMintError::PersistenceIssue);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| PostMintExecution {
+                id: row.get("id"),
+                queue_item_id: row.get("queue_item_id"),
+                hook: row.get("hook"),
+                project_id: row.get("project_id"),
+                token_id: row.get("token_id"),
+                starknet_account_addr: row.get("starknet_account_addr"),
+                transaction_hash: row.get("transaction_hash"),
+                attempts: row.get("attempts"),
+                last_error: row.get("last_error"),
+                status: PostMintStatus::Pending,
+            })
+            .collect())
+    }
+}
+
+pub struct PostgresReportRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresReportRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl ReportRepository for PostgresReportRepository {
+    async fn get_daily_activity(&self, range: &TimeRange) -> Result<DailyActivity, ReportError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT (SELECT COUNT(*) FROM customer_keys WHERE created_at >= TO_TIMESTAMP($1::BIGINT / 1000.0) AND created_at < TO_TIMESTAMP($2::BIGINT / 1000.0)) AS new_registrations, (SELECT COUNT(*) FROM migration_queue WHERE created_at >= TO_TIMESTAMP($1::BIGINT / 1000.0) AND created_at < TO_TIMESTAMP($2::BIGINT / 1000.0)) AS tokens_queued, (SELECT COUNT(*) FROM migration_queue_history WHERE migration_status = 'success' AND created_at >= TO_TIMESTAMP($1::BIGINT / 1000.0) AND created_at < TO_TIMESTAMP($2::BIGINT / 1000.0)) AS tokens_minted, (SELECT COUNT(*) FROM migration_queue_history WHERE migration_status = 'error' AND created_at >= TO_TIMESTAMP($1::BIGINT / 1000.0) AND created_at < TO_TIMESTAMP($2::BIGINT / 1000.0)) AS tokens_failed;",
+                &[&range.from, &range.to],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to compute daily activity {:#?}", e);
+                return Err(ReportError::FailedToCompute);
+            }
+        };
+        let row = &rows[0];
+
+        Ok(DailyActivity {
+            new_registrations: row.get("new_registrations"),
+            tokens_queued: row.get("tokens_queued"),
+            tokens_minted: row.get("tokens_minted"),
+            tokens_failed: row.get("tokens_failed"),
+        })
+    }
+
+    async fn save_report(&self, report: &SignedDailyReport) -> Result<(), ReportError> {
+        let payload = match serde_json::to_string(&report.report) {
+            Ok(p) => p,
+            Err(_e) => return Err(ReportError::PersistenceIssue),
+        };
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO daily_reports (day, report, signature) VALUES ($1, $2, $3) ON CONFLICT (day) DO NOTHING;",
+                &[&report.report.day, &payload, &report.signature],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist daily report {:#?}", e);
+                Err(ReportError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_report(&self, day: &str) -> Result<SignedDailyReport, ReportError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT report, signature FROM daily_reports WHERE day = $1;",
+                &[&day],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch daily report {:#?}", e);
+                return Err(ReportError::PersistenceIssue);
+            }
+        };
+        if 0 == rows.len() {
+            return Err(ReportError::NotFound);
+        }
+
+        match serde_json::from_str(rows[0].get("report")) {
+            Ok(report) => Ok(SignedDailyReport {
+                report,
+                signature: rows[0].get("signature"),
+            }),
+            Err(e) => {
+                error!("Failed to deserialize daily report {:#?}", e);
+                Err(ReportError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn list_report_days(&self) -> Result<Vec<String>, ReportError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query("SELECT day FROM daily_reports ORDER BY day DESC;", &[])
+            .await
+        {
+            Ok(rows) => Ok(rows.iter().map(|row| row.get("day")).collect()),
+            Err(e) => {
+                error!("Failed to list daily reports {:#?}", e);
+                Err(ReportError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresWebhookRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresWebhookRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+fn webhook_delivery_status_to_str(status: &WebhookDeliveryStatus) -> &'static str {
+    match status {
+        WebhookDeliveryStatus::Pending => "pending",
+        WebhookDeliveryStatus::Delivered => "delivered",
+        WebhookDeliveryStatus::Failed => "failed",
+    }
+}
+
+// Comma separated, as stored in the events column
+fn webhook_events_to_string(events: &[WebhookEvent]) -> String {
+    events
+        .iter()
+        .map(WebhookEvent::as_str)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn row_to_webhook_subscription(row: &Row) -> WebhookSubscription {
+    WebhookSubscription {
+        id: row.get("id"),
+        keplr_wallet_pubkey: row
+            .get::<&str, Option<String>>("keplr_wallet_pubkey")
+            .map(JunoAddress::unchecked),
+        callback_url: row.get("callback_url"),
+        events: row
+            .get::<&str, &str>("events")
+            .split(',')
+            .filter_map(WebhookEvent::parse)
+            .collect(),
+        secret: row.get("secret"),
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for PostgresWebhookRepository {
+    async fn save_subscription(
+        &self,
+        subscription: &WebhookSubscription,
+    ) -> Result<(), WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let keplr_wallet_pubkey = subscription
+            .keplr_wallet_pubkey
+            .as_ref()
+            .map(|k| k.as_str());
+        match client
+            .execute(
+                "INSERT INTO webhook_subscriptions (id, keplr_wallet_pubkey, callback_url, events, secret) VALUES ($1, $2, $3, $4, $5) ON CONFLICT (id) DO UPDATE SET events = EXCLUDED.events;",
+                &[
+                    &subscription.id,
+                    &keplr_wallet_pubkey,
+                    &subscription.callback_url,
+                    &webhook_events_to_string(&subscription.events),
+                    &subscription.secret,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist webhook subscription {:#?}", e);
+                Err(WebhookError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_subscriptions(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<WebhookSubscription>, WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, callback_url, events, secret FROM webhook_subscriptions WHERE keplr_wallet_pubkey = $1 OR keplr_wallet_pubkey IS NULL ORDER BY created_at ASC;",
+                &[&keplr_wallet_pubkey.as_str()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch webhook subscriptions {:#?}", e);
+                return Err(WebhookError::PersistenceIssue);
+            }
+        };
+
+        Ok(rows.iter().map(row_to_webhook_subscription).collect())
+    }
+
+    async fn get_subscription(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<WebhookSubscription>, WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query_opt(
+                "SELECT id, keplr_wallet_pubkey, callback_url, events, secret FROM webhook_subscriptions WHERE id = $1;",
+                &[&id],
+            )
+            .await
+        {
+            Ok(row) => Ok(row.as_ref().map(row_to_webhook_subscription)),
+            Err(e) => {
+                error!("Failed to fetch webhook subscription {:#?}", e);
+                Err(WebhookError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn find_subscription(
+        &self,
+        keplr_wallet_pubkey: Option<&JunoAddress>,
+        callback_url: &str,
+    ) -> Result<Option<WebhookSubscription>, WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let keplr_wallet_pubkey = keplr_wallet_pubkey.map(|k| k.as_str());
+        match client
+            .query_opt(
+                "SELECT id, keplr_wallet_pubkey, callback_url, events, secret FROM webhook_subscriptions WHERE keplr_wallet_pubkey IS NOT DISTINCT FROM $1 AND callback_url = $2 ORDER BY created_at ASC LIMIT 1;",
+                &[&keplr_wallet_pubkey, &callback_url],
+            )
+            .await
+        {
+            Ok(row) => Ok(row.as_ref().map(row_to_webhook_subscription)),
+            Err(e) => {
+                error!("Failed to fetch webhook subscription {:#?}", e);
+                Err(WebhookError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn list_subscriptions(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<WebhookSubscription>, WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, callback_url, events, secret FROM webhook_subscriptions WHERE keplr_wallet_pubkey = $1 ORDER BY created_at ASC;",
+                &[&keplr_wallet_pubkey.as_str()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to list webhook subscriptions {:#?}", e);
+                return Err(WebhookError::PersistenceIssue);
+            }
+        };
+
+        Ok(rows.iter().map(row_to_webhook_subscription).collect())
+    }
+
+    async fn delete_subscription(&self, id: Uuid) -> Result<(), WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute("DELETE FROM webhook_subscriptions WHERE id = $1;", &[&id])
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to delete webhook subscription {:#?}", e);
+                Err(WebhookError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn save_delivery(&self, delivery: &WebhookDelivery) -> Result<(), WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let payload = match serde_json::to_string(&delivery.payload) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to serialize webhook payload {:#?}", e);
+                return Err(WebhookError::PersistenceIssue);
+            }
+        };
+        match client
+            .execute(
+                "INSERT INTO webhook_deliveries (id, subscription_id, callback_url, payload, attempts, next_attempt_at, last_error, status) VALUES ($1, $2, $3, $4, $5, TO_TIMESTAMP($6::BIGINT / 1000.0), $7, $8) ON CONFLICT (id) DO UPDATE SET attempts = EXCLUDED.attempts, next_attempt_at = EXCLUDED.next_attempt_at, last_error = EXCLUDED.last_error, status = EXCLUDED.status, updated_at = NOW();",
+                &[
+                    &delivery.id,
+                    &delivery.subscription_id,
+                    &delivery.callback_url,
+                    &payload,
+                    &delivery.attempts,
+                    &delivery.next_attempt_at,
+                    &delivery.last_error,
+                    &webhook_delivery_status_to_str(&delivery.status),
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist webhook delivery {:#?}", e);
+                Err(WebhookError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_due_deliveries(&self, now_ms: i64) -> Result<Vec<WebhookDelivery>, WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT d.id, d.subscription_id, d.callback_url, d.payload, d.attempts, (EXTRACT(EPOCH FROM d.next_attempt_at) * 1000)::BIGINT AS next_attempt_at, d.last_error, s.secret FROM webhook_deliveries d INNER JOIN webhook_subscriptions s ON s.id = d.subscription_id WHERE d.status = 'pending' AND d.next_attempt_at <= TO_TIMESTAMP($1::BIGINT / 1000.0) ORDER BY d.next_attempt_at ASC;",
+                &[&now_ms],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch due webhook deliveries {:#?}", e);
+                return Err(WebhookError::PersistenceIssue);
+            }
+        };
+
+        let mut deliveries = Vec::new();
+        for row in rows {
+            let payload = match serde_json::from_str(row.get("payload")) {
+                Ok(p) => p,
+                Err(e) => {
+                    error!("Skipping webhook delivery with unreadable payload {:#?}", e);
+                    continue;
+                }
+            };
+            deliveries.push(WebhookDelivery {
+                id: row.get("id"),
+                subscription_id: row.get("subscription_id"),
+                callback_url: row.get("callback_url"),
+                payload,
+                attempts: row.get("attempts"),
+                next_attempt_at: row.get("next_attempt_at"),
+                last_error: row.get("last_error"),
+                status: WebhookDeliveryStatus::Pending,
+                signing_secret: row.get("secret"),
+            });
+        }
+        Ok(deliveries)
+    }
+
+    async fn log_attempt(&self, attempt: &WebhookDeliveryAttempt) -> Result<(), WebhookError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let status_code = attempt.status_code.map(i32::from);
+        match client
+            .execute(
+                "INSERT INTO webhook_delivery_log (delivery_id, attempt, status_code, error, created_at) VALUES ($1, $2, $3, $4, TO_TIMESTAMP($5::BIGINT / 1000.0));",
+                &[
+                    &attempt.delivery_id,
+                    &attempt.attempt,
+                    &status_code,
+                    &attempt.error,
+                    &attempt.created_at,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to log webhook delivery attempt {:#?}", e);
+                Err(WebhookError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresBatchAnalyticsRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresBatchAnalyticsRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+fn batch_outcome_to_str(outcome: &BatchOutcome) -> &'static str {
+    match outcome {
+        BatchOutcome::Accepted => "accepted",
+        BatchOutcome::Rejected => "rejected",
+        BatchOutcome::SubmissionFailed => "submission_failed",
+    }
+}
+
+#[async_trait]
+impl BatchAnalyticsRepository for PostgresBatchAnalyticsRepository {
+    async fn save_batch(&self, record: &BatchRecord) -> Result<(), BatchAnalyticsError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO batch_analytics (id, project_id, transaction_hash, item_count, fee, outcome, rejection_reason, submitted_at, latency_ms) VALUES ($1, $2, $3, $4, $5::TEXT::NUMERIC, $6, $7, TO_TIMESTAMP($8::BIGINT / 1000.0), $9);",
+                &[
+                    &record.id,
+                    &record.project_id.as_str(),
+                    &record.transaction_hash,
+                    &record.item_count,
+                    &record.fee,
+                    &batch_outcome_to_str(&record.outcome),
+                    &record.rejection_reason,
+                    &record.submitted_at,
+                    &record.latency_ms,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist batch analytics {:#?}", e);
+                Err(BatchAnalyticsError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_batches(
+        &self,
+        query: &BatchAnalyticsQuery,
+    ) -> Result<Vec<BatchRecord>, BatchAnalyticsError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, project_id, transaction_hash, item_count, fee::TEXT AS fee, outcome, rejection_reason, (EXTRACT(EPOCH FROM submitted_at) * 1000)::BIGINT AS submitted_at, latency_ms FROM batch_analytics WHERE ($1::BIGINT IS NULL OR submitted_at >= TO_TIMESTAMP($1::BIGINT / 1000.0)) AND ($2::BIGINT IS NULL OR submitted_at < TO_TIMESTAMP($2::BIGINT / 1000.0)) AND ($3::VARCHAR IS NULL OR project_id = $3) ORDER BY submitted_at DESC;",
+                &[
+                    &query.from,
+                    &query.to,
+                    &query.project_id.as_ref().map(StarknetAddress::as_str),
+                ],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch batch analytics {:#?}", e);
+                return Err(BatchAnalyticsError::PersistenceIssue);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| BatchRecord {
+                id: row.get("id"),
+                project_id: StarknetAddress::unchecked(row.get::<&str, String>("project_id")),
+                transaction_hash: row.get("transaction_hash"),
+                item_count: row.get("item_count"),
+                fee: row.get("fee"),
+                outcome: match row.get::<&str, &str>("outcome") {
+                    "accepted" => BatchOutcome::Accepted,
+                    "rejected" => BatchOutcome::Rejected,
+                    _ => BatchOutcome::SubmissionFailed,
+                },
+                rejection_reason: row.get("rejection_reason"),
+                submitted_at: row.get("submitted_at"),
+                latency_ms: row.get("latency_ms"),
+            })
+            .collect())
+    }
+
+    async fn delete_batches_before(&self, before: i64) -> Result<u64, BatchAnalyticsError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "DELETE FROM batch_analytics WHERE submitted_at < TO_TIMESTAMP($1::BIGINT / 1000.0);",
+                &[&before],
+            )
+            .await
+        {
+            Ok(deleted) => Ok(deleted),
+            Err(e) => {
+                error!("Failed to prune batch analytics {:#?}", e);
+                Err(BatchAnalyticsError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresFunnelEventRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresFunnelEventRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl FunnelEventRepository for PostgresFunnelEventRepository {
+    async fn save_event(&self, event: &FunnelEvent) -> Result<(), FunnelEventError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO analytics_events (id, session_id, event, project_id, created_at) VALUES ($1, $2, $3, $4, TO_TIMESTAMP($5::BIGINT / 1000.0));",
+                &[
+                    &event.id,
+                    &event.session_id,
+                    &event.event.as_str(),
+                    &event.project_id.as_ref().map(ProjectId::as_str),
+                    &event.created_at,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist funnel event {:#?}", e);
+                Err(FunnelEventError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn delete_events_before(&self, before: i64) -> Result<u64, FunnelEventError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "DELETE FROM analytics_events WHERE created_at < TO_TIMESTAMP($1::BIGINT / 1000.0);",
+                &[&before],
+            )
+            .await
+        {
+            Ok(deleted) => Ok(deleted),
+            Err(e) => {
+                error!("Failed to prune funnel events {:#?}", e);
+                Err(FunnelEventError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresMaintenanceRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresMaintenanceRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl MaintenanceRepository for PostgresMaintenanceRepository {
+    async fn table_stats(&self, tables: &[&str]) -> Result<Vec<TableStats>, MaintenanceError> {
+        let client = self.connection_pool.get().await.unwrap();
+        // Row counts are the estimates autovacuum keeps, counting them would scan every table
+        let rows = match client
+            .query(
+                "SELECT relname::TEXT AS relname, n_live_tup, n_dead_tup, pg_total_relation_size(relid) AS total_bytes, (EXTRACT(EPOCH FROM GREATEST(last_vacuum, last_autovacuum)) * 1000)::BIGINT AS last_vacuum, (EXTRACT(EPOCH FROM GREATEST(last_analyze, last_autoanalyze)) * 1000)::BIGINT AS last_analyze FROM pg_stat_user_tables WHERE relname = ANY($1);",
+                &[&tables],
+            )
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                error!("Failed to fetch table stats {:#?}", e);
+                return Err(MaintenanceError::PersistenceIssue);
+            }
+        };
+
+        Ok(rows
+            .iter()
+            .map(|row| TableStats {
+                table: row.get("relname"),
+                live_rows: row.get("n_live_tup"),
+                dead_rows: row.get("n_dead_tup"),
+                total_bytes: row.get("total_bytes"),
+                last_vacuum: row.get("last_vacuum"),
+                last_analyze: row.get("last_analyze"),
+            })
+            .collect())
+    }
+
+    async fn delete_expired_rows(&self, now: i64) -> Result<u64, MaintenanceError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let mut deleted = 0;
+        for statement in [
+            "DELETE FROM signature_challenges WHERE expires_at < $1::BIGINT / 1000;",
+            "DELETE FROM token_locks WHERE locked_until < TO_TIMESTAMP($1::BIGINT / 1000.0);",
+        ] {
+            match client.execute(statement, &[&now]).await {
+                Ok(rows) => deleted += rows,
+                Err(e) => {
+                    error!("Failed to prune expired rows {:#?}", e);
+                    return Err(MaintenanceError::PersistenceIssue);
+                }
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
+pub struct PostgresBatchSizeRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresBatchSizeRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl BatchSizeRepository for PostgresBatchSizeRepository {
+    async fn get_batch_size(
+        &self,
+        project_id: &StarknetAddress,
+    ) -> Result<Option<ProjectBatchSize>, BatchSizeError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT project_id, batch_size, consecutive_successes, (EXTRACT(EPOCH FROM updated_at) * 1000)::BIGINT AS updated_at FROM project_batch_sizes WHERE project_id = $1;",
+                &[&project_id.as_str()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch project batch size {:#?}", e);
+                return Err(BatchSizeError::PersistenceIssue);
+            }
+        };
+
+        Ok(rows.first().map(|row| ProjectBatchSize {
+            project_id: project_id.clone(),
+            batch_size: row.get::<&str, i32>("batch_size") as u32,
+            consecutive_successes: row.get::<&str, i32>("consecutive_successes") as u32,
+            updated_at: row.get("updated_at"),
+        }))
+    }
+
+    async fn save_batch_size(&self, batch_size: &ProjectBatchSize) -> Result<(), BatchSizeError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO project_batch_sizes (project_id, batch_size, consecutive_successes, updated_at) VALUES ($1, $2, $3, TO_TIMESTAMP($4::BIGINT / 1000.0)) ON CONFLICT (project_id) DO UPDATE SET batch_size = EXCLUDED.batch_size, consecutive_successes = EXCLUDED.consecutive_successes, updated_at = EXCLUDED.updated_at;",
+                &[
+                    &batch_size.project_id.as_str(),
+                    &(batch_size.batch_size as i32),
+                    &(batch_size.consecutive_successes as i32),
+                    &batch_size.updated_at,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist project batch size {:#?}", e);
+                Err(BatchSizeError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresAuditRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresAuditRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl AuditRepository for PostgresAuditRepository {
+    async fn save_bridge_request(&self, audit: &BridgeRequestAudit) -> Result<(), AuditError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let checks = match serde_json::to_string(&audit.checks) {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Failed to serialize bridge request checks {:#?}", e);
+                return Err(AuditError::PersistenceIssue);
+            }
+        };
+        match client
+            .execute(
+                "INSERT INTO bridge_requests (id, keplr_wallet_pubkey, starknet_account_addr, project_id, token_ids, checks, outcome, status, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, TO_TIMESTAMP($9::BIGINT / 1000.0));",
+                &[
+                    &audit.id,
+                    &audit.keplr_wallet_pubkey.as_str(),
+                    &audit.starknet_account_addr.as_str(),
+                    &audit.project_id.as_str(),
+                    &token_ids_to_strings(&audit.token_ids),
+                    &checks,
+                    &audit.outcome,
+                    &i32::from(audit.status),
+                    &audit.created_at,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist bridge request audit {:#?}", e);
+                Err(AuditError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_bridge_requests(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<BridgeRequestAudit>, AuditError> {
+        let client = self.connection_pool.get().await.unwrap();
+        let rows = match client
+            .query(
+                "SELECT id, keplr_wallet_pubkey, starknet_account_addr, project_id, token_ids, checks, outcome, status, (EXTRACT(EPOCH FROM created_at) * 1000)::BIGINT AS created_at FROM bridge_requests WHERE keplr_wallet_pubkey = $1 ORDER BY created_at DESC;",
+                &[&keplr_wallet_pubkey.as_str()],
+            )
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to fetch bridge request audits {:#?}", e);
+                return Err(AuditError::PersistenceIssue);
+            }
+        };
+
+        let mut audits = Vec::new();
+        for row in rows {
+            let checks = match serde_json::from_str(row.get("checks")) {
+                Ok(c) => c,
+                Err(e) => {
+                    error!(
+                        "Skipping bridge request audit with unreadable checks {:#?}",
+                        e
+                    );
+                    continue;
+                }
+            };
+            audits.push(BridgeRequestAudit {
+                id: row.get("id"),
+                keplr_wallet_pubkey: JunoAddress::unchecked(
+                    row.get::<&str, String>("keplr_wallet_pubkey"),
+                ),
+                starknet_account_addr: StarknetAddress::unchecked(
+                    row.get::<&str, String>("starknet_account_addr"),
+                ),
+                project_id: ProjectId::unchecked(row.get::<&str, String>("project_id")),
+                token_ids: row
+                    .get::<&str, Vec<String>>("token_ids")
+                    .into_iter()
+                    .map(TokenId::unchecked)
+                    .collect(),
+                checks,
+                outcome: row.get("outcome"),
+                status: row.get::<&str, i32>("status") as u16,
+                created_at: row.get("created_at"),
+            });
+        }
+        Ok(audits)
+    }
+}
+
+pub struct PostgresTransferProofRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresTransferProofRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+fn row_to_transfer_proof(row: &Row) -> TransferProof {
+    TransferProof {
+        project_id: ProjectId::unchecked(row.get::<&str, String>("project_id")),
+        token_id: TokenId::unchecked(row.get::<&str, String>("token_id")),
+        sender: row.get("sender"),
+        recipient: row.get("recipient"),
+        transaction_hash: row.get("transaction_hash"),
+        height: row.get("height"),
+        block_time: row.get("block_time"),
+        created_at: row.get("created_at"),
+    }
+}
+
+#[async_trait]
+impl TransferProofRepository for PostgresTransferProofRepository {
+    async fn get_proof(
+        &self,
+        project_id: &ProjectId,
+        token_id: &TokenId,
+    ) -> Result<Option<TransferProof>, TransferProofError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query_opt(
+                "SELECT project_id, token_id, sender, recipient, transaction_hash, height, (EXTRACT(EPOCH FROM block_time) * 1000)::BIGINT AS block_time, (EXTRACT(EPOCH FROM created_at) * 1000)::BIGINT AS created_at FROM transfer_proofs WHERE project_id = $1 AND token_id = $2;",
+                &[&project_id.as_str(), &token_id.as_str()],
+            )
+            .await
+        {
+            Ok(row) => Ok(row.as_ref().map(row_to_transfer_proof)),
+            Err(e) => {
+                error!("Failed to fetch transfer proof {:#?}", e);
+                Err(TransferProofError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_proofs(
+        &self,
+        project_id: &ProjectId,
+        token_ids: &[TokenId],
+    ) -> Result<Vec<TransferProof>, TransferProofError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query(
+                "SELECT project_id, token_id, sender, recipient, transaction_hash, height, (EXTRACT(EPOCH FROM block_time) * 1000)::BIGINT AS block_time, (EXTRACT(EPOCH FROM created_at) * 1000)::BIGINT AS created_at FROM transfer_proofs WHERE project_id = $1 AND token_id = ANY($2);",
+                &[&project_id.as_str(), &token_ids_to_strings(token_ids)],
+            )
+            .await
+        {
+            Ok(rows) => Ok(rows.iter().map(row_to_transfer_proof).collect()),
+            Err(e) => {
+                error!("Failed to fetch transfer proofs {:#?}", e);
+                Err(TransferProofError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn save_proof(&self, proof: &TransferProof) -> Result<(), TransferProofError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "INSERT INTO transfer_proofs (project_id, token_id, sender, recipient, transaction_hash, height, block_time, created_at) VALUES ($1, $2, $3, $4, $5, $6, TO_TIMESTAMP($7::BIGINT / 1000.0), TO_TIMESTAMP($8::BIGINT / 1000.0)) ON CONFLICT (project_id, token_id) DO NOTHING;",
+                &[
+                    &proof.project_id.as_str(),
+                    &proof.token_id.as_str(),
+                    &proof.sender,
+                    &proof.recipient,
+                    &proof.transaction_hash,
+                    &proof.height,
+                    &proof.block_time,
+                    &proof.created_at,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to persist transfer proof {:#?}", e);
+                Err(TransferProofError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresReverseQueueManager {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresReverseQueueManager {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+fn reverse_queue_status_to_str(status: &ReverseQueueStatus) -> &'static str {
+    match status {
+        ReverseQueueStatus::Pending => "pending",
+        ReverseQueueStatus::Processing => "processing",
+        ReverseQueueStatus::Success => "success",
+        ReverseQueueStatus::Error => "error",
+    }
+}
+
+fn row_to_reverse_queue_item(row: &Row) -> ReverseQueueItem {
+    ReverseQueueItem {
+        id: row.get("id"),
+        starknet_account_addr: StarknetAddress::unchecked(
+            row.get::<&str, String>("starknet_account_addr"),
+        ),
+        keplr_wallet_pubkey: JunoAddress::unchecked(row.get::<&str, String>("keplr_wallet_pubkey")),
+        starknet_project_id: StarknetAddress::unchecked(
+            row.get::<&str, String>("starknet_project_id"),
+        ),
+        project_id: ProjectId::unchecked(row.get::<&str, String>("project_id")),
+        token_id: TokenId::unchecked(row.get::<&str, String>("token_id")),
+        starknet_transaction_hash: row.get("starknet_transaction_hash"),
+        status: match row.get::<&str, &str>("status") {
+            "processing" => ReverseQueueStatus::Processing,
+            "success" => ReverseQueueStatus::Success,
+            "error" => ReverseQueueStatus::Error,
+            _ => ReverseQueueStatus::Pending,
+        },
+        juno_transaction_hash: row.get("juno_transaction_hash"),
+        note: row.get("note"),
+    }
+}
+
+#[async_trait]
+impl ReverseQueueManager for PostgresReverseQueueManager {
+    async fn enqueue(
+        &self,
+        items: Vec<ReverseQueueItem>,
+    ) -> Result<Vec<ReverseQueueItem>, ReverseQueueError> {
+        let mut client = self.connection_pool.get().await.unwrap();
+        let tx = match client.build_transaction().start().await {
+            Ok(tx) => tx,
+            Err(e) => {
+                error!("Failed to start reverse queue transaction {:#?}", e);
+                return Err(ReverseQueueError::PersistenceIssue);
+            }
+        };
+
+        // Dropping the transaction on any failure leaves the queue untouched
+        for item in &items {
+            match tx
+                .execute(
+                    "INSERT INTO reverse_migration_queue (id, starknet_account_addr, keplr_wallet_pubkey, starknet_project_id, project_id, token_id, starknet_transaction_hash, status) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (project_id, token_id, starknet_transaction_hash) DO NOTHING;",
+                    &[
+                        &item.id,
+                        &item.starknet_account_addr.as_str(),
+                        &item.keplr_wallet_pubkey.as_str(),
+                        &item.starknet_project_id.as_str(),
+                        &item.project_id.as_str(),
+                        &item.token_id.as_str(),
+                        &item.starknet_transaction_hash,
+                        &reverse_queue_status_to_str(&item.status),
+                    ],
+                )
+                .await
+            {
+                Ok(0) => return Err(ReverseQueueError::AlreadyRequested(item.token_id.clone())),
+                Ok(_) => (),
+                Err(e) => {
+                    error!("Failed to enqueue reverse queue item {:#?}", e);
+                    return Err(ReverseQueueError::PersistenceIssue);
+                }
+            }
+        }
+
+        match tx.commit().await {
+            Ok(_) => Ok(items),
+            Err(e) => {
+                error!("Failed to commit reverse queue items {:#?}", e);
+                Err(ReverseQueueError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_batch(&self, limit: usize) -> Result<Vec<ReverseQueueItem>, ReverseQueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query(
+                "SELECT id, starknet_account_addr, keplr_wallet_pubkey, starknet_project_id, project_id, token_id, starknet_transaction_hash, status, juno_transaction_hash, note FROM reverse_migration_queue WHERE status = 'pending' ORDER BY created_at LIMIT $1;",
+                &[&(limit as i64)],
+            )
+            .await
+        {
+            Ok(rows) => Ok(rows.iter().map(row_to_reverse_queue_item).collect()),
+            Err(e) => {
+                error!("Failed to fetch reverse queue batch {:#?}", e);
+                Err(ReverseQueueError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn update_status(
+        &self,
+        ids: &[Uuid],
+        status: ReverseQueueStatus,
+        juno_transaction_hash: Option<String>,
+        note: Option<String>,
+    ) -> Result<(), ReverseQueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .execute(
+                "UPDATE reverse_migration_queue SET status = $1, juno_transaction_hash = $2, note = $3, updated_at = NOW() WHERE id = ANY($4);",
+                &[
+                    &reverse_queue_status_to_str(&status),
+                    &juno_transaction_hash,
+                    &note,
+                    &ids,
+                ],
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to update reverse queue items {:#?}", e);
+                Err(ReverseQueueError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn get_customer_state(
+        &self,
+        starknet_account_addr: &StarknetAddress,
+        starknet_project_id: &StarknetAddress,
+    ) -> Result<Vec<ReverseQueueItem>, ReverseQueueError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query(
+                "SELECT id, starknet_account_addr, keplr_wallet_pubkey, starknet_project_id, project_id, token_id, starknet_transaction_hash, status, juno_transaction_hash, note FROM reverse_migration_queue WHERE starknet_account_addr = $1 AND starknet_project_id = $2 ORDER BY created_at;",
+                &[&starknet_account_addr.as_str(), &starknet_project_id.as_str()],
+            )
+            .await
+        {
+            Ok(rows) => Ok(rows.iter().map(row_to_reverse_queue_item).collect()),
+            Err(e) => {
+                error!("Failed to fetch reverse queue items {:#?}", e);
+                Err(ReverseQueueError::PersistenceIssue)
+            }
+        }
+    }
+}
+
+pub struct PostgresIssueRecordRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresIssueRecordRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl IssueRecordRepository for PostgresIssueRecordRepository {
+    async fn get_reported_subjects(
+        &self,
+        subjects: &[String],
+    ) -> Result<Vec<String>, IssueTrackerError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query(
+                "SELECT subject FROM issue_records WHERE subject = ANY($1);",
+                &[&subjects],
+            )
+            .await
+        {
+            Ok(rows) => Ok(rows.iter().map(|row| row.get("subject")).collect()),
+            Err(e) => {
+                error!("Failed to fetch issue records {:#?}", e);
+                Err(IssueTrackerError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn save_records(&self, records: &[IssueRecord]) -> Result<(), IssueTrackerError> {
+        let client = self.connection_pool.get().await.unwrap();
+        for record in records {
+            if let Err(e) = client
+                .execute(
+                    "INSERT INTO issue_records (subject, issue_url, created_at) VALUES ($1, $2, TO_TIMESTAMP($3::BIGINT / 1000.0)) ON CONFLICT (subject) DO NOTHING;",
+                    &[&record.subject, &record.issue_url, &record.created_at],
+                )
+                .await
+            {
+                error!("Failed to persist issue record {:#?}", e);
+                return Err(IssueTrackerError::PersistenceIssue);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct PostgresCheckResultRepository {
+    connection_pool: Arc<Pool>,
+}
+
+impl PostgresCheckResultRepository {
+    pub fn new(connection_pool: Arc<Pool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+// Height is part of the key, results computed without Juno transfer are stored at 0
+fn row_to_check_result(row: &Row) -> Option<CheckResult> {
+    let failure = match row.get::<&str, Option<String>>("failure") {
+        Some(code) => Some(serde_json::from_value(serde_json::Value::String(code)).ok()?),
+        None => None,
+    };
+
+    Some(CheckResult {
+        keplr_wallet_pubkey: JunoAddress::unchecked(row.get::<&str, String>("keplr_wallet_pubkey")),
+        project_id: ProjectId::unchecked(row.get::<&str, String>("project_id")),
+        token_id: TokenId::unchecked(row.get::<&str, String>("token_id")),
+        height: Some(row.get::<&str, i64>("height")).filter(|h| 0 != *h),
+        failure,
+        checked_at: row.get("checked_at"),
+    })
+}
+
+#[async_trait]
+impl CheckResultRepository for PostgresCheckResultRepository {
+    async fn get_results(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        token_ids: &[TokenId],
+        since: i64,
+    ) -> Result<Vec<CheckResult>, CheckCacheError> {
+        let client = self.connection_pool.get().await.unwrap();
+        match client
+            .query(
+                "SELECT DISTINCT ON (token_id) keplr_wallet_pubkey, project_id, token_id, height, failure, (EXTRACT(EPOCH FROM checked_at) * 1000)::BIGINT AS checked_at FROM bridge_check_results WHERE keplr_wallet_pubkey = $1 AND project_id = $2 AND token_id = ANY($3) AND checked_at >= TO_TIMESTAMP($4::BIGINT / 1000.0) ORDER BY token_id, checked_at DESC;",
+                &[
+                    &keplr_wallet_pubkey.as_str(),
+                    &project_id.as_str(),
+                    &token_ids_to_strings(token_ids),
+                    &since,
+                ],
+            )
+            .await
+        {
+            Ok(rows) => Ok(rows.iter().filter_map(row_to_check_result).collect()),
+            Err(e) => {
+                error!("Failed to fetch check results {:#?}", e);
+                Err(CheckCacheError::PersistenceIssue)
+            }
+        }
+    }
+
+    async fn save_results(
+        &self,
+        results: &[CheckResult],
+        expired_before: i64,
+    ) -> Result<(), CheckCacheError> {
+        let client = self.connection_pool.get().await.unwrap();
+        if let Err(e) = client
+            .execute(
+                "DELETE FROM bridge_check_results WHERE checked_at < TO_TIMESTAMP($1::BIGINT / 1000.0);",
+                &[&expired_before],
+            )
+            .await
+        {
+            error!("Failed to prune check results {:#?}", e);
+            return Err(CheckCacheError::PersistenceIssue);
+        }
+
+        for result in results {
+            let failure = result
+                .failure
+                .and_then(|f| serde_json::to_value(f).ok())
+                .and_then(|v| v.as_str().map(String::from));
+            if let Err(e) = client
+                .execute(
+                    "INSERT INTO bridge_check_results (keplr_wallet_pubkey, project_id, token_id, height, failure, checked_at) VALUES ($1, $2, $3, $4, $5, TO_TIMESTAMP($6::BIGINT / 1000.0)) ON CONFLICT (keplr_wallet_pubkey, project_id, token_id, height) DO UPDATE SET failure = EXCLUDED.failure, checked_at = EXCLUDED.checked_at;",
+                    &[
+                        &result.keplr_wallet_pubkey.as_str(),
+                        &result.project_id.as_str(),
+                        &result.token_id.as_str(),
+                        &result.height.unwrap_or_default(),
+                        &failure,
+                        &result.checked_at,
+                    ],
+                )
+                .await
+            {
+                error!("Failed to persist check result {:#?}", e);
+                return Err(CheckCacheError::PersistenceIssue);
+            }
+        }
+
+        Ok(())
     }
 }
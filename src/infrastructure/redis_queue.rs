@@ -0,0 +1,1184 @@
+use crate::domain::{
+    bridge::{
+        QueueError, QueueEvent, QueueItem, QueueItemTransition, QueueManager, QueueStatus,
+        QueueUpdateError,
+    },
+    ids::{JunoAddress, QueueItemId, StarknetAddress, TokenId},
+    pagination::{Cursor, Page, PageRequest},
+    stats::status_label,
+    status_message::StatusNote,
+};
+use async_trait::async_trait;
+use log::error;
+use redis::{aio::ConnectionManager, streams::StreamRangeReply, AsyncCommands, RedisError, Script};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use uuid::Uuid;
+
+// Items hydrated per round trip when listing with a status filter
+const LIST_CHUNK_SIZE: usize = 100;
+
+// Appends the transition to the item history and the event stream
+const RECORD_TRANSITION: &str = r#"
+local function record_transition(prefix, id, event_id, status, hash, worker_id, now_us)
+    local transition = {status = status, transaction_hash = hash or cjson.null, worker_id = worker_id, created_at = math.floor(tonumber(now_us) / 1000)}
+    redis.call('RPUSH', prefix .. ':history:' .. id, cjson.encode(transition))
+    if hash then
+        redis.call('XADD', prefix .. ':events', '*', 'id', event_id, 'queue_item_id', id, 'migration_status', status, 'transaction_hash', hash, 'worker_id', worker_id)
+    else
+        redis.call('XADD', prefix .. ':events', '*', 'id', event_id, 'queue_item_id', id, 'migration_status', status, 'worker_id', worker_id)
+    end
+end
+"#;
+
+// ARGV : prefix, keplr, starknet, project, worker id, now (epoch us), request id ('' for
+// none), then item id, token id and event id of each item. Tokens waiting to be minted
+// keep their item, returns the item id of every token. Nothing is written if a token of
+// the wallet is queued with a final status
+const ENQUEUE: &str = r#"
+local prefix, keplr, project = ARGV[1], ARGV[2], ARGV[4]
+local function active_item(id)
+    if not id then
+        return nil
+    end
+    local status = redis.call('HGET', prefix .. ':item:' .. id, 'migration_status')
+    if status == 'pending' or status == 'processing' then
+        return id
+    end
+    return nil
+end
+local ids, existing = {}, {}
+for i = 8, #ARGV, 3 do
+    local token_key = prefix .. ':token:' .. keplr .. ':' .. project .. ':' .. ARGV[i + 1]
+    existing[i] = active_item(redis.call('GET', prefix .. ':active:' .. project .. ':' .. ARGV[i + 1])) or active_item(redis.call('GET', token_key))
+    if not existing[i] and redis.call('EXISTS', token_key) == 1 then
+        return false
+    end
+    ids[#ids + 1] = existing[i] or ARGV[i]
+end
+local now_ms = math.floor(tonumber(ARGV[6]) / 1000)
+for i = 8, #ARGV, 3 do
+    if not existing[i] then
+        local id = ARGV[i]
+        redis.call('SET', prefix .. ':token:' .. keplr .. ':' .. project .. ':' .. ARGV[i + 1], id)
+        redis.call('SET', prefix .. ':active:' .. project .. ':' .. ARGV[i + 1], id)
+        redis.call('HSET', prefix .. ':item:' .. id, 'id', id, 'keplr_wallet_pubkey', keplr, 'starknet_wallet_pubkey', ARGV[3], 'project_id', project, 'token_id', ARGV[i + 1], 'migration_status', 'pending', 'attempts', 0, 'updated_by', ARGV[5], 'created_at', ARGV[6])
+        if ARGV[7] ~= '' then
+            redis.call('HSET', prefix .. ':item:' .. id, 'request_id', ARGV[7])
+        end
+        redis.call('ZADD', prefix .. ':pending', now_ms, id)
+        redis.call('ZADD', prefix .. ':items', ARGV[6], id)
+        redis.call('SADD', prefix .. ':status:pending', id)
+        redis.call('SADD', prefix .. ':customer:' .. keplr .. ':' .. project, id)
+        redis.call('SADD', prefix .. ':wallet:' .. keplr, project)
+        record_transition(prefix, id, ARGV[i + 2], 'pending', false, ARGV[5], ARGV[6])
+    end
+end
+return ids
+"#;
+
+// ARGV : prefix, status, hash mode (set, clear or keep_or_empty), hash, note mode (set
+// or clear), note, note params ('' for none), attempts mode (keep, increment or reset),
+// next attempt (keep, '' to clear or epoch ms), required status ('' for any), worker
+// id, now (epoch us), then item id and event id of each item. Returns the number of
+// items updated
+const UPDATE: &str = r#"
+local prefix, status = ARGV[1], ARGV[2]
+local updated = 0
+for i = 13, #ARGV, 2 do
+    local id = ARGV[i]
+    local key = prefix .. ':item:' .. id
+    local current = redis.call('HMGET', key, 'migration_status', 'transaction_hash', 'created_at', 'next_attempt_at')
+    if current[1] and (ARGV[10] == '' or current[1] == ARGV[10]) then
+        local hash = current[2]
+        if ARGV[3] == 'set' then
+            hash = ARGV[4]
+        elseif ARGV[3] == 'clear' then
+            hash = false
+        elseif not hash then
+            hash = ''
+        end
+        local next_attempt_at = current[4]
+        if ARGV[9] ~= 'keep' then
+            next_attempt_at = ARGV[9] ~= '' and ARGV[9] or false
+        end
+
+        redis.call('HSET', key, 'migration_status', status, 'updated_by', ARGV[11], 'updated_at', ARGV[12])
+        if hash then redis.call('HSET', key, 'transaction_hash', hash) else redis.call('HDEL', key, 'transaction_hash') end
+        if ARGV[5] == 'set' then redis.call('HSET', key, 'note', ARGV[6]) else redis.call('HDEL', key, 'note') end
+        if ARGV[7] ~= '' then redis.call('HSET', key, 'note_params', ARGV[7]) else redis.call('HDEL', key, 'note_params') end
+        if ARGV[8] == 'increment' then
+            redis.call('HINCRBY', key, 'attempts', 1)
+        elseif ARGV[8] == 'reset' then
+            redis.call('HSET', key, 'attempts', 0)
+        end
+        if next_attempt_at then redis.call('HSET', key, 'next_attempt_at', next_attempt_at) else redis.call('HDEL', key, 'next_attempt_at') end
+
+        -- Items without transaction hash are the ones batches are made of
+        if hash then
+            redis.call('ZREM', prefix .. ':pending', id)
+        else
+            redis.call('ZADD', prefix .. ':pending', next_attempt_at or math.floor(tonumber(current[3]) / 1000), id)
+        end
+        redis.call('SREM', prefix .. ':status:' .. current[1], id)
+        redis.call('SADD', prefix .. ':status:' .. status, id)
+
+        if current[1] ~= status or current[2] ~= hash then
+            record_transition(prefix, id, ARGV[i + 1], status, hash, ARGV[11], ARGV[12])
+        end
+        updated = updated + 1
+    end
+end
+return updated
+"#;
+
+// ARGV : prefix, worker id, now (epoch us), then item id and event id of each item.
+// Items waiting for a batch and processing items of the worker are marked as processing
+// by it, returns their ids
+const CLAIM: &str = r#"
+local prefix, worker_id, now_us = ARGV[1], ARGV[2], ARGV[3]
+local claimed = {}
+for i = 4, #ARGV, 2 do
+    local id = ARGV[i]
+    local key = prefix .. ':item:' .. id
+    local current = redis.call('HMGET', key, 'migration_status', 'transaction_hash', 'updated_by')
+    if current[1] and (not current[2] or (current[1] == 'processing' and current[3] == worker_id)) then
+        redis.call('HSET', key, 'migration_status', 'processing', 'transaction_hash', '', 'updated_by', worker_id, 'updated_at', now_us)
+        redis.call('HDEL', key, 'note', 'note_params')
+        redis.call('ZREM', prefix .. ':pending', id)
+        redis.call('SREM', prefix .. ':status:' .. current[1], id)
+        redis.call('SADD', prefix .. ':status:processing', id)
+        if current[1] ~= 'processing' or current[2] ~= '' then
+            record_transition(prefix, id, ARGV[i + 1], 'processing', '', worker_id, now_us)
+        end
+        claimed[#claimed + 1] = id
+    end
+end
+return claimed
+"#;
+
+// KEYS : lock of each token, ARGV : holder. Locks taken since by another holder are kept
+const UNLOCK: &str = r#"
+for _, key in ipairs(KEYS) do
+    if redis.call('GET', key) == ARGV[1] then
+        redis.call('DEL', key)
+    end
+end
+return 0
+"#;
+
+pub async fn get_redis_connection(redis_url: &str) -> Result<ConnectionManager, RedisError> {
+    let client = redis::Client::open(redis_url)?;
+    ConnectionManager::new(client).await
+}
+
+enum HashUpdate<'a> {
+    Set(&'a str),
+    Clear,
+    // Empty hash keeps cancelled items and dead letters out of get_batch
+    KeepOrEmpty,
+}
+
+enum AttemptsUpdate {
+    Keep,
+    Increment,
+    Reset,
+}
+
+enum NextAttemptUpdate {
+    Keep,
+    Clear,
+    At(i64),
+}
+
+struct ItemUpdate<'a> {
+    status: QueueStatus,
+    transaction_hash: HashUpdate<'a>,
+    note: Option<&'a StatusNote>,
+    attempts: AttemptsUpdate,
+    next_attempt_at: NextAttemptUpdate,
+    // Items in another status are left untouched
+    only_from: Option<QueueStatus>,
+}
+
+/// Migration queue kept in Redis, items are hashes indexed by sorted sets and status
+/// transitions are appended to a stream. Workers poll a sorted set of pending items
+/// scored by the time they become eligible, instead of scanning a table. Scripts build
+/// their keys from `prefix`, so a cluster needs a hash tag prefix such as `{bridge}`.
+pub struct RedisQueueManager {
+    connection: ConnectionManager,
+    prefix: String,
+    batch_size: u8,
+    worker_id: String,
+    enqueue_script: Script,
+    update_script: Script,
+    claim_script: Script,
+    unlock_script: Script,
+}
+
+impl RedisQueueManager {
+    pub fn new(
+        connection: ConnectionManager,
+        prefix: &str,
+        batch_size: u8,
+        worker_id: &str,
+    ) -> Self {
+        Self {
+            connection,
+            prefix: prefix.into(),
+            batch_size,
+            worker_id: worker_id.into(),
+            enqueue_script: Script::new(&format!("{}{}", RECORD_TRANSITION, ENQUEUE)),
+            update_script: Script::new(&format!("{}{}", RECORD_TRANSITION, UPDATE)),
+            claim_script: Script::new(&format!("{}{}", RECORD_TRANSITION, CLAIM)),
+            unlock_script: Script::new(UNLOCK),
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}:{}", self.prefix, name)
+    }
+
+    fn token_lock_key(&self, project_id: &StarknetAddress, token_id: &TokenId) -> String {
+        self.key(&format!("lock:{}:{}", project_id, token_id))
+    }
+
+    /// Whether a bridge request holds the token of each item, in the same order.
+    async fn locked_items(&self, items: &[QueueItem]) -> Result<Vec<bool>, RedisError> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = redis::pipe();
+        for qi in items {
+            pipe.exists(self.token_lock_key(&qi.project_id, &qi.token_id));
+        }
+
+        pipe.query_async(&mut self.connection.clone()).await
+    }
+
+    /// Items by id, in the same order, `None` for unknown ones.
+    async fn fetch_items(&self, ids: &[String]) -> Result<Vec<Option<QueueItem>>, RedisError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = redis::pipe();
+        for id in ids {
+            pipe.hgetall(self.key(&format!("item:{}", id)));
+        }
+        let rows: Vec<HashMap<String, String>> =
+            pipe.query_async(&mut self.connection.clone()).await?;
+
+        Ok(rows.iter().map(hydrate_queue_item).collect())
+    }
+
+    async fn items_in_set(&self, set: &str) -> Result<Vec<QueueItem>, RedisError> {
+        let ids: Vec<String> = self.connection.clone().smembers(self.key(set)).await?;
+        Ok(self
+            .fetch_items(&ids)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    /// Processing items without transaction hash last updated before `before` (epoch
+    /// milliseconds). Items not updated since `updated_at` was recorded go by their
+    /// creation.
+    async fn stale_processing_ids(&self, before: i64) -> Result<Vec<QueueItemId>, RedisError> {
+        let ids: Vec<String> = self
+            .connection
+            .clone()
+            .smembers(self.key(&format!(
+                "status:{}",
+                status_label(&QueueStatus::Processing)
+            )))
+            .await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = redis::pipe();
+        for id in &ids {
+            pipe.hget(
+                self.key(&format!("item:{}", id)),
+                &["transaction_hash", "updated_at", "created_at"],
+            );
+        }
+        let rows: Vec<(Option<String>, Option<i64>, Option<i64>)> =
+            pipe.query_async(&mut self.connection.clone()).await?;
+
+        Ok(ids
+            .iter()
+            .zip(rows)
+            .filter(|(_, (hash, updated_at, created_at))| {
+                hash.as_deref().map_or(true, str::is_empty)
+                    && updated_at
+                        .or(*created_at)
+                        .map_or(false, |at| at / 1000 < before)
+            })
+            .filter_map(|(id, _)| id.parse().ok())
+            .collect())
+    }
+
+    /// Processing items this worker updated last, sent ones, or last updated before
+    /// `stale_before` (epoch milliseconds) when given.
+    async fn recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, RedisError> {
+        let ids: Vec<String> = self
+            .connection
+            .clone()
+            .smembers(self.key(&format!(
+                "status:{}",
+                status_label(&QueueStatus::Processing)
+            )))
+            .await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = redis::pipe();
+        for id in &ids {
+            pipe.hget(
+                self.key(&format!("item:{}", id)),
+                &["updated_by", "transaction_hash", "updated_at", "created_at"],
+            );
+        }
+        let rows: Vec<(Option<String>, Option<String>, Option<i64>, Option<i64>)> =
+            pipe.query_async(&mut self.connection.clone()).await?;
+        let ids: Vec<String> = ids
+            .into_iter()
+            .zip(rows)
+            .filter(|(_, (updated_by, tx_hash, updated_at, created_at))| {
+                updated_by.as_deref() == Some(self.worker_id.as_str())
+                    || tx_hash.as_deref().map_or(false, |h| !h.is_empty())
+                    || stale_before.map_or(false, |before| {
+                        updated_at
+                            .or(*created_at)
+                            .map_or(false, |at| at / 1000 < before)
+                    })
+            })
+            .map(|(id, _)| id)
+            .collect();
+
+        Ok(self
+            .fetch_items(&ids)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    async fn requeue_stale_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, RedisError> {
+        let ids = self.stale_processing_ids(before).await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.update_items(
+            &ids,
+            ItemUpdate {
+                status: QueueStatus::Pending,
+                transaction_hash: HashUpdate::Clear,
+                note: Some(note),
+                attempts: AttemptsUpdate::Keep,
+                next_attempt_at: NextAttemptUpdate::Keep,
+                only_from: Some(QueueStatus::Processing),
+            },
+        )
+        .await?;
+        let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+
+        // Items a worker moved on with meanwhile were left untouched
+        Ok(self
+            .fetch_items(&ids)
+            .await?
+            .into_iter()
+            .flatten()
+            .filter(|qi| matches!(qi.status, QueueStatus::Pending))
+            .collect())
+    }
+
+    async fn update_items(
+        &self,
+        ids: &[QueueItemId],
+        update: ItemUpdate<'_>,
+    ) -> Result<usize, RedisError> {
+        let mut invocation = self.update_script.prepare_invoke();
+        invocation
+            .arg(&self.prefix)
+            .arg(status_label(&update.status));
+        match update.transaction_hash {
+            HashUpdate::Set(hash) => invocation.arg("set").arg(hash),
+            HashUpdate::Clear => invocation.arg("clear").arg(""),
+            HashUpdate::KeepOrEmpty => invocation.arg("keep_or_empty").arg(""),
+        };
+        match update.note {
+            Some(note) => invocation
+                .arg("set")
+                .arg(&note.template)
+                .arg(params_json(&note.params).unwrap_or_default()),
+            None => invocation.arg("clear").arg("").arg(""),
+        };
+        invocation.arg(match update.attempts {
+            AttemptsUpdate::Keep => "keep",
+            AttemptsUpdate::Increment => "increment",
+            AttemptsUpdate::Reset => "reset",
+        });
+        match update.next_attempt_at {
+            NextAttemptUpdate::Keep => invocation.arg("keep"),
+            NextAttemptUpdate::Clear => invocation.arg(""),
+            NextAttemptUpdate::At(at) => invocation.arg(at),
+        };
+        invocation
+            .arg(update.only_from.as_ref().map_or("", status_label))
+            .arg(&self.worker_id)
+            .arg(now_us());
+        for id in ids {
+            invocation
+                .arg(id.to_string())
+                .arg(Uuid::new_v4().to_string());
+        }
+
+        invocation.invoke_async(&mut self.connection.clone()).await
+    }
+}
+
+#[async_trait]
+impl QueueManager for RedisQueueManager {
+    async fn enqueue(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        // Same token twice would overwrite its index, the unique index refuses it in postgres
+        if token_ids.iter().collect::<HashSet<_>>().len() != token_ids.len() {
+            error!("Error enqueueing duplicated tokens {:#?}", &token_ids);
+            return Err(QueueError::FailedToEnqueue);
+        }
+
+        let mut queue_items = Vec::new();
+        let mut invocation = self.enqueue_script.prepare_invoke();
+        invocation
+            .arg(&self.prefix)
+            .arg(keplr_wallet_pubkey.as_str())
+            .arg(starknet_wallet_pubkey.as_str())
+            .arg(project_id.as_str())
+            .arg(&self.worker_id)
+            .arg(now_us())
+            .arg(request_id.unwrap_or_default());
+        for token in &token_ids {
+            let mut item = QueueItem::new(
+                keplr_wallet_pubkey,
+                starknet_wallet_pubkey,
+                project_id,
+                token.clone(),
+            );
+            let id = QueueItemId::new();
+            invocation
+                .arg(id.to_string())
+                .arg(token.as_str())
+                .arg(Uuid::new_v4().to_string());
+            item.id = Some(id);
+            item.request_id = request_id.map(String::from);
+            queue_items.push(item);
+        }
+
+        match invocation
+            .invoke_async::<_, Option<Vec<String>>>(&mut self.connection.clone())
+            .await
+        {
+            Ok(Some(ids)) => {
+                // Tokens waiting to be minted answered with the item they already had
+                let existing: Vec<String> = queue_items
+                    .iter()
+                    .zip(&ids)
+                    .filter(|(item, id)| item.id.map(|i| i.to_string()).as_ref() != Some(*id))
+                    .map(|(_, id)| id.clone())
+                    .collect();
+                if existing.is_empty() {
+                    return Ok(queue_items);
+                }
+                let queued = match self.fetch_items(&existing).await {
+                    Ok(items) => items.into_iter().flatten().collect::<Vec<QueueItem>>(),
+                    Err(e) => {
+                        error!("Failed to fetch queued tokens {:#?}", e);
+                        return Err(QueueError::FailedToEnqueue);
+                    }
+                };
+                for item in queue_items.iter_mut() {
+                    if let Some(qi) = queued.iter().find(|qi| qi.token_id == item.token_id) {
+                        *item = qi.clone();
+                    }
+                }
+                Ok(queue_items)
+            }
+            Ok(None) => {
+                error!("Tokens already queued {:#?}", &token_ids);
+                Err(QueueError::FailedToEnqueue)
+            }
+            Err(e) => {
+                error!("Error enqueueing token {:#?} {:#?}", &token_ids, e);
+                Err(QueueError::FailedToEnqueue)
+            }
+        }
+    }
+
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let page_size = self.batch_size as isize;
+        let now_ms = now_us() / 1000;
+        let mut batch = Vec::new();
+        let mut offset = 0;
+        // Pending items of every project share the sorted set, pages are read until the
+        // batch is full so items of excluded projects cannot starve the others
+        loop {
+            let ids: Result<Vec<String>, RedisError> = self
+                .connection
+                .clone()
+                .zrangebyscore_limit(self.key("pending"), "-inf", now_ms, offset, page_size)
+                .await;
+            let page_len = ids.as_ref().map_or(0, Vec::len);
+            let items = match ids {
+                Ok(ids) => self.fetch_items(&ids).await,
+                Err(e) => Err(e),
+            };
+            let items: Vec<QueueItem> = match items {
+                Ok(items) => items
+                    .into_iter()
+                    .flatten()
+                    .filter(|qi| !excluded_projects.contains(&qi.project_id))
+                    .collect(),
+                Err(e) => {
+                    error!("{}", e);
+                    return Err(QueueError::FailedToGetBatch);
+                }
+            };
+            // Tokens checked by a bridge request wait for the next batch
+            let locked = match self.locked_items(&items).await {
+                Ok(locked) => locked,
+                Err(e) => {
+                    error!("{}", e);
+                    return Err(QueueError::FailedToGetBatch);
+                }
+            };
+            batch.extend(
+                items
+                    .into_iter()
+                    .zip(locked)
+                    .filter(|(_, locked)| !locked)
+                    .map(|(qi, _)| qi),
+            );
+
+            if excluded_projects.is_empty()
+                || batch.len() >= self.batch_size as usize
+                || (page_len as isize) < page_size
+            {
+                break;
+            }
+            offset += page_size;
+        }
+        batch.truncate(self.batch_size as usize);
+
+        Ok(batch)
+    }
+
+    async fn get_customer_migration_state(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
+    ) -> Vec<QueueItem> {
+        match self
+            .items_in_set(&format!("customer:{}:{}", keplr_wallet_pubkey, project_id))
+            .await
+        {
+            Ok(items) => items,
+            Err(err) => {
+                error!("Error while fetching customer migration state : {:#?}", err);
+                Vec::new()
+            }
+        }
+    }
+
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let projects: Result<Vec<String>, RedisError> = self
+            .connection
+            .clone()
+            .smembers(self.key(&format!("wallet:{}", keplr_wallet_pubkey)))
+            .await;
+        let mut projects = projects.map_err(|err| {
+            error!("Error while fetching wallet projects : {:#?}", err);
+            QueueError::FailedToGetBatch
+        })?;
+        projects.sort();
+
+        let mut items = Vec::new();
+        for project in projects {
+            let mut project_items = self
+                .items_in_set(&format!("customer:{}:{}", keplr_wallet_pubkey, project))
+                .await
+                .map_err(|err| {
+                    error!("Error while fetching wallet migrations : {:#?}", err);
+                    QueueError::FailedToGetBatch
+                })?;
+            project_items.sort_by(|a, b| a.token_id.as_str().cmp(b.token_id.as_str()));
+            items.extend(project_items);
+        }
+
+        Ok(items)
+    }
+
+    async fn update_queue_items_status(
+        &self,
+        ids: &[QueueItemId],
+        transaction_hash: String,
+        status: QueueStatus,
+    ) -> Result<(), QueueUpdateError> {
+        match self
+            .update_items(
+                ids,
+                ItemUpdate {
+                    status,
+                    transaction_hash: HashUpdate::Set(&transaction_hash),
+                    note: None,
+                    attempts: AttemptsUpdate::Keep,
+                    next_attempt_at: NextAttemptUpdate::Keep,
+                    only_from: None,
+                },
+            )
+            .await
+        {
+            Ok(updated) if updated == ids.len() => Ok(()),
+            Ok(_) => Err(QueueUpdateError::StatusUpdateFail(ids.to_vec())),
+            Err(e) => {
+                error!("Failed to update queue items in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut invocation = self.claim_script.prepare_invoke();
+        invocation
+            .arg(&self.prefix)
+            .arg(&self.worker_id)
+            .arg(now_us());
+        for id in ids {
+            invocation
+                .arg(id.to_string())
+                .arg(Uuid::new_v4().to_string());
+        }
+
+        match invocation
+            .invoke_async::<_, Vec<String>>(&mut self.connection.clone())
+            .await
+        {
+            Ok(claimed) => Ok(claimed.iter().filter_map(|id| id.parse().ok()).collect()),
+            Err(e) => {
+                error!("Failed to claim queue items in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn get_queue_item_history(
+        &self,
+        id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError> {
+        let transitions: Vec<String> = match self
+            .connection
+            .clone()
+            .lrange(self.key(&format!("history:{}", id)), 0, -1)
+            .await
+        {
+            Ok(t) => t,
+            Err(e) => {
+                error!("Failed to fetch queue item history {:#?}", e);
+                return Err(QueueError::NotFound);
+            }
+        };
+
+        Ok(transitions
+            .iter()
+            .filter_map(|t| serde_json::from_str(t).ok())
+            .collect())
+    }
+
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        match self
+            .update_items(
+                ids,
+                ItemUpdate {
+                    status: QueueStatus::Pending,
+                    transaction_hash: HashUpdate::Clear,
+                    note: Some(note),
+                    attempts: AttemptsUpdate::Keep,
+                    next_attempt_at: NextAttemptUpdate::Keep,
+                    only_from: None,
+                },
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to defer queue items in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        match self.requeue_stale_items(before, note).await {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                error!("Failed to requeue stale processing items in redis {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError> {
+        match self
+            .items_in_set(&format!(
+                "status:{}",
+                status_label(&QueueStatus::Processing)
+            ))
+            .await
+        {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                error!("Failed to fetch processing queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        match self.recoverable_items(stale_before).await {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                error!("Failed to fetch recoverable queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn count_pending_items(&self) -> Result<i64, QueueError> {
+        let key = self.key(&format!("status:{}", status_label(&QueueStatus::Pending)));
+        match self.connection.clone().scard::<_, i64>(key).await {
+            Ok(count) => Ok(count),
+            Err(e) => {
+                error!("Failed to count pending queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError> {
+        match self.fetch_items(&[id.to_string()]).await {
+            Ok(mut items) => items.pop().flatten().ok_or(QueueError::NotFound),
+            Err(e) => {
+                error!("Failed to fetch queue item {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        match self
+            .update_items(
+                ids,
+                ItemUpdate {
+                    status: QueueStatus::Error,
+                    transaction_hash: HashUpdate::KeepOrEmpty,
+                    note: Some(note),
+                    attempts: AttemptsUpdate::Keep,
+                    next_attempt_at: NextAttemptUpdate::Keep,
+                    only_from: None,
+                },
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to cancel queue items in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError> {
+        match self
+            .update_items(
+                ids,
+                ItemUpdate {
+                    status: QueueStatus::Pending,
+                    transaction_hash: HashUpdate::Clear,
+                    note: Some(note),
+                    attempts: AttemptsUpdate::Increment,
+                    next_attempt_at: NextAttemptUpdate::At(
+                        now_us() / 1000 + delay.as_millis() as i64,
+                    ),
+                    only_from: None,
+                },
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to schedule queue items retry in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        match self
+            .update_items(
+                ids,
+                ItemUpdate {
+                    status: QueueStatus::DeadLetter,
+                    transaction_hash: HashUpdate::KeepOrEmpty,
+                    note: Some(note),
+                    attempts: AttemptsUpdate::Increment,
+                    next_attempt_at: NextAttemptUpdate::Clear,
+                    only_from: None,
+                },
+            )
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to dead letter queue items in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        match self
+            .update_items(
+                ids,
+                ItemUpdate {
+                    status: QueueStatus::Pending,
+                    transaction_hash: HashUpdate::Clear,
+                    note: Some(note),
+                    attempts: AttemptsUpdate::Reset,
+                    next_attempt_at: NextAttemptUpdate::Clear,
+                    only_from: Some(QueueStatus::DeadLetter),
+                },
+            )
+            .await
+        {
+            Ok(updated) if updated == ids.len() => Ok(()),
+            Ok(_) => Err(QueueUpdateError::StatusUpdateFail(ids.to_vec())),
+            Err(e) => {
+                error!("Failed to requeue dead letters in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError> {
+        let min = match &page.after {
+            Some(c) => c.created_at.to_string(),
+            None => "-inf".into(),
+        };
+        let after = page
+            .after
+            .as_ref()
+            .map(|c| (c.created_at, c.id.to_string()));
+        let status = status.as_ref().map(status_label);
+
+        let mut rows = Vec::new();
+        let mut offset = 0;
+        loop {
+            // Items created at the cursor timestamp come first, ties are ordered by id
+            let chunk: Vec<(String, f64)> = match self
+                .connection
+                .clone()
+                .zrangebyscore_limit_withscores(
+                    self.key("items"),
+                    &min,
+                    "+inf",
+                    offset,
+                    LIST_CHUNK_SIZE as isize,
+                )
+                .await
+            {
+                Ok(c) => c,
+                Err(e) => {
+                    error!("Failed to list queue items {:#?}", e);
+                    return Err(QueueError::FailedToGetBatch);
+                }
+            };
+            offset += chunk.len() as isize;
+            let exhausted = chunk.len() < LIST_CHUNK_SIZE;
+
+            let chunk: Vec<(String, i64)> = chunk
+                .into_iter()
+                .map(|(id, created_at)| (id, created_at as i64))
+                .filter(|(id, created_at)| match &after {
+                    Some((after_created_at, after_id)) => {
+                        created_at > after_created_at || id > after_id
+                    }
+                    None => true,
+                })
+                .collect();
+            let ids: Vec<String> = chunk.iter().map(|(id, _)| id.clone()).collect();
+            let items = match self.fetch_items(&ids).await {
+                Ok(i) => i,
+                Err(e) => {
+                    error!("Failed to list queue items {:#?}", e);
+                    return Err(QueueError::FailedToGetBatch);
+                }
+            };
+            for ((_, created_at), item) in chunk.into_iter().zip(items) {
+                let Some(item) = item else { continue };
+                if status.map_or(false, |s| s != status_label(&item.status)) {
+                    continue;
+                }
+                let Some(id) = item.id.as_ref() else { continue };
+                rows.push((Cursor::new(created_at, *id.as_uuid()), item));
+            }
+
+            if exhausted || rows.len() as i64 > page.limit {
+                break;
+            }
+        }
+
+        Ok(Page::from_rows(rows, page.limit))
+    }
+
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError> {
+        // Cursors carry the stream entry id as created_at, sequence numbers stay below 1000
+        let start = match &page.after {
+            Some(c) => format!("({}-{}", c.created_at / 1000, c.created_at % 1000),
+            None => "-".into(),
+        };
+        let reply: StreamRangeReply = match self
+            .connection
+            .clone()
+            .xrange_count(self.key("events"), start, "+", page.limit + 1)
+            .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                error!("Failed to list queue events {:#?}", e);
+                return Err(QueueError::FailedToGetBatch);
+            }
+        };
+
+        Ok(Page::from_rows(
+            reply
+                .ids
+                .iter()
+                .filter_map(|entry| {
+                    let (ms, seq) = entry.id.split_once('-')?;
+                    let (ms, seq) = (ms.parse::<i64>().ok()?, seq.parse::<i64>().ok()?);
+                    let id = Uuid::parse_str(&entry.get::<String>("id")?).ok()?;
+                    let event = QueueEvent {
+                        id,
+                        queue_item_id: entry.get::<String>("queue_item_id")?.parse().ok()?,
+                        status: parse_status(&entry.get::<String>("migration_status")?)?,
+                        transaction_hash: entry.get("transaction_hash"),
+                        worker_id: entry.get("worker_id"),
+                        created_at: ms,
+                    };
+                    Some((Cursor::new(ms * 1000 + seq.min(999), id), event))
+                })
+                .collect(),
+            page.limit,
+        ))
+    }
+
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError> {
+        let mut pipe = redis::pipe();
+        for id in ids {
+            let key = self.key(&format!("item:{}", id));
+            for (field, fee) in [("estimated_fee", estimated_fee), ("actual_fee", actual_fee)] {
+                match fee {
+                    Some(fee) => pipe.hset(&key, field, fee).ignore(),
+                    None => pipe.hdel(&key, field).ignore(),
+                };
+            }
+        }
+
+        match pipe
+            .query_async::<_, ()>(&mut self.connection.clone())
+            .await
+        {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("Failed to record mint fees in redis {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError> {
+        if token_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut pipe = redis::pipe();
+        for token in token_ids {
+            pipe.cmd("SET")
+                .arg(self.token_lock_key(project_id, token))
+                .arg(holder)
+                .arg("NX")
+                .arg("PX")
+                .arg(ttl.as_millis() as u64);
+        }
+
+        match pipe
+            .query_async::<_, Vec<Option<String>>>(&mut self.connection.clone())
+            .await
+        {
+            Ok(replies) => Ok(token_ids
+                .iter()
+                .zip(replies)
+                .filter(|(_, reply)| reply.is_some())
+                .map(|(token, _)| token.clone())
+                .collect()),
+            Err(e) => {
+                error!("Failed to lock tokens in redis {:#?}", e);
+                Err(QueueError::FailedToLockTokens)
+            }
+        }
+    }
+
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError> {
+        if token_ids.is_empty() {
+            return Ok(());
+        }
+        let mut invocation = self.unlock_script.prepare_invoke();
+        for token in token_ids {
+            invocation.key(self.token_lock_key(project_id, token));
+        }
+        invocation.arg(holder);
+
+        match invocation
+            .invoke_async::<_, i64>(&mut self.connection.clone())
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to unlock tokens in redis {:#?}", e);
+                Err(QueueError::FailedToLockTokens)
+            }
+        }
+    }
+}
+
+fn parse_status(value: &str) -> Option<QueueStatus> {
+    QueueStatus::ALL
+        .into_iter()
+        .find(|s| status_label(s) == value)
+}
+
+fn hydrate_queue_item(fields: &HashMap<String, String>) -> Option<QueueItem> {
+    Some(QueueItem {
+        id: Some(fields.get("id")?.parse().ok()?),
+        keplr_wallet_pubkey: JunoAddress::unchecked(fields.get("keplr_wallet_pubkey")?.clone()),
+        starknet_wallet_pubkey: StarknetAddress::unchecked(
+            fields.get("starknet_wallet_pubkey")?.clone(),
+        ),
+        project_id: StarknetAddress::unchecked(fields.get("project_id")?.clone()),
+        token_id: TokenId::unchecked(fields.get("token_id")?.clone()),
+        status: parse_status(fields.get("migration_status")?)?,
+        transaction_hash: fields.get("transaction_hash").cloned(),
+        note: fields.get("note").cloned(),
+        note_params: fields
+            .get("note_params")
+            .and_then(|p| serde_json::from_str(p).ok())
+            .unwrap_or_default(),
+        attempts: fields
+            .get("attempts")
+            .and_then(|a| a.parse().ok())
+            .unwrap_or_default(),
+        estimated_fee: fields.get("estimated_fee").cloned(),
+        actual_fee: fields.get("actual_fee").cloned(),
+        request_id: fields.get("request_id").cloned(),
+    })
+}
+
+// Parameters are kept as a JSON object, absent when the message has none
+fn params_json(params: &BTreeMap<String, String>) -> Option<String> {
+    match params.is_empty() {
+        true => None,
+        false => serde_json::to_string(params).ok(),
+    }
+}
+
+fn now_us() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as i64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use log::error;
+use reqwest::Client;
+use sha2::Sha256;
+
+use crate::domain::report::{ReportError, ReportPublisher, ReportSigner, SignedDailyReport};
+
+/// HMAC-SHA256 signature, hex encoded.
+pub struct HmacReportSigner {
+    key: Vec<u8>,
+}
+
+impl HmacReportSigner {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.as_bytes().to_vec(),
+        }
+    }
+}
+
+impl ReportSigner for HmacReportSigner {
+    fn sign(&self, payload: &str) -> String {
+        // HMAC accepts keys of any length
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.key).unwrap();
+        mac.update(payload.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+/// Posts generated reports to an HTTP endpoint (mail relay, chat webhook...).
+pub struct WebhookReportPublisher {
+    url: String,
+    client: Client,
+}
+
+impl WebhookReportPublisher {
+    pub fn new(url: &str, client: Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl ReportPublisher for WebhookReportPublisher {
+    async fn publish(&self, report: &SignedDailyReport) -> Result<(), ReportError> {
+        match self.client.post(&self.url).json(report).send().await {
+            Ok(r) if r.status().is_success() => Ok(()),
+            Ok(r) => {
+                error!("Report webhook responded with status {}", r.status());
+                Err(ReportError::PublicationFailed)
+            }
+            Err(e) => {
+                error!("Failed to call report webhook {:#?}", e);
+                Err(ReportError::PublicationFailed)
+            }
+        }
+    }
+}
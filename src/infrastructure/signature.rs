@@ -0,0 +1,198 @@
+use base64::{engine::general_purpose, Engine};
+use bech32::FromBase32;
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use log::warn;
+use sha3::{Digest, Keccak256};
+use std::sync::Arc;
+
+use crate::domain::bridge::{SignedHash, SignedHashValidator, SignedHashValidatorError};
+
+#[derive(Debug)]
+pub enum SignatureValidatorConfigError {
+    NoValidator,
+    UnknownValidator(String),
+}
+
+pub struct KeplrSignatureVeirfier {}
+impl SignedHashValidator for KeplrSignatureVeirfier {
+    fn verify(
+        &self,
+        signed_hash: &SignedHash,
+        starknet_account_addrr: &str,
+        keplr_wallet_pubkey: &str,
+    ) -> Result<String, SignedHashValidatorError> {
+        let pubkey = signed_hash.pub_key.key_value.to_string();
+        let signature = verify_keplr_sign::Signature {
+            pub_key: verify_keplr_sign::PublicKey {
+                sig_type: signed_hash.pub_key.key_type.to_string(),
+                sig_value: pubkey.to_string(),
+            },
+            signature: signed_hash.signature.to_string(),
+        };
+
+        let is_signature_ok = verify_keplr_sign::verify_arbitrary(
+            keplr_wallet_pubkey,
+            &pubkey,
+            starknet_account_addrr.as_bytes(),
+            &signature,
+        );
+
+        if !is_signature_ok {
+            return Err(SignedHashValidatorError::FailedToVerifyHash);
+        }
+
+        Ok(signature.signature)
+    }
+}
+
+/// `pub_key.type` of wallets signing with an Ethereum key, Metamask through Evmos / Ethermint.
+pub const ETH_SECP256K1_KEY_TYPE: &str = "ethermint/PubKeyEthSecp256k1";
+
+/// EIP-191 `personal_sign` of the starknet account address. Only handles keys of type
+/// `ethermint/PubKeyEthSecp256k1`, the juno address being derived from the key the
+/// Ethereum way (last 20 bytes of the keccak256 of the key).
+pub struct EvmPersonalSignVerifier {}
+
+impl EvmPersonalSignVerifier {
+    fn personal_sign_hash(message: &[u8]) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()).as_bytes());
+        hasher.update(message);
+        hasher.finalize().into()
+    }
+
+    fn recover_key(message: &[u8], signature: &str) -> Option<VerifyingKey> {
+        let bytes = hex::decode(signature.trim_start_matches("0x")).ok()?;
+        if bytes.len() != 65 {
+            return None;
+        }
+        let signature = Signature::from_slice(&bytes[..64]).ok()?;
+        // Metamask sends v as 27 / 28, hardware wallets as 0 / 1
+        let v = match bytes[64] {
+            27 | 28 => bytes[64] - 27,
+            v => v,
+        };
+        let recovery_id = RecoveryId::from_byte(v)?;
+
+        VerifyingKey::recover_from_prehash(
+            &Self::personal_sign_hash(message),
+            &signature,
+            recovery_id,
+        )
+        .ok()
+    }
+
+    fn address_bytes(key: &VerifyingKey) -> Vec<u8> {
+        let uncompressed = key.to_encoded_point(false);
+        Keccak256::digest(&uncompressed.as_bytes()[1..])[12..].to_vec()
+    }
+}
+
+impl SignedHashValidator for EvmPersonalSignVerifier {
+    fn verify(
+        &self,
+        signed_hash: &SignedHash,
+        starknet_account_addrr: &str,
+        keplr_wallet_pubkey: &str,
+    ) -> Result<String, SignedHashValidatorError> {
+        if signed_hash.pub_key.key_type != ETH_SECP256K1_KEY_TYPE {
+            return Err(SignedHashValidatorError::FailedToVerifyHash);
+        }
+
+        let key = Self::recover_key(starknet_account_addrr.as_bytes(), &signed_hash.signature)
+            .ok_or(SignedHashValidatorError::FailedToVerifyHash)?;
+        let declared_key = general_purpose::STANDARD
+            .decode(&signed_hash.pub_key.key_value)
+            .map_err(|_| SignedHashValidatorError::FailedToVerifyHash)?;
+        if key.to_encoded_point(true).as_bytes() != declared_key.as_slice() {
+            return Err(SignedHashValidatorError::FailedToVerifyHash);
+        }
+
+        let wallet_address = bech32::decode(keplr_wallet_pubkey)
+            .ok()
+            .and_then(|(_, data, _)| Vec::<u8>::from_base32(&data).ok())
+            .ok_or(SignedHashValidatorError::FailedToVerifyHash)?;
+        if wallet_address != Self::address_bytes(&key) {
+            return Err(SignedHashValidatorError::FailedToVerifyHash);
+        }
+
+        Ok(signed_hash.signature.to_string())
+    }
+}
+
+/// Accepts any signature, meant for staging environments where customers sign with
+/// throwaway wallets.
+pub struct PermissiveSignedHashValidator {}
+impl SignedHashValidator for PermissiveSignedHashValidator {
+    fn verify(
+        &self,
+        signed_hash: &SignedHash,
+        starknet_account_addrr: &str,
+        keplr_wallet_pubkey: &str,
+    ) -> Result<String, SignedHashValidatorError> {
+        warn!(
+            "Accepting signature of {} for {} without verifying it",
+            keplr_wallet_pubkey, starknet_account_addrr
+        );
+        Ok(signed_hash.signature.to_string())
+    }
+}
+
+/// Accepts a signature as soon as one of its validators does, so wallets signing
+/// with different schemes can use the bridge side by side.
+pub struct CompositeSignedHashValidator {
+    validators: Vec<Arc<dyn SignedHashValidator>>,
+}
+
+impl CompositeSignedHashValidator {
+    pub fn new(validators: Vec<Arc<dyn SignedHashValidator>>) -> Self {
+        Self { validators }
+    }
+}
+
+impl SignedHashValidator for CompositeSignedHashValidator {
+    fn verify(
+        &self,
+        signed_hash: &SignedHash,
+        starknet_account_addrr: &str,
+        keplr_wallet_pubkey: &str,
+    ) -> Result<String, SignedHashValidatorError> {
+        self.validators
+            .iter()
+            .find_map(|v| {
+                v.verify(signed_hash, starknet_account_addrr, keplr_wallet_pubkey)
+                    .ok()
+            })
+            .ok_or(SignedHashValidatorError::FailedToVerifyHash)
+    }
+}
+
+/// Builds the validator from scheme names (`keplr-adr36`, `evm-eip191`, `test-permissive`),
+/// several names are combined into a composite validator trying them in order.
+pub fn configure_signed_hash_validator(
+    names: &[String],
+) -> Result<Arc<dyn SignedHashValidator>, SignatureValidatorConfigError> {
+    let mut validators = Vec::new();
+    for name in names {
+        let validator: Arc<dyn SignedHashValidator> = match name.trim() {
+            "keplr-adr36" => Arc::new(KeplrSignatureVeirfier {}),
+            "evm-eip191" => Arc::new(EvmPersonalSignVerifier {}),
+            "test-permissive" => {
+                warn!("Signatures are not verified, test-permissive validator is enabled");
+                Arc::new(PermissiveSignedHashValidator {})
+            }
+            other => {
+                return Err(SignatureValidatorConfigError::UnknownValidator(
+                    other.to_string(),
+                ))
+            }
+        };
+        validators.push(validator);
+    }
+
+    match validators.len() {
+        0 => Err(SignatureValidatorConfigError::NoValidator),
+        1 => Ok(validators.remove(0)),
+        _ => Ok(Arc::new(CompositeSignedHashValidator::new(validators))),
+    }
+}
@@ -0,0 +1,912 @@
+use crate::domain::{
+    bridge::{
+        QueueError, QueueEvent, QueueItem, QueueItemTransition, QueueManager, QueueStatus,
+        QueueUpdateError,
+    },
+    ids::{JunoAddress, ProjectId, QueueItemId, StarknetAddress, TokenId},
+    pagination::{Cursor, Page, PageRequest},
+    save_customer_data::{CustomerKeys, DataRepository, SaveCustomerDataError},
+    status_message::StatusNote,
+};
+use async_trait::async_trait;
+use log::error;
+use rusqlite::{params, Connection, OptionalExtension, Row, ToSql};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use uuid::Uuid;
+
+// Applied on every start, statements are idempotent
+const SCHEMA: &str = include_str!("../../data/sqlite/schema.sql");
+
+const QUEUE_ITEM_COLUMNS: &str = "id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, transaction_hash, migration_status, note, note_params, attempts, estimated_fee, actual_fee, request_id, created_at";
+
+// Columns added after the schema was first released, which files created before lack
+const ADDED_QUEUE_COLUMNS: [(&str, &str); 3] = [
+    ("estimated_fee", "TEXT DEFAULT NULL"),
+    ("actual_fee", "TEXT DEFAULT NULL"),
+    ("request_id", "TEXT DEFAULT NULL"),
+];
+
+/// Single file database for local development, e.g. `sqlite://bridge.db` or
+/// `sqlite::memory:`. Statements run on the calling task, which is fine at
+/// development load but not for production.
+#[derive(Clone)]
+pub struct SqliteDatabase {
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteDatabase {
+    pub fn open(database_url: &str) -> Result<Self, rusqlite::Error> {
+        let path = database_url
+            .trim_start_matches("sqlite://")
+            .trim_start_matches("sqlite:");
+        let connection = match path {
+            "" | ":memory:" => Connection::open_in_memory()?,
+            path => Connection::open(path)?,
+        };
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        connection.execute_batch(SCHEMA)?;
+        add_missing_queue_columns(&connection)?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Connection> {
+        // A panic mid statement leaves the connection usable, sqlite rolled it back
+        match self.connection.lock() {
+            Ok(c) => c,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+pub struct SqliteDataRepository {
+    database: SqliteDatabase,
+}
+
+impl SqliteDataRepository {
+    pub fn new(database: SqliteDatabase) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl DataRepository for SqliteDataRepository {
+    async fn save_customer_keys(&self, keys: CustomerKeys) -> Result<(), SaveCustomerDataError> {
+        let token_ids: Vec<&str> = keys.token_ids.iter().map(|t| t.as_str()).collect();
+        let token_ids = match serde_json::to_string(&token_ids) {
+            Ok(t) => t,
+            Err(_) => return Err(SaveCustomerDataError::FailedToPersistToDatabase),
+        };
+
+        match self.database.lock().execute(
+            "INSERT INTO customer_keys (keplr_wallet_pubkey, project_id, token_ids) VALUES (?1, ?2, ?3) ON CONFLICT (keplr_wallet_pubkey, project_id) DO UPDATE SET token_ids = excluded.token_ids",
+            params![keys.keplr_wallet_pubkey.as_str(), keys.project_id.as_str(), token_ids],
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error while saving customer to database {:#?}", e);
+                Err(SaveCustomerDataError::FailedToPersistToDatabase)
+            }
+        }
+    }
+
+    async fn get_customer_keys(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+    ) -> Result<CustomerKeys, SaveCustomerDataError> {
+        let token_ids = match self
+            .database
+            .lock()
+            .query_row(
+                "SELECT token_ids FROM customer_keys WHERE keplr_wallet_pubkey = ?1 AND project_id = ?2",
+                params![keplr_wallet_pubkey.as_str(), project_id.as_str()],
+                |row| row.get::<usize, String>(0),
+            )
+            .optional()
+        {
+            Ok(Some(t)) => t,
+            Ok(None) => return Err(SaveCustomerDataError::NotFound),
+            Err(e) => {
+                error!("Error while fetching customer keys {:#?}", e);
+                return Err(SaveCustomerDataError::NotFound);
+            }
+        };
+        let token_ids: Vec<String> = match serde_json::from_str(&token_ids) {
+            Ok(t) => t,
+            Err(_) => return Err(SaveCustomerDataError::NotFound),
+        };
+
+        Ok(CustomerKeys {
+            keplr_wallet_pubkey: keplr_wallet_pubkey.clone(),
+            project_id: project_id.clone(),
+            token_ids: token_ids.into_iter().map(TokenId::unchecked).collect(),
+        })
+    }
+
+    async fn save_authorized_sender(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+        sender: &JunoAddress,
+    ) -> Result<(), SaveCustomerDataError> {
+        match self.database.lock().execute(
+            "INSERT INTO authorized_senders (keplr_wallet_pubkey, project_id, sender) VALUES (?1, ?2, ?3) ON CONFLICT DO NOTHING",
+            params![keplr_wallet_pubkey.as_str(), project_id.as_str(), sender.as_str()],
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Error while saving authorized sender to database {:#?}", e);
+                Err(SaveCustomerDataError::FailedToPersistToDatabase)
+            }
+        }
+    }
+
+    async fn get_authorized_senders(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &ProjectId,
+    ) -> Result<Vec<JunoAddress>, SaveCustomerDataError> {
+        let connection = self.database.lock();
+        let senders = connection
+            .prepare("SELECT sender FROM authorized_senders WHERE keplr_wallet_pubkey = ?1 AND project_id = ?2")
+            .and_then(|mut statement| {
+                statement
+                    .query_map(
+                        params![keplr_wallet_pubkey.as_str(), project_id.as_str()],
+                        |row| row.get::<usize, String>(0),
+                    )?
+                    .collect::<Result<Vec<String>, _>>()
+            });
+
+        match senders {
+            Ok(senders) => Ok(senders.into_iter().map(JunoAddress::unchecked).collect()),
+            Err(e) => {
+                error!("Error while fetching authorized senders {:#?}", e);
+                Err(SaveCustomerDataError::NotFound)
+            }
+        }
+    }
+}
+
+pub struct SqliteQueueManager {
+    database: SqliteDatabase,
+    batch_size: u8,
+    worker_id: String,
+}
+
+impl SqliteQueueManager {
+    pub fn new(database: SqliteDatabase, batch_size: u8, worker_id: &str) -> Self {
+        Self {
+            database,
+            batch_size,
+            worker_id: worker_id.into(),
+        }
+    }
+
+    fn query_items(&self, sql: &str, values: &[&dyn ToSql]) -> rusqlite::Result<Vec<QueueItem>> {
+        let connection = self.database.lock();
+        let mut statement = connection.prepare(sql)?;
+        let items = statement
+            .query_map(values, hydrate_queue_item)?
+            .map(|row| row.map(|(_, item)| item));
+        items.collect()
+    }
+
+    fn insert_items(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        request_id: Option<&str>,
+    ) -> rusqlite::Result<Vec<QueueItem>> {
+        let mut connection = self.database.lock();
+        let tx = connection.transaction()?;
+        let mut queue_items = Vec::new();
+        {
+            let mut existing = tx.prepare(&format!("SELECT {} FROM migration_queue WHERE project_id = ?1 AND token_id = ?2 AND migration_status IN ('pending', 'processing')", QUEUE_ITEM_COLUMNS))?;
+            let mut statement = tx.prepare("INSERT INTO migration_queue (id, keplr_wallet_pubkey, starknet_wallet_pubkey, project_id, token_id, updated_by, created_at, updated_at, request_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7, ?8)")?;
+            for token in token_ids {
+                // A token waiting to be minted keeps its item, resubmitting it cannot mint it twice
+                if let Some(row) = existing
+                    .query_map(
+                        params![project_id.as_str(), token.as_str()],
+                        hydrate_queue_item,
+                    )?
+                    .next()
+                {
+                    queue_items.push(row?.1);
+                    continue;
+                }
+                let id = QueueItemId::new();
+                statement.execute(params![
+                    uuid_value(id.as_uuid()),
+                    keplr_wallet_pubkey.as_str(),
+                    starknet_wallet_pubkey.as_str(),
+                    project_id.as_str(),
+                    token.as_str(),
+                    self.worker_id,
+                    now_us(),
+                    request_id,
+                ])?;
+                let mut item = QueueItem::new(
+                    keplr_wallet_pubkey,
+                    starknet_wallet_pubkey,
+                    project_id,
+                    token.clone(),
+                );
+                item.id = Some(id);
+                item.request_id = request_id.map(String::from);
+                queue_items.push(item);
+            }
+        }
+        tx.commit()?;
+
+        Ok(queue_items)
+    }
+
+    /// Runs `sql` once per item in a single transaction, the item id bound to `?1` and
+    /// `values` to the next parameters. Returns the number of rows updated.
+    fn update_items(
+        &self,
+        ids: &[QueueItemId],
+        sql: &str,
+        values: &[&dyn ToSql],
+    ) -> rusqlite::Result<usize> {
+        let mut connection = self.database.lock();
+        let tx = connection.transaction()?;
+        let mut updated = 0;
+        {
+            let mut statement = tx.prepare(sql)?;
+            for id in ids {
+                let id = uuid_value(id.as_uuid());
+                let mut item_values: Vec<&dyn ToSql> = vec![&id];
+                item_values.extend_from_slice(values);
+                updated += statement.execute(item_values.as_slice())?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(updated)
+    }
+
+    fn update_with_note(
+        &self,
+        ids: &[QueueItemId],
+        sql: &str,
+        status: QueueStatus,
+        note: &StatusNote,
+    ) -> rusqlite::Result<usize> {
+        self.update_items(
+            ids,
+            sql,
+            &[
+                &status_value(&status),
+                &note.template,
+                &params_json(&note.params),
+                &self.worker_id,
+                &now_us(),
+            ],
+        )
+    }
+}
+
+#[async_trait]
+impl QueueManager for SqliteQueueManager {
+    async fn enqueue(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        starknet_wallet_pubkey: &StarknetAddress,
+        project_id: &StarknetAddress,
+        token_ids: Vec<TokenId>,
+        request_id: Option<&str>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        match self.insert_items(
+            keplr_wallet_pubkey,
+            starknet_wallet_pubkey,
+            project_id,
+            &token_ids,
+            request_id,
+        ) {
+            Ok(queue_items) => Ok(queue_items),
+            Err(e) => {
+                error!("Error enqueueing token {:#?} {:#?}", &token_ids, e);
+                Err(QueueError::FailedToEnqueue)
+            }
+        }
+    }
+
+    async fn get_batch(
+        &self,
+        excluded_projects: &[StarknetAddress],
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        let now_ms = now_us() / 1000;
+        let batch_size = self.batch_size as i64;
+        // Excluded projects are bound after the first two parameters
+        let placeholders: Vec<String> = (0..excluded_projects.len())
+            .map(|i| format!("?{}", i + 3))
+            .collect();
+        let excluded_projects: Vec<&str> = excluded_projects
+            .iter()
+            .map(StarknetAddress::as_str)
+            .collect();
+        let mut values: Vec<&dyn ToSql> = vec![&now_ms, &batch_size];
+        values.extend(excluded_projects.iter().map(|p| p as &dyn ToSql));
+        match self.query_items(
+            &format!("SELECT {} FROM migration_queue WHERE transaction_hash IS NULL AND (next_attempt_at IS NULL OR next_attempt_at <= ?1) AND project_id NOT IN ({}) AND NOT EXISTS (SELECT 1 FROM token_locks l WHERE l.project_id = migration_queue.project_id AND l.token_id = migration_queue.token_id AND ?1 < l.locked_until) ORDER BY created_at ASC LIMIT ?2", QUEUE_ITEM_COLUMNS, placeholders.join(", ")),
+            &values,
+        ) {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                error!("{}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn get_customer_migration_state(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+        project_id: &StarknetAddress,
+    ) -> Vec<QueueItem> {
+        match self.query_items(
+            &format!(
+                "SELECT {} FROM migration_queue WHERE keplr_wallet_pubkey = ?1 AND project_id = ?2",
+                QUEUE_ITEM_COLUMNS
+            ),
+            &[&keplr_wallet_pubkey.as_str(), &project_id.as_str()],
+        ) {
+            Ok(items) => items,
+            Err(err) => {
+                error!("Error while fetching customer migration state : {:#?}", err);
+                Vec::new()
+            }
+        }
+    }
+
+    async fn get_wallet_migrations(
+        &self,
+        keplr_wallet_pubkey: &JunoAddress,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        self.query_items(
+            &format!(
+                "SELECT {} FROM migration_queue WHERE keplr_wallet_pubkey = ?1 ORDER BY project_id ASC, created_at ASC, id ASC",
+                QUEUE_ITEM_COLUMNS
+            ),
+            &[&keplr_wallet_pubkey.as_str()],
+        )
+        .map_err(|err| {
+            error!("Error while fetching wallet migrations : {:#?}", err);
+            QueueError::FailedToGetBatch
+        })
+    }
+
+    async fn update_queue_items_status(
+        &self,
+        ids: &[QueueItemId],
+        transaction_hash: String,
+        status: QueueStatus,
+    ) -> Result<(), QueueUpdateError> {
+        match self.update_items(
+            ids,
+            "UPDATE migration_queue SET migration_status = ?2, transaction_hash = ?3, note = NULL, note_params = NULL, updated_by = ?4, updated_at = ?5 WHERE id = ?1",
+            &[&status_value(&status), &transaction_hash, &self.worker_id, &now_us()],
+        ) {
+            Ok(updated) if updated == ids.len() => Ok(()),
+            Ok(_) => Err(QueueUpdateError::StatusUpdateFail(ids.to_vec())),
+            Err(e) => {
+                error!("Failed to update queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn claim_queue_items(
+        &self,
+        ids: &[QueueItemId],
+    ) -> Result<Vec<QueueItemId>, QueueUpdateError> {
+        // Batches are not claimed in this schema, items still waiting are claimed here
+        let mut claimed = Vec::new();
+        for id in ids {
+            match self.update_items(
+                &[*id],
+                "UPDATE migration_queue SET migration_status = ?2, transaction_hash = '', note = NULL, note_params = NULL, updated_by = ?3, updated_at = ?4 WHERE id = ?1 AND (transaction_hash IS NULL OR (migration_status = ?2 AND updated_by = ?3))",
+                &[&status_value(&QueueStatus::Processing), &self.worker_id, &now_us()],
+            ) {
+                Ok(1) => claimed.push(*id),
+                Ok(_) => (),
+                Err(e) => {
+                    error!("Failed to claim queue items in database {:#?}", e);
+                    return Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()));
+                }
+            }
+        }
+
+        Ok(claimed)
+    }
+
+    async fn get_queue_item_history(
+        &self,
+        id: &QueueItemId,
+    ) -> Result<Vec<QueueItemTransition>, QueueError> {
+        let connection = self.database.lock();
+        let transitions = connection
+            .prepare("SELECT migration_status, transaction_hash, worker_id, created_at FROM migration_queue_history WHERE queue_item_id = ?1 ORDER BY created_at ASC, rowid ASC")
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![uuid_value(id.as_uuid())], |row| {
+                        Ok(QueueItemTransition {
+                            status: status_from_value(&row.get::<&str, String>("migration_status")?),
+                            transaction_hash: row.get("transaction_hash")?,
+                            worker_id: row.get("worker_id")?,
+                            created_at: row.get::<&str, i64>("created_at")? / 1000,
+                        })
+                    })?
+                    .collect::<Result<Vec<_>, _>>()
+            });
+
+        match transitions {
+            Ok(t) => Ok(t),
+            Err(e) => {
+                error!("Failed to fetch queue item history {:#?}", e);
+                Err(QueueError::NotFound)
+            }
+        }
+    }
+
+    async fn defer_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        match self.update_with_note(
+            ids,
+            "UPDATE migration_queue SET migration_status = ?2, transaction_hash = NULL, note = ?3, note_params = ?4, updated_by = ?5, updated_at = ?6 WHERE id = ?1",
+            QueueStatus::Pending,
+            note,
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to defer queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn get_processing_items(&self) -> Result<Vec<QueueItem>, QueueError> {
+        match self.query_items(
+            &format!(
+                "SELECT {} FROM migration_queue WHERE migration_status = ?1",
+                QUEUE_ITEM_COLUMNS
+            ),
+            &[&status_value(&QueueStatus::Processing)],
+        ) {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                error!("Failed to fetch processing queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn get_recoverable_items(
+        &self,
+        stale_before: Option<i64>,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        // Timestamps are epoch microseconds in this schema
+        match self.query_items(
+            &format!(
+                "SELECT {} FROM migration_queue WHERE migration_status = ?1 AND (updated_by = ?2 OR COALESCE(transaction_hash, '') <> '' OR updated_at < ?3)",
+                QUEUE_ITEM_COLUMNS
+            ),
+            &[
+                &status_value(&QueueStatus::Processing),
+                &self.worker_id,
+                &stale_before.map(|before| before * 1000),
+            ],
+        ) {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                error!("Failed to fetch recoverable queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn requeue_stale_processing_items(
+        &self,
+        before: i64,
+        note: &StatusNote,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        // Timestamps are epoch microseconds in this schema
+        match self.query_items(
+            &format!(
+                "UPDATE migration_queue SET migration_status = ?1, transaction_hash = NULL, note = ?2, note_params = ?3, updated_by = ?4, updated_at = ?5 WHERE migration_status = ?6 AND COALESCE(transaction_hash, '') = '' AND updated_at < ?7 RETURNING {}",
+                QUEUE_ITEM_COLUMNS
+            ),
+            &[
+                &status_value(&QueueStatus::Pending),
+                &note.template,
+                &params_json(&note.params),
+                &self.worker_id,
+                &now_us(),
+                &status_value(&QueueStatus::Processing),
+                &(before * 1000),
+            ],
+        ) {
+            Ok(items) => Ok(items),
+            Err(e) => {
+                error!("Failed to requeue stale processing items in database {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn count_pending_items(&self) -> Result<i64, QueueError> {
+        match self.database.lock().query_row(
+            "SELECT COUNT(*) FROM migration_queue WHERE migration_status = ?1",
+            params![status_value(&QueueStatus::Pending)],
+            |row| row.get::<usize, i64>(0),
+        ) {
+            Ok(count) => Ok(count),
+            Err(e) => {
+                error!("Failed to count pending queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn get_queue_item(&self, id: &QueueItemId) -> Result<QueueItem, QueueError> {
+        match self.query_items(
+            &format!(
+                "SELECT {} FROM migration_queue WHERE id = ?1",
+                QUEUE_ITEM_COLUMNS
+            ),
+            &[&uuid_value(id.as_uuid())],
+        ) {
+            Ok(mut items) => items.pop().ok_or(QueueError::NotFound),
+            Err(e) => {
+                error!("Failed to fetch queue item {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn cancel_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        // Empty hash keeps cancelled items out of get_batch
+        match self.update_with_note(
+            ids,
+            "UPDATE migration_queue SET migration_status = ?2, transaction_hash = COALESCE(transaction_hash, ''), note = ?3, note_params = ?4, updated_by = ?5, updated_at = ?6 WHERE id = ?1",
+            QueueStatus::Error,
+            note,
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to cancel queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn schedule_retry(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+        delay: Duration,
+    ) -> Result<(), QueueUpdateError> {
+        let next_attempt_at = now_us() / 1000 + delay.as_millis() as i64;
+        match self.update_items(
+            ids,
+            "UPDATE migration_queue SET migration_status = ?2, transaction_hash = NULL, note = ?3, note_params = ?4, attempts = attempts + 1, next_attempt_at = ?7, updated_by = ?5, updated_at = ?6 WHERE id = ?1",
+            &[
+                &status_value(&QueueStatus::Pending),
+                &note.template,
+                &params_json(&note.params),
+                &self.worker_id,
+                &now_us(),
+                &next_attempt_at,
+            ],
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to schedule queue items retry in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn dead_letter_queue_items(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        // Empty hash keeps dead letters out of get_batch, same as cancelled items
+        match self.update_with_note(
+            ids,
+            "UPDATE migration_queue SET migration_status = ?2, transaction_hash = COALESCE(transaction_hash, ''), note = ?3, note_params = ?4, attempts = attempts + 1, next_attempt_at = NULL, updated_by = ?5, updated_at = ?6 WHERE id = ?1",
+            QueueStatus::DeadLetter,
+            note,
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to dead letter queue items in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn requeue_dead_letters(
+        &self,
+        ids: &[QueueItemId],
+        note: &StatusNote,
+    ) -> Result<(), QueueUpdateError> {
+        let dead_letter = status_value(&QueueStatus::DeadLetter);
+        match self.update_items(
+            ids,
+            "UPDATE migration_queue SET migration_status = ?2, transaction_hash = NULL, note = ?3, note_params = ?4, attempts = 0, next_attempt_at = NULL, updated_by = ?5, updated_at = ?6 WHERE id = ?1 AND migration_status = ?7",
+            &[
+                &status_value(&QueueStatus::Pending),
+                &note.template,
+                &params_json(&note.params),
+                &self.worker_id,
+                &now_us(),
+                &dead_letter,
+            ],
+        ) {
+            Ok(updated) if updated == ids.len() => Ok(()),
+            Ok(_) => Err(QueueUpdateError::StatusUpdateFail(ids.to_vec())),
+            Err(e) => {
+                error!("Failed to requeue dead letters in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn list_queue_items(
+        &self,
+        status: Option<QueueStatus>,
+        page: &PageRequest,
+    ) -> Result<Page<QueueItem>, QueueError> {
+        let (after_created_at, after_id) = cursor_params(&page.after);
+        let status = status.as_ref().map(status_value);
+        let connection = self.database.lock();
+        let rows = connection
+            .prepare(&format!("SELECT {} FROM migration_queue WHERE (?1 IS NULL OR created_at > ?1 OR (created_at = ?1 AND id > ?2)) AND (?3 IS NULL OR migration_status = ?3) ORDER BY created_at ASC, id ASC LIMIT ?4", QUEUE_ITEM_COLUMNS))
+            .and_then(|mut statement| {
+                statement
+                    .query_map(
+                        params![after_created_at, after_id, status, page.limit + 1],
+                        hydrate_queue_item,
+                    )?
+                    .collect::<Result<Vec<_>, _>>()
+            });
+
+        match rows {
+            Ok(rows) => Ok(Page::from_rows(rows, page.limit)),
+            Err(e) => {
+                error!("Failed to list queue items {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn list_queue_events(&self, page: &PageRequest) -> Result<Page<QueueEvent>, QueueError> {
+        let (after_created_at, after_id) = cursor_params(&page.after);
+        let connection = self.database.lock();
+        let rows = connection
+            .prepare("SELECT id, queue_item_id, migration_status, transaction_hash, worker_id, created_at FROM migration_queue_history WHERE ?1 IS NULL OR created_at > ?1 OR (created_at = ?1 AND id > ?2) ORDER BY created_at ASC, id ASC LIMIT ?3")
+            .and_then(|mut statement| {
+                statement
+                    .query_map(params![after_created_at, after_id, page.limit + 1], |row| {
+                        let cursor = row_cursor(row)?;
+                        let event = QueueEvent {
+                            id: cursor.id,
+                            queue_item_id: QueueItemId::from(parse_uuid(
+                                &row.get::<&str, String>("queue_item_id")?,
+                            )?),
+                            status: status_from_value(&row.get::<&str, String>("migration_status")?),
+                            transaction_hash: row.get("transaction_hash")?,
+                            worker_id: row.get("worker_id")?,
+                            created_at: cursor.created_at / 1000,
+                        };
+                        Ok((cursor, event))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()
+            });
+
+        match rows {
+            Ok(rows) => Ok(Page::from_rows(rows, page.limit)),
+            Err(e) => {
+                error!("Failed to list queue events {:#?}", e);
+                Err(QueueError::FailedToGetBatch)
+            }
+        }
+    }
+
+    async fn record_mint_fees(
+        &self,
+        ids: &[QueueItemId],
+        estimated_fee: Option<&str>,
+        actual_fee: Option<&str>,
+    ) -> Result<(), QueueUpdateError> {
+        match self.update_items(
+            ids,
+            "UPDATE migration_queue SET estimated_fee = ?2, actual_fee = ?3 WHERE id = ?1",
+            &[&estimated_fee, &actual_fee],
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to record mint fees in database {:#?}", e);
+                Err(QueueUpdateError::StatusUpdateFail(ids.to_vec()))
+            }
+        }
+    }
+
+    async fn lock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Vec<TokenId>, QueueError> {
+        let now_ms = now_us() / 1000;
+        let locked_until = now_ms + ttl.as_millis() as i64;
+        let connection = self.database.lock();
+
+        let mut locked = Vec::new();
+        for token in token_ids {
+            match connection.execute(
+                "INSERT INTO token_locks (project_id, token_id, holder, locked_until) VALUES (?1, ?2, ?3, ?4) ON CONFLICT (project_id, token_id) DO UPDATE SET holder = excluded.holder, locked_until = excluded.locked_until WHERE token_locks.holder = excluded.holder OR token_locks.locked_until <= ?5",
+                params![project_id.as_str(), token.as_str(), holder, locked_until, now_ms],
+            ) {
+                Ok(0) => (),
+                Ok(_) => locked.push(token.clone()),
+                Err(e) => {
+                    error!("Failed to lock tokens in database {:#?}", e);
+                    return Err(QueueError::FailedToLockTokens);
+                }
+            }
+        }
+
+        Ok(locked)
+    }
+
+    async fn unlock_tokens(
+        &self,
+        project_id: &StarknetAddress,
+        token_ids: &[TokenId],
+        holder: &str,
+    ) -> Result<(), QueueError> {
+        let connection = self.database.lock();
+        for token in token_ids {
+            if let Err(e) = connection.execute(
+                "DELETE FROM token_locks WHERE project_id = ?1 AND token_id = ?2 AND holder = ?3",
+                params![project_id.as_str(), token.as_str(), holder],
+            ) {
+                error!("Failed to unlock tokens in database {:#?}", e);
+                return Err(QueueError::FailedToLockTokens);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Same values as the postgres enum, so dumps can be moved between both
+fn status_value(status: &QueueStatus) -> &'static str {
+    match status {
+        QueueStatus::Pending => "pending",
+        QueueStatus::Processing => "processing",
+        QueueStatus::Success => "success",
+        QueueStatus::Error => "error",
+        QueueStatus::DeadLetter => "dead_letter",
+    }
+}
+
+fn status_from_value(value: &str) -> QueueStatus {
+    match value {
+        "processing" => QueueStatus::Processing,
+        "success" => QueueStatus::Success,
+        "error" => QueueStatus::Error,
+        "dead_letter" => QueueStatus::DeadLetter,
+        _ => QueueStatus::Pending,
+    }
+}
+
+fn add_missing_queue_columns(connection: &Connection) -> rusqlite::Result<()> {
+    let columns = connection
+        .prepare("SELECT name FROM pragma_table_info('migration_queue')")?
+        .query_map([], |row| row.get::<usize, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (column, definition) in ADDED_QUEUE_COLUMNS {
+        if !columns.iter().any(|c| c == column) {
+            connection.execute_batch(&format!(
+                "ALTER TABLE migration_queue ADD COLUMN {} {};",
+                column, definition
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn hydrate_queue_item(row: &Row) -> rusqlite::Result<(Cursor, QueueItem)> {
+    let cursor = row_cursor(row)?;
+    let item = QueueItem {
+        id: Some(QueueItemId::from(cursor.id)),
+        keplr_wallet_pubkey: JunoAddress::unchecked(
+            row.get::<&str, String>("keplr_wallet_pubkey")?,
+        ),
+        starknet_wallet_pubkey: StarknetAddress::unchecked(
+            row.get::<&str, String>("starknet_wallet_pubkey")?,
+        ),
+        project_id: StarknetAddress::unchecked(row.get::<&str, String>("project_id")?),
+        token_id: TokenId::unchecked(row.get::<&str, String>("token_id")?),
+        transaction_hash: row.get("transaction_hash")?,
+        status: status_from_value(&row.get::<&str, String>("migration_status")?),
+        note: row.get("note")?,
+        note_params: row
+            .get::<&str, Option<String>>("note_params")?
+            .and_then(|p| serde_json::from_str(&p).ok())
+            .unwrap_or_default(),
+        attempts: row.get("attempts")?,
+        estimated_fee: row.get("estimated_fee")?,
+        actual_fee: row.get("actual_fee")?,
+        request_id: row.get("request_id")?,
+    };
+
+    Ok((cursor, item))
+}
+
+fn row_cursor(row: &Row) -> rusqlite::Result<Cursor> {
+    Ok(Cursor::new(
+        row.get("created_at")?,
+        parse_uuid(&row.get::<&str, String>("id")?)?,
+    ))
+}
+
+// Uuids are stored as 32 lowercase hex digits, the form the history triggers generate,
+// so text ordering of ids matches between queue items, events and cursors
+fn uuid_value(id: &Uuid) -> String {
+    id.simple().to_string()
+}
+
+fn parse_uuid(value: &str) -> rusqlite::Result<Uuid> {
+    Uuid::parse_str(value).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+    })
+}
+
+fn cursor_params(after: &Option<Cursor>) -> (Option<i64>, Option<String>) {
+    match after {
+        Some(c) => (Some(c.created_at), Some(uuid_value(&c.id))),
+        None => (None, None),
+    }
+}
+
+// Parameters are kept as a JSON object, NULL when the message has none
+fn params_json(params: &BTreeMap<String, String>) -> Option<String> {
+    match params.is_empty() {
+        true => None,
+        false => serde_json::to_string(params).ok(),
+    }
+}
+
+// Epoch microseconds, the precision of postgres timestamps used by cursors
+fn now_us() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as i64)
+        .unwrap_or_default()
+}
@@ -1,26 +1,380 @@
 use async_trait::async_trait;
-use log::{error, info};
+use log::{error, info, warn};
 use starknet::{
     accounts::{Account, AccountCall, Call, SingleOwnerAccount},
-    core::types::{AddTransactionResult, BlockId, CallFunction, FieldElement, TransactionStatus},
+    core::{
+        crypto::compute_hash_on_elements,
+        types::{BlockId, CallFunction, FieldElement, TransactionStatus},
+        utils::{cairo_short_string_to_felt, get_selector_from_name, starknet_keccak},
+    },
     macros::selector,
     providers::{Provider, SequencerGatewayProvider},
     signers::{LocalWallet, SigningKey},
 };
-use std::sync::Arc;
-use tokio::time::{sleep, Duration};
+use std::{collections::HashMap, sync::Arc};
+use tokio::{
+    sync::Mutex,
+    time::{sleep, Duration},
+};
+use tokio_util::sync::CancellationToken;
 
-use crate::domain::bridge::{MintError, QueueItem, QueueStatus, StarknetManager};
+use crate::domain::{
+    attestation::{AttestationError, AttestationSigner, MintAttestation},
+    bridge::{
+        joined_request_ids, MintError, MintStatusError, MintSubmission, QueueItem, StarknetManager,
+        TransactionOutcome,
+    },
+    consume_queue::REVERTED_CALL_REASON,
+    fee_strategy::{FeeError, FeeStrategy},
+    health::{HealthCheck, HealthCheckError},
+    ids::{StarknetAddress, TokenId},
+    project_registry::ProjectRegistry,
+    reverse_bridge::{StarknetTokenTransfer, StarknetTransferError, StarknetTransferVerifier},
+    warm_up::{WarmUp, WarmUpError},
+};
 
 const TRANSACTION_CHECK_WAIT_TIME: u64 = 5;
+// Revert message emitted by OpenZeppelin Pausable when contract is paused
+const PAUSED_REVERT_MESSAGE: &str = "Pausable: paused";
+
+// Entrypoint of contracts missing from the project registry
+const DEFAULT_MINT_SELECTOR: &str = "mint";
+// Matches historical mint(to, token_id: Uint256) entrypoint
+const DEFAULT_CALLDATA_TEMPLATE: &str = "to token_id:u256";
+
+#[derive(Debug)]
+pub enum CalldataTemplateError {
+    InvalidDefinition(String),
+    InvalidField(String),
+    InvalidValue(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CalldataEncoding {
+    Felt,
+    U256,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CalldataField {
+    To,
+    TokenId(CalldataEncoding),
+    Value(CalldataEncoding),
+    Constant(FieldElement),
+}
+
+/// Mint calldata layout, whitespace separated list of fields among `to`,
+/// `token_id:felt`, `token_id:u256`, `value:felt`, `value:u256` and constants
+/// (decimal or 0x prefixed hex). `value` is the minted amount, always one.
+#[derive(Debug, Clone)]
+pub struct CalldataTemplate {
+    fields: Vec<CalldataField>,
+}
+
+impl CalldataTemplate {
+    pub fn parse(template: &str) -> Result<Self, CalldataTemplateError> {
+        let mut fields = Vec::new();
+        for field in template.split_whitespace() {
+            let parsed = match field {
+                "to" => CalldataField::To,
+                "token_id" | "token_id:felt" => CalldataField::TokenId(CalldataEncoding::Felt),
+                "token_id:u256" => CalldataField::TokenId(CalldataEncoding::U256),
+                "value" | "value:felt" => CalldataField::Value(CalldataEncoding::Felt),
+                "value:u256" => CalldataField::Value(CalldataEncoding::U256),
+                constant => CalldataField::Constant(
+                    parse_felt(constant)
+                        .map_err(|_| CalldataTemplateError::InvalidField(constant.to_string()))?,
+                ),
+            };
+            fields.push(parsed);
+        }
+
+        Ok(Self { fields })
+    }
+
+    fn has_recipient(&self) -> bool {
+        self.fields.contains(&CalldataField::To)
+    }
+
+    pub fn encode(
+        &self,
+        to: FieldElement,
+        token_id: &str,
+        token_id_format: TokenIdFormat,
+    ) -> Result<Vec<FieldElement>, CalldataTemplateError> {
+        let token = token_id_format
+            .encode(token_id)
+            .map_err(|_| CalldataTemplateError::InvalidValue(token_id.to_string()))?;
+
+        let mut calldata = Vec::new();
+        for field in &self.fields {
+            match field {
+                CalldataField::To => calldata.push(to),
+                CalldataField::TokenId(encoding) => encode_value(&mut calldata, token, encoding)?,
+                CalldataField::Value(encoding) => {
+                    encode_value(&mut calldata, FieldElement::ONE, encoding)?
+                }
+                CalldataField::Constant(c) => calldata.push(*c),
+            };
+        }
+
+        Ok(calldata)
+    }
+}
+
+impl Default for CalldataTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_CALLDATA_TEMPLATE).unwrap()
+    }
+}
+
+fn parse_felt(value: &str) -> Result<FieldElement, CalldataTemplateError> {
+    let parsed = match value.starts_with("0x") {
+        true => FieldElement::from_hex_be(value).ok(),
+        false => FieldElement::from_dec_str(value).ok(),
+    };
+    parsed.ok_or_else(|| CalldataTemplateError::InvalidValue(value.to_string()))
+}
+
+fn encode_value(
+    calldata: &mut Vec<FieldElement>,
+    value: FieldElement,
+    encoding: &CalldataEncoding,
+) -> Result<(), CalldataTemplateError> {
+    match encoding {
+        CalldataEncoding::Felt => calldata.push(value),
+        CalldataEncoding::U256 => {
+            let bytes = value.to_bytes_be();
+            let (high, low) = bytes.split_at(16);
+            let invalid = |_| CalldataTemplateError::InvalidValue(value.to_string());
+            calldata.push(FieldElement::from_byte_slice_be(low).map_err(invalid)?);
+            calldata.push(FieldElement::from_byte_slice_be(high).map_err(invalid)?);
+        }
+    };
 
-struct TransactionRejected(Option<String>);
+    Ok(())
+}
+
+/// Per project calldata templates, projects without explicit template use the default one.
+#[derive(Debug, Clone, Default)]
+pub struct CalldataTemplates {
+    default: CalldataTemplate,
+    projects: Vec<(FieldElement, CalldataTemplate)>,
+}
+
+impl CalldataTemplates {
+    /// Parses definitions formatted as `project_address=template`.
+    pub fn parse(definitions: &[String]) -> Result<Self, CalldataTemplateError> {
+        let mut projects = Vec::new();
+        for definition in definitions {
+            let Some((project, template)) = definition.split_once('=') else {
+                return Err(CalldataTemplateError::InvalidDefinition(
+                    definition.to_string(),
+                ));
+            };
+            let project = FieldElement::from_hex_be(project.trim())
+                .map_err(|_| CalldataTemplateError::InvalidDefinition(definition.to_string()))?;
+            let template = CalldataTemplate::parse(template)?;
+            if !template.has_recipient() {
+                return Err(CalldataTemplateError::InvalidDefinition(format!(
+                    "Mint template has to contain recipient : {}",
+                    definition
+                )));
+            }
+            projects.push((project, template));
+        }
+
+        Ok(Self {
+            default: CalldataTemplate::default(),
+            projects,
+        })
+    }
+
+    pub fn for_project(&self, project_id: &str) -> &CalldataTemplate {
+        let Ok(project) = FieldElement::from_hex_be(project_id) else {
+            return &self.default;
+        };
+        self.projects
+            .iter()
+            .find(|(p, _)| *p == project)
+            .map_or(&self.default, |(_, t)| t)
+    }
+}
+
+#[derive(Debug)]
+pub enum TokenIdFormatError {
+    InvalidDefinition(String),
+    InvalidTokenId(String),
+}
+
+/// How token ids of a collection are turned into the felt minted on Starknet. Queue
+/// items keep the Juno token id, it is only encoded when calling contracts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenIdFormat {
+    Decimal,
+    Hex,
+    // Starknet keccak of the id, for collections naming their tokens
+    HashedString,
+}
+
+impl TokenIdFormat {
+    pub fn parse(format: &str) -> Option<Self> {
+        match format.trim() {
+            "decimal" => Some(Self::Decimal),
+            "hex" => Some(Self::Hex),
+            "string" => Some(Self::HashedString),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, token_id: &str) -> Result<FieldElement, TokenIdFormatError> {
+        let invalid = || TokenIdFormatError::InvalidTokenId(token_id.to_string());
+        match self {
+            Self::Decimal => FieldElement::from_dec_str(token_id).map_err(|_| invalid()),
+            Self::Hex => FieldElement::from_hex_be(token_id).map_err(|_| invalid()),
+            Self::HashedString => Ok(starknet_keccak(token_id.as_bytes())),
+        }
+    }
+
+    /// Token id a felt was encoded from, hashed ids cannot be recovered. Hex ids come
+    /// back lowercase without leading zeros.
+    pub fn decode(&self, token: FieldElement) -> Option<String> {
+        match self {
+            Self::Decimal => Some(token.to_string()),
+            Self::Hex => Some(format!("{:#x}", token)),
+            Self::HashedString => None,
+        }
+    }
+}
+
+/// Per project token id formats, projects without explicit format use decimal ids.
+#[derive(Debug, Clone, Default)]
+pub struct TokenIdFormats {
+    projects: Vec<(FieldElement, TokenIdFormat)>,
+}
+
+impl TokenIdFormats {
+    /// Parses definitions formatted as `project_address=decimal|hex|string`.
+    pub fn parse(definitions: &[String]) -> Result<Self, TokenIdFormatError> {
+        let mut projects = Vec::new();
+        for definition in definitions {
+            let invalid = || TokenIdFormatError::InvalidDefinition(definition.to_string());
+            let Some((project, format)) = definition.split_once('=') else {
+                return Err(invalid());
+            };
+            let project = FieldElement::from_hex_be(project.trim()).map_err(|_| invalid())?;
+            let format = TokenIdFormat::parse(format).ok_or_else(invalid)?;
+            projects.push((project, format));
+        }
+
+        Ok(Self { projects })
+    }
+
+    pub fn for_project(&self, project_id: &str) -> TokenIdFormat {
+        let Ok(project) = FieldElement::from_hex_be(project_id) else {
+            return TokenIdFormat::Decimal;
+        };
+        self.projects
+            .iter()
+            .find(|(p, _)| *p == project)
+            .map_or(TokenIdFormat::Decimal, |(_, f)| *f)
+    }
+}
+
+#[derive(Debug)]
+pub enum NonceError {
+    FetchFailed(String),
+}
+
+#[async_trait]
+pub trait NonceSource: Send + Sync {
+    async fn fetch_nonce(&self) -> Result<FieldElement, NonceError>;
+}
+
+/// Reads the admin account nonce from the pending block, so transactions not yet
+/// accepted on L2 are accounted for.
+pub struct AccountNonceSource {
+    provider: Arc<SequencerGatewayProvider>,
+    account_address: FieldElement,
+}
+
+impl AccountNonceSource {
+    pub fn new(provider: Arc<SequencerGatewayProvider>, account_address: FieldElement) -> Self {
+        Self {
+            provider,
+            account_address,
+        }
+    }
+}
+
+#[async_trait]
+impl NonceSource for AccountNonceSource {
+    async fn fetch_nonce(&self) -> Result<FieldElement, NonceError> {
+        self.provider
+            .get_nonce(self.account_address, BlockId::Pending)
+            .await
+            .map_err(|e| NonceError::FetchFailed(e.to_string()))
+    }
+}
+
+/// Hands out sequential nonces for the admin account so several mint batches can be
+/// submitted without waiting for the previous one. The account nonce is fetched once
+/// and fetched again after a rejection, as the local counter may then be ahead of chain.
+pub struct NonceManager {
+    source: Arc<dyn NonceSource>,
+    next: Mutex<Option<FieldElement>>,
+}
+
+impl NonceManager {
+    pub fn new(source: Arc<dyn NonceSource>) -> Self {
+        Self {
+            source,
+            next: Mutex::new(None),
+        }
+    }
+
+    pub async fn next_nonce(&self) -> Result<FieldElement, NonceError> {
+        let mut next = self.next.lock().await;
+        let nonce = match *next {
+            Some(n) => n,
+            None => self.source.fetch_nonce().await?,
+        };
+        *next = Some(nonce + FieldElement::ONE);
+
+        Ok(nonce)
+    }
+
+    pub async fn resync(&self) {
+        warn!("Resynchronizing starknet account nonce");
+        *self.next.lock().await = None;
+    }
+}
+
+// Estimation and submission errors only carry the sequencer message, a reverted call
+// refuses the whole batch before anything is sent
+fn batch_mint_error(message: &str) -> MintError {
+    if message.contains(PAUSED_REVERT_MESSAGE) {
+        return MintError::ContractPaused;
+    }
+    if message.contains(REVERTED_CALL_REASON) {
+        return MintError::Reverted;
+    }
+    MintError::Failure
+}
 
 pub struct OnChainStartknetManager {
     provider: Arc<SequencerGatewayProvider>,
     account_address: String,
     account_private_key: String,
     chain_id: FieldElement,
+    calldata_templates: Arc<CalldataTemplates>,
+    fee_strategy: Arc<dyn FeeStrategy>,
+    token_id_formats: Arc<TokenIdFormats>,
+    project_registry: Arc<ProjectRegistry>,
+    nonces: NonceManager,
+    max_calls_per_transaction: usize,
+    // Project and mint calls of sent transactions, until their fee is read
+    submissions: std::sync::Mutex<HashMap<String, (StarknetAddress, usize)>>,
 }
 
 impl OnChainStartknetManager {
@@ -29,89 +383,276 @@ impl OnChainStartknetManager {
         account_addr: &str,
         account_pk: &str,
         chain_id: FieldElement,
+        calldata_templates: Arc<CalldataTemplates>,
+        fee_strategy: Arc<dyn FeeStrategy>,
+        token_id_formats: Arc<TokenIdFormats>,
+        project_registry: Arc<ProjectRegistry>,
+        max_calls_per_transaction: usize,
     ) -> Self {
+        let nonce_source = AccountNonceSource::new(
+            provider.clone(),
+            FieldElement::from_hex_be(account_addr).unwrap(),
+        );
         Self {
             provider,
+            nonces: NonceManager::new(Arc::new(nonce_source)),
             account_address: account_addr.to_string(),
             account_private_key: account_pk.to_string(),
             chain_id,
+            calldata_templates,
+            fee_strategy,
+            token_id_formats,
+            project_registry,
+            max_calls_per_transaction,
+            submissions: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
-    async fn check_transaction_status(
+    fn mint_selector(&self, project_id: &StarknetAddress) -> Result<FieldElement, MintError> {
+        let name = self
+            .project_registry
+            .find_by_starknet_contract(project_id)
+            .map_or(DEFAULT_MINT_SELECTOR, |p| p.mint_selector.as_str());
+        get_selector_from_name(name).map_err(|e| {
+            error!("Invalid mint selector {} : {}", name, e.to_string());
+            MintError::Failure
+        })
+    }
+
+    /// Max fee the fee strategy pays for a transaction of `calls` mint calls.
+    async fn max_fee(
         &self,
-        tx_result: &AddTransactionResult,
-    ) -> Result<(), TransactionRejected> {
+        project_id: &StarknetAddress,
+        estimated_fee: u64,
+        calls: usize,
+    ) -> Result<FieldElement, MintError> {
+        let max_fee = match self
+            .fee_strategy
+            .max_fee(project_id, u128::from(estimated_fee), calls)
+        {
+            Ok(f) => f,
+            Err(e) => {
+                // Nothing is sent, the nonce handed out is still free
+                self.nonces.resync().await;
+                return Err(match e {
+                    FeeError::AboveCap { .. } => MintError::FeeAboveCap,
+                    FeeError::Overflow => {
+                        error!("Invalid max fee for estimate {}", estimated_fee);
+                        MintError::Failure
+                    }
+                });
+            }
+        };
         info!(
-            "Checking transaction status : {}",
-            hex::encode(tx_result.transaction_hash.to_bytes_be())
+            "Mint fee on project {} estimated to {}, max fee {}",
+            project_id, estimated_fee, max_fee
         );
-        let provider = self.provider.clone();
-        loop {
-            let tx_status_info = &provider
-                .get_transaction_status(
-                    FieldElement::from_dec_str(&tx_result.transaction_hash.to_string()).unwrap(),
-                )
-                .await;
-
-            if tx_status_info.is_err() {
-                sleep(Duration::from_secs(TRANSACTION_CHECK_WAIT_TIME)).await;
-                continue;
-            }
 
-            let tx = tx_status_info.as_ref().unwrap();
-            if TransactionStatus::Rejected == tx.status {
-                return match &tx.transaction_failure_reason {
-                    Some(fr) => Err(TransactionRejected(Some(fr.code.to_string()))),
-                    None => Err(TransactionRejected(None)),
-                };
-            }
-            if TransactionStatus::AcceptedOnL2 == tx.status
-                || TransactionStatus::AcceptedOnL1 == tx.status
-            {
-                info!(
-                    "Transaction with hash {}, has status : {:#?}",
-                    hex::encode(tx_result.transaction_hash.to_bytes_be()),
-                    tx.status
-                );
-                return Ok(());
-            }
+        FieldElement::from_dec_str(&max_fee.to_string()).map_err(|_| MintError::Failure)
+    }
 
-            sleep(Duration::from_secs(TRANSACTION_CHECK_WAIT_TIME)).await;
-        }
+    fn record_submission(
+        &self,
+        transaction_hash: FieldElement,
+        project_id: &StarknetAddress,
+        calls: usize,
+    ) {
+        self.submissions.lock().unwrap().insert(
+            format!("0x{}", hex::encode(transaction_hash.to_bytes_be())),
+            (project_id.clone(), calls),
+        );
     }
 }
 
 #[async_trait]
 impl StarknetManager for OnChainStartknetManager {
-    async fn project_has_token(&self, project_id: &str, token_id: &str) -> bool {
+    async fn project_has_token(
+        &self,
+        project_id: &StarknetAddress,
+        token_id: &TokenId,
+    ) -> Result<bool, MintStatusError> {
         let provider = self.provider.clone();
         info!(
             "Checking if project {} has token id {} minted",
             project_id, token_id
         );
+        let token = match self
+            .token_id_formats
+            .for_project(project_id.as_str())
+            .encode(token_id.as_str())
+        {
+            Ok(t) => t,
+            Err(e) => {
+                error!("Invalid token id of project {} : {:#?}", project_id, e);
+                return Ok(false);
+            }
+        };
+        // ownerOf takes a Uint256, hashed ids do not fit in its low part
+        let mut calldata = Vec::new();
+        if let Err(e) = encode_value(&mut calldata, token, &CalldataEncoding::U256) {
+            error!("Invalid token id of project {} : {:#?}", project_id, e);
+            return Ok(false);
+        }
         let res = provider
             .call_contract(
                 CallFunction {
-                    contract_address: FieldElement::from_hex_be(project_id).unwrap(),
+                    contract_address: FieldElement::from_hex_be(project_id.as_str()).unwrap(),
                     entry_point_selector: selector!("ownerOf"),
-                    calldata: vec![
-                        FieldElement::from_dec_str(token_id).unwrap(),
-                        FieldElement::ZERO,
-                    ],
+                    calldata,
                 },
                 BlockId::Latest,
             )
             .await;
+        if res.is_ok() {
+            return Ok(true);
+        }
 
-        res.is_ok()
+        // ownerOf reverts on tokens not minted, which cannot be told apart from an
+        // unreachable gateway without asking it something that always answers
+        match self.read_account_nonce().await {
+            Ok(_) => Ok(false),
+            Err(e) => Err(MintStatusError::Unavailable(e)),
+        }
+    }
+
+    async fn project_is_paused(&self, project_id: &StarknetAddress) -> bool {
+        let provider = self.provider.clone();
+        let res = provider
+            .call_contract(
+                CallFunction {
+                    contract_address: FieldElement::from_hex_be(project_id.as_str()).unwrap(),
+                    entry_point_selector: selector!("paused"),
+                    calldata: vec![],
+                },
+                BlockId::Latest,
+            )
+            .await;
+
+        // Contracts not exposing paused() cannot be paused from here, revert is
+        // detected while minting instead.
+        match res {
+            Ok(r) => r.result.first().map_or(false, |p| FieldElement::ZERO != *p),
+            Err(_e) => false,
+        }
+    }
+
+    async fn get_transaction_status(&self, transaction_hash: &str) -> Option<String> {
+        let hash = FieldElement::from_hex_be(transaction_hash).ok()?;
+        match self.provider.get_transaction_status(hash).await {
+            Ok(tx) => Some(format!("{:?}", tx.status)),
+            Err(e) => {
+                error!(
+                    "Failed to fetch transaction {} status : {}",
+                    transaction_hash,
+                    e.to_string()
+                );
+                None
+            }
+        }
+    }
+
+    async fn get_transaction_outcome(&self, transaction_hash: &str) -> TransactionOutcome {
+        let Ok(hash) = FieldElement::from_hex_be(transaction_hash) else {
+            return TransactionOutcome::NotReceived;
+        };
+        let tx = match self.provider.get_transaction_status(hash).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                error!(
+                    "Failed to fetch transaction {} status : {}",
+                    transaction_hash,
+                    e.to_string()
+                );
+                return TransactionOutcome::Pending;
+            }
+        };
+
+        match tx.status {
+            TransactionStatus::AcceptedOnL2 | TransactionStatus::AcceptedOnL1 => {
+                TransactionOutcome::Accepted
+            }
+            TransactionStatus::Rejected => {
+                self.nonces.resync().await;
+                TransactionOutcome::Rejected(
+                    tx.transaction_failure_reason.map(|fr| fr.code.to_string()),
+                )
+            }
+            TransactionStatus::NotReceived => TransactionOutcome::NotReceived,
+            _ => TransactionOutcome::Pending,
+        }
+    }
+
+    async fn get_transaction_fee(&self, transaction_hash: &str) -> Option<String> {
+        let hash = FieldElement::from_hex_be(transaction_hash).ok()?;
+        let submission = self.submissions.lock().unwrap().remove(transaction_hash);
+        match self.provider.get_transaction_receipt(hash).await {
+            Ok(receipt) => {
+                let fee = receipt.actual_fee.map(|fee| fee.to_string());
+                // Fee strategies follow what accepted transactions paid
+                let accepted = matches!(
+                    receipt.status,
+                    TransactionStatus::AcceptedOnL2 | TransactionStatus::AcceptedOnL1
+                );
+                if let (true, Some((project_id, calls)), Some(actual_fee)) = (
+                    accepted,
+                    submission,
+                    fee.as_deref().and_then(|f| f.parse::<u128>().ok()),
+                ) {
+                    self.fee_strategy
+                        .record_accepted_fee(&project_id, actual_fee, calls);
+                }
+                fee
+            }
+            Err(e) => {
+                error!(
+                    "Failed to fetch transaction {} receipt : {}",
+                    transaction_hash,
+                    e.to_string()
+                );
+                None
+            }
+        }
+    }
+
+    async fn wait_for_transaction(
+        &self,
+        transaction_hash: &str,
+        cancel: &CancellationToken,
+    ) -> TransactionOutcome {
+        info!("Checking transaction status : {}", transaction_hash);
+        loop {
+            let outcome = tokio::select! {
+                _ = cancel.cancelled() => return TransactionOutcome::Pending,
+                outcome = self.get_transaction_outcome(transaction_hash) => outcome,
+            };
+            match outcome {
+                TransactionOutcome::Accepted | TransactionOutcome::Rejected(_) => {
+                    info!(
+                        "Transaction with hash {}, has status : {:#?}",
+                        transaction_hash, outcome
+                    );
+                    return outcome;
+                }
+                _ => tokio::select! {
+                    _ = cancel.cancelled() => {
+                        info!("Stopped waiting for transaction {}", transaction_hash);
+                        return TransactionOutcome::Pending;
+                    }
+                    _ = sleep(Duration::from_secs(TRANSACTION_CHECK_WAIT_TIME)) => {}
+                },
+            };
+        }
+    }
+
+    fn max_calls_per_transaction(&self) -> usize {
+        self.max_calls_per_transaction
     }
 
     async fn mint_project_token(
         &self,
-        project_id: &str,
-        tokens: &[String],
-        starknet_account_addr: &str,
+        project_id: &StarknetAddress,
+        tokens: &[TokenId],
+        starknet_account_addr: &StarknetAddress,
     ) -> Result<String, MintError> {
         info!(
             "Trying to mint tokens {:#?} on project {}",
@@ -123,31 +664,55 @@ impl StarknetManager for OnChainStartknetManager {
         ));
 
         let address = FieldElement::from_hex_be(self.account_address.as_str()).unwrap();
-        let to = FieldElement::from_hex_be(starknet_account_addr).unwrap();
+        let to = FieldElement::from_hex_be(starknet_account_addr.as_str()).unwrap();
 
         let account = SingleOwnerAccount::new(provider, signer, address, self.chain_id);
+        let template = self.calldata_templates.for_project(project_id.as_str());
+        let token_id_format = self.token_id_formats.for_project(project_id.as_str());
+        let selector = self.mint_selector(project_id)?;
         let mut calls = Vec::new();
         for t in tokens {
+            let calldata = match template.encode(to, t.as_str(), token_id_format) {
+                Ok(c) => c,
+                Err(e) => {
+                    error!("Failed to encode mint calldata for token {} : {:#?}", t, e);
+                    return Err(MintError::Failure);
+                }
+            };
             calls.push(Call {
-                to: FieldElement::from_hex_be(project_id).unwrap(),
-                selector: selector!("mint"),
-                calldata: vec![
-                    to,
-                    FieldElement::from_dec_str(t).unwrap(),
-                    FieldElement::ZERO,
-                ],
+                to: FieldElement::from_hex_be(project_id.as_str()).unwrap(),
+                selector,
+                calldata,
             })
         }
 
-        let account_attached_call = account.execute(&calls.as_slice());
+        let nonce = match self.nonces.next_nonce().await {
+            Ok(n) => n,
+            Err(e) => {
+                error!("Failed to fetch starknet account nonce : {:#?}", e);
+                return Err(MintError::Failure);
+            }
+        };
+        let account_attached_call = account.execute(&calls.as_slice()).nonce(nonce);
 
-        // This value is set only to allow transactions during spike time
-        let account_attached_call = account_attached_call.fee_estimate_multiplier(10.0);
+        let estimated_fee = match account_attached_call.estimate_fee().await {
+            Ok(estimate) => estimate.overall_fee,
+            Err(e) => {
+                self.nonces.resync().await;
+                error!("Failed to estimate mint fee -> {}", e.to_string());
+                if e.to_string().contains(PAUSED_REVERT_MESSAGE) {
+                    return Err(MintError::ContractPaused);
+                }
+                return Err(MintError::Failure);
+            }
+        };
+        let max_fee = self.max_fee(project_id, estimated_fee, calls.len()).await?;
 
-        let res = account_attached_call.send().await;
+        let res = account_attached_call.max_fee(max_fee).send().await;
 
         match res {
             Ok(tx) => {
+                self.record_submission(tx.transaction_hash, project_id, calls.len());
                 info!(
                     "Token id {:#?} minting in progress -> #{}",
                     tokens,
@@ -160,6 +725,7 @@ impl StarknetManager for OnChainStartknetManager {
                 ))
             }
             Err(e) => {
+                self.nonces.resync().await;
                 error!(
                     "Error while minting token id {:#?} -> {}",
                     tokens,
@@ -171,9 +737,9 @@ impl StarknetManager for OnChainStartknetManager {
     }
     async fn batch_mint_tokens(
         &self,
-        project_id: &str,
+        project_id: &StarknetAddress,
         queue_items: Vec<QueueItem>,
-    ) -> Result<(String, QueueStatus), MintError> {
+    ) -> Result<MintSubmission, MintError> {
         let provider = self.provider.clone();
         let signer = LocalWallet::from(SigningKey::from_secret_scalar(
             FieldElement::from_hex_be(self.account_private_key.as_str()).unwrap(),
@@ -182,44 +748,247 @@ impl StarknetManager for OnChainStartknetManager {
         let address = FieldElement::from_hex_be(self.account_address.as_str()).unwrap();
 
         let account = SingleOwnerAccount::new(provider, signer, address, self.chain_id);
+        let template = self.calldata_templates.for_project(project_id.as_str());
+        let token_id_format = self.token_id_formats.for_project(project_id.as_str());
+        let selector = self.mint_selector(project_id)?;
+        let request_ids = joined_request_ids(&queue_items);
         let mut calls = Vec::new();
         for qi in queue_items {
             let to = FieldElement::from_hex_be(qi.starknet_wallet_pubkey.as_str()).unwrap();
+            let calldata = match template.encode(to, qi.token_id.as_str(), token_id_format) {
+                Ok(c) => c,
+                Err(e) => {
+                    error!(
+                        "Failed to encode mint calldata for token {} : {:#?}",
+                        qi.token_id, e
+                    );
+                    return Err(MintError::Failure);
+                }
+            };
             calls.push(Call {
-                to: FieldElement::from_hex_be(project_id).unwrap(),
-                selector: selector!("mint"),
-                calldata: vec![
-                    to,
-                    FieldElement::from_dec_str(qi.token_id.as_str()).unwrap(),
-                    FieldElement::ZERO,
-                ],
+                to: FieldElement::from_hex_be(project_id.as_str()).unwrap(),
+                selector,
+                calldata,
             })
         }
 
-        let account_attached_call = account.execute(&calls.as_slice());
+        let nonce = match self.nonces.next_nonce().await {
+            Ok(n) => n,
+            Err(e) => {
+                error!("Failed to fetch starknet account nonce : {:#?}", e);
+                return Err(MintError::Failure);
+            }
+        };
+        let account_attached_call = account.execute(&calls.as_slice()).nonce(nonce);
 
-        // This value is set only to allow transactions during spike time
-        let account_attached_call = account_attached_call.fee_estimate_multiplier(10.0);
+        let estimated_fee = match account_attached_call.estimate_fee().await {
+            Ok(estimate) => estimate.overall_fee,
+            Err(e) => {
+                self.nonces.resync().await;
+                error!("Failed to estimate mint fee -> {}", e.to_string());
+                return Err(batch_mint_error(&e.to_string()));
+            }
+        };
+        let max_fee = self.max_fee(project_id, estimated_fee, calls.len()).await?;
 
-        let res = account_attached_call.send().await;
+        let res = account_attached_call.max_fee(max_fee).send().await;
 
         match res {
             Ok(tx) => {
+                self.record_submission(tx.transaction_hash, project_id, calls.len());
                 info!(
-                    "Batch transaction in progress -> #{}",
-                    hex::encode(tx.transaction_hash.to_bytes_be())
+                    "Batch transaction in progress -> #{} for requests {}",
+                    hex::encode(tx.transaction_hash.to_bytes_be()),
+                    request_ids.as_deref().unwrap_or("unknown")
                 );
 
-                let tx_hash = format!("0x{}", hex::encode(tx.transaction_hash.to_bytes_be()));
-                return match self.check_transaction_status(&tx).await {
-                    Err(_e) => Ok((tx_hash, QueueStatus::Error)),
-                    Ok(_) => Ok((tx_hash, QueueStatus::Success)),
-                };
+                Ok(MintSubmission {
+                    transaction_hash: format!(
+                        "0x{}",
+                        hex::encode(tx.transaction_hash.to_bytes_be())
+                    ),
+                    estimated_fee: Some(estimated_fee.to_string()),
+                })
             }
             Err(e) => {
+                self.nonces.resync().await;
                 error!("Error while batching transaction -> {}", e.to_string());
-                Err(MintError::Failure)
+                Err(batch_mint_error(&e.to_string()))
             }
         }
     }
 }
+
+#[async_trait]
+impl WarmUp for OnChainStartknetManager {
+    fn name(&self) -> &str {
+        "starknet gateway"
+    }
+
+    async fn warm_up(&self) -> Result<(), WarmUpError> {
+        self.read_account_nonce().await.map_err(WarmUpError::Failed)
+    }
+}
+
+#[async_trait]
+impl HealthCheck for OnChainStartknetManager {
+    fn name(&self) -> &str {
+        "starknet gateway"
+    }
+
+    async fn check(&self) -> Result<(), HealthCheckError> {
+        self.read_account_nonce()
+            .await
+            .map_err(HealthCheckError::Failed)
+    }
+}
+
+impl OnChainStartknetManager {
+    // The sequencer gateway does not expose the chain id, reading the admin account
+    // nonce is the cheapest call reaching it and fails as well on a wrong account
+    async fn read_account_nonce(&self) -> Result<(), String> {
+        let address =
+            FieldElement::from_hex_be(&self.account_address).map_err(|e| e.to_string())?;
+        self.provider
+            .get_nonce(address, BlockId::Latest)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(())
+    }
+}
+
+/// Reads ERC721 `Transfer(from, to, token_id: Uint256)` events from transaction receipts.
+pub struct OnChainTransferVerifier {
+    provider: Arc<SequencerGatewayProvider>,
+    token_id_formats: Arc<TokenIdFormats>,
+}
+
+impl OnChainTransferVerifier {
+    pub fn new(
+        provider: Arc<SequencerGatewayProvider>,
+        token_id_formats: Arc<TokenIdFormats>,
+    ) -> Self {
+        Self {
+            provider,
+            token_id_formats,
+        }
+    }
+}
+
+#[async_trait]
+impl StarknetTransferVerifier for OnChainTransferVerifier {
+    async fn get_token_transfers(
+        &self,
+        transaction_hash: &str,
+    ) -> Result<Vec<StarknetTokenTransfer>, StarknetTransferError> {
+        let Ok(hash) = FieldElement::from_hex_be(transaction_hash) else {
+            return Err(StarknetTransferError::NotAccepted);
+        };
+        let receipt = match self.provider.get_transaction_receipt(hash).await {
+            Ok(r) => r,
+            Err(e) => {
+                error!(
+                    "Failed to fetch transaction {} receipt : {}",
+                    transaction_hash,
+                    e.to_string()
+                );
+                return Err(StarknetTransferError::FetchFailed);
+            }
+        };
+        match receipt.status {
+            TransactionStatus::AcceptedOnL2 | TransactionStatus::AcceptedOnL1 => (),
+            _ => return Err(StarknetTransferError::NotAccepted),
+        };
+
+        Ok(receipt
+            .events
+            .iter()
+            .filter(|e| e.keys.first() == Some(&selector!("Transfer")) && 4 <= e.data.len())
+            // Token ids above 2^128 are never minted by the bridge
+            .filter(|e| FieldElement::ZERO == e.data[3])
+            .map(|e| {
+                let contract = format!("{:#x}", e.from_address);
+                // Hashed ids cannot be decoded, they never match a requested token
+                let token_id = self
+                    .token_id_formats
+                    .for_project(&contract)
+                    .decode(e.data[2])
+                    .unwrap_or_else(|| e.data[2].to_string());
+                StarknetTokenTransfer {
+                    contract: StarknetAddress::unchecked(contract),
+                    from: StarknetAddress::unchecked(format!("{:#x}", e.data[0])),
+                    to: StarknetAddress::unchecked(format!("{:#x}", e.data[1])),
+                    token_id: TokenId::unchecked(token_id),
+                }
+            })
+            .collect())
+    }
+}
+
+// Domain tag hashed first, so attestations cannot be replayed as other signed messages
+const ATTESTATION_DOMAIN: &str = "bridge.mint_attestation";
+
+/// Signs mint attestations on the Stark curve, verifiable on Starknet with
+/// `check_ecdsa_signature(message_hash, public_key, r, s)`.
+pub struct StarkAttestationSigner {
+    signing_key: SigningKey,
+    chain_id: FieldElement,
+    token_id_formats: Arc<TokenIdFormats>,
+}
+
+impl StarkAttestationSigner {
+    pub fn new(
+        private_key: &str,
+        chain_id: FieldElement,
+        token_id_formats: Arc<TokenIdFormats>,
+    ) -> Self {
+        let Ok(secret) = FieldElement::from_hex_be(private_key.trim()) else {
+            panic!("Attestation private key is not a felt");
+        };
+        Self {
+            signing_key: SigningKey::from_secret_scalar(secret),
+            chain_id,
+            token_id_formats,
+        }
+    }
+
+    pub fn message_hash(&self, item: &QueueItem) -> Result<FieldElement, AttestationError> {
+        let invalid = |value: &str| AttestationError::InvalidValue(value.to_string());
+        let hex = |value: &str| FieldElement::from_hex_be(value).map_err(|_| invalid(value));
+        let transaction_hash = item
+            .transaction_hash
+            .as_deref()
+            .ok_or_else(|| invalid(""))?;
+
+        Ok(compute_hash_on_elements(&[
+            cairo_short_string_to_felt(ATTESTATION_DOMAIN)
+                .map_err(|_| invalid(ATTESTATION_DOMAIN))?,
+            self.chain_id,
+            hex(item.project_id.as_str())?,
+            hex(item.starknet_wallet_pubkey.as_str())?,
+            self.token_id_formats
+                .for_project(item.project_id.as_str())
+                .encode(item.token_id.as_str())
+                .map_err(|_| invalid(item.token_id.as_str()))?,
+            hex(transaction_hash)?,
+        ]))
+    }
+}
+
+impl AttestationSigner for StarkAttestationSigner {
+    fn attest(&self, item: &QueueItem) -> Result<MintAttestation, AttestationError> {
+        let message_hash = self.message_hash(item)?;
+        let signature = self.signing_key.sign(&message_hash).map_err(|e| {
+            error!("Failed to sign attestation {:#?}", e);
+            AttestationError::FailedToSign
+        })?;
+
+        Ok(MintAttestation {
+            message_hash: format!("{:#x}", message_hash),
+            signature_r: format!("{:#x}", signature.r),
+            signature_s: format!("{:#x}", signature.s),
+            public_key: format!("{:#x}", self.signing_key.verifying_key().scalar()),
+        })
+    }
+}
@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use sha2::Sha256;
+
+use crate::domain::webhook::{WebhookDelivery, WebhookSendError, WebhookSender};
+
+pub const SIGNATURE_HEADER: &str = "X-Bridge-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Bridge-Timestamp";
+pub const DELIVERY_HEADER: &str = "X-Bridge-Delivery";
+
+/// Signature subscribers recompute to authenticate a payload, hex encoded
+/// HMAC-SHA256 of `{timestamp}.{body}`.
+pub fn webhook_signature(secret: &str, timestamp: i64, body: &str) -> String {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Posts webhook payloads as JSON, signed with the secret of their subscription or,
+/// for subscriptions without one, the secret shared with every subscriber.
+pub struct HttpWebhookSender {
+    secret: String,
+    client: Client,
+}
+
+impl HttpWebhookSender {
+    pub fn new(secret: &str, client: Client) -> Self {
+        Self {
+            secret: secret.into(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(
+        &self,
+        delivery: &WebhookDelivery,
+        timestamp: i64,
+    ) -> Result<(), WebhookSendError> {
+        let body = match serde_json::to_string(&delivery.payload) {
+            Ok(b) => b,
+            Err(e) => return Err(WebhookSendError::Unreachable(e.to_string())),
+        };
+        let secret = delivery.signing_secret.as_deref().unwrap_or(&self.secret);
+        let signature = webhook_signature(secret, timestamp, &body);
+
+        match self
+            .client
+            .post(&delivery.callback_url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, format!("sha256={}", signature))
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(body)
+            .send()
+            .await
+        {
+            Ok(r) if r.status().is_success() => Ok(()),
+            Ok(r) => Err(WebhookSendError::Rejected(r.status().as_u16())),
+            Err(e) => Err(WebhookSendError::Unreachable(e.to_string())),
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::{BatchAnalytics, BatchAnalyticsQuery, BatchRecord},
+        bridge::QueueManager,
+        clock::Clock,
+        consume_queue::{consume_queue, MintRetryPolicy},
+        ids::TokenId,
+        post_mint::PostMintHooks,
+        webhook::WebhookNotifier,
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+        InMemoryQueueManager, InMemoryStarknetTransactionManager, InMemoryWebhookRepository,
+        ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const KEPLR_WALLET: &str = "k3plr-pk1";
+const RETENTION_DAYS: u64 = 30;
+
+#[derive(Debug, World)]
+struct AnalyticsWorld {
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    analytics: Arc<BatchAnalytics>,
+    clock: ManualClock,
+}
+
+impl Default for AnalyticsWorld {
+    fn default() -> Self {
+        let clock = ManualClock::new(1_672_531_200_000);
+        Self {
+            queue_manager: Arc::new(InMemoryQueueManager::with_clock(Arc::new(clock.clone()))),
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            analytics: Arc::new(BatchAnalytics::new(
+                Arc::new(InMemoryBatchAnalyticsRepository::new()),
+                Arc::new(clock.clone()),
+                Duration::from_secs(RETENTION_DAYS * 86_400),
+            )),
+            clock,
+        }
+    }
+}
+
+async fn batches(world: &AnalyticsWorld, query: BatchAnalyticsQuery) -> Vec<BatchRecord> {
+    world.analytics.batches(&query).await.unwrap()
+}
+
+async fn last_batch(world: &AnalyticsWorld) -> BatchRecord {
+    batches(world, BatchAnalyticsQuery::default())
+        .await
+        .into_iter()
+        .next()
+        .expect("A batch should be recorded")
+}
+
+#[given(expr = "tokens {string} are queued")]
+async fn given_queued_tokens(world: &mut AnalyticsWorld, tokens: String) {
+    let token_ids: Vec<TokenId> = tokens.split(',').map(|t| t.parse().unwrap()).collect();
+    world
+        .queue_manager
+        .enqueue(
+            &KEPLR_WALLET.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+            token_ids,
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given(expr = "the sequencer rejects transactions with {string}")]
+async fn given_sequencer_rejects(world: &mut AnalyticsWorld, reason: String) {
+    world
+        .starknet_manager
+        .reject_transactions(Some(&reason))
+        .await;
+}
+
+#[given("starknet mints fail")]
+fn given_mints_fail(world: &mut AnalyticsWorld) {
+    world.starknet_manager.fail_mints(true);
+}
+
+#[when("the worker consumes the queue")]
+async fn when_the_worker_consumes(world: &mut AnalyticsWorld) {
+    let _ = consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(WebhookNotifier::new(
+            Arc::new(InMemoryWebhookRepository::new()),
+            Arc::new(world.clock.clone()),
+        )),
+        world.analytics.clone(),
+        &[],
+        &MintRetryPolicy::default(),
+        &CancellationToken::new(),
+    )
+    .await;
+}
+
+#[when(expr = "{int} days elapse")]
+fn when_days_elapse(world: &mut AnalyticsWorld, days: u64) {
+    world.clock.advance(Duration::from_secs(days * 86_400));
+}
+
+#[when("analytics are pruned")]
+async fn when_analytics_are_pruned(world: &mut AnalyticsWorld) {
+    world.analytics.prune().await.unwrap();
+}
+
+#[then(expr = "{int} batch(es) should be recorded")]
+async fn then_batches_recorded(world: &mut AnalyticsWorld, count: usize) {
+    assert_eq!(
+        count,
+        batches(world, BatchAnalyticsQuery::default()).await.len()
+    );
+}
+
+#[then(expr = "{int} batch(es) should be recorded over the last day")]
+async fn then_batches_recorded_last_day(world: &mut AnalyticsWorld, count: usize) {
+    let query = BatchAnalyticsQuery {
+        from: Some(world.clock.now_ms() - 86_400_000),
+        to: None,
+        project_id: None,
+    };
+    assert_eq!(count, batches(world, query).await.len());
+}
+
+#[then(expr = "the last batch should be {string} with {int} items")]
+async fn then_last_batch_should_be(world: &mut AnalyticsWorld, outcome: String, items: i32) {
+    let batch = last_batch(world).await;
+    assert_eq!(serde_json::json!(outcome), serde_json::json!(batch.outcome));
+    assert_eq!(items, batch.item_count);
+}
+
+#[then(expr = "the last batch should have paid {string}")]
+async fn then_last_batch_fee(world: &mut AnalyticsWorld, fee: String) {
+    assert_eq!(Some(fee), last_batch(world).await.fee);
+}
+
+#[then(expr = "token {string} should have been estimated to {string} and paid {string}")]
+async fn then_token_fees(
+    world: &mut AnalyticsWorld,
+    token: String,
+    estimated: String,
+    paid: String,
+) {
+    let item = world
+        .queue_manager
+        .get_customer_migration_state(
+            &KEPLR_WALLET.parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+        )
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id.as_str() == token)
+        .expect("Token should be queued");
+    assert_eq!(Some(estimated), item.estimated_fee);
+    assert_eq!(Some(paid), item.actual_fee);
+}
+
+#[then("the last batch latency should be recorded")]
+async fn then_last_batch_latency(world: &mut AnalyticsWorld) {
+    assert!(last_batch(world).await.latency_ms.is_some());
+}
+
+#[then(expr = "the last batch should have been rejected because of {string}")]
+async fn then_last_batch_rejection(world: &mut AnalyticsWorld, reason: String) {
+    assert_eq!(Some(reason), last_batch(world).await.rejection_reason);
+}
+
+#[then("the last batch should have no transaction")]
+async fn then_last_batch_without_transaction(world: &mut AnalyticsWorld) {
+    let batch = last_batch(world).await;
+    assert_eq!(None, batch.transaction_hash);
+    assert_eq!(None, batch.latency_ms);
+}
+
+#[tokio::main]
+async fn main() {
+    AnalyticsWorld::cucumber()
+        .run_and_exit("features/analytics.feature")
+        .await;
+}
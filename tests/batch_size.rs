@@ -0,0 +1,295 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::{BatchAnalytics, BatchAnalyticsQuery},
+        batch_size::{AdaptiveBatchPolicy, BatchSizeRepository, BatchSizeTuner},
+        bridge::{QueueManager, QueueStatus},
+        consume_queue::{consume_queue, paused_projects, MintRetryPolicy},
+        ids::{StarknetAddress, TokenId},
+        post_mint::PostMintHooks,
+        webhook::WebhookNotifier,
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryBatchSizeRepository,
+        InMemoryPostMintExecutionRepository, InMemoryQueueManager,
+        InMemoryStarknetTransactionManager, InMemoryWebhookRepository, ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const KEPLR_WALLET: &str = "k3plr-pk1";
+
+#[derive(Debug, World)]
+struct BatchSizeWorld {
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    analytics_repository: InMemoryBatchAnalyticsRepository,
+    batch_size_repository: InMemoryBatchSizeRepository,
+    policy: Option<AdaptiveBatchPolicy>,
+    analytics: Arc<BatchAnalytics>,
+    clock: ManualClock,
+}
+
+impl Default for BatchSizeWorld {
+    fn default() -> Self {
+        let clock = ManualClock::new(1_672_531_200_000);
+        let analytics_repository = InMemoryBatchAnalyticsRepository::new();
+        Self {
+            queue_manager: Arc::new(InMemoryQueueManager::with_clock(Arc::new(clock.clone()))),
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            analytics: Arc::new(BatchAnalytics::new(
+                Arc::new(analytics_repository.clone()),
+                Arc::new(clock.clone()),
+                Duration::from_secs(86_400),
+            )),
+            analytics_repository,
+            batch_size_repository: InMemoryBatchSizeRepository::new(),
+            policy: None,
+            clock,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+// Worker process state is rebuilt from persisted values only
+fn start_worker(world: &mut BatchSizeWorld) {
+    let tuner = world.policy.clone().map(|policy| {
+        Arc::new(BatchSizeTuner::new(
+            Arc::new(world.batch_size_repository.clone()),
+            Arc::new(world.clock.clone()),
+            policy,
+        ))
+    });
+    world.analytics = Arc::new(BatchAnalytics::with_tuner(
+        Arc::new(world.analytics_repository.clone()),
+        Arc::new(world.clock.clone()),
+        Duration::from_secs(86_400),
+        tuner,
+    ));
+}
+
+async fn consume(world: &BatchSizeWorld) {
+    // Paused projects are left out of the batch, as the worker does
+    let excluded_projects = paused_projects(&world.starknet_manager, &[project()]).await;
+    let _ = consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(WebhookNotifier::new(
+            Arc::new(InMemoryWebhookRepository::new()),
+            Arc::new(world.clock.clone()),
+        )),
+        world.analytics.clone(),
+        &excluded_projects,
+        &MintRetryPolicy::default(),
+        &CancellationToken::new(),
+    )
+    .await;
+}
+
+#[given(
+    expr = "adaptive batch size starts at {int} between {int} and {int}, growing by {int} after {int} accepted batches"
+)]
+fn given_adaptive_batch_size(
+    world: &mut BatchSizeWorld,
+    initial: u32,
+    min: u32,
+    max: u32,
+    step: u32,
+    increase_after: u32,
+) {
+    world.policy = Some(AdaptiveBatchPolicy {
+        initial,
+        min,
+        max,
+        step,
+        increase_after,
+    });
+    start_worker(world);
+}
+
+#[given(expr = "{int} tokens are queued")]
+async fn given_queued_tokens(world: &mut BatchSizeWorld, count: usize) {
+    let token_ids: Vec<TokenId> = (0..count)
+        .map(|i| (700 + i).to_string().parse().unwrap())
+        .collect();
+    world
+        .queue_manager
+        .enqueue(
+            &KEPLR_WALLET.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            token_ids,
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given(expr = "the mint of token {string} reverts")]
+async fn given_mint_reverts(world: &mut BatchSizeWorld, token_id: String) {
+    world
+        .starknet_manager
+        .revert_mints_of(&token_id.parse().unwrap())
+        .await;
+}
+
+#[given(expr = "the mint of token {string} reverts on chain")]
+async fn given_mint_reverts_on_chain(world: &mut BatchSizeWorld, token_id: String) {
+    world
+        .starknet_manager
+        .fail_mints_on_chain_of(&token_id.parse().unwrap())
+        .await;
+}
+
+#[given(expr = "the sequencer rejects transactions with {string}")]
+async fn given_sequencer_rejects(world: &mut BatchSizeWorld, reason: String) {
+    world
+        .starknet_manager
+        .reject_transactions(Some(&reason))
+        .await;
+}
+
+#[given(expr = "transactions hold at most {int} calls")]
+fn given_transactions_hold_at_most(world: &mut BatchSizeWorld, max_calls: usize) {
+    world
+        .starknet_manager
+        .limit_calls_per_transaction(max_calls);
+}
+
+#[given("starknet mints fail")]
+fn given_mints_fail(world: &mut BatchSizeWorld) {
+    world.starknet_manager.fail_mints(true);
+}
+
+#[given(expr = "the project contract is {word}")]
+async fn given_project_contract_state(world: &mut BatchSizeWorld, state: String) {
+    world
+        .starknet_manager
+        .pause_project(&project(), "paused" == state)
+        .await;
+}
+
+#[when("the worker consumes the queue")]
+async fn when_the_worker_consumes(world: &mut BatchSizeWorld) {
+    consume(world).await;
+}
+
+#[when(expr = "the worker consumes the queue {int} times")]
+async fn when_the_worker_consumes_times(world: &mut BatchSizeWorld, times: usize) {
+    for _ in 0..times {
+        consume(world).await;
+    }
+}
+
+#[when("the worker restarts")]
+fn when_the_worker_restarts(world: &mut BatchSizeWorld) {
+    start_worker(world);
+}
+
+#[then(expr = "the last batch should have minted {int} items")]
+async fn then_last_batch_items(world: &mut BatchSizeWorld, items: i32) {
+    let batches = world
+        .analytics
+        .batches(&BatchAnalyticsQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(Some(items), batches.first().map(|b| b.item_count));
+}
+
+#[then(expr = "{int} batches should have been sent")]
+async fn then_batches_sent(world: &mut BatchSizeWorld, count: usize) {
+    let batches = world
+        .analytics
+        .batches(&BatchAnalyticsQuery::default())
+        .await
+        .unwrap();
+    assert_eq!(count, batches.len());
+}
+
+#[then(expr = "queued tokens should be minted in {int} transactions")]
+async fn then_tokens_minted_in(world: &mut BatchSizeWorld, count: usize) {
+    let items = world
+        .queue_manager
+        .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &project())
+        .await;
+    assert!(items
+        .iter()
+        .all(|qi| matches!(qi.status, QueueStatus::Success)));
+    let transactions: HashSet<Option<String>> =
+        items.into_iter().map(|qi| qi.transaction_hash).collect();
+    assert_eq!(count, transactions.len());
+}
+
+#[then(expr = "{int} queued tokens should be minted")]
+async fn then_tokens_minted(world: &mut BatchSizeWorld, count: usize) {
+    let items = world
+        .queue_manager
+        .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &project())
+        .await;
+    let minted = items
+        .iter()
+        .filter(|qi| matches!(qi.status, QueueStatus::Success))
+        .count();
+    assert_eq!(count, minted);
+}
+
+#[then(expr = "{int} queued items should be pending with note {string}")]
+async fn then_items_pending_with_note(world: &mut BatchSizeWorld, count: usize, note: String) {
+    let items = world
+        .queue_manager
+        .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &project())
+        .await;
+    let pending = items
+        .iter()
+        .filter(|qi| matches!(qi.status, QueueStatus::Pending))
+        .filter(|qi| Some(&note) == qi.note.as_ref())
+        .count();
+    assert_eq!(count, pending);
+}
+
+#[then(expr = "{int} queued items should be dead lettered with note {string}")]
+async fn then_items_dead_lettered_with_note(
+    world: &mut BatchSizeWorld,
+    count: usize,
+    note: String,
+) {
+    let items = world
+        .queue_manager
+        .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &project())
+        .await;
+    let dead_letters = items
+        .iter()
+        .filter(|qi| matches!(qi.status, QueueStatus::DeadLetter))
+        .filter(|qi| Some(&note) == qi.note.as_ref())
+        .count();
+    assert_eq!(count, dead_letters);
+}
+
+#[then(expr = "the project batch size should be {int}")]
+async fn then_project_batch_size(world: &mut BatchSizeWorld, batch_size: usize) {
+    assert_eq!(
+        Some(batch_size),
+        world.analytics.batch_size(&project()).await
+    );
+    assert!(world
+        .batch_size_repository
+        .get_batch_size(&project())
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::main]
+async fn main() {
+    BatchSizeWorld::cucumber()
+        .run_and_exit("features/batch_size.feature")
+        .await;
+}
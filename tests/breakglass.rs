@@ -0,0 +1,196 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        breakglass::{
+            authenticate_operator, handle_breakglass_confirmation, handle_breakglass_request,
+            BreakglassError, BreakglassMint, BreakglassMintRequest, BreakglassRepository, Operator,
+        },
+        bridge::StarknetManager,
+        ids::TokenId,
+    },
+    infrastructure::in_memory::{
+        InMemoryBreakglassRepository, InMemoryStarknetTransactionManager, ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+const CONFIRMATION_WINDOW: Duration = Duration::from_secs(300);
+
+#[derive(Debug, World)]
+struct BreakglassWorld {
+    mint_id: Option<Uuid>,
+    confirmations: Vec<Result<BreakglassMint, BreakglassError>>,
+    authenticated: Option<String>,
+    breakglass_repository: InMemoryBreakglassRepository,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    clock: ManualClock,
+}
+
+impl Default for BreakglassWorld {
+    fn default() -> Self {
+        Self {
+            mint_id: None,
+            confirmations: Vec::new(),
+            authenticated: None,
+            breakglass_repository: InMemoryBreakglassRepository::new(),
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            clock: ManualClock::new(1_672_531_200_000),
+        }
+    }
+}
+
+fn operator(name: &str) -> Operator {
+    Operator {
+        name: name.into(),
+        api_key: format!("{}-key", name),
+    }
+}
+
+fn token_ids(tokens: &str) -> Vec<TokenId> {
+    tokens
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(", ")
+        .map(|t| t.parse().unwrap())
+        .collect()
+}
+
+#[given(expr = "{string} requested a breakglass mint of tokens {} on {string}")]
+async fn given_a_breakglass_request(
+    world: &mut BreakglassWorld,
+    requester: String,
+    tokens: String,
+    project: String,
+) {
+    let req = BreakglassMintRequest {
+        starknet_project_addr: project.parse().unwrap(),
+        starknet_account_addr: "0xacc0".parse().unwrap(),
+        token_ids: token_ids(&tokens),
+        reason: "Juno transfer stuck in mempool".into(),
+    };
+    let mint = handle_breakglass_request(
+        &req,
+        &operator(&requester),
+        Arc::new(world.breakglass_repository.clone()),
+        Arc::new(world.clock.clone()),
+    )
+    .await
+    .expect("Breakglass request should be accepted");
+
+    world.mint_id = Some(mint.id);
+}
+
+#[when(expr = "{int} seconds elapse")]
+fn when_time_elapses(world: &mut BreakglassWorld, seconds: u64) {
+    world.clock.advance(Duration::from_secs(seconds));
+}
+
+async fn confirm(
+    world: &BreakglassWorld,
+    confirmer: &str,
+) -> Result<BreakglassMint, BreakglassError> {
+    handle_breakglass_confirmation(
+        world.mint_id.as_ref().unwrap(),
+        &operator(confirmer),
+        CONFIRMATION_WINDOW,
+        Arc::new(world.breakglass_repository.clone()),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(world.clock.clone()),
+        &CancellationToken::new(),
+    )
+    .await
+}
+
+#[given(expr = "starknet rejects transactions with {string}")]
+async fn given_starknet_rejects(world: &mut BreakglassWorld, reason: String) {
+    world
+        .starknet_manager
+        .reject_transactions(Some(&reason))
+        .await;
+}
+
+#[when(expr = "{string} confirms the breakglass mint")]
+async fn when_operator_confirms(world: &mut BreakglassWorld, confirmer: String) {
+    // Outcome is checked against the persisted mint
+    let confirmation = confirm(world, &confirmer).await;
+    world.confirmations.push(confirmation);
+}
+
+#[when(expr = "{string} and {string} confirm the breakglass mint at the same time")]
+async fn when_operators_confirm_concurrently(
+    world: &mut BreakglassWorld,
+    first: String,
+    second: String,
+) {
+    let (first, second) = futures::join!(confirm(world, &first), confirm(world, &second));
+    world.confirmations.extend([first, second]);
+}
+
+#[when(expr = "api key {string} is presented")]
+fn when_api_key_is_presented(world: &mut BreakglassWorld, api_key: String) {
+    let operators = vec![operator("alice"), operator("bob"), operator("carol")];
+    world.authenticated = authenticate_operator(&operators, &api_key).map(|o| o.name.clone());
+}
+
+#[then(expr = "operator {string} should be authenticated")]
+fn then_operator_is_authenticated(world: &mut BreakglassWorld, name: String) {
+    assert_eq!(Some(name), world.authenticated);
+}
+
+#[then("no operator should be authenticated")]
+fn then_no_operator_is_authenticated(world: &mut BreakglassWorld) {
+    assert_eq!(None, world.authenticated);
+}
+
+#[then(expr = "the breakglass mint should be {string}")]
+async fn then_mint_status_is(world: &mut BreakglassWorld, status: String) {
+    let mint = world
+        .breakglass_repository
+        .get(world.mint_id.as_ref().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(status, serde_json::to_value(&mint.status).unwrap());
+}
+
+#[then(expr = "only {int} confirmation should have been accepted")]
+fn then_confirmations_accepted(world: &mut BreakglassWorld, count: usize) {
+    let accepted = world.confirmations.iter().filter(|c| c.is_ok()).count();
+    assert_eq!(count, accepted, "{:#?}", world.confirmations);
+    assert!(world
+        .confirmations
+        .iter()
+        .filter_map(|c| c.as_ref().err())
+        .all(|e| matches!(e, BreakglassError::AlreadyHandled)));
+}
+
+#[then(expr = "tokens {} should be minted on {string}")]
+async fn then_tokens_are_minted(world: &mut BreakglassWorld, tokens: String, project: String) {
+    for token_id in token_ids(&tokens) {
+        assert!(world
+            .starknet_manager
+            .project_has_token(&project.parse().unwrap(), &token_id)
+            .await
+            .unwrap());
+    }
+}
+
+#[then(expr = "tokens {} should not be minted on {string}")]
+async fn then_tokens_are_not_minted(world: &mut BreakglassWorld, tokens: String, project: String) {
+    for token_id in token_ids(&tokens) {
+        assert!(!world
+            .starknet_manager
+            .project_has_token(&project.parse().unwrap(), &token_id)
+            .await
+            .unwrap());
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    BreakglassWorld::cucumber()
+        .run_and_exit("features/breakglass.feature")
+        .await;
+}
@@ -1,22 +1,37 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use bridge_juno_to_starknet_backend::{
     domain::{
+        backpressure::QueueBackpressure,
         bridge::{
-            handle_bridge_request, BridgeError, BridgeRequest, BridgeResponse, QueueManager,
-            SignedHash, SignedHashValidator, StarknetManager, Transaction, TransactionRepository,
+            handle_bridge_request, BridgeError, BridgeRequest, BridgeResponse, JunoContractQuerier,
+            QueueItem, QueueManager, SignedHash, SignedHashValidator, StarknetManager,
+            TokenCheckCode, Transaction, TransactionRepository,
+        },
+        challenge::ChallengeService,
+        check_cache::CheckResultCache,
+        clock::SystemClock,
+        error_catalog::CatalogedError,
+        project_registry::{
+            MigrationPolicy, MigrationWindow, Project, ProjectRegistry, TransferWindow,
         },
         save_customer_data::DataRepository,
+        transfer_proof::TransferProofRepository,
+        wallet_link::{WalletLink, WalletLinkRepository},
     },
     infrastructure::in_memory::{
-        InMemoryDataRepository, InMemoryQueueManager, InMemoryStarknetTransactionManager,
-        InMemoryTransactionRepository, TestSignedHashValidator,
+        InMemoryChallengeRepository, InMemoryCheckResultRepository, InMemoryDataRepository,
+        InMemoryJunoContractQuerier, InMemoryQueueManager, InMemoryStarknetTransactionManager,
+        InMemoryTransactionRepository, InMemoryTransferProofRepository,
+        InMemoryWalletLinkRepository, TestSignedHashValidator,
     },
 };
 use cucumber::{gherkin::Step, given, then, when, World};
 use std::future::ready;
+use tokio_util::sync::CancellationToken;
 
-const STARKNET_PROJECT_ADDR: &str = "starknet_project_addr";
+const STARKNET_PROJECT_ADDR: &str = "0x057a2b0d";
+const STARKNET_BURN_PROJECT_ADDR: &str = "0x057a2b0e";
 
 #[derive(Debug, World)]
 struct BridgeWorld {
@@ -27,6 +42,19 @@ struct BridgeWorld {
     starknet_manager: Option<Arc<dyn StarknetManager>>,
     data_repository: Option<Arc<dyn DataRepository>>,
     queue_manager: Option<Arc<dyn QueueManager>>,
+    queued_items: Vec<QueueItem>,
+    wallet_link_repository: Option<Arc<dyn WalletLinkRepository>>,
+    project_registry: ProjectRegistry,
+    juno_node: InMemoryTransactionRepository,
+    // Ownership is only verified on chain when a scenario sets token holders
+    juno_contract: Option<InMemoryJunoContractQuerier>,
+    transfer_proofs: InMemoryTransferProofRepository,
+    challenges: ChallengeService,
+    require_sign_doc: bool,
+    check_cache: CheckResultCache,
+    backpressure: Option<QueueBackpressure>,
+    budget: Duration,
+    cancel: CancellationToken,
 }
 impl BridgeWorld {
     fn with_signed_hash_validator(&mut self, validator: Arc<dyn SignedHashValidator>) {
@@ -45,6 +73,13 @@ impl BridgeWorld {
     fn with_queue_manager(&mut self, queue_manager: Arc<dyn QueueManager>) {
         self.queue_manager = Some(queue_manager);
     }
+
+    fn with_wallet_link_repository(
+        &mut self,
+        wallet_link_repository: Arc<dyn WalletLinkRepository>,
+    ) {
+        self.wallet_link_repository = Some(wallet_link_repository);
+    }
 }
 
 impl Default for BridgeWorld {
@@ -57,13 +92,56 @@ impl Default for BridgeWorld {
             starknet_manager: None,
             data_repository: None,
             queue_manager: None,
+            queued_items: Vec::new(),
+            wallet_link_repository: None,
+            project_registry: ProjectRegistry::new(vec![
+                Project {
+                    juno_contract: "projectId".parse().unwrap(),
+                    starknet_contract: STARKNET_PROJECT_ADDR.parse().unwrap(),
+                    juno_admin_address: "juno-admin-account".parse().unwrap(),
+                    mint_selector: "mint".into(),
+                    migration_window: MigrationWindow::default(),
+                    migration_policy: MigrationPolicy::TransferToAdmin,
+                    transfer_window: TransferWindow::default(),
+                },
+                Project {
+                    juno_contract: "burnProject".parse().unwrap(),
+                    starknet_contract: STARKNET_BURN_PROJECT_ADDR.parse().unwrap(),
+                    juno_admin_address: "juno-admin-account".parse().unwrap(),
+                    mint_selector: "mint".into(),
+                    migration_window: MigrationWindow::default(),
+                    migration_policy: MigrationPolicy::Burn,
+                    transfer_window: TransferWindow::default(),
+                },
+            ]),
+            juno_node: InMemoryTransactionRepository::new(Vec::new()),
+            juno_contract: None,
+            transfer_proofs: InMemoryTransferProofRepository::new(),
+            challenges: ChallengeService::new(
+                Arc::new(InMemoryChallengeRepository::new()),
+                Arc::new(SystemClock),
+                Duration::from_secs(300),
+                false,
+            ),
+            require_sign_doc: false,
+            // Disabled unless a scenario asks for it, scenarios reuse tokens of other wallets
+            check_cache: CheckResultCache::new(
+                Arc::new(InMemoryCheckResultRepository::new()),
+                Arc::new(SystemClock),
+                Duration::ZERO,
+            ),
+            backpressure: None,
+            budget: Duration::from_secs(25),
+            cancel: CancellationToken::new(),
         }
     }
 }
 
 #[given("a request with values:")]
 fn given_request_with_values(case: &mut BridgeWorld, step: &Step) {
-    let Some(table) = step.table.as_ref() else { return };
+    let Some(table) = step.table.as_ref() else {
+        return;
+    };
     // Skipping first row as it is headers
     for row in table.rows.iter().skip(1) {
         // Retrieving col values with number.
@@ -75,15 +153,15 @@ fn given_request_with_values(case: &mut BridgeWorld, step: &Step) {
                 },
                 signature: row[0].to_string(),
             },
-            &row[1],
-            STARKNET_PROJECT_ADDR,
-            &row[2],
-            &row[3],
+            row[1].parse().unwrap(),
+            row[2].parse().unwrap(),
+            row[3].parse().unwrap(),
             row[4]
                 .replace("[", "")
                 .replace("]", "")
                 .split(", ")
-                .collect::<Vec<&str>>(),
+                .map(|t| t.parse().unwrap())
+                .collect(),
         );
 
         case.request = Some(request);
@@ -94,8 +172,127 @@ fn given_request_with_values(case: &mut BridgeWorld, step: &Step) {
 fn given_the_following_transactions_list(case: &mut BridgeWorld, step: &Step) {
     let transactions: Vec<Transaction> =
         serde_json::from_str(step.docstring.as_ref().unwrap()).unwrap();
-    let transaction_repository = Arc::new(InMemoryTransactionRepository::new(transactions));
-    case.with_transaction_repository(transaction_repository);
+    case.juno_node = InMemoryTransactionRepository::new(transactions);
+    case.with_transaction_repository(Arc::new(case.juno_node.clone()));
+}
+
+#[given(expr = "project {word} accepts transfers from {word} until {word}")]
+fn given_transfer_window(case: &mut BridgeWorld, project: String, starts: String, ends: String) {
+    case.project_registry = case
+        .project_registry
+        .clone()
+        .with_transfer_windows(&[format!("{}={}/{}", project, starts, ends)])
+        .unwrap();
+}
+
+#[given(expr = "the juno node answers in {int} milliseconds")]
+fn given_slow_juno_node(case: &mut BridgeWorld, latency: u64) {
+    case.juno_node.slow_down(Duration::from_millis(latency));
+}
+
+#[given(expr = "the starknet node answers in {int} milliseconds")]
+fn given_slow_starknet_node(case: &mut BridgeWorld, latency: u64) {
+    let starknet_node = InMemoryStarknetTransactionManager::new();
+    starknet_node.slow_down(Duration::from_millis(latency));
+    case.with_starknet_manager(Arc::new(starknet_node));
+}
+
+#[given("the starknet node cannot be reached")]
+fn given_unreachable_starknet_node(case: &mut BridgeWorld) {
+    let starknet_node = InMemoryStarknetTransactionManager::new();
+    starknet_node.make_unreachable(true);
+    case.with_starknet_manager(Arc::new(starknet_node));
+}
+
+#[given(expr = "a bridge request budget of {int} milliseconds")]
+fn given_request_budget(case: &mut BridgeWorld, budget: u64) {
+    case.budget = Duration::from_millis(budget);
+}
+
+#[given(expr = "keplr wallet {word} is linked to starknet account {word}")]
+async fn given_wallet_is_linked(case: &mut BridgeWorld, keplr: String, starknet: String) {
+    case.wallet_link_repository
+        .as_ref()
+        .unwrap()
+        .save_link(WalletLink {
+            keplr_wallet_pubkey: keplr.parse().unwrap(),
+            starknet_account_addr: starknet.parse().unwrap(),
+        })
+        .await
+        .unwrap();
+}
+
+#[given("wallet links cannot be read")]
+fn given_wallet_links_cannot_be_read(case: &mut BridgeWorld) {
+    let wallet_link_repository = InMemoryWalletLinkRepository::new();
+    wallet_link_repository.make_unavailable(true);
+    case.with_wallet_link_repository(Arc::new(wallet_link_repository));
+}
+
+#[given(expr = "keplr wallet {word} authorized sender {word} for project {word}")]
+async fn given_authorized_sender(
+    case: &mut BridgeWorld,
+    keplr: String,
+    sender: String,
+    project: String,
+) {
+    case.data_repository
+        .as_ref()
+        .unwrap()
+        .save_authorized_sender(
+            &keplr.parse().unwrap(),
+            &project.parse().unwrap(),
+            &sender.parse().unwrap(),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("Failed to save authorized sender"));
+}
+
+#[given(expr = "the juno contract reports token {word} held by {word}")]
+async fn given_token_holder(case: &mut BridgeWorld, token: String, holder: String) {
+    case.juno_contract
+        .get_or_insert_with(InMemoryJunoContractQuerier::new)
+        .set_owner(
+            &"projectId".parse().unwrap(),
+            &token.parse().unwrap(),
+            &holder.parse().unwrap(),
+        )
+        .await;
+}
+
+#[given("the juno node is down")]
+fn given_juno_node_is_down(case: &mut BridgeWorld) {
+    case.juno_node.fail_fetches(true);
+}
+
+#[given("sign docs are required")]
+fn given_sign_docs_are_required(case: &mut BridgeWorld) {
+    case.require_sign_doc = true;
+}
+
+#[given("the request signs document")]
+fn given_request_signs_document(case: &mut BridgeWorld, step: &Step) {
+    let request = case.request.as_mut().expect("Request should be set first");
+    request.sign_doc = step.docstring.as_ref().map(|d| d.trim().to_string());
+}
+
+#[given(expr = "check results are reused for {int} seconds")]
+fn given_check_results_are_reused(case: &mut BridgeWorld, ttl: u64) {
+    case.check_cache = CheckResultCache::new(
+        Arc::new(InMemoryCheckResultRepository::new()),
+        Arc::new(SystemClock),
+        Duration::from_secs(ttl),
+    );
+}
+
+#[given("an empty queue")]
+fn given_an_empty_queue(case: &mut BridgeWorld) {
+    case.with_queue_manager(Arc::new(InMemoryQueueManager::new()));
+}
+
+#[given("the service is shutting down")]
+fn given_the_service_is_shutting_down(case: &mut BridgeWorld) {
+    case.cancel.cancel();
 }
 
 #[when("I execute the request")]
@@ -104,19 +301,39 @@ async fn when_i_execute_the_request(case: &mut BridgeWorld) {
         case.response = Some(
             handle_bridge_request(
                 request,
-                "juno-admin-account",
+                &case.project_registry,
+                &SystemClock,
                 "starknet-admin-account",
                 case.validator.as_ref().unwrap().clone(),
                 case.transactions_repository.as_ref().unwrap().clone(),
+                case.juno_contract
+                    .as_ref()
+                    .map(|c| c as &dyn JunoContractQuerier),
+                Arc::new(case.transfer_proofs.clone()),
                 case.starknet_manager.as_ref().unwrap().clone(),
                 case.data_repository.as_ref().unwrap().clone(),
                 case.queue_manager.as_ref().unwrap().clone(),
+                case.wallet_link_repository.as_ref().unwrap().clone(),
+                &case.challenges,
+                case.require_sign_doc,
+                &case.check_cache,
+                case.backpressure.as_ref(),
+                &case.cancel,
+                case.budget,
             )
             .await,
         )
     }
 }
 
+#[then(expr = "the request should have been refused with code {string}")]
+fn then_refused_with_code(case: &mut BridgeWorld, code: String) {
+    match case.response.as_ref() {
+        Some(Err(e)) => assert_eq!(code, e.catalog_entry().code),
+        other => panic!("Expected request to be refused, got {:#?}", other),
+    }
+}
+
 #[then("the signed hash should not be valid")]
 fn then_the_signed_hash_sould_not_be_valid(case: &mut BridgeWorld) {
     if let Some(response) = &case.response {
@@ -170,7 +387,6 @@ fn then_current_owner_is_not_admin(case: &mut BridgeWorld) {
 
 #[then("nfts migration request should have been enqueued and response should be ok")]
 async fn then_nfts_should_be_minted_on_starknet(case: &mut BridgeWorld) {
-    let starknet_project_id = &case.request.as_ref().unwrap().starknet_project_addr;
     let tokens_id = &case.request.as_ref().unwrap().tokens_id;
     let queue_manager = &case.queue_manager.as_ref().unwrap().clone();
 
@@ -180,23 +396,275 @@ async fn then_nfts_should_be_minted_on_starknet(case: &mut BridgeWorld) {
             Ok(r) => r,
         };
 
-        assert_eq!(2, queue_manager.get_batch().await.unwrap().len())
+        assert_eq!(2, queue_manager.get_batch(&[]).await.unwrap().len())
+    }
+}
+
+#[given(expr = "the request targets starknet contract {word}")]
+fn given_request_targets_contract(case: &mut BridgeWorld, contract: String) {
+    case.request.as_mut().unwrap().starknet_project_addr = Some(contract.parse().unwrap());
+}
+
+#[then(expr = "the request should have been refused because project {word} is not bridged")]
+fn then_project_is_not_bridged(case: &mut BridgeWorld, project: String) {
+    match &case.response {
+        Some(Err(BridgeError::UnknownProject(p))) => assert_eq!(&project, p),
+        r => panic!("Expected an unknown project error, got {:#?}", r),
+    }
+}
+
+#[then("tokens should have been enqueued for the registered starknet contract")]
+async fn then_enqueued_for_registered_contract(case: &mut BridgeWorld) {
+    let batch = case
+        .queue_manager
+        .as_ref()
+        .unwrap()
+        .get_batch(&[])
+        .await
+        .unwrap();
+    assert!(!batch.is_empty());
+    for item in batch {
+        assert_eq!(STARKNET_PROJECT_ADDR, item.project_id.as_str());
     }
 }
 
-fn main() {
+#[then("I should receive an error because starknet account is not the linked one")]
+fn then_starknet_account_is_not_linked_one(case: &mut BridgeWorld) {
+    match &case.response {
+        Some(Err(BridgeError::StarknetAccountMismatch(_))) => (),
+        r => panic!("Expected a starknet account mismatch, got {:#?}", r),
+    }
+}
+
+#[then(expr = "token {word} should have passed checks")]
+fn then_token_should_have_passed_checks(case: &mut BridgeWorld, token: String) {
+    match &case.response {
+        Some(Ok(res)) => match res.checks.get(token.as_str()) {
+            Some((_token, None)) => (),
+            c => panic!("Token {} should have passed checks, got {:#?}", token, c),
+        },
+        r => panic!("Response should be ok, got {:#?}", r),
+    }
+}
+
+#[then(expr = "transfer of token {word} by {word} should have been proven")]
+async fn then_transfer_proven(case: &mut BridgeWorld, token: String, sender: String) {
+    let proof = case
+        .transfer_proofs
+        .get_proof(&"projectId".parse().unwrap(), &token.parse().unwrap())
+        .await
+        .unwrap()
+        .expect("Transfer proof should have been saved");
+    assert_eq!(sender, proof.sender);
+    assert_eq!(Some("juno-admin-account"), proof.recipient.as_deref());
+}
+
+#[then(expr = "burn of token {word} on {word} by {word} should have been proven")]
+async fn then_burn_proven(case: &mut BridgeWorld, token: String, project: String, sender: String) {
+    let proof = case
+        .transfer_proofs
+        .get_proof(&project.parse().unwrap(), &token.parse().unwrap())
+        .await
+        .unwrap()
+        .expect("Burn proof should have been saved");
+    assert_eq!(sender, proof.sender);
+    assert_eq!(None, proof.recipient);
+}
+
+#[then(expr = "token {word} should have failed checks with code {string}")]
+fn then_token_should_have_failed_with(case: &mut BridgeWorld, token: String, code: String) {
+    let Some(Ok(response)) = &case.response else {
+        panic!("Expected checks in response, got {:#?}", case.response);
+    };
+    let code: TokenCheckCode = serde_json::from_value(serde_json::json!(code)).unwrap();
+    match response.checks.get(token.as_str()) {
+        Some((_, Some(message))) => assert_eq!(code.default_message(), message),
+        other => panic!(
+            "Token {} should have failed checks, got {:#?}",
+            token, other
+        ),
+    }
+}
+
+#[then(expr = "the juno node should have been asked {int} time(s)")]
+fn then_juno_node_asked(case: &mut BridgeWorld, fetches: usize) {
+    assert_eq!(fetches, case.juno_node.fetches());
+}
+
+#[then(expr = "checks of token(s) {string} should be incomplete")]
+fn then_checks_incomplete(case: &mut BridgeWorld, tokens: String) {
+    let Some(Ok(res)) = &case.response else {
+        panic!("Response should be ok, got {:#?}", case.response);
+    };
+    for token in tokens.split(", ") {
+        match res.checks.get(token) {
+            Some((_token, Some(err))) => {
+                assert_eq!(TokenCheckCode::ChecksIncomplete.default_message(), err)
+            }
+            c => panic!("Token {} checks should be incomplete, got {:#?}", token, c),
+        }
+    }
+}
+
+#[then(expr = "only token(s) {string} should have been enqueued")]
+async fn then_only_tokens_enqueued(case: &mut BridgeWorld, tokens: String) {
+    let mut enqueued: Vec<String> = case
+        .queue_manager
+        .as_ref()
+        .unwrap()
+        .get_batch(&[])
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|qi| qi.token_id.to_string())
+        .collect();
+    enqueued.sort();
+    assert_eq!(tokens.split(", ").collect::<Vec<&str>>(), enqueued);
+}
+
+#[then(expr = "token {word} should have been enqueued with note {string}")]
+async fn then_token_enqueued_with_note(case: &mut BridgeWorld, token: String, note: String) {
+    let qi = case
+        .queue_manager
+        .as_ref()
+        .unwrap()
+        .get_batch(&[])
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued");
+    assert_eq!(Some(note), qi.note);
+}
+
+#[then(expr = "only token(s) {string} should be part of the next batch")]
+async fn then_only_tokens_batched(case: &mut BridgeWorld, tokens: String) {
+    then_only_tokens_enqueued(case, tokens).await;
+}
+
+#[then("the request should have been cancelled without enqueueing anything")]
+async fn then_request_should_have_been_cancelled(case: &mut BridgeWorld) {
+    match &case.response {
+        Some(Err(BridgeError::Cancelled)) => (),
+        r => panic!("Request should have been cancelled, got {:#?}", r),
+    }
+    let queue_manager = case.queue_manager.as_ref().unwrap();
+    assert_eq!(0, queue_manager.get_batch(&[]).await.unwrap().len());
+}
+
+#[given(expr = "{int} tokens are pending in the queue")]
+async fn given_pending_tokens(case: &mut BridgeWorld, count: u64) {
+    let token_ids = (0..count).map(|i| (900 + i).to_string().parse().unwrap());
+    case.queue_manager
+        .as_ref()
+        .unwrap()
+        .enqueue(
+            &"k3plr-queued".parse().unwrap(),
+            &"0x5700".parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+            token_ids.collect(),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given(expr = "token {word} is queued for keplr wallet {word}")]
+async fn given_token_queued(case: &mut BridgeWorld, token: String, keplr_wallet: String) {
+    let queued = case
+        .queue_manager
+        .as_ref()
+        .unwrap()
+        .enqueue(
+            &keplr_wallet.parse().unwrap(),
+            &"0x5700".parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+    case.queued_items.extend(queued);
+}
+
+#[then(expr = "token {word} should have kept its queue item")]
+async fn then_token_kept_queue_item(case: &mut BridgeWorld, token: String) {
+    let queued = case
+        .queued_items
+        .iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should have been queued");
+    let batch = case
+        .queue_manager
+        .as_ref()
+        .unwrap()
+        .get_batch(&[])
+        .await
+        .unwrap();
+    let ids: Vec<_> = batch
+        .iter()
+        .filter(|qi| qi.token_id == token)
+        .map(|qi| qi.id)
+        .collect();
+    assert_eq!(vec![queued.id], ids);
+}
+
+#[given(expr = "token {word} is being checked by another request")]
+async fn given_token_locked(case: &mut BridgeWorld, token: String) {
+    let locked = case
+        .queue_manager
+        .as_ref()
+        .unwrap()
+        .lock_tokens(
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+            &[token.parse().unwrap()],
+            "another-request",
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+    assert_eq!(1, locked.len());
+}
+
+#[given(expr = "the queue accepts at most {int} pending items, drained {int} every {int} seconds")]
+fn given_queue_backpressure(case: &mut BridgeWorld, max_pending: i64, batch_size: u8, poll: u64) {
+    case.backpressure = Some(QueueBackpressure::new(
+        max_pending,
+        batch_size,
+        Duration::from_secs(poll),
+    ));
+}
+
+#[then(expr = "the customer should be told to retry in {int} seconds")]
+fn then_customer_should_retry_in(case: &mut BridgeWorld, seconds: u64) {
+    match &case.response {
+        Some(Err(BridgeError::QueueFull(retry_after))) => assert_eq!(seconds, *retry_after),
+        r => panic!("Queue should be full, got {:#?}", r),
+    }
+}
+
+#[then(expr = "{int} tokens should be pending in the queue")]
+async fn then_pending_tokens(case: &mut BridgeWorld, count: i64) {
+    let queue_manager = case.queue_manager.as_ref().unwrap();
+    assert_eq!(count, queue_manager.count_pending_items().await.unwrap());
+}
+
+#[tokio::main]
+async fn main() {
     let validator = Arc::new(TestSignedHashValidator {});
     let starknet_manager = Arc::new(InMemoryStarknetTransactionManager::new());
     let data_repository = Arc::new(InMemoryDataRepository::new());
     let queue_manager = Arc::new(InMemoryQueueManager::new());
+    let wallet_link_repository = Arc::new(InMemoryWalletLinkRepository::new());
 
     let world = BridgeWorld::cucumber().before(move |_feature, _rule, _scenario, _world| {
         _world.with_signed_hash_validator(validator.clone());
         _world.with_starknet_manager(starknet_manager.clone());
         _world.with_data_repository(data_repository.clone());
         _world.with_queue_manager(queue_manager.clone());
+        _world.with_wallet_link_repository(wallet_link_repository.clone());
         Box::pin(ready(()))
     });
 
-    futures::executor::block_on(world.run_and_exit("features/bridge.feature"));
+    world.run_and_exit("features/bridge.feature").await;
 }
@@ -0,0 +1,207 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        bridge::{
+            QueueManager, StarknetManager, Transaction, TransactionFetchError,
+            TransactionRepository,
+        },
+        clock::SystemClock,
+        consume_queue::{consume_queue, ConsumerError, MintRetryPolicy},
+        ids::StarknetAddress,
+        post_mint::PostMintHooks,
+        webhook::WebhookNotifier,
+    },
+    infrastructure::{
+        chaos::{
+            ChaosQueueManager, ChaosStarknetManager, ChaosTransactionRepository, FailureInjector,
+            FailureInjectorError, FailureRates,
+        },
+        in_memory::{
+            InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+            InMemoryQueueManager, InMemoryStarknetTransactionManager,
+            InMemoryTransactionRepository, InMemoryWebhookRepository,
+        },
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const KEPLR_WALLET: &str = "k3plr-pk1";
+
+#[derive(Debug, World)]
+struct ChaosWorld {
+    // Same queue without injected failures, assertions read through it
+    queue: Arc<dyn QueueManager>,
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: Arc<dyn StarknetManager>,
+    transaction_repository: Arc<dyn TransactionRepository>,
+    retry_policy: MintRetryPolicy,
+    refused: Option<FailureInjectorError>,
+    fetch: Option<Result<Vec<Transaction>, TransactionFetchError>>,
+    failed_to_get_batch: bool,
+}
+
+impl Default for ChaosWorld {
+    fn default() -> Self {
+        let queue: Arc<dyn QueueManager> = Arc::new(InMemoryQueueManager::new());
+        Self {
+            queue: queue.clone(),
+            queue_manager: queue,
+            starknet_manager: Arc::new(InMemoryStarknetTransactionManager::new()),
+            transaction_repository: Arc::new(InMemoryTransactionRepository::new(Vec::new())),
+            retry_policy: MintRetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::ZERO,
+                max_delay: Duration::ZERO,
+            },
+            refused: None,
+            fetch: None,
+            failed_to_get_batch: false,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+fn rates(juno: f64, starknet: f64, database: f64) -> FailureRates {
+    FailureRates {
+        juno_server_error: juno,
+        starknet_rejection: starknet,
+        database_timeout: database,
+    }
+}
+
+async fn consume(world: &mut ChaosWorld) {
+    let res = consume_queue(
+        world.queue_manager.clone(),
+        world.starknet_manager.clone(),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(WebhookNotifier::new(
+            Arc::new(InMemoryWebhookRepository::new()),
+            Arc::new(SystemClock),
+        )),
+        Arc::new(BatchAnalytics::new(
+            Arc::new(InMemoryBatchAnalyticsRepository::new()),
+            Arc::new(SystemClock),
+            Duration::from_secs(86_400),
+        )),
+        &[],
+        &world.retry_policy,
+        &CancellationToken::new(),
+    )
+    .await;
+    world.failed_to_get_batch = matches!(res, Err(ConsumerError::FailedToGetNextBatch));
+}
+
+#[given(expr = "a failure injector with rates juno {float}, starknet {float} and database {float}")]
+fn given_a_failure_injector(world: &mut ChaosWorld, juno: f64, starknet: f64, database: f64) {
+    let injector = Arc::new(
+        FailureInjector::new(rates(juno, starknet, database), Duration::from_millis(10)).unwrap(),
+    );
+    world.queue_manager = Arc::new(ChaosQueueManager::new(
+        world.queue_manager.clone(),
+        injector.clone(),
+    ));
+    world.starknet_manager = Arc::new(ChaosStarknetManager::new(
+        world.starknet_manager.clone(),
+        injector.clone(),
+    ));
+    world.transaction_repository = Arc::new(ChaosTransactionRepository::new(
+        world.transaction_repository.clone(),
+        injector,
+    ));
+}
+
+#[given(expr = "token {string} is queued")]
+async fn given_a_queued_token(world: &mut ChaosWorld, token: String) {
+    world
+        .queue
+        .enqueue(
+            &KEPLR_WALLET.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[when(expr = "transactions of project {string} are fetched")]
+async fn when_transactions_are_fetched(world: &mut ChaosWorld, project_id: String) {
+    world.fetch = Some(
+        world
+            .transaction_repository
+            .get_contract_transactions(&project_id.parse().unwrap(), &CancellationToken::new())
+            .await,
+    );
+}
+
+#[when("the worker consumes the queue")]
+async fn when_the_worker_consumes(world: &mut ChaosWorld) {
+    consume(world).await;
+}
+
+#[when(expr = "the worker consumes the queue {int} times")]
+async fn when_the_worker_consumes_times(world: &mut ChaosWorld, times: usize) {
+    for _ in 0..times {
+        consume(world).await;
+    }
+}
+
+#[when(expr = "a failure injector is configured with a juno rate of {float}")]
+fn when_a_failure_injector_is_configured(world: &mut ChaosWorld, juno: f64) {
+    world.refused = FailureInjector::new(rates(juno, 0.0, 0.0), Duration::ZERO).err();
+}
+
+#[then(expr = "the fetch should fail with a Juno server error {int}")]
+fn then_the_fetch_should_fail(world: &mut ChaosWorld, status: u16) {
+    match &world.fetch {
+        Some(Err(TransactionFetchError::JunoBlockchainServerError(s))) => assert_eq!(status, *s),
+        r => panic!("Fetch should fail, got {:#?}", r),
+    }
+}
+
+#[then(expr = "token {string} should be {string} after {int} attempt(s)")]
+async fn then_token_should_be(
+    world: &mut ChaosWorld,
+    token: String,
+    status: String,
+    attempts: i32,
+) {
+    let qi = world
+        .queue
+        .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &project())
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued");
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(attempts, qi.attempts);
+}
+
+#[then("the worker should have failed to get its batch")]
+fn then_the_worker_should_have_failed(world: &mut ChaosWorld) {
+    assert!(world.failed_to_get_batch);
+}
+
+#[then("the failure injector should be refused")]
+fn then_the_failure_injector_should_be_refused(world: &mut ChaosWorld) {
+    assert!(matches!(
+        world.refused,
+        Some(FailureInjectorError::InvalidRate(_))
+    ));
+}
+
+#[tokio::main]
+async fn main() {
+    ChaosWorld::cucumber()
+        .run_and_exit("features/chaos.feature")
+        .await;
+}
@@ -0,0 +1,284 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        breakglass::Operator,
+        bridge::{QueueItem, QueueManager, QueueStatus},
+        consume_queue::{consume_queue, MintRetryPolicy},
+        ids::StarknetAddress,
+        issue_tracker::{report_dead_letters, IssueReporter},
+        pagination::PageRequest,
+        post_mint::PostMintHooks,
+        project_registry::{
+            MigrationPolicy, MigrationWindow, Project, ProjectRegistry, TransferWindow,
+        },
+        queue_admin::{handle_requeue_dead_letter, QueueAdminError},
+        webhook::WebhookNotifier,
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryDataRepository, InMemoryIssueRecordRepository,
+        InMemoryIssueTracker, InMemoryPostMintExecutionRepository, InMemoryQueueManager,
+        InMemoryStarknetTransactionManager, InMemoryTransactionRepository,
+        InMemoryWalletLinkRepository, InMemoryWebhookRepository, ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const KEPLR_WALLET: &str = "k3plr-pk1";
+
+#[derive(Debug, World)]
+struct DeadLetterWorld {
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    clock: ManualClock,
+    retry_policy: MintRetryPolicy,
+    issue_tracker: InMemoryIssueTracker,
+    issue_reporter: IssueReporter,
+    result: Option<Result<QueueItem, QueueAdminError>>,
+}
+
+impl Default for DeadLetterWorld {
+    fn default() -> Self {
+        let clock = ManualClock::new(1_672_531_200_000);
+        let issue_tracker = InMemoryIssueTracker::new();
+        Self {
+            queue_manager: Arc::new(InMemoryQueueManager::with_clock(Arc::new(clock.clone()))),
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            clock,
+            retry_policy: MintRetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_secs(60),
+                max_delay: Duration::from_secs(300),
+            },
+            issue_reporter: IssueReporter::new(
+                Arc::new(issue_tracker.clone()),
+                Arc::new(InMemoryIssueRecordRepository::new()),
+                Arc::new(clock.clone()),
+            ),
+            issue_tracker,
+            result: None,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+async fn queued_token(world: &DeadLetterWorld, token: &str) -> QueueItem {
+    world
+        .queue_manager
+        .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &project())
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued")
+}
+
+async fn consume(world: &DeadLetterWorld) {
+    let _ = consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(WebhookNotifier::new(
+            Arc::new(InMemoryWebhookRepository::new()),
+            Arc::new(world.clock.clone()),
+        )),
+        Arc::new(BatchAnalytics::new(
+            Arc::new(InMemoryBatchAnalyticsRepository::new()),
+            Arc::new(world.clock.clone()),
+            Duration::from_secs(86_400),
+        )),
+        &[],
+        &world.retry_policy,
+        &CancellationToken::new(),
+    )
+    .await;
+}
+
+#[given("the issue tracker is down")]
+fn given_the_issue_tracker_is_down(world: &mut DeadLetterWorld) {
+    world.issue_tracker.fail_issues(true);
+}
+
+#[given("the issue tracker is up")]
+fn given_the_issue_tracker_is_up(world: &mut DeadLetterWorld) {
+    world.issue_tracker.fail_issues(false);
+}
+
+#[given(expr = "token {string} is queued")]
+async fn given_a_queued_token(world: &mut DeadLetterWorld, token: String) {
+    world
+        .queue_manager
+        .enqueue(
+            &KEPLR_WALLET.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given("starknet mints fail")]
+fn given_mints_fail(world: &mut DeadLetterWorld) {
+    world.starknet_manager.fail_mints(true);
+}
+
+#[given("starknet mints succeed")]
+fn given_mints_succeed(world: &mut DeadLetterWorld) {
+    world.starknet_manager.fail_mints(false);
+}
+
+#[when("the worker consumes the queue")]
+async fn when_the_worker_consumes(world: &mut DeadLetterWorld) {
+    consume(world).await;
+}
+
+// Waits out the longest backoff between two runs so every run retries
+#[when(expr = "the worker consumes the queue {int} times")]
+async fn when_the_worker_consumes_times(world: &mut DeadLetterWorld, times: usize) {
+    for _ in 0..times {
+        consume(world).await;
+        world.clock.advance(world.retry_policy.max_delay);
+    }
+}
+
+#[when(expr = "{int} seconds elapse")]
+fn when_seconds_elapse(world: &mut DeadLetterWorld, seconds: u64) {
+    world.clock.advance(Duration::from_secs(seconds));
+}
+
+#[when("1 hour elapses")]
+fn when_an_hour_elapses(world: &mut DeadLetterWorld) {
+    world.clock.advance(Duration::from_secs(3600));
+}
+
+#[when(expr = "the operator requeues dead letter {string}")]
+async fn when_the_operator_requeues(world: &mut DeadLetterWorld, token: String) {
+    let qi = queued_token(world, &token).await;
+    let operator = Operator {
+        name: "alice".into(),
+        api_key: "secret".into(),
+    };
+    world.result = Some(
+        handle_requeue_dead_letter(&qi.id.unwrap(), &operator, world.queue_manager.clone()).await,
+    );
+}
+
+#[when("dead letters are reported")]
+async fn when_dead_letters_are_reported(world: &mut DeadLetterWorld) {
+    let registry = ProjectRegistry::new(vec![Project {
+        juno_contract: "projectId".parse().unwrap(),
+        starknet_contract: project(),
+        juno_admin_address: "juno-admin-account".parse().unwrap(),
+        mint_selector: "mint".into(),
+        migration_window: MigrationWindow::default(),
+        migration_policy: MigrationPolicy::TransferToAdmin,
+        transfer_window: TransferWindow::default(),
+    }]);
+    report_dead_letters(
+        &world.issue_reporter,
+        world.queue_manager.clone(),
+        &registry,
+        Arc::new(InMemoryDataRepository::new()),
+        Arc::new(InMemoryWalletLinkRepository::new()),
+        Arc::new(InMemoryTransactionRepository::new(Vec::new())),
+        Arc::new(world.starknet_manager.clone()),
+        &CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+}
+
+#[then(expr = "token {string} should be {string} after {int} attempt(s)")]
+async fn then_token_should_be(
+    world: &mut DeadLetterWorld,
+    token: String,
+    status: String,
+    attempts: i32,
+) {
+    let qi = queued_token(world, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(attempts, qi.attempts);
+}
+
+#[then(expr = "the dead letter queue should list token {string}")]
+async fn then_dead_letters_should_list(world: &mut DeadLetterWorld, token: String) {
+    let page = world
+        .queue_manager
+        .list_queue_items(
+            Some(QueueStatus::DeadLetter),
+            &PageRequest::new(None, None, 100).unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(page.items.iter().any(|qi| qi.token_id == token));
+}
+
+#[then("the dead letter queue should be empty")]
+async fn then_dead_letters_should_be_empty(world: &mut DeadLetterWorld) {
+    let page = world
+        .queue_manager
+        .list_queue_items(
+            Some(QueueStatus::DeadLetter),
+            &PageRequest::new(None, None, 100).unwrap(),
+        )
+        .await
+        .unwrap();
+    assert!(page.items.is_empty());
+}
+
+#[then(expr = "{int} issue(s) should have been opened")]
+async fn then_issues_should_have_been_opened(world: &mut DeadLetterWorld, count: usize) {
+    assert_eq!(count, world.issue_tracker.issues().await.len());
+}
+
+#[then(expr = "the last issue should list token {string}")]
+async fn then_the_last_issue_should_list(world: &mut DeadLetterWorld, token: String) {
+    let issues = world.issue_tracker.issues().await;
+    let issue = issues.last().expect("An issue should have been opened");
+    assert!(issue.body.contains(&format!("- token {} ", token)));
+}
+
+#[then("the last issue should have the support bundle attached")]
+async fn then_the_last_issue_should_have_the_bundle(world: &mut DeadLetterWorld) {
+    let issues = world.issue_tracker.issues().await;
+    let issue = issues.last().expect("An issue should have been opened");
+    let attachment = issue
+        .attachment
+        .as_ref()
+        .expect("Support bundle should be attached");
+    let bundle: serde_json::Value = serde_json::from_str(&attachment.content).unwrap();
+    assert_eq!(KEPLR_WALLET, bundle["keplr_wallet_pubkey"]);
+}
+
+#[then("the requeue should be accepted")]
+fn then_requeue_should_be_accepted(world: &mut DeadLetterWorld) {
+    match &world.result {
+        Some(Ok(_)) => (),
+        r => panic!("Requeue should be accepted, got {:#?}", r),
+    }
+}
+
+#[then(expr = "the requeue should be rejected because of its {string} status")]
+fn then_requeue_should_be_rejected(world: &mut DeadLetterWorld, status: String) {
+    match &world.result {
+        Some(Err(QueueAdminError::InvalidStatus(s))) => {
+            assert_eq!(serde_json::json!(status), serde_json::json!(s))
+        }
+        r => panic!("Requeue should be rejected, got {:#?}", r),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    DeadLetterWorld::cucumber()
+        .run_and_exit("features/dead_letter.feature")
+        .await;
+}
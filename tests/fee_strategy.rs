@@ -0,0 +1,216 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        bridge::QueueManager,
+        consume_queue::{consume_queue, MintRetryPolicy},
+        fee_strategy::{
+            fee_strategy_for_network, FeeError, FeeMultipliers, FeeStrategy, FeeStrategyError,
+        },
+        ids::StarknetAddress,
+        post_mint::PostMintHooks,
+        webhook::WebhookNotifier,
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+        InMemoryQueueManager, InMemoryStarknetTransactionManager, InMemoryWebhookRepository,
+        ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const KEPLR_WALLET: &str = "k3plr-pk1";
+
+#[derive(Debug, World)]
+struct FeeStrategyWorld {
+    multiplier: f64,
+    multipliers: Vec<String>,
+    definitions: Vec<String>,
+    strategy: Option<(String, Arc<dyn FeeStrategy>)>,
+    max_fee: Option<Result<u128, FeeError>>,
+    queue_manager: Arc<dyn QueueManager>,
+    project: Option<StarknetAddress>,
+}
+
+impl Default for FeeStrategyWorld {
+    fn default() -> Self {
+        Self {
+            multiplier: 1.0,
+            multipliers: Vec::new(),
+            definitions: Vec::new(),
+            strategy: None,
+            max_fee: None,
+            queue_manager: Arc::new(InMemoryQueueManager::new()),
+            project: None,
+        }
+    }
+}
+
+impl FeeStrategyWorld {
+    fn parse(&self, network: &str) -> Result<Arc<dyn FeeStrategy>, FeeStrategyError> {
+        fee_strategy_for_network(
+            network,
+            &self.definitions,
+            FeeMultipliers::parse(self.multiplier, &self.multipliers).unwrap(),
+        )
+    }
+
+    // The same strategy is kept for a network, as the worker process does
+    fn strategy(&mut self, network: &str) -> Arc<dyn FeeStrategy> {
+        if let Some((n, strategy)) = &self.strategy {
+            if n == network {
+                return strategy.clone();
+            }
+        }
+        let strategy = self.parse(network).unwrap();
+        self.strategy = Some((network.to_string(), strategy.clone()));
+        strategy
+    }
+}
+
+#[given(expr = "fee estimates are multiplied by {float}")]
+fn given_default_multiplier(world: &mut FeeStrategyWorld, multiplier: f64) {
+    world.multiplier = multiplier;
+}
+
+#[given(expr = "project {string} fee estimates are multiplied by {float}")]
+fn given_project_multiplier(world: &mut FeeStrategyWorld, project: String, multiplier: f64) {
+    world
+        .multipliers
+        .push(format!("{}={}", project, multiplier));
+}
+
+#[given(expr = "the {string} network uses the {string} fee strategy")]
+fn given_network_strategy(world: &mut FeeStrategyWorld, network: String, strategy: String) {
+    world.definitions.push(format!("{}={}", network, strategy));
+}
+
+#[given(expr = "tokens {string} are queued on project {string}")]
+async fn given_queued_tokens(world: &mut FeeStrategyWorld, tokens: String, project: String) {
+    let project: StarknetAddress = project.parse().unwrap();
+    world
+        .queue_manager
+        .enqueue(
+            &KEPLR_WALLET.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project,
+            tokens.split(',').map(|t| t.parse().unwrap()).collect(),
+            None,
+        )
+        .await
+        .unwrap();
+    world.project = Some(project);
+}
+
+#[when(
+    regex = r#"^the max fee of (\d+) calls? on project "(\S+)" estimated to (\d+) is computed on "(\S+)"$"#
+)]
+fn when_max_fee_is_computed(
+    world: &mut FeeStrategyWorld,
+    calls: usize,
+    project: String,
+    estimated_fee: u128,
+    network: String,
+) {
+    let strategy = world.strategy(&network);
+    world.max_fee = Some(strategy.max_fee(&project.parse().unwrap(), estimated_fee, calls));
+}
+
+#[when(
+    regex = r#"^a transaction of (\d+) calls? on project "(\S+)" was accepted for (\d+) on "(\S+)"$"#
+)]
+fn when_transaction_was_accepted(
+    world: &mut FeeStrategyWorld,
+    calls: usize,
+    project: String,
+    actual_fee: u128,
+    network: String,
+) {
+    world
+        .strategy(&network)
+        .record_accepted_fee(&project.parse().unwrap(), actual_fee, calls);
+}
+
+#[when(expr = "the worker consumes the queue on {string}")]
+async fn when_the_worker_consumes(world: &mut FeeStrategyWorld, network: String) {
+    let clock = ManualClock::new(1_672_531_200_000);
+    let starknet_manager = InMemoryStarknetTransactionManager::new();
+    starknet_manager
+        .use_fee_strategy(world.strategy(&network))
+        .await;
+    let _ = consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(starknet_manager),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(WebhookNotifier::new(
+            Arc::new(InMemoryWebhookRepository::new()),
+            Arc::new(clock.clone()),
+        )),
+        Arc::new(BatchAnalytics::new(
+            Arc::new(InMemoryBatchAnalyticsRepository::new()),
+            Arc::new(clock),
+            Duration::from_secs(86_400),
+        )),
+        &[],
+        &MintRetryPolicy::default(),
+        &CancellationToken::new(),
+    )
+    .await;
+}
+
+#[then(expr = "the max fee should be {int}")]
+fn then_max_fee_should_be(world: &mut FeeStrategyWorld, max_fee: u128) {
+    match &world.max_fee {
+        Some(Ok(fee)) => assert_eq!(max_fee, *fee),
+        r => panic!("Max fee should be computed, got {:#?}", r),
+    }
+}
+
+#[then("the fee should be above the cap")]
+fn then_fee_above_cap(world: &mut FeeStrategyWorld) {
+    match &world.max_fee {
+        Some(Err(FeeError::AboveCap { .. })) => (),
+        r => panic!("Fee should be above the cap, got {:#?}", r),
+    }
+}
+
+#[then("the fee strategies should be refused")]
+fn then_strategies_refused(world: &mut FeeStrategyWorld) {
+    match world.parse("mainnet") {
+        Err(FeeStrategyError::InvalidDefinition(_)) => (),
+        Ok(_) => panic!("Fee strategies {:#?} should be refused", world.definitions),
+    }
+}
+
+#[then(expr = "token {string} should be {string} with note {string} after {int} attempts")]
+async fn then_token_should_be(
+    world: &mut FeeStrategyWorld,
+    token: String,
+    status: String,
+    note: String,
+    attempts: i32,
+) {
+    let qi = world
+        .queue_manager
+        .get_customer_migration_state(
+            &KEPLR_WALLET.parse().unwrap(),
+            world.project.as_ref().unwrap(),
+        )
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued");
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(Some(note), qi.note);
+    assert_eq!(attempts, qi.attempts);
+}
+
+#[tokio::main]
+async fn main() {
+    FeeStrategyWorld::cucumber()
+        .run_and_exit("features/fee_strategy.feature")
+        .await;
+}
@@ -0,0 +1,136 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::funnel::{FunnelAnalytics, FunnelEventRequest, FunnelStep},
+    infrastructure::in_memory::{InMemoryFunnelEventRepository, ManualClock},
+};
+use cucumber::{given, then, when, World};
+use uuid::Uuid;
+
+const NOW_MS: i64 = 1_700_000_000_000;
+const DAY: Duration = Duration::from_secs(86_400);
+
+#[derive(Debug, World)]
+struct FunnelWorld {
+    repository: InMemoryFunnelEventRepository,
+    clock: ManualClock,
+    sample_rate: f64,
+    retention: Duration,
+    pruned: Option<u64>,
+}
+
+impl Default for FunnelWorld {
+    fn default() -> Self {
+        Self {
+            repository: InMemoryFunnelEventRepository::new(),
+            clock: ManualClock::new(NOW_MS),
+            sample_rate: 1.0,
+            retention: 30 * DAY,
+            pruned: None,
+        }
+    }
+}
+
+impl FunnelWorld {
+    fn analytics(&self) -> FunnelAnalytics {
+        FunnelAnalytics::new(
+            Arc::new(self.repository.clone()),
+            Arc::new(self.clock.clone()),
+            self.sample_rate,
+            self.retention,
+        )
+    }
+
+    async fn record_sessions(&self, sessions: usize, steps: &[FunnelStep]) {
+        let analytics = self.analytics();
+        for _ in 0..sessions {
+            let session_id = Uuid::new_v4().to_string();
+            for step in steps {
+                analytics
+                    .record(&FunnelEventRequest {
+                        event: *step,
+                        session_id: session_id.clone(),
+                        project_id: None,
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    async fn events_per_session(&self) -> HashMap<String, usize> {
+        let mut sessions = HashMap::new();
+        for event in self.repository.events().await {
+            *sessions.entry(event.session_id).or_insert(0) += 1;
+        }
+        sessions
+    }
+}
+
+#[given(expr = "funnel events are sampled at {float}")]
+fn given_sample_rate(world: &mut FunnelWorld, sample_rate: f64) {
+    world.sample_rate = sample_rate;
+}
+
+#[given(expr = "funnel events are kept {int} days")]
+fn given_retention(world: &mut FunnelWorld, days: u32) {
+    world.retention = days * DAY;
+}
+
+#[given(expr = "{int} sessions connected their wallet {int} days ago")]
+async fn given_past_sessions(world: &mut FunnelWorld, sessions: usize, days: u32) {
+    world.clock.set(NOW_MS - (days * DAY).as_millis() as i64);
+    world
+        .record_sessions(sessions, &[FunnelStep::WalletConnected])
+        .await;
+    world.clock.set(NOW_MS);
+}
+
+#[when(expr = "{int} sessions connect their wallet and submit a bridge request")]
+async fn when_sessions_go_through_funnel(world: &mut FunnelWorld, sessions: usize) {
+    world
+        .record_sessions(
+            sessions,
+            &[FunnelStep::WalletConnected, FunnelStep::BridgeSubmitted],
+        )
+        .await;
+}
+
+#[when("funnel events are pruned")]
+async fn when_pruning(world: &mut FunnelWorld) {
+    world.pruned = Some(world.analytics().prune().await.unwrap());
+}
+
+#[then(expr = "{int} funnel event(s) should be recorded")]
+async fn then_events_are_recorded(world: &mut FunnelWorld, count: usize) {
+    assert_eq!(count, world.repository.events().await.len());
+}
+
+#[then(expr = "every recorded session should have {int} funnel events")]
+async fn then_sessions_are_complete(world: &mut FunnelWorld, count: usize) {
+    for (session, events) in world.events_per_session().await {
+        assert_eq!(count, events, "session {}", session);
+    }
+}
+
+#[then(expr = "between {int} and {int} sessions should be recorded")]
+async fn then_sessions_are_sampled(world: &mut FunnelWorld, min: usize, max: usize) {
+    let sessions = world.events_per_session().await.len();
+    assert!(
+        (min..=max).contains(&sessions),
+        "{} sessions recorded",
+        sessions
+    );
+}
+
+#[then(expr = "{int} funnel events should have been pruned")]
+fn then_events_are_pruned(world: &mut FunnelWorld, count: u64) {
+    assert_eq!(Some(count), world.pruned);
+}
+
+#[tokio::main]
+async fn main() {
+    FunnelWorld::cucumber()
+        .run_and_exit("features/funnel.feature")
+        .await;
+}
@@ -0,0 +1,1185 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::{
+    body::to_bytes,
+    http::header::{
+        HeaderMap, ACCEPT, ACCEPT_LANGUAGE, ACCESS_CONTROL_ALLOW_ORIGIN,
+        ACCESS_CONTROL_REQUEST_METHOD, AUTHORIZATION, CONTENT_TYPE, ORIGIN,
+    },
+    middleware::from_fn,
+    test, web, App, ResponseError,
+};
+use async_trait::async_trait;
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::{BatchAnalytics, BatchAnalyticsRepository, BatchOutcome, BatchRecord},
+        attestation::AttestationSigner,
+        backpressure::QueueBackpressure,
+        breakglass::Operator,
+        bridge::{QueueManager, QueueStatus, StarknetManager, Transaction},
+        calendar::parse_timestamp,
+        challenge::ChallengeService,
+        check_cache::CheckResultCache,
+        clock::{Clock, SystemClock},
+        consume_queue::{MintRetryPolicy, TRANSACTION_REJECTED_NOTE},
+        funnel::FunnelAnalytics,
+        health::{HealthCheck, HealthCheckError},
+        ids::QueueItemId,
+        maintenance::{DatabaseMaintenance, TableStats},
+        metrics::{Metrics, NoopMetrics},
+        migration_watch::MigrationWatch,
+        post_mint::PostMintHooks,
+        project_registry::{
+            MigrationPolicy, MigrationWindow, Project, ProjectRegistry, TransferWindow,
+        },
+        report::ReportSigner,
+        save_customer_data::{CustomerKeys, DataRepository},
+        stats::PublicStatsCache,
+        status_message::StatusNote,
+        wallet_link::{WalletLink, WalletLinkRepository},
+        webhook::{WebhookNotifier, WebhookRepository, WebhookRetryPolicy},
+    },
+    infrastructure::{
+        app::Config,
+        http::{
+            admin::{
+                batch_analytics, bridge_requests, cancel_queue_item, database_maintenance,
+                inspect_queue_item, queue_browser, queue_item_history, requeue_queue_item,
+                requeue_stale_processing_items,
+            },
+            handlers::{
+                bridge, challenge, cors, get_customer_migration_state, get_customer_proof_bundle,
+                get_eligible_tokens, get_transaction_status, get_wallet_migrations, health,
+                json_config, ready, record_event, save_customer_tokens,
+            },
+            metrics::{record_metrics, scrape_metrics},
+            openapi::openapi_spec,
+            rate_limit::{rate_limit, RateLimiter},
+            request_id::{request_id, X_REQUEST_ID},
+            schema_version::{schema_version, X_BRIDGE_SCHEMA_VERSION},
+            HttpClientConfig,
+        },
+        in_memory::{
+            InMemoryAuditRepository, InMemoryBatchAnalyticsRepository,
+            InMemoryBreakglassRepository, InMemoryChallengeRepository,
+            InMemoryCheckResultRepository, InMemoryDataRepository, InMemoryFunnelEventRepository,
+            InMemoryJunoContractQuerier, InMemoryMaintenanceRepository,
+            InMemoryPostMintExecutionRepository, InMemoryQueueManager, InMemoryReportRepository,
+            InMemoryReverseQueueManager, InMemoryStarknetTransactionManager,
+            InMemoryStarknetTransferVerifier, InMemoryStatsRepository,
+            InMemoryTransactionRepository, InMemoryTransferProofRepository,
+            InMemoryWalletLinkRepository, InMemoryWebhookRepository, TestSignedHashValidator,
+        },
+        juno::JunoRetryPolicy,
+        jwt::{HmacJwtVerifier, JwtClaims},
+        metrics::PrometheusMetrics,
+        report::HmacReportSigner,
+        starknet::{CalldataTemplates, StarkAttestationSigner, TokenIdFormats},
+    },
+};
+use cucumber::{gherkin::Step, given, then, when, World};
+use reqwest::Url;
+use serde_json::{json, Value};
+use starknet::{
+    core::{
+        chain_id,
+        crypto::{ecdsa_verify, Signature},
+        types::FieldElement,
+    },
+    providers::SequencerGatewayProvider,
+};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+const FRONTEND_URI: &str = "http://frontend.test";
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const ADMIN_JWT_SECRET: &str = "admin-jwt-secret";
+
+/// Dependency of the readiness probe, `None` answers, otherwise fails with the reason.
+#[derive(Debug)]
+struct StubHealthCheck {
+    name: String,
+    failure: Option<String>,
+}
+
+#[async_trait]
+impl HealthCheck for StubHealthCheck {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> Result<(), HealthCheckError> {
+        match &self.failure {
+            None => Ok(()),
+            Some(reason) if reason == "hanging" => {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            }
+            Some(reason) => Err(HealthCheckError::Failed(reason.clone())),
+        }
+    }
+}
+
+#[derive(Debug, World)]
+struct HttpWorld {
+    transactions: Vec<Transaction>,
+    juno_contract: InMemoryJunoContractQuerier,
+    data_repository: InMemoryDataRepository,
+    queue_manager: Arc<dyn QueueManager>,
+    wallet_link_repository: Arc<dyn WalletLinkRepository>,
+    challenge_repository: InMemoryChallengeRepository,
+    challenge_ttl: Duration,
+    require_challenge: bool,
+    require_schema_version: bool,
+    migration_window: MigrationWindow,
+    nonce: Option<String>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    queue_backpressure: Option<QueueBackpressure>,
+    metrics: Arc<dyn Metrics>,
+    batch_analytics_repository: InMemoryBatchAnalyticsRepository,
+    funnel_event_repository: InMemoryFunnelEventRepository,
+    funnel_sample_rate: f64,
+    maintenance_repository: InMemoryMaintenanceRepository,
+    health_checks: Vec<Arc<dyn HealthCheck>>,
+    audit_repository: InMemoryAuditRepository,
+    transfer_proof_repository: InMemoryTransferProofRepository,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    proof_bundle_signer: Option<Arc<dyn ReportSigner>>,
+    attestation_signer: Option<Arc<dyn AttestationSigner>>,
+    shutdown: CancellationToken,
+    operators: Vec<Operator>,
+    queued: Option<String>,
+    status: Option<u16>,
+    allowed_origin: Option<String>,
+    content_type: Option<String>,
+    headers: Option<HeaderMap>,
+    text: String,
+    body: Option<Value>,
+    elapsed: Duration,
+}
+
+impl Default for HttpWorld {
+    fn default() -> Self {
+        Self {
+            transactions: Vec::new(),
+            juno_contract: InMemoryJunoContractQuerier::new(),
+            data_repository: InMemoryDataRepository::new(),
+            queue_manager: Arc::new(InMemoryQueueManager::new()),
+            wallet_link_repository: Arc::new(InMemoryWalletLinkRepository::new()),
+            challenge_repository: InMemoryChallengeRepository::new(),
+            challenge_ttl: Duration::from_secs(300),
+            require_challenge: false,
+            require_schema_version: false,
+            migration_window: MigrationWindow::default(),
+            nonce: None,
+            rate_limiter: None,
+            queue_backpressure: None,
+            metrics: Arc::new(NoopMetrics),
+            batch_analytics_repository: InMemoryBatchAnalyticsRepository::new(),
+            funnel_event_repository: InMemoryFunnelEventRepository::new(),
+            funnel_sample_rate: 1.0,
+            maintenance_repository: InMemoryMaintenanceRepository::new(),
+            health_checks: Vec::new(),
+            audit_repository: InMemoryAuditRepository::new(),
+            transfer_proof_repository: InMemoryTransferProofRepository::new(),
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            proof_bundle_signer: None,
+            attestation_signer: None,
+            shutdown: CancellationToken::new(),
+            operators: Vec::new(),
+            queued: None,
+            status: None,
+            allowed_origin: None,
+            content_type: None,
+            headers: None,
+            text: String::new(),
+            body: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// Application configuration wired with in-memory ports only.
+fn config(world: &HttpWorld) -> Config {
+    let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+    let webhook_repository: Arc<dyn WebhookRepository> = Arc::new(InMemoryWebhookRepository::new());
+    Config {
+        juno_lcd: String::new(),
+        juno_lcd_max_pages: 1,
+        juno_retry_policy: JunoRetryPolicy::default(),
+        database_url: String::new(),
+        data_repository: Arc::new(world.data_repository.clone()),
+        queue_manager: world.queue_manager.clone(),
+        queue_broker: None,
+        queue_poll_interval: Duration::from_secs(60),
+        queue_backpressure: world.queue_backpressure.clone(),
+        migration_watch: MigrationWatch::new(Duration::from_millis(20), Duration::from_secs(60)),
+        transaction_repository: Arc::new(InMemoryTransactionRepository::new(
+            world.transactions.clone(),
+        )),
+        juno_contract_querier: Arc::new(world.juno_contract.clone()),
+        verify_juno_ownership: false,
+        transfer_proof_repository: Arc::new(world.transfer_proof_repository.clone()),
+        signed_hash_validator: Arc::new(TestSignedHashValidator {}),
+        starknet_manager: Arc::new(world.starknet_manager.clone()),
+        stats_repository: Arc::new(InMemoryStatsRepository::default()),
+        public_stats_cache: Arc::new(PublicStatsCache::new(
+            Duration::from_secs(60),
+            clock.clone(),
+        )),
+        wallet_link_repository: world.wallet_link_repository.clone(),
+        challenges: Arc::new(ChallengeService::new(
+            Arc::new(world.challenge_repository.clone()),
+            clock.clone(),
+            world.challenge_ttl,
+            world.require_challenge,
+        )),
+        require_sign_doc: false,
+        require_schema_version: world.require_schema_version,
+        check_cache: Arc::new(CheckResultCache::new(
+            Arc::new(InMemoryCheckResultRepository::new()),
+            clock.clone(),
+            Duration::ZERO,
+        )),
+        audit_repository: Arc::new(world.audit_repository.clone()),
+        breakglass_repository: Arc::new(InMemoryBreakglassRepository::new()),
+        operators: world.operators.clone(),
+        admin_jwt_verifier: Some(Arc::new(HmacJwtVerifier::new(ADMIN_JWT_SECRET))),
+        breakglass_confirmation_window: Duration::from_secs(300),
+        bridge_request_budget: Duration::from_secs(25),
+        rate_limiter: world.rate_limiter.clone(),
+        starknet_provider: Arc::new(SequencerGatewayProvider::new(
+            Url::parse("http://127.0.0.1:5050/gateway").unwrap(),
+            Url::parse("http://127.0.0.1:5050/feeder_gateway").unwrap(),
+        )),
+        project_registry: Arc::new(ProjectRegistry::new(vec![Project {
+            juno_contract: "projectId".parse().unwrap(),
+            starknet_contract: STARKNET_PROJECT_ADDR.parse().unwrap(),
+            juno_admin_address: "juno-admin-account".parse().unwrap(),
+            mint_selector: "mint".into(),
+            migration_window: world.migration_window,
+            migration_policy: MigrationPolicy::TransferToAdmin,
+            transfer_window: TransferWindow::default(),
+        }])),
+        starknet_admin_address: "0xad0".into(),
+        starknet_private_key: "0x1".into(),
+        frontend_uri: FRONTEND_URI.into(),
+        chain_id: chain_id::TESTNET,
+        calldata_templates: Arc::new(CalldataTemplates::parse(&[]).unwrap()),
+        post_mint_hooks: Arc::new(PostMintHooks::new()),
+        post_mint_repository: Arc::new(InMemoryPostMintExecutionRepository::new()),
+        post_mint_max_attempts: 5,
+        mint_retry_policy: MintRetryPolicy::default(),
+        webhook_notifier: Arc::new(WebhookNotifier::new(
+            webhook_repository.clone(),
+            clock.clone(),
+        )),
+        webhook_repository,
+        webhook_sender: None,
+        webhook_retry_policy: WebhookRetryPolicy::default(),
+        batch_analytics: Arc::new(BatchAnalytics::new(
+            Arc::new(world.batch_analytics_repository.clone()),
+            clock.clone(),
+            Duration::from_secs(90 * 86_400),
+        )),
+        funnel_analytics: Arc::new(FunnelAnalytics::new(
+            Arc::new(world.funnel_event_repository.clone()),
+            clock.clone(),
+            world.funnel_sample_rate,
+            Duration::from_secs(30 * 86_400),
+        )),
+        database_maintenance: Some(Arc::new(DatabaseMaintenance::new(
+            Arc::new(world.maintenance_repository.clone()),
+            clock.clone(),
+            Duration::from_secs(300),
+            0.2,
+            false,
+        ))),
+        report_repository: Arc::new(InMemoryReportRepository::new()),
+        report_signer: None,
+        report_publisher: None,
+        proof_bundle_signer: world.proof_bundle_signer.clone(),
+        attestation_signer: world.attestation_signer.clone(),
+        issue_reporter: None,
+        object_storage: None,
+        object_storage_url_ttl: Duration::from_secs(3600),
+        http_client: HttpClientConfig::default(),
+        metrics: world.metrics.clone(),
+        clock,
+        shutdown: world.shutdown.clone(),
+        worker_shutdown_grace_period: Duration::from_secs(25),
+        reverse_queue_manager: Arc::new(InMemoryReverseQueueManager::new()),
+        starknet_transfer_verifier: Arc::new(InMemoryStarknetTransferVerifier::new()),
+        juno_tx_broadcaster: None,
+        reverse_batch_size: 20,
+        transaction_reconciler: None,
+        stale_processing_age: None,
+        warm_up_targets: Vec::new(),
+        warm_up_timeout: Duration::from_secs(10),
+        health_checks: world.health_checks.clone(),
+        health_check_timeout: Duration::from_millis(100),
+    }
+}
+
+async fn call(world: &mut HttpWorld, request: test::TestRequest) {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config(world)))
+            .app_data(json_config())
+            .wrap(from_fn(rate_limit))
+            .wrap(from_fn(schema_version))
+            .wrap(from_fn(record_metrics))
+            .wrap(cors(FRONTEND_URI))
+            .wrap(from_fn(request_id))
+            .service(health)
+            .service(ready)
+            .service(scrape_metrics)
+            .service(challenge)
+            .service(bridge)
+            .service(get_transaction_status)
+            .service(save_customer_tokens)
+            .service(record_event)
+            .service(get_customer_migration_state)
+            .service(get_wallet_migrations)
+            .service(get_customer_proof_bundle)
+            .service(get_eligible_tokens)
+            .service(openapi_spec)
+            .service(
+                web::scope("/admin")
+                    .service(queue_browser)
+                    .service(inspect_queue_item)
+                    .service(queue_item_history)
+                    .service(requeue_queue_item)
+                    .service(requeue_stale_processing_items)
+                    .service(cancel_queue_item)
+                    .service(batch_analytics)
+                    .service(bridge_requests)
+                    .service(database_maintenance),
+            ),
+    )
+    .await;
+
+    // Middlewares such as CORS reject requests with an error instead of a response
+    let started_at = std::time::Instant::now();
+    let (status, headers, body) = match test::try_call_service(&app, request.to_request()).await {
+        Ok(response) => (
+            response.status(),
+            response.headers().clone(),
+            test::read_body(response).await,
+        ),
+        Err(e) => {
+            let response = e.as_response_error().error_response();
+            (
+                response.status(),
+                response.headers().clone(),
+                to_bytes(response.into_body()).await.unwrap(),
+            )
+        }
+    };
+    world.elapsed = started_at.elapsed();
+    world.status = Some(status.as_u16());
+    world.allowed_origin = headers
+        .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+        .map(|v| v.to_str().unwrap().to_string());
+    world.content_type = headers
+        .get(CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string());
+    world.headers = Some(headers);
+    world.text = String::from_utf8_lossy(&body).to_string();
+    world.body = serde_json::from_slice(&body).ok();
+}
+
+#[given("the following juno transactions")]
+fn given_juno_transactions(world: &mut HttpWorld, step: &Step) {
+    world.transactions = serde_json::from_str(step.docstring.as_ref().unwrap()).unwrap();
+}
+
+#[given(expr = "the {string} dependency answers")]
+fn given_dependency_up(world: &mut HttpWorld, name: String) {
+    world.health_checks.push(Arc::new(StubHealthCheck {
+        name,
+        failure: None,
+    }));
+}
+
+#[given(expr = "the {string} dependency fails with {string}")]
+fn given_dependency_down(world: &mut HttpWorld, name: String, reason: String) {
+    world.health_checks.push(Arc::new(StubHealthCheck {
+        name,
+        failure: Some(reason),
+    }));
+}
+
+#[given(expr = "customer {word} registered tokens {string} on project {word}")]
+async fn given_registered_tokens(
+    world: &mut HttpWorld,
+    keplr: String,
+    tokens: String,
+    project: String,
+) {
+    let saved = world
+        .data_repository
+        .save_customer_keys(CustomerKeys {
+            keplr_wallet_pubkey: keplr.parse().unwrap(),
+            project_id: project.parse().unwrap(),
+            token_ids: tokens.split(", ").map(|t| t.parse().unwrap()).collect(),
+        })
+        .await;
+    assert!(saved.is_ok());
+}
+
+#[given(expr = "keplr wallet {word} holds tokens {string} on juno")]
+async fn given_wallet_holds_tokens(world: &mut HttpWorld, keplr: String, tokens: String) {
+    for token in tokens.split(", ") {
+        world
+            .juno_contract
+            .set_owner(
+                &"projectId".parse().unwrap(),
+                &token.parse().unwrap(),
+                &keplr.parse().unwrap(),
+            )
+            .await;
+    }
+}
+
+#[given(expr = "token {word} was already minted on starknet")]
+async fn given_token_minted(world: &mut HttpWorld, token: String) {
+    world
+        .starknet_manager
+        .mint_project_token(
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+            &[token.parse().unwrap()],
+            &"0x5700".parse().unwrap(),
+        )
+        .await
+        .unwrap_or_else(|_| panic!("Token {} should have been minted", token));
+}
+
+#[given(expr = "keplr wallet {word} is linked to starknet account {word}")]
+async fn given_wallet_is_linked(world: &mut HttpWorld, keplr: String, starknet: String) {
+    world
+        .wallet_link_repository
+        .save_link(WalletLink {
+            keplr_wallet_pubkey: keplr.parse().unwrap(),
+            starknet_account_addr: starknet.parse().unwrap(),
+        })
+        .await
+        .unwrap();
+}
+
+#[given("the service is shutting down")]
+fn given_service_is_shutting_down(world: &mut HttpWorld) {
+    world.shutdown.cancel();
+}
+
+#[given(expr = "token {string} of {word} is queued")]
+async fn given_token_is_queued(world: &mut HttpWorld, token: String, keplr: String) {
+    let queued = world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+    world.queued = queued[0].id.map(|id| id.to_string());
+}
+
+#[given(expr = "token {string} of {word} is queued on project {word}")]
+async fn given_token_is_queued_on_project(
+    world: &mut HttpWorld,
+    token: String,
+    keplr: String,
+    project: String,
+) {
+    world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project.parse().unwrap(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given(expr = "proof bundles are signed with {string}")]
+fn given_proof_bundle_signing_key(world: &mut HttpWorld, key: String) {
+    world.proof_bundle_signer = Some(Arc::new(HmacReportSigner::new(&key)));
+}
+
+#[given(expr = "mint attestations are signed with {word}")]
+fn given_attestation_private_key(world: &mut HttpWorld, key: String) {
+    world.attestation_signer = Some(Arc::new(StarkAttestationSigner::new(
+        &key,
+        chain_id::TESTNET,
+        Arc::new(TokenIdFormats::default()),
+    )));
+}
+
+#[given(expr = "the queued token was minted in transaction {word}")]
+async fn given_queued_token_minted(world: &mut HttpWorld, tx_hash: String) {
+    let id: QueueItemId = world
+        .queued
+        .as_ref()
+        .expect("A token should have been queued")
+        .parse()
+        .unwrap();
+    world
+        .queue_manager
+        .update_queue_items_status(&[id], tx_hash, QueueStatus::Success)
+        .await
+        .unwrap();
+}
+
+#[given(
+    expr = "the mint of the queued token was rejected in transaction {word} with reason {word}"
+)]
+async fn given_queued_mint_rejected(world: &mut HttpWorld, tx_hash: String, reason: String) {
+    let id: QueueItemId = world
+        .queued
+        .as_ref()
+        .expect("A token should have been queued")
+        .parse()
+        .unwrap();
+    let note = StatusNote::new(TRANSACTION_REJECTED_NOTE)
+        .with("tx_hash", &tx_hash)
+        .with("reason", &reason);
+    world
+        .queue_manager
+        .schedule_retry(&[id], &note, Duration::from_secs(60))
+        .await
+        .unwrap();
+}
+
+#[given(expr = "starknet rejects transactions with {string}")]
+async fn given_starknet_rejects(world: &mut HttpWorld, reason: String) {
+    world
+        .starknet_manager
+        .reject_transactions(Some(&reason))
+        .await;
+}
+
+#[given("starknet holds transactions")]
+fn given_starknet_holds(world: &mut HttpWorld) {
+    world.starknet_manager.hold_transactions(true);
+}
+
+#[given(expr = "an operator {word} with api key {word}")]
+fn given_operator(world: &mut HttpWorld, name: String, api_key: String) {
+    world.operators.push(Operator { name, api_key });
+}
+
+#[given(expr = "a batch of {int} items was minted in transaction {word} at {int}")]
+async fn given_batch_minted(world: &mut HttpWorld, items: i32, tx_hash: String, at: i64) {
+    world
+        .batch_analytics_repository
+        .save_batch(&BatchRecord {
+            id: Uuid::new_v4(),
+            project_id: STARKNET_PROJECT_ADDR.parse().unwrap(),
+            transaction_hash: Some(tx_hash),
+            item_count: items,
+            fee: Some("1200000000000000".into()),
+            outcome: BatchOutcome::Accepted,
+            rejection_reason: None,
+            submitted_at: at,
+            latency_ms: Some(30_000),
+        })
+        .await
+        .unwrap();
+}
+
+#[given(expr = "table {word} holds {int} live and {int} dead rows")]
+async fn given_table_stats(world: &mut HttpWorld, table: String, live: i64, dead: i64) {
+    world
+        .maintenance_repository
+        .set_table_stats(TableStats {
+            table,
+            live_rows: live,
+            dead_rows: dead,
+            total_bytes: (live + dead) * 512,
+            last_vacuum: None,
+            last_analyze: Some(SystemClock.now_ms()),
+        })
+        .await;
+}
+
+// `{queued}` stands for the id of the last queued item
+fn admin_uri(world: &HttpWorld, uri: &str) -> String {
+    uri.replace("{queued}", world.queued.as_deref().unwrap_or_default())
+}
+
+fn admin_token(secret: &str, subject: &str, expires_in: i64) -> String {
+    HmacJwtVerifier::new(secret).sign(&JwtClaims {
+        sub: subject.into(),
+        exp: SystemClock.now_secs() + expires_in,
+    })
+}
+
+#[when(expr = "I GET {string} accepting {string}")]
+async fn when_getting_accepting(world: &mut HttpWorld, uri: String, accept: String) {
+    let request = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header((ACCEPT, accept));
+    call(world, request).await;
+}
+
+#[when(expr = "I GET {string} with request id {string}")]
+async fn when_getting_with_request_id(world: &mut HttpWorld, uri: String, request_id: String) {
+    let request = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header((X_REQUEST_ID, request_id));
+    call(world, request).await;
+}
+
+#[when(expr = "I GET {string} in language {string}")]
+async fn when_getting_in_language(world: &mut HttpWorld, uri: String, language: String) {
+    let request = test::TestRequest::get()
+        .uri(&uri)
+        .insert_header((ACCEPT_LANGUAGE, language));
+    call(world, request).await;
+}
+
+#[when(expr = "I GET {string} with api key {word} accepting {string}")]
+async fn when_getting_with_api_key_accepting(
+    world: &mut HttpWorld,
+    uri: String,
+    api_key: String,
+    accept: String,
+) {
+    let request = test::TestRequest::get()
+        .uri(&admin_uri(world, &uri))
+        .insert_header(("X-Api-Key", api_key))
+        .insert_header((ACCEPT, accept));
+    call(world, request).await;
+}
+
+#[when(expr = "I {word} {string} with api key {word}")]
+async fn when_calling_with_api_key(
+    world: &mut HttpWorld,
+    method: String,
+    uri: String,
+    api_key: String,
+) {
+    let request = test::TestRequest::default()
+        .method(method.parse().unwrap())
+        .uri(&admin_uri(world, &uri))
+        .insert_header(("X-Api-Key", api_key));
+    call(world, request).await;
+}
+
+#[when(expr = "I {word} {string} with a token of {word} expiring in {int} seconds")]
+async fn when_calling_with_token(
+    world: &mut HttpWorld,
+    method: String,
+    uri: String,
+    subject: String,
+    expires_in: i64,
+) {
+    let token = admin_token(ADMIN_JWT_SECRET, &subject, expires_in);
+    let request = test::TestRequest::default()
+        .method(method.parse().unwrap())
+        .uri(&admin_uri(world, &uri))
+        .insert_header((AUTHORIZATION, format!("Bearer {}", token)));
+    call(world, request).await;
+}
+
+#[when(expr = "I {word} {string} with a token of {word} signed with {string}")]
+async fn when_calling_with_forged_token(
+    world: &mut HttpWorld,
+    method: String,
+    uri: String,
+    subject: String,
+    secret: String,
+) {
+    let token = admin_token(&secret, &subject, 300);
+    let request = test::TestRequest::default()
+        .method(method.parse().unwrap())
+        .uri(&admin_uri(world, &uri))
+        .insert_header((AUTHORIZATION, format!("Bearer {}", token)));
+    call(world, request).await;
+}
+
+fn bridge_body(keplr: &str, tokens: &str, starknet: &str, signature: &str) -> Value {
+    let tokens: Vec<&str> = tokens.split(", ").filter(|t| !t.is_empty()).collect();
+    json!({
+        "signed_hash": {
+            "pub_key": { "type": "tendermint/PubKeySecp256k1", "value": "Avt8e5UqfoRAh0RBUzHCu9arv7UFEFdfcv657h6TtSZE" },
+            "signature": signature,
+        },
+        "starknet_account_addr": starknet,
+        "starknet_project_addr": STARKNET_PROJECT_ADDR,
+        "keplr_wallet_pubkey": keplr,
+        "project_id": "projectId",
+        "tokens_id": tokens,
+    })
+}
+
+#[when(expr = "{word} bridges tokens {string} to {word} with signature {word}")]
+async fn when_bridging_tokens(
+    world: &mut HttpWorld,
+    keplr: String,
+    tokens: String,
+    starknet: String,
+    signature: String,
+) {
+    let body = bridge_body(&keplr, &tokens, &starknet, &signature);
+    call(
+        world,
+        test::TestRequest::post().uri("/bridge").set_json(body),
+    )
+    .await;
+}
+
+#[when(
+    expr = "{word} bridges tokens {string} to {word} with signature {word} and request id {string}"
+)]
+async fn when_bridging_tokens_with_request_id(
+    world: &mut HttpWorld,
+    keplr: String,
+    tokens: String,
+    starknet: String,
+    signature: String,
+    request_id: String,
+) {
+    let body = bridge_body(&keplr, &tokens, &starknet, &signature);
+    call(
+        world,
+        test::TestRequest::post()
+            .uri("/bridge")
+            .insert_header((X_REQUEST_ID, request_id))
+            .set_json(body),
+    )
+    .await;
+}
+
+#[when(
+    expr = "{word} bridges tokens {string} to {word} with signature {word} and the issued nonce"
+)]
+async fn when_bridging_tokens_with_nonce(
+    world: &mut HttpWorld,
+    keplr: String,
+    tokens: String,
+    starknet: String,
+    signature: String,
+) {
+    let mut body = bridge_body(&keplr, &tokens, &starknet, &signature);
+    body["nonce"] = json!(world
+        .nonce
+        .clone()
+        .expect("A challenge should have been issued"));
+    call(
+        world,
+        test::TestRequest::post().uri("/bridge").set_json(body),
+    )
+    .await;
+}
+
+#[given(expr = "a rate limit of {int} requests per minute")]
+fn given_rate_limit(world: &mut HttpWorld, limit: u32) {
+    world.rate_limiter = Some(Arc::new(RateLimiter::new(
+        limit,
+        Duration::from_secs(60),
+        Arc::new(SystemClock),
+    )));
+}
+
+#[given(expr = "the queue accepts at most {int} pending items, drained {int} every minute")]
+fn given_queue_backpressure(world: &mut HttpWorld, max_pending: i64, batch_size: u8) {
+    world.queue_backpressure = Some(QueueBackpressure::new(
+        max_pending,
+        batch_size,
+        Duration::from_secs(60),
+    ));
+}
+
+#[given(expr = "metrics are scraped with prefix {word}")]
+fn given_prometheus_metrics(world: &mut HttpWorld, prefix: String) {
+    world.metrics = Arc::new(PrometheusMetrics::new(&prefix));
+}
+
+#[given(expr = "migrations of the project are open from {string} to {string}")]
+fn given_migration_window(world: &mut HttpWorld, opens_at: String, closes_at: String) {
+    world.migration_window = MigrationWindow {
+        opens_at: parse_timestamp(&opens_at),
+        closes_at: parse_timestamp(&closes_at),
+    };
+}
+
+#[given("schema versions are required")]
+fn given_schema_versions_required(world: &mut HttpWorld) {
+    world.require_schema_version = true;
+}
+
+#[given("signature challenges are required")]
+fn given_challenges_required(world: &mut HttpWorld) {
+    world.require_challenge = true;
+}
+
+#[given(expr = "signature challenges expire after {int} seconds")]
+fn given_challenge_ttl(world: &mut HttpWorld, ttl: u64) {
+    world.challenge_ttl = Duration::from_secs(ttl);
+}
+
+#[when(expr = "{word} requests a challenge")]
+async fn when_requesting_challenge(world: &mut HttpWorld, keplr: String) {
+    let uri = format!("/challenge?keplr_wallet_pubkey={}", keplr);
+    call(world, test::TestRequest::get().uri(&uri)).await;
+    world.nonce = world
+        .body
+        .as_ref()
+        .and_then(|b| b["data"]["nonce"].as_str())
+        .map(String::from);
+}
+
+#[when(expr = "I POST {string} with:")]
+async fn when_posting(world: &mut HttpWorld, uri: String, step: &Step) {
+    let request = test::TestRequest::post()
+        .uri(&uri)
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .set_payload(step.docstring.as_ref().unwrap().to_string());
+    call(world, request).await;
+}
+
+#[when(expr = "I POST {string} with schema version {string} and:")]
+async fn when_posting_with_schema_version(
+    world: &mut HttpWorld,
+    uri: String,
+    version: String,
+    step: &Step,
+) {
+    let request = test::TestRequest::post()
+        .uri(&uri)
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .insert_header((X_BRIDGE_SCHEMA_VERSION, version))
+        .set_payload(step.docstring.as_ref().unwrap().to_string());
+    call(world, request).await;
+}
+
+#[when(expr = "I GET {string}")]
+async fn when_getting(world: &mut HttpWorld, uri: String) {
+    call(world, test::TestRequest::get().uri(&uri)).await;
+}
+
+#[when(expr = "I GET {string} while the queued token is minted in transaction {word}")]
+async fn when_getting_while_minted(world: &mut HttpWorld, uri: String, tx_hash: String) {
+    let id: QueueItemId = world
+        .queued
+        .as_ref()
+        .expect("A token should have been queued")
+        .parse()
+        .unwrap();
+    let queue_manager = world.queue_manager.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        queue_manager
+            .update_queue_items_status(&[id], tx_hash, QueueStatus::Success)
+            .await
+            .unwrap();
+    });
+    call(world, test::TestRequest::get().uri(&uri)).await;
+}
+
+#[when(expr = "{string} sends a preflight request for {word} {string}")]
+async fn when_sending_preflight(
+    world: &mut HttpWorld,
+    origin: String,
+    method: String,
+    uri: String,
+) {
+    let request = test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri(&uri)
+        .insert_header((ORIGIN, origin))
+        .insert_header((ACCESS_CONTROL_REQUEST_METHOD, method));
+    call(world, request).await;
+}
+
+#[then(expr = "the response status should be {int}")]
+fn then_status_should_be(world: &mut HttpWorld, status: u16) {
+    assert_eq!(Some(status), world.status, "body : {:#?}", world.body);
+}
+
+#[then(expr = "the response should have taken between {int} and {int} seconds")]
+fn then_response_took(world: &mut HttpWorld, min: u64, max: u64) {
+    assert!(
+        Duration::from_secs(min) <= world.elapsed && world.elapsed < Duration::from_secs(max),
+        "elapsed : {:#?}",
+        world.elapsed
+    );
+}
+
+#[then("the response should be ok")]
+fn then_response_should_be_ok(world: &mut HttpWorld) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(json!(true), body["ok"], "body : {:#?}", body);
+}
+
+#[then(expr = "the response should fail with code {string}")]
+fn then_response_should_fail(world: &mut HttpWorld, code: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(json!(false), body["ok"]);
+    assert_eq!(json!(code), body["error"]["code"], "body : {:#?}", body);
+}
+
+#[then(expr = "the error details should report token {string} as {string}")]
+fn then_details_should_report(world: &mut HttpWorld, token: String, message: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(
+        json!(message),
+        body["error"]["details"]["checks"][&token][1],
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "the error details should have {string} equal to {string}")]
+fn then_details_field(world: &mut HttpWorld, pointer: String, value: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(
+        Some(&json!(value)),
+        body["error"]["details"].pointer(&pointer),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "token {string} of {word} should be queued")]
+async fn then_token_should_be_queued(world: &mut HttpWorld, token: String, keplr: String) {
+    let queued = world
+        .queue_manager
+        .get_customer_migration_state(
+            &keplr.parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+        )
+        .await;
+    assert!(queued.iter().any(|qi| qi.token_id == token));
+}
+
+#[then(expr = "token {string} of {word} should be queued for request {string}")]
+async fn then_token_should_be_queued_for_request(
+    world: &mut HttpWorld,
+    token: String,
+    keplr: String,
+    request_id: String,
+) {
+    let queued = world
+        .queue_manager
+        .get_customer_migration_state(
+            &keplr.parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+        )
+        .await;
+    let item = queued
+        .iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued");
+    assert_eq!(Some(request_id), item.request_id);
+}
+
+#[then(expr = "the response data should have {string} equal to {string}")]
+fn then_response_data_field(world: &mut HttpWorld, pointer: String, value: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(
+        Some(&json!(value)),
+        body["data"].pointer(&pointer),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "the response data should have {string} equal to {int}")]
+fn then_response_data_number(world: &mut HttpWorld, pointer: String, value: u64) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(
+        Some(&json!(value)),
+        body["data"].pointer(&pointer),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "the response body should have {string} equal to {string}")]
+fn then_response_body_field(world: &mut HttpWorld, pointer: String, value: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(
+        Some(&json!(value)),
+        body.pointer(&pointer),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then("the proof bundle signature should match its content")]
+fn then_proof_bundle_signature_matches(world: &mut HttpWorld) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    let signer = world
+        .proof_bundle_signer
+        .as_ref()
+        .expect("Proof bundles should be signed");
+    let payload = serde_json::to_string(&body["bundle"]).unwrap();
+    assert_eq!(
+        Some(&json!(signer.sign(&payload))),
+        body.get("signature"),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "the response data should not have {string}")]
+fn then_response_data_missing_field(world: &mut HttpWorld, pointer: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert!(
+        body["data"].pointer(&pointer).map_or(true, Value::is_null),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "the attestation of item {int} should verify against the signer key")]
+fn then_attestation_verifies(world: &mut HttpWorld, index: usize) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    let attestation = &body["data"][index]["attestation"];
+    let felt = |field: &str| {
+        FieldElement::from_hex_be(attestation[field].as_str().expect("Felt should be set")).unwrap()
+    };
+    let signature = Signature {
+        r: felt("signature_r"),
+        s: felt("signature_s"),
+    };
+    assert!(
+        ecdsa_verify(&felt("public_key"), &felt("message_hash"), &signature).unwrap(),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "{int} funnel event(s) should be recorded")]
+async fn then_funnel_events_are_recorded(world: &mut HttpWorld, count: usize) {
+    assert_eq!(count, world.funnel_event_repository.events().await.len());
+}
+
+#[then(expr = "the response data should have {int} entries")]
+fn then_response_data_entries(world: &mut HttpWorld, entries: usize) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert_eq!(
+        Some(entries),
+        body["data"].as_array().map(Vec::len),
+        "body : {:#?}",
+        body
+    );
+}
+
+#[then(expr = "the response content type should be {string}")]
+fn then_content_type_should_be(world: &mut HttpWorld, content_type: String) {
+    assert_eq!(Some(content_type), world.content_type);
+}
+
+#[then(expr = "the response text should contain {string}")]
+fn then_text_should_contain(world: &mut HttpWorld, expected: String) {
+    assert!(world.text.contains(&expected), "{}", world.text);
+}
+
+#[then(expr = "the CSV header should be {string}")]
+fn then_csv_header_should_be(world: &mut HttpWorld, header: String) {
+    assert_eq!(Some(header.as_str()), world.text.lines().next());
+}
+
+#[then(expr = "the CSV should have {int} row(s)")]
+fn then_csv_rows(world: &mut HttpWorld, rows: usize) {
+    assert_eq!(rows, world.text.lines().skip(1).count(), "{}", world.text);
+}
+
+#[then(expr = "CSV row {int} should contain {string}")]
+fn then_csv_row_should_contain(world: &mut HttpWorld, row: usize, value: String) {
+    let line = world.text.lines().nth(row).unwrap_or_default();
+    assert!(
+        line.split(',').any(|field| field == value),
+        "{} not found in {}",
+        value,
+        line
+    );
+}
+
+#[then(expr = "the response header {string} should be {string}")]
+fn then_header_should_be(world: &mut HttpWorld, name: String, value: String) {
+    let header = world
+        .headers
+        .as_ref()
+        .and_then(|h| h.get(name.as_str()))
+        .map(|v| v.to_str().unwrap().to_string());
+    assert_eq!(Some(value), header, "headers : {:#?}", world.headers);
+}
+
+#[then(expr = "the response header {string} should not be {string}")]
+fn then_header_should_not_be(world: &mut HttpWorld, name: String, value: String) {
+    let header = world
+        .headers
+        .as_ref()
+        .and_then(|h| h.get(name.as_str()))
+        .map(|v| v.to_str().unwrap().to_string());
+    assert_ne!(Some(value), header, "headers : {:#?}", world.headers);
+}
+
+#[then(expr = "the response header {string} should be set")]
+fn then_header_should_be_set(world: &mut HttpWorld, name: String) {
+    let headers = world
+        .headers
+        .as_ref()
+        .expect("Response should have been received");
+    assert!(
+        headers.contains_key(name.as_str()),
+        "headers : {:#?}",
+        headers
+    );
+}
+
+#[then(expr = "the response should allow origin {string}")]
+fn then_origin_should_be_allowed(world: &mut HttpWorld, origin: String) {
+    assert_eq!(Some(origin), world.allowed_origin);
+}
+
+#[then("the response should not allow any origin")]
+fn then_no_origin_should_be_allowed(world: &mut HttpWorld) {
+    assert_eq!(None, world.allowed_origin);
+}
+
+#[then(expr = "the OpenAPI document should describe {string}")]
+fn then_openapi_should_describe(world: &mut HttpWorld, path: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert!(
+        body["paths"].get(&path).is_some(),
+        "{} not in {:#?}",
+        path,
+        body["paths"]
+    );
+}
+
+#[then(expr = "the OpenAPI document should define schema {string}")]
+fn then_openapi_should_define(world: &mut HttpWorld, schema: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    assert!(body["components"]["schemas"].get(&schema).is_some());
+}
+
+#[then(expr = "the OpenAPI document should list error code {string}")]
+fn then_openapi_should_list_code(world: &mut HttpWorld, code: String) {
+    let body = world.body.as_ref().expect("Response should be JSON");
+    let description = body["info"]["description"].as_str().unwrap_or_default();
+    assert!(
+        description.contains(&format!("`{}`", code)),
+        "{}",
+        description
+    );
+}
+
+#[actix_web::main]
+async fn main() {
+    HttpWorld::cucumber()
+        .run_and_exit("features/http.feature")
+        .await;
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::HashMap,
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use actix_web::{dev::ServerHandle, web, App, HttpResponse, HttpServer};
+use bridge_juno_to_starknet_backend::{
+    domain::bridge::{Transaction, TransactionFetchError, TransactionRepository},
+    infrastructure::juno::{JunoLcd, JunoRetryPolicy},
+};
+use cucumber::{given, then, when, World};
+use reqwest::Client;
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+/// Transfers served by the fake LCD, token ids match their position in the listing.
+#[derive(Debug, Clone)]
+struct LcdState {
+    contract: String,
+    transactions: usize,
+    count_total: bool,
+    response_delay: Duration,
+    requested_offsets: Arc<Mutex<Vec<usize>>>,
+    // Transactions included in a block but failed
+    failed_tokens: Arc<Mutex<Vec<usize>>>,
+    // Pages listed without their transaction results
+    missing_results: Arc<Mutex<bool>>,
+}
+
+#[derive(Debug, World)]
+struct PaginationWorld {
+    lcd: Option<LcdState>,
+    server: Option<ServerHandle>,
+    address: String,
+    max_pages: u32,
+    max_response_bytes: usize,
+    retry_policy: JunoRetryPolicy,
+    result: Option<Result<Vec<Transaction>, TransactionFetchError>>,
+    elapsed: Duration,
+}
+
+impl Default for PaginationWorld {
+    fn default() -> Self {
+        Self {
+            lcd: None,
+            server: None,
+            address: String::new(),
+            max_pages: 20,
+            max_response_bytes: 8 * 1024 * 1024,
+            retry_policy: JunoRetryPolicy::default(),
+            result: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+async fn transactions_page(
+    query: web::Query<HashMap<String, String>>,
+    state: web::Data<LcdState>,
+) -> HttpResponse {
+    let offset: usize = query["pagination.offset"].parse().unwrap();
+    let limit: usize = query["pagination.limit"].parse().unwrap();
+    state.requested_offsets.lock().unwrap().push(offset);
+    tokio::time::sleep(state.response_delay).await;
+
+    let listed = offset..state.transactions.min(offset + limit);
+    let failed_tokens = state.failed_tokens.lock().unwrap().clone();
+    let txs: Vec<_> = listed
+        .clone()
+        .map(|i| {
+            json!({
+                "body": {
+                    "messages": [{
+                        "sender": "k3plr-pk1",
+                        "contract": state.contract,
+                        "msg": { "transfer_nft": { "recipient": "juno-admin-account", "token_id": i.to_string() } }
+                    }],
+                    "memo": ""
+                },
+                "signatures": []
+            })
+        })
+        .collect();
+    let listed = match *state.missing_results.lock().unwrap() {
+        true => 0..0,
+        false => listed,
+    };
+    let tx_responses: Vec<_> = listed
+        .map(|i| {
+            json!({
+                "height": (i + 1).to_string(),
+                "txhash": format!("TX{}", i),
+                "code": if failed_tokens.contains(&i) { 5 } else { 0 },
+                "raw_log": ""
+            })
+        })
+        .collect();
+    let total = match state.count_total {
+        true => state.transactions.to_string(),
+        false => "0".into(),
+    };
+
+    HttpResponse::Ok().json(json!({
+        "txs": txs,
+        "tx_responses": tx_responses,
+        "pagination": { "next_key": null, "total": total }
+    }))
+}
+
+async fn start_lcd(world: &mut PaginationWorld, state: LcdState) {
+    let data = web::Data::new(state.clone());
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .route("/cosmos/tx/v1beta1/txs", web::get().to(transactions_page))
+    })
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    world.address = format!("http://{}", server.addrs()[0]);
+    let server = server.run();
+    world.server = Some(server.handle());
+    tokio::spawn(server);
+    world.lcd = Some(state);
+}
+
+#[given(expr = "the Juno LCD holds {int} transactions for contract {string}")]
+async fn given_lcd_transactions(
+    world: &mut PaginationWorld,
+    transactions: usize,
+    contract: String,
+) {
+    let state = LcdState {
+        contract,
+        transactions,
+        count_total: true,
+        response_delay: Duration::ZERO,
+        requested_offsets: Arc::new(Mutex::new(Vec::new())),
+        failed_tokens: Arc::new(Mutex::new(Vec::new())),
+        missing_results: Arc::new(Mutex::new(false)),
+    };
+    start_lcd(world, state).await;
+}
+
+#[given(expr = "the Juno LCD holds {int} transactions for contract {string} without counting them")]
+async fn given_uncounted_lcd_transactions(
+    world: &mut PaginationWorld,
+    transactions: usize,
+    contract: String,
+) {
+    let state = LcdState {
+        contract,
+        transactions,
+        count_total: false,
+        response_delay: Duration::ZERO,
+        requested_offsets: Arc::new(Mutex::new(Vec::new())),
+        failed_tokens: Arc::new(Mutex::new(Vec::new())),
+        missing_results: Arc::new(Mutex::new(false)),
+    };
+    start_lcd(world, state).await;
+}
+
+#[given(expr = "the Juno LCD answers after {int} milliseconds")]
+async fn given_slow_lcd(world: &mut PaginationWorld, delay: u64) {
+    let state = LcdState {
+        contract: "projectId".into(),
+        transactions: 1,
+        count_total: true,
+        response_delay: Duration::from_millis(delay),
+        requested_offsets: Arc::new(Mutex::new(Vec::new())),
+        failed_tokens: Arc::new(Mutex::new(Vec::new())),
+        missing_results: Arc::new(Mutex::new(false)),
+    };
+    start_lcd(world, state).await;
+}
+
+#[given(expr = "the Juno LCD transaction of token {int} failed")]
+fn given_failed_transaction(world: &mut PaginationWorld, token: usize) {
+    let lcd = world.lcd.as_ref().unwrap();
+    lcd.failed_tokens.lock().unwrap().push(token);
+}
+
+#[given("the Juno LCD leaves out transaction results")]
+fn given_missing_results(world: &mut PaginationWorld) {
+    let lcd = world.lcd.as_ref().unwrap();
+    *lcd.missing_results.lock().unwrap() = true;
+}
+
+#[given("the Juno LCD is unreachable")]
+fn given_unreachable_lcd(world: &mut PaginationWorld) {
+    // Port released right away, connections to it are refused
+    let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+    world.address = format!("http://{}", listener.local_addr().unwrap());
+}
+
+#[given(expr = "unreachable LCD calls are tried {int} times, waiting {int} to {int} milliseconds")]
+fn given_retry_policy(world: &mut PaginationWorld, max_attempts: u32, base: u64, max: u64) {
+    world.retry_policy.max_attempts = max_attempts;
+    world.retry_policy.base_delay = Duration::from_millis(base);
+    world.retry_policy.max_delay = Duration::from_millis(max);
+}
+
+#[given(expr = "LCD calls time out after {int} milliseconds")]
+fn given_timeout(world: &mut PaginationWorld, timeout: u64) {
+    world.retry_policy.timeout = Duration::from_millis(timeout);
+}
+
+#[given(expr = "at most {int} pages are fetched")]
+fn given_max_pages(world: &mut PaginationWorld, max_pages: u32) {
+    world.max_pages = max_pages;
+}
+
+#[given(expr = "responses over {int} bytes are refused")]
+fn given_max_response_bytes(world: &mut PaginationWorld, max_response_bytes: usize) {
+    world.max_response_bytes = max_response_bytes;
+}
+
+#[when(expr = "I fetch the transactions of token {string} on contract {string}")]
+async fn when_fetching_transactions(world: &mut PaginationWorld, token: String, contract: String) {
+    let lcd = JunoLcd::new(
+        &world.address,
+        world.max_pages,
+        world.max_response_bytes,
+        Client::new(),
+        world.retry_policy.clone(),
+    );
+    let started_at = Instant::now();
+    let transactions = lcd
+        .get_transactions_for_contract(
+            &contract.parse().unwrap(),
+            &token.parse().unwrap(),
+            &CancellationToken::new(),
+        )
+        .await;
+    world.elapsed = started_at.elapsed();
+    world.result = Some(transactions);
+
+    if let Some(server) = world.server.take() {
+        server.stop(true).await;
+    }
+}
+
+#[then(expr = "{int} transaction(s) should have been found")]
+fn then_transactions_found(world: &mut PaginationWorld, count: usize) {
+    assert_eq!(
+        count,
+        world.result.as_ref().unwrap().as_ref().unwrap().len()
+    );
+}
+
+#[then("the fetch should fail because the response is too large")]
+fn then_response_too_large(world: &mut PaginationWorld) {
+    assert!(matches!(
+        world.result,
+        Some(Err(TransactionFetchError::ResponseTooLarge))
+    ));
+}
+
+#[then("the fetch should fail to read the response")]
+fn then_response_unreadable(world: &mut PaginationWorld) {
+    assert!(matches!(
+        world.result,
+        Some(Err(TransactionFetchError::DeserializationFailed))
+    ));
+}
+
+#[then("the fetch should fail to reach the LCD")]
+fn then_fetch_failed(world: &mut PaginationWorld) {
+    assert!(matches!(
+        world.result,
+        Some(Err(TransactionFetchError::FetchError(_)))
+    ));
+}
+
+#[then(expr = "the fetch should have taken between {int} and {int} milliseconds")]
+fn then_fetch_took(world: &mut PaginationWorld, min: u64, max: u64) {
+    assert!(
+        Duration::from_millis(min) <= world.elapsed && world.elapsed < Duration::from_millis(max),
+        "elapsed : {:#?}",
+        world.elapsed
+    );
+}
+
+#[then(
+    expr = "the wait after {int} failed attempt(s) should be between {int} and {int} milliseconds"
+)]
+fn then_retry_delay(world: &mut PaginationWorld, failed_attempts: u32, min: u64, max: u64) {
+    for _ in 0..20 {
+        let delay = world.retry_policy.delay(failed_attempts);
+        assert!(
+            Duration::from_millis(min) <= delay && delay <= Duration::from_millis(max),
+            "delay : {:#?}",
+            delay
+        );
+    }
+}
+
+#[then(expr = "{int} page(s) should have been requested")]
+fn then_pages_requested(world: &mut PaginationWorld, pages: usize) {
+    let offsets = world
+        .lcd
+        .as_ref()
+        .unwrap()
+        .requested_offsets
+        .lock()
+        .unwrap();
+    assert_eq!(pages, offsets.len());
+    for (page, offset) in offsets.iter().enumerate() {
+        assert_eq!(page * 100, *offset);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    PaginationWorld::cucumber()
+        .run_and_exit("features/juno_pagination.feature")
+        .await;
+}
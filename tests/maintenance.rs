@@ -0,0 +1,178 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        maintenance::{DatabaseMaintenance, MaintenanceAction, MaintenanceOverview, TableStats},
+        metrics::Metrics,
+    },
+    infrastructure::{
+        in_memory::{InMemoryMaintenanceRepository, ManualClock},
+        metrics::PrometheusMetrics,
+    },
+};
+use cucumber::{given, then, when, World};
+
+const NOW_MS: i64 = 1_700_000_000_000;
+const DAY_MS: i64 = 86_400_000;
+
+#[derive(Debug, World)]
+struct MaintenanceWorld {
+    repository: InMemoryMaintenanceRepository,
+    clock: ManualClock,
+    metrics: Arc<dyn Metrics>,
+    prune_expired_rows: bool,
+    maintenance: Option<Arc<DatabaseMaintenance>>,
+    overview: Option<MaintenanceOverview>,
+    pruned: Option<u64>,
+}
+
+impl Default for MaintenanceWorld {
+    fn default() -> Self {
+        Self {
+            repository: InMemoryMaintenanceRepository::new(),
+            clock: ManualClock::new(NOW_MS),
+            metrics: Arc::new(PrometheusMetrics::new("bridge")),
+            prune_expired_rows: false,
+            maintenance: None,
+            overview: None,
+            pruned: None,
+        }
+    }
+}
+
+impl MaintenanceWorld {
+    // Kept across runs, it remembers when it last ran
+    fn maintenance(&mut self) -> Arc<DatabaseMaintenance> {
+        self.maintenance
+            .get_or_insert_with(|| {
+                Arc::new(DatabaseMaintenance::new(
+                    Arc::new(self.repository.clone()),
+                    Arc::new(self.clock.clone()),
+                    Duration::from_secs(300),
+                    0.2,
+                    self.prune_expired_rows,
+                ))
+            })
+            .clone()
+    }
+
+    async fn set_table(&self, table: String, live: i64, dead: i64, analyzed_at: i64) {
+        self.repository
+            .set_table_stats(TableStats {
+                table,
+                live_rows: live,
+                dead_rows: dead,
+                total_bytes: (live + dead) * 512,
+                last_vacuum: None,
+                last_analyze: Some(analyzed_at),
+            })
+            .await;
+    }
+
+    fn hints_of(&self, table: &str) -> Vec<MaintenanceAction> {
+        self.overview
+            .as_ref()
+            .expect("Overview should have been fetched")
+            .hints
+            .iter()
+            .filter(|h| h.table == table)
+            .map(|h| h.action)
+            .collect()
+    }
+}
+
+#[given(expr = "table {word} holds {int} live and {int} dead rows")]
+async fn given_table(world: &mut MaintenanceWorld, table: String, live: i64, dead: i64) {
+    world.set_table(table, live, dead, NOW_MS).await;
+}
+
+#[given(expr = "table {word} holds {int} live and {int} dead rows analyzed {int} days ago")]
+async fn given_table_analyzed(
+    world: &mut MaintenanceWorld,
+    table: String,
+    live: i64,
+    dead: i64,
+    days: i64,
+) {
+    world
+        .set_table(table, live, dead, NOW_MS - days * DAY_MS)
+        .await;
+}
+
+#[given("expired rows are pruned")]
+fn given_pruning(world: &mut MaintenanceWorld) {
+    world.prune_expired_rows = true;
+}
+
+#[given(expr = "{int} row(s) expired")]
+async fn given_expired_rows(world: &mut MaintenanceWorld, rows: usize) {
+    for _ in 0..rows {
+        world.repository.add_expiring_row(NOW_MS - 1).await;
+    }
+}
+
+#[given(expr = "{int} row(s) expire(s) in {int} seconds")]
+async fn given_expiring_rows(world: &mut MaintenanceWorld, rows: usize, seconds: i64) {
+    for _ in 0..rows {
+        world
+            .repository
+            .add_expiring_row(NOW_MS + seconds * 1000)
+            .await;
+    }
+}
+
+#[when("the maintenance overview is fetched")]
+async fn when_overview(world: &mut MaintenanceWorld) {
+    world.overview = Some(world.maintenance().overview().await.unwrap());
+}
+
+#[when("database maintenance runs")]
+async fn when_maintenance_runs(world: &mut MaintenanceWorld) {
+    let maintenance = world.maintenance();
+    world.pruned = Some(maintenance.run(world.metrics.as_ref()).await.unwrap());
+}
+
+#[when(expr = "{int} seconds elapsed")]
+fn when_time_elapsed(world: &mut MaintenanceWorld, seconds: i64) {
+    world.clock.set(NOW_MS + seconds * 1000);
+}
+
+#[then(expr = "table {word} should be hinted a vacuum")]
+fn then_vacuum_hinted(world: &mut MaintenanceWorld, table: String) {
+    assert!(world.hints_of(&table).contains(&MaintenanceAction::Vacuum));
+}
+
+#[then(expr = "table {word} should be hinted an analyze")]
+fn then_analyze_hinted(world: &mut MaintenanceWorld, table: String) {
+    assert!(world.hints_of(&table).contains(&MaintenanceAction::Analyze));
+}
+
+#[then(expr = "{int} maintenance hint(s) should be given")]
+fn then_hints_given(world: &mut MaintenanceWorld, count: usize) {
+    let overview = world.overview.as_ref().unwrap();
+    assert_eq!(count, overview.hints.len(), "{:#?}", overview.hints);
+}
+
+#[then(expr = "the dead rows gauge of table {word} should be {int}")]
+fn then_dead_rows_gauge(world: &mut MaintenanceWorld, table: String, rows: u64) {
+    let rendered = world.metrics.render().unwrap();
+    let line = format!("bridge_db_table_dead_rows{{table=\"{}\"}} {}", table, rows);
+    assert!(rendered.lines().any(|l| l == line), "{}", rendered);
+}
+
+#[then(expr = "{int} expired row(s) should have been pruned")]
+fn then_rows_pruned(world: &mut MaintenanceWorld, rows: u64) {
+    assert_eq!(Some(rows), world.pruned);
+}
+
+#[then(expr = "{int} expiring row(s) should be left")]
+async fn then_rows_left(world: &mut MaintenanceWorld, rows: usize) {
+    assert_eq!(rows, world.repository.rows_left().await);
+}
+
+#[tokio::main]
+async fn main() {
+    MaintenanceWorld::cucumber()
+        .run_and_exit("features/maintenance.feature")
+        .await;
+}
@@ -0,0 +1,73 @@
+use std::{net::UdpSocket, sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::metrics::Metrics,
+    infrastructure::metrics::{configure_metrics, StatsdMetrics},
+};
+use cucumber::{given, then, when, World};
+
+#[derive(Debug, Default, World)]
+struct MetricsWorld {
+    agent: Option<UdpSocket>,
+    metrics: Option<Arc<dyn Metrics>>,
+    configuration_failed: bool,
+}
+
+#[given("a statsd agent")]
+fn given_statsd_agent(world: &mut MetricsWorld) {
+    let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    agent
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .unwrap();
+    world.agent = Some(agent);
+}
+
+#[given(expr = "metrics are pushed to the agent with prefix {string}")]
+fn given_statsd_metrics(world: &mut MetricsWorld, prefix: String) {
+    let address = world.agent.as_ref().unwrap().local_addr().unwrap();
+    world.metrics = Some(Arc::new(
+        StatsdMetrics::new(&address.to_string(), &prefix).unwrap(),
+    ));
+}
+
+#[when(expr = "counter {string} is incremented with tag {string} set to {string}")]
+fn when_counter_incremented(world: &mut MetricsWorld, name: String, tag: String, value: String) {
+    world
+        .metrics
+        .as_ref()
+        .unwrap()
+        .increment(&name, &[(tag.as_str(), value.as_str())]);
+}
+
+#[when(expr = "{string} takes {int} milliseconds")]
+fn when_timing(world: &mut MetricsWorld, name: String, millis: u64) {
+    world
+        .metrics
+        .as_ref()
+        .unwrap()
+        .timing(&name, Duration::from_millis(millis), &[]);
+}
+
+#[when(expr = "metrics are configured with backend {string}")]
+fn when_configuring(world: &mut MetricsWorld, backend: String) {
+    world.configuration_failed = configure_metrics(&backend, "127.0.0.1:8125", "bridge").is_err();
+}
+
+#[then(expr = "the agent should receive {string}")]
+fn then_agent_should_receive(world: &mut MetricsWorld, packet: String) {
+    let mut buffer = [0; 512];
+    let size = world.agent.as_ref().unwrap().recv(&mut buffer).unwrap();
+    assert_eq!(packet, String::from_utf8_lossy(&buffer[..size]));
+}
+
+#[then("metrics configuration should fail")]
+fn then_configuration_should_fail(world: &mut MetricsWorld) {
+    assert!(world.configuration_failed);
+}
+
+#[tokio::main]
+async fn main() {
+    MetricsWorld::cucumber()
+        .run_and_exit("features/metrics.feature")
+        .await;
+}
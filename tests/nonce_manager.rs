@@ -0,0 +1,133 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use bridge_juno_to_starknet_backend::infrastructure::starknet::{
+    NonceError, NonceManager, NonceSource,
+};
+use cucumber::{given, then, when, World};
+use futures::future::join_all;
+use starknet::core::types::FieldElement;
+
+const UNREACHABLE: u64 = u64::MAX;
+
+#[derive(Default)]
+struct StubNonceSource {
+    nonce: AtomicU64,
+    fetches: AtomicUsize,
+}
+
+#[async_trait]
+impl NonceSource for StubNonceSource {
+    async fn fetch_nonce(&self) -> Result<FieldElement, NonceError> {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        match self.nonce.load(Ordering::SeqCst) {
+            UNREACHABLE => Err(NonceError::FetchFailed("gateway unreachable".into())),
+            n => Ok(FieldElement::from(n)),
+        }
+    }
+}
+
+#[derive(World)]
+struct NonceWorld {
+    source: Arc<StubNonceSource>,
+    manager: Arc<NonceManager>,
+    nonces: Vec<FieldElement>,
+}
+
+impl std::fmt::Debug for NonceWorld {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NonceWorld")
+            .field("nonces", &self.nonces)
+            .finish()
+    }
+}
+
+impl Default for NonceWorld {
+    fn default() -> Self {
+        let source = Arc::new(StubNonceSource::default());
+        Self {
+            manager: Arc::new(NonceManager::new(source.clone())),
+            source,
+            nonces: Vec::new(),
+        }
+    }
+}
+
+fn parse_nonces(nonces: &str) -> Vec<FieldElement> {
+    nonces
+        .split(',')
+        .map(|n| FieldElement::from(n.trim().parse::<u64>().unwrap()))
+        .collect()
+}
+
+#[given(expr = "the starknet account nonce is {int}")]
+fn given_account_nonce(world: &mut NonceWorld, nonce: u64) {
+    world.source.nonce.store(nonce, Ordering::SeqCst);
+}
+
+#[given("the starknet account nonce cannot be fetched")]
+fn given_unreachable_gateway(world: &mut NonceWorld) {
+    world.source.nonce.store(UNREACHABLE, Ordering::SeqCst);
+}
+
+#[when(expr = "{int} nonce(s) is/are requested")]
+async fn when_nonces_requested(world: &mut NonceWorld, count: usize) {
+    for _ in 0..count {
+        if let Ok(n) = world.manager.next_nonce().await {
+            world.nonces.push(n);
+        }
+    }
+}
+
+#[when(expr = "{int} nonces are requested concurrently")]
+async fn when_nonces_requested_concurrently(world: &mut NonceWorld, count: usize) {
+    let requests = (0..count).map(|_| {
+        let manager = world.manager.clone();
+        tokio::spawn(async move { manager.next_nonce().await })
+    });
+    for res in join_all(requests).await {
+        world.nonces.push(res.unwrap().unwrap());
+    }
+}
+
+#[when(expr = "a transaction is rejected while the account nonce is {int}")]
+async fn when_transaction_rejected(world: &mut NonceWorld, nonce: u64) {
+    world.source.nonce.store(nonce, Ordering::SeqCst);
+    world.manager.resync().await;
+}
+
+#[then(regex = r"^nonces ([\d, ]+) should have been handed out$")]
+fn then_nonces_handed_out(world: &mut NonceWorld, nonces: String) {
+    assert_eq!(parse_nonces(&nonces), world.nonces);
+}
+
+#[then(expr = "{int} distinct nonces from {int} should have been handed out")]
+fn then_distinct_nonces(world: &mut NonceWorld, count: u64, from: u64) {
+    let handed_out: HashSet<FieldElement> = world.nonces.iter().copied().collect();
+    let expected: HashSet<FieldElement> = (from..from + count).map(FieldElement::from).collect();
+    assert_eq!(expected, handed_out);
+    assert_eq!(count as usize, world.nonces.len());
+}
+
+#[then("no nonce should have been handed out")]
+fn then_no_nonce(world: &mut NonceWorld) {
+    assert!(world.nonces.is_empty());
+}
+
+#[then(expr = "the account nonce should have been fetched {int} time(s)")]
+fn then_fetched(world: &mut NonceWorld, fetches: usize) {
+    assert_eq!(fetches, world.source.fetches.load(Ordering::SeqCst));
+}
+
+#[tokio::main]
+async fn main() {
+    NonceWorld::cucumber()
+        .run_and_exit("features/nonce_manager.feature")
+        .await;
+}
@@ -0,0 +1,148 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        bridge::{QueueItem, QueueManager, QueueStatus},
+        ids::{StarknetAddress, TokenId},
+        queue_broker::{wait_for_work, BrokerQueueManager, QueueBroker},
+    },
+    infrastructure::in_memory::{InMemoryQueueBroker, InMemoryQueueManager},
+};
+use cucumber::{given, then, when, World};
+use tokio::task::JoinHandle;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0c4a";
+
+#[derive(Debug, World)]
+struct BrokerWorld {
+    store: Arc<dyn QueueManager>,
+    broker: InMemoryQueueBroker,
+    queue_manager: Arc<dyn QueueManager>,
+    waiting_worker: Option<JoinHandle<()>>,
+}
+
+impl Default for BrokerWorld {
+    fn default() -> Self {
+        let store: Arc<dyn QueueManager> = Arc::new(InMemoryQueueManager::new());
+        let broker = InMemoryQueueBroker::new();
+        Self {
+            queue_manager: Arc::new(BrokerQueueManager::new(
+                store.clone(),
+                Arc::new(broker.clone()),
+            )),
+            store,
+            broker,
+            waiting_worker: None,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+fn token_ids(tokens: &str) -> Vec<TokenId> {
+    tokens
+        .split(',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse().unwrap())
+        .collect()
+}
+
+fn sorted_tokens(items: &[QueueItem]) -> Vec<String> {
+    let mut tokens: Vec<String> = items.iter().map(|qi| qi.token_id.to_string()).collect();
+    tokens.sort();
+    tokens
+}
+
+async fn stored_token(world: &BrokerWorld, token: &str) -> QueueItem {
+    world
+        .store
+        .get_customer_migration_state(&"k3plr-pk1".parse().unwrap(), &project())
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be stored")
+}
+
+#[given("the broker refuses messages")]
+fn given_broker_refuses(world: &mut BrokerWorld) {
+    world.broker.fail_publishing(true);
+}
+
+#[given("a worker is waiting for work")]
+fn given_waiting_worker(world: &mut BrokerWorld) {
+    let broker: Option<Arc<dyn QueueBroker>> = Some(Arc::new(world.broker.clone()));
+    world.waiting_worker = Some(tokio::spawn(async move {
+        wait_for_work(&broker, Duration::from_secs(3600)).await
+    }));
+}
+
+#[when(expr = "tokens {string} of {string} are enqueued")]
+async fn when_tokens_are_enqueued(world: &mut BrokerWorld, tokens: String, keplr: String) {
+    world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            token_ids(&tokens),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[when(expr = "the worker mints its batch with transaction {string}")]
+async fn when_worker_mints_batch(world: &mut BrokerWorld, transaction_hash: String) {
+    let batch = world.queue_manager.get_batch(&[]).await.unwrap();
+    let ids: Vec<_> = batch.iter().filter_map(|qi| qi.id).collect();
+    world
+        .queue_manager
+        .update_queue_items_status(&ids, transaction_hash, QueueStatus::Success)
+        .await
+        .unwrap();
+}
+
+#[then(expr = "the broker should deliver tokens {string}")]
+async fn then_broker_should_deliver(world: &mut BrokerWorld, tokens: String) {
+    let ids = world.broker.next_delivery().await.unwrap();
+    let mut delivered = Vec::new();
+    for id in &ids {
+        delivered.push(world.store.get_queue_item(id).await.unwrap());
+    }
+    let expected: Vec<String> = token_ids(&tokens).iter().map(|t| t.to_string()).collect();
+    assert_eq!(expected, sorted_tokens(&delivered));
+}
+
+#[then("the worker should be woken before its next poll")]
+async fn then_worker_should_be_woken(world: &mut BrokerWorld) {
+    let worker = world
+        .waiting_worker
+        .take()
+        .expect("A worker should be waiting");
+    tokio::time::timeout(Duration::from_secs(5), worker)
+        .await
+        .expect("Worker should be woken")
+        .unwrap();
+}
+
+#[then(expr = "token {string} should be {string} in the store")]
+async fn then_token_should_be(world: &mut BrokerWorld, token: String, status: String) {
+    let qi = stored_token(world, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+}
+
+#[then(expr = "the next batch should contain tokens {string}")]
+async fn then_batch_should_contain(world: &mut BrokerWorld, tokens: String) {
+    let batch = world.queue_manager.get_batch(&[]).await.unwrap();
+    let expected: Vec<String> = token_ids(&tokens).iter().map(|t| t.to_string()).collect();
+    assert_eq!(expected, sorted_tokens(&batch));
+}
+
+#[tokio::main]
+async fn main() {
+    BrokerWorld::cucumber()
+        .run_and_exit("features/queue_broker.feature")
+        .await;
+}
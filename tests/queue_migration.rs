@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        bridge::{QueueError, QueueItem, QueueManager, QueueStatus},
+        ids::{StarknetAddress, TokenId},
+        pagination::PageRequest,
+        queue_migration::MigratingQueueManager,
+    },
+    infrastructure::in_memory::InMemoryQueueManager,
+};
+use cucumber::{given, then, when, World};
+
+const STARKNET_PROJECT_ADDR: &str = "0x0c4a";
+
+#[derive(Debug, World)]
+struct MigrationWorld {
+    legacy: Arc<dyn QueueManager>,
+    primary: Arc<dyn QueueManager>,
+    queue_manager: Arc<dyn QueueManager>,
+    enqueued: Option<Result<Vec<QueueItem>, QueueError>>,
+}
+
+impl Default for MigrationWorld {
+    fn default() -> Self {
+        let legacy: Arc<dyn QueueManager> = Arc::new(InMemoryQueueManager::new());
+        let primary: Arc<dyn QueueManager> = Arc::new(InMemoryQueueManager::new());
+        Self {
+            queue_manager: Arc::new(MigratingQueueManager::new(
+                primary.clone(),
+                legacy.clone(),
+                10,
+            )),
+            legacy,
+            primary,
+            enqueued: None,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+fn token_ids(tokens: &str) -> Vec<TokenId> {
+    tokens
+        .split(',')
+        .filter(|t| !t.is_empty())
+        .map(|t| t.parse().unwrap())
+        .collect()
+}
+
+fn sorted_tokens(items: &[QueueItem]) -> Vec<String> {
+    let mut tokens: Vec<String> = items.iter().map(|qi| qi.token_id.to_string()).collect();
+    tokens.sort();
+    tokens
+}
+
+async fn enqueue(queue_manager: &Arc<dyn QueueManager>, keplr: &str, tokens: &str) {
+    queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            token_ids(tokens),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+async fn backend_tokens(queue_manager: &Arc<dyn QueueManager>) -> Vec<String> {
+    sorted_tokens(
+        &queue_manager
+            .get_customer_migration_state(&"k3plr-pk1".parse().unwrap(), &project())
+            .await,
+    )
+}
+
+async fn queued_token(queue_manager: &Arc<dyn QueueManager>, token: &str) -> QueueItem {
+    queue_manager
+        .get_customer_migration_state(&"k3plr-pk1".parse().unwrap(), &project())
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued")
+}
+
+fn backend<'a>(world: &'a MigrationWorld, name: &str) -> &'a Arc<dyn QueueManager> {
+    match name {
+        "legacy" => &world.legacy,
+        _ => &world.primary,
+    }
+}
+
+#[given(expr = "tokens {string} of {string} are queued in the {word} backend")]
+async fn given_queued_tokens(
+    world: &mut MigrationWorld,
+    tokens: String,
+    keplr: String,
+    name: String,
+) {
+    enqueue(backend(world, &name), &keplr, &tokens).await;
+}
+
+#[when(expr = "tokens {string} of {string} are enqueued")]
+async fn when_tokens_are_enqueued(world: &mut MigrationWorld, tokens: String, keplr: String) {
+    world.enqueued = Some(
+        world
+            .queue_manager
+            .enqueue(
+                &keplr.parse().unwrap(),
+                &"0x5741".parse().unwrap(),
+                &project(),
+                token_ids(&tokens),
+                None,
+            )
+            .await,
+    );
+}
+
+#[when(expr = "tokens {string} are minted with transaction {string}")]
+async fn when_tokens_are_minted(
+    world: &mut MigrationWorld,
+    tokens: String,
+    transaction_hash: String,
+) {
+    let mut ids = Vec::new();
+    for token in token_ids(&tokens) {
+        ids.push(
+            queued_token(&world.queue_manager, token.as_str())
+                .await
+                .id
+                .unwrap(),
+        );
+    }
+    world
+        .queue_manager
+        .update_queue_items_status(&ids, transaction_hash, QueueStatus::Success)
+        .await
+        .unwrap();
+}
+
+#[then(expr = "the {word} backend should hold tokens {string}")]
+async fn then_backend_should_hold(world: &mut MigrationWorld, name: String, tokens: String) {
+    let expected: Vec<String> = token_ids(&tokens).iter().map(|t| t.to_string()).collect();
+    assert_eq!(expected, backend_tokens(backend(world, &name)).await);
+}
+
+#[then("the enqueue should be refused")]
+fn then_enqueue_should_be_refused(world: &mut MigrationWorld) {
+    match &world.enqueued {
+        Some(Err(QueueError::FailedToEnqueue)) => (),
+        r => panic!("Enqueue should be refused, got {:#?}", r),
+    }
+}
+
+#[then(expr = "a batch of {int} should contain tokens {string} and one more")]
+async fn then_batch_should_drain_legacy(world: &mut MigrationWorld, size: usize, tokens: String) {
+    let queue_manager =
+        MigratingQueueManager::new(world.primary.clone(), world.legacy.clone(), size);
+    let batch = queue_manager.get_batch(&[]).await.unwrap();
+    assert_eq!(size, batch.len());
+    let batched = sorted_tokens(&batch);
+    for token in token_ids(&tokens) {
+        assert!(batched.contains(&token.to_string()));
+    }
+}
+
+#[then(expr = "token {string} should be {string} in the {word} backend")]
+async fn then_token_should_be(
+    world: &mut MigrationWorld,
+    token: String,
+    status: String,
+    name: String,
+) {
+    let qi = queued_token(backend(world, &name), &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+}
+
+#[then(expr = "listing the queue by pages of {int} should return tokens {string}")]
+async fn then_listing_should_return(world: &mut MigrationWorld, limit: i64, tokens: String) {
+    let mut listed = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = PageRequest::new(cursor.as_deref(), Some(limit), limit).unwrap();
+        let page = world
+            .queue_manager
+            .list_queue_items(None, &page)
+            .await
+            .unwrap();
+        assert!(page.items.len() as i64 <= limit);
+        listed.extend(page.items);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    let expected: Vec<String> = token_ids(&tokens).iter().map(|t| t.to_string()).collect();
+    assert_eq!(expected, sorted_tokens(&listed));
+}
+
+#[then(expr = "the migrations of {string} should list tokens {string}")]
+async fn then_wallet_migrations_should_list(
+    world: &mut MigrationWorld,
+    keplr: String,
+    tokens: String,
+) {
+    let items = world
+        .queue_manager
+        .get_wallet_migrations(&keplr.parse().unwrap())
+        .await
+        .unwrap();
+    let expected: Vec<String> = token_ids(&tokens).iter().map(|t| t.to_string()).collect();
+    assert_eq!(expected, sorted_tokens(&items));
+}
+
+#[tokio::main]
+async fn main() {
+    MigrationWorld::cucumber()
+        .run_and_exit("features/queue_migration.feature")
+        .await;
+}
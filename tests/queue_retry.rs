@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        bridge::{QueueItem, QueueManager, QueueStatus},
+        ids::{QueueItemId, StarknetAddress},
+        queue_admin::{handle_retry_queue_item, QueueAdminError},
+    },
+    infrastructure::in_memory::InMemoryQueueManager,
+};
+use cucumber::{given, then, when, World};
+
+const STARKNET_PROJECT_ADDR: &str = "0x0c4a";
+
+#[derive(Debug, World)]
+struct RetryWorld {
+    queue_manager: Arc<dyn QueueManager>,
+    result: Option<Result<QueueItem, QueueAdminError>>,
+}
+
+impl Default for RetryWorld {
+    fn default() -> Self {
+        Self {
+            queue_manager: Arc::new(InMemoryQueueManager::new()),
+            result: None,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+async fn enqueue(world: &RetryWorld, keplr: &str, token: &str) -> QueueItem {
+    world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap()
+        .remove(0)
+}
+
+async fn queued_token(world: &RetryWorld, token: &str) -> QueueItem {
+    world
+        .queue_manager
+        .get_customer_migration_state(&"k3plr-pk1".parse().unwrap(), &project())
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued")
+}
+
+#[given(expr = "token {string} of {string} is queued")]
+async fn given_a_queued_token(world: &mut RetryWorld, token: String, keplr: String) {
+    enqueue(world, &keplr, &token).await;
+}
+
+#[given(expr = "token {string} of {string} failed to mint with transaction {string}")]
+async fn given_a_failed_token(
+    world: &mut RetryWorld,
+    token: String,
+    keplr: String,
+    transaction_hash: String,
+) {
+    let qi = enqueue(world, &keplr, &token).await;
+    world
+        .queue_manager
+        .update_queue_items_status(&[qi.id.unwrap()], transaction_hash, QueueStatus::Error)
+        .await
+        .unwrap();
+}
+
+#[when(expr = "the customer retries token {string}")]
+async fn when_the_customer_retries(world: &mut RetryWorld, token: String) {
+    let qi = queued_token(world, &token).await;
+    world.result =
+        Some(handle_retry_queue_item(&qi.id.unwrap(), world.queue_manager.clone()).await);
+}
+
+#[when("the customer retries an unknown queue item")]
+async fn when_the_customer_retries_unknown_item(world: &mut RetryWorld) {
+    world.result =
+        Some(handle_retry_queue_item(&QueueItemId::new(), world.queue_manager.clone()).await);
+}
+
+#[then("the retry should be accepted")]
+fn then_retry_should_be_accepted(world: &mut RetryWorld) {
+    match &world.result {
+        Some(Ok(_)) => (),
+        r => panic!("Retry should be accepted, got {:#?}", r),
+    }
+}
+
+#[then(expr = "token {string} should be {string} without transaction hash")]
+async fn then_token_should_be(world: &mut RetryWorld, token: String, status: String) {
+    let qi = queued_token(world, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(None, qi.transaction_hash);
+}
+
+#[then(expr = "the retry should be rejected because of its {string} status")]
+fn then_retry_should_be_rejected(world: &mut RetryWorld, status: String) {
+    match &world.result {
+        Some(Err(QueueAdminError::InvalidStatus(s))) => {
+            assert_eq!(serde_json::json!(status), serde_json::json!(s))
+        }
+        r => panic!("Retry should be rejected, got {:#?}", r),
+    }
+}
+
+#[then("the retry should be rejected because the item does not exist")]
+fn then_retry_should_be_not_found(world: &mut RetryWorld) {
+    match &world.result {
+        Some(Err(QueueAdminError::NotFound)) => (),
+        r => panic!("Retry should not find the item, got {:#?}", r),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    RetryWorld::cucumber()
+        .run_and_exit("features/queue_retry.feature")
+        .await;
+}
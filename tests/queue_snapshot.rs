@@ -0,0 +1,205 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        bridge::{QueueManager, QueueStatus},
+        clock::Clock,
+        consume_queue::{consume_queue, MintRetryPolicy},
+        ids::StarknetAddress,
+        post_mint::PostMintHooks,
+        queue_snapshot::{
+            restore_queue_snapshot, take_queue_snapshot, QueueSnapshot, QueueSnapshotError,
+            QueueSnapshotRepository,
+        },
+        webhook::WebhookNotifier,
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+        InMemoryQueueManager, InMemoryStarknetTransactionManager, InMemoryWebhookRepository,
+        ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const KEPLR_WALLET: &str = "k3plr-pk1";
+
+#[derive(Debug, World)]
+struct QueueSnapshotWorld {
+    // Both views share the same in-memory queue
+    queue_manager: Arc<dyn QueueManager>,
+    snapshots: Arc<dyn QueueSnapshotRepository>,
+    restored: Arc<dyn QueueSnapshotRepository>,
+    clock: Arc<dyn Clock>,
+    snapshot: Option<QueueSnapshot>,
+    result: Option<Result<usize, QueueSnapshotError>>,
+}
+
+impl Default for QueueSnapshotWorld {
+    fn default() -> Self {
+        let clock = Arc::new(ManualClock::new(1_672_531_200_000));
+        let queue_manager = InMemoryQueueManager::with_clock(clock.clone());
+        Self {
+            queue_manager: Arc::new(queue_manager.clone()),
+            snapshots: Arc::new(queue_manager),
+            restored: Arc::new(InMemoryQueueManager::with_clock(clock.clone())),
+            clock,
+            snapshot: None,
+            result: None,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+fn snapshot(world: &mut QueueSnapshotWorld) -> &mut QueueSnapshot {
+    world
+        .snapshot
+        .as_mut()
+        .expect("Queue should be snapshotted")
+}
+
+#[given(expr = "token(s) {string} is/are queued")]
+async fn given_queued_tokens(world: &mut QueueSnapshotWorld, tokens: String) {
+    world
+        .queue_manager
+        .enqueue(
+            &KEPLR_WALLET.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            tokens.split(", ").map(|t| t.parse().unwrap()).collect(),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given("the worker minted the queue")]
+async fn given_the_worker_minted(world: &mut QueueSnapshotWorld) {
+    consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(InMemoryStarknetTransactionManager::new()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(WebhookNotifier::new(
+            Arc::new(InMemoryWebhookRepository::new()),
+            world.clock.clone(),
+        )),
+        Arc::new(BatchAnalytics::new(
+            Arc::new(InMemoryBatchAnalyticsRepository::new()),
+            world.clock.clone(),
+            Duration::from_secs(86_400),
+        )),
+        &[],
+        &MintRetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(60),
+            max_delay: Duration::from_secs(300),
+        },
+        &CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+}
+
+#[when("the operator snapshots the queue")]
+async fn when_the_operator_snapshots(world: &mut QueueSnapshotWorld) {
+    world.snapshot = Some(
+        take_queue_snapshot(world.snapshots.clone(), world.clock.clone())
+            .await
+            .unwrap(),
+    );
+}
+
+#[when(expr = "token {string} is marked {string} in the snapshot")]
+fn when_token_is_marked(world: &mut QueueSnapshotWorld, token: String, status: String) {
+    let status: QueueStatus = serde_json::from_value(serde_json::json!(status)).unwrap();
+    let entry = snapshot(world)
+        .entries
+        .iter_mut()
+        .find(|e| e.item.token_id == token)
+        .expect("Token should be in the snapshot");
+    entry.item.status = status;
+}
+
+#[when("batches are dropped from the snapshot")]
+fn when_batches_are_dropped(world: &mut QueueSnapshotWorld) {
+    snapshot(world).batches.clear();
+}
+
+#[when("the operator restores the snapshot into an empty queue")]
+async fn when_restoring_into_an_empty_queue(world: &mut QueueSnapshotWorld) {
+    let repository = world.restored.clone();
+    world.result = Some(restore_queue_snapshot(snapshot(world), repository).await);
+}
+
+#[when("the operator restores the snapshot into the same queue")]
+async fn when_restoring_into_the_same_queue(world: &mut QueueSnapshotWorld) {
+    let repository = world.snapshots.clone();
+    world.result = Some(restore_queue_snapshot(snapshot(world), repository).await);
+}
+
+#[then(expr = "the snapshot should hold {int} items in {int} batch(es)")]
+fn then_the_snapshot_should_hold(world: &mut QueueSnapshotWorld, items: usize, batches: usize) {
+    let snapshot = snapshot(world);
+    assert_eq!(items, snapshot.entries.len());
+    assert_eq!(batches, snapshot.batches.len());
+}
+
+#[then(expr = "the restore should succeed with {int} items")]
+fn then_the_restore_should_succeed(world: &mut QueueSnapshotWorld, items: usize) {
+    match &world.result {
+        Some(Ok(restored)) => assert_eq!(items, *restored),
+        r => panic!("Restore should succeed, got {:#?}", r),
+    }
+}
+
+#[then("the restored queue should match the snapshot")]
+async fn then_the_restored_queue_should_match(world: &mut QueueSnapshotWorld) {
+    let restored = world.restored.load_queue().await.unwrap();
+    let snapshot = snapshot(world);
+    assert_eq!(
+        serde_json::to_value(&snapshot.entries).unwrap(),
+        serde_json::to_value(&restored).unwrap()
+    );
+}
+
+#[then("the restored queue should be empty")]
+async fn then_the_restored_queue_should_be_empty(world: &mut QueueSnapshotWorld) {
+    assert!(world.restored.load_queue().await.unwrap().is_empty());
+}
+
+#[then("the restore should be refused because the checksum does not match")]
+fn then_refused_for_checksum(world: &mut QueueSnapshotWorld) {
+    match &world.result {
+        Some(Err(QueueSnapshotError::ChecksumMismatch)) => (),
+        r => panic!("Restore should be refused, got {:#?}", r),
+    }
+}
+
+#[then("the restore should be refused because of an inconsistent batch")]
+fn then_refused_for_batch(world: &mut QueueSnapshotWorld) {
+    match &world.result {
+        Some(Err(QueueSnapshotError::InconsistentBatch(_))) => (),
+        r => panic!("Restore should be refused, got {:#?}", r),
+    }
+}
+
+#[then("the restore should be refused because items already exist")]
+fn then_refused_for_existing_items(world: &mut QueueSnapshotWorld) {
+    match &world.result {
+        Some(Err(QueueSnapshotError::PersistenceIssue(_))) => (),
+        r => panic!("Restore should be refused, got {:#?}", r),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    QueueSnapshotWorld::cucumber()
+        .run_and_exit("features/queue_snapshot.feature")
+        .await;
+}
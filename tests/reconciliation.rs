@@ -0,0 +1,235 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        bridge::{QueueItem, QueueManager, QueueStatus},
+        consume_queue::{consume_queue, MintRetryPolicy},
+        ids::StarknetAddress,
+        post_mint::PostMintHooks,
+        queue_admin::requeue_stale_processing_items,
+        reconciliation::TransactionReconciler,
+        webhook::WebhookNotifier,
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+        InMemoryQueueManager, InMemoryStarknetTransactionManager, InMemoryWebhookRepository,
+        ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const KEPLR_WALLET: &str = "k3plr-pk1";
+
+#[derive(Debug, World)]
+struct ReconciliationWorld {
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    clock: ManualClock,
+    retry_policy: MintRetryPolicy,
+    reconciler: TransactionReconciler,
+    requeued: usize,
+}
+
+impl Default for ReconciliationWorld {
+    fn default() -> Self {
+        let clock = ManualClock::new(1_672_531_200_000);
+        Self {
+            queue_manager: Arc::new(InMemoryQueueManager::with_clock(Arc::new(clock.clone()))),
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            clock,
+            retry_policy: MintRetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_secs(60),
+                max_delay: Duration::from_secs(300),
+            },
+            reconciler: TransactionReconciler::new(100),
+            requeued: 0,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+async fn queued_token(world: &ReconciliationWorld, token: &str) -> QueueItem {
+    world
+        .queue_manager
+        .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &project())
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued")
+}
+
+async fn consume(world: &ReconciliationWorld, cancel: &CancellationToken) {
+    let _ = consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(WebhookNotifier::new(
+            Arc::new(InMemoryWebhookRepository::new()),
+            Arc::new(world.clock.clone()),
+        )),
+        Arc::new(BatchAnalytics::new(
+            Arc::new(InMemoryBatchAnalyticsRepository::new()),
+            Arc::new(world.clock.clone()),
+            Duration::from_secs(86_400),
+        )),
+        &[],
+        &world.retry_policy,
+        cancel,
+    )
+    .await;
+}
+
+#[given(expr = "token {string} is queued")]
+async fn given_a_queued_token(world: &mut ReconciliationWorld, token: String) {
+    world
+        .queue_manager
+        .enqueue(
+            &KEPLR_WALLET.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given(expr = "the worker crashed after marking token {string} as processing")]
+async fn given_worker_crashed(world: &mut ReconciliationWorld, token: String) {
+    let id = queued_token(world, &token).await.id.unwrap();
+    world
+        .queue_manager
+        .update_queue_items_status(&[id], String::from(""), QueueStatus::Processing)
+        .await
+        .unwrap();
+}
+
+#[given(expr = "starknet reverts its mints with {string}")]
+async fn given_starknet_reverts_mints(world: &mut ReconciliationWorld, reason: String) {
+    world.starknet_manager.hold_transactions(false);
+    world.starknet_manager.revert_mints(&reason).await;
+}
+
+#[given(expr = "starknet rejects transactions with {string}")]
+async fn given_starknet_rejects(world: &mut ReconciliationWorld, reason: String) {
+    world
+        .starknet_manager
+        .reject_transactions(Some(&reason))
+        .await;
+}
+
+#[given("starknet accepts transactions")]
+async fn given_starknet_accepts(world: &mut ReconciliationWorld) {
+    world.starknet_manager.reject_transactions(None).await;
+}
+
+#[given("starknet cannot be reached")]
+fn given_starknet_unreachable(world: &mut ReconciliationWorld) {
+    world.starknet_manager.make_unreachable(true);
+}
+
+#[given("starknet can be reached again")]
+fn given_starknet_reachable(world: &mut ReconciliationWorld) {
+    world.starknet_manager.make_unreachable(false);
+}
+
+#[given("starknet holds transactions")]
+fn given_starknet_holds(world: &mut ReconciliationWorld) {
+    world.starknet_manager.hold_transactions(true);
+}
+
+#[given("starknet releases transactions")]
+fn given_starknet_releases(world: &mut ReconciliationWorld) {
+    world.starknet_manager.hold_transactions(false);
+}
+
+#[when("the worker consumes the queue")]
+async fn when_the_worker_consumes(world: &mut ReconciliationWorld) {
+    consume(world, &CancellationToken::new()).await;
+}
+
+#[when("the worker is stopped while waiting for its transaction")]
+async fn when_the_worker_is_stopped(world: &mut ReconciliationWorld) {
+    let cancel = CancellationToken::new();
+    let stop = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stop.cancel();
+    });
+    consume(world, &cancel).await;
+}
+
+#[when(expr = "{int} seconds elapse")]
+fn when_seconds_elapse(world: &mut ReconciliationWorld, seconds: u64) {
+    world.clock.advance(Duration::from_secs(seconds));
+}
+
+#[when("transactions are reconciled")]
+async fn when_transactions_are_reconciled(world: &mut ReconciliationWorld) {
+    world.requeued = world
+        .reconciler
+        .run(
+            world.queue_manager.clone(),
+            Arc::new(world.starknet_manager.clone()),
+            &PostMintHooks::new(),
+            Arc::new(InMemoryPostMintExecutionRepository::new()),
+            &WebhookNotifier::new(
+                Arc::new(InMemoryWebhookRepository::new()),
+                Arc::new(world.clock.clone()),
+            ),
+            &world.retry_policy,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+}
+
+#[when(expr = "items stalled in processing for {int} seconds are requeued")]
+async fn when_stalled_items_are_requeued(world: &mut ReconciliationWorld, seconds: u64) {
+    world.requeued = requeue_stale_processing_items(
+        world.queue_manager.clone(),
+        &world.clock,
+        Duration::from_secs(seconds),
+    )
+    .await
+    .unwrap()
+    .len();
+}
+
+#[then(expr = "{int} item(s) should have been requeued")]
+fn then_items_should_have_been_requeued(world: &mut ReconciliationWorld, count: usize) {
+    assert_eq!(count, world.requeued);
+}
+
+#[then(expr = "token {string} should be {string} after {int} attempt(s)")]
+async fn then_token_should_be(
+    world: &mut ReconciliationWorld,
+    token: String,
+    status: String,
+    attempts: i32,
+) {
+    let qi = queued_token(world, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(attempts, qi.attempts);
+}
+
+#[then(expr = "token {string} should have note {string}")]
+async fn then_token_should_have_note(world: &mut ReconciliationWorld, token: String, note: String) {
+    let qi = queued_token(world, &token).await;
+    assert_eq!(Some(note), qi.note);
+}
+
+#[tokio::main]
+async fn main() {
+    ReconciliationWorld::cucumber()
+        .run_and_exit("features/reconciliation.feature")
+        .await;
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        error_catalog::CatalogedError,
+        ids::TokenId,
+        project_registry::{
+            MigrationPolicy, MigrationWindow, Project, ProjectRegistry, TransferWindow,
+        },
+        reverse_bridge::{
+            consume_reverse_queue, handle_reverse_bridge_request, ReverseBridgeError,
+            ReverseBridgeRequest, ReverseQueueItem, ReverseQueueManager, StarknetTokenTransfer,
+        },
+        wallet_link::{WalletLink, WalletLinkRepository},
+    },
+    infrastructure::in_memory::{
+        InMemoryJunoTxBroadcaster, InMemoryReverseQueueManager, InMemoryStarknetTransferVerifier,
+        InMemoryWalletLinkRepository,
+    },
+};
+use cucumber::{given, then, when, World};
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+const STARKNET_ADMIN_ADDR: &str = "0x0ad0";
+
+#[derive(Debug, World)]
+struct ReverseBridgeWorld {
+    project_registry: ProjectRegistry,
+    verifier: InMemoryStarknetTransferVerifier,
+    wallet_links: Arc<dyn WalletLinkRepository>,
+    queue: InMemoryReverseQueueManager,
+    broadcaster: InMemoryJunoTxBroadcaster,
+    result: Option<Result<Vec<ReverseQueueItem>, ReverseBridgeError>>,
+}
+
+impl Default for ReverseBridgeWorld {
+    fn default() -> Self {
+        Self {
+            project_registry: ProjectRegistry::new(vec![Project {
+                juno_contract: "projectId".parse().unwrap(),
+                starknet_contract: STARKNET_PROJECT_ADDR.parse().unwrap(),
+                juno_admin_address: "juno-admin-account".parse().unwrap(),
+                mint_selector: "mint".into(),
+                migration_window: MigrationWindow::default(),
+                migration_policy: MigrationPolicy::TransferToAdmin,
+                transfer_window: TransferWindow::default(),
+            }]),
+            verifier: InMemoryStarknetTransferVerifier::new(),
+            wallet_links: Arc::new(InMemoryWalletLinkRepository::new()),
+            queue: InMemoryReverseQueueManager::new(),
+            broadcaster: InMemoryJunoTxBroadcaster::new(),
+            result: None,
+        }
+    }
+}
+
+fn token_ids(tokens: &str) -> Vec<TokenId> {
+    tokens.split(", ").map(|t| t.parse().unwrap()).collect()
+}
+
+async fn transferred(world: &ReverseBridgeWorld, hash: &str, tokens: &str, from: &str, to: &str) {
+    for token_id in token_ids(tokens) {
+        world
+            .verifier
+            .add_transfer(
+                hash,
+                StarknetTokenTransfer {
+                    // Receipts do not zero pad addresses
+                    contract: "0xd1e".parse().unwrap(),
+                    from: from.parse().unwrap(),
+                    to: to.parse().unwrap(),
+                    token_id,
+                },
+            )
+            .await;
+    }
+}
+
+async fn queued(world: &ReverseBridgeWorld) -> Vec<ReverseQueueItem> {
+    world
+        .queue
+        .get_customer_state(
+            &"0x5741".parse().unwrap(),
+            &STARKNET_PROJECT_ADDR.parse().unwrap(),
+        )
+        .await
+        .unwrap()
+}
+
+#[given(expr = "keplr wallet {word} is linked to starknet account {word}")]
+async fn given_wallet_link(world: &mut ReverseBridgeWorld, keplr: String, starknet: String) {
+    world
+        .wallet_links
+        .save_link(WalletLink {
+            keplr_wallet_pubkey: keplr.parse().unwrap(),
+            starknet_account_addr: starknet.parse().unwrap(),
+        })
+        .await
+        .unwrap();
+}
+
+#[given(expr = "starknet transaction {word} transferred tokens {string} from {word} to the admin")]
+async fn given_transfer_to_admin(
+    world: &mut ReverseBridgeWorld,
+    hash: String,
+    tokens: String,
+    from: String,
+) {
+    transferred(world, &hash, &tokens, &from, STARKNET_ADMIN_ADDR).await;
+}
+
+#[given(expr = "starknet transaction {word} burnt token {string} of {word}")]
+async fn given_burn(world: &mut ReverseBridgeWorld, hash: String, tokens: String, from: String) {
+    transferred(world, &hash, &tokens, &from, "0x0").await;
+}
+
+#[given("the juno node rejects transfers")]
+fn given_juno_rejects(world: &mut ReverseBridgeWorld) {
+    world.broadcaster.fail_transfers(true);
+}
+
+#[when(expr = "{word} asks tokens {string} back with starknet transaction {word} from {word}")]
+async fn when_asking_tokens_back(
+    world: &mut ReverseBridgeWorld,
+    keplr: String,
+    tokens: String,
+    hash: String,
+    starknet: String,
+) {
+    let request = ReverseBridgeRequest {
+        starknet_account_addr: starknet.parse().unwrap(),
+        keplr_wallet_pubkey: keplr.parse().unwrap(),
+        project_id: STARKNET_PROJECT_ADDR.parse().unwrap(),
+        token_ids: token_ids(&tokens),
+        transaction_hash: hash,
+    };
+    world.result = Some(
+        handle_reverse_bridge_request(
+            &request,
+            &world.project_registry,
+            STARKNET_ADMIN_ADDR,
+            Arc::new(world.verifier.clone()),
+            world.wallet_links.clone(),
+            Arc::new(world.queue.clone()),
+        )
+        .await,
+    );
+}
+
+#[when("the worker transfers tokens back")]
+async fn when_worker_transfers_back(world: &mut ReverseBridgeWorld) {
+    consume_reverse_queue(
+        Arc::new(world.queue.clone()),
+        Arc::new(world.broadcaster.clone()),
+        20,
+    )
+    .await
+    .unwrap();
+}
+
+#[then(expr = "tokens {string} should be {word}")]
+async fn then_tokens_status(world: &mut ReverseBridgeWorld, tokens: String, status: String) {
+    let items = queued(world).await;
+    for token in token_ids(&tokens) {
+        let item = items
+            .iter()
+            .find(|i| i.token_id == token)
+            .unwrap_or_else(|| panic!("Token {} should be queued", token));
+        assert_eq!(serde_json::json!(status), serde_json::json!(item.status));
+    }
+}
+
+#[then(expr = "tokens {string} should be transferred back to {word} in a single transaction")]
+async fn then_transferred_back(world: &mut ReverseBridgeWorld, tokens: String, keplr: String) {
+    let transfers = world.broadcaster.transfers().await;
+    assert_eq!(1, transfers.len());
+    assert_eq!("projectId", transfers[0].contract.as_str());
+    assert_eq!(keplr, transfers[0].recipient.as_str());
+    let mut sent = transfers[0].token_ids.clone();
+    sent.sort();
+    assert_eq!(token_ids(&tokens), sent);
+}
+
+#[then(expr = "the request should fail with {string}")]
+fn then_request_fails(world: &mut ReverseBridgeWorld, code: String) {
+    match &world.result {
+        Some(Err(e)) => assert_eq!(code, e.catalog_entry().code),
+        r => panic!("Request should have failed with {}, got {:#?}", code, r),
+    }
+}
+
+#[then("nothing should be queued")]
+async fn then_nothing_queued(world: &mut ReverseBridgeWorld) {
+    assert!(queued(world).await.is_empty());
+}
+
+#[tokio::main]
+async fn main() {
+    ReverseBridgeWorld::cucumber()
+        .run_and_exit("features/reverse_bridge.feature")
+        .await;
+}
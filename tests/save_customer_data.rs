@@ -1,10 +1,17 @@
 use std::{future::ready, sync::Arc};
 
 use bridge_juno_to_starknet_backend::{
-    domain::save_customer_data::{
-        handle_save_customer_data, DataRepository, SaveCustomerDataRequest,
+    domain::{
+        breakglass::Operator,
+        bridge::{PubKey, SignedHash},
+        error_catalog::CatalogedError,
+        save_customer_data::{
+            handle_approve_sender, handle_authorize_sender, handle_save_customer_data,
+            ApproveSenderRequest, AuthorizeSenderRequest, DataRepository, SaveCustomerDataError,
+            SaveCustomerDataOutcome, SaveCustomerDataRequest,
+        },
     },
-    infrastructure::in_memory::InMemoryDataRepository,
+    infrastructure::in_memory::{InMemoryDataRepository, TestSignedHashValidator},
 };
 use cucumber::{gherkin::Step, given, then, when, World};
 
@@ -12,6 +19,8 @@ use cucumber::{gherkin::Step, given, then, when, World};
 struct SaveCustomerDataWorld {
     request: Option<SaveCustomerDataRequest>,
     response: bool,
+    outcome: Option<SaveCustomerDataOutcome>,
+    authorization: Option<Result<(), SaveCustomerDataError>>,
     data_repository: Option<Arc<dyn DataRepository>>,
 }
 
@@ -21,6 +30,8 @@ impl Default for SaveCustomerDataWorld {
         Self {
             request: None,
             response: false,
+            outcome: None,
+            authorization: None,
             data_repository: None,
         }
     }
@@ -34,18 +45,21 @@ impl SaveCustomerDataWorld {
 
 #[given("a request")]
 fn given_a_request(case: &mut SaveCustomerDataWorld, step: &Step) {
-    let Some(table) = step.table.as_ref() else { return };
+    let Some(table) = step.table.as_ref() else {
+        return;
+    };
 
     for row in table.rows.iter().skip(1) {
         // Retrieving col values with number.
         let request = SaveCustomerDataRequest::new(
-            &row[0],
-            &row[1],
+            row[0].parse().unwrap(),
+            row[1].parse().unwrap(),
             row[2]
                 .replace("[", "")
                 .replace("]", "")
                 .split(", ")
-                .collect::<Vec<&str>>(),
+                .map(|t| t.parse().unwrap())
+                .collect(),
         );
 
         case.request = Some(request);
@@ -65,6 +79,7 @@ async fn when_i_execute_the_request(case: &mut SaveCustomerDataWorld) {
     }
 
     case.response = response.is_err();
+    case.outcome = response.ok();
 }
 
 #[then("data should have been persisted to database")]
@@ -81,7 +96,128 @@ async fn then_data_should_have_been_persited(case: &mut SaveCustomerDataWorld) {
     };
 }
 
-fn main() {
+#[then(expr = "{int} token(s) should have been added, {int} duplicate(s) and {int} registered")]
+fn then_outcome(case: &mut SaveCustomerDataWorld, added: usize, duplicates: usize, total: usize) {
+    assert_eq!(
+        Some(SaveCustomerDataOutcome {
+            added,
+            duplicates,
+            total
+        }),
+        case.outcome
+    );
+}
+
+fn signed_hash(signature: &str) -> SignedHash {
+    SignedHash {
+        pub_key: PubKey {
+            key_type: "tendermint/PubKeySecp256k1".into(),
+            key_value: "Avt8e5UqfoRAh0RBUzHCu9arv7UFEFdfcv657h6TtSZE".into(),
+        },
+        signature: signature.into(),
+    }
+}
+
+#[when(expr = "{word} authorizes sender {word} on project {word} with sender signature {word}")]
+async fn when_customer_authorizes_sender(
+    case: &mut SaveCustomerDataWorld,
+    keplr: String,
+    sender: String,
+    project: String,
+    sender_signature: String,
+) {
+    let request = AuthorizeSenderRequest {
+        signed_hash: signed_hash("aValidSignedHash"),
+        keplr_wallet_pubkey: keplr.parse().unwrap(),
+        project_id: project.parse().unwrap(),
+        sender: sender.parse().unwrap(),
+        sender_signed_hash: match sender_signature.as_str() {
+            "none" => None,
+            signature => Some(signed_hash(signature)),
+        },
+    };
+    case.authorization = Some(
+        handle_authorize_sender(
+            &request,
+            Arc::new(TestSignedHashValidator {}),
+            case.data_repository.as_ref().unwrap().clone(),
+        )
+        .await,
+    );
+}
+
+#[when(expr = "operator {string} approves sender {word} for {word} on project {word}")]
+async fn when_operator_approves_sender(
+    case: &mut SaveCustomerDataWorld,
+    operator: String,
+    sender: String,
+    keplr: String,
+    project: String,
+) {
+    let request = ApproveSenderRequest {
+        keplr_wallet_pubkey: keplr.parse().unwrap(),
+        project_id: project.parse().unwrap(),
+        sender: sender.parse().unwrap(),
+        reason: "DAO proposal 42 transfers the tokens of the customer".into(),
+    };
+    case.authorization = Some(
+        handle_approve_sender(
+            &request,
+            &Operator {
+                name: operator,
+                api_key: String::new(),
+            },
+            case.data_repository.as_ref().unwrap().clone(),
+        )
+        .await,
+    );
+}
+
+#[then(expr = "the authorization should be refused with code {string}")]
+fn then_authorization_refused(case: &mut SaveCustomerDataWorld, code: String) {
+    match case.authorization.as_ref() {
+        Some(Err(e)) => assert_eq!(code, e.catalog_entry().code),
+        Some(Ok(_)) => panic!("Authorization should have been refused"),
+        None => panic!("No authorization was made"),
+    }
+}
+
+#[then(expr = "{word} should be an authorized sender of {word} on project {word}")]
+async fn then_sender_authorized(
+    case: &mut SaveCustomerDataWorld,
+    sender: String,
+    keplr: String,
+    project: String,
+) {
+    let senders = case
+        .data_repository
+        .as_ref()
+        .unwrap()
+        .get_authorized_senders(&keplr.parse().unwrap(), &project.parse().unwrap())
+        .await
+        .unwrap_or_default();
+    assert!(senders.iter().any(|s| sender == s.as_str()));
+}
+
+#[then(expr = "{word} should not be an authorized sender of {word} on project {word}")]
+async fn then_sender_not_authorized(
+    case: &mut SaveCustomerDataWorld,
+    sender: String,
+    keplr: String,
+    project: String,
+) {
+    let senders = case
+        .data_repository
+        .as_ref()
+        .unwrap()
+        .get_authorized_senders(&keplr.parse().unwrap(), &project.parse().unwrap())
+        .await
+        .unwrap_or_default();
+    assert!(senders.iter().all(|s| sender != s.as_str()));
+}
+
+#[tokio::main]
+async fn main() {
     let repo = Arc::new(InMemoryDataRepository::new());
     let world =
         SaveCustomerDataWorld::cucumber().before(move |_feature, _rule, _scenario, _world| {
@@ -89,5 +225,7 @@ fn main() {
             Box::pin(ready(()))
         });
 
-    futures::executor::block_on(world.run_and_exit("features/save-customer-data.feature"));
+    world
+        .run_and_exit("features/save-customer-data.feature")
+        .await;
 }
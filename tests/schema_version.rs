@@ -0,0 +1,63 @@
+use bridge_juno_to_starknet_backend::infrastructure::migrations::{
+    check_schema_version, expected_migrations, MigrationError,
+};
+use cucumber::{given, then, when, World};
+
+#[derive(Debug, Default, World)]
+struct SchemaVersionWorld {
+    applied: Vec<String>,
+    result: Option<Result<(), MigrationError>>,
+}
+
+#[given("every expected migration is applied")]
+fn given_every_migration(world: &mut SchemaVersionWorld) {
+    world.applied = expected_migrations()
+        .into_iter()
+        .map(String::from)
+        .collect();
+}
+
+#[given("every expected migration but the latest one is applied")]
+fn given_latest_migration_missing(world: &mut SchemaVersionWorld) {
+    given_every_migration(world);
+    world.applied.pop();
+}
+
+#[given(expr = "migration {string} is applied")]
+fn given_migration(world: &mut SchemaVersionWorld, name: String) {
+    world.applied.push(name);
+}
+
+#[when("the schema version is checked")]
+fn when_checking(world: &mut SchemaVersionWorld) {
+    world.result = Some(check_schema_version(&world.applied));
+}
+
+#[then("the schema should be accepted")]
+fn then_accepted(world: &mut SchemaVersionWorld) {
+    assert!(matches!(world.result, Some(Ok(()))), "{:#?}", world.result);
+}
+
+#[then("the schema should be refused as older, missing the latest migration")]
+fn then_refused_as_older(world: &mut SchemaVersionWorld) {
+    let latest = *expected_migrations().last().unwrap();
+    match &world.result {
+        Some(Err(MigrationError::SchemaBehind(missing))) => assert_eq!(&vec![latest], missing),
+        r => panic!("Schema should be older, got {:#?}", r),
+    }
+}
+
+#[then(expr = "the schema should be refused as newer because of {string}")]
+fn then_refused_as_newer(world: &mut SchemaVersionWorld, name: String) {
+    match &world.result {
+        Some(Err(MigrationError::SchemaAhead(unknown))) => assert_eq!(&vec![name], unknown),
+        r => panic!("Schema should be newer, got {:#?}", r),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    SchemaVersionWorld::cucumber()
+        .run_and_exit("features/schema_version.feature")
+        .await;
+}
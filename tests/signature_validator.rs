@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use bridge_juno_to_starknet_backend::{
+    domain::bridge::{PubKey, SignedHash, SignedHashValidator, SignedHashValidatorError},
+    infrastructure::signature::{configure_signed_hash_validator, SignatureValidatorConfigError},
+};
+use cucumber::{given, then, when, World};
+
+#[derive(Debug, Default, World)]
+struct SignatureWorld {
+    validator: Option<Result<Arc<dyn SignedHashValidator>, SignatureValidatorConfigError>>,
+    verification: Option<Result<String, SignedHashValidatorError>>,
+}
+
+impl SignatureWorld {
+    fn validator(&self) -> &Arc<dyn SignedHashValidator> {
+        match self.validator.as_ref() {
+            Some(Ok(v)) => v,
+            other => panic!("Validator should have been configured, got {:#?}", other),
+        }
+    }
+}
+
+#[given(expr = "signature validators {string}")]
+fn given_validators(world: &mut SignatureWorld, names: String) {
+    let names: Vec<String> = names
+        .split(',')
+        .filter(|n| !n.is_empty())
+        .map(String::from)
+        .collect();
+    world.validator = Some(configure_signed_hash_validator(&names));
+}
+
+#[when("a customer submits a forged signature")]
+fn when_forged_signature(world: &mut SignatureWorld) {
+    let signed_hash = SignedHash {
+        pub_key: PubKey {
+            key_type: "tendermint/PubKeySecp256k1".into(),
+            key_value: "A2mh3UuNkOBpvEmK9Pp5xrT6m0hcXj1dE7P4UAJjmfqP".into(),
+        },
+        signature: "Zm9yZ2VkLXNpZ25hdHVyZQ==".into(),
+    };
+    world.verification = Some(world.validator().verify(
+        &signed_hash,
+        "0x0123456789abcdef",
+        "juno1customer0000000000000000000000000000",
+    ));
+}
+
+const METAMASK_SIGNATURE: &str = "0x3a1554573a1fcf29910edacc759fc428d6389d2a1f94a96686214c691fa930fa628515bd431b5e81b3583c9f0200dfcc0a98f659a38395e969bb3390768427941c";
+
+fn submit_metamask_signature(world: &mut SignatureWorld, keplr_wallet_pubkey: &str) {
+    let signed_hash = SignedHash {
+        pub_key: PubKey {
+            key_type: "ethermint/PubKeyEthSecp256k1".into(),
+            key_value: "Ak47ga+cIjTK0J1nnOYDXtE5I0fOZM5AX13NNiKKJd5u".into(),
+        },
+        signature: METAMASK_SIGNATURE.into(),
+    };
+    world.verification = Some(world.validator().verify(
+        &signed_hash,
+        "0x063675fa1ecea10063722e61557ed7f49ed2503d6cdd74f4b31e9770b473650c",
+        keplr_wallet_pubkey,
+    ));
+}
+
+#[when("a customer submits a metamask signature")]
+fn when_metamask_signature(world: &mut SignatureWorld) {
+    submit_metamask_signature(world, "juno1936ndcmqtkwpdfar67ccnrjjjwt2vhprn58u70");
+}
+
+#[when(expr = "a customer submits a metamask signature for wallet {string}")]
+fn when_metamask_signature_for_wallet(world: &mut SignatureWorld, keplr_wallet_pubkey: String) {
+    submit_metamask_signature(world, &keplr_wallet_pubkey);
+}
+
+#[then("the metamask signature should be accepted")]
+fn then_metamask_accepted(world: &mut SignatureWorld) {
+    assert_eq!(
+        Some(METAMASK_SIGNATURE),
+        world.verification.as_ref().and_then(|v| v.as_deref().ok())
+    );
+}
+
+#[then("the signature should be rejected")]
+fn then_rejected(world: &mut SignatureWorld) {
+    assert!(matches!(
+        world.verification,
+        Some(Err(SignedHashValidatorError::FailedToVerifyHash))
+    ));
+}
+
+#[then("the signature should be accepted")]
+fn then_accepted(world: &mut SignatureWorld) {
+    assert_eq!(
+        Some("Zm9yZ2VkLXNpZ25hdHVyZQ=="),
+        world.verification.as_ref().and_then(|v| v.as_deref().ok())
+    );
+}
+
+#[then(expr = "configuration should fail because validator {string} is unknown")]
+fn then_unknown_validator(world: &mut SignatureWorld, name: String) {
+    match world.validator.as_ref() {
+        Some(Err(SignatureValidatorConfigError::UnknownValidator(n))) => assert_eq!(&name, n),
+        other => panic!("Expected an unknown validator error, got {:#?}", other),
+    }
+}
+
+#[then("configuration should fail because no validator is configured")]
+fn then_no_validator(world: &mut SignatureWorld) {
+    assert!(matches!(
+        world.validator,
+        Some(Err(SignatureValidatorConfigError::NoValidator))
+    ));
+}
+
+#[tokio::main]
+async fn main() {
+    SignatureWorld::cucumber()
+        .run_and_exit("features/signature_validator.feature")
+        .await;
+}
@@ -0,0 +1,356 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        bridge::{QueueItem, QueueManager, QueueStatus},
+        ids::{StarknetAddress, TokenId},
+        pagination::PageRequest,
+        save_customer_data::{CustomerKeys, DataRepository},
+        status_message::StatusNote,
+    },
+    infrastructure::sqlite::{SqliteDataRepository, SqliteDatabase, SqliteQueueManager},
+};
+use cucumber::{given, then, when, World};
+use uuid::Uuid;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0c4a";
+
+#[derive(Debug, World)]
+struct SqliteWorld {
+    data_repository: Arc<dyn DataRepository>,
+    queue_manager: Arc<dyn QueueManager>,
+    database_path: Option<PathBuf>,
+}
+
+impl SqliteWorld {
+    fn open(database_url: &str) -> Self {
+        let database = SqliteDatabase::open(database_url).unwrap();
+        Self {
+            data_repository: Arc::new(SqliteDataRepository::new(database.clone())),
+            queue_manager: Arc::new(SqliteQueueManager::new(database, 10, "worker-test")),
+            database_path: None,
+        }
+    }
+}
+
+impl Default for SqliteWorld {
+    fn default() -> Self {
+        Self::open("sqlite::memory:")
+    }
+}
+
+impl Drop for SqliteWorld {
+    fn drop(&mut self) {
+        if let Some(path) = self.database_path.take() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+fn token_ids(tokens: &str) -> Vec<TokenId> {
+    tokens.split(',').map(|t| t.parse().unwrap()).collect()
+}
+
+async fn queued_token(world: &SqliteWorld, token: &str) -> QueueItem {
+    queued_token_of(world, "k3plr-pk1", token).await
+}
+
+async fn queued_token_of(world: &SqliteWorld, keplr: &str, token: &str) -> QueueItem {
+    world
+        .queue_manager
+        .get_customer_migration_state(&keplr.parse().unwrap(), &project())
+        .await
+        .into_iter()
+        .find(|qi| qi.token_id == token)
+        .expect("Token should be queued")
+}
+
+async fn save_tokens(world: &SqliteWorld, keplr: &str, tokens: &str, project_id: &str) {
+    world
+        .data_repository
+        .save_customer_keys(CustomerKeys {
+            keplr_wallet_pubkey: keplr.parse().unwrap(),
+            project_id: project_id.parse().unwrap(),
+            token_ids: token_ids(tokens),
+        })
+        .await
+        .unwrap();
+}
+
+#[given(expr = "customer {string} saved tokens {string} of project {string}")]
+async fn given_saved_tokens(
+    world: &mut SqliteWorld,
+    keplr: String,
+    tokens: String,
+    project_id: String,
+) {
+    save_tokens(world, &keplr, &tokens, &project_id).await;
+}
+
+#[when(expr = "customer {string} saves tokens {string} of project {string}")]
+async fn when_saving_tokens(
+    world: &mut SqliteWorld,
+    keplr: String,
+    tokens: String,
+    project_id: String,
+) {
+    save_tokens(world, &keplr, &tokens, &project_id).await;
+}
+
+#[then(expr = "customer {string} should have tokens {string} of project {string}")]
+async fn then_customer_should_have_tokens(
+    world: &mut SqliteWorld,
+    keplr: String,
+    tokens: String,
+    project_id: String,
+) {
+    let keys = world
+        .data_repository
+        .get_customer_keys(&keplr.parse().unwrap(), &project_id.parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(token_ids(&tokens), keys.token_ids);
+}
+
+#[given(expr = "tokens {string} of {string} are queued")]
+async fn given_queued_tokens(world: &mut SqliteWorld, tokens: String, keplr: String) {
+    world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            token_ids(&tokens),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given(expr = "tokens {string} of {string} are queued on project {word}")]
+async fn given_queued_tokens_on_project(
+    world: &mut SqliteWorld,
+    tokens: String,
+    keplr: String,
+    project: String,
+) {
+    world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project.parse().unwrap(),
+            token_ids(&tokens),
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[when(expr = "token {string} is minted with transaction {string}")]
+async fn when_token_is_minted(world: &mut SqliteWorld, token: String, transaction_hash: String) {
+    let qi = queued_token(world, &token).await;
+    world
+        .queue_manager
+        .update_queue_items_status(&[qi.id.unwrap()], transaction_hash, QueueStatus::Success)
+        .await
+        .unwrap();
+}
+
+#[when(expr = "token {string} is retried in {int} seconds")]
+async fn when_token_is_retried(world: &mut SqliteWorld, token: String, delay: u64) {
+    let qi = queued_token(world, &token).await;
+    world
+        .queue_manager
+        .schedule_retry(
+            &[qi.id.unwrap()],
+            &StatusNote::new("mint_failed"),
+            Duration::from_secs(delay),
+        )
+        .await
+        .unwrap();
+}
+
+#[when(expr = "token {string} is dead lettered")]
+async fn when_token_is_dead_lettered(world: &mut SqliteWorld, token: String) {
+    let qi = queued_token(world, &token).await;
+    world
+        .queue_manager
+        .dead_letter_queue_items(&[qi.id.unwrap()], &StatusNote::new("mint_failed"))
+        .await
+        .unwrap();
+}
+
+#[when(expr = "token {string} is requeued")]
+async fn when_token_is_requeued(world: &mut SqliteWorld, token: String) {
+    let qi = queued_token(world, &token).await;
+    world
+        .queue_manager
+        .requeue_dead_letters(&[qi.id.unwrap()], &StatusNote::new("requeued"))
+        .await
+        .unwrap();
+}
+
+#[given(
+    expr = "a database created before the active token index where {string} then {string} queued token {string}"
+)]
+async fn given_database_before_active_token_index(
+    world: &mut SqliteWorld,
+    first: String,
+    second: String,
+    token: String,
+) {
+    let path = std::env::temp_dir().join(format!("bridge-{}.sqlite", Uuid::new_v4()));
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    // Tables and indexes preceding the duplicate repair, as they were before the index
+    let schema: Vec<&str> = include_str!("../data/sqlite/schema.sql")
+        .lines()
+        .take_while(|statement| !statement.starts_with("UPDATE migration_queue"))
+        .collect();
+    connection.execute_batch(&schema.join("\n")).unwrap();
+    for (created_at, keplr) in [(1, first), (2, second)] {
+        connection
+            .execute(
+                "INSERT INTO migration_queue (id, keplr_wallet_pubkey, starknet_wallet_pubkey, \
+                 project_id, token_id, created_at, updated_at) VALUES (?1, ?2, '0x5741', ?3, ?4, \
+                 ?5, ?5)",
+                rusqlite::params![
+                    Uuid::new_v4().to_string(),
+                    keplr,
+                    STARKNET_PROJECT_ADDR,
+                    token,
+                    created_at
+                ],
+            )
+            .unwrap();
+    }
+    world.database_path = Some(path);
+}
+
+#[when("the database is opened")]
+async fn when_database_is_opened(world: &mut SqliteWorld) {
+    let path = world.database_path.take().unwrap();
+    *world = SqliteWorld::open(path.to_str().unwrap());
+    world.database_path = Some(path);
+}
+
+#[then(expr = "token {string} of {string} should be {string} with note {string}")]
+async fn then_token_of_should_have_note(
+    world: &mut SqliteWorld,
+    token: String,
+    keplr: String,
+    status: String,
+    note: String,
+) {
+    let qi = queued_token_of(world, &keplr, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(Some(note), qi.note);
+}
+
+#[then(expr = "token {string} of {string} should be {string}")]
+async fn then_token_of_should_be(
+    world: &mut SqliteWorld,
+    token: String,
+    keplr: String,
+    status: String,
+) {
+    let qi = queued_token_of(world, &keplr, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(None, qi.note);
+}
+
+#[then(expr = "the batch should contain {int} items")]
+async fn then_batch_should_contain(world: &mut SqliteWorld, count: usize) {
+    assert_eq!(
+        count,
+        world.queue_manager.get_batch(&[]).await.unwrap().len()
+    );
+}
+
+#[then(expr = "the batch should contain {int} items once project {word} is closed")]
+async fn then_batch_without_closed_project(world: &mut SqliteWorld, count: usize, project: String) {
+    let closed: StarknetAddress = project.parse().unwrap();
+    let batch = world
+        .queue_manager
+        .get_batch(&[closed.clone()])
+        .await
+        .unwrap();
+    assert_eq!(count, batch.len());
+    assert!(batch.iter().all(|qi| qi.project_id != closed));
+}
+
+#[then(expr = "token {string} should be {string} with transaction hash {string}")]
+async fn then_token_should_have_hash(
+    world: &mut SqliteWorld,
+    token: String,
+    status: String,
+    transaction_hash: String,
+) {
+    let qi = queued_token(world, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(Some(transaction_hash), qi.transaction_hash);
+}
+
+#[then(expr = "token {string} should be {string} after {int} attempts")]
+async fn then_token_should_have_attempts(
+    world: &mut SqliteWorld,
+    token: String,
+    status: String,
+    attempts: i32,
+) {
+    let qi = queued_token(world, &token).await;
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert_eq!(attempts, qi.attempts);
+}
+
+#[then(expr = "token {string} history should be {string}")]
+async fn then_token_history_should_be(world: &mut SqliteWorld, token: String, statuses: String) {
+    let qi = queued_token(world, &token).await;
+    let history = world
+        .queue_manager
+        .get_queue_item_history(&qi.id.unwrap())
+        .await
+        .unwrap();
+    let expected: Vec<&str> = statuses.split(',').collect();
+    assert_eq!(
+        serde_json::json!(expected),
+        serde_json::json!(history
+            .iter()
+            .map(|t| t.status.clone())
+            .collect::<Vec<QueueStatus>>())
+    );
+    assert!(history
+        .iter()
+        .all(|t| Some("worker-test") == t.worker_id.as_deref()));
+}
+
+#[then(expr = "listing the queue by pages of {int} should return {int} items")]
+async fn then_listing_should_return(world: &mut SqliteWorld, limit: i64, count: usize) {
+    let mut listed = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page = PageRequest::new(cursor.as_deref(), Some(limit), limit).unwrap();
+        let page = world
+            .queue_manager
+            .list_queue_items(None, &page)
+            .await
+            .unwrap();
+        listed.extend(page.items);
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(count, listed.len());
+}
+
+#[tokio::main]
+async fn main() {
+    SqliteWorld::cucumber()
+        .run_and_exit("features/sqlite.feature")
+        .await;
+}
@@ -0,0 +1,177 @@
+use bridge_juno_to_starknet_backend::infrastructure::starknet::{
+    CalldataTemplate, CalldataTemplateError, CalldataTemplates, TokenIdFormatError, TokenIdFormats,
+};
+use cucumber::{given, then, when, World};
+use starknet::core::{types::FieldElement, utils::starknet_keccak};
+
+#[derive(Debug, Default, World)]
+struct TokenIdFormatWorld {
+    definitions: Vec<String>,
+    calldata_definitions: Vec<String>,
+    project: String,
+    encoded: Option<Result<FieldElement, TokenIdFormatError>>,
+    calldata: Vec<FieldElement>,
+}
+
+impl TokenIdFormatWorld {
+    fn formats(&self) -> TokenIdFormats {
+        TokenIdFormats::parse(&self.definitions).unwrap()
+    }
+
+    fn felt(&self) -> FieldElement {
+        match &self.encoded {
+            Some(Ok(felt)) => *felt,
+            e => panic!("Token id should be encoded, got {:#?}", e),
+        }
+    }
+}
+
+#[given(expr = "project {string} token ids are formatted as {string}")]
+fn given_project_format(world: &mut TokenIdFormatWorld, project: String, format: String) {
+    world.definitions.push(format!("{}={}", project, format));
+}
+
+#[given(expr = "project {string} mints with calldata {string}")]
+fn given_project_calldata(world: &mut TokenIdFormatWorld, project: String, template: String) {
+    world
+        .calldata_definitions
+        .push(format!("{}={}", project, template));
+}
+
+#[when(expr = "token {string} of project {string} is encoded")]
+fn when_token_is_encoded(world: &mut TokenIdFormatWorld, token_id: String, project: String) {
+    world.encoded = Some(world.formats().for_project(&project).encode(&token_id));
+    world.project = project;
+}
+
+#[when(expr = "mint calldata of token {string} of project {string} is encoded for {string}")]
+fn when_calldata_is_encoded(
+    world: &mut TokenIdFormatWorld,
+    token_id: String,
+    project: String,
+    to: String,
+) {
+    let format = world.formats().for_project(&project);
+    world.encoded = Some(format.encode(&token_id));
+    world.calldata = CalldataTemplate::default()
+        .encode(FieldElement::from_hex_be(&to).unwrap(), &token_id, format)
+        .unwrap();
+}
+
+#[when(
+    expr = "mint calldata of token {string} of project {string} is encoded for {string} as {string}"
+)]
+fn when_calldata_is_encoded_as(
+    world: &mut TokenIdFormatWorld,
+    token_id: String,
+    project: String,
+    to: String,
+    template: String,
+) {
+    let format = world.formats().for_project(&project);
+    world.calldata = CalldataTemplate::parse(&template)
+        .unwrap()
+        .encode(FieldElement::from_hex_be(&to).unwrap(), &token_id, format)
+        .unwrap();
+}
+
+#[when(
+    expr = "mint calldata of token {string} of project {string} is encoded for {string} with its template"
+)]
+fn when_calldata_is_encoded_with_project_template(
+    world: &mut TokenIdFormatWorld,
+    token_id: String,
+    project: String,
+    to: String,
+) {
+    let format = world.formats().for_project(&project);
+    world.calldata = CalldataTemplates::parse(&world.calldata_definitions)
+        .unwrap()
+        .for_project(&project)
+        .encode(FieldElement::from_hex_be(&to).unwrap(), &token_id, format)
+        .unwrap();
+}
+
+#[then(expr = "the token felt should be {string}")]
+fn then_felt_should_be(world: &mut TokenIdFormatWorld, felt: String) {
+    assert_eq!(FieldElement::from_hex_be(&felt).unwrap(), world.felt());
+}
+
+#[then(expr = "the token felt should be the starknet keccak of {string}")]
+fn then_felt_should_be_hashed(world: &mut TokenIdFormatWorld, token_id: String) {
+    assert_eq!(starknet_keccak(token_id.as_bytes()), world.felt());
+}
+
+#[then(expr = "the token felt should decode to {string}")]
+fn then_felt_should_decode_to(world: &mut TokenIdFormatWorld, token_id: String) {
+    let format = world.formats().for_project(&world.project);
+    assert_eq!(Some(token_id), format.decode(world.felt()));
+}
+
+#[then("the token felt should not decode")]
+fn then_felt_should_not_decode(world: &mut TokenIdFormatWorld) {
+    let format = world.formats().for_project(&world.project);
+    assert_eq!(None, format.decode(world.felt()));
+}
+
+#[then("the token id should be invalid")]
+fn then_token_id_should_be_invalid(world: &mut TokenIdFormatWorld) {
+    match &world.encoded {
+        Some(Err(TokenIdFormatError::InvalidTokenId(_))) => (),
+        e => panic!("Token id should be invalid, got {:#?}", e),
+    }
+}
+
+#[then("the calldata should hold the recipient then the token felt as a Uint256")]
+fn then_calldata_should_hold_token(world: &mut TokenIdFormatWorld) {
+    let bytes = world.felt().to_bytes_be();
+    let (high, low) = bytes.split_at(16);
+    assert_eq!(
+        vec![
+            FieldElement::from_hex_be("0x5741").unwrap(),
+            FieldElement::from_byte_slice_be(low).unwrap(),
+            FieldElement::from_byte_slice_be(high).unwrap(),
+        ],
+        world.calldata
+    );
+}
+
+#[then(expr = "the calldata should be {string}")]
+fn then_calldata_should_be(world: &mut TokenIdFormatWorld, calldata: String) {
+    let expected: Vec<FieldElement> = calldata
+        .split_whitespace()
+        .map(|felt| FieldElement::from_hex_be(felt).unwrap())
+        .collect();
+    assert_eq!(expected, world.calldata);
+}
+
+#[then(expr = "calldata template {string} should be refused")]
+fn then_calldata_template_should_be_refused(_world: &mut TokenIdFormatWorld, template: String) {
+    match CalldataTemplate::parse(&template) {
+        Err(CalldataTemplateError::InvalidField(_)) => (),
+        r => panic!("Template should be refused, got {:#?}", r),
+    }
+}
+
+#[then(expr = "calldata templates {string} should be refused")]
+fn then_calldata_templates_should_be_refused(_world: &mut TokenIdFormatWorld, definition: String) {
+    match CalldataTemplates::parse(&[definition]) {
+        Err(CalldataTemplateError::InvalidDefinition(_)) => (),
+        r => panic!("Definition should be refused, got {:#?}", r),
+    }
+}
+
+#[then(expr = "token id formats {string} should be refused")]
+fn then_formats_should_be_refused(_world: &mut TokenIdFormatWorld, definition: String) {
+    match TokenIdFormats::parse(&[definition]) {
+        Err(TokenIdFormatError::InvalidDefinition(_)) => (),
+        r => panic!("Definition should be refused, got {:#?}", r),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    TokenIdFormatWorld::cucumber()
+        .run_and_exit("features/token_id_format.feature")
+        .await;
+}
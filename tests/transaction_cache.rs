@@ -0,0 +1,95 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        bridge::{Transaction, TransactionRepository},
+        transaction_cache::CachedTransactionRepository,
+    },
+    infrastructure::in_memory::{InMemoryTransactionRepository, ManualClock},
+};
+use cucumber::{gherkin::Step, given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, World)]
+struct CacheWorld {
+    node: InMemoryTransactionRepository,
+    clock: ManualClock,
+    cache: Option<Arc<dyn TransactionRepository>>,
+    found: HashMap<String, usize>,
+}
+
+impl Default for CacheWorld {
+    fn default() -> Self {
+        Self {
+            node: InMemoryTransactionRepository::new(Vec::new()),
+            clock: ManualClock::new(1_672_531_200_000),
+            cache: None,
+            found: HashMap::new(),
+        }
+    }
+}
+
+#[given("the following juno transactions")]
+fn given_juno_transactions(world: &mut CacheWorld, step: &Step) {
+    let transactions: Vec<Transaction> =
+        serde_json::from_str(step.docstring.as_ref().unwrap()).unwrap();
+    world.node = InMemoryTransactionRepository::new(transactions);
+}
+
+#[given(expr = "transactions are cached for {int} seconds")]
+fn given_cache_ttl(world: &mut CacheWorld, seconds: u64) {
+    world.cache = Some(Arc::new(CachedTransactionRepository::new(
+        Arc::new(world.node.clone()),
+        Duration::from_secs(seconds),
+        Arc::new(world.clock.clone()),
+    )));
+}
+
+#[given("the juno node fails")]
+fn given_node_fails(world: &mut CacheWorld) {
+    world.node.fail_fetches(true);
+}
+
+#[given("the juno node recovers")]
+fn given_node_recovers(world: &mut CacheWorld) {
+    world.node.fail_fetches(false);
+}
+
+#[when(expr = "I fetch the transactions of tokens {string} on contract {string}")]
+async fn when_fetching(world: &mut CacheWorld, tokens: String, contract: String) {
+    let cache = world.cache.clone().unwrap();
+    for token in tokens.split(", ") {
+        if let Ok(transactions) = cache
+            .get_transactions_for_contract(
+                &contract.parse().unwrap(),
+                &token.parse().unwrap(),
+                &CancellationToken::new(),
+            )
+            .await
+        {
+            world.found.insert(token.to_string(), transactions.len());
+        }
+    }
+}
+
+#[when(expr = "{int} seconds elapse")]
+fn when_seconds_elapse(world: &mut CacheWorld, seconds: u64) {
+    world.clock.advance(Duration::from_secs(seconds));
+}
+
+#[then(expr = "{int} transaction(s) should have been found for token {string}")]
+fn then_transactions_found(world: &mut CacheWorld, count: usize, token: String) {
+    assert_eq!(Some(&count), world.found.get(&token));
+}
+
+#[then(expr = "the juno node should have been called {int} time(s)")]
+fn then_node_called(world: &mut CacheWorld, calls: usize) {
+    assert_eq!(calls, world.node.fetches());
+}
+
+#[tokio::main]
+async fn main() {
+    CacheWorld::cucumber()
+        .run_and_exit("features/transaction_cache.feature")
+        .await;
+}
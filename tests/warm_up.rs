@@ -0,0 +1,129 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use bridge_juno_to_starknet_backend::domain::warm_up::{
+    warm_up, WarmUp, WarmUpError, WarmUpOutcome,
+};
+use cucumber::{given, then, when, World};
+
+#[derive(Debug, Clone, Copy)]
+enum Behavior {
+    Answering,
+    Failing,
+    Hanging,
+}
+
+#[derive(Debug)]
+struct StubDependency {
+    name: String,
+    behavior: Behavior,
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl WarmUp for StubDependency {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn warm_up(&self) -> Result<(), WarmUpError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        match self.behavior {
+            Behavior::Answering => Ok(()),
+            Behavior::Failing => Err(WarmUpError::Failed("connection refused".into())),
+            Behavior::Hanging => {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, World)]
+struct WarmUpWorld {
+    dependencies: Vec<Arc<StubDependency>>,
+    outcomes: Vec<WarmUpOutcome>,
+    elapsed: Duration,
+}
+
+impl WarmUpWorld {
+    fn outcome(&self, name: &str) -> &WarmUpOutcome {
+        self.outcomes
+            .iter()
+            .find(|o| o.name == name)
+            .expect("Dependency should have been warmed up")
+    }
+}
+
+#[given(expr = "a {string} dependency {word}")]
+fn given_dependency(world: &mut WarmUpWorld, name: String, behavior: String) {
+    let behavior = match behavior.as_str() {
+        "answering" => Behavior::Answering,
+        "failing" => Behavior::Failing,
+        "hanging" => Behavior::Hanging,
+        b => panic!("Unknown dependency behavior {}", b),
+    };
+    world.dependencies.push(Arc::new(StubDependency {
+        name,
+        behavior,
+        calls: AtomicUsize::new(0),
+    }));
+}
+
+#[when(expr = "the api warms up with a timeout of {int} milliseconds")]
+async fn when_api_warms_up(world: &mut WarmUpWorld, timeout: u64) {
+    let targets: Vec<Arc<dyn WarmUp>> = world
+        .dependencies
+        .iter()
+        .map(|d| d.clone() as Arc<dyn WarmUp>)
+        .collect();
+    let started = Instant::now();
+    world.outcomes = warm_up(&targets, Duration::from_millis(timeout)).await;
+    world.elapsed = started.elapsed();
+}
+
+#[then(expr = "{string} should be warmed up")]
+fn then_should_be_warmed_up(world: &mut WarmUpWorld, name: String) {
+    assert!(world.outcome(&name).result.is_ok());
+}
+
+#[then(expr = "{string} should have failed")]
+fn then_should_have_failed(world: &mut WarmUpWorld, name: String) {
+    assert!(matches!(
+        world.outcome(&name).result,
+        Err(WarmUpError::Failed(_))
+    ));
+}
+
+#[then(expr = "{string} should have timed out")]
+fn then_should_have_timed_out(world: &mut WarmUpWorld, name: String) {
+    assert!(matches!(
+        world.outcome(&name).result,
+        Err(WarmUpError::TimedOut)
+    ));
+}
+
+#[then("every dependency should have been called once")]
+fn then_called_once(world: &mut WarmUpWorld) {
+    for dependency in &world.dependencies {
+        assert_eq!(1, dependency.calls.load(Ordering::SeqCst));
+    }
+}
+
+#[then(expr = "the warm-up should have taken less than {int} milliseconds")]
+fn then_warm_up_took_less_than(world: &mut WarmUpWorld, max: u64) {
+    assert!(world.elapsed < Duration::from_millis(max));
+}
+
+#[tokio::main]
+async fn main() {
+    WarmUpWorld::cucumber()
+        .run_and_exit("features/warm_up.feature")
+        .await;
+}
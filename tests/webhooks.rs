@@ -0,0 +1,475 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        breakglass::Operator,
+        bridge::{PubKey, QueueManager, SignedHash},
+        consume_queue::{consume_queue, MintRetryPolicy},
+        ids::StarknetAddress,
+        pagination::PageRequest,
+        post_mint::PostMintHooks,
+        queue_admin::handle_cancel_queue_item,
+        webhook::{
+            handle_delete_webhook, handle_list_webhooks, handle_register_operator_webhook,
+            handle_register_webhook, handle_test_webhook, run_webhook_deliveries,
+            RegisterOperatorWebhookRequest, RegisterWebhookRequest, SignedWebhookRequest,
+            WebhookError, WebhookEvent, WebhookNotifier, WebhookRetryPolicy, WebhookSubscription,
+            WebhookTestResult,
+        },
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+        InMemoryQueueManager, InMemoryStarknetTransactionManager, InMemoryWebhookRepository,
+        InMemoryWebhookSender, ManualClock, TestSignedHashValidator,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const STARKNET_PROJECT_ADDR: &str = "0x0d1e";
+
+#[derive(Debug, World)]
+struct WebhookWorld {
+    queue_manager: Arc<dyn QueueManager>,
+    repository: InMemoryWebhookRepository,
+    sender: InMemoryWebhookSender,
+    clock: ManualClock,
+    retry_policy: WebhookRetryPolicy,
+    result: Option<Result<(), WebhookError>>,
+    registered: Vec<WebhookSubscription>,
+    test_result: Option<Result<WebhookTestResult, WebhookError>>,
+}
+
+impl Default for WebhookWorld {
+    fn default() -> Self {
+        let clock = ManualClock::new(1_672_531_200_000);
+        Self {
+            queue_manager: Arc::new(InMemoryQueueManager::with_clock(Arc::new(clock.clone()))),
+            repository: InMemoryWebhookRepository::new(),
+            sender: InMemoryWebhookSender::new(),
+            clock,
+            retry_policy: WebhookRetryPolicy {
+                max_attempts: 3,
+                base_delay: Duration::from_secs(30),
+                max_delay: Duration::from_secs(300),
+            },
+            result: None,
+            registered: Vec::new(),
+            test_result: None,
+        }
+    }
+}
+
+fn project() -> StarknetAddress {
+    STARKNET_PROJECT_ADDR.parse().unwrap()
+}
+
+fn notifier(world: &WebhookWorld) -> WebhookNotifier {
+    WebhookNotifier::new(
+        Arc::new(world.repository.clone()),
+        Arc::new(world.clock.clone()),
+    )
+}
+
+fn signed_hash(signature: &str) -> SignedHash {
+    SignedHash {
+        pub_key: PubKey {
+            key_type: "tendermint/PubKeySecp256k1".into(),
+            key_value: "pub-key".into(),
+        },
+        signature: signature.into(),
+    }
+}
+
+fn events(events: &str) -> Vec<WebhookEvent> {
+    events
+        .split(',')
+        .map(|e| WebhookEvent::parse(e).expect("Unknown webhook event"))
+        .collect()
+}
+
+async fn register(
+    world: &mut WebhookWorld,
+    keplr_wallet: &str,
+    url: &str,
+    signature: &str,
+    events: Vec<WebhookEvent>,
+) {
+    let request = RegisterWebhookRequest {
+        signed_hash: signed_hash(signature),
+        keplr_wallet_pubkey: keplr_wallet.parse().unwrap(),
+        callback_url: url.into(),
+        events,
+    };
+    let result = handle_register_webhook(
+        &request,
+        Arc::new(TestSignedHashValidator {}),
+        Arc::new(world.repository.clone()),
+    )
+    .await;
+    world.result = Some(match result {
+        Ok((subscription, _)) => {
+            world.registered.push(subscription);
+            Ok(())
+        }
+        Err(e) => Err(e),
+    });
+}
+
+/// Latest subscription registered to `url`.
+fn registered(world: &WebhookWorld, url: &str) -> WebhookSubscription {
+    world
+        .registered
+        .iter()
+        .rev()
+        .find(|s| s.callback_url == url)
+        .cloned()
+        .expect("Webhook should have been registered")
+}
+
+async fn deliver(world: &WebhookWorld) {
+    run_webhook_deliveries(
+        Arc::new(world.repository.clone()),
+        Arc::new(world.sender.clone()),
+        Arc::new(world.clock.clone()),
+        &world.retry_policy,
+    )
+    .await
+    .unwrap();
+}
+
+#[given(expr = "keplr wallet {word} registered webhook {string}")]
+async fn given_a_registered_webhook(world: &mut WebhookWorld, keplr_wallet: String, url: String) {
+    register(world, &keplr_wallet, &url, "aValidSignedHash", Vec::new()).await;
+    assert!(matches!(world.result, Some(Ok(_))));
+}
+
+#[given(expr = "keplr wallet {word} registered webhook {string} for {string}")]
+async fn given_a_filtered_webhook(
+    world: &mut WebhookWorld,
+    keplr_wallet: String,
+    url: String,
+    filter: String,
+) {
+    register(
+        world,
+        &keplr_wallet,
+        &url,
+        "aValidSignedHash",
+        events(&filter),
+    )
+    .await;
+    assert!(matches!(world.result, Some(Ok(_))));
+}
+
+#[given(expr = "an operator registered webhook {string}")]
+async fn given_an_operator_webhook(world: &mut WebhookWorld, url: String) {
+    let operator = Operator {
+        name: "alice".into(),
+        api_key: "secret".into(),
+    };
+    handle_register_operator_webhook(
+        &RegisterOperatorWebhookRequest {
+            callback_url: url,
+            events: Vec::new(),
+        },
+        &operator,
+        Arc::new(world.repository.clone()),
+    )
+    .await
+    .unwrap();
+}
+
+#[given(expr = "token {string} of keplr wallet {word} is queued")]
+async fn given_a_queued_token(world: &mut WebhookWorld, token: String, keplr_wallet: String) {
+    world
+        .queue_manager
+        .enqueue(
+            &keplr_wallet.parse().unwrap(),
+            &"0x5741".parse().unwrap(),
+            &project(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given("the callback fails")]
+fn given_the_callback_fails(world: &mut WebhookWorld) {
+    world.sender.fail_deliveries(true);
+}
+
+#[given("the callback recovers")]
+fn given_the_callback_recovers(world: &mut WebhookWorld) {
+    world.sender.fail_deliveries(false);
+}
+
+#[when(expr = "keplr wallet {word} registers webhook {string} with signature {string}")]
+async fn when_registering(
+    world: &mut WebhookWorld,
+    keplr_wallet: String,
+    url: String,
+    signature: String,
+) {
+    register(world, &keplr_wallet, &url, &signature, Vec::new()).await;
+}
+
+#[when(expr = "keplr wallet {word} registers webhook {string} for {string}")]
+async fn when_registering_filtered(
+    world: &mut WebhookWorld,
+    keplr_wallet: String,
+    url: String,
+    filter: String,
+) {
+    register(
+        world,
+        &keplr_wallet,
+        &url,
+        "aValidSignedHash",
+        events(&filter),
+    )
+    .await;
+}
+
+#[when(expr = "keplr wallet {word} deletes webhook {string}")]
+async fn when_deleting(world: &mut WebhookWorld, keplr_wallet: String, url: String) {
+    let id = registered(world, &url).id;
+    let request = SignedWebhookRequest {
+        signed_hash: signed_hash("aValidSignedHash"),
+        keplr_wallet_pubkey: keplr_wallet.parse().unwrap(),
+    };
+    world.result = Some(
+        handle_delete_webhook(
+            id,
+            &request,
+            Arc::new(TestSignedHashValidator {}),
+            Arc::new(world.repository.clone()),
+        )
+        .await,
+    );
+}
+
+#[when(expr = "keplr wallet {word} tests webhook {string}")]
+async fn when_testing(world: &mut WebhookWorld, keplr_wallet: String, url: String) {
+    let id = registered(world, &url).id;
+    let request = SignedWebhookRequest {
+        signed_hash: signed_hash("aValidSignedHash"),
+        keplr_wallet_pubkey: keplr_wallet.parse().unwrap(),
+    };
+    world.test_result = Some(
+        handle_test_webhook(
+            id,
+            &request,
+            Arc::new(TestSignedHashValidator {}),
+            Arc::new(world.repository.clone()),
+            Arc::new(world.sender.clone()),
+            Arc::new(world.clock.clone()),
+        )
+        .await,
+    );
+}
+
+#[when("the worker consumes the queue")]
+async fn when_the_worker_consumes(world: &mut WebhookWorld) {
+    consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(InMemoryStarknetTransactionManager::new()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        Arc::new(notifier(world)),
+        Arc::new(BatchAnalytics::new(
+            Arc::new(InMemoryBatchAnalyticsRepository::new()),
+            Arc::new(world.clock.clone()),
+            Duration::from_secs(86_400),
+        )),
+        &[],
+        &MintRetryPolicy::default(),
+        &CancellationToken::new(),
+    )
+    .await
+    .unwrap();
+}
+
+#[when(expr = "the operator cancels token {string}")]
+async fn when_the_operator_cancels(world: &mut WebhookWorld, token: String) {
+    let page = world
+        .queue_manager
+        .list_queue_items(None, &PageRequest::new(None, None, 100).unwrap())
+        .await
+        .unwrap();
+    let id = page
+        .items
+        .iter()
+        .find(|qi| qi.token_id == token)
+        .and_then(|qi| qi.id)
+        .expect("Token should be queued");
+    let operator = Operator {
+        name: "alice".into(),
+        api_key: "secret".into(),
+    };
+    handle_cancel_queue_item(
+        &id,
+        &operator,
+        world.queue_manager.clone(),
+        &notifier(world),
+    )
+    .await
+    .unwrap();
+}
+
+#[when("the worker delivers webhooks")]
+async fn when_the_worker_delivers(world: &mut WebhookWorld) {
+    deliver(world).await;
+}
+
+// Waits out the longest backoff between two runs so every run retries
+#[when(expr = "the worker delivers webhooks {int} times")]
+async fn when_the_worker_delivers_times(world: &mut WebhookWorld, times: usize) {
+    for _ in 0..times {
+        deliver(world).await;
+        world.clock.advance(world.retry_policy.max_delay);
+    }
+}
+
+#[when(expr = "{int} seconds elapse")]
+fn when_seconds_elapse(world: &mut WebhookWorld, seconds: u64) {
+    world.clock.advance(Duration::from_secs(seconds));
+}
+
+#[then(expr = "{string} should have received {string} for token {string}")]
+async fn then_callback_should_have_received(
+    world: &mut WebhookWorld,
+    url: String,
+    event: String,
+    token: String,
+) {
+    let sent = world.sender.sent().await;
+    assert!(
+        sent.iter().any(|(u, payload)| *u == url
+            && serde_json::json!(event) == serde_json::json!(payload.event)
+            && payload.token_id == token),
+        "{} should have received {} for token {}, got {:#?}",
+        url,
+        event,
+        token,
+        sent
+    );
+}
+
+#[then(expr = "{string} should have received nothing")]
+async fn then_callback_should_have_received_nothing(world: &mut WebhookWorld, url: String) {
+    let sent = world.sender.sent().await;
+    assert!(!sent.iter().any(|(u, _)| *u == url));
+    assert!(world.repository.deliveries().await.is_empty());
+}
+
+#[then(expr = "the registration should be refused because of {string}")]
+fn then_registration_should_be_refused(world: &mut WebhookWorld, reason: String) {
+    match (&world.result, reason.as_str()) {
+        (Some(Err(WebhookError::InvalidSign)), "invalid_sign") => (),
+        (Some(Err(WebhookError::InvalidCallbackUrl)), "invalid_callback_url") => (),
+        (Some(Err(WebhookError::InvalidEventFilter)), "invalid_event_filter") => (),
+        (r, _) => panic!("Registration should be refused, got {:#?}", r),
+    }
+}
+
+#[then("the deletion should be refused because the webhook is not found")]
+fn then_deletion_should_be_refused(world: &mut WebhookWorld) {
+    assert!(
+        matches!(world.result, Some(Err(WebhookError::NotFound))),
+        "{:#?}",
+        world.result
+    );
+}
+
+#[then(expr = "keplr wallet {word} should have {int} webhook(s) without their secret")]
+async fn then_wallet_should_have_webhooks(
+    world: &mut WebhookWorld,
+    keplr_wallet: String,
+    count: usize,
+) {
+    let subscriptions = handle_list_webhooks(
+        &keplr_wallet.parse().unwrap(),
+        Arc::new(world.repository.clone()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(count, subscriptions.len(), "{:#?}", subscriptions);
+    assert!(subscriptions.iter().all(|s| s.secret.is_none()));
+}
+
+#[then(expr = "both registrations of {string} should have the same id and secret")]
+fn then_registrations_are_the_same(world: &mut WebhookWorld, url: String) {
+    let registrations: Vec<_> = world
+        .registered
+        .iter()
+        .filter(|s| s.callback_url == url)
+        .collect();
+    assert_eq!(2, registrations.len());
+    assert_eq!(registrations[0].id, registrations[1].id);
+    assert!(registrations[0].secret.is_some());
+    assert_eq!(registrations[0].secret, registrations[1].secret);
+}
+
+#[then(expr = "{string} should only have received {string}")]
+async fn then_callback_should_only_have_received(
+    world: &mut WebhookWorld,
+    url: String,
+    event: String,
+) {
+    let sent = world.sender.sent().await;
+    let received: Vec<_> = sent.iter().filter(|(u, _)| *u == url).collect();
+    assert!(!received.is_empty(), "{} received nothing", url);
+    for (_, payload) in received {
+        assert_eq!(serde_json::json!(event), serde_json::json!(payload.event));
+    }
+}
+
+#[then(expr = "deliveries to {string} should be signed with its own secret")]
+async fn then_deliveries_are_signed_with_own_secret(world: &mut WebhookWorld, url: String) {
+    let secret = registered(world, &url).secret;
+    let deliveries = world.repository.deliveries().await;
+    assert!(!deliveries.is_empty());
+    for delivery in deliveries.iter().filter(|d| d.callback_url == url) {
+        assert!(secret.is_some());
+        assert_eq!(secret, delivery.signing_secret);
+    }
+}
+
+#[then("the test delivery should have been delivered")]
+fn then_test_delivered(world: &mut WebhookWorld) {
+    let result = world.test_result.as_ref().unwrap().as_ref().unwrap();
+    assert!(result.delivered, "{:#?}", result);
+}
+
+#[then(expr = "the test delivery should have failed with status {int}")]
+fn then_test_failed(world: &mut WebhookWorld, status: u16) {
+    let result = world.test_result.as_ref().unwrap().as_ref().unwrap();
+    assert!(!result.delivered);
+    assert_eq!(Some(status), result.status_code);
+}
+
+#[then(expr = "the delivery should be {string} after {int} attempt(s)")]
+async fn then_the_delivery_should_be(world: &mut WebhookWorld, status: String, attempts: i32) {
+    let deliveries = world.repository.deliveries().await;
+    assert_eq!(1, deliveries.len());
+    assert_eq!(
+        serde_json::json!(status),
+        serde_json::json!(deliveries[0].status)
+    );
+    assert_eq!(attempts, deliveries[0].attempts);
+}
+
+#[then(expr = "{int} delivery attempts should have been logged")]
+async fn then_attempts_should_have_been_logged(world: &mut WebhookWorld, attempts: usize) {
+    assert_eq!(attempts, world.repository.attempts().await.len());
+}
+
+#[tokio::main]
+async fn main() {
+    WebhookWorld::cucumber()
+        .run_and_exit("features/webhooks.feature")
+        .await;
+}
@@ -0,0 +1,239 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        bridge::{QueueManager, QueueStatus},
+        clock::SystemClock,
+        consume_queue::{consume_queue, recover_processing_items, ConsumerError, MintRetryPolicy},
+        ids::QueueItemId,
+        post_mint::PostMintHooks,
+        webhook::WebhookNotifier,
+    },
+    infrastructure::{
+        app::cancel_after_grace_period,
+        in_memory::{
+            InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+            InMemoryQueueManager, InMemoryStarknetTransactionManager, InMemoryWebhookRepository,
+        },
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, World)]
+struct WorkerWorld {
+    queue_manager: Arc<dyn QueueManager>,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    cancelled: bool,
+    elapsed: Option<Duration>,
+}
+
+impl Default for WorkerWorld {
+    fn default() -> Self {
+        Self {
+            queue_manager: Arc::new(InMemoryQueueManager::new()),
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            cancelled: false,
+            elapsed: None,
+        }
+    }
+}
+
+/// Cancels the returned token once `delay` elapsed.
+fn cancel_after(delay: Duration) -> CancellationToken {
+    let cancel = CancellationToken::new();
+    let trigger = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        trigger.cancel();
+    });
+    cancel
+}
+
+#[given(expr = "token {string} of {string} is queued for {string} on project {string}")]
+async fn given_a_queued_token(
+    world: &mut WorkerWorld,
+    token: String,
+    keplr: String,
+    starknet: String,
+    project: String,
+) {
+    world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &starknet.parse().unwrap(),
+            &project.parse().unwrap(),
+            vec![token.parse().unwrap()],
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[given("starknet transactions stay pending")]
+fn given_transactions_stay_pending(world: &mut WorkerWorld) {
+    world.starknet_manager.hold_transactions(true);
+}
+
+#[given(expr = "starknet transactions are accepted after {int} milliseconds")]
+fn given_transactions_accepted_after(world: &mut WorkerWorld, delay: u64) {
+    let starknet_manager = world.starknet_manager.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        starknet_manager.hold_transactions(false);
+    });
+}
+
+#[given(expr = "a previous worker stopped after sending transaction {string} for the queue")]
+async fn given_a_previous_worker_stopped(world: &mut WorkerWorld, transaction_hash: String) {
+    let ids: Vec<QueueItemId> = world
+        .queue_manager
+        .get_batch(&[])
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|qi| qi.id)
+        .collect();
+    world
+        .queue_manager
+        .update_queue_items_status(&ids, transaction_hash, QueueStatus::Processing)
+        .await
+        .unwrap();
+}
+
+fn no_webhooks() -> Arc<WebhookNotifier> {
+    Arc::new(WebhookNotifier::new(
+        Arc::new(InMemoryWebhookRepository::new()),
+        Arc::new(SystemClock),
+    ))
+}
+
+fn no_analytics() -> Arc<BatchAnalytics> {
+    Arc::new(BatchAnalytics::new(
+        Arc::new(InMemoryBatchAnalyticsRepository::new()),
+        Arc::new(SystemClock),
+        Duration::from_secs(86_400),
+    ))
+}
+
+#[when(expr = "the worker is cancelled {int} milliseconds into consuming the queue")]
+async fn when_cancelled_while_consuming(world: &mut WorkerWorld, delay: u64) {
+    let cancel = cancel_after(Duration::from_millis(delay));
+    let started_at = Instant::now();
+    let res = consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        no_webhooks(),
+        no_analytics(),
+        &[],
+        &MintRetryPolicy::default(),
+        &cancel,
+    )
+    .await;
+    world.elapsed = Some(started_at.elapsed());
+    world.cancelled = matches!(res, Err(ConsumerError::Cancelled));
+}
+
+#[when(
+    expr = "the worker is asked to shut down {int} milliseconds into consuming the queue with a grace period of {int} second(s)"
+)]
+async fn when_shut_down_while_consuming(world: &mut WorkerWorld, delay: u64, grace: u64) {
+    let shutdown = cancel_after(Duration::from_millis(delay));
+    let interrupt = cancel_after_grace_period(&shutdown, Duration::from_secs(grace));
+    let started_at = Instant::now();
+    let res = consume_queue(
+        world.queue_manager.clone(),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        no_webhooks(),
+        no_analytics(),
+        &[],
+        &MintRetryPolicy::default(),
+        &interrupt,
+    )
+    .await;
+    world.elapsed = Some(started_at.elapsed());
+    world.cancelled = matches!(res, Err(ConsumerError::Cancelled));
+}
+
+#[when(expr = "the worker is cancelled {int} milliseconds into recovering processing items")]
+async fn when_cancelled_while_recovering(world: &mut WorkerWorld, delay: u64) {
+    let cancel = cancel_after(Duration::from_millis(delay));
+    let started_at = Instant::now();
+    let res = recover_processing_items(
+        world.queue_manager.clone(),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        no_webhooks(),
+        &MintRetryPolicy::default(),
+        None,
+        &cancel,
+    )
+    .await;
+    world.elapsed = Some(started_at.elapsed());
+    world.cancelled = matches!(res, Err(ConsumerError::Cancelled));
+}
+
+#[then(expr = "the worker should have stopped within {int} second(s)")]
+fn then_worker_should_have_stopped(world: &mut WorkerWorld, seconds: u64) {
+    assert!(world.cancelled, "Worker should report it was cancelled");
+    let elapsed = world.elapsed.expect("Worker should have run");
+    assert!(
+        elapsed < Duration::from_secs(seconds),
+        "Worker took {:?} to stop",
+        elapsed
+    );
+}
+
+#[then("the worker should have finished its batch")]
+fn then_worker_should_have_finished(world: &mut WorkerWorld) {
+    assert!(!world.cancelled, "Worker should not have been interrupted");
+}
+
+#[then(expr = "token {string} should be {string}")]
+async fn then_token_should_be(world: &mut WorkerWorld, token: String, status: String) {
+    let queue_items = world
+        .queue_manager
+        .get_customer_migration_state(&"k3plr-pk1".parse().unwrap(), &"0x0c4a".parse().unwrap())
+        .await;
+    let qi = queue_items
+        .iter()
+        .find(|qi| qi.token_id == token.as_str())
+        .expect("Token should be queued");
+
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+}
+
+#[then(expr = "token {string} should still be {string} with its transaction hash recorded")]
+async fn then_token_should_still_be(world: &mut WorkerWorld, token: String, status: String) {
+    let queue_items = world
+        .queue_manager
+        .get_customer_migration_state(&"k3plr-pk1".parse().unwrap(), &"0x0c4a".parse().unwrap())
+        .await;
+    let qi = queue_items
+        .iter()
+        .find(|qi| qi.token_id == token.as_str())
+        .expect("Token should be queued");
+
+    assert_eq!(serde_json::json!(status), serde_json::json!(qi.status));
+    assert!(qi
+        .transaction_hash
+        .as_ref()
+        .map_or(false, |hash| !hash.is_empty()));
+}
+
+#[tokio::main]
+async fn main() {
+    WorkerWorld::cucumber()
+        .run_and_exit("features/worker_cancellation.feature")
+        .await;
+}
@@ -0,0 +1,262 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge_juno_to_starknet_backend::{
+    domain::{
+        analytics::BatchAnalytics,
+        bridge::{QueueItem, QueueManager, QueueStatus, StarknetManager},
+        clock::{Clock, SystemClock},
+        consume_queue::{consume_queue, recover_processing_items, MintRetryPolicy},
+        ids::{QueueItemId, TokenId},
+        post_mint::PostMintHooks,
+        webhook::WebhookNotifier,
+    },
+    infrastructure::in_memory::{
+        InMemoryBatchAnalyticsRepository, InMemoryPostMintExecutionRepository,
+        InMemoryQueueManager, InMemoryStarknetTransactionManager, InMemoryWebhookRepository,
+        ManualClock,
+    },
+};
+use cucumber::{given, then, when, World};
+use tokio_util::sync::CancellationToken;
+
+const KEPLR_WALLET: &str = "k3plr-pk1";
+const PROJECT: &str = "0x0c4a";
+
+#[derive(World)]
+struct ClaimWorld {
+    clock: ManualClock,
+    queue_manager: InMemoryQueueManager,
+    starknet_manager: InMemoryStarknetTransactionManager,
+    tokens: Vec<TokenId>,
+}
+
+impl Default for ClaimWorld {
+    fn default() -> Self {
+        let clock = ManualClock::new(1_672_531_200_000);
+        Self {
+            queue_manager: InMemoryQueueManager::with_clock(Arc::new(clock.clone())),
+            clock,
+            starknet_manager: InMemoryStarknetTransactionManager::new(),
+            tokens: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ClaimWorld {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClaimWorld")
+            .field("tokens", &self.tokens)
+            .finish()
+    }
+}
+
+impl ClaimWorld {
+    async fn queued_with_status(&self, status: &str) -> Vec<QueueItem> {
+        let status: QueueStatus = serde_json::from_value(serde_json::json!(status)).unwrap();
+        self.queue_manager
+            .get_customer_migration_state(&KEPLR_WALLET.parse().unwrap(), &PROJECT.parse().unwrap())
+            .await
+            .into_iter()
+            .filter(|qi| qi.status == status)
+            .collect()
+    }
+}
+
+fn no_webhooks() -> Arc<WebhookNotifier> {
+    Arc::new(WebhookNotifier::new(
+        Arc::new(InMemoryWebhookRepository::new()),
+        Arc::new(SystemClock),
+    ))
+}
+
+/// Claims the items of the next batch on behalf of the worker of `queue_manager`.
+async fn claim_pending_items(queue_manager: &InMemoryQueueManager) {
+    let ids: Vec<QueueItemId> = queue_manager
+        .get_batch(&[])
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|qi| qi.id)
+        .collect();
+    queue_manager.claim_queue_items(&ids).await.unwrap();
+}
+
+#[given(expr = "tokens {string} of {string} are queued for {string} on project {string}")]
+async fn given_queued_tokens(
+    world: &mut ClaimWorld,
+    tokens: String,
+    keplr: String,
+    starknet: String,
+    project: String,
+) {
+    let tokens: Vec<TokenId> = tokens.split(',').map(|t| t.parse().unwrap()).collect();
+    world
+        .queue_manager
+        .enqueue(
+            &keplr.parse().unwrap(),
+            &starknet.parse().unwrap(),
+            &project.parse().unwrap(),
+            tokens.clone(),
+            None,
+        )
+        .await
+        .unwrap();
+    world.tokens.extend(tokens);
+}
+
+#[given("starknet transactions hold a single mint")]
+fn given_single_mint_transactions(world: &mut ClaimWorld) {
+    world.starknet_manager.limit_calls_per_transaction(1);
+}
+
+#[given("starknet transactions stay pending")]
+fn given_transactions_stay_pending(world: &mut ClaimWorld) {
+    world.starknet_manager.hold_transactions(true);
+}
+
+#[given(expr = "starknet transactions are accepted after {int} milliseconds")]
+fn given_transactions_accepted_after(world: &mut ClaimWorld, delay: u64) {
+    let starknet_manager = world.starknet_manager.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        starknet_manager.hold_transactions(false);
+    });
+}
+
+#[given(expr = "worker {string} claims the pending items after {int} milliseconds")]
+fn given_worker_claims_pending_items(world: &mut ClaimWorld, worker: String, delay: u64) {
+    let queue_manager = world.queue_manager.for_worker(&worker);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        claim_pending_items(&queue_manager).await;
+    });
+}
+
+#[given(expr = "worker {string} claimed the pending items")]
+async fn given_worker_claimed_pending_items(world: &mut ClaimWorld, worker: String) {
+    claim_pending_items(&world.queue_manager.for_worker(&worker)).await;
+}
+
+#[given(expr = "worker {string} sent transaction {string} for the pending items")]
+async fn given_worker_sent_pending_items(world: &mut ClaimWorld, worker: String, tx_hash: String) {
+    let queue_manager = world.queue_manager.for_worker(&worker);
+    let ids: Vec<QueueItemId> = queue_manager
+        .get_batch(&[])
+        .await
+        .unwrap()
+        .iter()
+        .filter_map(|qi| qi.id)
+        .collect();
+    let claimed = queue_manager.claim_queue_items(&ids).await.unwrap();
+    queue_manager
+        .update_queue_items_status(&claimed, tx_hash, QueueStatus::Processing)
+        .await
+        .unwrap();
+}
+
+#[given(expr = "{int} seconds went by")]
+fn given_seconds_went_by(world: &mut ClaimWorld, seconds: u64) {
+    world.clock.advance(Duration::from_secs(seconds));
+}
+
+#[when(expr = "worker {string} consumes the queue")]
+async fn when_worker_consumes_the_queue(world: &mut ClaimWorld, worker: String) {
+    consume_queue(
+        Arc::new(world.queue_manager.for_worker(&worker)),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        no_webhooks(),
+        Arc::new(BatchAnalytics::new(
+            Arc::new(InMemoryBatchAnalyticsRepository::new()),
+            Arc::new(SystemClock),
+            Duration::from_secs(86_400),
+        )),
+        &[],
+        &MintRetryPolicy::default(),
+        &CancellationToken::new(),
+    )
+    .await
+    .unwrap_or_else(|_| panic!("Worker should have consumed the queue"));
+}
+
+async fn recover(world: &ClaimWorld, worker: &str, stale_before: Option<i64>) {
+    recover_processing_items(
+        Arc::new(world.queue_manager.for_worker(worker)),
+        Arc::new(world.starknet_manager.clone()),
+        Arc::new(PostMintHooks::new()),
+        Arc::new(InMemoryPostMintExecutionRepository::new()),
+        no_webhooks(),
+        &MintRetryPolicy::default(),
+        stale_before,
+        &CancellationToken::new(),
+    )
+    .await
+    .unwrap_or_else(|_| panic!("Worker should have recovered processing items"));
+}
+
+#[when(expr = "worker {string} recovers the items left processing")]
+async fn when_worker_recovers(world: &mut ClaimWorld, worker: String) {
+    recover(world, &worker, None).await;
+}
+
+#[when(expr = "worker {string} recovers the items left processing for over {int} seconds")]
+async fn when_worker_recovers_stale_items(world: &mut ClaimWorld, worker: String, age: i64) {
+    let stale_before = world.clock.now_ms() - age * 1000;
+    recover(world, &worker, Some(stale_before)).await;
+}
+
+#[then(expr = "{int} token(s) should have been minted")]
+async fn then_tokens_should_have_been_minted(world: &mut ClaimWorld, count: usize) {
+    let mut minted = 0;
+    for token in &world.tokens {
+        if world
+            .starknet_manager
+            .project_has_token(&PROJECT.parse().unwrap(), token)
+            .await
+            .unwrap()
+        {
+            minted += 1;
+        }
+    }
+
+    assert_eq!(count, minted);
+}
+
+#[then(expr = "{int} token(s) should be {string}")]
+async fn then_tokens_should_be(world: &mut ClaimWorld, count: usize, status: String) {
+    assert_eq!(count, world.queued_with_status(&status).await.len());
+}
+
+#[then(expr = "{int} token(s) should still be {string} without transaction hash")]
+async fn then_tokens_should_still_be_unsent(world: &mut ClaimWorld, count: usize, status: String) {
+    let unsent = world
+        .queued_with_status(&status)
+        .await
+        .into_iter()
+        .filter(|qi| qi.transaction_hash.as_deref().map_or(true, str::is_empty))
+        .count();
+
+    assert_eq!(count, unsent);
+}
+
+#[then(expr = "token {string} should be {string}")]
+async fn then_token_should_be(world: &mut ClaimWorld, token: String, status: String) {
+    assert!(
+        world
+            .queued_with_status(&status)
+            .await
+            .iter()
+            .any(|qi| qi.token_id == token.as_str()),
+        "Token {} should be {}",
+        token,
+        status
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    ClaimWorld::cucumber()
+        .run_and_exit("features/worker_claims.feature")
+        .await;
+}